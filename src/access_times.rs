@@ -0,0 +1,145 @@
+use crate::file_scanner::FileInfo;
+use std::path::Path;
+
+/// Slack between creation and last access: creating or copying a file can set its access time a moment later
+const ACCESS_TOLERANCE_SECS: i64 = 2;
+
+/// Whether the file system records when files are read
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccessTimeMode {
+    /// Every access updates the time (strictatime)
+    Updated,
+    /// Updated with a delay or only once in a while (Linux relatime, NTFS one-hour granularity);
+    /// good enough to tell "never read" from "read at some point"
+    Relaxed,
+    /// Access times are not updated (noatime, NtfsDisableLastAccessUpdate)
+    Disabled,
+    Unknown,
+}
+
+impl AccessTimeMode {
+    pub fn is_reliable(self) -> bool {
+        matches!(self, AccessTimeMode::Updated | AccessTimeMode::Relaxed)
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            AccessTimeMode::Updated => "Access times are updated on every read",
+            AccessTimeMode::Relaxed => "Access times are updated, but not on every read (relatime / delayed)",
+            AccessTimeMode::Disabled => "Access-time updates are disabled on this volume: \"Accessed\" only shows when files were created or copied",
+            AccessTimeMode::Unknown => "Could not tell whether this volume updates access times",
+        }
+    }
+
+    /// The less reliable of two modes (for a scan spanning several volumes)
+    pub fn weakest(self, other: AccessTimeMode) -> AccessTimeMode {
+        let rank = |mode: AccessTimeMode| match mode {
+            AccessTimeMode::Updated => 0,
+            AccessTimeMode::Relaxed => 1,
+            AccessTimeMode::Unknown => 2,
+            AccessTimeMode::Disabled => 3,
+        };
+        if rank(other) > rank(self) {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+/// Not read since it was created or last written (access time not after creation or modification)
+pub fn never_accessed(file: &FileInfo) -> bool {
+    let Some(accessed) = file.accessed_timestamp else {
+        return false;
+    };
+    let written = file.created_timestamp.unwrap_or(0).max(file.modified_timestamp);
+    accessed <= written + ACCESS_TOLERANCE_SECS
+}
+
+/// Check whether the volume holding `folder` updates access times (from the mount options)
+#[cfg(target_os = "linux")]
+pub fn detect(folder: &Path) -> AccessTimeMode {
+    let Ok(folder) = folder.canonicalize() else {
+        return AccessTimeMode::Unknown;
+    };
+    let Ok(mountinfo) = std::fs::read_to_string("/proc/self/mountinfo") else {
+        return AccessTimeMode::Unknown;
+    };
+
+    // Line format: "id parent major:minor root mount_point options ..."; the longest matching mount point wins
+    let options = mountinfo
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(' ').collect();
+            let mount_point = fields.get(4)?.replace("\\040", " ");
+            let options = fields.get(5)?;
+            folder.starts_with(&mount_point).then(|| (mount_point.len(), options.to_string()))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, options)| options);
+
+    match options {
+        Some(options) if options.split(',').any(|o| o == "noatime") => AccessTimeMode::Disabled,
+        Some(options) if options.split(',').any(|o| o == "relatime") => AccessTimeMode::Relaxed,
+        Some(_) => AccessTimeMode::Updated,
+        None => AccessTimeMode::Unknown,
+    }
+}
+
+/// Check whether the volume holding `folder` updates access times (from `mount`)
+#[cfg(target_os = "macos")]
+pub fn detect(folder: &Path) -> AccessTimeMode {
+    let Ok(folder) = folder.canonicalize() else {
+        return AccessTimeMode::Unknown;
+    };
+    let Ok(output) = std::process::Command::new("mount").output() else {
+        return AccessTimeMode::Unknown;
+    };
+
+    // Line format: "/dev/disk3s5 on /System/Volumes/Data (apfs, local, journaled, nobrowse)"
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let options = stdout
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(" on ")?;
+            let (mount_point, options) = rest.rsplit_once(" (")?;
+            folder.starts_with(mount_point).then(|| (mount_point.len(), options.to_string()))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, options)| options);
+
+    match options {
+        Some(options) if options.contains("noatime") => AccessTimeMode::Disabled,
+        Some(_) => AccessTimeMode::Updated,
+        None => AccessTimeMode::Unknown,
+    }
+}
+
+/// Check the system-wide NTFS last-access setting (applies to every volume)
+#[cfg(windows)]
+pub fn detect(_folder: &Path) -> AccessTimeMode {
+    std::process::Command::new("reg")
+        .args([
+            "query",
+            r"HKLM\SYSTEM\CurrentControlSet\Control\FileSystem",
+            "/v",
+            "NtfsDisableLastAccessUpdate",
+        ])
+        .output()
+        .ok()
+        .and_then(|output| {
+            // Line format: "    NtfsDisableLastAccessUpdate    REG_DWORD    0x80000002"
+            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+            let line = stdout.lines().find(|l| l.contains("NtfsDisableLastAccessUpdate"))?.to_string();
+            let value = line.split("0x").nth(1)?.trim().to_string();
+            u32::from_str_radix(&value, 16).ok()
+        })
+        // Low bit set: disabled (user or system managed); NTFS otherwise updates within the hour
+        .map(|value| if value & 1 == 1 { AccessTimeMode::Disabled } else { AccessTimeMode::Relaxed })
+        .unwrap_or(AccessTimeMode::Unknown)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn detect(_folder: &Path) -> AccessTimeMode {
+    AccessTimeMode::Unknown
+}
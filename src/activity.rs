@@ -0,0 +1,68 @@
+use crate::file_scanner::FileInfo;
+
+/// Weekday labels, Monday first
+pub const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Modification times of a file list counted by weekday and hour of day
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Activity {
+    /// Files per weekday (Monday first) and hour
+    pub grid: [[usize; 24]; 7],
+    pub by_weekday: [usize; 7],
+    pub by_hour: [usize; 24],
+    /// Files counted (folders and files without a modification time are left out)
+    pub files: usize,
+}
+
+impl Activity {
+    /// Count the files' modification times, shifted by `offset_minutes` from UTC
+    pub fn count(files: &[FileInfo], offset_minutes: i32) -> Self {
+        let mut activity = Activity::default();
+        for file in files.iter().filter(|f| !f.is_dir && f.modified_timestamp != 0) {
+            let (weekday, hour) = weekday_hour(file.modified_timestamp, offset_minutes);
+            activity.grid[weekday][hour] += 1;
+            activity.by_weekday[weekday] += 1;
+            activity.by_hour[hour] += 1;
+            activity.files += 1;
+        }
+        activity
+    }
+
+    /// Largest cell of the weekday x hour grid
+    pub fn grid_max(&self) -> usize {
+        self.grid.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    /// The busiest weekday and hour (None without files)
+    pub fn peak(&self) -> Option<(usize, usize, usize)> {
+        (0..7)
+            .flat_map(|day| (0..24).map(move |hour| (day, hour)))
+            .map(|(day, hour)| (day, hour, self.grid[day][hour]))
+            .filter(|&(_, _, count)| count > 0)
+            .max_by_key(|&(_, _, count)| count)
+    }
+}
+
+/// Weekday (0 = Monday) and hour of a Unix timestamp at a UTC offset
+pub fn weekday_hour(timestamp: i64, offset_minutes: i32) -> (usize, usize) {
+    let secs = timestamp + i64::from(offset_minutes) * 60;
+    let days = secs.div_euclid(86_400);
+    let hour = secs.rem_euclid(86_400) / 3600;
+    // 1970-01-01 was a Thursday
+    let weekday = (days + 3).rem_euclid(7);
+    (weekday as usize, hour as usize)
+}
+
+/// "UTC", "UTC+2", "UTC-5:30"
+pub fn offset_label(offset_minutes: i32) -> String {
+    if offset_minutes == 0 {
+        return "UTC".to_string();
+    }
+    let sign = if offset_minutes < 0 { '-' } else { '+' };
+    let (hours, minutes) = (offset_minutes.abs() / 60, offset_minutes.abs() % 60);
+    if minutes == 0 {
+        format!("UTC{}{}", sign, hours)
+    } else {
+        format!("UTC{}{}:{:02}", sign, hours, minutes)
+    }
+}
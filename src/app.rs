@@ -1,42 +1,263 @@
-use crate::csv_export;
+use crate::access_times::{self, AccessTimeMode};
+use crate::activity::{self, Activity};
+use crate::clipboard;
+use crate::cloud_export::{CloudExportJob, CloudTarget};
+use crate::columns::{self, ColumnFormat, FileColumn};
+use crate::compare::{self, RootComparison};
+use crate::content_mix::{self, ContentKind, ContentMix};
+use crate::csv_export::{self, ExportColumn, ExportFormat, ExportJob, ExportOptions, ExportSplit};
+use crate::diagnostics::{self, Anonymizer, BundleContents};
+use crate::disk_space::{self, VolumeSpace};
 use crate::document_parser;
-use crate::file_scanner::{self, format_date, format_size, is_today, FileInfo};
+use crate::duplicates::{self, KeepRule};
+use crate::elevation::{self, ElevatedOp, ElevatedResults};
+use crate::extensions::{self, CheckMessage, ExtensionRules};
+use crate::file_scanner::{self, format_bytes, format_date, format_size, Calendar, DiskUsage, FileId, FileInfo, RecentDays, ScanFilter, ScanProgress, ScanReport, SizeFormat, SizeUnit, UnitSystem};
+use crate::folder_notes::{self, FolderNotes, ReadmeExcerpt};
+use crate::hashing::{self, ContentHashMessage, HashAlgorithm, HashCache};
+use crate::hooks::{self, Hook, HookEvent, HookKind};
+use crate::jobs::{self, Job, JobError, JobItem, JobOutcome};
+use crate::kiosk::KioskPolicy;
+use crate::links;
+use crate::mail_store::{self, MailStoreSummary};
+use crate::manifest::{self, ManifestMessage};
+use crate::manifest_check::{self, CheckStatus, ManifestCheck, VerifyMessage};
+use crate::mft;
+use crate::name_audit;
+use crate::notification;
+use crate::owner;
+use crate::pane::{ActivePane, FilePane};
+use crate::permissions::{self, AuditMessage, FolderPermissions, PermissionFlag};
+use crate::priority;
+use crate::renames::{self, RenameOp, RenameSimulation};
+use crate::report_mail::{self, MailDelivery, SmtpSecurity};
+use crate::retention::{self, LogEntry, PlannedItem, RetentionAction, RetentionLog, RetentionRule, RetentionRun};
+use crate::retry;
+use crate::reviewed::ReviewedFiles;
+use crate::sample_data;
+use crate::session::Session;
+use crate::settings::{AppSettings, PreviewQuality, WindowPlacement};
+use crate::sidecars;
+use crate::similarity::{self, HashingMessage, PerceptualHash};
+use crate::snapshots::{self, SnapshotSearch};
+use crate::split::{self, Joiner, SplitManifest};
+use crate::streams::{self, DataStream, StreamMessage};
+use crate::text_stats::{self, TextCountMessage};
+use crate::text_match::{self, Collation, NameForm};
+use crate::thumbnail::{self, Thumbnail, ThumbnailGenerator, ThumbnailKind, ThumbnailOptions};
+use crate::users;
+use crate::versions::{self, VersionInfo};
+use crate::watcher::{self, FolderWatcher};
 use eframe::egui;
 use egui_extras::{Column, TableBuilder};
 use pdfium_render::prelude::*;
 use rodio::{buffer::SamplesBuffer, Decoder, OutputStream, OutputStreamHandle, Sink, Source};
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::{self, Receiver};
-use std::sync::Once;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, Once, OnceLock};
 use std::thread;
 use std::time::{Duration, Instant};
 
-/// Global FFmpeg availability (checked once at startup)
-static FFMPEG_CHECKED: Once = Once::new();
-static FFMPEG_AVAILABLE: AtomicBool = AtomicBool::new(false);
+/// Video decoder for thumbnails, looked up once at startup (None: no decoder found)
+static VIDEO_DECODER: OnceLock<Option<&'static str>> = OnceLock::new();
 
 /// Global Pdfium availability
 static PDFIUM_CHECKED: Once = Once::new();
 static PDFIUM_AVAILABLE: AtomicBool = AtomicBool::new(false);
 static PDFIUM_DOWNLOADING: AtomicBool = AtomicBool::new(false);
 
-/// Data for a loaded image preview
-struct ImagePreviewData {
-    pixels: Vec<u8>,
-    width: usize,
-    height: usize,
+/// Media target presets for "fit to target size" (label, capacity in bytes)
+const TARGET_PRESETS: [(&str, u64); 5] = [
+    ("CD (700 MB)", 737_280_000),
+    ("DVD (4.7 GB)", 4_700_372_992),
+    ("DVD DL (8.5 GB)", 8_543_666_176),
+    ("Blu-ray (25 GB)", 25_025_314_816),
+    ("Upload limit (25 GB)", 25 * 1024 * 1024 * 1024),
+];
+
+/// Batch thumbnail export request (report thumbnails folder)
+struct ThumbnailExportJob {
+    /// (absolute path, thumbnail kind) for each exported file
+    files: Vec<(String, Option<ThumbnailKind>)>,
+    dir: PathBuf,
+    options: ThumbnailOptions,
+    /// Thumbnail file name per file, or an error
+    reply: Sender<Result<Vec<Option<String>>, String>>,
 }
 
-#[derive(Clone, Copy, PartialEq)]
-pub enum SortColumn {
-    Name,
-    Extension,
-    Size,
-    Path,
-    Date,
+/// Preview textures get mip levels, so previews drawn smaller than their pixel size don't alias
+const PREVIEW_TEXTURE_OPTIONS: egui::TextureOptions = egui::TextureOptions {
+    mipmap_mode: Some(egui::TextureFilter::Linear),
+    ..egui::TextureOptions::LINEAR
+};
+
+/// Wait this long on a video row before starting FFmpeg, so sweeping the mouse over a list doesn't queue extractions
+const VIDEO_HOVER_DELAY: Duration = Duration::from_millis(300);
+
+/// Work item for the preview service
+enum PreviewJob {
+    /// Hover preview: (path, kind, options, hover id)
+    Hover(String, ThumbnailKind, ThumbnailOptions, u64),
+    /// Batch thumbnails for an export
+    Export(ThumbnailExportJob),
+    /// Thumbnail for a card of the duplicate manager; not cancelled by hovers
+    Card(String, ThumbnailKind, ThumbnailOptions),
+}
+
+/// Files found by a background scan
+struct ScanOutput {
+    files: Vec<FileInfo>,
+    /// Network reads that needed a retry
+    retries: usize,
+    source: ScanSource,
+    /// Folders and files that could not be read
+    report: ScanReport,
+}
+
+/// How a scan listed the files
+enum ScanSource {
+    /// Walked the folders
+    Walker,
+    /// Read the NTFS master file table
+    NtfsIndex,
+    /// Refreshed the cached NTFS index from the USN journal (number of changed entries)
+    NtfsJournal(usize),
+}
+
+type ScanResult = Result<ScanOutput, String>;
+
+/// Long-lived background thread that generates preview thumbnails.
+/// Owns a single ThumbnailGenerator (Pdfium bound once, FFmpeg path resolved once)
+/// and handles jobs one at a time, for hover previews and batch exports alike,
+/// so at most one FFmpeg process runs at any time.
+struct PreviewService {
+    sender: Sender<PreviewJob>,
+    /// Hover results: (path, thumbnail or None if generation failed)
+    receiver: Receiver<(String, Option<Thumbnail>)>,
+    /// Id of the current hover; an in-flight hover with an older id is cancelled
+    current_hover: Arc<AtomicU64>,
+}
+
+impl PreviewService {
+    fn start() -> Self {
+        let (job_tx, job_rx) = mpsc::channel::<PreviewJob>();
+        let (result_tx, result_rx) = mpsc::channel();
+        let current_hover = Arc::new(AtomicU64::new(0));
+        let worker_hover = Arc::clone(&current_hover);
+
+        thread::spawn(move || {
+            let mut generator = ThumbnailGenerator::default();
+            let mut pending: Option<PreviewJob> = None;
+
+            loop {
+                let job = match pending.take() {
+                    Some(job) => job,
+                    None => match job_rx.recv() {
+                        Ok(job) => job,
+                        Err(_) => break, // App has shut down
+                    },
+                };
+
+                match job {
+                    PreviewJob::Hover(mut path, mut kind, mut options, mut id) => {
+                        // Only the most recent hover matters - skip hovers that queued up meanwhile
+                        while let Ok(next) = job_rx.try_recv() {
+                            match next {
+                                PreviewJob::Hover(p, k, o, i) => {
+                                    path = p;
+                                    kind = k;
+                                    options = o;
+                                    id = i;
+                                }
+                                export => {
+                                    pending = Some(export);
+                                    break;
+                                }
+                            }
+                        }
+
+                        FileListerApp::debug_log(&format!("[DEBUG] Preview service: {}", path));
+                        let cancelled = || worker_hover.load(Ordering::Relaxed) != id;
+                        let preview = match generator.generate_cancellable(&path, kind, &options, &cancelled) {
+                            Ok(thumbnail) => Some(thumbnail),
+                            // Hover moved on: not a failure, and nobody is waiting for it
+                            Err(e) if e == thumbnail::CANCELLED => {
+                                FileListerApp::debug_log(&format!("[DEBUG] Preview cancelled: {}", path));
+                                continue;
+                            }
+                            Err(e) => {
+                                FileListerApp::debug_log(&format!("[ERROR] Preview failed for {}: {}", path, e));
+                                None
+                            }
+                        };
+                        if result_tx.send((path, preview)).is_err() {
+                            break;
+                        }
+                    }
+                    PreviewJob::Export(job) => {
+                        let result = thumbnail::export_thumbnails(&mut generator, &job.files, &job.dir, &job.options);
+                        let _ = job.reply.send(result);
+                    }
+                    PreviewJob::Card(path, kind, options) => {
+                        let preview = generator.generate(&path, kind, &options).ok();
+                        if result_tx.send((path, preview)).is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+
+        Self {
+            sender: job_tx,
+            receiver: result_rx,
+            current_hover,
+        }
+    }
+
+    /// Request a hover preview, cancelling any hover preview still in progress
+    fn request_hover(&self, path: String, kind: ThumbnailKind, options: ThumbnailOptions) {
+        let id = self.current_hover.fetch_add(1, Ordering::Relaxed) + 1;
+        let _ = self.sender.send(PreviewJob::Hover(path, kind, options, id));
+    }
+
+    /// Request a thumbnail that isn't tied to the pointer (results arrive like hover previews)
+    fn request_card(&self, path: String, kind: ThumbnailKind, options: ThumbnailOptions) {
+        let _ = self.sender.send(PreviewJob::Card(path, kind, options));
+    }
+
+    /// Cancel the hover preview in progress (kills its FFmpeg process)
+    fn cancel_hover(&self) {
+        self.current_hover.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// CSV export waiting for its thumbnails folder to be generated
+struct PendingThumbnailExport {
+    csv_path: PathBuf,
+    files: Vec<FileInfo>,
+    receiver: Receiver<Result<Vec<Option<String>>, String>>,
+    /// Temporary folder of thumbnails an HTML report embeds
+    embedded_dir: Option<PathBuf>,
+}
+
+/// Subject and body of the report e-mail, editable before sending
+struct ReportDraft {
+    subject: String,
+    body: String,
+}
+
+/// CSV export being written on a background thread
+struct RunningExport {
+    job: ExportJob,
+    /// Thumbnails generated for the report, if it has a Thumbnail column
+    thumbnail_count: Option<usize>,
+    /// Temporary thumbnails folder of an HTML report, removed once the report is written
+    embedded_dir: Option<PathBuf>,
 }
 
 #[derive(Clone, Copy, PartialEq)]
@@ -45,6 +266,475 @@ pub enum SortOrder {
     Descending,
 }
 
+/// Action that can be performed on a single file row.
+/// The same list drives the row context menu, the command palette and keyboard shortcuts.
+#[derive(Clone, Copy, PartialEq)]
+pub enum RowAction {
+    Open,
+    OpenLocation,
+    CopyPath,
+    Rename,
+    MoveToFolder,
+    CreateSymlink,
+    CreateShortcut,
+    SplitJoin,
+    Properties,
+    ToggleReviewed,
+    Delete,
+}
+
+impl RowAction {
+    /// All row actions in menu order
+    pub const ALL: [RowAction; 11] = [
+        RowAction::Open,
+        RowAction::OpenLocation,
+        RowAction::CopyPath,
+        RowAction::Rename,
+        RowAction::MoveToFolder,
+        RowAction::CreateSymlink,
+        RowAction::CreateShortcut,
+        RowAction::SplitJoin,
+        RowAction::Properties,
+        RowAction::ToggleReviewed,
+        RowAction::Delete,
+    ];
+
+    /// Menu label (with icon)
+    pub fn label(self) -> &'static str {
+        match self {
+            RowAction::Open => "📄 Open",
+            RowAction::OpenLocation => "📂 Open file location",
+            RowAction::CopyPath => "📋 Copy path",
+            RowAction::Rename => "✏️ Rename",
+            RowAction::MoveToFolder => "📁 Move / Copy to folder...",
+            RowAction::CreateSymlink => "🔗 Create symlink in folder...",
+            RowAction::CreateShortcut => "↗ Create shortcut (.lnk) in folder...",
+            RowAction::SplitJoin => "✂ Split into parts / join...",
+            RowAction::Properties => "ℹ Properties",
+            RowAction::ToggleReviewed => "✔ Mark / unmark reviewed",
+            RowAction::Delete => "🗑️ Delete",
+        }
+    }
+
+    /// Keyboard shortcut (applies to the single selected file)
+    pub fn shortcut(self) -> Option<egui::KeyboardShortcut> {
+        use egui::{Key, KeyboardShortcut, Modifiers};
+        match self {
+            RowAction::Open => Some(KeyboardShortcut::new(Modifiers::NONE, Key::Enter)),
+            RowAction::CopyPath => Some(KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::C)),
+            RowAction::Rename => Some(KeyboardShortcut::new(Modifiers::NONE, Key::F2)),
+            RowAction::Properties => Some(KeyboardShortcut::new(Modifiers::ALT, Key::Enter)),
+            RowAction::ToggleReviewed => Some(KeyboardShortcut::new(Modifiers::NONE, Key::R)),
+            // Destructive or dialog-based actions are menu-only
+            RowAction::OpenLocation
+            | RowAction::MoveToFolder
+            | RowAction::CreateSymlink
+            | RowAction::CreateShortcut
+            | RowAction::SplitJoin
+            | RowAction::Delete => None,
+        }
+    }
+
+    /// Changes or removes the file (hidden in read-only mode)
+    fn is_destructive(self) -> bool {
+        matches!(self, RowAction::Rename | RowAction::MoveToFolder | RowAction::Delete)
+    }
+
+    /// Offered for folder rows too
+    fn applies_to_folders(self) -> bool {
+        matches!(self, RowAction::Open | RowAction::OpenLocation | RowAction::CopyPath | RowAction::Properties)
+    }
+
+    /// Whether a separator is drawn before this action in menus
+    fn starts_group(self) -> bool {
+        matches!(self, RowAction::Rename | RowAction::Properties | RowAction::Delete)
+    }
+}
+
+/// What the Move / Copy window does with files in the destination folder
+#[derive(Clone, Copy, PartialEq)]
+enum TransferMode {
+    Move,
+    Copy,
+    /// Symbolic link to the original
+    Symlink,
+    /// Windows shortcut (.lnk) to the original
+    Shortcut,
+}
+
+impl TransferMode {
+    const ALL: [TransferMode; 4] = [
+        TransferMode::Move,
+        TransferMode::Copy,
+        TransferMode::Symlink,
+        TransferMode::Shortcut,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            TransferMode::Move => "Move",
+            TransferMode::Copy => "Copy",
+            TransferMode::Symlink => "Symlink",
+            TransferMode::Shortcut => "Shortcut (.lnk)",
+        }
+    }
+
+    /// Past tense for status messages
+    fn done_label(self) -> &'static str {
+        match self {
+            TransferMode::Move => "Moved",
+            TransferMode::Copy => "Copied",
+            TransferMode::Symlink => "Linked",
+            TransferMode::Shortcut => "Created shortcuts for",
+        }
+    }
+}
+
+/// Files waiting for a destination in the Move / Copy window
+struct PendingTransfer {
+    /// (absolute path, file name)
+    files: Vec<(String, String)>,
+    mode: TransferMode,
+}
+
+/// What a running bulk job does (for reporting when it finishes)
+enum BulkJobKind {
+    /// Sources with their names in the destination, to update the list in place
+    Transfer { dest_folder: PathBuf, mode: TransferMode, items: Vec<(PathBuf, String)> },
+    Delete { paths: Vec<PathBuf> },
+    /// Retention rules run; logged when it finishes
+    Retention { run: Arc<Mutex<RetentionRun>> },
+    /// Splitting a file into parts
+    Split { file_name: String, dest_folder: PathBuf },
+    /// Joining parts; the joiner knows whether the result was verified
+    Join { joiner: Arc<Mutex<Joiner>> },
+    /// Moving the files of a logged retention run back; sources are added as they are restored
+    RetentionUndo { started_at: i64, restored: Arc<Mutex<Vec<PathBuf>>> },
+}
+
+/// Background hashing for the Hash column
+struct ContentHashing {
+    receiver: Receiver<ContentHashMessage>,
+    cancel: Arc<AtomicBool>,
+    done: usize,
+    total: usize,
+}
+
+/// Background line / word counting for the Lines and Words columns
+struct TextCounting {
+    receiver: Receiver<TextCountMessage>,
+    cancel: Arc<AtomicBool>,
+    done: usize,
+    total: usize,
+}
+
+/// File picked for "Split into parts / join"
+struct SplitDialog {
+    path: PathBuf,
+    file_size: u64,
+    /// Where the parts go (the file's folder unless changed)
+    dest_folder: PathBuf,
+    /// The file is a split manifest: join instead
+    manifest: Option<Result<SplitManifest, String>>,
+}
+
+/// Bulk move/copy/delete running in the background
+struct BulkJob {
+    job: Job,
+    kind: BulkJobKind,
+}
+
+/// Which names the bulk rename window fixes
+#[derive(Clone, Copy, PartialEq)]
+enum BulkRenameKind {
+    /// Normalize Extensions: lowercase, common spelling, missing extensions
+    Extensions(ExtensionRules),
+    /// Sanitize Names: make names safe for Windows, SharePoint and ZIP
+    SafeNames,
+}
+
+impl BulkRenameKind {
+    fn title(self) -> &'static str {
+        match self {
+            BulkRenameKind::Extensions(_) => "Normalize Extensions",
+            BulkRenameKind::SafeNames => "Sanitize Names",
+        }
+    }
+}
+
+/// Files and rules for the Normalize Extensions / Sanitize Names window
+struct BulkRename {
+    files: Vec<FileInfo>,
+    kind: BulkRenameKind,
+    /// Renames the rules produce (new names can be edited before applying)
+    plan: Vec<RenameOp>,
+    /// Edit buffer per rename: the new file name
+    new_names: Vec<String>,
+    /// Dry run of the plan (conflicts per rename)
+    simulation: RenameSimulation,
+}
+
+impl BulkRename {
+    fn new(files: Vec<FileInfo>, kind: BulkRenameKind) -> Self {
+        let mut normalizer = Self {
+            files,
+            kind,
+            plan: Vec::new(),
+            new_names: Vec::new(),
+            simulation: RenameSimulation::default(),
+        };
+        normalizer.replan();
+        normalizer
+    }
+
+    /// Rebuild the plan from the rules (discards edited names)
+    fn replan(&mut self) {
+        self.plan = match self.kind {
+            BulkRenameKind::Extensions(rules) => extensions::plan_renames(&self.files, &rules),
+            BulkRenameKind::SafeNames => name_audit::plan_sanitize(&self.files),
+        };
+        self.new_names = self
+            .plan
+            .iter()
+            .map(|op| op.to.file_name().unwrap_or_default().to_string_lossy().to_string())
+            .collect();
+        self.simulation = renames::simulate(&self.plan);
+    }
+}
+
+/// Perceptual grouping of visually similar images for the duplicates view
+struct SimilarImages {
+    /// Perceptual hash per image path (filled when hashing finishes)
+    hashes: Vec<(String, u64)>,
+    /// Path -> (group id, group size) at the current tolerance
+    groups: HashMap<String, (usize, usize)>,
+    /// Running background hashing
+    receiver: Option<Receiver<HashingMessage>>,
+    /// Images hashed so far, total
+    progress: (usize, usize),
+}
+
+/// "Wrong extension" filter: headers of the scanned files read in the background
+struct ExtensionCheck {
+    /// Path -> extension of the format the content has, for files whose extension disagrees
+    mismatches: HashMap<String, &'static str>,
+    /// Running check
+    receiver: Option<Receiver<CheckMessage>>,
+    /// Files checked so far, total
+    progress: (usize, usize),
+}
+
+/// "Streams" filter (Windows): alternate data streams of the scanned files, listed in the background
+struct StreamCheck {
+    /// Path -> alternate streams, for files that have any
+    streams: HashMap<String, Vec<DataStream>>,
+    /// Running listing
+    receiver: Option<Receiver<StreamMessage>>,
+    /// Files listed so far, total
+    progress: (usize, usize),
+}
+
+/// Manifest check window: a folder checked against a list of expected files
+struct ManifestVerification {
+    manifest: PathBuf,
+    /// Running check
+    receiver: Option<Receiver<VerifyMessage>>,
+    /// Expected files checked so far, total
+    progress: (usize, usize),
+    result: Option<Result<ManifestCheck, String>>,
+    /// Which of CheckStatus::ALL the window lists
+    shown: [bool; 3],
+}
+
+/// Permissions window: owner and who can write for each scanned folder
+struct PermissionAudit {
+    folders: Vec<FolderPermissions>,
+    /// Running audit
+    receiver: Option<Receiver<AuditMessage>>,
+    /// Folders read so far, total
+    progress: (usize, usize),
+    /// List only world-writable or everyone-readable folders
+    flagged_only: bool,
+}
+
+/// Files each filter toggle would keep on its own out of those matching the filter text,
+/// counted by apply_filter and shown next to the toggles
+#[derive(Debug, Default, Clone, Copy)]
+struct FilterCounts {
+    /// Files matching the filter text (and the content filter)
+    text: usize,
+    duplicates: usize,
+    /// Only known while the versions view is on (grouping is computed for it)
+    versions: Option<usize>,
+    /// Files modified in each of RecentDays::ALL
+    recent: [usize; 3],
+    cloud: usize,
+    /// Reviewed files, i.e. what "Hide reviewed" hides
+    reviewed: usize,
+    never_accessed: usize,
+    too_long: usize,
+    unsafe_names: usize,
+    /// Zero-byte files and empty folders
+    empty: usize,
+    /// Once the extension check has finished
+    wrong_extension: Option<usize>,
+    /// Once the stream listing has finished
+    streams: Option<usize>,
+    orphaned: usize,
+}
+
+/// Toggle label with its count badge: "Show today only (17)"
+fn counted(label: &str, count: Option<usize>) -> String {
+    match count {
+        Some(count) => format!("{} ({})", label, count),
+        None => label.to_string(),
+    }
+}
+
+/// Display text of a table row, formatted once and reused while the file is unchanged,
+/// so drawing a visible row doesn't clone or format anything
+struct RowText {
+    name: String,
+    extension: String,
+    relative_path: String,
+    absolute_path: String,
+    size: String,
+    size_bytes: String,
+    on_disk: String,
+    items: String,
+    modified: String,
+    accessed: String,
+    hash: String,
+    lines: String,
+    words: String,
+    name_length: usize,
+    path_length: usize,
+    /// Values the text was formatted from (rescans, hashing and counting replace them)
+    stamp: RowStamp,
+}
+
+/// Size, modified, accessed, items, hash and line count of a row
+type RowStamp = (u64, i64, Option<i64>, u32, Option<Arc<str>>, Option<u64>);
+
+impl RowText {
+    fn stamp(file: &FileInfo) -> RowStamp {
+        (
+            file.file_size,
+            file.modified_timestamp,
+            file.accessed_timestamp,
+            file.item_count,
+            file.hash.clone(),
+            file.line_count,
+        )
+    }
+
+    fn new(file: &FileInfo, format: &ColumnFormat) -> Self {
+        Self {
+            name: file.name().to_string(),
+            extension: file.extension().to_string(),
+            relative_path: file.relative_path().to_string(),
+            absolute_path: file.absolute_path().to_string(),
+            size: columns::SIZE.text(file, format),
+            size_bytes: format_bytes(file.file_size, &format.size),
+            on_disk: columns::ON_DISK.text(file, format),
+            items: columns::ITEMS.text(file, format),
+            modified: columns::MODIFIED.text(file, format),
+            accessed: columns::ACCESSED.text(file, format),
+            hash: columns::HASH.text(file, format),
+            lines: columns::LINES.text(file, format),
+            words: columns::WORDS.text(file, format),
+            name_length: name_audit::name_length(file),
+            path_length: name_audit::path_length(file),
+            stamp: Self::stamp(file),
+        }
+    }
+}
+
+/// Folders "Open Locations" opens without asking
+const OPEN_LOCATIONS_UNASKED: usize = 10;
+
+/// Most folders "Open Locations" opens at once
+const OPEN_LOCATIONS_MAX: usize = 50;
+
+/// Rows kept in the display text cache; scrolling past more starts it over
+const ROW_TEXT_CACHE_LIMIT: usize = 4096;
+
+/// Duplicates view by content: files of equal size, hashed (SHA-256) in the background
+struct ContentDuplicates {
+    /// Size of each file that shares its size with another
+    sizes: HashMap<String, u64>,
+    /// (path, size, SHA-256) of the files hashed so far
+    hashed: Vec<(String, u64, String)>,
+    /// Path -> (group id, group size), filled when hashing finishes
+    groups: HashMap<String, (usize, usize)>,
+    hashing: Option<ContentHashing>,
+}
+
+/// Duplicate manager window: groups side by side, which file of each group to keep
+struct DuplicateManager {
+    groups: Vec<Vec<FileInfo>>,
+    /// What the files of a group share ("equal names", "equal content", "similar images")
+    basis: &'static str,
+    /// Paths of the files to keep; the others are deleted or moved
+    keep: HashSet<String>,
+    /// Card thumbnails asked from the preview service
+    requested: HashSet<String>,
+}
+
+impl DuplicateManager {
+    fn apply_rule(&mut self, rule: KeepRule) {
+        self.keep = self
+            .groups
+            .iter()
+            .filter_map(|group| rule.keeper(group).map(|idx| group[idx].absolute_path().to_string()))
+            .collect();
+    }
+
+    /// Files not kept: (absolute path, file name)
+    fn rest(&self) -> Vec<(String, String)> {
+        self.groups
+            .iter()
+            .flatten()
+            .filter(|f| !self.keep.contains(f.absolute_path()))
+            .map(|f| (f.absolute_path().to_string(), f.full_name().to_string()))
+            .collect()
+    }
+
+    /// First group in which no file is kept
+    fn group_without_keeper(&self) -> Option<usize> {
+        self.groups
+            .iter()
+            .position(|group| !group.iter().any(|f| self.keep.contains(f.absolute_path())))
+    }
+}
+
+/// State of the Search Snapshots window
+struct SnapshotBrowser {
+    query: String,
+    /// Label for saving the current listing as a snapshot
+    label: String,
+    result: Option<SnapshotSearch>,
+    /// Running search (or snapshot listing)
+    receiver: Option<Receiver<SnapshotSearch>>,
+}
+
+/// Snapshot of file details for the Properties window
+struct FileProperties {
+    full_name: String,
+    absolute_path: String,
+    file_size: u64,
+    modified_timestamp: i64,
+    created_timestamp: Option<i64>,
+    accessed_timestamp: Option<i64>,
+    readonly: bool,
+    owner: Option<String>,
+    owner_orphaned: bool,
+    /// Message count and date range of a mail archive (.pst / .mbox)
+    mail_summary: Option<Result<MailStoreSummary, String>>,
+    /// Mail archive still being read
+    mail_receiver: Option<Receiver<Result<MailStoreSummary, String>>>,
+}
+
 /// Content type for document preview
 #[derive(Clone)]
 pub enum DocumentPreviewContent {
@@ -75,20 +765,107 @@ pub enum DocumentPreviewContent {
 pub struct FileListerApp {
     /// Selected folders for scanning (multiple folder support)
     selected_folders: Vec<PathBuf>,
+    /// Individually added files (ad-hoc listing, e.g. from the clipboard)
+    added_files: Vec<PathBuf>,
     files: Vec<FileInfo>,
+    /// Folders above the scanned files with totals ("Show folders"), listed before the files
+    folder_rows: Vec<FileInfo>,
+    /// Media / document / archive / other composition of each folder row (by absolute path)
+    folder_mixes: HashMap<String, ContentMix>,
     filtered_files: Vec<FileInfo>,
     status_message: String,
     error_message: Option<String>,
     recursive: bool,
-    sort_column: SortColumn,
+    /// List dotfiles and files with the Hidden/System attribute
+    include_hidden: bool,
+    /// Leave out files matched by .gitignore/.ignore files
+    respect_gitignore: bool,
+    /// Keep the list in sync with file changes in the scanned folders
+    live_update: bool,
+    folder_watcher: Option<FolderWatcher>,
+    sort_column: FileColumn,
     sort_order: SortOrder,
+    /// Shift-clicked column that orders files equal in the sort column
+    secondary_sort: Option<(FileColumn, SortOrder)>,
     filter_text: String,
     /// Map of full_name -> count for detecting duplicates
     duplicate_counts: HashMap<String, usize>,
     /// Show only duplicate files
     show_duplicates_only: bool,
-    /// Show only files modified today
-    show_today_only: bool,
+    /// Duplicates view groups visually similar images instead of equal names
+    similar_images: Option<SimilarImages>,
+    /// Duplicates view groups files with equal content instead of equal names
+    content_duplicates: Option<ContentDuplicates>,
+    /// Show only versions and copies of the same document (report_v1, report_v2, report (1))
+    show_versions_only: bool,
+    /// Absolute path -> version group, computed while the versions view is on
+    versions: HashMap<String, VersionInfo>,
+    /// Scanned paths of each hard-linked file
+    hard_links: HashMap<FileId, Vec<String>>,
+    /// Size and disk usage of the listed files (hard links counted once)
+    listed_usage: DiskUsage,
+    /// Count badges of the filter toggles
+    filter_counts: FilterCounts,
+    /// Show only files modified today, yesterday or in the last 7 days (local calendar days)
+    modified_within: Option<RecentDays>,
+    /// Show only online-only cloud placeholder files
+    show_cloud_only: bool,
+    /// Show only files whose owner account no longer exists
+    show_orphaned_only: bool,
+    /// Files marked as reviewed (persisted, valid while unmodified)
+    reviewed: ReviewedFiles,
+    /// Hide files marked as reviewed
+    hide_reviewed: bool,
+    /// The user's notes on folders (persisted)
+    folder_notes: FolderNotes,
+    /// README / ABOUT excerpt of each scanned folder, read when the scan starts
+    folder_readmes: Vec<(PathBuf, Option<ReadmeExcerpt>)>,
+    /// Folder whose note is being edited, and the text so far
+    editing_folder_note: Option<(PathBuf, String)>,
+    /// Companion files per grouped primary file (absolute path), when sidecar grouping is on
+    sidecars: HashMap<String, Vec<FileInfo>>,
+    /// Paths of all grouped companions (rows listed under an expanded primary)
+    sidecar_companions: HashSet<String>,
+    /// Primary files whose companions are shown as rows below them
+    expanded_sidecars: HashSet<String>,
+    /// Show only files not read since they were created or last written
+    show_never_accessed: bool,
+    /// Show only files whose name or full path exceeds the length limits
+    show_too_long: bool,
+    /// Show only files with names unsafe for Windows, SharePoint or ZIP
+    show_unsafe_names: bool,
+    /// Show only zero-byte files and the empty folders of a recursive scan
+    show_empty_only: bool,
+    /// Show only files whose extension disagrees with their content (None: filter off)
+    extension_check: Option<ExtensionCheck>,
+    /// Path -> extension detected from the content, for files without extension ("(png?)")
+    extension_suggestions: HashMap<String, &'static str>,
+    /// Files without extension being detected after a scan
+    suggestion_receiver: Option<Receiver<Vec<(String, &'static str)>>>,
+    /// Show only files with alternate data streams (None: filter off)
+    stream_check: Option<StreamCheck>,
+    /// Display text of recently drawn rows, by path
+    row_texts: HashMap<String, Rc<RowText>>,
+    /// Size format and calendar the cached row text was formatted with
+    row_text_format: Option<(SizeFormat, Calendar)>,
+    /// Whether the scanned volumes update access times (weakest of all scanned folders)
+    access_time_mode: AccessTimeMode,
+    /// Free / total space of the volume holding the first scanned folder
+    volume_space: Option<VolumeSpace>,
+    /// Started with --read-only (or by workstation policy): read-only mode can't be switched off
+    read_only_locked: bool,
+    /// Restrictions of a shared workstation (allowed folders, user profiles, locked settings)
+    kiosk: Option<KioskPolicy>,
+    /// Ask who is using the tool (workstation policy lists the user profiles)
+    choose_user_profile: bool,
+    /// Name typed for a new user profile
+    new_user_profile: String,
+    /// Restrict the list to these absolute paths (label, paths), e.g. "Only in <root>"
+    content_filter: Option<(String, HashSet<String>)>,
+    /// Receiver for a running root comparison
+    comparison_receiver: Option<Receiver<RootComparison>>,
+    /// Finished root comparison (shown in the Compare Roots window)
+    comparison: Option<RootComparison>,
     /// Index of file being renamed (in filtered_files)
     editing_index: Option<usize>,
     /// Text buffer for renaming
@@ -101,16 +878,30 @@ pub struct FileListerApp {
     show_delete_confirm: bool,
     /// File paths pending deletion (for confirmation modal)
     pending_delete_paths: Vec<(String, String)>, // (absolute_path, full_name)
+    /// Folders waiting for confirmation in "Open Locations": (folder, first selected file in it)
+    pending_open_locations: Option<Vec<(String, String)>>,
+    /// Command palette (Ctrl+Shift+P) for the selected file: the filter typed so far
+    command_palette: Option<String>,
     /// Receiver for background scan results
-    scan_receiver: Option<Receiver<Result<Vec<FileInfo>, String>>>,
+    scan_receiver: Option<Receiver<ScanResult>>,
+    /// Folders and files the last scan could not read
+    scan_report: ScanReport,
     /// Flag indicating scanning is in progress
     is_scanning: bool,
+    /// Files scanned and estimated total of the running scan
+    scan_progress: Option<Arc<ScanProgress>>,
     /// Cache of loaded image textures (absolute_path -> texture)
     image_cache: HashMap<String, egui::TextureHandle>,
-    /// Receiver for background image loading
-    image_receiver: Option<Receiver<(String, ImagePreviewData)>>,
+    /// Background thumbnail generator (started on first preview)
+    preview_service: Option<PreviewService>,
+    /// Paths whose thumbnail could not be generated
+    preview_failed: HashSet<String>,
     /// Path currently being loaded in background
     image_loading_path: Option<String>,
+    /// Video row under the pointer and since when (hover-intent delay before extracting)
+    video_hover_intent: Option<(String, Instant)>,
+    /// A video row was hovered this frame (otherwise its extraction is cancelled)
+    video_hover_seen: bool,
     /// When the current image/video loading started (for timeout)
     image_loading_start: Option<Instant>,
     /// Cache of loaded document content (absolute_path -> content)
@@ -134,34 +925,173 @@ pub struct FileListerApp {
     audio_loading_path: Option<String>,
     /// Receiver for background audio loading results (path, samples, sample_rate, channels, duration_secs)
     audio_receiver: Option<Receiver<(String, Option<(Vec<i16>, u32, u16)>, Option<f64>)>>,
+    /// Persisted user settings
+    settings: AppSettings,
+    /// Pixels per point of the last frame (to detect DPI changes between monitors)
+    last_pixels_per_point: Option<f32>,
+    /// File shown in the Properties window
+    properties: Option<FileProperties>,
+    /// Show the Settings window
+    show_settings: bool,
+    /// "Export diagnostic bundle" replaces folders, home folder and user name with placeholders
+    diagnostics_anonymize: bool,
+    /// CSV export waiting for thumbnails from the preview service
+    thumbnail_export: Option<PendingThumbnailExport>,
+    /// CSV export in progress (progress dialog)
+    running_export: Option<RunningExport>,
+    /// Export the files the filters hide instead of the listed ones (inverse export)
+    export_excluded: bool,
+    /// Show the "Export to cloud" window (target setup, sign-in code, progress)
+    show_cloud_export: bool,
+    /// Listing being pushed to Google Sheets or Microsoft 365
+    cloud_export: Option<CloudExportJob>,
+    /// "Send report" window: editable subject and summary
+    report_draft: Option<ReportDraft>,
+    /// Report e-mail being written and sent (status line or error)
+    report_mail_receiver: Option<Receiver<Result<String, String>>>,
+    /// Scan/export hooks running in the background (failure messages)
+    hook_receivers: Vec<Receiver<Vec<String>>>,
+    /// File manifest or burn list being hashed in the background
+    manifest_receiver: Option<Receiver<ManifestMessage>>,
+    /// What manifest_receiver writes ("manifest", "burn list"), for status messages
+    manifest_kind: &'static str,
+    /// Move/copy waiting for a destination folder
+    pending_transfer: Option<PendingTransfer>,
+    /// "Split into parts / join" window
+    split_dialog: Option<SplitDialog>,
+    /// Duplicate manager window
+    duplicate_manager: Option<DuplicateManager>,
+    /// Checksums being computed for the Hash column
+    content_hashing: Option<ContentHashing>,
+    /// Text files being counted for the Lines / Words columns
+    text_counting: Option<TextCounting>,
+    /// Second pane in two-panel mode (None when the mode is off)
+    right_pane: Option<FilePane>,
+    /// Pane that F5/F6 copy or move from
+    active_pane: ActivePane,
+    /// Open Normalize Extensions / Sanitize Names window
+    bulk_rename: Option<BulkRename>,
+    /// Last applied bulk renames, reversed (new path -> original path), for undo
+    last_bulk_renames: Vec<RenameOp>,
+    /// Listed files found missing when an operation tried to use them (grayed out until refreshed)
+    stale_paths: HashSet<String>,
+    /// Bookmark being relabelled (index, text buffer)
+    editing_bookmark: Option<(usize, String)>,
+    /// Operations that failed with access denied, offered for "Retry as administrator"
+    elevation_offer: Option<Vec<ElevatedOp>>,
+    /// Receiver for the elevated helper's results
+    elevation_receiver: Option<Receiver<ElevatedResults>>,
+    /// Session left by a run that crashed, offered for restore at startup
+    session_restore: Option<Session>,
+    /// Selection (absolute paths) to re-apply when the restored session's or a refresh's scan finishes
+    pending_selection: Option<HashSet<String>>,
+    /// Vertical scroll offset of the file table in the last frame
+    table_scroll: f32,
+    /// Scroll offset to restore once a refresh's scan has finished
+    pending_scroll: Option<f32>,
+    /// Last autosaved session (saved_at zeroed, for change detection)
+    last_session: Session,
+    /// When the session was last checked for autosave
+    last_autosave: Instant,
+    /// Running bulk move/copy/delete (shown in the progress panel)
+    bulk_job: Option<BulkJob>,
+    /// Finished long operation (title, outcome), notified next frame if the window is unfocused
+    pending_notification: Option<(String, String)>,
+    /// Clicks on OS notifications (focus the window)
+    notification_clicks: (Sender<()>, Receiver<()>),
+    /// Search Snapshots window (saved listings of offline disks)
+    snapshot_browser: Option<SnapshotBrowser>,
+    /// Statistics window: modification activity of the listed files (recounted by apply_filter)
+    activity: Option<Activity>,
+    /// Permissions audit window
+    permission_audit: Option<PermissionAudit>,
+    /// Manifest check window
+    manifest_verification: Option<ManifestVerification>,
+    /// Retention rules window
+    show_retention: bool,
+    /// Files the rules matched in the last dry run ("Run now" acts on these)
+    retention_plan: Option<Vec<PlannedItem>>,
+    /// Dry run in progress
+    retention_receiver: Option<Receiver<Result<Vec<PlannedItem>, String>>>,
 }
 
 impl Default for FileListerApp {
     fn default() -> Self {
         Self {
             selected_folders: Vec::new(),
+            added_files: Vec::new(),
             files: Vec::new(),
+            folder_rows: Vec::new(),
+            folder_mixes: HashMap::new(),
             filtered_files: Vec::new(),
             status_message: String::from("Select a folder to scan"),
             error_message: None,
             recursive: false,
-            sort_column: SortColumn::Name,
+            include_hidden: false,
+            respect_gitignore: false,
+            live_update: false,
+            folder_watcher: None,
+            sort_column: columns::NAME,
             sort_order: SortOrder::Ascending,
+            secondary_sort: None,
             filter_text: String::new(),
             duplicate_counts: HashMap::new(),
             show_duplicates_only: false,
-            show_today_only: false,
+            similar_images: None,
+            content_duplicates: None,
+            show_versions_only: false,
+            versions: HashMap::new(),
+            hard_links: HashMap::new(),
+            listed_usage: DiskUsage::default(),
+            filter_counts: FilterCounts::default(),
+            modified_within: None,
+            show_cloud_only: false,
+            show_orphaned_only: false,
+            reviewed: ReviewedFiles::load(),
+            folder_notes: FolderNotes::load(),
+            folder_readmes: Vec::new(),
+            editing_folder_note: None,
+            hide_reviewed: false,
+            sidecars: HashMap::new(),
+            sidecar_companions: HashSet::new(),
+            expanded_sidecars: HashSet::new(),
+            show_never_accessed: false,
+            show_too_long: false,
+            show_unsafe_names: false,
+            show_empty_only: false,
+            extension_check: None,
+            extension_suggestions: HashMap::new(),
+            suggestion_receiver: None,
+            stream_check: None,
+            row_texts: HashMap::new(),
+            row_text_format: None,
+            access_time_mode: AccessTimeMode::Unknown,
+            volume_space: None,
+            read_only_locked: false,
+            kiosk: None,
+            choose_user_profile: false,
+            new_user_profile: String::new(),
+            content_filter: None,
+            comparison_receiver: None,
+            comparison: None,
             editing_index: None,
             editing_text: String::new(),
             request_rename_focus: false,
             selected_files: HashSet::new(),
             show_delete_confirm: false,
+            pending_open_locations: None,
+            command_palette: None,
             pending_delete_paths: Vec::new(),
             scan_receiver: None,
+            scan_report: ScanReport::default(),
             is_scanning: false,
+            scan_progress: None,
             image_cache: HashMap::new(),
-            image_receiver: None,
+            preview_service: None,
+            preview_failed: HashSet::new(),
             image_loading_path: None,
+            video_hover_intent: None,
+            video_hover_seen: false,
             image_loading_start: None,
             document_cache: HashMap::new(),
             document_receiver: None,
@@ -173,12 +1103,56 @@ impl Default for FileListerApp {
             audio_error_path: None,
             audio_loading_path: None,
             audio_receiver: None,
+            settings: AppSettings::default(),
+            last_pixels_per_point: None,
+            properties: None,
+            show_settings: false,
+            diagnostics_anonymize: true,
+            thumbnail_export: None,
+            running_export: None,
+            export_excluded: false,
+            show_cloud_export: false,
+            cloud_export: None,
+            report_draft: None,
+            report_mail_receiver: None,
+            hook_receivers: Vec::new(),
+            manifest_receiver: None,
+            manifest_kind: "manifest",
+            pending_transfer: None,
+            split_dialog: None,
+            duplicate_manager: None,
+            content_hashing: None,
+            text_counting: None,
+            right_pane: None,
+            active_pane: ActivePane::Left,
+            bulk_rename: None,
+            last_bulk_renames: Vec::new(),
+            stale_paths: HashSet::new(),
+            editing_bookmark: None,
+            elevation_offer: None,
+            elevation_receiver: None,
+            session_restore: None,
+            pending_selection: None,
+            table_scroll: 0.0,
+            pending_scroll: None,
+            last_session: Session::default(),
+            last_autosave: Instant::now(),
+            bulk_job: None,
+            pending_notification: None,
+            notification_clicks: mpsc::channel(),
+            snapshot_browser: None,
+            activity: None,
+            permission_audit: None,
+            manifest_verification: None,
+            show_retention: false,
+            retention_plan: None,
+            retention_receiver: None,
         }
     }
 }
 
 impl FileListerApp {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>, settings: AppSettings, read_only: bool, kiosk: Option<KioskPolicy>) -> Self {
         // Load fonts with Thai/Unicode support
         let mut fonts = egui::FontDefinitions::default();
 
@@ -234,8 +1208,8 @@ impl FileListerApp {
 
         cc.egui_ctx.set_fonts(fonts);
 
-        // Check if FFmpeg is available (for video thumbnails)
-        Self::check_ffmpeg_availability();
+        // Find a video decoder (FFmpeg, else the platform's own) for video thumbnails
+        Self::check_video_decoder();
 
         // Check if Pdfium is available (for PDF previews)
         Self::check_pdfium_availability();
@@ -245,24 +1219,231 @@ impl FileListerApp {
 
         let mut app = Self::default();
         app.audio_stream = audio_stream;
+        app.choose_user_profile = kiosk
+            .as_ref()
+            .is_some_and(|k| !k.user_profiles.is_empty() && !k.allows_profile(users::active().as_deref()));
+        app.kiosk = kiosk;
+        if settings.dual_pane {
+            app.right_pane = Some(FilePane::new(settings.right_pane_folder.clone(), app.allowed_roots()));
+        }
+        app.settings = settings;
+        app.read_only_locked = read_only;
+        // A session file left behind means the last run did not exit cleanly
+        // (the user profile chooser comes first on a shared workstation)
+        app.session_restore = Session::load().filter(|session| !session.is_empty() && !app.choose_user_profile);
         app
     }
 
+    /// Read-only (audit) mode: delete, move and rename are disabled
+    fn is_read_only(&self) -> bool {
+        self.read_only_locked || self.settings.read_only
+    }
+
+    /// Folders the workstation policy allows (empty: any)
+    fn allowed_roots(&self) -> Vec<PathBuf> {
+        self.kiosk.as_ref().map(|k| k.allowed_roots.clone()).unwrap_or_default()
+    }
+
+    /// Report and refuse a path outside the folders the workstation policy allows; true if refused
+    fn refuse_if_not_allowed(&mut self, path: &std::path::Path) -> bool {
+        match self.kiosk.as_ref().map(|k| k.check(path)) {
+            Some(Err(e)) => {
+                self.error_message = Some(e);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Settings and retention rules are locked by the workstation policy
+    fn settings_locked(&self) -> bool {
+        self.kiosk.as_ref().is_some_and(|k| k.lock_settings)
+    }
+
+    /// Save this user's settings and switch to another user profile (None: default):
+    /// its settings, bookmarks and reviewed marks are loaded and the list starts empty
+    fn switch_user_profile(&mut self, name: Option<String>) {
+        if self.kiosk.as_ref().is_some_and(|k| !k.allows_profile(name.as_deref())) {
+            self.error_message = Some("This user profile is not allowed on this workstation".to_string());
+            return;
+        }
+        if let Err(e) = self.settings.save() {
+            self.error_message = Some(e);
+        }
+        Session::clear();
+        if let Err(e) = users::switch(name.as_deref()) {
+            self.error_message = Some(e);
+            return;
+        }
+
+        self.settings = AppSettings::load();
+        // A new profile's folder appears in the list right away
+        let _ = self.settings.save();
+        retry::configure(self.settings.network_retries, self.settings.retry_delay_ms);
+        priority::configure(self.settings.background_priority, self.settings.throttle_pause_ms);
+        text_match::configure_collation(self.settings.sort_collation);
+        text_match::configure_name_form(self.settings.name_form);
+        text_match::configure_natural_sort(self.settings.natural_sort);
+        self.reviewed = ReviewedFiles::load();
+        self.folder_notes = FolderNotes::load();
+        self.editing_folder_note = None;
+        self.right_pane = self
+            .settings
+            .dual_pane
+            .then(|| FilePane::new(self.settings.right_pane_folder.clone(), self.allowed_roots()));
+        self.active_pane = ActivePane::Left;
+        self.selected_folders.clear();
+        self.added_files.clear();
+        self.filter_text.clear();
+        self.session_restore = None;
+        self.last_session = Session::default();
+        self.choose_user_profile = false;
+        self.new_user_profile.clear();
+        self.scan_all_folders();
+        self.status_message = format!("User profile: {}", users::label(name.as_deref()));
+    }
+
+    /// Menu of user profiles: switch, or create one by name (unless the workstation policy lists them)
+    fn show_user_profile_menu(&mut self, ui: &mut egui::Ui) {
+        let active = users::active();
+        let fixed: Option<Vec<String>> = self
+            .kiosk
+            .as_ref()
+            .filter(|k| !k.user_profiles.is_empty())
+            .map(|k| k.user_profiles.clone());
+        let mut switch_to = None;
+        ui.menu_button(format!("👤 {}", users::label(active.as_deref())), |ui| {
+            let names: Vec<Option<String>> = match &fixed {
+                Some(names) => names.iter().cloned().map(Some).collect(),
+                None => std::iter::once(None).chain(users::list().into_iter().map(Some)).collect(),
+            };
+            for name in names {
+                if ui.selectable_label(name == active, users::label(name.as_deref())).clicked() {
+                    switch_to = Some(name);
+                    ui.close();
+                }
+            }
+            if fixed.is_none() {
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.new_user_profile).hint_text("New profile").desired_width(120.0));
+                    if ui.button("Create").clicked() {
+                        switch_to = Some(Some(self.new_user_profile.clone()));
+                        ui.close();
+                    }
+                });
+            }
+        })
+        .response
+        .on_hover_text("User profile: own settings, bookmarks, reviewed marks, snapshots and session");
+
+        if let Some(name) = switch_to {
+            if name != active {
+                self.switch_user_profile(name);
+            }
+        }
+    }
+
+    /// Ask who is using a shared workstation whose policy lists the user profiles
+    fn show_user_profile_chooser(&mut self, ctx: &egui::Context) {
+        if !self.choose_user_profile {
+            return;
+        }
+        let names = self.kiosk.as_ref().map(|k| k.user_profiles.clone()).unwrap_or_default();
+        let mut chosen = None;
+        egui::Window::new("Who is using File Lister?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                for name in names {
+                    if ui.add_sized([200.0, 28.0], egui::Button::new(&name)).clicked() {
+                        chosen = Some(name);
+                    }
+                }
+            });
+        if let Some(name) = chosen {
+            self.switch_user_profile(Some(name));
+        }
+    }
+
+    /// Report and refuse a destructive action in read-only mode; true if refused
+    fn refuse_if_read_only(&mut self) -> bool {
+        if self.is_read_only() {
+            self.error_message = Some("Read-only mode: delete, move and rename are disabled".to_string());
+        }
+        self.is_read_only()
+    }
+
+    /// Which files scans and the folder watcher leave out
+    fn scan_filter(&self) -> ScanFilter {
+        ScanFilter {
+            include_hidden: self.include_hidden,
+            respect_gitignore: self.respect_gitignore,
+        }
+    }
+
     fn scan_all_folders(&mut self) {
         self.error_message = None;
+        self.folder_watcher = None; // Restarted when the scan finishes
         self.selected_files.clear(); // Clear selections on rescan
         self.image_cache.clear(); // Clear image cache on rescan
+        self.preview_failed.clear();
         self.document_cache.clear(); // Clear document cache on rescan
+        self.content_filter = None; // Comparison results refer to the old scan
+        self.comparison = None;
+        self.similar_images = None;
+        self.extension_check = None;
+        self.extension_suggestions.clear();
+        self.suggestion_receiver = None;
+        self.stream_check = None;
+        self.scan_report = ScanReport::default();
+        self.stop_content_duplicates();
+        self.duplicate_manager = None; // Its groups refer to the old scan
+        self.stop_content_hashing();
+        self.stop_text_counting();
+
+        // Workstation policy: only allowed folders are scanned
+        if let Some(kiosk) = &self.kiosk {
+            let refused: Vec<String> = self
+                .selected_folders
+                .iter()
+                .chain(&self.added_files)
+                .filter(|path| !kiosk.allows(path))
+                .map(|path| path.display().to_string())
+                .collect();
+            if !refused.is_empty() {
+                self.selected_folders.retain(|path| kiosk.allows(path));
+                self.added_files.retain(|path| kiosk.allows(path));
+                self.error_message = Some(format!("Outside the folders allowed on this workstation: {}", refused.join(", ")));
+            }
+        }
 
-        if self.selected_folders.is_empty() {
+        if self.selected_folders.is_empty() && self.added_files.is_empty() {
             self.files.clear();
+            self.folder_rows.clear();
             self.filtered_files.clear();
             self.status_message = String::from("Select a folder to scan");
             return;
         }
 
         let folders = self.selected_folders.clone();
+        let added_files = self.added_files.clone();
         let recursive = self.recursive;
+        let filter = self.scan_filter();
+        self.folder_readmes = folders.iter().map(|folder| (folder.clone(), folder_notes::readme_excerpt(folder))).collect();
+        let resolve_owners = self.settings.resolve_owners;
+        let ntfs_fast_scan = self.settings.ntfs_fast_scan;
+
+        // Mount options decide whether "Accessed" and "Never accessed" mean anything
+        self.access_time_mode = folders
+            .iter()
+            .cloned()
+            .chain(added_files.iter().filter_map(|f| f.parent().map(|p| p.to_path_buf())))
+            .map(|folder| access_times::detect(&folder))
+            .reduce(AccessTimeMode::weakest)
+            .unwrap_or(AccessTimeMode::Unknown);
+        self.volume_space = folders.first().and_then(|folder| disk_space::volume_space(folder));
 
         // Create channel for receiving results
         let (tx, rx) = mpsc::channel();
@@ -270,408 +1451,5544 @@ impl FileListerApp {
         self.is_scanning = true;
         self.status_message = String::from("Scanning...");
 
-        // Spawn background thread for scanning
+        // Quick count pass alongside the scan, so progress can be shown as a percentage
+        let progress = Arc::new(ScanProgress::default());
+        self.scan_progress = Some(progress.clone());
+        let count_folders = folders.clone();
+        let count_progress = progress.clone();
         thread::spawn(move || {
-            let result = file_scanner::scan_folders(&folders, recursive)
-                .map_err(|e| e.to_string());
-            let _ = tx.send(result);
+            priority::enter_background();
+            file_scanner::count_files(&count_folders, recursive, filter, &count_progress);
         });
-    }
 
-    /// Check for scan results from background thread
-    fn check_scan_results(&mut self) {
-        if let Some(receiver) = &self.scan_receiver {
-            // Try to receive without blocking
-            if let Ok(result) = receiver.try_recv() {
-                match result {
-                    Ok(files) => {
-                        self.status_message = format!("Scanned: {} files found", files.len());
-                        self.files = files;
-                        self.sort_files();
-                        self.apply_filter();
-                    }
-                    Err(e) => {
-                        self.error_message = Some(format!("Error scanning folder: {}", e));
-                        self.files.clear();
-                        self.filtered_files.clear();
-                    }
+        // Spawn background thread for scanning
+        thread::spawn(move || {
+            priority::enter_background();
+            // Falls back to the folder walker when the volume isn't NTFS or can't be opened
+            // (and is always used for .gitignore filtering, which needs the folder structure)
+            let mft_files = if ntfs_fast_scan && !filter.respect_gitignore {
+                mft::scan(&folders, recursive, filter.include_hidden)
+            } else {
+                None
+            };
+            let (mut files, report, source) = match mft_files {
+                Some((files, journal_changes)) => {
+                    progress.scanned.store(files.len(), Ordering::Relaxed);
+                    let source = match journal_changes {
+                        Some(changes) => ScanSource::NtfsJournal(changes),
+                        None => ScanSource::NtfsIndex,
+                    };
+                    (files, ScanReport::default(), source)
                 }
-                self.is_scanning = false;
-                self.scan_receiver = None;
+                None => {
+                    let (files, report) = file_scanner::scan_folders_with_progress(&folders, recursive, filter, &progress);
+                    (files, report, ScanSource::Walker)
+                }
+            };
+            files.extend(file_scanner::scan_files(&added_files));
+            if resolve_owners {
+                owner::resolve_owners(&mut files);
             }
-        }
+            progress.finished.store(true, Ordering::Relaxed);
+            let _ = tx.send(Ok(ScanOutput {
+                files,
+                retries: retry::take_thread_retries(),
+                source,
+                report,
+            }));
+        });
     }
 
-    /// Check for completed background image loads
-    fn check_image_loads(&mut self, ctx: &egui::Context) {
-        // Check for timeout (10 seconds for video thumbnails)
-        if let Some(start_time) = self.image_loading_start {
-            if start_time.elapsed() > Duration::from_secs(10) {
-                // Timeout - clear loading state
-                self.image_loading_path = None;
-                self.image_receiver = None;
-                self.image_loading_start = None;
+    /// Add folders from clipboard paths and rescan
+    fn scan_folder_from_clipboard(&mut self) {
+        let paths = match clipboard::read_clipboard_paths() {
+            Ok(paths) => paths,
+            Err(e) => {
+                self.error_message = Some(e);
                 return;
             }
-        }
-
-        if let Some(receiver) = &self.image_receiver {
-            // Try to receive without blocking
-            if let Ok((path, data)) = receiver.try_recv() {
-                let size = [data.width, data.height];
-                let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &data.pixels);
-                let texture = ctx.load_texture(
-                    format!("preview_{}", path),
-                    color_image,
-                    egui::TextureOptions::default(),
-                );
+        };
 
-                // Store in cache
-                self.image_cache.insert(path.clone(), texture);
-                self.image_loading_path = None;
-                self.image_receiver = None;
-                self.image_loading_start = None;
-                ctx.request_repaint();
+        let folders: Vec<PathBuf> = paths.into_iter().filter(|p| p.is_dir()).collect();
+        if folders.is_empty() {
+            self.error_message = Some("Clipboard does not contain a folder path".to_string());
+            return;
+        }
+        for folder in folders {
+            if !self.selected_folders.contains(&folder) {
+                self.selected_folders.push(folder);
             }
         }
+        self.scan_all_folders();
     }
 
-    /// Get elapsed loading time in seconds (for UI display)
-    fn get_loading_elapsed_secs(&self) -> Option<u64> {
-        self.image_loading_start.map(|start| start.elapsed().as_secs())
-    }
-
-    fn sort_files(&mut self) {
-        let order = self.sort_order;
-        match self.sort_column {
-            SortColumn::Name => {
-                self.files.sort_by(|a, b| {
-                    let cmp = a.name.to_lowercase().cmp(&b.name.to_lowercase());
-                    if order == SortOrder::Descending { cmp.reverse() } else { cmp }
-                });
-            }
-            SortColumn::Extension => {
-                self.files.sort_by(|a, b| {
-                    let cmp = a.extension.to_lowercase().cmp(&b.extension.to_lowercase());
-                    if order == SortOrder::Descending { cmp.reverse() } else { cmp }
-                });
-            }
-            SortColumn::Size => {
-                self.files.sort_by(|a, b| {
-                    let cmp = a.file_size.cmp(&b.file_size);
-                    if order == SortOrder::Descending { cmp.reverse() } else { cmp }
-                });
-            }
-            SortColumn::Path => {
-                self.files.sort_by(|a, b| {
-                    let cmp = a.relative_path.to_lowercase().cmp(&b.relative_path.to_lowercase());
-                    if order == SortOrder::Descending { cmp.reverse() } else { cmp }
-                });
-            }
-            SortColumn::Date => {
-                self.files.sort_by(|a, b| {
-                    let cmp = a.modified_timestamp.cmp(&b.modified_timestamp);
-                    if order == SortOrder::Descending { cmp.reverse() } else { cmp }
-                });
+    /// Replace the scanned folders with a freshly generated sample folder (recursive scan)
+    fn scan_sample_data(&mut self) {
+        match sample_data::create_sample_folder() {
+            Ok(folder) => {
+                self.selected_folders = vec![folder];
+                self.added_files.clear();
+                self.recursive = true;
+                self.scan_all_folders();
             }
+            Err(e) => self.error_message = Some(format!("Could not create the sample data: {}", e)),
         }
-        self.apply_filter();
     }
 
-    fn apply_filter(&mut self) {
-        // Clear selections when filter changes (indices would be invalid)
-        self.selected_files.clear();
-
-        // First compute duplicates on ALL files (before filtering)
-        self.compute_duplicates();
-
-        let filter = self.filter_text.to_lowercase();
-
-        // Apply text filter
-        let text_filtered: Vec<FileInfo> = if filter.is_empty() {
-            self.files.clone()
-        } else {
-            self.files
-                .iter()
-                .filter(|f| {
-                    f.name.to_lowercase().contains(&filter)
-                        || f.extension.to_lowercase().contains(&filter)
-                        || f.relative_path.to_lowercase().contains(&filter)
-                        || f.full_name.to_lowercase().contains(&filter)
-                })
-                .cloned()
-                .collect()
-        };
-
-        // Apply duplicates filter if enabled
-        let after_duplicates: Vec<FileInfo> = if self.show_duplicates_only {
-            text_filtered
-                .into_iter()
-                .filter(|f| self.is_duplicate(&f.full_name).is_some())
-                .collect()
-        } else {
-            text_filtered
+    /// Add files from clipboard paths to the listing and rescan
+    fn add_files_from_clipboard(&mut self) {
+        let paths = match clipboard::read_clipboard_paths() {
+            Ok(paths) => paths,
+            Err(e) => {
+                self.error_message = Some(e);
+                return;
+            }
         };
 
-        // Apply today filter if enabled
-        if self.show_today_only {
-            self.filtered_files = after_duplicates
-                .into_iter()
-                .filter(|f| is_today(f.modified_timestamp))
-                .collect();
-        } else {
-            self.filtered_files = after_duplicates;
+        let files: Vec<PathBuf> = paths.into_iter().filter(|p| p.is_file()).collect();
+        if files.is_empty() {
+            self.error_message = Some("Clipboard does not contain any existing file paths".to_string());
+            return;
         }
-    }
-
-    fn compute_duplicates(&mut self) {
-        self.duplicate_counts.clear();
-        // Compute duplicates on ALL files, not just filtered
-        for file in &self.files {
-            *self.duplicate_counts.entry(file.full_name.clone()).or_insert(0) += 1;
+        for file in files {
+            if !self.added_files.contains(&file) {
+                self.added_files.push(file);
+            }
         }
+        self.scan_all_folders();
     }
 
-    fn is_duplicate(&self, full_name: &str) -> Option<usize> {
-        self.duplicate_counts.get(full_name).and_then(|&count| {
-            if count > 1 { Some(count) } else { None }
-        })
-    }
+    /// Show the bookmarks sidebar: click scans a pinned folder, right-click to rename/remove
+    fn show_bookmarks_panel(&mut self, ctx: &egui::Context) {
+        if !self.settings.show_bookmarks {
+            return;
+        }
 
-    /// Get file type icon based on extension
-    fn get_file_type_icon(extension: &str) -> &'static str {
-        match extension.to_lowercase().as_str() {
-            // Documents
-            "txt" | "md" | "rtf" => "📝",
-            "pdf" => "📕",
-            "doc" | "docx" | "odt" => "📘",
-            "xls" | "xlsx" | "ods" => "📗",
-            "ppt" | "pptx" | "odp" => "📙",
+        let mut scan: Option<PathBuf> = None;
+        let mut add_to_scan: Option<PathBuf> = None;
+        let mut remove: Option<usize> = None;
+        let mut move_up: Option<usize> = None;
+        let mut changed = false;
+        egui::SidePanel::left("bookmarks_panel")
+            .resizable(true)
+            .default_width(180.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.strong("Bookmarks");
+                    if ui
+                        .add_enabled(!self.selected_folders.is_empty(), egui::Button::new("📌 Pin").small())
+                        .on_hover_text("Pin the selected folders")
+                        .clicked()
+                    {
+                        for folder in &self.selected_folders {
+                            self.settings.add_bookmark(folder.clone());
+                        }
+                        changed = true;
+                    }
+                    if ui.small_button("➕").on_hover_text("Pin a folder...").clicked() {
+                        if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                            self.settings.add_bookmark(folder);
+                            changed = true;
+                        }
+                    }
+                });
+                ui.separator();
 
-            // Images
-            "jpg" | "jpeg" | "png" | "gif" | "bmp" | "ico" | "svg" | "webp" | "tiff" | "tif" => "🖼",
-            "psd" | "ai" | "sketch" => "🎨",
+                if self.settings.bookmarks.is_empty() {
+                    ui.label(egui::RichText::new("No pinned folders").weak());
+                }
 
-            // Audio
-            "mp3" | "wav" | "flac" | "aac" | "ogg" | "wma" | "m4a" => "🎵",
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (idx, bookmark) in self.settings.bookmarks.iter_mut().enumerate() {
+                        // Inline relabel
+                        if let Some((editing_idx, text)) = &mut self.editing_bookmark {
+                            if *editing_idx == idx {
+                                let response = ui.text_edit_singleline(text);
+                                response.request_focus();
+                                if response.lost_focus() {
+                                    if ui.input(|i| i.key_pressed(egui::Key::Enter)) && !text.trim().is_empty() {
+                                        bookmark.label = text.trim().to_string();
+                                        changed = true;
+                                    }
+                                    self.editing_bookmark = None;
+                                }
+                                continue;
+                            }
+                        }
 
-            // Video
-            "mp4" | "avi" | "mkv" | "mov" | "wmv" | "flv" | "webm" | "m4v" => "🎬",
+                        // No existence check here: stat'ing NAS shares every frame would stall the UI
+                        let scanned = self.selected_folders.contains(&bookmark.path);
+                        let response = ui
+                            .add_enabled(
+                                !self.is_scanning,
+                                egui::Button::selectable(scanned, format!("📁 {}", bookmark.label)),
+                            )
+                            .on_hover_text(bookmark.path.display().to_string());
+                        if response.clicked() {
+                            if ui.input(|i| i.modifiers.command || i.modifiers.shift) {
+                                add_to_scan = Some(bookmark.path.clone());
+                            } else {
+                                scan = Some(bookmark.path.clone());
+                            }
+                        }
+                        response.context_menu(|ui| {
+                            if ui.button("➕ Add to scanned folders").clicked() {
+                                add_to_scan = Some(bookmark.path.clone());
+                                ui.close();
+                            }
+                            if ui.button("✏️ Rename").clicked() {
+                                self.editing_bookmark = Some((idx, bookmark.label.clone()));
+                                ui.close();
+                            }
+                            if idx > 0 && ui.button("⬆ Move up").clicked() {
+                                move_up = Some(idx);
+                                ui.close();
+                            }
+                            if ui.button("🗑 Remove").clicked() {
+                                remove = Some(idx);
+                                ui.close();
+                            }
+                        });
+                    }
+                });
 
-            // Archives
-            "zip" | "rar" | "7z" | "tar" | "gz" | "bz2" | "xz" => "📦",
+                ui.add_space(4.0);
+                ui.label(egui::RichText::new("Ctrl/Shift+click adds to the scanned folders").weak().small());
+            });
 
-            // Code
-            "rs" | "py" | "js" | "ts" | "jsx" | "tsx" | "c" | "cpp" | "h" | "hpp" => "💻",
-            "java" | "kt" | "go" | "rb" | "php" | "swift" | "cs" | "vb" => "💻",
-            "html" | "htm" | "css" | "scss" | "sass" | "less" => "🌐",
-            "sh" | "bash" | "ps1" | "bat" | "cmd" => "⚡",
+        if let Some(idx) = remove {
+            self.settings.bookmarks.remove(idx);
+            changed = true;
+        }
+        if let Some(idx) = move_up {
+            self.settings.bookmarks.swap(idx - 1, idx);
+            changed = true;
+        }
+        if changed {
+            let _ = self.settings.save();
+        }
 
-            // Data
-            "json" | "xml" | "csv" | "sql" | "db" | "sqlite" => "📊",
-            "yaml" | "yml" | "toml" | "ini" | "cfg" | "conf" | "config" => "⚙",
+        if let Some(folder) = scan {
+            self.selected_folders = vec![folder];
+            self.added_files.clear();
+            self.scan_all_folders();
+        } else if let Some(folder) = add_to_scan {
+            if !self.selected_folders.contains(&folder) {
+                self.selected_folders.push(folder);
+                self.scan_all_folders();
+            }
+        }
+    }
 
-            // Executables
-            "exe" | "msi" | "dll" | "so" | "dylib" => "⚙",
+    /// Current working state for autosave
+    fn current_session(&self) -> Session {
+        let mut selected: Vec<usize> = self.selected_files.iter().copied().collect();
+        selected.sort_unstable();
+        Session {
+            folders: self.selected_folders.clone(),
+            added_files: self.added_files.clone(),
+            recursive: self.recursive,
+            include_hidden: self.include_hidden,
+            respect_gitignore: self.respect_gitignore,
+            live_update: self.live_update,
+            filter_text: self.filter_text.clone(),
+            show_duplicates_only: self.show_duplicates_only,
+            show_versions_only: self.show_versions_only,
+            modified_within: self.modified_within,
+            show_cloud_only: self.show_cloud_only,
+            show_orphaned_only: self.show_orphaned_only,
+            hide_reviewed: self.hide_reviewed,
+            show_never_accessed: self.show_never_accessed,
+            show_too_long: self.show_too_long,
+            show_unsafe_names: self.show_unsafe_names,
+            show_empty_only: self.show_empty_only,
+            selected_paths: selected
+                .into_iter()
+                .filter_map(|idx| self.filtered_files.get(idx))
+                .map(|f| f.absolute_path().to_string())
+                .collect(),
+            saved_at: 0,
+        }
+    }
 
-            // Fonts
-            "ttf" | "otf" | "woff" | "woff2" | "eot" => "🔤",
+    /// Save the session every few seconds when it changed (for crash recovery)
+    fn autosave_session(&mut self, ctx: &egui::Context) {
+        // Wake up for the next check even when idle, so the last change gets saved
+        ctx.request_repaint_after(Duration::from_secs(10));
 
-            // Default
-            _ => "📄",
+        // Don't overwrite the crashed session before the user decided about it
+        if self.session_restore.is_some() || self.last_autosave.elapsed() < Duration::from_secs(10) {
+            return;
         }
-    }
+        self.last_autosave = Instant::now();
 
-    fn toggle_sort(&mut self, column: SortColumn) {
-        if self.sort_column == column {
-            // Toggle order if same column
-            self.sort_order = match self.sort_order {
-                SortOrder::Ascending => SortOrder::Descending,
-                SortOrder::Descending => SortOrder::Ascending,
-            };
+        let session = self.current_session();
+        if session == self.last_session {
+            return;
+        }
+        if session.is_empty() {
+            Session::clear();
         } else {
-            // New column, start with ascending
-            self.sort_column = column;
-            self.sort_order = SortOrder::Ascending;
+            let mut saved = session.clone();
+            saved.saved_at = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0);
+            let _ = saved.save();
         }
-        self.sort_files();
+        self.last_session = session;
     }
 
-    fn get_sort_indicator(&self, column: SortColumn) -> &str {
-        if self.sort_column == column {
-            match self.sort_order {
-                SortOrder::Ascending => " ^",
-                SortOrder::Descending => " v",
-            }
-        } else {
-            ""
-        }
+    /// Restore folders, filters and selection from a crashed session
+    fn restore_session(&mut self, session: Session) {
+        self.selected_folders = session.folders;
+        self.added_files = session.added_files;
+        self.recursive = session.recursive;
+        self.include_hidden = session.include_hidden;
+        self.respect_gitignore = session.respect_gitignore;
+        self.live_update = session.live_update;
+        self.filter_text = session.filter_text;
+        self.show_duplicates_only = session.show_duplicates_only;
+        self.show_versions_only = session.show_versions_only;
+        self.modified_within = session.modified_within;
+        self.show_cloud_only = session.show_cloud_only;
+        self.show_orphaned_only = session.show_orphaned_only;
+        self.hide_reviewed = session.hide_reviewed;
+        self.show_never_accessed = session.show_never_accessed;
+        self.show_too_long = session.show_too_long;
+        self.show_unsafe_names = session.show_unsafe_names;
+        self.show_empty_only = session.show_empty_only;
+        self.pending_selection = Some(session.selected_paths.into_iter().collect());
+        self.scan_all_folders();
     }
 
-    fn open_in_explorer(file_path: &str) {
-        // Open file manager and select the file (cross-platform)
-        #[cfg(target_os = "windows")]
-        {
-            let _ = Command::new("explorer")
-                .args(["/select,", file_path])
-                .spawn();
-        }
+    /// Offer to restore the session of a run that did not exit cleanly
+    fn show_session_restore_window(&mut self, ctx: &egui::Context) {
+        let Some(session) = &self.session_restore else {
+            return;
+        };
 
-        #[cfg(target_os = "macos")]
-        {
-            let _ = Command::new("open")
-                .args(["-R", file_path])
-                .spawn();
-        }
+        let mut restore = false;
+        let mut discard = false;
+        egui::Window::new("Restore Session")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "File Lister did not close normally last time (session saved {}).",
+                    format_date(session.saved_at, self.settings.calendar)
+                ));
+                ui.add_space(4.0);
+                for folder in &session.folders {
+                    ui.label(format!("📁 {}", folder.display()));
+                }
+                if !session.added_files.is_empty() {
+                    ui.label(format!("{} individually added file(s)", session.added_files.len()));
+                }
+                if !session.filter_text.is_empty() {
+                    ui.label(format!("Filter: {}", session.filter_text));
+                }
+                if !session.selected_paths.is_empty() {
+                    ui.label(format!("{} selected file(s)", session.selected_paths.len()));
+                }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Restore").clicked() {
+                        restore = true;
+                    }
+                    if ui.button("Start fresh").clicked() {
+                        discard = true;
+                    }
+                });
+            });
 
-        #[cfg(target_os = "linux")]
-        {
-            // Try to open parent folder with default file manager
-            if let Some(parent) = std::path::Path::new(file_path).parent() {
-                let _ = Command::new("xdg-open")
-                    .arg(parent)
-                    .spawn();
+        if restore {
+            if let Some(session) = self.session_restore.take() {
+                self.restore_session(session);
             }
+        } else if discard {
+            self.session_restore = None;
+            Session::clear();
         }
     }
 
-    fn export_csv(&mut self, path: &PathBuf) {
-        // Export filtered files
-        match csv_export::export_to_csv(&self.filtered_files, path) {
-            Ok(_) => {
-                self.status_message = format!("Exported {} files to: {}", self.filtered_files.len(), path.display());
-                self.error_message = None;
-            }
-            Err(e) => {
-                self.error_message = Some(format!("Export failed: {}", e));
+    /// Compare file content across the selected folders in the background
+    fn start_root_comparison(&mut self) {
+        let files = self.files.clone();
+        let roots = self.selected_folders.clone();
+        let use_cache = self.settings.cache_hashes;
+        let (tx, rx) = mpsc::channel();
+        self.comparison_receiver = Some(rx);
+        self.comparison = None;
+        self.status_message = format!("Comparing {} folders (size + SHA-256)...", roots.len());
+
+        thread::spawn(move || {
+            priority::enter_background();
+            let mut cache = if use_cache { HashCache::load() } else { HashCache::default() };
+            let comparison = compare::compare_roots(&files, &roots, &mut cache);
+            if use_cache {
+                // A cache that fails to save only costs re-hashing next time
+                let _ = cache.save();
             }
-        }
+            let _ = tx.send(comparison);
+        });
     }
 
-    fn delete_file(&mut self, file_path: &str) {
-        let path = std::path::Path::new(file_path);
-        match std::fs::remove_file(path) {
-            Ok(_) => {
-                self.status_message = format!("Deleted: {}", path.file_name().unwrap_or_default().to_string_lossy());
-                self.error_message = None;
-                // Re-scan to update the list
-                self.scan_all_folders();
+    /// Check for a finished root comparison
+    fn check_comparison(&mut self) {
+        let Some(receiver) = &self.comparison_receiver else {
+            return;
+        };
+        if let Ok(comparison) = receiver.try_recv() {
+            let mut status = format!("Compared {} folders ({} files hashed", comparison.roots.len(), comparison.hashed);
+            if comparison.cached > 0 {
+                status.push_str(&format!(", {} from checksum cache", comparison.cached));
             }
-            Err(e) => {
-                self.error_message = Some(format!("Delete failed: {}", e));
+            status.push(')');
+            if comparison.errors > 0 {
+                status.push_str(&format!(", {} unreadable", comparison.errors));
             }
+            if comparison.skipped_cloud > 0 {
+                status.push_str(&format!(", {} online-only skipped", comparison.skipped_cloud));
+            }
+            self.notify_finished("Comparison finished", &status);
+            self.status_message = status;
+            self.comparison = Some(comparison);
+            self.comparison_receiver = None;
         }
     }
 
-    fn move_file(&mut self, file_path: &str) {
-        let source = std::path::Path::new(file_path);
-        if let Some(file_name) = source.file_name() {
-            if let Some(dest_folder) = rfd::FileDialog::new()
-                .set_title("Select destination folder")
-                .pick_folder()
+    /// Show the Compare Roots window: per folder, files unique to it vs shared with another folder
+    fn show_comparison_window(&mut self, ctx: &egui::Context) {
+        let Some(comparison) = &self.comparison else {
+            return;
+        };
+
+        let mut open = true;
+        let mut show_unique: Option<usize> = None;
+        let mut export_unique: Option<usize> = None;
+        egui::Window::new("Compare Roots")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label("Files are matched by size + SHA-256 content hash.");
+                ui.add_space(6.0);
+                egui::Grid::new("root_comparison")
+                    .num_columns(5)
+                    .spacing([12.0, 6.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Folder");
+                        ui.strong("Files");
+                        ui.strong("Also elsewhere");
+                        ui.strong("Only here");
+                        ui.strong("");
+                        ui.end_row();
+
+                        for (idx, summary) in comparison.roots.iter().enumerate() {
+                            ui.label(summary.root.display().to_string());
+                            ui.label(summary.total.to_string());
+                            ui.label(summary.shared.to_string());
+                            ui.label(format!(
+                                "{} ({})",
+                                summary.unique.len(),
+                                format_size(summary.unique_size, &self.settings.size_format)
+                            ));
+                            ui.horizontal(|ui| {
+                                ui.add_enabled_ui(!summary.unique.is_empty(), |ui| {
+                                    if ui.button("Show").on_hover_text("Show only these files in the list").clicked() {
+                                        show_unique = Some(idx);
+                                    }
+                                    if ui
+                                        .button("Export...")
+                                        .on_hover_text("Export files missing from the other folders to CSV")
+                                        .clicked()
+                                    {
+                                        export_unique = Some(idx);
+                                    }
+                                });
+                            });
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        let show_unique = show_unique.map(|idx| {
+            let summary = &comparison.roots[idx];
+            (format!("Only in {}", summary.root.display()), summary.unique.iter().cloned().collect())
+        });
+        let export_unique: Option<HashSet<String>> =
+            export_unique.map(|idx| comparison.roots[idx].unique.iter().cloned().collect());
+
+        if let Some(content_filter) = show_unique {
+            self.content_filter = Some(content_filter);
+            self.apply_filter();
+        }
+
+        if let Some(unique) = export_unique {
+            let files: Vec<FileInfo> = self.files
+                .iter()
+                .filter(|f| unique.contains(f.absolute_path()))
+                .cloned()
+                .collect();
+            if let Some(path) = rfd::FileDialog::new()
+                .add_filter("CSV files", &["csv"])
+                .set_file_name("missing_from_backup.csv")
+                .save_file()
             {
-                let dest_path = dest_folder.join(file_name);
-                match std::fs::rename(source, &dest_path) {
+                match csv_export::export_to_csv(&files, &path) {
                     Ok(_) => {
-                        self.status_message = format!("Moved: {} → {}", file_name.to_string_lossy(), dest_folder.display());
+                        self.status_message = format!("Exported {} files to: {}", files.len(), path.display());
                         self.error_message = None;
-                        self.scan_all_folders();
-                    }
-                    Err(_) => {
-                        // If rename fails (cross-device), try copy + delete
-                        if let Err(copy_err) = std::fs::copy(source, &dest_path) {
-                            self.error_message = Some(format!("Move failed: {}", copy_err));
-                        } else if let Err(del_err) = std::fs::remove_file(source) {
-                            self.error_message = Some(format!("Move partial: copied but failed to delete source: {}", del_err));
-                            self.scan_all_folders();
-                        } else {
-                            self.status_message = format!("Moved: {} → {}", file_name.to_string_lossy(), dest_folder.display());
-                            self.error_message = None;
-                            self.scan_all_folders();
-                        }
                     }
+                    Err(e) => self.error_message = Some(format!("Export failed: {}", e)),
                 }
             }
         }
+
+        if !open {
+            self.comparison = None;
+        }
     }
 
-    fn move_selected_files(&mut self) {
-        if self.selected_files.is_empty() {
-            return;
+    /// Remember a finished long operation for an OS notification
+    fn notify_finished(&mut self, title: &str, outcome: &str) {
+        if self.settings.notify_when_unfocused {
+            self.pending_notification = Some((title.to_string(), outcome.to_string()));
         }
+    }
 
-        if let Some(dest_folder) = rfd::FileDialog::new()
-            .set_title("Select destination folder")
-            .pick_folder()
-        {
-            let mut moved_count = 0;
-            let mut failed_count = 0;
-            let mut errors: Vec<String> = Vec::new();
+    /// Notify about a finished operation if the window is in the background; focus it on click
+    fn handle_notifications(&mut self, ctx: &egui::Context) {
+        if self.notification_clicks.1.try_recv().is_ok() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
 
-            let files_to_move: Vec<(String, String)> = self.selected_files
-                .iter()
-                .filter_map(|&idx| {
-                    self.filtered_files.get(idx).map(|f| {
-                        (f.absolute_path.clone(), f.full_name.clone())
-                    })
-                })
-                .collect();
+        // Checked a frame after the operation, so focus changes during blocking operations are seen
+        let Some((title, outcome)) = self.pending_notification.take() else {
+            return;
+        };
+        let focused = ctx.input(|i| i.viewport().focused).unwrap_or(true);
+        if !focused {
+            notification::notify(&title, &outcome, self.notification_clicks.0.clone());
+            // Taskbar flash / dock bounce; clicking it brings the window back
+            ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(
+                egui::UserAttentionType::Informational,
+            ));
+        }
+    }
 
-            for (source_path, file_name) in files_to_move {
-                let source = std::path::Path::new(&source_path);
-                let dest_path = dest_folder.join(&file_name);
+    /// Scan the same folders again, keeping the sort, the filters (the content filter too, it is
+    /// by path), the selection (by path) and the scroll position. Analyses of the old scan
+    /// (similar images, extension check, comparison) are cleared as with any scan.
+    fn refresh(&mut self) {
+        if self.is_scanning || (self.selected_folders.is_empty() && self.added_files.is_empty()) {
+            return;
+        }
+        let selected: HashSet<String> = self
+            .selected_files
+            .iter()
+            .filter_map(|&idx| self.filtered_files.get(idx))
+            .map(|f| f.absolute_path().to_string())
+            .collect();
+        let content_filter = self.content_filter.take();
+        self.scan_all_folders();
+        self.content_filter = content_filter;
+        self.pending_selection = Some(selected);
+        self.pending_scroll = Some(self.table_scroll);
+    }
 
-                let move_result = std::fs::rename(source, &dest_path)
-                    .or_else(|_| {
-                        // Try copy + delete for cross-device moves
-                        std::fs::copy(source, &dest_path)?;
-                        std::fs::remove_file(source)
+    /// F5 (Ctrl+R in two-panel mode, where F5 copies) refreshes the list
+    fn handle_refresh_shortcut(&mut self, ctx: &egui::Context) {
+        if self.editing_index.is_some() || ctx.wants_keyboard_input() {
+            return;
+        }
+        let f5 = self.right_pane.is_none() && ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F5));
+        if f5 || ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::R)) {
+            self.refresh();
+        }
+    }
+
+    /// Check for scan results from background thread
+    fn check_scan_results(&mut self) {
+        if let Some(receiver) = &self.scan_receiver {
+            // Try to receive without blocking
+            if let Ok(result) = receiver.try_recv() {
+                match result {
+                    Ok(ScanOutput { files, retries, source, report }) => {
+                        let cloud_count = files.iter().filter(|f| f.is_cloud_placeholder).count();
+                        let orphaned_count = files.iter().filter(|f| f.owner_orphaned).count();
+                        let mut notes: Vec<String> = Vec::new();
+                        if cloud_count > 0 {
+                            notes.push(format!("{} online-only", cloud_count));
+                        }
+                        if orphaned_count > 0 {
+                            notes.push(format!("{} with orphaned owner", orphaned_count));
+                        }
+                        if retries > 0 {
+                            notes.push(format!("{} network reads retried", retries));
+                        }
+                        match source {
+                            ScanSource::Walker => {}
+                            ScanSource::NtfsIndex => notes.push("NTFS index".to_string()),
+                            ScanSource::NtfsJournal(changes) => notes.push(format!("NTFS journal: {} changes", changes)),
+                        }
+                        if !report.is_empty() {
+                            notes.push(report.summary());
+                        }
+                        self.status_message = if notes.is_empty() {
+                            format!("Scanned: {} files found", files.len())
+                        } else {
+                            format!("Scanned: {} files found ({})", files.len(), notes.join(", "))
+                        };
+                        self.files = files;
+                        self.scan_report = report;
+                        self.stale_paths.clear();
+                        self.sort_files();
+                        self.apply_filter();
+                        if let Some(paths) = self.pending_selection.take() {
+                            self.select_paths(&paths);
+                        }
+                        if self.live_update {
+                            self.folder_watcher =
+                                Some(FolderWatcher::start(&self.selected_folders, self.recursive, self.scan_filter()));
+                        }
+                        self.notify_finished("Scan finished", &self.status_message.clone());
+                        let summary = hooks::summary(HookEvent::Scan, "gui", &self.selected_folders, &self.files, &[]);
+                        self.run_hooks(HookEvent::Scan, summary);
+                        self.start_content_hashing();
+                        self.start_text_counting();
+                        self.start_extension_suggestions();
+                    }
+                    Err(e) => {
+                        self.error_message = Some(format!("Error scanning folder: {}", e));
+                        self.files.clear();
+                        self.folder_rows.clear();
+                        self.filtered_files.clear();
+                        self.notify_finished("Scan failed", &e);
+                    }
+                }
+                self.is_scanning = false;
+                self.scan_receiver = None;
+                self.scan_progress = None;
+            }
+        }
+    }
+
+    /// Select the listed files with these absolute paths
+    fn select_paths(&mut self, paths: &HashSet<String>) {
+        self.selected_files = self.filtered_files
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| paths.contains(f.absolute_path()))
+            .map(|(idx, _)| idx)
+            .collect();
+    }
+
+    /// Apply file changes reported by the folder watcher (live update), keeping the selection
+    fn check_folder_changes(&mut self, ctx: &egui::Context) {
+        let Some(watcher) = &self.folder_watcher else {
+            return;
+        };
+        // Wake up for the next poll even when idle
+        ctx.request_repaint_after(watcher::POLL_INTERVAL);
+        let changes: Vec<_> = watcher.receiver.try_iter().collect();
+        if changes.is_empty() || self.is_scanning {
+            return;
+        }
+
+        let selected: HashSet<String> = self.selected_files
+            .iter()
+            .filter_map(|&idx| self.filtered_files.get(idx))
+            .map(|f| f.absolute_path().to_string())
+            .collect();
+        let (mut added, mut removed, mut modified) = (0, 0, 0);
+        for change in changes {
+            // Modified files are read again
+            let outdated: HashSet<String> = change.removed
+                .iter()
+                .chain(&change.modified)
+                .map(|path| path.to_string_lossy().to_string())
+                .collect();
+            self.files.retain(|f| !outdated.contains(f.absolute_path()));
+            let mut new_files: Vec<FileInfo> = change.added
+                .iter()
+                .chain(&change.modified)
+                .map(|path| file_scanner::file_info(path, &change.root))
+                .collect();
+            file_scanner::tag_source_folder(&mut new_files, &change.folder);
+            if self.settings.resolve_owners {
+                owner::resolve_owners(&mut new_files);
+            }
+            self.files.extend(new_files);
+            added += change.added.len();
+            removed += change.removed.len();
+            modified += change.modified.len();
+        }
+        self.sort_files();
+        self.select_paths(&selected);
+        self.status_message = format!(
+            "Live update: {} added, {} removed, {} changed ({} files)",
+            added, removed, modified, self.files.len()
+        );
+        let mut summary = hooks::summary(HookEvent::Scan, "watch", &self.selected_folders, &self.files, &[]);
+        summary["changes"] = serde_json::json!({ "added": added, "removed": removed, "changed": modified });
+        self.run_hooks(HookEvent::Scan, summary);
+        if added + modified > 0 {
+            self.start_content_hashing();
+            self.start_text_counting();
+        }
+    }
+
+    /// Hash the files that have no checksum yet, if the Hash column is on
+    fn start_content_hashing(&mut self) {
+        self.stop_content_hashing();
+        if !self.settings.show_hashes {
+            return;
+        }
+        let files: Vec<FileInfo> = self.files.iter().filter(|f| f.hash.is_none()).cloned().collect();
+        if files.is_empty() {
+            return;
+        }
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.content_hashing = Some(ContentHashing {
+            total: files.len(),
+            receiver: hashing::spawn_content_hashing(
+                files,
+                self.settings.hash_algorithm,
+                self.settings.cache_hashes,
+                Arc::clone(&cancel),
+            ),
+            cancel,
+            done: 0,
+        });
+    }
+
+    fn stop_content_hashing(&mut self) {
+        if let Some(running) = self.content_hashing.take() {
+            running.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Fill in the checksums hashed so far
+    fn check_content_hashing(&mut self) {
+        let Some(running) = &mut self.content_hashing else {
+            return;
+        };
+        let mut hashed: HashMap<String, Arc<str>> = HashMap::new();
+        let mut finished = None;
+        while let Ok(message) = running.receiver.try_recv() {
+            match message {
+                ContentHashMessage::Hashed(batch) => {
+                    running.done += batch.len();
+                    hashed.extend(batch.into_iter().map(|(path, hash)| (path, hash.into())));
+                }
+                ContentHashMessage::Finished { failed } => finished = Some(failed),
+            }
+        }
+        if !hashed.is_empty() {
+            for file in self.files.iter_mut().chain(self.filtered_files.iter_mut()) {
+                if let Some(hash) = hashed.get(file.absolute_path()) {
+                    file.hash = Some(Arc::clone(hash));
+                }
+            }
+        }
+        if let Some(failed) = finished {
+            let done = running.done;
+            self.content_hashing = None;
+            self.status_message = format!("Hashed {} files ({})", done, self.settings.hash_algorithm.label());
+            if failed > 0 {
+                self.status_message.push_str(&format!(", {} could not be read", failed));
+            }
+        }
+    }
+
+    /// Count the lines / words of text files not counted yet, if the Lines / Words columns are on
+    fn start_text_counting(&mut self) {
+        self.stop_text_counting();
+        if !self.settings.show_text_counts {
+            return;
+        }
+        let files: Vec<FileInfo> = self
+            .files
+            .iter()
+            .filter(|f| f.line_count.is_none() && !f.is_dir && text_stats::is_counted(f.extension()))
+            .cloned()
+            .collect();
+        if files.is_empty() {
+            return;
+        }
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.text_counting = Some(TextCounting {
+            total: files.len(),
+            receiver: text_stats::spawn_text_counting(files, Arc::clone(&cancel)),
+            cancel,
+            done: 0,
+        });
+    }
+
+    fn stop_text_counting(&mut self) {
+        if let Some(running) = self.text_counting.take() {
+            running.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Fill in the line / word counts done so far
+    fn check_text_counting(&mut self) {
+        let Some(running) = &mut self.text_counting else {
+            return;
+        };
+        let mut counted = HashMap::new();
+        let mut finished = None;
+        while let Ok(message) = running.receiver.try_recv() {
+            match message {
+                TextCountMessage::Counted(batch) => {
+                    running.done += batch.len();
+                    counted.extend(batch);
+                }
+                TextCountMessage::Finished { failed } => finished = Some(failed),
+            }
+        }
+        if !counted.is_empty() {
+            for file in self.files.iter_mut().chain(self.filtered_files.iter_mut()) {
+                if let Some(counts) = counted.get(file.absolute_path()) {
+                    file.line_count = Some(counts.lines);
+                    file.word_count = counts.words;
+                }
+            }
+        }
+        if let Some(failed) = finished {
+            let done = running.done;
+            self.text_counting = None;
+            self.status_message = format!("Counted lines of {} text files", done);
+            if failed > 0 {
+                self.status_message.push_str(&format!(", {} could not be read as text", failed));
+            }
+        }
+    }
+
+    /// Options of GUI exports in a format: its columns from the settings, else the standard
+    /// ones plus those of the optional columns shown (on disk, hash, lines and words)
+    fn export_options(&self, format: ExportFormat) -> ExportOptions {
+        if let Some(columns) = self.settings.format_columns.get(format) {
+            return ExportOptions {
+                columns: columns.to_vec(),
+                format,
+                calendar: self.settings.calendar,
+                size_format: self.settings.size_format,
+                ..Default::default()
+            };
+        }
+        let mut columns = ExportColumn::DEFAULT.to_vec();
+        if self.settings.show_disk_usage {
+            columns.insert(3, ExportColumn::SizeOnDisk);
+        }
+        if self.settings.show_hashes {
+            columns.push(ExportColumn::Hash);
+        }
+        if self.settings.show_text_counts {
+            columns.extend([ExportColumn::Lines, ExportColumn::Words]);
+        }
+        ExportOptions {
+            columns,
+            format,
+            calendar: self.settings.calendar,
+            size_format: self.settings.size_format,
+            ..Default::default()
+        }
+    }
+
+    /// Run the hooks of a finished scan or export in the background
+    fn run_hooks(&mut self, event: HookEvent, summary: serde_json::Value) {
+        self.hook_receivers.extend(hooks::spawn(&self.settings.hooks, event, summary));
+    }
+
+    /// Report hooks that failed
+    fn check_hooks(&mut self) {
+        let mut errors = Vec::new();
+        self.hook_receivers.retain(|receiver| match receiver.try_recv() {
+            Ok(failed) => {
+                errors.extend(failed);
+                false
+            }
+            Err(TryRecvError::Empty) => true,
+            Err(TryRecvError::Disconnected) => false,
+        });
+        if !errors.is_empty() {
+            self.error_message = Some(errors.join("\n"));
+        }
+    }
+
+    /// Check for completed background image loads
+    fn check_image_loads(&mut self, ctx: &egui::Context) {
+        // Check for timeout (10 seconds for video thumbnails)
+        if let Some(start_time) = self.image_loading_start {
+            if start_time.elapsed() > Duration::from_secs(10) {
+                // Timeout - clear loading state (a late result is still cached when it arrives)
+                self.image_loading_path = None;
+                self.image_loading_start = None;
+            }
+        }
+
+        let Some(service) = &self.preview_service else {
+            return;
+        };
+
+        // Try to receive without blocking
+        while let Ok((path, preview)) = service.receiver.try_recv() {
+            if let Some(data) = preview {
+                self.preview_failed.remove(&path);
+                let size = [data.width, data.height];
+                let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &data.pixels);
+                let texture = ctx.load_texture(format!("preview_{}", path), color_image, PREVIEW_TEXTURE_OPTIONS);
+
+                // Store in cache
+                self.image_cache.insert(path.clone(), texture);
+            } else {
+                // Don't retry on every hover frame
+                self.preview_failed.insert(path.clone());
+            }
+
+            if self.image_loading_path.as_ref() == Some(&path) {
+                self.image_loading_path = None;
+                self.image_loading_start = None;
+            }
+            ctx.request_repaint();
+        }
+    }
+
+    /// On-screen size for a preview texture: one texel per pixel, shrunk to fit the preview size and the window
+    fn preview_display_size(ctx: &egui::Context, texture_size: [usize; 2], max_size: f32) -> egui::Vec2 {
+        let natural = egui::vec2(texture_size[0] as f32, texture_size[1] as f32) / ctx.pixels_per_point();
+        let window = ctx.content_rect().size() * 0.9;
+        let limit = egui::vec2(max_size.min(window.x), max_size.min(window.y));
+        let scale = (limit.x / natural.x).min(limit.y / natural.y).min(1.0);
+        natural * scale
+    }
+
+    /// Get elapsed loading time in seconds (for UI display)
+    fn get_loading_elapsed_secs(&self) -> Option<u64> {
+        self.image_loading_start.map(|start| start.elapsed().as_secs())
+    }
+
+    /// Remember the window placement for the current monitor and react to DPI changes
+    fn track_window_placement(&mut self, ctx: &egui::Context) {
+        let (outer_rect, inner_rect, monitor_size, native_ppp) = ctx.input(|i| {
+            let viewport = i.viewport();
+            (viewport.outer_rect, viewport.inner_rect, viewport.monitor_size, viewport.native_pixels_per_point)
+        });
+
+        if let (Some(outer), Some(inner), Some(monitor)) = (outer_rect, inner_rect, monitor_size) {
+            // Key monitors by physical resolution so a 4K and a 1080p screen at different scales differ
+            let scale = native_ppp.unwrap_or(1.0);
+            let placement = WindowPlacement {
+                monitor: format!("{}x{}", (monitor.x * scale).round() as u32, (monitor.y * scale).round() as u32),
+                x: outer.min.x,
+                y: outer.min.y,
+                width: inner.width(),
+                height: inner.height(),
+            };
+            if self.settings.last_placement() != Some(&placement) {
+                self.settings.remember_placement(placement);
+            }
+        }
+
+        // Preview textures are rendered for a specific pixel density - regenerate them after a DPI change
+        let pixels_per_point = ctx.pixels_per_point();
+        if let Some(last) = self.last_pixels_per_point {
+            if (last - pixels_per_point).abs() > f32::EPSILON {
+                self.image_cache.clear();
+            }
+        }
+        self.last_pixels_per_point = Some(pixels_per_point);
+    }
+
+    /// Apply the always-on-top setting to the window and persist it
+    fn set_always_on_top(&mut self, ctx: &egui::Context, always_on_top: bool) {
+        self.settings.always_on_top = always_on_top;
+        let level = if always_on_top {
+            egui::WindowLevel::AlwaysOnTop
+        } else {
+            egui::WindowLevel::Normal
+        };
+        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(level));
+        if let Err(e) = self.settings.save() {
+            self.error_message = Some(e);
+        }
+    }
+
+    /// Turn two-panel mode on or off
+    fn set_dual_pane(&mut self, enabled: bool) {
+        self.settings.dual_pane = enabled;
+        self.right_pane = enabled.then(|| FilePane::new(self.settings.right_pane_folder.clone(), self.allowed_roots()));
+        self.active_pane = ActivePane::Left;
+        let _ = self.settings.save();
+    }
+
+    /// F5 copies and F6 moves the active pane's selection into the other pane's folder
+    fn handle_pane_shortcuts(&mut self, ctx: &egui::Context) {
+        if self.right_pane.is_none() || self.editing_index.is_some() || ctx.wants_keyboard_input() {
+            return;
+        }
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F5)) {
+            self.transfer_between_panes(TransferMode::Copy);
+        } else if !self.is_read_only() && ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F6)) {
+            self.transfer_between_panes(TransferMode::Move);
+        }
+    }
+
+    /// Copy or move the active pane's selected files into the other pane's folder
+    fn transfer_between_panes(&mut self, mode: TransferMode) {
+        let Some(right_pane) = &self.right_pane else {
+            return;
+        };
+
+        let (files, dest_folder) = match self.active_pane {
+            ActivePane::Left => {
+                let mut indices: Vec<usize> = self.selected_files.iter().copied().collect();
+                indices.sort_unstable();
+                let files: Vec<(String, String)> = indices
+                    .into_iter()
+                    .filter_map(|idx| self.filtered_files.get(idx))
+                    .map(|f| (f.absolute_path().to_string(), f.full_name().to_string()))
+                    .collect();
+                (files, right_pane.folder.clone())
+            }
+            // Files from the right pane go to the first folder of the main list
+            ActivePane::Right => (right_pane.selected_files(), self.selected_folders.first().cloned()),
+        };
+
+        let Some(dest_folder) = dest_folder else {
+            self.error_message = Some("The other pane has no folder".to_string());
+            return;
+        };
+        if files.is_empty() {
+            self.status_message = format!("No files selected in the {} pane", self.active_pane.label().to_lowercase());
+            return;
+        }
+
+        // Both panes are rescanned when the job finishes
+        self.transfer_files(&files, &dest_folder, mode);
+    }
+
+    fn sort_files(&mut self) {
+        let keys = self.sort_keys();
+        columns::sort(&mut self.files, &keys);
+        self.refresh_folder_rows();
+        self.apply_filter();
+    }
+
+    /// Recompute the folder rows from the scanned files, in the current sort order
+    fn refresh_folder_rows(&mut self) {
+        self.folder_rows.clear();
+        self.folder_mixes.clear();
+        if self.settings.show_folders {
+            self.folder_rows = file_scanner::folder_rows(&self.files);
+            self.folder_mixes = content_mix::folder_mixes(&self.folder_rows, &self.files);
+            let keys = self.sort_keys();
+            columns::sort(&mut self.folder_rows, &keys);
+        }
+    }
+
+    fn apply_filter(&mut self) {
+        // Clear selections when filter changes (indices would be invalid)
+        self.selected_files.clear();
+
+        // First compute duplicates on ALL files (before filtering)
+        self.compute_duplicates();
+        self.versions = if self.show_versions_only { versions::group(&self.files) } else { HashMap::new() };
+        self.hard_links = file_scanner::hard_link_groups(&self.files);
+
+        // Case-insensitive on Unicode-normalized text, optionally ignoring accents
+        let fold_accents = self.settings.fold_accents;
+        let transliterate = self.settings.transliterate_search;
+        let filter = text_match::fold(&self.filter_text, fold_accents);
+
+        // Apply text filter (folder rows go first and through the same filters)
+        let rows = self.folder_rows.iter().chain(&self.files);
+        let mut text_filtered: Vec<FileInfo> = if filter.is_empty() {
+            rows.cloned().collect()
+        } else {
+            rows.filter(|f| columns::matches_filter(f, &filter, fold_accents, transliterate))
+                .cloned()
+                .collect()
+        };
+        // Empty folders (recursive scans) are listed by the "Empty" filter only
+        let mut empty_folders: Vec<FileInfo> = self
+            .scan_report
+            .empty_folders
+            .iter()
+            .filter(|f| filter.is_empty() || columns::matches_filter(f, &filter, fold_accents, transliterate))
+            .cloned()
+            .collect();
+
+        self.filter_counts = self.count_filter_matches(&text_filtered);
+        self.filter_counts.empty += empty_folders.len();
+        if self.show_empty_only {
+            columns::sort(&mut empty_folders, &self.sort_keys());
+            text_filtered.splice(0..0, empty_folders);
+        }
+
+        // Apply duplicates filter if enabled
+        let after_duplicates: Vec<FileInfo> = if self.show_duplicates_only {
+            text_filtered
+                .into_iter()
+                .filter(|f| !f.is_dir && self.is_listed_duplicate(f))
+                .collect()
+        } else {
+            text_filtered
+        };
+
+        // Apply versions filter if enabled
+        let after_versions: Vec<FileInfo> = if self.show_versions_only {
+            after_duplicates
+                .into_iter()
+                .filter(|f| self.versions.contains_key(f.absolute_path()))
+                .collect()
+        } else {
+            after_duplicates
+        };
+
+        // Apply the today / yesterday / last 7 days filter if enabled
+        let after_today: Vec<FileInfo> = if let Some(range) = self.modified_within {
+            let (start, end) = range.bounds();
+            after_versions
+                .into_iter()
+                .filter(|f| (start..end).contains(&f.modified_timestamp))
+                .collect()
+        } else {
+            after_versions
+        };
+
+        // Apply online-only (cloud placeholder), orphaned owner, reviewed, never-accessed, length and unsafe name filters if enabled
+        let (name_limit, path_limit) = (self.settings.name_length_limit, self.settings.path_length_limit);
+        self.filtered_files = after_today
+            .into_iter()
+            .filter(|f| !self.show_cloud_only || f.is_cloud_placeholder)
+            .filter(|f| !self.show_orphaned_only || f.owner_orphaned)
+            .filter(|f| !self.hide_reviewed || !self.reviewed.contains(f))
+            .filter(|f| !self.show_never_accessed || access_times::never_accessed(f))
+            .filter(|f| !self.show_too_long || name_audit::is_too_long(f, name_limit, path_limit))
+            .filter(|f| !self.show_unsafe_names || name_audit::is_unsafe_name(f.full_name()))
+            .filter(|f| !self.show_empty_only || (f.file_size == 0 && (!f.is_dir || f.item_count == 0)))
+            .filter(|f| match &self.extension_check {
+                Some(check) => check.mismatches.contains_key(f.absolute_path()),
+                None => true,
+            })
+            .filter(|f| match &self.stream_check {
+                Some(check) => check.streams.contains_key(f.absolute_path()),
+                None => true,
+            })
+            .filter(|f| match &self.content_filter {
+                Some((_, paths)) => paths.contains(f.absolute_path()),
+                None => true,
+            })
+            .collect();
+
+        // Keep similar images or equal files next to each other (stable, so the sort order holds within a group)
+        let mut files = std::mem::take(&mut self.filtered_files);
+        if let Some(groups) = self.duplicate_groups().filter(|_| self.show_duplicates_only) {
+            files.sort_by_key(|f| groups.get(f.absolute_path()).map(|&(group, _)| group));
+        }
+        self.filtered_files = files;
+        // Versions of a document together, the one to keep first
+        if self.show_versions_only {
+            let versions = &self.versions;
+            self.filtered_files
+                .sort_by_key(|f| versions.get(f.absolute_path()).map(|v| (v.group, !v.latest)));
+        }
+
+        self.group_sidecars();
+        self.listed_usage = file_scanner::disk_usage(&self.filtered_files);
+        if self.activity.is_some() {
+            self.activity = Some(Activity::count(&self.filtered_files, self.settings.activity_utc_offset));
+        }
+    }
+
+    /// Kept by "Show duplicates only": in a group of similar images or equal content if one of
+    /// those views is on, else sharing its name with another file
+    fn is_listed_duplicate(&self, file: &FileInfo) -> bool {
+        match (&self.similar_images, &self.content_duplicates) {
+            (Some(similar), _) => similar.groups.contains_key(file.absolute_path()),
+            (None, Some(content)) => content.groups.contains_key(file.absolute_path()),
+            (None, None) => self.is_duplicate(file).is_some(),
+        }
+    }
+
+    /// Badge counts: the files each toggle would keep on its own out of `candidates`
+    /// (the files matching the filter text); one pass, run with every filter change
+    fn count_filter_matches(&self, candidates: &[FileInfo]) -> FilterCounts {
+        let (name_limit, path_limit) = (self.settings.name_length_limit, self.settings.path_length_limit);
+        let mismatches = self
+            .extension_check
+            .as_ref()
+            .filter(|check| check.receiver.is_none())
+            .map(|check| &check.mismatches);
+        let streams = self
+            .stream_check
+            .as_ref()
+            .filter(|check| check.receiver.is_none())
+            .map(|check| &check.streams);
+        let content_paths = self.content_filter.as_ref().map(|(_, paths)| paths);
+        let mut counts = FilterCounts {
+            versions: self.show_versions_only.then_some(0),
+            wrong_extension: mismatches.map(|_| 0),
+            streams: streams.map(|_| 0),
+            ..Default::default()
+        };
+        let recent_bounds = RecentDays::ALL.map(RecentDays::bounds);
+        let files = candidates
+            .iter()
+            .filter(|f| !f.is_dir)
+            .filter(|f| content_paths.is_none_or(|paths| paths.contains(f.absolute_path())));
+        for file in files {
+            let path = file.absolute_path();
+            counts.text += 1;
+            counts.duplicates += usize::from(self.is_listed_duplicate(file));
+            if let Some(versions) = &mut counts.versions {
+                *versions += usize::from(self.versions.contains_key(path));
+            }
+            for (count, (start, end)) in counts.recent.iter_mut().zip(recent_bounds) {
+                *count += usize::from((start..end).contains(&file.modified_timestamp));
+            }
+            counts.cloud += usize::from(file.is_cloud_placeholder);
+            counts.reviewed += usize::from(self.reviewed.contains(file));
+            counts.never_accessed += usize::from(access_times::never_accessed(file));
+            counts.too_long += usize::from(name_audit::is_too_long(file, name_limit, path_limit));
+            counts.unsafe_names += usize::from(name_audit::is_unsafe_name(file.full_name()));
+            counts.empty += usize::from(file.file_size == 0);
+            if let (Some(count), Some(mismatches)) = (&mut counts.wrong_extension, mismatches) {
+                *count += usize::from(mismatches.contains_key(path));
+            }
+            if let (Some(count), Some(streams)) = (&mut counts.streams, streams) {
+                *count += usize::from(streams.contains_key(path));
+            }
+            counts.orphaned += usize::from(file.owner_orphaned);
+        }
+        counts
+    }
+
+    /// Why nothing is listed: the active filters with the files each keeps on its own
+    fn empty_list_explanation(&self) -> Option<String> {
+        if !self.filtered_files.is_empty() || self.files.is_empty() {
+            return None;
+        }
+        let counts = &self.filter_counts;
+        let mut active = Vec::new();
+        if !self.filter_text.is_empty() || self.content_filter.is_some() {
+            active.push(format!("filter text ({})", counts.text));
+        }
+        let toggles = [
+            (self.show_duplicates_only, "duplicates only", Some(counts.duplicates)),
+            (self.show_versions_only, "versions", counts.versions),
+            (self.show_cloud_only, "online-only", Some(counts.cloud)),
+            (self.hide_reviewed, "hide reviewed", Some(counts.text - counts.reviewed)),
+            (self.show_never_accessed, "never accessed", Some(counts.never_accessed)),
+            (self.show_too_long, "too long", Some(counts.too_long)),
+            (self.show_unsafe_names, "unsafe names", Some(counts.unsafe_names)),
+            (self.show_empty_only, "empty", Some(counts.empty)),
+            (self.extension_check.is_some(), "wrong extension", counts.wrong_extension),
+            (self.stream_check.is_some(), "streams", counts.streams),
+            (self.show_orphaned_only, "orphaned owner", Some(counts.orphaned)),
+        ];
+        if let Some(range) = self.modified_within {
+            let idx = RecentDays::ALL.iter().position(|r| *r == range).unwrap_or(0);
+            active.push(counted(&range.label().to_lowercase(), Some(counts.recent[idx])));
+        }
+        for (on, label, count) in toggles {
+            if on {
+                active.push(counted(label, count));
+            }
+        }
+        if active.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "No files match all active filters. Each keeps on its own: {}",
+            active.join(", ")
+        ))
+    }
+
+    /// Collapse companion files (RAW+JPEG, video+subtitles) into their primary file's row;
+    /// companions of expanded groups are listed right below the primary
+    fn group_sidecars(&mut self) {
+        self.sidecars.clear();
+        self.sidecar_companions.clear();
+        if !self.settings.group_sidecars {
+            return;
+        }
+
+        let groups = sidecars::group(&self.filtered_files);
+        let companions: HashSet<usize> = groups.values().flatten().copied().collect();
+        let files = std::mem::take(&mut self.filtered_files);
+        for (idx, file) in files.iter().enumerate() {
+            if companions.contains(&idx) {
+                continue;
+            }
+            self.filtered_files.push(file.clone());
+            if let Some(members) = groups.get(&idx) {
+                let members: Vec<FileInfo> = members.iter().map(|&i| files[i].clone()).collect();
+                self.sidecar_companions.extend(members.iter().map(|m| m.absolute_path().to_string()));
+                if self.expanded_sidecars.contains(file.absolute_path()) {
+                    self.filtered_files.extend(members.iter().cloned());
+                }
+                self.sidecars.insert(file.absolute_path().to_string(), members);
+            }
+        }
+    }
+
+    /// Expand or collapse the companions below a grouped row, keeping the selection
+    fn toggle_sidecars(&mut self, idx: usize) {
+        let Some(path) = self.filtered_files.get(idx).map(|f| f.absolute_path().to_string()) else {
+            return;
+        };
+        let Some(members) = self.sidecars.get(&path).cloned() else {
+            return;
+        };
+        let count = members.len();
+
+        let selected: Vec<usize> = self.selected_files.drain().collect();
+        if self.expanded_sidecars.remove(&path) {
+            self.filtered_files.drain(idx + 1..idx + 1 + count);
+            self.selected_files = selected
+                .into_iter()
+                .filter(|&i| i <= idx || i > idx + count)
+                .map(|i| if i > idx { i - count } else { i })
+                .collect();
+        } else {
+            self.expanded_sidecars.insert(path);
+            self.filtered_files.splice(idx + 1..idx + 1, members);
+            self.selected_files = selected.into_iter().map(|i| if i > idx { i + count } else { i }).collect();
+        }
+    }
+
+    /// Add the companions of grouped files (if move/delete should take them along)
+    fn with_sidecars(&self, files: Vec<(String, String)>) -> Vec<(String, String)> {
+        if !self.settings.sidecars_follow {
+            return files;
+        }
+        let mut seen: HashSet<String> = files.iter().map(|(path, _)| path.clone()).collect();
+        let mut result = files.clone();
+        for (path, _) in &files {
+            for companion in self.sidecars.get(path).into_iter().flatten() {
+                if seen.insert(companion.absolute_path().to_string()) {
+                    result.push((companion.absolute_path().to_string(), companion.full_name().to_string()));
+                }
+            }
+        }
+        result
+    }
+
+    /// Mark or unmark the selected files (all listed files if none are selected) as reviewed
+    fn mark_reviewed(&mut self, reviewed: bool) {
+        let files: Vec<FileInfo> = if self.selected_files.is_empty() {
+            self.listed_files()
+        } else {
+            self.selected_files.iter().filter_map(|&idx| self.filtered_files.get(idx).cloned()).collect()
+        };
+        for file in &files {
+            // Keep the "Hide reviewed" badge current without refiltering (which clears the selection)
+            if self.reviewed.contains(file) != reviewed {
+                if reviewed {
+                    self.filter_counts.reviewed += 1;
+                } else {
+                    self.filter_counts.reviewed = self.filter_counts.reviewed.saturating_sub(1);
+                }
+            }
+            if reviewed {
+                self.reviewed.mark(file);
+            } else {
+                self.reviewed.unmark(file);
+            }
+        }
+        self.save_reviewed();
+        self.status_message = if reviewed {
+            format!("Marked {} files as reviewed", files.len())
+        } else {
+            format!("Unmarked {} files", files.len())
+        };
+    }
+
+    /// Collapsible list of the folders and files the scan could not read
+    fn show_scan_report(&mut self, ui: &mut egui::Ui) {
+        if self.scan_report.is_empty() {
+            return;
+        }
+        let title = egui::RichText::new(format!("⚠ {}", self.scan_report.summary())).color(egui::Color32::from_rgb(255, 140, 0));
+        egui::CollapsingHeader::new(title).id_salt("scan_report").show(ui, |ui| {
+            if ui
+                .small_button("📋 Copy list")
+                .on_hover_text("Copy the paths and errors, one per line")
+                .clicked()
+            {
+                let text: Vec<String> = self
+                    .scan_report
+                    .errors
+                    .iter()
+                    .map(|e| format!("{}\t{}", e.path.display(), e.error))
+                    .collect();
+                ui.ctx().copy_text(text.join("\n"));
+            }
+            egui::ScrollArea::vertical().id_salt("scan_report_list").max_height(160.0).show(ui, |ui| {
+                egui::Grid::new("scan_report_grid").striped(true).show(ui, |ui| {
+                    for error in &self.scan_report.errors {
+                        let icon = if error.is_folder { "📁" } else { "📄" };
+                        ui.label(format!("{} {}", icon, error.path.display()));
+                        ui.weak(&error.error);
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+        ui.add_space(5.0);
+    }
+
+    /// Banner above the table: each scanned folder's README excerpt and the user's own note
+    fn show_folder_notes(&mut self, ui: &mut egui::Ui) {
+        let shown = self.folder_readmes.iter().any(|(folder, readme)| readme.is_some() || self.folder_notes.get(folder).is_some());
+        if !shown && self.editing_folder_note.is_none() {
+            // Nothing to show: only the way to add a note
+            if !self.folder_readmes.is_empty() {
+                ui.horizontal(|ui| {
+                    for (folder, _) in &self.folder_readmes {
+                        let name = folder.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| folder.display().to_string());
+                        if ui
+                            .small_button(format!("📝 Add note to {}", name))
+                            .on_hover_text("Note what this folder is; it is shown whenever the folder is listed")
+                            .clicked()
+                        {
+                            self.editing_folder_note = Some((folder.clone(), String::new()));
+                        }
+                    }
+                });
+                ui.add_space(5.0);
+            }
+            return;
+        }
+
+        let mut edit: Option<PathBuf> = None;
+        let mut save = false;
+        let mut cancel = false;
+        egui::CollapsingHeader::new("📝 Folder notes").default_open(true).show(ui, |ui| {
+            egui::ScrollArea::vertical().id_salt("folder_notes").max_height(160.0).show(ui, |ui| {
+                for (folder, readme) in &self.folder_readmes {
+                    ui.horizontal(|ui| {
+                        ui.strong(folder.display().to_string());
+                        let label = if self.folder_notes.get(folder).is_some() { "✏ Edit note" } else { "📝 Add note" };
+                        if self.editing_folder_note.is_none() && ui.small_button(label).clicked() {
+                            edit = Some(folder.clone());
+                        }
+                    });
+                    match &mut self.editing_folder_note {
+                        Some((editing, text)) if editing == folder => {
+                            ui.add(
+                                egui::TextEdit::multiline(text)
+                                    .hint_text("What is this folder? (source, date, who to ask...)")
+                                    .desired_rows(3)
+                                    .desired_width(f32::INFINITY),
+                            );
+                            ui.horizontal(|ui| {
+                                save = ui.button("Save").clicked();
+                                cancel = ui.button("Cancel").clicked();
+                            });
+                        }
+                        _ => {
+                            if let Some(note) = self.folder_notes.get(folder) {
+                                ui.label(egui::RichText::new(note).color(egui::Color32::from_rgb(90, 150, 230)));
+                            }
+                        }
+                    }
+                    if let Some(readme) = readme {
+                        ui.horizontal(|ui| {
+                            ui.weak(&readme.file_name);
+                            if ui.small_button("Open").clicked() {
+                                if let Err(e) = open::that(&readme.path) {
+                                    self.error_message = Some(format!("Failed to open {}: {}", readme.file_name, e));
+                                }
+                            }
+                        });
+                        for line in &readme.lines {
+                            ui.label(egui::RichText::new(line).italics());
+                        }
+                        if readme.truncated {
+                            ui.weak("…");
+                        }
+                    }
+                    ui.add_space(4.0);
+                }
+            });
+        });
+        ui.add_space(5.0);
+
+        if let Some(folder) = edit {
+            let text = self.folder_notes.get(&folder).unwrap_or_default().to_string();
+            self.editing_folder_note = Some((folder, text));
+        }
+        if save {
+            if let Some((folder, text)) = self.editing_folder_note.take() {
+                self.folder_notes.set(&folder, &text);
+                if let Err(e) = self.folder_notes.save() {
+                    self.error_message = Some(e);
+                }
+            }
+        }
+        if cancel {
+            self.editing_folder_note = None;
+        }
+    }
+
+    /// Persist review marks and refresh the list if reviewed files are hidden
+    fn save_reviewed(&mut self) {
+        if let Err(e) = self.reviewed.save() {
+            self.error_message = Some(e);
+        }
+        if self.hide_reviewed {
+            self.apply_filter();
+        }
+    }
+
+    /// Switch the duplicates view to visually similar images, hashing images in the background
+    fn start_similar_images(&mut self) {
+        let receiver = similarity::spawn_hashing(self.files.clone(), self.settings.similar_image_hash, self.settings.cache_hashes);
+        self.similar_images = Some(SimilarImages {
+            hashes: Vec::new(),
+            groups: HashMap::new(),
+            receiver: Some(receiver),
+            progress: (0, 0),
+        });
+        self.status_message = "Computing perceptual hashes of images...".to_string();
+    }
+
+    /// Turn on the "Wrong extension" filter, reading the file headers in the background
+    fn start_extension_check(&mut self) {
+        self.extension_check = Some(ExtensionCheck {
+            mismatches: HashMap::new(),
+            receiver: Some(extensions::spawn_check(self.files.clone())),
+            progress: (0, 0),
+        });
+        self.status_message = "Checking extensions against file contents...".to_string();
+        self.apply_filter();
+    }
+
+    /// Check extension check progress
+    fn check_extension_check(&mut self) {
+        let Some(check) = &mut self.extension_check else {
+            return;
+        };
+        let Some(receiver) = &check.receiver else {
+            return;
+        };
+        let mut finished = None;
+        while let Ok(message) = receiver.try_recv() {
+            match message {
+                CheckMessage::Progress { done, total } => check.progress = (done, total),
+                CheckMessage::Finished(mismatches) => finished = Some(mismatches),
+            }
+        }
+        if let Some(mismatches) = finished {
+            check.mismatches = mismatches.into_iter().collect();
+            check.receiver = None;
+            let status = format!("{} files have an extension that doesn't match their content", check.mismatches.len());
+            self.notify_finished("Extension check finished", &status);
+            self.status_message = status;
+            self.apply_filter();
+        }
+    }
+
+    /// Detect the type of files without extension in the background, for "(png?)" hints
+    fn start_extension_suggestions(&mut self) {
+        let files: Vec<FileInfo> =
+            self.files.iter().filter(|f| !f.is_dir && f.extension().is_empty()).cloned().collect();
+        self.suggestion_receiver = (!files.is_empty()).then(|| extensions::spawn_suggestions(files));
+    }
+
+    /// Pick up the detected types of files without extension
+    fn check_extension_suggestions(&mut self) {
+        let Some(receiver) = &self.suggestion_receiver else {
+            return;
+        };
+        if let Ok(suggestions) = receiver.try_recv() {
+            self.extension_suggestions = suggestions.into_iter().collect();
+            self.suggestion_receiver = None;
+        }
+    }
+
+    /// Turn on the "Streams" filter, listing alternate data streams in the background
+    fn start_stream_check(&mut self) {
+        self.stream_check = Some(StreamCheck {
+            streams: HashMap::new(),
+            receiver: Some(streams::spawn_stream_scan(self.files.clone())),
+            progress: (0, 0),
+        });
+        self.status_message = "Listing alternate data streams...".to_string();
+        self.apply_filter();
+    }
+
+    /// Check stream listing progress
+    fn check_stream_check(&mut self) {
+        let Some(check) = &mut self.stream_check else {
+            return;
+        };
+        let Some(receiver) = &check.receiver else {
+            return;
+        };
+        let mut finished = None;
+        while let Ok(message) = receiver.try_recv() {
+            match message {
+                StreamMessage::Progress { done, total } => check.progress = (done, total),
+                StreamMessage::Finished(found, failed) => finished = Some((found, failed)),
+            }
+        }
+        if let Some((found, failed)) = finished {
+            check.streams = found.into_iter().collect();
+            check.receiver = None;
+            let mut status = format!("{} files have alternate data streams", check.streams.len());
+            if failed > 0 {
+                status.push_str(&format!(" ({} could not be read)", failed));
+            }
+            self.notify_finished("Stream listing finished", &status);
+            self.status_message = status;
+            self.apply_filter();
+        }
+    }
+
+    /// Read the permissions of the scanned folders and the folders below them in the background
+    fn start_permission_audit(&mut self) {
+        let folders = permissions::audited_folders(&self.selected_folders, &self.files);
+        self.status_message = format!("Reading the permissions of {} folders...", folders.len());
+        self.permission_audit = Some(PermissionAudit {
+            progress: (0, folders.len()),
+            receiver: Some(permissions::spawn_audit(folders)),
+            folders: Vec::new(),
+            flagged_only: false,
+        });
+    }
+
+    /// Check permissions audit progress
+    fn check_permission_audit(&mut self) {
+        let Some(audit) = &mut self.permission_audit else {
+            return;
+        };
+        let Some(receiver) = &audit.receiver else {
+            return;
+        };
+        let mut finished = None;
+        while let Ok(message) = receiver.try_recv() {
+            match message {
+                AuditMessage::Progress { done, total } => audit.progress = (done, total),
+                AuditMessage::Finished(folders) => finished = Some(folders),
+            }
+        }
+        if let Some(folders) = finished {
+            let flagged = folders.iter().filter(|f| f.is_flagged()).count();
+            let status = format!("Permissions of {} folders read, {} flagged", folders.len(), flagged);
+            audit.folders = folders;
+            audit.receiver = None;
+            self.notify_finished("Permissions audit finished", &status);
+            self.status_message = status;
+        }
+    }
+
+    /// Check image hashing progress
+    fn check_similar_images(&mut self) {
+        let Some(similar) = &mut self.similar_images else {
+            return;
+        };
+        let Some(receiver) = &similar.receiver else {
+            return;
+        };
+        let mut finished = None;
+        while let Ok(message) = receiver.try_recv() {
+            match message {
+                HashingMessage::Progress { done, total } => similar.progress = (done, total),
+                HashingMessage::Finished(hashes) => finished = Some(hashes),
+            }
+        }
+        if let Some(hashes) = finished {
+            similar.groups = similarity::group_similar(&hashes, self.settings.similar_image_distance);
+            similar.hashes = hashes;
+            similar.receiver = None;
+            let grouped = similar.groups.len();
+            let status = format!("Hashed {} images: {} in groups of similar images", similar.hashes.len(), grouped);
+            self.notify_finished("Similar images found", &status);
+            self.status_message = status;
+            self.apply_filter();
+        }
+    }
+
+    /// Groups of the duplicates view when it groups by similarity or content (not by name)
+    fn duplicate_groups(&self) -> Option<&HashMap<String, (usize, usize)>> {
+        match (&self.similar_images, &self.content_duplicates) {
+            (Some(similar), _) => Some(&similar.groups),
+            (None, Some(content)) => Some(&content.groups),
+            (None, None) => None,
+        }
+    }
+
+    /// Switch the duplicates view to equal content: files of the same size are hashed in the background
+    fn start_content_duplicates(&mut self) {
+        self.stop_content_duplicates();
+        let candidates = hashing::same_size_files(&self.files);
+        let sizes = candidates.iter().map(|f| (f.absolute_path().to_string(), f.file_size)).collect();
+        let hashing = (!candidates.is_empty()).then(|| {
+            let cancel = Arc::new(AtomicBool::new(false));
+            ContentHashing {
+                total: candidates.len(),
+                receiver: hashing::spawn_content_hashing(
+                    candidates,
+                    HashAlgorithm::Sha256,
+                    self.settings.cache_hashes,
+                    Arc::clone(&cancel),
+                ),
+                cancel,
+                done: 0,
+            }
+        });
+        self.status_message = match &hashing {
+            Some(running) => format!("Hashing {} files of equal size...", running.total),
+            None => "No files share their size: no duplicates by content".to_string(),
+        };
+        self.content_duplicates = Some(ContentDuplicates {
+            sizes,
+            hashed: Vec::new(),
+            groups: HashMap::new(),
+            hashing,
+        });
+        self.apply_filter();
+    }
+
+    fn stop_content_duplicates(&mut self) {
+        if let Some(running) = self.content_duplicates.take().and_then(|content| content.hashing) {
+            running.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Check content hashing progress; group the files when it finishes
+    fn check_content_duplicates(&mut self) {
+        let Some(content) = &mut self.content_duplicates else {
+            return;
+        };
+        let Some(running) = &mut content.hashing else {
+            return;
+        };
+        let mut finished = None;
+        while let Ok(message) = running.receiver.try_recv() {
+            match message {
+                ContentHashMessage::Hashed(batch) => {
+                    running.done += batch.len();
+                    for (path, hash) in batch {
+                        let size = content.sizes.get(&path).copied().unwrap_or_default();
+                        content.hashed.push((path, size, hash));
+                    }
+                }
+                ContentHashMessage::Finished { failed } => finished = Some(failed),
+            }
+        }
+        if let Some(failed) = finished {
+            content.hashing = None;
+            content.groups = hashing::group_by_content(&content.hashed);
+            let groups = content.groups.values().map(|&(group, _)| group).max().map_or(0, |last| last + 1);
+            let mut status = format!("{} files in {} groups of identical content", content.groups.len(), groups);
+            if failed > 0 {
+                status.push_str(&format!(", {} could not be read", failed));
+            }
+            self.notify_finished("Duplicates by content found", &status);
+            self.status_message = status;
+            self.apply_filter();
+        }
+    }
+
+    /// Regroup similar images after the tolerance changed
+    fn regroup_similar_images(&mut self) {
+        if let Some(similar) = &mut self.similar_images {
+            similar.groups = similarity::group_similar(&similar.hashes, self.settings.similar_image_distance);
+            self.apply_filter();
+        }
+    }
+
+    fn compute_duplicates(&mut self) {
+        self.duplicate_counts.clear();
+        // Compute duplicates on ALL files, not just filtered
+        for file in &self.files {
+            *self.duplicate_counts.entry(file.normalized_name().into_owned()).or_insert(0) += 1;
+        }
+    }
+
+    /// Color of a content kind in the folder composition bars
+    fn content_kind_color(kind: ContentKind) -> egui::Color32 {
+        match kind {
+            ContentKind::Media => egui::Color32::from_rgb(200, 80, 160),
+            ContentKind::Document => egui::Color32::from_rgb(70, 130, 200),
+            ContentKind::Archive => egui::Color32::from_rgb(220, 160, 50),
+            ContentKind::Other => egui::Color32::from_gray(140),
+        }
+    }
+
+    /// Stacked bar of a folder's media / document / archive / other shares by size
+    fn content_mix_bar(ui: &mut egui::Ui, mix: &ContentMix) {
+        let (rect, response) = ui.allocate_exact_size(egui::vec2(40.0, 10.0), egui::Sense::hover());
+        let mut left = rect.left();
+        for kind in ContentKind::ALL {
+            let width = rect.width() * mix.share(kind);
+            if width > 0.0 {
+                let segment = egui::Rect::from_min_max(egui::pos2(left, rect.top()), egui::pos2(left + width, rect.bottom()));
+                ui.painter().rect_filled(segment, 0.0, Self::content_kind_color(kind));
+                left += width;
+            }
+        }
+        response.on_hover_text(mix.describe());
+    }
+
+    /// Heatmap color for a file's age: red (today) through yellow to blue (10+ years)
+    fn age_heat_color(modified_timestamp: i64, now: i64) -> egui::Color32 {
+        if modified_timestamp == 0 {
+            return egui::Color32::GRAY;
+        }
+        // Log scale so days, months and years all get visible color steps
+        let age_days = ((now - modified_timestamp).max(0) as f32) / 86400.0;
+        let t = ((1.0 + age_days).ln() / (1.0 + 3650.0_f32).ln()).clamp(0.0, 1.0);
+
+        let hot = egui::Color32::from_rgb(230, 60, 40);
+        let warm = egui::Color32::from_rgb(235, 190, 60);
+        let cold = egui::Color32::from_rgb(70, 100, 170);
+        if t < 0.5 {
+            hot.lerp_to_gamma(warm, t * 2.0)
+        } else {
+            warm.lerp_to_gamma(cold, (t - 0.5) * 2.0)
+        }
+    }
+
+    /// Human readable age ("today", "yesterday", "5 days", "3 months", "2 years")
+    fn format_age(modified_timestamp: i64) -> String {
+        let days = file_scanner::local_days_ago(modified_timestamp);
+        match days {
+            0 => "Modified today".to_string(),
+            1 => "Modified yesterday".to_string(),
+            2..=59 => format!("Modified {} days ago", days),
+            60..=729 => format!("Modified {} months ago", days / 30),
+            _ => format!("Modified {} years ago", days / 365),
+        }
+    }
+
+    /// Display text of a filtered row, from the cache while the file and the format are unchanged
+    fn row_text(&mut self, idx: usize) -> Rc<RowText> {
+        let format = (self.settings.size_format, self.settings.calendar);
+        if self.row_text_format != Some(format) || self.row_texts.len() > ROW_TEXT_CACHE_LIMIT {
+            self.row_texts.clear();
+            self.row_text_format = Some(format);
+        }
+        let file = &self.filtered_files[idx];
+        if let Some(text) = self.row_texts.get(file.absolute_path()) {
+            if text.stamp == RowText::stamp(file) {
+                return Rc::clone(text);
+            }
+        }
+        let column_format = ColumnFormat {
+            size: format.0,
+            calendar: format.1,
+        };
+        let text = Rc::new(RowText::new(file, &column_format));
+        self.row_texts.insert(text.absolute_path.clone(), Rc::clone(&text));
+        text
+    }
+
+    fn is_duplicate(&self, file: &FileInfo) -> Option<usize> {
+        self.duplicate_counts.get(&*file.normalized_name()).and_then(|&count| {
+            if count > 1 { Some(count) } else { None }
+        })
+    }
+
+    /// Get file type icon based on extension
+    fn get_file_type_icon(extension: &str) -> &'static str {
+        match extension.to_lowercase().as_str() {
+            // Documents
+            "txt" | "md" | "rtf" => "📝",
+            "pdf" => "📕",
+            "doc" | "docx" | "odt" => "📘",
+            "xls" | "xlsx" | "ods" => "📗",
+            "ppt" | "pptx" | "odp" => "📙",
+
+            // Images
+            "jpg" | "jpeg" | "png" | "gif" | "bmp" | "ico" | "svg" | "webp" | "tiff" | "tif" => "🖼",
+            "psd" | "ai" | "sketch" => "🎨",
+
+            // Audio
+            "mp3" | "wav" | "flac" | "aac" | "ogg" | "wma" | "m4a" => "🎵",
+
+            // Video
+            "mp4" | "avi" | "mkv" | "mov" | "wmv" | "flv" | "webm" | "m4v" => "🎬",
+
+            // Archives
+            "zip" | "rar" | "7z" | "tar" | "gz" | "bz2" | "xz" => "📦",
+
+            // Code
+            "rs" | "py" | "js" | "ts" | "jsx" | "tsx" | "c" | "cpp" | "h" | "hpp" => "💻",
+            "java" | "kt" | "go" | "rb" | "php" | "swift" | "cs" | "vb" => "💻",
+            "html" | "htm" | "css" | "scss" | "sass" | "less" => "🌐",
+            "sh" | "bash" | "ps1" | "bat" | "cmd" => "⚡",
+
+            // Data
+            "json" | "xml" | "csv" | "sql" | "db" | "sqlite" => "📊",
+            "yaml" | "yml" | "toml" | "ini" | "cfg" | "conf" | "config" => "⚙",
+
+            // Executables
+            "exe" | "msi" | "dll" | "so" | "dylib" => "⚙",
+
+            // Fonts
+            "ttf" | "otf" | "woff" | "woff2" | "eot" => "🔤",
+
+            // Default
+            _ => "📄",
+        }
+    }
+
+    /// (column, descending) of the sort column and the secondary one
+    fn sort_keys(&self) -> Vec<(FileColumn, bool)> {
+        [(self.sort_column, self.sort_order)]
+            .into_iter()
+            .chain(self.secondary_sort)
+            .map(|(column, order)| (column, order == SortOrder::Descending))
+            .collect()
+    }
+
+    /// Header click: sort by the column, or reverse it if it is the sort column. With Shift,
+    /// the column (or its order, clicked again) becomes the secondary sort instead.
+    fn toggle_sort(&mut self, column: FileColumn, secondary: bool) {
+        if secondary && !self.sort_column.is(&column) {
+            self.secondary_sort = match self.secondary_sort {
+                Some((current, SortOrder::Ascending)) if current.is(&column) => Some((column, SortOrder::Descending)),
+                _ => Some((column, SortOrder::Ascending)),
+            };
+        } else if self.sort_column.is(&column) {
+            // Toggle order if same column
+            self.sort_order = match self.sort_order {
+                SortOrder::Ascending => SortOrder::Descending,
+                SortOrder::Descending => SortOrder::Ascending,
+            };
+        } else {
+            // New column, start with ascending
+            self.sort_column = column;
+            self.sort_order = SortOrder::Ascending;
+            self.secondary_sort = None;
+        }
+        self.sort_files();
+    }
+
+    fn get_sort_indicator(&self, column: FileColumn) -> &str {
+        if self.sort_column.is(&column) {
+            match self.sort_order {
+                SortOrder::Ascending => " ^",
+                SortOrder::Descending => " v",
+            }
+        } else {
+            match self.secondary_sort {
+                Some((secondary, SortOrder::Ascending)) if secondary.is(&column) => " ^2",
+                Some((secondary, SortOrder::Descending)) if secondary.is(&column) => " v2",
+                _ => "",
+            }
+        }
+    }
+
+    fn open_in_explorer(file_path: &str) {
+        // Open file manager and select the file (cross-platform)
+        #[cfg(target_os = "windows")]
+        {
+            let _ = Command::new("explorer")
+                .args(["/select,", file_path])
+                .spawn();
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            let _ = Command::new("open")
+                .args(["-R", file_path])
+                .spawn();
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            // Try to open parent folder with default file manager
+            if let Some(parent) = std::path::Path::new(file_path).parent() {
+                let _ = Command::new("xdg-open")
+                    .arg(parent)
+                    .spawn();
+            }
+        }
+    }
+
+    /// Open the folder of each selected file once (with its first selected file highlighted);
+    /// many folders are confirmed first
+    fn open_selected_locations(&mut self) {
+        let mut indices: Vec<usize> = self.selected_files.iter().copied().collect();
+        indices.sort_unstable();
+        let mut seen = HashSet::new();
+        let locations: Vec<(String, String)> = indices
+            .into_iter()
+            .filter_map(|idx| self.filtered_files.get(idx))
+            .filter_map(|f| {
+                let folder = Path::new(f.absolute_path()).parent()?.display().to_string();
+                seen.insert(folder.clone()).then(|| (folder, f.absolute_path().to_string()))
+            })
+            .collect();
+        if locations.len() > OPEN_LOCATIONS_UNASKED {
+            self.pending_open_locations = Some(locations);
+        } else {
+            self.open_locations(&locations);
+        }
+    }
+
+    /// Open up to OPEN_LOCATIONS_MAX of the folders
+    fn open_locations(&mut self, locations: &[(String, String)]) {
+        for (_, file) in locations.iter().take(OPEN_LOCATIONS_MAX) {
+            Self::open_in_explorer(file);
+        }
+        self.status_message = if locations.len() > OPEN_LOCATIONS_MAX {
+            format!("Opened {} of {} folders", OPEN_LOCATIONS_MAX, locations.len())
+        } else {
+            format!("Opened {} folders", locations.len())
+        };
+    }
+
+    /// Confirmation of "Open Locations" for many folders
+    fn show_open_locations_window(&mut self, ctx: &egui::Context) {
+        let Some(locations) = &self.pending_open_locations else {
+            return;
+        };
+
+        let mut open = true;
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new("📂 Open Locations")
+            .collapsible(false)
+            .resizable(true)
+            .default_width(520.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(format!("The selected files are in {} folders:", locations.len()));
+                egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                    for (folder, _) in locations {
+                        ui.label(folder);
+                    }
+                });
+                if locations.len() > OPEN_LOCATIONS_MAX {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(255, 140, 0),
+                        format!("Only the first {} are opened; copy the list for the rest", OPEN_LOCATIONS_MAX),
+                    );
+                }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    let count = locations.len().min(OPEN_LOCATIONS_MAX);
+                    confirmed = ui.button(format!("Open {} folders", count)).clicked();
+                    if ui.button("📋 Copy list").on_hover_text("Copy the folder paths, one per line").clicked() {
+                        let list: Vec<&str> = locations.iter().map(|(folder, _)| folder.as_str()).collect();
+                        ui.ctx().copy_text(list.join("\n"));
+                    }
+                    cancelled = ui.button("Cancel").clicked();
+                });
+            });
+
+        if confirmed {
+            if let Some(locations) = self.pending_open_locations.take() {
+                self.open_locations(&locations);
+            }
+        } else if cancelled || !open {
+            self.pending_open_locations = None;
+        }
+    }
+
+    /// Row actions offered for a file row (none destructive in read-only mode)
+    fn row_actions(&self, idx: usize) -> Vec<RowAction> {
+        let read_only = self.is_read_only();
+        let is_dir = self.filtered_files.get(idx).is_some_and(|f| f.is_dir);
+        RowAction::ALL
+            .into_iter()
+            .filter(|a| !(read_only && a.is_destructive()) && (!is_dir || a.applies_to_folders()))
+            .collect()
+    }
+
+    /// Build the context menu for a file row from the declarative action list
+    fn row_context_menu(&mut self, ui: &mut egui::Ui, idx: usize) {
+        for action in self.row_actions(idx) {
+            if action.starts_group() {
+                ui.separator();
+            }
+            let mut button = egui::Button::new(action.label());
+            if let Some(shortcut) = action.shortcut() {
+                button = button.shortcut_text(ui.ctx().format_shortcut(&shortcut));
+            }
+            if ui.add(button).clicked() {
+                self.execute_row_action(ui.ctx(), action, idx);
+                ui.close();
+            }
+        }
+    }
+
+    /// Run keyboard shortcuts for row actions against the single selected file
+    fn handle_row_shortcuts(&mut self, ctx: &egui::Context) {
+        // Don't steal keys from text inputs (filter, rename)
+        if self.editing_index.is_some() || ctx.wants_keyboard_input() || self.selected_files.len() != 1 {
+            return;
+        }
+        let Some(&idx) = self.selected_files.iter().next() else {
+            return;
+        };
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::P)) {
+            self.command_palette = Some(String::new());
+            return;
+        }
+        let read_only = self.is_read_only();
+        let mut shortcuts: Vec<(RowAction, egui::KeyboardShortcut)> = RowAction::ALL
+            .into_iter()
+            .filter(|a| !(read_only && a.is_destructive()))
+            .filter_map(|a| Some((a, a.shortcut()?)))
+            .collect();
+        // consume_shortcut lets extra Alt/Shift through (Alt+Enter matches plain Enter), so the
+        // shortcuts with the most modifiers are tried first
+        let modifier_count = |m: egui::Modifiers| [m.alt, m.ctrl, m.shift, m.command || m.mac_cmd].into_iter().filter(|&on| on).count();
+        shortcuts.sort_by_key(|(_, shortcut)| std::cmp::Reverse(modifier_count(shortcut.modifiers)));
+        for (action, shortcut) in shortcuts {
+            if ctx.input_mut(|i| i.consume_shortcut(&shortcut)) {
+                self.execute_row_action(ctx, action, idx);
+                return;
+            }
+        }
+    }
+
+    /// Command palette: the row actions for the selected file, filtered by what is typed.
+    /// Enter runs the first match, Escape closes it.
+    fn show_command_palette(&mut self, ctx: &egui::Context) {
+        let Some(query) = &self.command_palette else {
+            return;
+        };
+        let needle = query.to_lowercase();
+        // The actions apply to a single file
+        let idx = match self.selected_files.iter().next() {
+            Some(&idx) if self.selected_files.len() == 1 => idx,
+            _ => {
+                self.command_palette = None;
+                return;
+            }
+        };
+        let actions: Vec<RowAction> = self
+            .row_actions(idx)
+            .into_iter()
+            .filter(|a| a.label().to_lowercase().contains(&needle))
+            .collect();
+        let file_name = self.filtered_files.get(idx).map(|f| f.full_name().to_string()).unwrap_or_default();
+
+        let mut chosen = None;
+        let mut close = false;
+        egui::Window::new("Command Palette")
+            .collapsible(false)
+            .resizable(false)
+            .title_bar(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 80.0])
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(&file_name).weak());
+                let Some(query) = &mut self.command_palette else {
+                    return;
+                };
+                let response = ui.add(
+                    egui::TextEdit::singleline(query)
+                        .hint_text("Type an action...")
+                        .desired_width(f32::INFINITY),
+                );
+                response.request_focus();
+                if response.lost_focus() {
+                    if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        chosen = actions.first().copied();
+                    } else if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                        close = true;
+                    }
+                }
+                ui.separator();
+                if actions.is_empty() {
+                    ui.label(egui::RichText::new("No matching action").weak());
+                }
+                for &action in &actions {
+                    let mut button = egui::Button::new(action.label()).frame(false);
+                    if let Some(shortcut) = action.shortcut() {
+                        button = button.shortcut_text(ctx.format_shortcut(&shortcut));
+                    }
+                    if ui.add_sized([ui.available_width(), 20.0], button).clicked() {
+                        chosen = Some(action);
+                    }
+                }
+            });
+
+        if let Some(action) = chosen {
+            self.command_palette = None;
+            self.execute_row_action(ctx, action, idx);
+        } else if close {
+            self.command_palette = None;
+        }
+    }
+
+    /// Perform a row action on a file (index into filtered_files)
+    fn execute_row_action(&mut self, ctx: &egui::Context, action: RowAction, idx: usize) {
+        if action.is_destructive() && self.refuse_if_read_only() {
+            return;
+        }
+        let Some(file) = self.filtered_files.get(idx) else {
+            return;
+        };
+        if file.is_dir && !action.applies_to_folders() {
+            return;
+        }
+        let file_path = file.absolute_path().to_string();
+
+        if action != RowAction::CopyPath && std::fs::symlink_metadata(&file_path).is_err() {
+            self.error_message = Some(format!("{} no longer exists (moved or deleted since the scan)", file.full_name()));
+            self.stale_paths.insert(file_path);
+            return;
+        }
+
+        match action {
+            RowAction::Open => {
+                if let Err(e) = open::that(&file_path) {
+                    self.error_message = Some(format!("Open failed: {}", e));
+                }
+            }
+            RowAction::OpenLocation => Self::open_in_explorer(&file_path),
+            RowAction::CopyPath => {
+                ctx.copy_text(file_path.clone());
+                self.status_message = format!("Copied path: {}", file_path);
+            }
+            RowAction::Rename => self.start_rename(idx),
+            RowAction::ToggleReviewed => {
+                let file = file.clone();
+                if self.reviewed.contains(&file) {
+                    self.reviewed.unmark(&file);
+                    self.status_message = format!("Unmarked {}", file.full_name());
+                } else {
+                    self.reviewed.mark(&file);
+                    self.status_message = format!("Marked {} as reviewed", file.full_name());
+                }
+                self.save_reviewed();
+            }
+            RowAction::MoveToFolder => self.move_file(&file_path),
+            RowAction::CreateSymlink => self.link_files(idx, TransferMode::Symlink),
+            RowAction::CreateShortcut => self.link_files(idx, TransferMode::Shortcut),
+            RowAction::SplitJoin => {
+                let path = PathBuf::from(&file_path);
+                self.split_dialog = Some(SplitDialog {
+                    dest_folder: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+                    manifest: split::is_manifest(&path).then(|| SplitManifest::load(&path)),
+                    file_size: file.file_size,
+                    path,
+                });
+            }
+            RowAction::Properties => {
+                let metadata = std::fs::metadata(&file_path).ok();
+                let to_timestamp = |time: std::io::Result<std::time::SystemTime>| {
+                    time.ok()
+                        .and_then(|t| t.duration_since(std::time::SystemTime::UNIX_EPOCH).ok())
+                        .map(|d| d.as_secs() as i64)
+                };
+                self.properties = Some(FileProperties {
+                    full_name: file.full_name().to_string(),
+                    absolute_path: file_path,
+                    file_size: file.file_size,
+                    modified_timestamp: file.modified_timestamp,
+                    created_timestamp: metadata.as_ref().and_then(|m| to_timestamp(m.created())),
+                    accessed_timestamp: metadata.as_ref().and_then(|m| to_timestamp(m.accessed())),
+                    readonly: metadata.map(|m| m.permissions().readonly()).unwrap_or(false),
+                    owner: file.owner.clone(),
+                    owner_orphaned: file.owner_orphaned,
+                    mail_summary: None,
+                    // Reading an online-only archive would download it
+                    mail_receiver: (mail_store::is_mail_store(file.extension()) && !file.is_cloud_placeholder)
+                        .then(|| mail_store::spawn_summary(PathBuf::from(file.absolute_path()))),
+                });
+            }
+            RowAction::Delete => {
+                // A grouped file goes with its companions: confirm the whole group
+                let full_name = self.filtered_files[idx].full_name().to_string();
+                let paths = self.with_sidecars(vec![(file_path.clone(), full_name)]);
+                if paths.len() > 1 {
+                    self.pending_delete_paths = paths;
+                    self.show_delete_confirm = true;
+                } else {
+                    self.delete_file(&file_path);
+                }
+            }
+        }
+    }
+
+    /// Pick up the mail archive summary for the Properties window
+    fn check_mail_summary(&mut self) {
+        let Some(props) = &mut self.properties else {
+            return;
+        };
+        let Some(receiver) = &props.mail_receiver else {
+            return;
+        };
+        if let Ok(result) = receiver.try_recv() {
+            props.mail_summary = Some(result);
+            props.mail_receiver = None;
+        }
+    }
+
+    /// Show the Properties window for the selected file
+    fn show_properties_window(&mut self, ctx: &egui::Context) {
+        let Some(props) = &self.properties else {
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new("Properties")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Grid::new("file_properties")
+                    .num_columns(2)
+                    .spacing([10.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new("Name:").strong());
+                        ui.label(&props.full_name);
+                        ui.end_row();
+                        ui.label(egui::RichText::new("Location:").strong());
+                        ui.label(&props.absolute_path);
+                        ui.end_row();
+                        ui.label(egui::RichText::new("Size:").strong());
+                        ui.label(format!(
+                            "{} ({})",
+                            format_size(props.file_size, &self.settings.size_format),
+                            format_bytes(props.file_size, &self.settings.size_format)
+                        ));
+                        ui.end_row();
+                        ui.label(egui::RichText::new("Modified:").strong());
+                        ui.label(format_date(props.modified_timestamp, self.settings.calendar));
+                        ui.end_row();
+                        ui.label(egui::RichText::new("Created:").strong());
+                        ui.label(props.created_timestamp.map(|t| format_date(t, self.settings.calendar)).unwrap_or_else(|| "-".to_string()));
+                        ui.end_row();
+                        ui.label(egui::RichText::new("Accessed:").strong());
+                        ui.label(props.accessed_timestamp.map(|t| format_date(t, self.settings.calendar)).unwrap_or_else(|| "-".to_string()));
+                        ui.end_row();
+                        ui.label(egui::RichText::new("Read-only:").strong());
+                        ui.label(if props.readonly { "Yes" } else { "No" });
+                        ui.end_row();
+                        if let Some(owner) = &props.owner {
+                            ui.label(egui::RichText::new("Owner:").strong());
+                            if props.owner_orphaned {
+                                ui.colored_label(egui::Color32::RED, format!("{} (account deleted)", owner));
+                            } else {
+                                ui.label(owner);
+                            }
+                            ui.end_row();
+                        }
+                        if props.mail_receiver.is_some() {
+                            ui.label(egui::RichText::new("Mail store:").strong());
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label("Reading...");
+                            });
+                            ui.end_row();
+                        }
+                        match &props.mail_summary {
+                            Some(Ok(summary)) => {
+                                ui.label(egui::RichText::new("Mail store:").strong());
+                                ui.label(&summary.format);
+                                ui.end_row();
+                                ui.label(egui::RichText::new("Messages:").strong());
+                                ui.label(match summary.folders {
+                                    Some(folders) => format!("{} in {} folders", summary.messages, folders),
+                                    None => summary.messages.to_string(),
+                                });
+                                ui.end_row();
+                                if let (Some(first), Some(last)) = (summary.first, summary.last) {
+                                    ui.label(egui::RichText::new("Date range:").strong());
+                                    ui.label(format!("{} – {}", format_date(first, self.settings.calendar), format_date(last, self.settings.calendar)));
+                                    ui.end_row();
+                                }
+                                for note in &summary.notes {
+                                    ui.label("");
+                                    ui.weak(note);
+                                    ui.end_row();
+                                }
+                            }
+                            Some(Err(e)) => {
+                                ui.label(egui::RichText::new("Mail store:").strong());
+                                ui.colored_label(egui::Color32::RED, e);
+                                ui.end_row();
+                            }
+                            None => {}
+                        }
+                    });
+            });
+
+        if !open {
+            self.properties = None;
+        }
+    }
+
+    /// Show the Settings window (preview generation options)
+    fn show_settings_window(&mut self, ctx: &egui::Context) {
+        if !self.show_settings {
+            return;
+        }
+
+        let mut open = true;
+        let mut preview_changed = false;
+        let mut export_diagnostics = false;
+        egui::Window::new("Settings")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new("Previews").strong());
+                egui::Grid::new("preview_settings")
+                    .num_columns(2)
+                    .spacing([10.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("Preview size:");
+                        preview_changed |= ui
+                            .add(egui::Slider::new(&mut self.settings.preview_max_size, 200.0..=1200.0).suffix(" pt"))
+                            .changed();
+                        ui.end_row();
+
+                        ui.label("Video frame at:");
+                        preview_changed |= ui
+                            .add(egui::DragValue::new(&mut self.settings.video_frame_secs).range(0.0..=600.0).speed(0.5).suffix(" s"))
+                            .changed();
+                        ui.end_row();
+
+                        ui.label("PDF render DPI:");
+                        preview_changed |= ui
+                            .add(egui::Slider::new(&mut self.settings.pdf_render_dpi, 72.0..=300.0))
+                            .changed();
+                        ui.end_row();
+
+                        ui.label("Quality:");
+                        egui::ComboBox::from_id_salt("preview_quality")
+                            .selected_text(self.settings.preview_quality.label())
+                            .show_ui(ui, |ui| {
+                                for quality in PreviewQuality::ALL {
+                                    preview_changed |= ui
+                                        .selectable_value(&mut self.settings.preview_quality, quality, quality.label())
+                                        .changed();
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("Cloud files:");
+                        ui.checkbox(&mut self.settings.preview_cloud_files, "Preview online-only files")
+                            .on_hover_text("Reading an online-only OneDrive/Dropbox/iCloud file downloads it");
+                        ui.end_row();
+                    });
+
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("Notifications").strong());
+                ui.checkbox(&mut self.settings.notify_when_unfocused, "Notify when a long operation finishes in the background")
+                    .on_hover_text("Scan, export, move/copy, delete and compare; shown only while the window is not focused");
+
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("Checksums").strong());
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.settings.cache_hashes, "Cache checksums")
+                        .on_hover_text("Files with unchanged size and modification time are not re-hashed");
+                    if ui.button("Clear cache").clicked() {
+                        self.status_message = match HashCache::clear() {
+                            Ok(_) => "Checksum cache cleared".to_string(),
+                            Err(e) => e,
+                        };
+                    }
+                });
+
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("Network shares").strong());
+                let mut retry_changed = false;
+                ui.horizontal(|ui| {
+                    ui.label("Retry failed reads:");
+                    retry_changed |= ui
+                        .add(egui::DragValue::new(&mut self.settings.network_retries).range(0..=10).suffix(" times"))
+                        .on_hover_text("Timeouts and dropped connections while scanning or previewing; missing files and denied access fail at once")
+                        .changed();
+                    ui.label("first after");
+                    retry_changed |= ui
+                        .add(egui::DragValue::new(&mut self.settings.retry_delay_ms).range(50..=5000).suffix(" ms"))
+                        .on_hover_text("The delay doubles for every further retry")
+                        .changed();
+                });
+                if retry_changed {
+                    retry::configure(self.settings.network_retries, self.settings.retry_delay_ms);
+                }
+                ui.label(format!("Reads retried this session: {}", retry::total_retries()));
+
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("Scanning").strong());
+                ui.checkbox(&mut self.settings.ntfs_fast_scan, "Fast NTFS scan (read the master file table)")
+                    .on_hover_text("Lists a whole local NTFS drive in seconds when running as administrator, and rescans only apply the changes from the USN journal; otherwise folders are walked as usual");
+                let mut priority_changed = ui
+                    .checkbox(&mut self.settings.background_priority, "Background priority")
+                    .on_hover_text("Scans and hashing run at low CPU and I/O priority and pause regularly, so the machine stays usable; they take longer")
+                    .changed();
+                ui.add_enabled_ui(self.settings.background_priority, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Pause after every 50 ms of work:");
+                        priority_changed |= ui
+                            .add(egui::DragValue::new(&mut self.settings.throttle_pause_ms).range(10..=1000).suffix(" ms"))
+                            .on_hover_text("Longer pauses leave more of the disk to other programs; 50 ms halves the scan speed, 200 ms takes five times as long")
+                            .changed();
+                    });
+                });
+                if priority_changed {
+                    priority::configure(self.settings.background_priority, self.settings.throttle_pause_ms);
+                }
+
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("Filter").strong());
+                if ui
+                    .checkbox(&mut self.settings.fold_accents, "Ignore accents (cafe = café = CAFÉ)")
+                    .on_hover_text("Matching is always case-insensitive and Unicode-normalized")
+                    .changed()
+                {
+                    self.apply_filter();
+                }
+                if ui
+                    .checkbox(&mut self.settings.transliterate_search, "Match Latin spelling (krungthep = กรุงเทพ)")
+                    .on_hover_text("A filter typed in Latin letters also finds Thai, Cyrillic, Greek, Chinese and other names by their romanization")
+                    .changed()
+                {
+                    self.apply_filter();
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Sort names:");
+                    let before = self.settings.sort_collation;
+                    egui::ComboBox::from_id_salt("sort_collation")
+                        .selected_text(self.settings.sort_collation.label())
+                        .show_ui(ui, |ui| {
+                            for collation in Collation::ALL {
+                                ui.selectable_value(&mut self.settings.sort_collation, collation, collation.label());
+                            }
+                        })
+                        .response
+                        .on_hover_text("Alphabetical order of the language, e.g. Thai words by consonant rather than by leading vowel");
+                    if self.settings.sort_collation != before {
+                        text_match::configure_collation(self.settings.sort_collation);
+                        self.sort_files();
+                    }
+                });
+                if ui
+                    .checkbox(&mut self.settings.natural_sort, "Natural sort (file2 before file10)")
+                    .on_hover_text("Name and path columns compare the numbers in names by value instead of digit by digit")
+                    .changed()
+                {
+                    text_match::configure_natural_sort(self.settings.natural_sort);
+                    self.sort_files();
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Compare names as:");
+                    let before = self.settings.name_form;
+                    egui::ComboBox::from_id_salt("name_form")
+                        .selected_text(self.settings.name_form.label())
+                        .show_ui(ui, |ui| {
+                            for form in NameForm::ALL {
+                                ui.selectable_value(&mut self.settings.name_form, form, form.label());
+                            }
+                        })
+                        .response
+                        .on_hover_text("Names copied from a Mac store accents (and Thai vowels) as separate characters; normalizing makes them sort with and count as duplicates of the same names written on Windows. Names on disk are not changed");
+                    if self.settings.name_form != before {
+                        text_match::configure_name_form(self.settings.name_form);
+                        self.compute_duplicates();
+                        self.sort_files();
+                        self.apply_filter();
+                    }
+                });
+
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("Export columns").strong())
+                    .on_hover_text("Columns each format gets in exports and in profiles without their own columns. Untouched formats get the standard columns plus the optional columns shown in the list");
+                egui::Grid::new("format_columns").striped(true).show(ui, |ui| {
+                    ui.label("");
+                    for format in ExportFormat::ALL {
+                        ui.label(format.label());
+                    }
+                    ui.end_row();
+                    for column in ExportColumn::ALL {
+                        ui.label(column.header());
+                        for format in ExportFormat::ALL {
+                            let chosen = self.settings.format_columns.get(format);
+                            let mut on = chosen.unwrap_or(&ExportColumn::DEFAULT).contains(&column);
+                            if ui.checkbox(&mut on, "").changed() {
+                                // The first change starts from the standard columns
+                                let mut columns = chosen.unwrap_or(&ExportColumn::DEFAULT).to_vec();
+                                columns.retain(|c| *c != column);
+                                if on {
+                                    columns.push(column);
+                                }
+                                // Always in the standard order
+                                columns.sort_by_key(|c| ExportColumn::ALL.iter().position(|a| a == c));
+                                // Unchecking the last column would mean the standard ones again
+                                if !columns.is_empty() {
+                                    *self.settings.format_columns.get_mut(format) = columns;
+                                }
+                            }
+                        }
+                        ui.end_row();
+                    }
+                    ui.label("");
+                    for format in ExportFormat::ALL {
+                        if ui
+                            .add_enabled(self.settings.format_columns.get(format).is_some(), egui::Button::new("Reset").small())
+                            .on_hover_text("Back to the standard columns")
+                            .clicked()
+                        {
+                            self.settings.format_columns.get_mut(format).clear();
+                        }
+                    }
+                    ui.end_row();
+                });
+
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("Review").strong());
+                if ui
+                    .button("Clear all reviewed marks")
+                    .on_hover_text("Every file shows as not reviewed again")
+                    .clicked()
+                {
+                    self.reviewed.clear();
+                    self.save_reviewed();
+                    self.status_message = "Cleared all reviewed marks".to_string();
+                }
+
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("Sizes").strong());
+                let size_format = &mut self.settings.size_format;
+                egui::Grid::new("size_settings")
+                    .num_columns(2)
+                    .spacing([10.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("Units:");
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut size_format.system, UnitSystem::Binary, "Binary (KiB, MiB)");
+                            ui.radio_value(&mut size_format.system, UnitSystem::Si, "SI (kB, MB)");
+                        });
+                        ui.end_row();
+
+                        ui.label("Always show in:");
+                        egui::ComboBox::from_id_salt("size_unit")
+                            .selected_text(size_format.unit.label(size_format.system))
+                            .show_ui(ui, |ui| {
+                                for unit in SizeUnit::ALL {
+                                    ui.selectable_value(&mut size_format.unit, unit, unit.label(size_format.system));
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("Digit grouping:");
+                        ui.checkbox(&mut size_format.thousands_separators, "Use locale thousands separators");
+                        ui.end_row();
+                    });
+
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("Dates").strong());
+                ui.horizontal(|ui| {
+                    ui.label("Year:");
+                    egui::ComboBox::from_id_salt("calendar")
+                        .selected_text(self.settings.calendar.label())
+                        .show_ui(ui, |ui| {
+                            for calendar in Calendar::ALL {
+                                ui.selectable_value(&mut self.settings.calendar, calendar, calendar.label());
+                            }
+                        })
+                        .response
+                        .on_hover_text("Used in the file list, Properties and exports (the CSV header then reads \"Modified (B.E.)\")");
+                });
+
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("Hooks").strong())
+                    .on_hover_text("Feed dashboards: a JSON summary (event, folders, file count, total size, output files) is POSTed to the URL or passed to the command on stdin. Also run by the command line");
+                let mut removed = None;
+                for (idx, hook) in self.settings.hooks.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut hook.enabled, "");
+                        egui::ComboBox::from_id_salt(("hook_event", idx))
+                            .selected_text(hook.on.label())
+                            .show_ui(ui, |ui| {
+                                for event in HookEvent::ALL {
+                                    ui.selectable_value(&mut hook.on, event, event.label());
+                                }
+                            });
+                        egui::ComboBox::from_id_salt(("hook_kind", idx))
+                            .selected_text(hook.kind.label())
+                            .show_ui(ui, |ui| {
+                                for kind in HookKind::ALL {
+                                    ui.selectable_value(&mut hook.kind, kind, kind.label());
+                                }
+                            });
+                        let hint = match hook.kind {
+                            HookKind::Webhook => "https://dashboard.example/ingest",
+                            HookKind::Command => "python upload.py",
+                        };
+                        ui.add(egui::TextEdit::singleline(&mut hook.target).hint_text(hint).desired_width(260.0));
+                        if ui.small_button("🗑").on_hover_text("Remove hook").clicked() {
+                            removed = Some(idx);
+                        }
+                    });
+                }
+                if let Some(idx) = removed {
+                    self.settings.hooks.remove(idx);
+                }
+                if ui.button("➕ Add hook").clicked() {
+                    self.settings.hooks.push(Hook::default());
+                }
+
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("Troubleshooting").strong());
+                ui.horizontal(|ui| {
+                    export_diagnostics = ui
+                        .button("🩺 Export diagnostic bundle...")
+                        .on_hover_text("Save a zip to attach to bug reports: environment, scan summary, app state, settings (SMTP password removed) and the end of the debug log")
+                        .clicked();
+                    ui.checkbox(&mut self.diagnostics_anonymize, "Anonymize")
+                        .on_hover_text("Replace the scanned folders, your home folder and user name with placeholders, leave out file names, mail addresses and hook targets");
+                });
+                ui.weak(format!("Debug log: {}", diagnostics::debug_log_path().display()));
+            });
+
+        if export_diagnostics {
+            self.export_diagnostics();
+        }
+
+        // Regenerate previews with the new settings
+        if preview_changed {
+            self.image_cache.clear();
+            self.preview_failed.clear();
+        }
+
+        if !open {
+            self.show_settings = false;
+            if let Err(e) = self.settings.save() {
+                self.error_message = Some(e);
+            }
+        }
+    }
+
+    /// Save a diagnostic bundle (zip) for bug reports
+    fn export_diagnostics(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Zip files", &["zip"])
+            .set_file_name("file_lister_diagnostics.zip")
+            .save_file()
+        else {
+            return;
+        };
+        let anonymize = self.diagnostics_anonymize;
+        let anonymizer = if anonymize { Anonymizer::new(&self.selected_folders) } else { Anonymizer::off() };
+        match diagnostics::write_bundle(&path, &self.diagnostic_contents(anonymize), &self.settings, &anonymizer) {
+            Ok(()) => self.status_message = format!("Diagnostic bundle saved to: {}", path.display()),
+            Err(e) => self.error_message = Some(e),
+        }
+    }
+
+    /// Environment, scan summary and app state for the diagnostic bundle; anonymized, file
+    /// names are left out (failed previews by extension, scan errors without paths)
+    fn diagnostic_contents(&self, anonymize: bool) -> BundleContents {
+        let environment = vec![
+            ("Version".to_string(), env!("CARGO_PKG_VERSION").to_string()),
+            ("OS".to_string(), format!("{} {}", std::env::consts::OS, std::env::consts::ARCH)),
+            ("CPUs".to_string(), std::thread::available_parallelism().map_or(1, |n| n.get()).to_string()),
+            ("Video decoder".to_string(), Self::video_decoder_name().unwrap_or("none").to_string()),
+            ("Pdfium".to_string(), if Self::is_pdfium_ready() { "ready" } else { "not available" }.to_string()),
+            ("Config folder".to_string(), users::config_dir().display().to_string()),
+            ("User profile".to_string(), users::active().unwrap_or_else(|| "default".to_string())),
+            ("Read-only".to_string(), self.is_read_only().to_string()),
+        ];
+
+        let mut extensions: HashMap<String, usize> = HashMap::new();
+        for file in self.files.iter().filter(|f| !f.is_dir) {
+            *extensions.entry(file.extension().to_lowercase()).or_insert(0) += 1;
+        }
+        let mut extensions: Vec<(String, usize)> = extensions.into_iter().collect();
+        extensions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        extensions.truncate(20);
+        let scan_errors: Vec<serde_json::Value> = self
+            .scan_report
+            .errors
+            .iter()
+            .map(|e| {
+                let path = if anonymize { String::new() } else { e.path.display().to_string() };
+                serde_json::json!({ "path": path, "error": e.error, "is_folder": e.is_folder })
+            })
+            .collect();
+        let mut failed_previews: Vec<String> = self
+            .preview_failed
+            .iter()
+            .map(|path| {
+                if anonymize {
+                    Path::new(path).extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default()
+                } else {
+                    path.clone()
+                }
+            })
+            .collect();
+        failed_previews.sort();
+        let scan_summary = serde_json::json!({
+            "folders": self.selected_folders.iter().map(|f| f.display().to_string()).collect::<Vec<_>>(),
+            "file_count": self.files.iter().filter(|f| !f.is_dir).count(),
+            "listed_count": self.filtered_files.len(),
+            "total_size": self.files.iter().map(|f| f.file_size).sum::<u64>(),
+            "cloud_placeholders": self.files.iter().filter(|f| f.is_cloud_placeholder).count(),
+            "top_extensions": extensions,
+            "scan_errors": scan_errors,
+            "failed_previews": failed_previews,
+        });
+
+        let mut session = self.current_session();
+        let selected = session.selected_paths.len();
+        if anonymize {
+            session.selected_paths.clear();
+            session.added_files.clear();
+        }
+        let mut app_state = serde_json::to_value(&session).unwrap_or_default();
+        app_state["selected_count"] = selected.into();
+        app_state["sort_column"] = self.sort_column.id.into();
+        app_state["sort_descending"] = (self.sort_order == SortOrder::Descending).into();
+        if let Some((column, order)) = self.secondary_sort {
+            app_state["secondary_sort_column"] = column.id.into();
+            app_state["secondary_sort_descending"] = (order == SortOrder::Descending).into();
+        }
+        app_state["right_pane_open"] = self.right_pane.is_some().into();
+        app_state["running"] = serde_json::json!({
+            "scan": self.is_scanning,
+            "bulk_job": self.bulk_job.is_some(),
+            "hashing": self.content_hashing.is_some(),
+            "text_counting": self.text_counting.is_some(),
+        });
+
+        BundleContents {
+            environment,
+            scan_summary,
+            app_state,
+        }
+    }
+
+    fn export_csv(&mut self, path: &PathBuf) {
+        if self.settings.export_thumbnails {
+            self.start_thumbnail_export(path);
+            return;
+        }
+
+        // Export filtered files
+        let options = ExportOptions {
+            split: self.settings.export_split,
+            ..self.export_options(ExportFormat::from_path(path))
+        };
+        self.running_export = Some(RunningExport {
+            job: ExportJob::spawn(self.export_files(), None, options, path.clone()),
+            thumbnail_count: None,
+            embedded_dir: None,
+        });
+    }
+
+    /// Listed files without the folder rows (what exports and bulk actions work on)
+    fn listed_files(&self) -> Vec<FileInfo> {
+        self.filtered_files.iter().filter(|f| !f.is_dir).cloned().collect()
+    }
+
+    /// Scanned files the current filters hide (collapsed sidecars count as listed), in sort order
+    fn excluded_files(&self) -> Vec<FileInfo> {
+        let listed: HashSet<&str> = self
+            .filtered_files
+            .iter()
+            .map(|f| f.absolute_path())
+            .chain(self.sidecar_companions.iter().map(String::as_str))
+            .collect();
+        self.files
+            .iter()
+            .filter(|f| !f.is_dir && !listed.contains(f.absolute_path()))
+            .cloned()
+            .collect()
+    }
+
+    /// What the CSV export writes: the listed files, or with "Excluded" the ones the filters hide
+    fn export_files(&self) -> Vec<FileInfo> {
+        if self.export_excluded {
+            self.excluded_files()
+        } else {
+            self.listed_files()
+        }
+    }
+
+    /// Selected files in list order, or all listed files if none are selected
+    fn selected_or_listed(&self) -> Vec<FileInfo> {
+        if self.selected_files.is_empty() {
+            return self.listed_files();
+        }
+        let mut indices: Vec<usize> = self.selected_files.iter().copied().collect();
+        indices.sort_unstable();
+        indices.into_iter().filter_map(|idx| self.filtered_files.get(idx).cloned()).collect()
+    }
+
+    /// Write the audio and video files of the selection to an M3U8 playlist
+    fn export_playlist(&mut self, path: &Path) {
+        let files: Vec<FileInfo> = self
+            .selected_or_listed()
+            .into_iter()
+            .filter(|f| Self::is_audio_file(f.extension()) || Self::is_video_file(f.extension()))
+            .collect();
+        match manifest::write_playlist(&files, path, self.settings.playlist_relative_paths) {
+            Ok(()) => {
+                self.status_message = format!("Exported playlist of {} files to {}", files.len(), path.display());
+                self.error_message = None;
+            }
+            Err(e) => self.error_message = Some(e),
+        }
+    }
+
+    /// Hash the selection into a manifest (paths, sizes, SHA-256) on a background thread
+    fn export_manifest(&mut self, path: &Path) {
+        let files = self.selected_or_listed();
+        self.status_message = format!("Hashing {} files for the manifest...", files.len());
+        self.manifest_kind = "manifest";
+        self.manifest_receiver = Some(manifest::spawn_manifest(files, path.to_path_buf(), self.settings.cache_hashes));
+    }
+
+    /// Export the selected (or listed) files grouped into media of the Target size, with SHA-256
+    fn export_burn_list(&mut self, path: &Path) {
+        let Some(capacity) = self.settings.target_size_bytes else {
+            return;
+        };
+        let files = self.selected_or_listed();
+        self.status_message = format!("Hashing {} files for the burn list...", files.len());
+        self.manifest_kind = "burn list";
+        self.manifest_receiver =
+            Some(manifest::spawn_burn_list(files, path.to_path_buf(), capacity, self.settings.cache_hashes));
+    }
+
+    /// Check a folder against a manifest of expected files: the scanned folder, or one picked
+    /// when several (or none) are scanned
+    fn start_manifest_verification(&mut self) {
+        let Some(manifest) = rfd::FileDialog::new()
+            .add_filter("Manifest", &["csv", "tsv", "json", "sha256", "sha1", "md5", "txt"])
+            .pick_file()
+        else {
+            return;
+        };
+        let folder = match self.selected_folders.as_slice() {
+            [folder] => folder.clone(),
+            _ => match rfd::FileDialog::new().set_title("Folder to check").pick_folder() {
+                Some(folder) => folder,
+                None => return,
+            },
+        };
+        self.status_message = format!("Checking {} against {}...", folder.display(), manifest.display());
+        self.manifest_verification = Some(ManifestVerification {
+            receiver: Some(manifest_check::spawn_verify(
+                manifest.clone(),
+                folder,
+                self.scan_filter(),
+                self.settings.cache_hashes,
+            )),
+            manifest,
+            progress: (0, 0),
+            result: None,
+            shown: [true; 3],
+        });
+    }
+
+    /// Check manifest check progress
+    fn check_manifest_verification(&mut self) {
+        let Some(verification) = &mut self.manifest_verification else {
+            return;
+        };
+        let Some(receiver) = &verification.receiver else {
+            return;
+        };
+        let mut finished = None;
+        while let Ok(message) = receiver.try_recv() {
+            match message {
+                VerifyMessage::Progress { done, total } => verification.progress = (done, total),
+                VerifyMessage::Finished(result) => finished = Some(result),
+            }
+        }
+        if let Some(result) = finished {
+            let status = match &result {
+                Ok(check) => format!("Manifest check: {}", check.describe()),
+                Err(e) => format!("Manifest check failed: {}", e),
+            };
+            verification.result = Some(result);
+            verification.receiver = None;
+            self.notify_finished("Manifest check finished", &status);
+            self.status_message = status;
+        }
+    }
+
+    /// Check manifest hashing progress
+    fn check_manifest(&mut self) {
+        let Some(receiver) = &self.manifest_receiver else {
+            return;
+        };
+        let mut finished = None;
+        while let Ok(message) = receiver.try_recv() {
+            match message {
+                ManifestMessage::Progress { done, total } => {
+                    self.status_message = format!("Hashing files for the {}: {} / {}", self.manifest_kind, done, total);
+                }
+                ManifestMessage::Finished(result) => finished = Some(result),
+            }
+        }
+        let Some(result) = finished else {
+            return;
+        };
+        self.manifest_receiver = None;
+        match result {
+            Ok(count) => {
+                self.status_message = format!("Exported {} of {} files", self.manifest_kind, count);
+                self.error_message = None;
+                self.notify_finished(&format!("Exported {}", self.manifest_kind), &self.status_message.clone());
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Export of the {} failed: {}", self.manifest_kind, e));
+                self.notify_finished(&format!("Export of the {} failed", self.manifest_kind), &e);
+            }
+        }
+    }
+
+    /// Report the export once its worker thread has finished
+    fn check_running_export(&mut self) {
+        let Some(running) = &mut self.running_export else {
+            return;
+        };
+        let Some(result) = running.job.poll() else {
+            return;
+        };
+        let Some(running) = self.running_export.take() else {
+            return;
+        };
+        if let Some(dir) = &running.embedded_dir {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+
+        match result {
+            Ok(outcome) => {
+                self.status_message = format!(
+                    "{} ({} in {})",
+                    Self::export_summary(running.job.rows_total, &outcome.written),
+                    format_size(outcome.bytes, &self.settings.size_format),
+                    jobs::format_duration(outcome.elapsed)
+                );
+                if let Some(count) = running.thumbnail_count {
+                    self.status_message.push_str(&format!(", {} thumbnails", count));
+                }
+                if self.export_excluded {
+                    self.status_message.push_str(", files excluded by the filters");
+                }
+                self.error_message = None;
+                self.notify_finished("Export finished", &self.status_message.clone());
+                let outputs: Vec<String> = outcome.written.iter().map(|p| p.display().to_string()).collect();
+                let summary = hooks::summary(HookEvent::Export, "gui", &self.selected_folders, &self.export_files(), &outputs);
+                self.run_hooks(HookEvent::Export, summary);
+            }
+            Err(e) if e == csv_export::CANCELLED => {
+                self.status_message = String::from("Export cancelled");
+                self.error_message = None;
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Export failed: {}", e));
+                self.notify_finished("Export failed", &e);
+            }
+        }
+    }
+
+    /// Progress dialog of the running export: rows written and a Cancel button
+    fn show_export_window(&mut self, ctx: &egui::Context) {
+        let Some(running) = &self.running_export else {
+            return;
+        };
+        let job = &running.job;
+
+        egui::Window::new("Exporting")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.add(
+                    egui::ProgressBar::new(job.fraction())
+                        .desired_width(300.0)
+                        .show_percentage(),
+                );
+                ui.label(format!("{} / {} rows written", job.rows_done, job.rows_total));
+                ui.add_space(5.0);
+                if job.is_cancelled() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Cancelling...");
+                    });
+                } else if ui.button("Cancel").clicked() {
+                    job.cancel();
+                }
+            });
+    }
+
+    /// Push the listed files to the configured cloud spreadsheet on a background thread
+    fn start_cloud_export(&mut self) {
+        let settings = self.settings.cloud_export.clone();
+        if let Some(missing) = settings.missing() {
+            self.error_message = Some(format!("Cloud export: enter the {} first", missing));
+            return;
+        }
+        let _ = self.settings.save();
+        let options = self.export_options(ExportFormat::Csv);
+        self.cloud_export = Some(CloudExportJob::spawn(settings, self.listed_files(), options));
+    }
+
+    /// Report the cloud export once it has finished
+    fn check_cloud_export(&mut self) {
+        let Some(job) = &mut self.cloud_export else {
+            return;
+        };
+        let Some(result) = job.poll() else {
+            return;
+        };
+        let target = job.target;
+        self.cloud_export = None;
+
+        match result {
+            Ok(outcome) => {
+                // Later exports update the spreadsheet this one created
+                if let Some(id) = outcome.created_spreadsheet {
+                    self.settings.cloud_export.google_spreadsheet_id = id;
+                    let _ = self.settings.save();
+                }
+                self.status_message = format!("Exported {} files to {}: {}", outcome.rows, target.label(), outcome.url);
+                self.error_message = None;
+                self.show_cloud_export = false;
+                self.notify_finished("Cloud export finished", &self.status_message.clone());
+                let outputs = [outcome.url];
+                let summary = hooks::summary(HookEvent::Export, "gui", &self.selected_folders, &self.listed_files(), &outputs);
+                self.run_hooks(HookEvent::Export, summary);
+            }
+            Err(e) if e == csv_export::CANCELLED => {
+                self.status_message = String::from("Cloud export cancelled");
+                self.error_message = None;
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Cloud export failed: {}", e));
+                self.notify_finished("Cloud export failed", &e);
+            }
+        }
+    }
+
+    /// Target setup, then the sign-in code and upload progress of the running cloud export
+    fn show_cloud_export_window(&mut self, ctx: &egui::Context) {
+        if !self.show_cloud_export && self.cloud_export.is_none() {
+            return;
+        }
+
+        let mut open = true;
+        let mut start = false;
+        egui::Window::new("Export to cloud")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if let Some(job) = &self.cloud_export {
+                    if let Some((url, code)) = &job.sign_in {
+                        ui.label(format!("Sign in to {} to allow the export:", job.target.label()));
+                        ui.horizontal(|ui| {
+                            ui.label("1. Open");
+                            ui.hyperlink(url);
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("2. Enter the code");
+                            ui.label(egui::RichText::new(code).monospace().strong().size(18.0));
+                            if ui.small_button("📋").on_hover_text("Copy the code").clicked() {
+                                ctx.copy_text(code.clone());
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Waiting for sign-in...");
+                        });
+                    } else {
+                        ui.add(
+                            egui::ProgressBar::new(job.fraction())
+                                .desired_width(300.0)
+                                .show_percentage(),
+                        );
+                        ui.label(format!("{} / {} rows uploaded", job.rows_done, job.rows_total));
+                    }
+                    ui.add_space(5.0);
+                    if job.is_cancelled() {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Cancelling...");
+                        });
+                    } else if ui.button("Cancel").clicked() {
+                        job.cancel();
+                    }
+                    return;
+                }
+
+                let cloud = &mut self.settings.cloud_export;
+                egui::Grid::new("cloud_export_settings")
+                    .num_columns(2)
+                    .spacing([10.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("Service:");
+                        egui::ComboBox::from_id_salt("cloud_target")
+                            .selected_text(cloud.target.label())
+                            .show_ui(ui, |ui| {
+                                for target in CloudTarget::ALL {
+                                    ui.selectable_value(&mut cloud.target, target, target.label());
+                                }
+                            });
+                        ui.end_row();
+
+                        match cloud.target {
+                            CloudTarget::GoogleSheets => {
+                                ui.label("Client ID:");
+                                ui.text_edit_singleline(&mut cloud.google_client_id)
+                                    .on_hover_text("OAuth client of type \"TVs and Limited Input devices\" in your Google Cloud project");
+                                ui.end_row();
+                                ui.label("Client secret:");
+                                ui.add(egui::TextEdit::singleline(&mut cloud.google_client_secret).password(true));
+                                ui.end_row();
+                                ui.label("Spreadsheet ID:");
+                                ui.text_edit_singleline(&mut cloud.google_spreadsheet_id)
+                                    .on_hover_text("Empty: a new spreadsheet is created and reused by later exports. Only spreadsheets created by this app can be updated");
+                                ui.end_row();
+                            }
+                            CloudTarget::Microsoft365 => {
+                                ui.label("Client ID:");
+                                ui.text_edit_singleline(&mut cloud.microsoft_client_id)
+                                    .on_hover_text("Application ID of an Entra ID app registration with public client flows allowed");
+                                ui.end_row();
+                                ui.label("Tenant:");
+                                ui.text_edit_singleline(&mut cloud.microsoft_tenant)
+                                    .on_hover_text("Tenant ID or domain; \"organizations\" for any work account");
+                                ui.end_row();
+                                ui.label("Drive:");
+                                ui.text_edit_singleline(&mut cloud.microsoft_drive)
+                                    .on_hover_text("\"me/drive\" for your OneDrive, \"sites/{site-id}/drive\" for a SharePoint library");
+                                ui.end_row();
+                                ui.label("Workbook:");
+                                ui.text_edit_singleline(&mut cloud.microsoft_workbook)
+                                    .on_hover_text("Path of an existing .xlsx file in the drive, e.g. Inventory/files.xlsx");
+                                ui.end_row();
+                            }
+                        }
+
+                        ui.label("Sheet:");
+                        ui.text_edit_singleline(&mut cloud.sheet_name)
+                            .on_hover_text("Its contents are replaced by the listing; created if missing");
+                        ui.end_row();
+                    });
+
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    let missing = cloud.missing();
+                    let button = ui.add_enabled(missing.is_none(), egui::Button::new(format!("Export {} files", self.filtered_files.len())));
+                    if let Some(missing) = missing {
+                        ui.label(format!("Enter the {}", missing));
+                    }
+                    start = button.clicked();
+                });
+            });
+
+        if start {
+            self.start_cloud_export();
+        }
+        if !open {
+            // Closing the window cancels a running export
+            if let Some(job) = &self.cloud_export {
+                job.cancel();
+            }
+            self.show_cloud_export = false;
+            let _ = self.settings.save();
+        }
+    }
+
+    /// Prepare the report e-mail from the listed files
+    fn open_report_draft(&mut self) {
+        let (subject, body) = report_mail::summary(
+            &self.listed_files(),
+            &self.selected_folders,
+            &self.settings.size_format,
+            self.settings.calendar,
+        );
+        self.report_draft = Some(ReportDraft { subject, body });
+    }
+
+    /// Report the e-mail once it was sent or handed to the mail app
+    fn check_report_mail(&mut self) {
+        let Some(receiver) = &self.report_mail_receiver else {
+            return;
+        };
+        let Ok(result) = receiver.try_recv() else {
+            return;
+        };
+        self.report_mail_receiver = None;
+        match result {
+            Ok(status) => {
+                self.status_message = status;
+                self.error_message = None;
+                self.report_draft = None;
+                self.notify_finished("Report sent", &self.status_message.clone());
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Sending the report failed: {}", e));
+                self.notify_finished("Sending the report failed", &e);
+            }
+        }
+    }
+
+    /// Recipients, delivery and the editable summary of the report e-mail
+    fn show_report_mail_window(&mut self, ctx: &egui::Context) {
+        let Some(draft) = &mut self.report_draft else {
+            return;
+        };
+
+        let mut open = true;
+        let mut send = false;
+        let sending = self.report_mail_receiver.is_some();
+        let mail = &mut self.settings.report_mail;
+        egui::Window::new("Send report")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Grid::new("report_mail")
+                    .num_columns(2)
+                    .spacing([10.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("To:");
+                        ui.text_edit_singleline(&mut mail.to)
+                            .on_hover_text("Separate several addresses with commas");
+                        ui.end_row();
+                        ui.label("Subject:");
+                        ui.add(egui::TextEdit::singleline(&mut draft.subject).desired_width(400.0));
+                        ui.end_row();
+                        ui.label("Send with:");
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut mail.delivery, MailDelivery::MailApp, "Mail app")
+                                .on_hover_text("Opens a new message with the report attached for you to send");
+                            ui.radio_value(&mut mail.delivery, MailDelivery::Smtp, "SMTP server")
+                                .on_hover_text("Sends directly, e.g. for a weekly report");
+                        });
+                        ui.end_row();
+
+                        if mail.delivery == MailDelivery::Smtp {
+                            ui.label("Server:");
+                            ui.horizontal(|ui| {
+                                ui.text_edit_singleline(&mut mail.smtp_host);
+                                ui.add(egui::DragValue::new(&mut mail.smtp_port).range(1..=65535));
+                                egui::ComboBox::from_id_salt("smtp_security")
+                                    .selected_text(mail.smtp_security.label())
+                                    .show_ui(ui, |ui| {
+                                        for security in SmtpSecurity::ALL {
+                                            ui.selectable_value(&mut mail.smtp_security, security, security.label());
+                                        }
+                                    });
+                            });
+                            ui.end_row();
+                            ui.label("From:");
+                            ui.text_edit_singleline(&mut mail.from);
+                            ui.end_row();
+                            ui.label("User name:");
+                            ui.text_edit_singleline(&mut mail.smtp_user)
+                                .on_hover_text("Empty: the sender address");
+                            ui.end_row();
+                            ui.label("Password:");
+                            ui.add(egui::TextEdit::singleline(&mut mail.smtp_password).password(true))
+                                .on_hover_text("Saved in settings.json; the FILE_LISTER_SMTP_PASSWORD environment variable is used instead when set");
+                            ui.end_row();
+                        }
+                    });
+
+                ui.add_space(5.0);
+                ui.add(
+                    egui::TextEdit::multiline(&mut draft.body)
+                        .font(egui::TextStyle::Monospace)
+                        .desired_width(500.0)
+                        .desired_rows(12),
+                );
+
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    if sending {
+                        ui.spinner();
+                        ui.label("Sending...");
+                        return;
+                    }
+                    let missing = mail.missing();
+                    send = ui.add_enabled(missing.is_none(), egui::Button::new("Send")).clicked();
+                    if let Some(missing) = missing {
+                        ui.label(format!("Enter the {}", missing));
+                    }
+                });
+            });
+
+        if send {
+            let (subject, body) = (draft.subject.clone(), draft.body.clone());
+            self.report_mail_receiver = Some(report_mail::spawn_send(
+                self.listed_files(),
+                self.export_options(ExportFormat::Csv),
+                subject,
+                body,
+                self.settings.report_mail.clone(),
+            ));
+            let _ = self.settings.save();
+        }
+        if !open {
+            self.report_draft = None;
+            let _ = self.settings.save();
+        }
+    }
+
+    /// Status line for a finished export ("to: path" or "into N files in: folder")
+    fn export_summary(file_count: usize, written: &[PathBuf]) -> String {
+        match written {
+            [path] => format!("Exported {} files to: {}", file_count, path.display()),
+            _ => format!(
+                "Exported {} files into {} files in: {}",
+                file_count,
+                written.len(),
+                written
+                    .first()
+                    .and_then(|p| p.parent())
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default()
+            ),
+        }
+    }
+
+    /// Generate a thumbnails/ folder next to the CSV on the preview service, then write the CSV.
+    /// HTML reports embed the thumbnails, so theirs go to a temporary folder instead.
+    fn start_thumbnail_export(&mut self, path: &std::path::Path) {
+        let embedded_dir = (ExportFormat::from_path(path) == ExportFormat::Html)
+            .then(|| std::env::temp_dir().join(format!("file-lister-report-{}", std::process::id())));
+        let dir = match &embedded_dir {
+            Some(dir) => dir.clone(),
+            None => path
+                .parent()
+                .map(|p| p.join("thumbnails"))
+                .unwrap_or_else(|| PathBuf::from("thumbnails")),
+        };
+        let export_files = self.export_files();
+        let files: Vec<(String, Option<ThumbnailKind>)> = export_files
+            .iter()
+            .map(|f| {
+                // Skip online-only cloud files unless previews may download them
+                let kind = if f.is_cloud_placeholder && !self.settings.preview_cloud_files {
+                    None
+                } else {
+                    ThumbnailKind::from_extension(f.extension())
+                };
+                (f.absolute_path().to_string(), kind)
+            })
+            .collect();
+
+        let (tx, rx) = mpsc::channel();
+        let job = ThumbnailExportJob {
+            files,
+            dir,
+            // Report thumbnails don't depend on the screen's pixel density
+            options: self.thumbnail_options(self.settings.preview_max_size.round() as u32),
+            reply: tx,
+        };
+        let service = self.preview_service.get_or_insert_with(PreviewService::start);
+        let _ = service.sender.send(PreviewJob::Export(job));
+
+        self.thumbnail_export = Some(PendingThumbnailExport {
+            csv_path: path.to_path_buf(),
+            files: export_files,
+            receiver: rx,
+            embedded_dir,
+        });
+        self.status_message = String::from("Generating thumbnails...");
+    }
+
+    /// Write the CSV once its thumbnails are ready
+    fn check_thumbnail_export(&mut self) {
+        let Some(pending) = &self.thumbnail_export else {
+            return;
+        };
+        let Ok(result) = pending.receiver.try_recv() else {
+            return;
+        };
+        let Some(pending) = self.thumbnail_export.take() else {
+            return;
+        };
+
+        match result {
+            Ok(names) => {
+                // CSV references thumbnails relative to its own location, HTML reads them from the temporary folder
+                let thumbnails: Vec<Option<String>> = names
+                    .into_iter()
+                    .map(|name| {
+                        name.map(|n| match &pending.embedded_dir {
+                            Some(dir) => dir.join(n).to_string_lossy().to_string(),
+                            None => format!("thumbnails/{}", n),
+                        })
+                    })
+                    .collect();
+                let count = thumbnails.iter().filter(|t| t.is_some()).count();
+                let options = ExportOptions {
+                    split: self.settings.export_split,
+                    ..self.export_options(ExportFormat::from_path(&pending.csv_path))
+                };
+                self.running_export = Some(RunningExport {
+                    job: ExportJob::spawn(pending.files, Some(thumbnails), options, pending.csv_path),
+                    thumbnail_count: Some(count),
+                    embedded_dir: pending.embedded_dir,
+                });
+                self.status_message = String::from("Writing report...");
+            }
+            Err(e) => {
+                if let Some(dir) = &pending.embedded_dir {
+                    let _ = std::fs::remove_dir_all(dir);
+                }
+                self.error_message = Some(format!("Export failed: {}", e));
+                self.notify_finished("Export failed", &e);
+            }
+        }
+    }
+
+    fn delete_file(&mut self, file_path: &str) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+        let path = std::path::Path::new(file_path);
+        match std::fs::remove_file(path) {
+            Ok(_) => {
+                self.status_message = format!("Deleted: {}", path.file_name().unwrap_or_default().to_string_lossy());
+                self.error_message = None;
+                self.update_rows(&HashSet::from([file_path.to_string()]), Vec::new());
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Delete failed: {}", e));
+                if elevation::is_access_denied(&e) {
+                    self.offer_elevation(vec![ElevatedOp::Delete { path: path.to_path_buf() }]);
+                }
+            }
+        }
+    }
+
+    fn move_file(&mut self, file_path: &str) {
+        let source = std::path::Path::new(file_path);
+        if let Some(file_name) = source.file_name() {
+            self.pending_transfer = Some(PendingTransfer {
+                files: self.with_sidecars(vec![(file_path.to_string(), file_name.to_string_lossy().to_string())]),
+                mode: TransferMode::Move,
+            });
+        }
+    }
+
+    /// Open the Move / Copy window in link mode for the clicked row,
+    /// or for the whole selection if the row is part of it
+    fn link_files(&mut self, idx: usize, mode: TransferMode) {
+        let mut indices: Vec<usize> = if self.selected_files.contains(&idx) {
+            self.selected_files.iter().copied().collect()
+        } else {
+            vec![idx]
+        };
+        indices.sort_unstable();
+
+        let files: Vec<(String, String)> = indices
+            .into_iter()
+            .filter_map(|i| self.filtered_files.get(i))
+            .map(|f| (f.absolute_path().to_string(), f.full_name().to_string()))
+            .collect();
+        self.pending_transfer = Some(PendingTransfer { files, mode });
+    }
+
+    fn move_selected_files(&mut self) {
+        if self.selected_files.is_empty() || self.refuse_if_read_only() {
+            return;
+        }
+
+        let files: Vec<(String, String)> = self.selected_files
+            .iter()
+            .filter_map(|&idx| {
+                self.filtered_files.get(idx).map(|f| {
+                    (f.absolute_path().to_string(), f.full_name().to_string())
+                })
+            })
+            .collect();
+
+        self.pending_transfer = Some(PendingTransfer {
+            files: self.with_sidecars(files),
+            mode: TransferMode::Move,
+        });
+    }
+
+    /// Move, copy or link files (absolute path, file name) into dest_folder
+    /// Offer to retry operations that failed with access denied as administrator (Windows)
+    fn offer_elevation(&mut self, ops: Vec<ElevatedOp>) {
+        if !ops.is_empty() && elevation::is_supported() {
+            self.elevation_offer = Some(ops);
+        }
+    }
+
+    /// Run the offered operations in an elevated helper (UAC prompt) in the background
+    fn retry_elevated(&mut self, ops: Vec<ElevatedOp>) {
+        let (tx, rx) = mpsc::channel();
+        self.elevation_receiver = Some(rx);
+        self.status_message = format!("Retrying {} operations as administrator...", ops.len());
+        thread::spawn(move || {
+            let _ = tx.send(elevation::run_elevated(&ops));
+        });
+    }
+
+    /// Check for results of the elevated helper
+    fn check_elevation(&mut self) {
+        let Some(receiver) = &self.elevation_receiver else {
+            return;
+        };
+        let Ok(result) = receiver.try_recv() else {
+            return;
+        };
+        self.elevation_receiver = None;
+
+        match result {
+            Ok(results) => {
+                let total = results.len();
+                let errors: Vec<String> = results.into_iter().filter_map(|r| r.err()).collect();
+                self.status_message = format!(
+                    "Administrator retry: {} succeeded, {} failed",
+                    total - errors.len(),
+                    errors.len()
+                );
+                self.error_message = if errors.is_empty() { None } else { Some(errors.join("; ")) };
+                self.scan_all_folders();
+            }
+            Err(e) => self.error_message = Some(e),
+        }
+    }
+
+    /// Show the "Access denied" window with "Retry as administrator"
+    fn show_elevation_window(&mut self, ctx: &egui::Context) {
+        let Some(ops) = &self.elevation_offer else {
+            return;
+        };
+
+        let mut retry = false;
+        let mut dismiss = false;
+        egui::Window::new("Access Denied")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!("{} operation(s) failed because access was denied:", ops.len()));
+                for op in ops.iter().take(10) {
+                    ui.label(egui::RichText::new(op.path().display().to_string()).weak());
+                }
+                if ops.len() > 10 {
+                    ui.label(format!("... and {} more", ops.len() - 10));
+                }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("🛡 Retry as administrator")
+                        .on_hover_text("Runs just these operations in an elevated helper (UAC prompt)")
+                        .clicked()
+                    {
+                        retry = true;
+                    }
+                    if ui.button("Dismiss").clicked() {
+                        dismiss = true;
+                    }
+                });
+            });
+
+        if retry {
+            if let Some(ops) = self.elevation_offer.take() {
+                self.retry_elevated(ops);
+            }
+        } else if dismiss {
+            self.elevation_offer = None;
+        }
+    }
+
+    /// Update the list in place after the app removed, renamed or added files, instead of
+    /// rescanning: filters, sorting and the selection are kept (use Refresh for other changes)
+    fn update_rows(&mut self, removed: &HashSet<String>, mut added: Vec<FileInfo>) {
+        let selected: HashSet<String> = self.selected_files
+            .iter()
+            .filter_map(|&idx| self.filtered_files.get(idx))
+            .map(|f| f.absolute_path().to_string())
+            .collect();
+        let replaced: HashSet<String> = added.iter().map(|f| f.absolute_path().to_string()).collect();
+        self.files
+            .retain(|file| !removed.contains(file.absolute_path()) && !replaced.contains(file.absolute_path()));
+        if self.settings.resolve_owners {
+            owner::resolve_owners(&mut added);
+        }
+        let hash_added = !added.is_empty();
+        self.files.extend(added);
+        self.stale_paths.retain(|path| !removed.contains(path));
+        self.sort_files();
+        self.select_paths(&selected);
+        if hash_added {
+            self.start_content_hashing();
+            self.start_text_counting();
+        }
+    }
+
+    /// Row for a file the app moved or copied in, if it lies in a scanned folder
+    /// (None outside them, or below a subfolder when the scan isn't recursive)
+    fn scanned_file(&self, path: &Path) -> Option<FileInfo> {
+        let canonical = path.canonicalize().ok().filter(|p| p.is_file())?;
+        let parent = canonical.parent()?;
+        self.selected_folders.iter().find_map(|root| {
+            let canonical_root = root.canonicalize().ok()?;
+            let inside = if self.recursive { parent.starts_with(&canonical_root) } else { parent == canonical_root };
+            inside.then(|| {
+                let mut file = file_scanner::file_info(&canonical, &canonical_root);
+                file_scanner::tag_source_folder(std::slice::from_mut(&mut file), root);
+                file
+            })
+        })
+    }
+
+    /// Stat paths before operating on them: missing ones are marked stale.
+    /// Returns the paths that still exist.
+    fn skip_missing(&mut self, paths: &[String]) -> HashSet<String> {
+        let mut existing = HashSet::new();
+        for path in paths {
+            if std::fs::symlink_metadata(path).is_ok() {
+                existing.insert(path.clone());
+            } else {
+                self.stale_paths.insert(path.clone());
+            }
+        }
+        existing
+    }
+
+    /// Re-read the stale rows from disk: drop files that are gone, update the rest
+    fn refresh_stale_rows(&mut self) {
+        let stale = std::mem::take(&mut self.stale_paths);
+        let mut removed = 0;
+        let mut updated = 0;
+        self.files.retain_mut(|file| {
+            if !stale.contains(file.absolute_path()) {
+                return true;
+            }
+            match file_scanner::refresh_file(file) {
+                Some(fresh) => {
+                    *file = fresh;
+                    updated += 1;
+                    true
+                }
+                None => {
+                    removed += 1;
+                    false
+                }
+            }
+        });
+        self.refresh_folder_rows();
+        self.apply_filter();
+        self.status_message = format!("Refreshed stale rows: {} removed, {} still present", removed, updated);
+        self.error_message = None;
+    }
+
+    /// Start moving/copying/linking files into dest_folder as a background job
+    fn transfer_files(&mut self, files: &[(String, String)], dest_folder: &std::path::Path, mode: TransferMode) {
+        if mode == TransferMode::Move && self.refuse_if_read_only() {
+            return;
+        }
+        if self.refuse_if_not_allowed(dest_folder) {
+            return;
+        }
+        if self.bulk_job.is_some() {
+            self.error_message = Some("Another move/copy/delete is still running".to_string());
+            return;
+        }
+
+        let paths: Vec<String> = files.iter().map(|(path, _)| path.clone()).collect();
+        let existing = self.skip_missing(&paths);
+        if existing.is_empty() {
+            self.error_message = Some("The files no longer exist (moved or deleted since the scan)".to_string());
+            return;
+        }
+        let items: Vec<JobItem> = files
+            .iter()
+            .filter(|(path, _)| existing.contains(path))
+            .map(|(path, name)| JobItem {
+                path: PathBuf::from(path),
+                name: name.clone(),
+            })
+            .collect();
+
+        // Fail before starting rather than halfway through: moves within a volume need no space
+        let mut estimate = None;
+        if matches!(mode, TransferMode::Copy | TransferMode::Move) {
+            let copied: Vec<(String, u64)> = items
+                .iter()
+                .filter(|item| mode == TransferMode::Copy || !disk_space::same_volume(&item.path, dest_folder))
+                .filter_map(|item| std::fs::metadata(&item.path).ok().map(|m| (item.name.clone(), m.len())))
+                .collect();
+            let transfer = disk_space::estimate_transfer(&copied, dest_folder);
+            let size_format = self.settings.size_format;
+            if !transfer.too_large.is_empty() {
+                let names: Vec<String> = transfer
+                    .too_large
+                    .iter()
+                    .take(10)
+                    .map(|(name, size)| format!("  {} ({})", name, format_size(*size, &size_format)))
+                    .collect();
+                let more = transfer.too_large.len().saturating_sub(names.len());
+                self.error_message = Some(format!(
+                    "{} file(s) are larger than FAT32's 4 GB limit and can't be copied to {}:\n{}{}",
+                    transfer.too_large.len(),
+                    dest_folder.display(),
+                    names.join("\n"),
+                    if more > 0 { format!("\n  ... and {} more", more) } else { String::new() }
+                ));
+                return;
+            }
+            if let Err(e) =
+                disk_space::check_fits(dest_folder, transfer.on_destination, |bytes| format_size(bytes, &size_format))
+            {
+                self.error_message = Some(e);
+                return;
+            }
+            estimate = Some(transfer);
+        }
+
+        let dest = dest_folder.to_path_buf();
+        let items_count = items.len();
+        let sources: Vec<(PathBuf, String)> = items.iter().map(|item| (item.path.clone(), item.name.clone())).collect();
+        let job = Job::spawn(items, move |item, on_bytes| {
+            let source = item.path.as_path();
+            let dest_path = dest.join(&item.name);
+            match mode {
+                TransferMode::Copy => jobs::copy_with_progress(source, &dest_path, on_bytes).map_err(|e| {
+                    // Don't leave a truncated copy behind
+                    let _ = std::fs::remove_file(&dest_path);
+                    JobError::from(e)
+                }),
+                TransferMode::Move => std::fs::rename(source, &dest_path)
+                    .or_else(|_| {
+                        // Try copy + delete for cross-device moves
+                        jobs::copy_with_progress(source, &dest_path, on_bytes)?;
+                        std::fs::remove_file(source)
+                    })
+                    .map_err(JobError::from),
+                TransferMode::Symlink => links::create_symlink(source, &dest_path).map_err(JobError::from),
+                TransferMode::Shortcut => {
+                    links::create_shortcut(source, &dest.join(format!("{}.lnk", item.name))).map_err(JobError::from)
+                }
+            }
+        });
+
+        self.status_message = format!("{}: {} files → {}", mode.label(), items_count, dest_folder.display());
+        // Cluster rounding only matters when it adds up (many small files on a USB stick)
+        if let Some(estimate) = estimate.filter(|e| e.on_destination > e.bytes) {
+            self.status_message.push_str(&format!(
+                " ({} on disk with {} clusters)",
+                format_size(estimate.on_destination, &self.settings.size_format),
+                format_size(estimate.cluster_size, &self.settings.size_format)
+            ));
+        }
+        if items_count < files.len() {
+            self.status_message.push_str(&format!(
+                " ({} skipped: no longer exist)",
+                files.len() - items_count
+            ));
+        }
+        self.settings.remember_destination(&dest_folder.to_string_lossy());
+        let _ = self.settings.save();
+        self.bulk_job = Some(BulkJob {
+            job,
+            kind: BulkJobKind::Transfer {
+                dest_folder: dest_folder.to_path_buf(),
+                mode,
+                items: sources,
+            },
+        });
+    }
+
+    /// Check the running bulk job; report and rescan once it has finished
+    fn check_bulk_job(&mut self) {
+        let Some(bulk_job) = &mut self.bulk_job else {
+            return;
+        };
+        let Some(outcome) = bulk_job.job.poll() else {
+            return;
+        };
+        let Some(bulk_job) = self.bulk_job.take() else {
+            return;
+        };
+
+        let JobOutcome { done, mut errors, missing, access_denied, bytes_done, elapsed } = outcome;
+        // Operations that can be retried as administrator
+        let denied_ops: Vec<ElevatedOp> = access_denied
+            .into_iter()
+            .filter_map(|path| match &bulk_job.kind {
+                BulkJobKind::Delete { .. } => Some(ElevatedOp::Delete { path }),
+                BulkJobKind::Transfer { dest_folder, mode, .. } => {
+                    let to = dest_folder.join(path.file_name()?);
+                    match mode {
+                        TransferMode::Move => Some(ElevatedOp::Move { from: path, to }),
+                        TransferMode::Copy => Some(ElevatedOp::Copy { from: path, to }),
+                        TransferMode::Symlink | TransferMode::Shortcut => None,
+                    }
+                }
+                BulkJobKind::Retention { .. }
+                | BulkJobKind::RetentionUndo { .. }
+                | BulkJobKind::Split { .. }
+                | BulkJobKind::Join { .. } => None,
+            })
+            .collect();
+        self.offer_elevation(denied_ops);
+
+        let (title, summary) = match &bulk_job.kind {
+            BulkJobKind::Transfer { dest_folder, mode, .. } => {
+                if *mode == TransferMode::Move {
+                    self.selected_files.clear();
+                }
+                (
+                    format!("{} finished", mode.label()),
+                    format!("{} {} files → {}", mode.done_label(), done, dest_folder.display()),
+                )
+            }
+            BulkJobKind::Delete { .. } => {
+                self.selected_files.clear();
+                ("Delete finished".to_string(), format!("Deleted {} files", done))
+            }
+            BulkJobKind::Retention { run } => {
+                self.selected_files.clear();
+                let run = std::mem::replace(&mut *run.lock().unwrap_or_else(|e| e.into_inner()), RetentionRun::start());
+                let summary = format!("Retention rules: {}", run.summary());
+                if let Err(e) = RetentionLog::record(run) {
+                    errors.push(e);
+                }
+                ("Retention rules finished".to_string(), summary)
+            }
+            BulkJobKind::RetentionUndo { started_at, restored } => {
+                let restored = restored.lock().unwrap_or_else(|e| e.into_inner());
+                if let Err(e) = RetentionLog::mark_moved_back(*started_at, &restored) {
+                    errors.push(e);
+                }
+                ("Undo finished".to_string(), format!("Moved back {} files", done))
+            }
+            BulkJobKind::Split { file_name, dest_folder } => {
+                let summary = if done > 0 {
+                    format!("Split {} into parts in {}", file_name, dest_folder.display())
+                } else {
+                    format!("{} was not split", file_name)
+                };
+                ("Split finished".to_string(), summary)
+            }
+            BulkJobKind::Join { joiner } => {
+                let joiner = joiner.lock().unwrap_or_else(|e| e.into_inner());
+                let summary = if joiner.is_complete() {
+                    format!("Joined {} (SHA-256 verified)", joiner.output().display())
+                } else {
+                    joiner.discard();
+                    if errors.is_empty() {
+                        errors.push("Parts are missing".to_string());
+                    }
+                    format!("{} was not joined", joiner.output().display())
+                };
+                ("Join finished".to_string(), summary)
+            }
+        };
+
+        self.status_message = format!(
+            "{} ({} in {})",
+            summary,
+            format_size(bytes_done, &self.settings.size_format),
+            jobs::format_duration(elapsed)
+        );
+        if !missing.is_empty() {
+            self.status_message.push_str(&format!(", {} skipped (no longer exist)", missing.len()));
+        }
+        if errors.is_empty() {
+            self.error_message = None;
+        } else {
+            self.status_message.push_str(&format!(", {} failed", errors.len()));
+            self.error_message = Some(errors.join("; "));
+        }
+        self.notify_finished(&title, &self.status_message.clone());
+
+        // Moves, copies and deletes update the list in place; the other jobs rescan
+        let gone = |path: &PathBuf| std::fs::symlink_metadata(path).is_err();
+        match &bulk_job.kind {
+            BulkJobKind::Transfer { dest_folder, mode, items } if matches!(mode, TransferMode::Move | TransferMode::Copy) => {
+                let removed: HashSet<String> = items
+                    .iter()
+                    .filter(|(path, _)| *mode == TransferMode::Move && gone(path))
+                    .map(|(path, _)| path.to_string_lossy().to_string())
+                    .collect();
+                let added: Vec<FileInfo> = items.iter().filter_map(|(_, name)| self.scanned_file(&dest_folder.join(name))).collect();
+                self.update_rows(&removed, added);
+            }
+            BulkJobKind::Delete { paths } => {
+                let removed: HashSet<String> =
+                    paths.iter().filter(|path| gone(path)).map(|path| path.to_string_lossy().to_string()).collect();
+                self.scan_report.empty_folders.retain(|f| !removed.contains(f.absolute_path()));
+                self.update_rows(&removed, Vec::new());
+            }
+            _ => self.scan_all_folders(),
+        }
+        if let Some(right_pane) = &mut self.right_pane {
+            right_pane.rescan();
+        }
+    }
+
+    /// Dry run of the retention rules on a background thread
+    fn start_retention_dry_run(&mut self) {
+        let rules = self.settings.retention_rules.clone();
+        let mut folders = rules.iter().filter(|rule| rule.enabled).flat_map(RetentionRule::folders);
+        if folders.any(|folder| self.refuse_if_not_allowed(folder)) {
+            return;
+        }
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(retention::plan(&rules));
+        });
+        self.retention_plan = None;
+        self.retention_receiver = Some(receiver);
+    }
+
+    fn check_retention_dry_run(&mut self) {
+        let Some(receiver) = &self.retention_receiver else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(Ok(plan)) => self.retention_plan = Some(plan),
+            Ok(Err(e)) => self.error_message = Some(e),
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {}
+        }
+        self.retention_receiver = None;
+    }
+
+    /// Carry out the dry run's plan as a bulk job; every file is logged for undo
+    fn run_retention(&mut self) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+        if self.bulk_job.is_some() {
+            self.error_message = Some("Another move/copy/delete is still running".to_string());
+            return;
+        }
+        let Some(plan) = self.retention_plan.take() else {
+            return;
+        };
+        let mut paths = plan.iter().flat_map(|item| std::iter::once(&item.source).chain(&item.destination));
+        if paths.any(|path| self.refuse_if_not_allowed(path)) {
+            return;
+        }
+
+        let items: Vec<JobItem> = plan
+            .iter()
+            .map(|item| JobItem {
+                path: item.source.clone(),
+                name: item.source.display().to_string(),
+            })
+            .collect();
+        let items_count = items.len();
+        let planned: HashMap<PathBuf, PlannedItem> =
+            plan.into_iter().map(|item| (item.source.clone(), item)).collect();
+        let run = Arc::new(Mutex::new(RetentionRun::start()));
+        let log = Arc::clone(&run);
+        let job = Job::spawn(items, move |job_item, on_bytes| {
+            let item = &planned[&job_item.path];
+            let result = retention::apply(item, on_bytes);
+            log.lock().unwrap_or_else(|e| e.into_inner()).entries.push(LogEntry::new(item, &result));
+            result
+        });
+
+        self.status_message = format!("Retention rules: {} files", items_count);
+        self.bulk_job = Some(BulkJob {
+            job,
+            kind: BulkJobKind::Retention { run },
+        });
+    }
+
+    /// Move the files of the last logged retention run back
+    fn undo_retention(&mut self) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+        if self.bulk_job.is_some() {
+            self.error_message = Some("Another move/copy/delete is still running".to_string());
+            return;
+        }
+        let log = RetentionLog::load();
+        let Some(run) = log.last_undoable() else {
+            self.error_message = Some("No retention run to undo".to_string());
+            return;
+        };
+
+        let moves: HashMap<PathBuf, PathBuf> = run.moves().into_iter().collect();
+        let started_at = run.started_at;
+        if moves.iter().any(|(destination, source)| self.refuse_if_not_allowed(destination) || self.refuse_if_not_allowed(source)) {
+            return;
+        }
+        let items: Vec<JobItem> = moves
+            .keys()
+            .map(|destination| JobItem {
+                path: destination.clone(),
+                name: destination.display().to_string(),
+            })
+            .collect();
+        self.status_message = format!("Undo retention run: {} files", items.len());
+        let restored = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&restored);
+        let job = Job::spawn(items, move |item, on_bytes| {
+            let source = &moves[&item.path];
+            retention::move_file(&item.path, source, on_bytes)?;
+            log.lock().unwrap_or_else(|e| e.into_inner()).push(source.clone());
+            Ok(())
+        });
+        self.bulk_job = Some(BulkJob {
+            job,
+            kind: BulkJobKind::RetentionUndo { started_at, restored },
+        });
+    }
+
+    /// Split a file into parts of the configured size as a bulk job
+    fn start_split(&mut self, path: PathBuf, dest_folder: PathBuf) {
+        if self.bulk_job.is_some() {
+            self.error_message = Some("Another move/copy/delete is still running".to_string());
+            return;
+        }
+        if self.refuse_if_not_allowed(&dest_folder) {
+            return;
+        }
+        let required = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let size_format = self.settings.size_format;
+        if let Err(e) = disk_space::check_fits(&dest_folder, required, |bytes| format_size(bytes, &size_format)) {
+            self.error_message = Some(e);
+            return;
+        }
+
+        let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let part_size = self.settings.split_part_size;
+        let dest = dest_folder.clone();
+        let items = vec![JobItem {
+            path,
+            name: file_name.clone(),
+        }];
+        let job = Job::spawn(items, move |item, on_bytes| split::split(&item.path, &dest, part_size, on_bytes).map(|_| ()));
+        self.status_message = format!("Splitting {}", file_name);
+        self.bulk_job = Some(BulkJob {
+            job,
+            kind: BulkJobKind::Split { file_name, dest_folder },
+        });
+    }
+
+    /// Join the parts listed in a split manifest as a bulk job (one item per part)
+    fn start_join(&mut self, manifest_path: &Path) {
+        if self.bulk_job.is_some() {
+            self.error_message = Some("Another move/copy/delete is still running".to_string());
+            return;
+        }
+        let joiner = match Joiner::open(manifest_path) {
+            Ok(joiner) => joiner,
+            Err(e) => {
+                self.error_message = Some(e);
+                return;
+            }
+        };
+        // The joined file is written next to the manifest
+        if let Some(folder) = joiner.output().parent() {
+            if self.refuse_if_not_allowed(folder) {
+                return;
+            }
+        }
+        let parts = joiner.parts();
+        let required: u64 = parts.iter().filter_map(|part| std::fs::metadata(part).ok()).map(|m| m.len()).sum();
+        let size_format = self.settings.size_format;
+        if let Some(folder) = manifest_path.parent() {
+            if let Err(e) = disk_space::check_fits(folder, required, |bytes| format_size(bytes, &size_format)) {
+                self.error_message = Some(e);
+                return;
+            }
+        }
+
+        let items: Vec<JobItem> = parts
+            .into_iter()
+            .map(|path| JobItem {
+                name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+                path,
+            })
+            .collect();
+        self.status_message = format!("Joining {}", joiner.output().display());
+        let joiner = Arc::new(Mutex::new(joiner));
+        let job_joiner = Arc::clone(&joiner);
+        let job = Job::spawn(items, move |item, on_bytes| {
+            job_joiner.lock().unwrap_or_else(|e| e.into_inner()).append(&item.path, on_bytes)
+        });
+        self.bulk_job = Some(BulkJob {
+            job,
+            kind: BulkJobKind::Join { joiner },
+        });
+    }
+
+    /// "Split into parts / join" window: part size and folder for a file, or the parts of a manifest
+    fn show_split_window(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.split_dialog else {
+            return;
+        };
+
+        let mut open = true;
+        let mut start = false;
+        let busy = self.bulk_job.is_some();
+        let size_format = self.settings.size_format;
+        let part_size = &mut self.settings.split_part_size;
+        let title = dialog.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        egui::Window::new(format!("Split / join: {}", title))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                match &dialog.manifest {
+                    Some(Ok(manifest)) => {
+                        ui.label(format!(
+                            "Join {} parts into {} ({})",
+                            manifest.parts.len(),
+                            manifest.file_name,
+                            format_size(manifest.size, &size_format)
+                        ));
+                        ui.weak("Every part and the joined file are checked against the SHA-256 in the manifest");
+                    }
+                    Some(Err(e)) => {
+                        ui.colored_label(egui::Color32::RED, e);
+                        return;
+                    }
+                    None => {
+                        ui.label(format!("Size: {}", format_size(dialog.file_size, &size_format)));
+                        ui.horizontal(|ui| {
+                            ui.label("Part size:");
+                            for (label, bytes) in split::PART_SIZE_PRESETS {
+                                ui.selectable_value(part_size, bytes, label);
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Custom:");
+                            let mut mb = *part_size / 1_000_000;
+                            if ui.add(egui::DragValue::new(&mut mb).range(1..=u64::MAX / 1_000_000).suffix(" MB")).changed() {
+                                *part_size = mb * 1_000_000;
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Save parts in:");
+                            if ui.button(Self::folder_label(&dialog.dest_folder.to_string_lossy())).clicked() {
+                                if let Some(folder) = rfd::FileDialog::new().set_directory(&dialog.dest_folder).pick_folder() {
+                                    dialog.dest_folder = folder;
+                                }
+                            }
+                        });
+                        ui.weak(format!(
+                            "{} parts + {}{} (sizes and SHA-256 for joining)",
+                            dialog.file_size.div_ceil((*part_size).max(1)).max(1),
+                            title,
+                            split::MANIFEST_SUFFIX
+                        ));
+                    }
+                }
+                ui.add_space(5.0);
+                let label = if dialog.manifest.is_some() { "Join" } else { "Split" };
+                start = ui.add_enabled(!busy, egui::Button::new(label)).clicked();
+                if busy {
+                    ui.weak("Waiting for the running move/copy/delete");
+                }
+            });
+
+        if start {
+            if let Some(dialog) = self.split_dialog.take() {
+                let _ = self.settings.save();
+                if dialog.manifest.is_some() {
+                    self.start_join(&dialog.path);
+                } else {
+                    self.start_split(dialog.path, dialog.dest_folder);
+                }
+            }
+        } else if !open {
+            self.split_dialog = None;
+        }
+    }
+
+    /// Open the duplicate manager on the groups of the duplicates view (names, content or similar images)
+    fn open_duplicate_manager(&mut self) {
+        if self.similar_images.as_ref().is_some_and(|s| s.receiver.is_some())
+            || self.content_duplicates.as_ref().is_some_and(|c| c.hashing.is_some())
+        {
+            self.status_message = "Wait for the hashing to finish".to_string();
+            return;
+        }
+        let (groups, basis) = match (&self.similar_images, &self.content_duplicates) {
+            (Some(similar), _) => (duplicates::by_group(&self.files, &similar.groups), "similar images"),
+            (None, Some(content)) => (duplicates::by_group(&self.files, &content.groups), "equal content"),
+            (None, None) => (duplicates::by_name(&self.files), "equal names"),
+        };
+        if groups.is_empty() {
+            self.status_message = "No duplicates to manage".to_string();
+            return;
+        }
+        let mut manager = DuplicateManager {
+            groups,
+            basis,
+            keep: HashSet::new(),
+            requested: HashSet::new(),
+        };
+        manager.apply_rule(self.settings.duplicate_keep_rule);
+        self.duplicate_manager = Some(manager);
+    }
+
+    /// Duplicate manager: one row of cards (thumbnail, folder, size, date, Keep) per group,
+    /// a keep rule for all groups, and delete / move for the files not kept
+    fn show_duplicate_manager_window(&mut self, ctx: &egui::Context) {
+        const CARD_WIDTH: f32 = 150.0;
+        const THUMB_HEIGHT: f32 = 100.0;
+        const ROW_HEIGHT: f32 = 230.0;
+
+        let Some(manager) = &mut self.duplicate_manager else {
+            return;
+        };
+
+        let mut open = true;
+        let mut rule = None;
+        let mut delete = false;
+        let mut move_rest = false;
+        let mut wanted: Vec<(String, ThumbnailKind)> = Vec::new();
+        let size_format = self.settings.size_format;
+        let calendar = self.settings.calendar;
+        let preview_cloud_files = self.settings.preview_cloud_files;
+        let (video_ready, pdfium_ready) = (Self::is_video_ready(), Self::is_pdfium_ready());
+        let (image_cache, preview_failed) = (&self.image_cache, &self.preview_failed);
+        let keep_rule = &mut self.settings.duplicate_keep_rule;
+
+        egui::Window::new(format!("Duplicate manager ({})", manager.basis))
+            .default_size([900.0, 600.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let rest = manager.rest();
+                let reclaimable: u64 = manager
+                    .groups
+                    .iter()
+                    .flatten()
+                    .filter(|f| !manager.keep.contains(f.absolute_path()))
+                    .map(|f| f.file_size)
+                    .sum();
+                ui.label(format!(
+                    "{} groups, {} files not kept ({})",
+                    manager.groups.len(),
+                    rest.len(),
+                    format_size(reclaimable, &size_format)
+                ));
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("duplicate_keep_rule")
+                        .selected_text(keep_rule.label())
+                        .show_ui(ui, |ui| {
+                            for candidate in KeepRule::ALL {
+                                ui.selectable_value(keep_rule, candidate, candidate.label());
+                            }
+                        });
+                    if ui.button("Apply to all groups").clicked() {
+                        rule = Some(*keep_rule);
+                    }
+                    ui.separator();
+                    let any = !rest.is_empty();
+                    delete = ui
+                        .add_enabled(any, egui::Button::new(format!("🗑 Delete {} not kept...", rest.len())))
+                        .clicked();
+                    move_rest = ui.add_enabled(any, egui::Button::new("📦 Move not kept...")).clicked();
+                });
+                if let Some(group) = manager.group_without_keeper() {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(220, 50, 50),
+                        format!("⚠ Group {} keeps no file: every copy would go", group + 1),
+                    );
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show_rows(
+                    ui,
+                    ROW_HEIGHT,
+                    manager.groups.len(),
+                    |ui, range| {
+                        for group_idx in range {
+                            let group = &manager.groups[group_idx];
+                            let group_size: u64 = group.iter().map(|f| f.file_size).sum();
+                            ui.strong(format!(
+                                "Group {}: {} files, {}",
+                                group_idx + 1,
+                                group.len(),
+                                format_size(group_size, &size_format)
+                            ));
+                            egui::ScrollArea::horizontal()
+                                .id_salt(("duplicate_group", group_idx))
+                                .max_height(ROW_HEIGHT - 24.0)
+                                .show(ui, |ui| {
+                                    ui.horizontal_top(|ui| {
+                                        for file in group {
+                                            let path = file.absolute_path();
+                                            let kept = manager.keep.contains(path);
+                                            let stroke = if kept {
+                                                egui::Stroke::new(2.0, egui::Color32::from_rgb(80, 170, 90))
+                                            } else {
+                                                egui::Stroke::new(1.0, egui::Color32::GRAY)
+                                            };
+                                            egui::Frame::group(ui.style()).stroke(stroke).show(ui, |ui| {
+                                                ui.set_width(CARD_WIDTH);
+                                                ui.vertical(|ui| {
+                                                    ui.allocate_ui(egui::vec2(CARD_WIDTH, THUMB_HEIGHT), |ui| {
+                                                        ui.centered_and_justified(|ui| match image_cache.get(path) {
+                                                            Some(texture) => {
+                                                                let [w, h] = texture.size();
+                                                                let scale = (CARD_WIDTH / w as f32).min(THUMB_HEIGHT / h as f32).min(1.0);
+                                                                ui.image((texture.id(), egui::vec2(w as f32, h as f32) * scale));
+                                                            }
+                                                            None => {
+                                                                ui.label(egui::RichText::new(Self::get_file_type_icon(file.extension())).size(40.0));
+                                                            }
+                                                        });
+                                                    });
+                                                    let kind = ThumbnailKind::from_extension(file.extension()).filter(|kind| match kind {
+                                                        ThumbnailKind::Image => true,
+                                                        ThumbnailKind::Video => video_ready,
+                                                        ThumbnailKind::Pdf => pdfium_ready,
+                                                    });
+                                                    if let Some(kind) = kind {
+                                                        if (!file.is_cloud_placeholder || preview_cloud_files)
+                                                            && !image_cache.contains_key(path)
+                                                            && !preview_failed.contains(path)
+                                                            && manager.requested.insert(path.to_string())
+                                                        {
+                                                            wanted.push((path.to_string(), kind));
+                                                        }
+                                                    }
+                                                    ui.add(egui::Label::new(egui::RichText::new(file.full_name()).strong()).truncate())
+                                                        .on_hover_text(path);
+                                                    let folder = path.rsplit_once(['/', '\\']).map(|(folder, _)| folder).unwrap_or_default();
+                                                    ui.add(egui::Label::new(egui::RichText::new(folder).weak()).truncate())
+                                                        .on_hover_text(folder);
+                                                    ui.label(format_size(file.file_size, &size_format));
+                                                    ui.label(format_date(file.modified_timestamp, calendar));
+                                                    let mut keep = kept;
+                                                    if ui.checkbox(&mut keep, "Keep").changed() {
+                                                        if keep {
+                                                            manager.keep.insert(path.to_string());
+                                                        } else {
+                                                            manager.keep.remove(path);
+                                                        }
+                                                    }
+                                                });
+                                            });
+                                        }
+                                    });
+                                });
+                        }
+                    },
+                );
+            });
+
+        if let Some(rule) = rule {
+            manager.apply_rule(rule);
+            let _ = self.settings.save();
+        }
+        let unkept_group = manager.group_without_keeper();
+        let rest = manager.rest();
+
+        if !wanted.is_empty() {
+            let max_size = (self.settings.preview_max_size * ctx.pixels_per_point()).round() as u32;
+            let options = self.thumbnail_options(max_size);
+            let service = self.preview_service.get_or_insert_with(PreviewService::start);
+            for (path, kind) in wanted {
+                service.request_card(path, kind, options);
+            }
+        }
+        if self.duplicate_manager.as_ref().is_some_and(|m| m.requested.iter().any(|path| !self.image_cache.contains_key(path) && !self.preview_failed.contains(path))) {
+            ctx.request_repaint_after(Duration::from_millis(200));
+        }
+
+        if delete || move_rest {
+            if let Some(group) = unkept_group {
+                self.error_message = Some(format!("Group {} keeps no file: tick Keep on at least one copy", group + 1));
+                return;
+            }
+            if self.refuse_if_read_only() {
+                return;
+            }
+            self.duplicate_manager = None;
+            let files = self.with_sidecars(rest);
+            if delete {
+                self.pending_delete_paths = files;
+                self.show_delete_confirm = true;
+            } else {
+                self.pending_transfer = Some(PendingTransfer {
+                    files,
+                    mode: TransferMode::Move,
+                });
+            }
+        } else if !open {
+            self.duplicate_manager = None;
+        }
+    }
+
+    /// Retention rules window: edit the rules, dry run with report, run now, undo the last run
+    fn show_retention_window(&mut self, ctx: &egui::Context) {
+        if !self.show_retention {
+            return;
+        }
+
+        let mut open = true;
+        let mut dry_run = false;
+        let mut run_now = false;
+        let mut undo = false;
+        let mut save_report = false;
+        let mut rules_changed = false;
+        let dry_running = self.retention_receiver.is_some();
+        let can_run = self.bulk_job.is_none() && !self.is_read_only();
+        let size_format = self.settings.size_format;
+        let calendar = self.settings.calendar;
+        let plan = &self.retention_plan;
+        let rules = &mut self.settings.retention_rules;
+        egui::Window::new("Retention rules")
+            .open(&mut open)
+            .default_width(700.0)
+            .show(ctx, |ui| {
+                ui.weak("Rules are checked top to bottom; a file matched by several rules gets the first one. Also run by --retention (e.g. from Task Scheduler or cron)");
+                ui.add_space(5.0);
+
+                let mut removed = None;
+                for (idx, rule) in rules.iter_mut().enumerate() {
+                    ui.group(|ui| {
+                        ui.horizontal(|ui| {
+                            rules_changed |= ui.checkbox(&mut rule.enabled, "").changed();
+                            rules_changed |= ui.add(egui::TextEdit::singleline(&mut rule.name).desired_width(200.0)).changed();
+                            if ui.small_button("🗑").on_hover_text("Remove rule").clicked() {
+                                removed = Some(idx);
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("In");
+                            if ui.button(Self::folder_label(&rule.folder.to_string_lossy())).clicked() {
+                                if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                                    rule.folder = folder;
+                                    rules_changed = true;
+                                }
+                            }
+                            ui.label("files older than");
+                            rules_changed |= ui.add(egui::DragValue::new(&mut rule.older_than_days).suffix(" days")).changed();
+                            ui.label("larger than");
+                            rules_changed |= ui.add(egui::DragValue::new(&mut rule.larger_than_mb).suffix(" MB")).changed();
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Matching");
+                            rules_changed |= ui
+                                .add(egui::TextEdit::singleline(&mut rule.patterns).hint_text("all files").desired_width(180.0))
+                                .on_hover_text("Wildcards separated by spaces, e.g. *.iso *.zip or logs/*")
+                                .changed();
+                            egui::ComboBox::from_id_salt(("retention_action", idx))
+                                .selected_text(rule.action.label())
+                                .show_ui(ui, |ui| {
+                                    for action in RetentionAction::ALL {
+                                        rules_changed |= ui.selectable_value(&mut rule.action, action, action.label()).changed();
+                                    }
+                                });
+                            if rule.action == RetentionAction::Move {
+                                if ui.button(Self::folder_label(&rule.destination.to_string_lossy())).clicked() {
+                                    if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                                        rule.destination = folder;
+                                        rules_changed = true;
+                                    }
+                                }
+                            } else {
+                                ui.colored_label(egui::Color32::from_rgb(220, 120, 50), "can't be undone");
+                            }
+                        });
+                    });
+                }
+                if let Some(idx) = removed {
+                    rules.remove(idx);
+                    rules_changed = true;
+                }
+                if ui.button("➕ Add rule").clicked() {
+                    rules.push(RetentionRule::default());
+                    rules_changed = true;
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    dry_run = ui
+                        .add_enabled(!dry_running && !rules.is_empty(), egui::Button::new("Dry run"))
+                        .on_hover_text("List the files the rules match without touching them")
+                        .clicked();
+                    if dry_running {
+                        ui.spinner();
+                    }
+                    let planned = plan.as_ref().is_some_and(|plan| !plan.is_empty());
+                    run_now = ui
+                        .add_enabled(can_run && planned, egui::Button::new("Run now"))
+                        .on_hover_text("Move/delete the files of the dry run and log them")
+                        .clicked();
+                    undo = ui
+                        .add_enabled(can_run, egui::Button::new("↩ Undo last run"))
+                        .on_hover_text("Move the files of the last logged run back (deleted files can't be restored)")
+                        .clicked();
+                    save_report = ui.add_enabled(plan.is_some(), egui::Button::new("Save report...")).clicked();
+                });
+
+                let Some(plan) = plan else {
+                    return;
+                };
+                let total: u64 = plan.iter().map(|item| item.size).sum();
+                ui.label(format!("{} files, {}", plan.len(), format_size(total, &size_format)));
+                egui::ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
+                    for item in plan {
+                        let target = match &item.destination {
+                            Some(destination) => format!("→ {}", destination.display()),
+                            None => "delete".to_string(),
+                        };
+                        ui.label(format!(
+                            "{}  ·  {}  ·  {}  ·  {}",
+                            item.source.display(),
+                            format_size(item.size, &size_format),
+                            format_date(item.modified, calendar),
+                            target
+                        ))
+                        .on_hover_text(&item.rule);
+                    }
+                });
+            });
+
+        // Edits invalidate the dry run, so Run now can't act on a stale plan
+        if rules_changed {
+            self.retention_plan = None;
+        }
+        if dry_run {
+            self.start_retention_dry_run();
+        }
+        if run_now {
+            self.run_retention();
+        }
+        if undo {
+            self.undo_retention();
+        }
+        if save_report {
+            if let (Some(plan), Some(path)) = (
+                &self.retention_plan,
+                rfd::FileDialog::new()
+                    .add_filter("Text files", &["txt"])
+                    .set_file_name("retention-dry-run.txt")
+                    .save_file(),
+            ) {
+                let report = retention::report(&self.settings.retention_rules, plan, &size_format, calendar);
+                if let Err(e) = std::fs::write(&path, report) {
+                    self.error_message = Some(format!("Failed to save report: {}", e));
+                }
+            }
+        }
+        if !open {
+            self.show_retention = false;
+            if let Err(e) = self.settings.save() {
+                self.error_message = Some(e);
+            }
+        }
+    }
+
+    /// Progress bar, throughput and ETA of the running bulk job
+    fn show_job_progress(&self, ui: &mut egui::Ui) {
+        let Some(bulk_job) = &self.bulk_job else {
+            return;
+        };
+        let progress = &bulk_job.job.progress;
+        let size_format = &self.settings.size_format;
+
+        let mut text = format!(
+            "{} / {} files · {} of {}",
+            progress.files_done,
+            progress.files_total,
+            format_size(progress.bytes_done, size_format),
+            format_size(progress.bytes_total, size_format)
+        );
+        if let Some(rate) = progress.bytes_per_sec() {
+            text.push_str(&format!(" · {}/s", format_size(rate as u64, size_format)));
+        }
+        if let Some(eta) = progress.eta() {
+            text.push_str(&format!(" · ETA {}", jobs::format_duration(eta)));
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::ProgressBar::new(progress.fraction())
+                    .desired_width(300.0)
+                    .show_percentage(),
+            );
+            ui.label(text);
+        });
+        if !progress.current.is_empty() {
+            ui.label(egui::RichText::new(&progress.current).weak());
+        }
+    }
+
+    /// Short button label for a destination folder ("name  (parent)")
+    fn folder_label(folder: &str) -> String {
+        let path = std::path::Path::new(folder);
+        match (path.file_name(), path.parent()) {
+            (Some(name), Some(parent)) => format!("📁 {}  ({})", name.to_string_lossy(), parent.display()),
+            _ => format!("📁 {}", folder),
+        }
+    }
+
+    /// Show the move/copy destination window with favorite and recent folders
+    fn show_transfer_window(&mut self, ctx: &egui::Context) {
+        let read_only = self.is_read_only();
+        let Some(pending) = &mut self.pending_transfer else {
+            return;
+        };
+
+        let mut open = true;
+        let mut destination: Option<PathBuf> = None;
+        let mut toggle_favorite: Option<String> = None;
+        let mut cancelled = false;
+        let favorites = &self.settings.favorite_destinations;
+        let recent = &self.settings.recent_destinations;
+
+        egui::Window::new("Move / Copy")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if let [(_, name)] = pending.files.as_slice() {
+                    ui.label(format!("File: {}", name));
+                } else {
+                    ui.label(format!("{} files selected", pending.files.len()));
+                }
+
+                ui.horizontal(|ui| {
+                    for mode in TransferMode::ALL {
+                        // .lnk shortcuts are a Windows-only format
+                        if mode == TransferMode::Shortcut && !cfg!(windows) {
+                            continue;
+                        }
+                        if mode == TransferMode::Move && read_only {
+                            continue;
+                        }
+                        ui.radio_value(&mut pending.mode, mode, mode.label());
+                    }
+                });
+
+                ui.add_space(6.0);
+                ui.label(egui::RichText::new("★ Favorites").strong());
+                if favorites.is_empty() {
+                    ui.label(egui::RichText::new("Pin a recent folder with ☆").weak());
+                }
+                for folder in favorites {
+                    ui.horizontal(|ui| {
+                        if ui.button(Self::folder_label(folder)).on_hover_text(folder).clicked() {
+                            destination = Some(PathBuf::from(folder));
+                        }
+                        if ui.small_button("★").on_hover_text("Unpin").clicked() {
+                            toggle_favorite = Some(folder.clone());
+                        }
+                    });
+                }
+
+                ui.add_space(6.0);
+                ui.label(egui::RichText::new("🕘 Recent").strong());
+                if recent.is_empty() {
+                    ui.label(egui::RichText::new("No recent destinations").weak());
+                }
+                for folder in recent.iter().filter(|f| !favorites.contains(f)) {
+                    ui.horizontal(|ui| {
+                        if ui.button(Self::folder_label(folder)).on_hover_text(folder).clicked() {
+                            destination = Some(PathBuf::from(folder));
+                        }
+                        if ui.small_button("☆").on_hover_text("Pin as favorite").clicked() {
+                            toggle_favorite = Some(folder.clone());
+                        }
+                    });
+                }
+
+                ui.add_space(6.0);
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("📂 Browse...").clicked() {
+                        destination = rfd::FileDialog::new()
+                            .set_title("Select destination folder")
+                            .pick_folder();
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+
+        if let Some(folder) = toggle_favorite {
+            self.settings.toggle_favorite_destination(&folder);
+            let _ = self.settings.save();
+        }
+
+        if let Some(dest_folder) = destination {
+            if let Some(pending) = self.pending_transfer.take() {
+                self.transfer_files(&pending.files, &dest_folder, pending.mode);
+            }
+        } else if !open || cancelled {
+            self.pending_transfer = None;
+        }
+    }
+
+    fn rename_file(&mut self, old_path: &str, new_name: &str) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+        if self.skip_missing(&[old_path.to_string()]).is_empty() {
+            self.error_message = Some("The file no longer exists (moved or deleted since the scan)".to_string());
+            return;
+        }
+        let old = std::path::Path::new(old_path);
+        if let Some(parent) = old.parent() {
+            let new_path = parent.join(new_name);
+            match std::fs::rename(old, &new_path) {
+                Ok(_) => {
+                    self.status_message = format!("Renamed to: {}", new_name);
+                    self.error_message = None;
+                    // Same row at the new name: listed path and source folder are kept
+                    let renamed = self.files.iter().find(|f| f.absolute_path() == old_path).map(|old| {
+                        let mut file = file_scanner::file_info(&new_path, &new_path);
+                        let relative_path = Path::new(old.relative_path()).with_file_name(new_name);
+                        file.set_location(&relative_path.to_string_lossy(), &Arc::from(old.source_folder()));
+                        file
+                    });
+                    for added in self.added_files.iter_mut().filter(|added| added.as_path() == old) {
+                        *added = new_path.clone();
+                    }
+                    self.update_rows(&HashSet::from([old_path.to_string()]), renamed.into_iter().collect());
+                }
+                Err(e) => {
+                    self.error_message = Some(format!("Rename failed: {}", e));
+                    if elevation::is_access_denied(&e) {
+                        self.offer_elevation(vec![ElevatedOp::Move {
+                            from: old.to_path_buf(),
+                            to: new_path,
+                        }]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Open the Normalize Extensions or Sanitize Names window for the selected files (all listed files if none selected)
+    fn open_bulk_rename(&mut self, kind: BulkRenameKind) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+        let files: Vec<FileInfo> = if self.selected_files.is_empty() {
+            self.listed_files()
+        } else {
+            let mut indices: Vec<usize> = self.selected_files.iter().copied().collect();
+            indices.sort_unstable();
+            indices.into_iter().filter_map(|idx| self.filtered_files.get(idx).cloned()).collect()
+        };
+        self.bulk_rename = Some(BulkRename::new(files, kind));
+    }
+
+    /// Apply the planned renames as one all-or-nothing batch
+    fn apply_bulk_renames(&mut self, kind: BulkRenameKind, plan: &[RenameOp]) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+        match renames::apply(plan) {
+            Ok(_) => {
+                self.status_message = match kind {
+                    BulkRenameKind::Extensions(_) => format!("Normalized {} extensions", plan.len()),
+                    BulkRenameKind::SafeNames => format!("Sanitized {} names", plan.len()),
+                };
+                self.error_message = None;
+                self.last_bulk_renames = plan
+                    .iter()
+                    .map(|op| RenameOp {
+                        from: op.to.clone(),
+                        to: op.from.clone(),
+                    })
+                    .collect();
+                self.scan_all_folders();
+            }
+            Err(e) => self.error_message = Some(e),
+        }
+    }
+
+    /// Revert the last extension normalization or name sanitizing
+    fn undo_bulk_renames(&mut self) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+        let plan = std::mem::take(&mut self.last_bulk_renames);
+        match renames::apply(&plan) {
+            Ok(_) => {
+                self.status_message = format!("Restored {} original names", plan.len());
+                self.error_message = None;
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Undo failed: {}", e));
+                self.last_bulk_renames = plan;
+            }
+        }
+        self.scan_all_folders();
+    }
+
+    /// Show the Normalize Extensions / Sanitize Names window: rules, preview of old -> new names, apply and undo
+    fn show_bulk_rename_window(&mut self, ctx: &egui::Context) {
+        let Some(normalizer) = &mut self.bulk_rename else {
+            return;
+        };
+
+        let mut open = true;
+        let mut apply = false;
+        let mut undo = false;
+        let can_undo = !self.last_bulk_renames.is_empty();
+        egui::Window::new(normalizer.kind.title())
+            .collapsible(false)
+            .resizable(true)
+            .default_width(560.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                match &mut normalizer.kind {
+                    BulkRenameKind::Extensions(rules) => {
+                        let mut rules_changed = false;
+                        rules_changed |= ui.checkbox(&mut rules.lowercase, "Lowercase (JPG -> jpg)").changed();
+                        rules_changed |= ui
+                            .checkbox(&mut rules.canonical, "Common spelling (jpeg -> jpg, tiff -> tif, htm -> html)")
+                            .changed();
+                        rules_changed |= ui
+                            .checkbox(&mut rules.add_missing, "Add missing extensions from file content")
+                            .on_hover_text("Online-only cloud files are skipped (reading them would download them)")
+                            .changed();
+                        rules_changed |= ui
+                            .checkbox(&mut rules.fix_mismatched, "Correct extensions that don't match the content (.jpg that is a PNG)")
+                            .on_hover_text("Reads the first bytes of each file; container formats (an .xlsx is a ZIP) are not changed")
+                            .changed();
+                        if rules_changed {
+                            normalizer.replan();
+                        }
+                    }
+                    BulkRenameKind::SafeNames => {
+                        ui.label("Characters < > : \" / \\ | ? * and control characters become _, emoji are removed,");
+                        ui.label("leading/trailing spaces and trailing dots are trimmed, reserved names (CON, NUL, COM1...) get a _ suffix.");
+                    }
+                }
+
+                ui.separator();
+                let conflicts = normalizer.simulation.conflict_count();
+                ui.label(format!("{} of {} files will be renamed", normalizer.plan.len(), normalizer.files.len()));
+                if normalizer.simulation.chained > 0 {
+                    ui.weak(format!(
+                        "{} renames reuse a name freed by another rename (chains or swaps); temporary names are used",
+                        normalizer.simulation.chained
+                    ));
+                }
+                if conflicts > 0 {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(200, 60, 60),
+                        format!("{} conflicts: edit the new names or drop them; nothing is renamed until all are resolved", conflicts),
+                    );
+                }
+
+                let mut edited = false;
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    egui::Grid::new("extension_renames").num_columns(3).spacing([8.0, 4.0]).striped(true).show(ui, |ui| {
+                        for (idx, op) in normalizer.plan.iter().enumerate() {
+                            ui.label(op.from.file_name().unwrap_or_default().to_string_lossy());
+                            if ui
+                                .add(egui::TextEdit::singleline(&mut normalizer.new_names[idx]).desired_width(220.0))
+                                .changed()
+                            {
+                                edited = true;
+                            }
+                            match &normalizer.simulation.conflicts[idx] {
+                                Some(reason) => {
+                                    ui.colored_label(egui::Color32::from_rgb(200, 60, 60), format!("⚠ {}", reason));
+                                }
+                                None => {
+                                    ui.label("");
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+                if edited {
+                    for (op, name) in normalizer.plan.iter_mut().zip(&normalizer.new_names) {
+                        op.to = op.from.with_file_name(name.trim());
+                    }
+                    normalizer.simulation = renames::simulate(&normalizer.plan);
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!normalizer.plan.is_empty() && conflicts == 0, egui::Button::new("Apply"))
+                        .on_hover_text("Rename all files as one batch: if any rename fails, all are undone")
+                        .clicked()
+                    {
+                        apply = true;
+                    }
+                    if ui
+                        .add_enabled(conflicts > 0, egui::Button::new("Drop conflicting"))
+                        .on_hover_text("Leave the conflicting files unchanged")
+                        .clicked()
+                    {
+                        let mut keep = normalizer.simulation.conflicts.iter().map(|c| c.is_none());
+                        let mut keep_names = keep.clone();
+                        normalizer.plan.retain(|_| keep.next().unwrap_or(false));
+                        normalizer.new_names.retain(|_| keep_names.next().unwrap_or(false));
+                        normalizer.simulation = renames::simulate(&normalizer.plan);
+                    }
+                    if ui
+                        .add_enabled(can_undo, egui::Button::new("Undo Last"))
+                        .on_hover_text("Restore the names changed by the last Normalize Extensions or Sanitize Names")
+                        .clicked()
+                    {
+                        undo = true;
+                    }
+                });
+            });
+
+        if apply {
+            let (kind, plan) = (normalizer.kind, normalizer.plan.clone());
+            self.bulk_rename = None;
+            self.apply_bulk_renames(kind, &plan);
+        } else if undo {
+            self.bulk_rename = None;
+            self.undo_bulk_renames();
+        } else if !open {
+            self.bulk_rename = None;
+        }
+    }
+
+    fn open_snapshot_browser(&mut self) {
+        let label = self
+            .selected_folders
+            .first()
+            .and_then(|folder| folder.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Snapshot".to_string());
+        self.snapshot_browser = Some(SnapshotBrowser {
+            query: String::new(),
+            label,
+            result: None,
+            receiver: None,
+        });
+        self.start_snapshot_search();
+    }
+
+    /// Search all saved snapshots in the background
+    fn start_snapshot_search(&mut self) {
+        let Some(browser) = &mut self.snapshot_browser else {
+            return;
+        };
+        let query = browser.query.clone();
+        let (tx, rx) = mpsc::channel();
+        browser.receiver = Some(rx);
+        thread::spawn(move || {
+            let _ = tx.send(snapshots::search(&query));
+        });
+    }
+
+    /// Check for a finished snapshot search
+    fn check_snapshot_search(&mut self) {
+        let Some(browser) = &mut self.snapshot_browser else {
+            return;
+        };
+        let Some(receiver) = &browser.receiver else {
+            return;
+        };
+        if let Ok(result) = receiver.try_recv() {
+            browser.result = Some(result);
+            browser.receiver = None;
+        }
+    }
+
+    /// Save the current listing as a snapshot
+    fn save_snapshot(&mut self, label: &str) {
+        match snapshots::save(label, &self.selected_folders, &self.files) {
+            Ok(path) => {
+                self.status_message = format!("Saved snapshot of {} files: {}", self.files.len(), path.display());
+                self.error_message = None;
+                self.start_snapshot_search();
+            }
+            Err(e) => self.error_message = Some(e),
+        }
+    }
+
+    /// Show the Search Snapshots window: find files on drives that are not plugged in
+    /// Statistics window: when the listed files were modified, by hour, weekday and both
+    fn show_statistics_window(&mut self, ctx: &egui::Context) {
+        let Some(activity) = &self.activity else {
+            return;
+        };
+
+        let mut open = true;
+        let mut offset = self.settings.activity_utc_offset;
+        let accent = egui::Color32::from_rgb(90, 150, 230);
+        egui::Window::new("📊 Statistics")
+            .resizable(true)
+            .default_width(620.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.heading("Modification activity");
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} listed files, times in", activity.files));
+                    let mut hours = offset as f32 / 60.0;
+                    if ui
+                        .add(egui::DragValue::new(&mut hours).range(-12.0..=14.0).speed(0.25).custom_formatter(|h, _| {
+                            activity::offset_label((h * 60.0).round() as i32)
+                        }))
+                        .on_hover_text("Shift to the time zone of the incident timeline or the job scheduler")
+                        .changed()
+                    {
+                        offset = (hours * 60.0).round() as i32;
+                    }
+                });
+                if let Some((day, hour, count)) = activity.peak() {
+                    ui.label(format!(
+                        "Busiest hour: {} {:02}:00-{:02}:00 ({} files)",
+                        activity::WEEKDAYS[day],
+                        hour,
+                        hour + 1,
+                        count
+                    ));
+                }
+                ui.add_space(6.0);
+
+                // One bar per value, hover shows the count
+                let bars = |ui: &mut egui::Ui, values: &[usize], width: f32, label: &dyn Fn(usize) -> String| {
+                    let max = values.iter().copied().max().unwrap_or(0).max(1);
+                    ui.horizontal(|ui| {
+                        ui.spacing_mut().item_spacing.x = 2.0;
+                        for (idx, &count) in values.iter().enumerate() {
+                            let (rect, response) = ui.allocate_exact_size(egui::vec2(width, 80.0), egui::Sense::hover());
+                            let height = rect.height() * count as f32 / max as f32;
+                            let bar = egui::Rect::from_min_max(egui::pos2(rect.left(), rect.bottom() - height), rect.max);
+                            ui.painter().rect_filled(rect, 0.0, ui.visuals().faint_bg_color);
+                            ui.painter().rect_filled(bar, 0.0, accent);
+                            response.on_hover_text(format!("{}: {} files", label(idx), count));
+                        }
+                    });
+                };
+
+                ui.strong("By hour");
+                bars(ui, &activity.by_hour, 18.0, &|hour| format!("{:02}:00-{:02}:00", hour, hour + 1));
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 2.0;
+                    for hour in 0..24 {
+                        let text = if hour % 3 == 0 { format!("{}", hour) } else { String::new() };
+                        ui.add_sized([18.0, 12.0], egui::Label::new(egui::RichText::new(text).small().weak()));
+                    }
+                });
+                ui.add_space(6.0);
+
+                ui.strong("By weekday");
+                bars(ui, &activity.by_weekday, 60.0, &|day| activity::WEEKDAYS[day].to_string());
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 2.0;
+                    for day in activity::WEEKDAYS {
+                        ui.add_sized([60.0, 12.0], egui::Label::new(egui::RichText::new(day).small().weak()));
+                    }
+                });
+                ui.add_space(6.0);
+
+                // Weekday x hour heatmap: scheduled jobs show up as a bright cell or column
+                ui.strong("Weekday x hour");
+                let max = activity.grid_max().max(1);
+                egui::Grid::new("activity_heatmap").spacing([2.0, 2.0]).show(ui, |ui| {
+                    for (day, hours) in activity.grid.iter().enumerate() {
+                        ui.label(egui::RichText::new(activity::WEEKDAYS[day]).small());
+                        for (hour, &count) in hours.iter().enumerate() {
+                            let (rect, response) = ui.allocate_exact_size(egui::vec2(18.0, 14.0), egui::Sense::hover());
+                            let color = if count == 0 {
+                                ui.visuals().faint_bg_color
+                            } else {
+                                // Square root scale, so a few files still show next to a burst
+                                let share = (count as f32 / max as f32).sqrt();
+                                accent.gamma_multiply(0.15 + 0.85 * share)
+                            };
+                            ui.painter().rect_filled(rect, 2.0, color);
+                            response.on_hover_text(format!(
+                                "{} {:02}:00-{:02}:00: {} files",
+                                activity::WEEKDAYS[day],
+                                hour,
+                                hour + 1,
+                                count
+                            ));
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+
+        if !open {
+            self.activity = None;
+        } else if offset != self.settings.activity_utc_offset {
+            self.settings.activity_utc_offset = offset;
+            let _ = self.settings.save();
+            self.activity = Some(Activity::count(&self.filtered_files, offset));
+        }
+    }
+
+    /// Permissions window: the audited folders with owner, who can write and flags, and a CSV report
+    fn show_permissions_window(&mut self, ctx: &egui::Context) {
+        let Some(audit) = &mut self.permission_audit else {
+            return;
+        };
+
+        let mut open = true;
+        let mut rerun = false;
+        let mut export = false;
+        egui::Window::new("🔐 Permissions")
+            .resizable(true)
+            .default_width(760.0)
+            .default_height(460.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if audit.receiver.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!("Reading permissions: {} of {} folders", audit.progress.0, audit.progress.1));
+                    });
+                    return;
+                }
+                let count = |flag: PermissionFlag| audit.folders.iter().filter(|f| f.flags.contains(&flag)).count();
+                let failed = audit.folders.iter().filter(|f| f.error.is_some()).count();
+                ui.label(format!(
+                    "{} folders: {} world-writable, {} readable by everyone, {} could not be read",
+                    audit.folders.len(),
+                    count(PermissionFlag::WorldWritable),
+                    count(PermissionFlag::EveryoneReadable),
+                    failed
+                ));
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut audit.flagged_only, "Only flagged folders");
+                    if ui.button("⟳ Read again").on_hover_text("Read the permissions again after changing them").clicked() {
+                        rerun = true;
+                    }
+                    if ui.button("Export report...").on_hover_text("Save the listed folders as CSV").clicked() {
+                        export = true;
+                    }
+                });
+                ui.separator();
+
+                let flagged_only = audit.flagged_only;
+                let warning = egui::Color32::from_rgb(230, 80, 60);
+                egui::ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+                    egui::Grid::new("permission_audit").num_columns(5).spacing([12.0, 4.0]).striped(true).show(ui, |ui| {
+                        ui.strong("Folder");
+                        ui.strong("Owner");
+                        ui.strong("Permissions");
+                        ui.strong("Can write");
+                        ui.strong("Flags");
+                        ui.end_row();
+                        for folder in audit.folders.iter().filter(|f| !flagged_only || f.is_flagged()) {
+                            ui.label(folder.path.display().to_string())
+                                .on_hover_text(format!("Can read: {}", folder.readers.join(", ")));
+                            ui.label(folder.owner.as_deref().unwrap_or("-"));
+                            match &folder.error {
+                                Some(error) => ui.colored_label(warning, error),
+                                None => ui.label(&folder.summary),
+                            };
+                            ui.label(folder.writers.join(", "));
+                            if folder.flags.contains(&PermissionFlag::WorldWritable) {
+                                ui.colored_label(warning, folder.flag_labels());
+                            } else {
+                                ui.label(folder.flag_labels());
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+
+        if export {
+            let listed: Vec<&FolderPermissions> =
+                audit.folders.iter().filter(|f| !audit.flagged_only || f.is_flagged()).collect();
+            if let Some(path) = rfd::FileDialog::new()
+                .add_filter("CSV files", &["csv"])
+                .set_file_name("permissions_report.csv")
+                .save_file()
+            {
+                match permissions::write_report(&listed, &path) {
+                    Ok(()) => {
+                        self.status_message = format!("Exported permissions of {} folders to: {}", listed.len(), path.display());
+                        self.error_message = None;
+                    }
+                    Err(e) => self.error_message = Some(format!("Export failed: {}", e)),
+                }
+            }
+        }
+        if !open {
+            self.permission_audit = None;
+        } else if rerun {
+            self.start_permission_audit();
+        }
+    }
+
+    /// Manifest check window: counts, the files that are not as expected, and a CSV report
+    fn show_manifest_check_window(&mut self, ctx: &egui::Context) {
+        let Some(verification) = &mut self.manifest_verification else {
+            return;
+        };
+
+        let mut open = true;
+        let mut export = false;
+        let mut show_in_list = false;
+        egui::Window::new("📋 Manifest check")
+            .resizable(true)
+            .default_width(640.0)
+            .default_height(420.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(format!("Manifest: {}", verification.manifest.display()));
+                if verification.receiver.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        if verification.progress.1 == 0 {
+                            ui.label("Scanning the folder...");
+                        } else {
+                            ui.label(format!("Checking {} of {} files", verification.progress.0, verification.progress.1));
+                        }
+                    });
+                    return;
+                }
+                let check = match &verification.result {
+                    Some(Ok(check)) => check,
+                    Some(Err(e)) => {
+                        ui.colored_label(egui::Color32::from_rgb(230, 80, 60), e);
+                        return;
+                    }
+                    None => return,
+                };
+                ui.label(format!("Folder: {}", check.folder.display()));
+                if check.is_clean() {
+                    ui.colored_label(egui::Color32::from_rgb(80, 180, 80), format!("✓ All {} expected files present and matching", check.expected));
+                } else {
+                    ui.label(check.describe());
+                }
+                ui.horizontal(|ui| {
+                    for (idx, status) in CheckStatus::ALL.into_iter().enumerate() {
+                        ui.checkbox(&mut verification.shown[idx], format!("{} ({})", status.label(), check.count(status)));
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.add_enabled_ui(!check.is_clean(), |ui| {
+                        if ui.button("Export report...").on_hover_text("Save the files that are not as expected as CSV").clicked() {
+                            export = true;
+                        }
+                        if ui
+                            .button("Show in list")
+                            .on_hover_text("List only the mismatched and unexpected files (when the folder is scanned)")
+                            .clicked()
+                        {
+                            show_in_list = true;
+                        }
+                    });
+                });
+                ui.separator();
+
+                let shown = verification.shown;
+                egui::ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+                    egui::Grid::new("manifest_check").num_columns(3).spacing([12.0, 4.0]).striped(true).show(ui, |ui| {
+                        ui.strong("Status");
+                        ui.strong("Path");
+                        ui.strong("Detail");
+                        ui.end_row();
+                        for entry in check.entries.iter().filter(|e| shown[e.status as usize]) {
+                            ui.label(entry.status.label());
+                            ui.label(&entry.path);
+                            ui.label(&entry.detail);
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+
+        if let Some(Ok(check)) = &verification.result {
+            if export {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("CSV files", &["csv"])
+                    .set_file_name("manifest_check.csv")
+                    .save_file()
+                {
+                    match manifest_check::write_report(check, &path) {
+                        Ok(()) => {
+                            self.status_message = format!("Exported manifest check to: {}", path.display());
+                            self.error_message = None;
+                        }
+                        Err(e) => self.error_message = Some(format!("Export failed: {}", e)),
+                    }
+                }
+            }
+            if show_in_list {
+                let paths: HashSet<String> = check.entries.iter().filter_map(|e| e.absolute_path.clone()).collect();
+                self.content_filter = Some(("Not as the manifest expects".to_string(), paths));
+                self.apply_filter();
+            }
+        }
+        if !open {
+            self.manifest_verification = None;
+        }
+    }
+
+    fn show_snapshot_window(&mut self, ctx: &egui::Context) {
+        let Some(browser) = &mut self.snapshot_browser else {
+            return;
+        };
+
+        let mut open = true;
+        let mut search = false;
+        let mut save = false;
+        let mut delete: Option<PathBuf> = None;
+        let can_save = !self.files.is_empty() && !self.is_scanning;
+        let size_format = &self.settings.size_format;
+        let calendar = self.settings.calendar;
+        egui::Window::new("Search Snapshots")
+            .collapsible(false)
+            .resizable(true)
+            .default_width(760.0)
+            .default_height(480.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Save current listing as:");
+                    ui.add(egui::TextEdit::singleline(&mut browser.label).desired_width(200.0));
+                    if ui
+                        .add_enabled(can_save && !browser.label.trim().is_empty(), egui::Button::new("📸 Save snapshot"))
+                        .on_hover_text("Keep this file list so it can be searched after the drive is unplugged")
+                        .clicked()
+                    {
+                        save = true;
+                    }
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("File name:");
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut browser.query)
+                            .hint_text("e.g. IMG_2041 or *.psd")
+                            .desired_width(260.0),
+                    );
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        search = true;
+                    }
+                    if ui.add_enabled(browser.receiver.is_none(), egui::Button::new("🔎 Search")).clicked() {
+                        search = true;
+                    }
+                    if browser.receiver.is_some() {
+                        ui.spinner();
+                    }
+                });
+
+                let Some(result) = &browser.result else {
+                    return;
+                };
+
+                egui::CollapsingHeader::new(format!("Saved snapshots ({})", result.snapshots.len()))
+                    .default_open(result.hits.is_empty())
+                    .show(ui, |ui| {
+                        if result.snapshots.is_empty() {
+                            ui.weak("No snapshots saved yet.");
+                        }
+                        egui::Grid::new("snapshot_list").num_columns(4).spacing([12.0, 4.0]).striped(true).show(ui, |ui| {
+                            for summary in &result.snapshots {
+                                ui.label(&summary.label);
+                                ui.label(format_date(summary.created, calendar));
+                                ui.label(format!("{} files", summary.file_count));
+                                if ui.small_button("🗑").on_hover_text("Delete this snapshot").clicked() {
+                                    delete = Some(summary.path.clone());
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    });
+                for error in &result.errors {
+                    ui.colored_label(egui::Color32::from_rgb(200, 60, 60), error);
+                }
+
+                if browser.query.trim().is_empty() {
+                    return;
+                }
+                ui.separator();
+                if result.total > result.hits.len() {
+                    ui.label(format!("{} matches (showing the first {})", result.total, result.hits.len()));
+                } else {
+                    ui.label(format!("{} matches", result.total));
+                }
+
+                TableBuilder::new(ui)
+                    .striped(true)
+                    .resizable(true)
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .column(Column::initial(140.0).clip(true)) // Snapshot
+                    .column(Column::initial(80.0).clip(true)) // Size
+                    .column(Column::initial(130.0).clip(true)) // Modified
+                    .column(Column::remainder().clip(true)) // Path
+                    .header(20.0, |mut header| {
+                        header.col(|ui| {
+                            ui.strong("Snapshot");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Size");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Modified");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Path");
+                        });
+                    })
+                    .body(|body| {
+                        body.rows(20.0, result.hits.len(), |mut row| {
+                            let hit = &result.hits[row.index()];
+                            row.col(|ui| {
+                                ui.label(&hit.label)
+                                    .on_hover_text(format!("Snapshot taken {}", format_date(hit.created, calendar)));
+                            });
+                            row.col(|ui| {
+                                ui.label(format_size(hit.file.size, size_format));
+                            });
+                            row.col(|ui| {
+                                ui.label(format_date(hit.file.modified, calendar));
+                            });
+                            row.col(|ui| {
+                                ui.label(&hit.file.path).context_menu(|ui| {
+                                    if ui.button("Copy path").clicked() {
+                                        ui.ctx().copy_text(hit.file.path.clone());
+                                        ui.close();
+                                    }
+                                });
+                            });
+                        });
                     });
+            });
 
-                match move_result {
-                    Ok(_) => moved_count += 1,
-                    Err(e) => {
-                        failed_count += 1;
-                        errors.push(format!("{}: {}", file_name, e));
-                    }
-                }
-            }
-
-            if failed_count == 0 {
-                self.status_message = format!("Moved {} files to {}", moved_count, dest_folder.display());
-                self.error_message = None;
-            } else {
-                self.status_message = format!("Moved {} files, {} failed", moved_count, failed_count);
-                self.error_message = Some(errors.join("; "));
+        if save {
+            let label = browser.label.trim().to_string();
+            self.save_snapshot(&label);
+        } else if let Some(path) = delete {
+            match snapshots::delete(&path) {
+                Ok(_) => self.start_snapshot_search(),
+                Err(e) => self.error_message = Some(e),
             }
-
-            self.selected_files.clear();
-            self.scan_all_folders();
+        } else if search {
+            self.start_snapshot_search();
         }
-    }
-
-    fn rename_file(&mut self, old_path: &str, new_name: &str) {
-        let old = std::path::Path::new(old_path);
-        if let Some(parent) = old.parent() {
-            let new_path = parent.join(new_name);
-            match std::fs::rename(old, &new_path) {
-                Ok(_) => {
-                    self.status_message = format!("Renamed to: {}", new_name);
-                    self.error_message = None;
-                    // Re-scan to update the list
-                    self.scan_all_folders();
-                }
-                Err(e) => {
-                    self.error_message = Some(format!("Rename failed: {}", e));
-                }
-            }
+        if !open {
+            self.snapshot_browser = None;
         }
     }
 
     fn start_rename(&mut self, idx: usize) {
-        if idx < self.filtered_files.len() {
+        if self.filtered_files.get(idx).is_some_and(|f| !f.is_dir) && !self.refuse_if_read_only() {
             self.editing_index = Some(idx);
-            self.editing_text = self.filtered_files[idx].full_name.clone();
+            self.editing_text = self.filtered_files[idx].full_name().to_string();
             self.request_rename_focus = true;
         }
     }
@@ -685,9 +7002,9 @@ impl FileListerApp {
     fn confirm_rename(&mut self) {
         if let Some(idx) = self.editing_index {
             if idx < self.filtered_files.len() {
-                let old_path = self.filtered_files[idx].absolute_path.clone();
+                let old_path = self.filtered_files[idx].absolute_path().to_string();
                 let new_name = self.editing_text.trim().to_string();
-                if !new_name.is_empty() && new_name != self.filtered_files[idx].full_name {
+                if !new_name.is_empty() && new_name != self.filtered_files[idx].full_name() {
                     self.rename_file(&old_path, &new_name);
                 }
             }
@@ -696,6 +7013,10 @@ impl FileListerApp {
     }
 
     fn toggle_selection(&mut self, idx: usize) {
+        // Folder rows are for looking only; file operations work on files
+        if self.filtered_files.get(idx).is_some_and(|f| f.is_dir) {
+            return;
+        }
         if self.selected_files.contains(&idx) {
             self.selected_files.remove(&idx);
         } else {
@@ -704,60 +7025,368 @@ impl FileListerApp {
     }
 
     fn select_all(&mut self) {
-        for idx in 0..self.filtered_files.len() {
-            self.selected_files.insert(idx);
+        for (idx, file) in self.filtered_files.iter().enumerate() {
+            if !file.is_dir {
+                self.selected_files.insert(idx);
+            }
+        }
+    }
+
+    /// Select every listed version except the latest of its group (the keep suggestion)
+    fn select_older_versions(&mut self) {
+        self.selected_files.clear();
+        for (idx, file) in self.filtered_files.iter().enumerate() {
+            if self.versions.get(file.absolute_path()).is_some_and(|v| !v.latest) {
+                self.selected_files.insert(idx);
+            }
         }
+        self.status_message = format!("Selected {} older versions", self.selected_files.len());
     }
 
     fn deselect_all(&mut self) {
         self.selected_files.clear();
     }
 
+    /// Total size of the selected files in bytes
+    fn selected_total_size(&self) -> u64 {
+        self.selected_files
+            .iter()
+            .filter_map(|&idx| self.filtered_files.get(idx))
+            .map(|f| f.file_size)
+            .sum()
+    }
+
+    /// Greedily add visible files (largest first) that still fit into the target capacity
+    fn auto_fill_target(&mut self) {
+        let Some(target) = self.settings.target_size_bytes else {
+            return;
+        };
+
+        let candidates: Vec<(usize, u64)> = (0..self.filtered_files.len())
+            .filter(|&idx| !self.selected_files.contains(&idx) && !self.filtered_files[idx].is_dir)
+            .map(|idx| (idx, self.filtered_files[idx].file_size))
+            .collect();
+        let picked = manifest::fit_largest_first(&candidates, self.selected_total_size(), target);
+        let added = picked.len();
+        self.selected_files.extend(picked);
+        let total = self.selected_total_size();
+
+        self.status_message = format!(
+            "Auto-selected {} files ({} of {} used)",
+            added,
+            format_size(total, &self.settings.size_format),
+            format_size(target, &self.settings.size_format)
+        );
+    }
+
+    /// Selection summary with remaining capacity of the media target
+    fn show_selection_summary(&mut self, ui: &mut egui::Ui) {
+        let selected_total = self.selected_total_size();
+        ui.label(format!(
+            "Selected: {} files ({})",
+            self.selected_files.len(),
+            format_size(selected_total, &self.settings.size_format)
+        ));
+
+        if ui
+            .add_enabled(!self.selected_files.is_empty(), egui::Button::new("💾 Save selection..."))
+            .on_hover_text("Save the selected paths to a text file")
+            .clicked()
+        {
+            if let Some(path) = rfd::FileDialog::new()
+                .add_filter("Selection files", &["txt"])
+                .set_file_name("selection.txt")
+                .save_file()
+            {
+                self.save_selection(&path);
+            }
+        }
+        if ui
+            .button("📂 Load selection...")
+            .on_hover_text("Re-select the files listed in a saved selection")
+            .clicked()
+        {
+            if let Some(path) = rfd::FileDialog::new()
+                .add_filter("Selection files", &["txt"])
+                .pick_file()
+            {
+                self.load_selection(&path);
+            }
+        }
+
+        // Playlist and manifest act on the selection, or on every listed file
+        let scope = if self.selected_files.is_empty() { "listed" } else { "selected" };
+        let is_media = |f: &FileInfo| Self::is_audio_file(f.extension()) || Self::is_video_file(f.extension());
+        let has_media = if self.selected_files.is_empty() {
+            self.filtered_files.iter().any(is_media)
+        } else {
+            self.selected_files.iter().filter_map(|&idx| self.filtered_files.get(idx)).any(is_media)
+        };
+        ui.add_enabled_ui(has_media, |ui| {
+            ui.menu_button("🎵 Export playlist...", |ui| {
+                ui.checkbox(&mut self.settings.playlist_relative_paths, "Relative paths")
+                    .on_hover_text("Paths relative to the playlist's folder, so the playlist keeps working when the folder is moved");
+                if ui.button(format!("Save M3U8 of {} audio/video files...", scope)).clicked() {
+                    ui.close();
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("M3U8 playlist", &["m3u8"])
+                        .add_filter("M3U playlist", &["m3u"])
+                        .set_file_name("playlist.m3u8")
+                        .save_file()
+                    {
+                        self.export_playlist(&path);
+                    }
+                }
+            })
+            .response
+            .on_disabled_hover_text("No audio or video files to list");
+        });
+        if ui
+            .add_enabled(self.manifest_receiver.is_none(), egui::Button::new("🧾 Export manifest..."))
+            .on_hover_text(format!(
+                "Paths, sizes and SHA-256 hashes of the {} files (CSV, or sha256sum format for .sha256)",
+                scope
+            ))
+            .clicked()
+        {
+            if let Some(path) = rfd::FileDialog::new()
+                .add_filter("CSV", &["csv"])
+                .add_filter("Checksum file", &["sha256"])
+                .set_file_name("manifest.csv")
+                .save_file()
+            {
+                self.export_manifest(&path);
+            }
+        }
+        if ui
+            .add_enabled(
+                self.manifest_verification.as_ref().is_none_or(|v| v.receiver.is_none()),
+                egui::Button::new("📋 Check against manifest..."),
+            )
+            .on_hover_text("Check the scanned folder against a list of expected files (CSV, TSV, JSON or checksum file): missing, unexpected and mismatched files")
+            .clicked()
+        {
+            self.start_manifest_verification();
+        }
+
+        ui.add_space(20.0);
+        ui.label("Target:");
+
+        let current_label = match self.settings.target_size_bytes {
+            None => "None".to_string(),
+            Some(bytes) => TARGET_PRESETS
+                .iter()
+                .find(|(_, size)| *size == bytes)
+                .map(|(label, _)| label.to_string())
+                .unwrap_or_else(|| format!("Custom ({})", format_size(bytes, &self.settings.size_format))),
+        };
+
+        let old_target = self.settings.target_size_bytes;
+        egui::ComboBox::from_id_salt("target_size")
+            .selected_text(current_label)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.settings.target_size_bytes, None, "None");
+                for (label, size) in TARGET_PRESETS {
+                    ui.selectable_value(&mut self.settings.target_size_bytes, Some(size), label);
+                }
+            });
+
+        // Custom capacity in GB (starts from the current target)
+        let mut save_target = old_target != self.settings.target_size_bytes;
+        if let Some(target) = self.settings.target_size_bytes {
+            const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+            let mut target_gb = target as f64 / GB;
+            let response = ui.add(
+                egui::DragValue::new(&mut target_gb)
+                    .range(0.01..=100_000.0)
+                    .speed(0.1)
+                    .suffix(" GB"),
+            );
+            if response.changed() {
+                self.settings.target_size_bytes = Some((target_gb * GB) as u64);
+            }
+            // Save once editing ends, not on every drag frame
+            save_target |= response.drag_stopped() || response.lost_focus();
+        }
+
+        if save_target {
+            let _ = self.settings.save();
+        }
+
+        if let Some(target) = self.settings.target_size_bytes {
+            if selected_total <= target {
+                ui.label(format!("Remaining: {}", format_size(target - selected_total, &self.settings.size_format)));
+            } else {
+                ui.colored_label(
+                    egui::Color32::RED,
+                    format!("Over by {}", format_size(selected_total - target, &self.settings.size_format)),
+                );
+            }
+            if ui
+                .button("Auto-fill")
+                .on_hover_text("Add the largest visible files that still fit into the target")
+                .clicked()
+            {
+                self.auto_fill_target();
+            }
+            if ui
+                .add_enabled(self.manifest_receiver.is_none(), egui::Button::new("💿 Export burn list..."))
+                .on_hover_text(format!(
+                    "Group the {} files into media of the target size for archiving tools: medium, paths, sizes and SHA-256 (CSV or XML)",
+                    scope
+                ))
+                .clicked()
+            {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("CSV", &["csv"])
+                    .add_filter("XML", &["xml"])
+                    .set_file_name("burn-list.csv")
+                    .save_file()
+                {
+                    self.export_burn_list(&path);
+                }
+            }
+        }
+    }
+
+    /// Write the absolute paths of the selected files, one per line
+    fn save_selection(&mut self, path: &std::path::Path) {
+        let mut indices: Vec<usize> = self.selected_files.iter().copied().collect();
+        indices.sort_unstable();
+        let mut content = String::from("# File Lister selection\n");
+        for file in indices.iter().filter_map(|&idx| self.filtered_files.get(idx)) {
+            content.push_str(file.absolute_path());
+            content.push('\n');
+        }
+
+        match std::fs::write(path, content) {
+            Ok(_) => {
+                self.status_message = format!("Saved selection of {} files to: {}", indices.len(), path.display());
+                self.error_message = None;
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to save selection: {}", e));
+            }
+        }
+    }
+
+    /// Select the visible files whose paths are listed in a saved selection
+    fn load_selection(&mut self, path: &std::path::Path) {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                self.error_message = Some(format!("Failed to read selection: {}", e));
+                return;
+            }
+        };
+
+        let saved: HashSet<&str> = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+
+        self.selected_files = self.filtered_files
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| saved.contains(f.absolute_path()))
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let missing = saved.len().saturating_sub(self.selected_files.len());
+        self.status_message = if missing == 0 {
+            format!("Restored selection of {} files", self.selected_files.len())
+        } else {
+            format!(
+                "Restored selection of {} files ({} not found or hidden by the filter)",
+                self.selected_files.len(),
+                missing
+            )
+        };
+        self.error_message = None;
+    }
+
     fn prepare_bulk_delete(&mut self) {
+        if self.refuse_if_read_only() {
+            return;
+        }
         // Collect paths of selected files for confirmation
-        self.pending_delete_paths = self.selected_files
+        let paths: Vec<(String, String)> = self.selected_files
             .iter()
             .filter_map(|&idx| {
                 self.filtered_files.get(idx).map(|f| {
-                    (f.absolute_path.clone(), f.full_name.clone())
+                    (f.absolute_path().to_string(), f.full_name().to_string())
                 })
             })
             .collect();
+        self.pending_delete_paths = self.with_sidecars(paths);
 
         if !self.pending_delete_paths.is_empty() {
             self.show_delete_confirm = true;
         }
     }
 
-    fn execute_bulk_delete(&mut self) {
-        let mut deleted_count = 0;
-        let mut failed_count = 0;
-        let mut errors: Vec<String> = Vec::new();
-
-        for (path, name) in &self.pending_delete_paths {
-            match std::fs::remove_file(path) {
-                Ok(_) => deleted_count += 1,
-                Err(e) => {
-                    failed_count += 1;
-                    errors.push(format!("{}: {}", name, e));
-                }
-            }
+    /// Confirm deleting every listed empty file and folder: files first, then folders deepest
+    /// first, so a folder holding only empty folders is empty by the time it is removed
+    fn prepare_empty_delete(&mut self) {
+        if self.refuse_if_read_only() {
+            return;
         }
-
-        // Update status message
-        if failed_count == 0 {
-            self.status_message = format!("Deleted {} files", deleted_count);
-            self.error_message = None;
-        } else {
-            self.status_message = format!("Deleted {} files, {} failed", deleted_count, failed_count);
-            self.error_message = Some(errors.join("; "));
+        let mut empty: Vec<&FileInfo> = self
+            .filtered_files
+            .iter()
+            .filter(|f| f.file_size == 0 && (!f.is_dir || f.item_count == 0))
+            .collect();
+        empty.sort_by_key(|f| (f.is_dir, std::cmp::Reverse(Path::new(f.absolute_path()).components().count())));
+        self.pending_delete_paths =
+            empty.into_iter().map(|f| (f.absolute_path().to_string(), f.full_name().to_string())).collect();
+        if !self.pending_delete_paths.is_empty() {
+            self.show_delete_confirm = true;
         }
+    }
 
-        // Clean up and rescan
-        self.pending_delete_paths.clear();
+    fn execute_bulk_delete(&mut self) {
         self.show_delete_confirm = false;
-        self.selected_files.clear();
-        self.scan_all_folders();
+        let paths = std::mem::take(&mut self.pending_delete_paths);
+        if self.refuse_if_read_only() {
+            return;
+        }
+        if self.bulk_job.is_some() {
+            self.error_message = Some("Another move/copy/delete is still running".to_string());
+            return;
+        }
+
+        let total = paths.len();
+        let existing = self.skip_missing(&paths.iter().map(|(path, _)| path.clone()).collect::<Vec<_>>());
+        if existing.is_empty() {
+            self.error_message = Some("The files no longer exist (moved or deleted since the scan)".to_string());
+            return;
+        }
+        let items: Vec<JobItem> = paths
+            .into_iter()
+            .filter(|(path, _)| existing.contains(path))
+            .map(|(path, name)| JobItem {
+                path: PathBuf::from(path),
+                name,
+            })
+            .collect();
+        self.status_message = format!("Deleting {} files...", items.len());
+        if items.len() < total {
+            self.status_message.push_str(&format!(" ({} skipped: no longer exist)", total - items.len()));
+        }
+        let paths: Vec<PathBuf> = items.iter().map(|item| item.path.clone()).collect();
+        // Folders come from "Delete all empty"; remove_dir refuses one that is no longer empty
+        let job = Job::spawn(items, |item, _| {
+            if item.path.is_dir() {
+                std::fs::remove_dir(&item.path).map_err(JobError::from)
+            } else {
+                std::fs::remove_file(&item.path).map_err(JobError::from)
+            }
+        });
+        self.bulk_job = Some(BulkJob {
+            job,
+            kind: BulkJobKind::Delete { paths },
+        });
     }
 
     fn cancel_bulk_delete(&mut self) {
@@ -767,36 +7396,27 @@ impl FileListerApp {
 
     /// Check if file extension is an image type
     fn is_image_file(extension: &str) -> bool {
-        let image_extensions = ["jpg", "jpeg", "png", "gif", "bmp", "ico", "webp"];
-        image_extensions.contains(&extension.to_lowercase().as_str())
+        ThumbnailKind::from_extension(extension) == Some(ThumbnailKind::Image)
     }
 
     /// Check if file extension is a video type
     fn is_video_file(extension: &str) -> bool {
-        let video_extensions = ["mp4", "avi", "mkv", "mov", "wmv", "flv", "webm", "m4v", "mpeg", "mpg", "3gp"];
-        video_extensions.contains(&extension.to_lowercase().as_str())
+        ThumbnailKind::from_extension(extension) == Some(ThumbnailKind::Video)
     }
 
     /// Check if file extension is a PDF
     fn is_pdf_file(extension: &str) -> bool {
-        extension.to_lowercase() == "pdf"
+        ThumbnailKind::from_extension(extension) == Some(ThumbnailKind::Pdf)
     }
 
     /// Check if file extension is an audio type
     fn is_audio_file(extension: &str) -> bool {
-        let audio_extensions = ["mp3", "wav", "ogg", "flac", "aac", "m4a", "wma", "opus"];
-        audio_extensions.contains(&extension.to_lowercase().as_str())
+        document_parser::AUDIO_EXTENSIONS.contains(&extension.to_lowercase().as_str())
     }
 
     /// Check if file extension is a code/source file
     fn is_code_file(extension: &str) -> bool {
-        let code_extensions = [
-            "html", "htm", "js", "jsx", "ts", "tsx", "css", "scss", "less",
-            "xml", "yaml", "yml", "json", "toml", "ini", "conf", "cfg",
-            "rs", "py", "rb", "go", "java", "c", "cpp", "h", "hpp",
-            "sh", "bash", "zsh", "bat", "ps1", "sql", "md", "markdown",
-        ];
-        code_extensions.contains(&extension.to_lowercase().as_str())
+        document_parser::CODE_EXTENSIONS.contains(&extension.to_lowercase().as_str())
     }
 
     /// Check if file is previewable (image, video, PDF, document, audio, or code)
@@ -861,7 +7481,7 @@ impl FileListerApp {
         thread::spawn(move || {
             // Read and decode audio in background (both are slow operations)
             let result = (|| -> Option<(Vec<i16>, u32, u16)> {
-                let file = std::fs::File::open(&path_clone).ok()?;
+                let file = retry::with_retry(|| std::fs::File::open(&path_clone)).ok()?;
                 let reader = std::io::BufReader::new(file);
                 let decoder = Decoder::new(reader).ok()?;
 
@@ -973,8 +7593,8 @@ impl FileListerApp {
         }
 
         let file = &self.filtered_files[idx];
-        let abs_path = file.absolute_path.clone();
-        let extension = file.extension.to_lowercase();
+        let abs_path = file.absolute_path().to_string();
+        let extension = file.extension().to_lowercase();
 
         // Already in cache - nothing to do
         if self.document_cache.contains_key(&abs_path) {
@@ -995,18 +7615,8 @@ impl FileListerApp {
             let path = std::path::Path::new(&abs_path);
             let ext = extension.as_str();
 
-            // Check if it's an audio file
-            let audio_extensions = ["mp3", "wav", "ogg", "flac", "aac", "m4a", "wma", "opus"];
-            let is_audio = audio_extensions.contains(&ext);
-
-            // Check if it's a code file
-            let code_extensions = [
-                "html", "htm", "js", "jsx", "ts", "tsx", "css", "scss", "less",
-                "xml", "yaml", "yml", "json", "toml", "ini", "conf", "cfg",
-                "rs", "py", "rb", "go", "java", "c", "cpp", "h", "hpp",
-                "sh", "bash", "zsh", "bat", "ps1", "sql", "md", "markdown",
-            ];
-            let is_code = code_extensions.contains(&ext);
+            let is_audio = document_parser::AUDIO_EXTENSIONS.contains(&ext);
+            let is_code = document_parser::CODE_EXTENSIONS.contains(&ext);
 
             let content = if is_audio {
                 // Audio metadata extraction
@@ -1091,12 +7701,17 @@ impl FileListerApp {
         let file = &self.filtered_files[idx];
 
         // Only load preview for previewable files (images and videos)
-        if !Self::is_previewable(&file.extension) {
+        if !Self::is_previewable(file.extension()) {
+            return;
+        }
+
+        // Never hydrate online-only cloud files unless allowed in settings
+        if file.is_cloud_placeholder && !self.settings.preview_cloud_files {
             return;
         }
 
-        let abs_path = file.absolute_path.clone();
-        let extension = file.extension.clone();
+        let abs_path = file.absolute_path().to_string();
+        let extension = file.extension().to_string();
 
         // Already in cache - nothing to do
         if self.image_cache.contains_key(&abs_path) {
@@ -1111,9 +7726,9 @@ impl FileListerApp {
         let is_video = Self::is_video_file(&extension);
         let is_pdf = Self::is_pdf_file(&extension);
 
-        // Don't try to load video thumbnails if FFmpeg isn't ready
-        if is_video && !Self::is_ffmpeg_ready() {
-            Self::debug_log("[DEBUG] load_hover_preview: Skipping video (FFmpeg not ready)");
+        // Don't try to load video thumbnails without a video decoder
+        if is_video && !Self::is_video_ready() {
+            Self::debug_log("[DEBUG] load_hover_preview: Skipping video (no video decoder)");
             return;
         }
 
@@ -1123,86 +7738,99 @@ impl FileListerApp {
             return;
         }
 
-        // Start background loading
-        let (tx, rx) = mpsc::channel();
-        self.image_receiver = Some(rx);
+        // Don't retry thumbnails that already failed
+        if self.preview_failed.contains(&abs_path) {
+            return;
+        }
+
+        // Videos: start FFmpeg only once the pointer has rested on the row
+        if is_video {
+            self.video_hover_seen = true;
+            let since = match &self.video_hover_intent {
+                Some((path, since)) if *path == abs_path => *since,
+                _ => {
+                    // Hover target changed: stop the extraction for the previous row
+                    self.cancel_video_preview();
+                    self.video_hover_intent = Some((abs_path.clone(), Instant::now()));
+                    ctx.request_repaint_after(VIDEO_HOVER_DELAY);
+                    return;
+                }
+            };
+            if since.elapsed() < VIDEO_HOVER_DELAY {
+                ctx.request_repaint_after(VIDEO_HOVER_DELAY - since.elapsed());
+                return;
+            }
+        }
+
         self.image_loading_path = Some(abs_path.clone());
         self.image_loading_start = Some(Instant::now());
 
-        Self::debug_log(&format!("[DEBUG] load_hover_preview: is_video={}, is_pdf={}, path={}", is_video, is_pdf, abs_path));
+        // Render at the display's pixel density so previews stay sharp on high-DPI monitors
+        let max_size = (self.settings.preview_max_size * ctx.pixels_per_point()).round() as u32;
 
-        // Spawn background thread to load and resize image/video/PDF thumbnail
-        thread::spawn(move || {
-            Self::debug_log(&format!("[DEBUG] Thread started for: {}", abs_path));
-            let image_data = if is_video {
-                // Extract thumbnail from video using FFmpeg
-                Self::debug_log("[DEBUG] Calling extract_video_thumbnail...");
-                Self::extract_video_thumbnail(&abs_path)
-            } else if is_pdf {
-                // Extract first page from PDF
-                Self::debug_log("[DEBUG] Calling extract_pdf_thumbnail...");
-                Self::extract_pdf_thumbnail(&abs_path)
-            } else {
-                // Load image directly
-                std::fs::read(&abs_path).ok()
-            };
-            Self::debug_log(&format!("[DEBUG] image_data result: {:?}", image_data.as_ref().map(|d| d.len())));
-
-            if let Some(data) = image_data {
-                if let Ok(image) = image::load_from_memory(&data) {
-                    // Resize large images for preview (max 400x400)
-                    let max_size = 400u32;
-                    let (width, height) = if image.width() > max_size || image.height() > max_size {
-                        let aspect = image.width() as f32 / image.height() as f32;
-                        if aspect > 1.0 {
-                            (max_size, (max_size as f32 / aspect) as u32)
-                        } else {
-                            ((max_size as f32 * aspect) as u32, max_size)
-                        }
-                    } else {
-                        (image.width(), image.height())
-                    };
+        Self::debug_log(&format!("[DEBUG] load_hover_preview: is_video={}, is_pdf={}, path={}", is_video, is_pdf, abs_path));
 
-                    let resized = image.resize(width, height, image::imageops::FilterType::Triangle);
-                    let image_buffer = resized.to_rgba8();
-                    let pixels = image_buffer.into_raw();
+        // Hand the request to the long-lived preview service
+        let Some(kind) = ThumbnailKind::from_extension(&extension) else {
+            return;
+        };
+        let options = self.thumbnail_options(max_size);
+        let service = self.preview_service.get_or_insert_with(PreviewService::start);
+        service.request_hover(abs_path, kind, options);
 
-                    let preview_data = ImagePreviewData {
-                        pixels,
-                        width: resized.width() as usize,
-                        height: resized.height() as usize,
-                    };
+        ctx.request_repaint();
+    }
 
-                    let _ = tx.send((abs_path, preview_data));
-                }
-            }
-        });
+    /// Cancel a video thumbnail that is still being extracted (the pointer left its row)
+    fn cancel_video_preview(&mut self) {
+        self.video_hover_intent = None;
+        let Some(path) = &self.image_loading_path else {
+            return;
+        };
+        let extension = std::path::Path::new(path).extension().unwrap_or_default().to_string_lossy();
+        if !Self::is_video_file(&extension) {
+            return;
+        }
+        if let Some(service) = &self.preview_service {
+            service.cancel_hover();
+        }
+        self.image_loading_path = None;
+        self.image_loading_start = None;
+    }
 
-        ctx.request_repaint();
+    /// Thumbnail options from the current settings
+    fn thumbnail_options(&self, max_size: u32) -> ThumbnailOptions {
+        ThumbnailOptions {
+            max_size,
+            video_frame_secs: self.settings.video_frame_secs,
+            pdf_render_dpi: self.settings.pdf_render_dpi,
+            quality: self.settings.preview_quality,
+        }
     }
 
-    /// Check for FFmpeg at startup (only runs once)
-    fn check_ffmpeg_availability() {
-        FFMPEG_CHECKED.call_once(|| {
-            // Check if FFmpeg exists in system PATH
-            if let Ok(output) = Command::new("where").arg("ffmpeg").output() {
-                if output.status.success() {
-                    let path_str = String::from_utf8_lossy(&output.stdout);
-                    if path_str.lines().next().is_some() {
-                        Self::debug_log("[DEBUG] FFmpeg found in system PATH");
-                        FFMPEG_AVAILABLE.store(true, Ordering::SeqCst);
-                        return;
-                    }
-                }
+    /// Look up the video decoder at startup (only runs once): FFmpeg in PATH, else
+    /// Media Foundation (Windows) or GStreamer (Linux)
+    fn check_video_decoder() {
+        VIDEO_DECODER.get_or_init(|| match thumbnail::find_video_decoder() {
+            Some(decoder) => {
+                Self::debug_log(&format!("[DEBUG] Video thumbnails through {}", decoder.label()));
+                Some(decoder.label())
+            }
+            None => {
+                Self::debug_log("[DEBUG] No video decoder found - video thumbnails disabled");
+                Self::debug_log("[DEBUG] Install FFmpeg with: winget install ffmpeg");
+                None
             }
-            Self::debug_log("[DEBUG] FFmpeg not found - video thumbnails disabled");
-            Self::debug_log("[DEBUG] Install FFmpeg with: winget install ffmpeg");
         });
     }
 
-    /// Check if FFmpeg is available
-    fn is_ffmpeg_ready() -> bool {
-        FFMPEG_AVAILABLE.load(Ordering::SeqCst)
+    /// Check if a video decoder is available
+    fn is_video_ready() -> bool {
+        Self::video_decoder_name().is_some()
+    }
+
+    fn video_decoder_name() -> Option<&'static str> {
+        VIDEO_DECODER.get().copied().flatten()
     }
 
     /// Check if FFmpeg is currently downloading (no longer used, kept for compatibility)
@@ -1210,14 +7838,6 @@ impl FileListerApp {
         false
     }
 
-    /// Get the path where Pdfium library should be stored
-    fn get_pdfium_path() -> PathBuf {
-        // Store in user's app data directory
-        let base = dirs::data_local_dir()
-            .unwrap_or_else(|| std::env::temp_dir());
-        base.join("file-lister").join("pdfium")
-    }
-
     /// Check for Pdfium at startup (only runs once), download if needed
     fn check_pdfium_availability() {
         PDFIUM_CHECKED.call_once(|| {
@@ -1229,7 +7849,7 @@ impl FileListerApp {
             }
 
             // Try to bind to downloaded Pdfium
-            let pdfium_dir = Self::get_pdfium_path();
+            let pdfium_dir = thumbnail::pdfium_dir();
             if let Ok(bindings) = Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path(&pdfium_dir)) {
                 Self::debug_log(&format!("[DEBUG] Pdfium library found at {:?}", pdfium_dir));
                 PDFIUM_AVAILABLE.store(true, Ordering::SeqCst);
@@ -1250,7 +7870,7 @@ impl FileListerApp {
         use std::io::{Read, Write};
 
         PDFIUM_DOWNLOADING.store(true, Ordering::SeqCst);
-        let pdfium_dir = Self::get_pdfium_path();
+        let pdfium_dir = thumbnail::pdfium_dir();
 
         // Create directory if it doesn't exist
         if let Err(e) = std::fs::create_dir_all(&pdfium_dir) {
@@ -1355,185 +7975,40 @@ impl FileListerApp {
         PDFIUM_DOWNLOADING.load(Ordering::SeqCst)
     }
 
-    /// Extract first page from PDF as image
-    fn extract_pdf_thumbnail(pdf_path: &str) -> Option<Vec<u8>> {
-        if !Self::is_pdfium_ready() {
-            Self::debug_log("[DEBUG] extract_pdf_thumbnail: Pdfium not ready");
-            return None;
-        }
-
-        Self::debug_log(&format!("[DEBUG] Extracting PDF thumbnail: {}", pdf_path));
-
-        // Try system library first, then downloaded library
-        let bindings = Pdfium::bind_to_system_library()
-            .or_else(|_| {
-                let pdfium_dir = Self::get_pdfium_path();
-                Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path(&pdfium_dir))
-            })
-            .ok()?;
-        let pdfium = Pdfium::new(bindings);
-        let document = pdfium.load_pdf_from_file(pdf_path, None).ok()?;
-
-        if document.pages().len() == 0 {
-            Self::debug_log("[DEBUG] PDF has no pages");
-            return None;
-        }
-
-        let page = document.pages().get(0).ok()?;
-
-        // Render at reasonable size for preview (max 400px width)
-        let page_width: f32 = page.width().value;
-        let page_height: f32 = page.height().value;
-        let scale: f32 = (400.0_f32 / page_width).min(1.0);
-        let width = (page_width * scale) as i32;
-        let height = (page_height * scale) as i32;
-
-        let bitmap = page
-            .render_with_config(
-                &PdfRenderConfig::new()
-                    .set_target_width(width)
-                    .set_target_height(height)
-            )
-            .ok()?;
-
-        // Convert to PNG bytes
-        let image = bitmap.as_image();
-        let mut png_bytes = Vec::new();
-        let mut cursor = std::io::Cursor::new(&mut png_bytes);
-        image.write_to(&mut cursor, image::ImageFormat::Png).ok()?;
-
-        Self::debug_log(&format!("[DEBUG] PDF thumbnail extracted: {} bytes", png_bytes.len()));
-        Some(png_bytes)
-    }
-
     /// Write debug log to file (for debugging on Windows GUI)
     fn debug_log(msg: &str) {
         use std::io::Write;
-        let log_path = std::env::temp_dir().join("file_lister_debug.log");
         if let Ok(mut file) = std::fs::OpenOptions::new()
             .create(true)
             .append(true)
-            .open(&log_path)
+            .open(diagnostics::debug_log_path())
         {
             let _ = writeln!(file, "{}", msg);
         }
     }
-
-    /// Find FFmpeg executable in system PATH
-    fn find_ffmpeg() -> Option<PathBuf> {
-        if let Ok(output) = Command::new("where").arg("ffmpeg").output() {
-            if output.status.success() {
-                let path_str = String::from_utf8_lossy(&output.stdout);
-                if let Some(first_line) = path_str.lines().next() {
-                    let path = PathBuf::from(first_line.trim());
-                    if path.exists() {
-                        return Some(path);
-                    }
-                }
-            }
-        }
-        None
-    }
-
-    /// Extract a thumbnail frame from a video file using FFmpeg (auto-downloads if needed)
-    fn extract_video_thumbnail(video_path: &str) -> Option<Vec<u8>> {
-        // Check if FFmpeg is ready
-        if !Self::is_ffmpeg_ready() {
-            Self::debug_log("[DEBUG] extract_video_thumbnail: FFmpeg not ready yet");
-            return None;
-        }
-
-        let ffmpeg = match Self::find_ffmpeg() {
-            Some(path) => path,
-            None => {
-                Self::debug_log("[DEBUG] extract_video_thumbnail: FFmpeg not found");
-                return None;
-            }
-        };
-        Self::debug_log(&format!("[DEBUG] Using FFmpeg: {:?}", ffmpeg));
-        Self::debug_log(&format!("[DEBUG] Video path: {}", video_path));
-
-        // Use a temp file instead of pipe (more reliable on Windows)
-        let temp_dir = std::env::temp_dir();
-        let temp_file = temp_dir.join(format!("thumb_{}.png", std::process::id()));
-        let temp_path = temp_file.to_string_lossy().to_string();
-
-        // Try to extract a frame at 1 second
-        let result = Command::new(&ffmpeg)
-            .args([
-                "-i", video_path,
-                "-ss", "00:00:01",
-                "-vframes", "1",
-                "-vcodec", "png",
-                "-y",
-                &temp_path
-            ])
-            .output();
-
-        match result {
-            Ok(output) => {
-                Self::debug_log(&format!("[DEBUG] FFmpeg exit status: {:?}", output.status));
-                if !output.stderr.is_empty() {
-                    Self::debug_log(&format!("[DEBUG] FFmpeg stderr: {}", String::from_utf8_lossy(&output.stderr)));
-                }
-
-                if output.status.success() {
-                    // Read the temp file
-                    if let Ok(data) = std::fs::read(&temp_file) {
-                        let _ = std::fs::remove_file(&temp_file);
-                        if !data.is_empty() {
-                            Self::debug_log(&format!("[DEBUG] Thumbnail extracted: {} bytes", data.len()));
-                            return Some(data);
-                        }
-                    }
-                }
-
-                // Try at 0 seconds if 1 second failed
-                Self::debug_log("[DEBUG] Trying at 0 seconds...");
-                let result2 = Command::new(&ffmpeg)
-                    .args([
-                        "-i", video_path,
-                        "-ss", "00:00:00",
-                        "-vframes", "1",
-                        "-vcodec", "png",
-                        "-y",
-                        &temp_path
-                    ])
-                    .output();
-
-                if let Ok(output2) = result2 {
-                    Self::debug_log(&format!("[DEBUG] FFmpeg (0s) exit status: {:?}", output2.status));
-                    if output2.status.success() {
-                        if let Ok(data) = std::fs::read(&temp_file) {
-                            let _ = std::fs::remove_file(&temp_file);
-                            if !data.is_empty() {
-                                Self::debug_log(&format!("[DEBUG] Thumbnail extracted at 0s: {} bytes", data.len()));
-                                return Some(data);
-                            }
-                        }
-                    }
-                }
-
-                let _ = std::fs::remove_file(&temp_file);
-                Self::debug_log("[ERROR] Failed to extract thumbnail");
-                None
-            }
-            Err(e) => {
-                Self::debug_log(&format!("[ERROR] Failed to run FFmpeg: {}", e));
-                None
-            }
-        }
-    }
-
 }
 
 impl eframe::App for FileListerApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Reset audio hover flag at start of frame
+        // Reset audio/video hover flags at start of frame
         self.audio_hover_active = false;
+        self.video_hover_seen = false;
+
+        // Track window placement and DPI changes
+        self.track_window_placement(ctx);
+
+        // Keyboard shortcuts for row actions
+        self.handle_row_shortcuts(ctx);
+
+        // F5/F6 copy/move between panes in two-panel mode
+        self.handle_pane_shortcuts(ctx);
+
+        // F5 / Ctrl+R refresh
+        self.handle_refresh_shortcut(ctx);
 
         // Check for background scan results
         self.check_scan_results();
+        self.check_folder_changes(ctx);
 
         // Check for background image load results
         self.check_image_loads(ctx);
@@ -1544,8 +8019,53 @@ impl eframe::App for FileListerApp {
         // Check for background audio load results
         self.check_audio_loads();
 
+        // Check for thumbnail export completion
+        self.check_thumbnail_export();
+        self.check_running_export();
+        self.check_cloud_export();
+        self.check_report_mail();
+        self.check_hooks();
+        self.check_mail_summary();
+        self.check_manifest();
+
+        // Check for root comparison results
+        self.check_comparison();
+
+        // Check bulk move/copy/delete progress
+        self.check_bulk_job();
+        self.check_content_hashing();
+        self.check_text_counting();
+        self.check_retention_dry_run();
+
+        // Check for elevated retry results
+        self.check_elevation();
+
+        // Check for snapshot search results
+        self.check_snapshot_search();
+
+        // Check perceptual image hashing progress
+        self.check_similar_images();
+        self.check_extension_check();
+        self.check_extension_suggestions();
+        self.check_stream_check();
+        self.check_content_duplicates();
+        self.check_permission_audit();
+        self.check_manifest_verification();
+
+        // OS notification for operations that finished in the background
+        self.handle_notifications(ctx);
+
+        // Check for second pane scan results
+        let right_pane_scanning = match &mut self.right_pane {
+            Some(pane) => {
+                pane.check_scan_results();
+                pane.is_scanning()
+            }
+            None => false,
+        };
+
         // Keep repainting while scanning or loading images/documents/audio
-        if self.is_scanning || self.image_receiver.is_some() || self.document_receiver.is_some() || self.audio_receiver.is_some() {
+        if self.is_scanning || right_pane_scanning || self.comparison_receiver.is_some() || self.bulk_job.is_some() || self.content_hashing.is_some() || self.text_counting.is_some() || self.retention_receiver.is_some() || self.elevation_receiver.is_some() || self.snapshot_browser.as_ref().is_some_and(|b| b.receiver.is_some()) || self.similar_images.as_ref().is_some_and(|s| s.receiver.is_some()) || self.extension_check.as_ref().is_some_and(|c| c.receiver.is_some()) || self.suggestion_receiver.is_some() || self.stream_check.as_ref().is_some_and(|c| c.receiver.is_some()) || self.permission_audit.as_ref().is_some_and(|a| a.receiver.is_some()) || self.manifest_verification.as_ref().is_some_and(|v| v.receiver.is_some()) || self.content_duplicates.as_ref().is_some_and(|c| c.hashing.is_some()) || self.image_loading_path.is_some() || self.thumbnail_export.is_some() || self.running_export.is_some() || self.cloud_export.is_some() || self.report_mail_receiver.is_some() || !self.hook_receivers.is_empty() || self.manifest_receiver.is_some() || self.properties.as_ref().is_some_and(|p| p.mail_receiver.is_some()) || self.document_receiver.is_some() || self.audio_receiver.is_some() {
             ctx.request_repaint();
         }
 
@@ -1569,25 +8089,112 @@ impl eframe::App for FileListerApp {
                             }
                         }
                     }
+                    if ui
+                        .button("📋 Scan folder from clipboard")
+                        .on_hover_text("Scan the folder path(s) copied from Explorer or a terminal")
+                        .clicked()
+                    {
+                        self.scan_folder_from_clipboard();
+                    }
+                    if ui
+                        .button("📋 Add files from clipboard")
+                        .on_hover_text("List exactly the copied files (one path per line)")
+                        .clicked()
+                    {
+                        self.add_files_from_clipboard();
+                    }
+                    if ui
+                        .button("🧪 Try with sample data")
+                        .on_hover_text(
+                            "Scan a freshly generated folder of fake files (images, documents, duplicates, \
+                             empty and misnamed files) to learn filters, previews and bulk operations safely",
+                        )
+                        .clicked()
+                    {
+                        self.scan_sample_data();
+                    }
                 });
 
                 ui.label(format!("{} folder(s) selected", self.selected_folders.len()));
+                let shortcut = if self.right_pane.is_some() { "Ctrl+R" } else { "F5" };
+                if ui
+                    .add_enabled(
+                        !self.is_scanning && (!self.selected_folders.is_empty() || !self.added_files.is_empty()),
+                        egui::Button::new("⟳ Refresh"),
+                    )
+                    .on_hover_text(format!(
+                        "Re-read the scanned folders from disk, keeping sort, filters, selection and scroll position ({})\n\
+                         Moves, renames and deletes made here update the list without a refresh",
+                        shortcut
+                    ))
+                    .clicked()
+                {
+                    self.refresh();
+                }
 
-                // Show loading spinner while scanning
+                // Content comparison across roots (e.g. laptop folder vs backup)
+                if self.selected_folders.len() >= 2 {
+                    let can_compare = !self.is_scanning && self.comparison_receiver.is_none() && !self.files.is_empty();
+                    if ui
+                        .add_enabled(can_compare, egui::Button::new("⚖ Compare folders"))
+                        .on_hover_text("Find files unique to each folder vs duplicated in another (size + hash)")
+                        .clicked()
+                    {
+                        self.start_root_comparison();
+                    }
+                    if self.comparison_receiver.is_some() {
+                        ui.spinner();
+                    }
+                }
+                if !self.added_files.is_empty() {
+                    ui.label(format!("+ {} file(s)", self.added_files.len()));
+                }
+
+                // Show loading spinner while scanning, with a percentage once the count pass is done
                 if self.is_scanning {
                     ui.spinner();
-                    ui.label("Scanning files...");
+                    match &self.scan_progress {
+                        Some(progress) => {
+                            let scanned = progress.scanned.load(Ordering::Relaxed);
+                            let estimated = progress.estimated.load(Ordering::Relaxed);
+                            match progress.fraction() {
+                                Some(fraction) => {
+                                    ui.add(
+                                        egui::ProgressBar::new(fraction)
+                                            .desired_width(160.0)
+                                            .text(format!("{:.0}%", fraction * 100.0)),
+                                    );
+                                    ui.label(format!("Scanning files... {} of ~{}", scanned, estimated));
+                                }
+                                None => {
+                                    ui.label(format!("Scanning files... {} (counting: {} so far)", scanned, estimated))
+                                        .on_hover_text("A quick count pass runs alongside the scan; the percentage shows once it is done");
+                                }
+                            }
+                        }
+                        None => {
+                            ui.label("Scanning files...");
+                        }
+                    }
+                    if ui
+                        .checkbox(&mut self.settings.background_priority, "🐢 Low priority")
+                        .on_hover_text("Lower the scan's CPU and I/O priority and pause it regularly so the machine stays usable (takes effect right away; same as Background priority in Settings)")
+                        .changed()
+                    {
+                        priority::configure(self.settings.background_priority, self.settings.throttle_pause_ms);
+                    }
                 }
             });
 
             // Display selected folders list with remove buttons
-            if !self.selected_folders.is_empty() {
+            if !self.selected_folders.is_empty() || !self.added_files.is_empty() {
                 ui.add_space(3.0);
                 egui::ScrollArea::vertical()
                     .id_salt("folder_list")
                     .max_height(60.0)
                     .show(ui, |ui| {
                         let mut folder_to_remove: Option<usize> = None;
+                        let mut clear_added_files = false;
                         for (idx, folder) in self.selected_folders.iter().enumerate() {
                             ui.horizontal(|ui| {
                                 ui.add_enabled_ui(!self.is_scanning, |ui| {
@@ -1598,9 +8205,22 @@ impl eframe::App for FileListerApp {
                                 ui.label(folder.display().to_string());
                             });
                         }
+                        if !self.added_files.is_empty() {
+                            ui.horizontal(|ui| {
+                                ui.add_enabled_ui(!self.is_scanning, |ui| {
+                                    if ui.small_button("x").clicked() {
+                                        clear_added_files = true;
+                                    }
+                                });
+                                ui.label(format!("{} individually added file(s)", self.added_files.len()));
+                            });
+                        }
                         if let Some(idx) = folder_to_remove {
                             self.selected_folders.remove(idx);
                             self.scan_all_folders();
+                        } else if clear_added_files {
+                            self.added_files.clear();
+                            self.scan_all_folders();
                         }
                     });
             }
@@ -1613,11 +8233,87 @@ impl eframe::App for FileListerApp {
                     let old_recursive = self.recursive;
                     ui.checkbox(&mut self.recursive, "Include subfolders (recursive)");
 
-                    // Re-scan if checkbox changed and folders are selected
-                    if old_recursive != self.recursive && !self.selected_folders.is_empty() {
+                    let old_filter = self.scan_filter();
+                    ui.checkbox(&mut self.include_hidden, "Hidden files")
+                        .on_hover_text("List dotfiles and files with the Hidden or System attribute (Thumbs.db, .DS_Store, desktop.ini)");
+                    ui.checkbox(&mut self.respect_gitignore, "Respect .gitignore")
+                        .on_hover_text("Leave out files and folders matched by .gitignore/.ignore files (e.g. target/, node_modules/)");
+
+                    // Re-scan if a checkbox changed and folders are selected
+                    if (old_recursive != self.recursive || old_filter != self.scan_filter()) && !self.selected_folders.is_empty() {
                         self.scan_all_folders();
                     }
+
+                    if ui
+                        .checkbox(&mut self.live_update, "Live update")
+                        .on_hover_text("Keep the list in sync as files are created, renamed, changed or deleted in the scanned folders")
+                        .changed()
+                    {
+                        self.folder_watcher = (self.live_update && !self.selected_folders.is_empty())
+                            .then(|| FolderWatcher::start(&self.selected_folders, self.recursive, self.scan_filter()));
+                    }
                 });
+
+                ui.add_space(10.0);
+
+                // Always-on-top toggle (for using the app as a side panel)
+                let mut always_on_top = self.settings.always_on_top;
+                if ui.checkbox(&mut always_on_top, "Always on top").changed() {
+                    self.set_always_on_top(ctx, always_on_top);
+                }
+
+                ui.add_space(10.0);
+
+                // Audit mode: no delete / move / rename (locked on with --read-only)
+                if self.read_only_locked {
+                    ui.add_enabled(false, egui::Checkbox::new(&mut true, "🔒 Read-only"))
+                        .on_disabled_hover_text("Locked by --read-only or the workstation policy: delete, move and rename are disabled");
+                } else if ui
+                    .checkbox(&mut self.settings.read_only, "🔒 Read-only")
+                    .on_hover_text("Disable delete, move and rename (for browsing production shares safely)")
+                    .changed()
+                {
+                    let _ = self.settings.save();
+                }
+
+                ui.add_space(10.0);
+
+                // Owner lookup for orphaned-file cleanup (NTFS owner SIDs)
+                if cfg!(windows) {
+                    ui.add_enabled_ui(!self.is_scanning, |ui| {
+                        if ui
+                            .checkbox(&mut self.settings.resolve_owners, "Resolve owners")
+                            .on_hover_text("Look up each file's owner while scanning, to find files owned by deleted accounts")
+                            .changed()
+                        {
+                            let _ = self.settings.save();
+                            if !self.settings.resolve_owners {
+                                self.show_orphaned_only = false;
+                            }
+                            self.scan_all_folders();
+                        }
+                    });
+                    ui.add_space(10.0);
+                }
+
+                // Pinned folders sidebar
+                if ui
+                    .checkbox(&mut self.settings.show_bookmarks, "Bookmarks")
+                    .on_hover_text("Sidebar with pinned folders for one-click scanning")
+                    .changed()
+                {
+                    let _ = self.settings.save();
+                }
+
+                // Two-panel (commander-style) layout
+                let mut dual_pane = self.right_pane.is_some();
+                if ui
+                    .checkbox(&mut dual_pane, "Two-panel mode")
+                    .on_hover_text("Second file pane; F5 copies and F6 moves the selection to the other pane")
+                    .changed()
+                {
+                    self.set_dual_pane(dual_pane);
+                }
             });
 
             ui.add_space(5.0);
@@ -1627,8 +8323,24 @@ impl eframe::App for FileListerApp {
                 ui.colored_label(egui::Color32::RED, error);
             }
 
-            // Status display
-            ui.label(&self.status_message);
+            // Status display, with the scanned volume's free space
+            ui.horizontal(|ui| {
+                ui.label(&self.status_message);
+                if let Some(space) = self.volume_space {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let size_format = &self.settings.size_format;
+                        ui.weak(format!(
+                            "💾 {} free of {}",
+                            format_size(space.free, size_format),
+                            format_size(space.total, size_format)
+                        ))
+                        .on_hover_text("Free space on the volume of the scanned folder");
+                    });
+                }
+            });
+
+            // Bulk move/copy/delete progress
+            self.show_job_progress(ui);
 
             ui.add_space(5.0);
         });
@@ -1638,134 +8350,771 @@ impl eframe::App for FileListerApp {
             ui.add_space(10.0);
             ui.horizontal(|ui| {
                 if !self.files.is_empty() {
-                    if ui.button("Export to CSV...").clicked() {
-                        if let Some(path) = rfd::FileDialog::new()
-                            .add_filter("CSV files", &["csv"])
-                            .set_file_name("files.csv")
-                            .save_file()
+                    ui.add_enabled_ui(self.thumbnail_export.is_none() && self.running_export.is_none(), |ui| {
+                        if ui
+                            .button("Export to CSV...")
+                            .on_hover_text("Pick TSV, JSON or HTML (a report with a summary and a sortable table) in the dialog for those formats; Settings → Export columns chooses each format's columns")
+                            .clicked()
+                        {
+                            if let Some(path) = rfd::FileDialog::new()
+                                .add_filter("CSV files", &["csv"])
+                                .add_filter("TSV files", &["tsv"])
+                                .add_filter("JSON files", &["json"])
+                                .add_filter("HTML report", &["html"])
+                                .set_file_name("files.csv")
+                                .save_file()
+                            {
+                                self.export_csv(&path);
+                            }
+                        }
+                        ui.checkbox(&mut self.export_excluded, "Excluded").on_hover_text(
+                            "Export the files the current filters hide instead of the listed ones \
+                             (e.g. a deletion candidate list for sign-off)",
+                        );
+                    });
+                    if ui
+                        .button("☁ Export to cloud...")
+                        .on_hover_text("Replace a sheet in Google Sheets or an Excel workbook in OneDrive/SharePoint with the listed files")
+                        .clicked()
+                    {
+                        self.show_cloud_export = true;
+                    }
+                    if ui
+                        .button("✉ Send report...")
+                        .on_hover_text("E-mail the listed files as CSV with a summary (count, total size, largest types)")
+                        .clicked()
+                    {
+                        self.open_report_draft();
+                    }
+
+                    // Split the report for systems with a row limit
+                    let split = &mut self.settings.export_split;
+                    let mut split_changed = false;
+                    egui::ComboBox::from_id_salt("export_split")
+                        .selected_text(split.label())
+                        .show_ui(ui, |ui| {
+                            let max_rows = match *split {
+                                ExportSplit::MaxRows(rows) => rows,
+                                _ => 10_000,
+                            };
+                            for option in [
+                                ExportSplit::None,
+                                ExportSplit::MaxRows(max_rows),
+                                ExportSplit::ByExtension,
+                                ExportSplit::ByTopFolder,
+                            ] {
+                                let selected = std::mem::discriminant(split) == std::mem::discriminant(&option);
+                                if ui.selectable_label(selected, option.label()).clicked() && !selected {
+                                    *split = option;
+                                    split_changed = true;
+                                }
+                            }
+                        });
+                    if let ExportSplit::MaxRows(rows) = split {
+                        split_changed |= ui
+                            .add(egui::DragValue::new(rows).range(1..=10_000_000).speed(100).suffix(" rows"))
+                            .changed();
+                    }
+                    if split_changed {
+                        let _ = self.settings.save();
+                    }
+
+                    if ui
+                        .checkbox(&mut self.settings.export_thumbnails, "Include thumbnails")
+                        .on_hover_text("Write image/video/PDF thumbnails to a thumbnails/ folder next to the report; HTML reports embed them instead")
+                        .changed()
+                    {
+                        let _ = self.settings.save();
+                    }
+
+                    if self.thumbnail_export.is_some() {
+                        ui.spinner();
+                    }
+
+                    ui.label(format!("  |  Showing {} of {} files", self.filtered_files.len(), self.files.len()));
+                    if self.settings.show_disk_usage {
+                        let usage = self.listed_usage;
+                        let size_format = &self.settings.size_format;
+                        ui.label(format!(
+                            "({}, {} on disk)",
+                            format_size(usage.logical, size_format),
+                            format_size(usage.allocated, size_format)
+                        ))
+                        .on_hover_text(format!(
+                            "Total size of the listed files, and the space they take on disk with each hard-linked file counted once ({} further hard links)",
+                            usage.extra_links
+                        ));
+                    }
+                }
+
+                // Spacer to push download buttons to the right
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    // Pdfium download button
+                    if Self::is_pdfium_ready() {
+                        ui.colored_label(egui::Color32::GREEN, "✓ PDF");
+                    } else if Self::is_pdfium_downloading() {
+                        ui.spinner();
+                        ui.label("Downloading Pdfium...");
+                        ctx.request_repaint(); // Keep updating while downloading
+                    } else {
+                        if ui.button("📥 Download Pdfium").clicked() {
+                            // Set downloading flag BEFORE spawning thread to avoid race condition
+                            PDFIUM_DOWNLOADING.store(true, Ordering::SeqCst);
+                            thread::spawn(|| {
+                                Self::download_pdfium();
+                            });
+                        }
+                    }
+
+                    ui.separator();
+
+                    // Video decoder status / FFmpeg install button
+                    if let Some(decoder) = Self::video_decoder_name() {
+                        ui.colored_label(egui::Color32::GREEN, "✓ Video")
+                            .on_hover_text(format!("Video thumbnails through {}", decoder));
+                    } else {
+                        if ui.button("📥 Install FFmpeg").clicked() {
+                            // Open FFmpeg download page
+                            let _ = open::that("https://www.gyan.dev/ffmpeg/builds/");
+                        }
+                        ui.label("⚠").on_hover_text("FFmpeg not found.\nClick to download, or run:\nwinget install ffmpeg");
+                    }
+
+                    ui.separator();
+                    ui.label("Preview Tools:");
+
+                    ui.separator();
+                    if ui
+                        .add_enabled(!self.files.is_empty(), egui::Button::new("📊 Statistics"))
+                        .on_hover_text("Modification activity of the listed files by weekday and hour")
+                        .clicked()
+                    {
+                        self.activity = Some(Activity::count(&self.filtered_files, self.settings.activity_utc_offset));
+                    }
+                    if ui
+                        .add_enabled(!self.files.is_empty(), egui::Button::new("🔐 Permissions"))
+                        .on_hover_text("Owner and who can write for each scanned folder; flags world-writable and everyone-readable folders")
+                        .clicked()
+                    {
+                        self.start_permission_audit();
+                    }
+                    if ui
+                        .button("🔎 Search snapshots")
+                        .on_hover_text("Find files in saved listings of other drives, even when they are not connected")
+                        .clicked()
+                    {
+                        self.open_snapshot_browser();
+                    }
+                    if !self.settings_locked() {
+                        if ui
+                            .button("🗄 Retention rules")
+                            .on_hover_text("Move or delete old and large files by rule, with a dry run first and undo")
+                            .clicked()
+                        {
+                            self.show_retention = true;
+                        }
+                        if ui.button("⚙ Settings").clicked() {
+                            self.show_settings = true;
+                        }
+                    }
+                    self.show_user_profile_menu(ui);
+                });
+            });
+            ui.add_space(10.0);
+        });
+
+        // Bookmarks sidebar
+        self.show_bookmarks_panel(ctx);
+
+        // Second pane (two-panel mode)
+        if self.right_pane.is_some() {
+            let mut folder_changed = false;
+            let mut transfer: Option<TransferMode> = None;
+            let active = self.active_pane == ActivePane::Right;
+            let panel = egui::SidePanel::right("right_pane")
+                .resizable(true)
+                .default_width(ctx.content_rect().width() / 2.0)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Active: {} pane", self.active_pane.label()));
+                        if ui.button("Copy → other pane (F5)").clicked() {
+                            transfer = Some(TransferMode::Copy);
+                        }
+                        if !self.is_read_only() && ui.button("Move → other pane (F6)").clicked() {
+                            transfer = Some(TransferMode::Move);
+                        }
+                    });
+                    ui.separator();
+                    if let Some(pane) = &mut self.right_pane {
+                        folder_changed = pane.show(ui, active, &self.settings.size_format, self.settings.calendar);
+                    }
+                });
+
+            // A click inside a pane makes it the active one
+            if let Some(pos) = ctx.input(|i| i.pointer.press_origin().filter(|_| i.pointer.primary_pressed())) {
+                if panel.response.rect.contains(pos) {
+                    self.active_pane = ActivePane::Right;
+                } else if ctx.available_rect().contains(pos) {
+                    self.active_pane = ActivePane::Left;
+                }
+            }
+
+            if folder_changed {
+                self.settings.right_pane_folder = self.right_pane.as_ref().and_then(|p| p.folder.clone());
+                let _ = self.settings.save();
+            }
+            if let Some(mode) = transfer {
+                self.transfer_between_panes(mode);
+            }
+        }
+
+        // Central panel for filter and table
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if !self.files.is_empty() {
+                // Filter input
+                ui.horizontal(|ui| {
+                    ui.label("Filter:");
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.filter_text)
+                            .hint_text("Type to filter by name, extension, or path...")
+                            .desired_width(300.0)
+                    );
+                    if response.changed() {
+                        self.apply_filter();
+                    }
+                    if ui.button("Clear").clicked() {
+                        self.filter_text.clear();
+                        self.apply_filter();
+                    }
+
+                    // Active content filter from a root comparison
+                    let mut clear_content_filter = false;
+                    if let Some((label, _)) = &self.content_filter {
+                        ui.add_space(10.0);
+                        ui.colored_label(egui::Color32::from_rgb(90, 150, 230), label);
+                        clear_content_filter = ui.small_button("✖").on_hover_text("Show all files").clicked();
+                    }
+                    if clear_content_filter {
+                        self.content_filter = None;
+                        self.apply_filter();
+                    }
+
+                    ui.add_space(20.0);
+
+                    // Show duplicates only checkbox
+                    let old_show_duplicates = self.show_duplicates_only;
+                    ui.checkbox(&mut self.show_duplicates_only, counted("Show duplicates only", Some(self.filter_counts.duplicates)));
+                    if old_show_duplicates != self.show_duplicates_only {
+                        self.apply_filter();
+                    }
+
+                    // Visually similar images instead of equal names
+                    if self.show_duplicates_only {
+                        let mut similar = self.similar_images.is_some();
+                        if ui
+                            .checkbox(&mut similar, "🖼 Similar images")
+                            .on_hover_text("Group photos that look the same (perceptual hash), even if recompressed or resized")
+                            .changed()
+                        {
+                            if similar {
+                                self.stop_content_duplicates();
+                                self.start_similar_images();
+                            } else {
+                                self.similar_images = None;
+                                self.apply_filter();
+                            }
+                        }
+                        if let Some(similar) = &self.similar_images {
+                            if similar.receiver.is_some() {
+                                ui.spinner();
+                                ui.label(format!("{}/{}", similar.progress.0, similar.progress.1));
+                            } else {
+                                let response = ui
+                                    .add(
+                                        egui::Slider::new(&mut self.settings.similar_image_distance, 0..=similarity::MAX_DISTANCE)
+                                            .text("tolerance"),
+                                    )
+                                    .on_hover_text("0 = visually identical; higher values also group edited or cropped variants");
+                                if response.changed() {
+                                    self.regroup_similar_images();
+                                }
+                            }
+                            let before = self.settings.similar_image_hash;
+                            egui::ComboBox::from_id_salt("similar_image_hash")
+                                .selected_text(self.settings.similar_image_hash.label())
+                                .show_ui(ui, |ui| {
+                                    for method in PerceptualHash::ALL {
+                                        ui.selectable_value(&mut self.settings.similar_image_hash, method, method.label());
+                                    }
+                                })
+                                .response
+                                .on_hover_text("pHash compares the image's low frequencies, so re-exports with other brightness, contrast or colors still match; it takes longer");
+                            if self.settings.similar_image_hash != before {
+                                // Rehash with the other method (cached hashes of either are kept)
+                                self.start_similar_images();
+                            }
+                        }
+
+                        // Equal content whatever the names (size first, then SHA-256)
+                        let mut by_content = self.content_duplicates.is_some();
+                        if ui
+                            .checkbox(&mut by_content, "🟰 Same content")
+                            .on_hover_text("Group files with identical content (same size, then SHA-256), whatever their names")
+                            .changed()
+                        {
+                            if by_content {
+                                self.similar_images = None;
+                                self.start_content_duplicates();
+                            } else {
+                                self.stop_content_duplicates();
+                                self.apply_filter();
+                            }
+                        }
+                        if let Some(running) = self.content_duplicates.as_ref().and_then(|c| c.hashing.as_ref()) {
+                            ui.spinner();
+                            ui.label(format!("{}/{}", running.done, running.total));
+                        }
+
+                        if ui
+                            .button("🗂 Manage...")
+                            .on_hover_text("Review the groups side by side, pick which file to keep and delete or move the rest")
+                            .clicked()
+                        {
+                            self.open_duplicate_manager();
+                        }
+                    }
+
+                    ui.add_space(10.0);
+
+                    // Versions and copies of the same document
+                    if ui
+                        .checkbox(&mut self.show_versions_only, counted("🗐 Versions", self.filter_counts.versions))
+                        .on_hover_text("Show only versions and copies of the same file (report_v1, report_final, photo (1)), grouped with the one to keep first")
+                        .changed()
+                    {
+                        self.apply_filter();
+                    }
+                    if self.show_versions_only
+                        && ui
+                            .button("Select older versions")
+                            .on_hover_text("Select all but the suggested version to keep (★) of each group")
+                            .clicked()
+                    {
+                        self.select_older_versions();
+                    }
+
+                    ui.add_space(10.0);
+
+                    // Modified today / yesterday / in the last 7 days (click again to turn off)
+                    ui.label("Modified:");
+                    for (range, count) in RecentDays::ALL.into_iter().zip(self.filter_counts.recent) {
+                        let on = self.modified_within == Some(range);
+                        if ui
+                            .selectable_label(on, counted(range.label(), Some(count)))
+                            .on_hover_text("By calendar days of this computer's time zone")
+                            .clicked()
+                        {
+                            self.modified_within = if on { None } else { Some(range) };
+                            self.apply_filter();
+                        }
+                    }
+
+                    ui.add_space(10.0);
+
+                    // Online-only cloud files checkbox
+                    if ui
+                        .checkbox(&mut self.show_cloud_only, counted("☁ Online-only", Some(self.filter_counts.cloud)))
+                        .on_hover_text("Show only cloud placeholder files (OneDrive, Dropbox, iCloud) that are not stored locally")
+                        .changed()
+                    {
+                        self.apply_filter();
+                    }
+
+                    ui.add_space(10.0);
+
+                    // Skip files already looked at in earlier sessions
+                    if ui
+                        .checkbox(&mut self.hide_reviewed, counted("Hide reviewed", Some(self.filter_counts.reviewed)))
+                        .on_hover_text("Hide files marked as reviewed (R, or \"✔ Mark reviewed\"); a file modified since shows again")
+                        .changed()
+                    {
+                        self.apply_filter();
+                    }
+
+                    ui.add_space(10.0);
+
+                    // Cold data: files nobody has opened since they were written
+                    if ui
+                        .checkbox(&mut self.show_never_accessed, counted("Never accessed", Some(self.filter_counts.never_accessed)))
+                        .on_hover_text("Show only files not read since they were created or last written (cold data to archive)")
+                        .changed()
+                    {
+                        self.apply_filter();
+                    }
+                    if (self.show_never_accessed || self.settings.show_last_accessed)
+                        && !self.access_time_mode.is_reliable()
+                    {
+                        ui.colored_label(egui::Color32::from_rgb(255, 140, 0), "⚠ access times unreliable")
+                            .on_hover_text(self.access_time_mode.describe());
+                    }
+
+                    ui.add_space(10.0);
+
+                    // Long names and paths break backup and sync tools
+                    if ui
+                        .checkbox(&mut self.show_too_long, counted("Too long", Some(self.filter_counts.too_long)))
+                        .on_hover_text("Show only files whose name or full path is longer than the limits")
+                        .changed()
+                    {
+                        self.apply_filter();
+                    }
+                    if self.show_too_long || self.settings.show_lengths {
+                        let name_limit = ui
+                            .add(egui::DragValue::new(&mut self.settings.name_length_limit).range(1..=1000).prefix("name > "))
+                            .on_hover_text("Longest name most file systems accept: 255");
+                        let path_limit = ui
+                            .add(egui::DragValue::new(&mut self.settings.path_length_limit).range(1..=32767).prefix("path > "))
+                            .on_hover_text("Windows MAX_PATH: 260 (lengths count UTF-16 units, as Windows does)");
+                        if name_limit.changed() || path_limit.changed() {
+                            let _ = self.settings.save();
+                            if self.show_too_long {
+                                self.apply_filter();
+                            }
+                        }
+                    }
+
+                    ui.add_space(10.0);
+
+                    // Names that break copies to Windows, SharePoint or ZIP
+                    if ui
+                        .checkbox(&mut self.show_unsafe_names, counted("Unsafe names", Some(self.filter_counts.unsafe_names)))
+                        .on_hover_text("Show only names that won't survive a copy to Windows, SharePoint/OneDrive or a ZIP: reserved names (CON, NUL...), < > : \" / \\ | ? *, trailing spaces or dots, emoji")
+                        .changed()
+                    {
+                        self.apply_filter();
+                    }
+
+                    ui.add_space(10.0);
+
+                    // Leftovers of failed syncs and copies
+                    if ui
+                        .checkbox(&mut self.show_empty_only, counted("Empty", Some(self.filter_counts.empty)))
+                        .on_hover_text("Show only zero-byte files and, in recursive scans, folders with nothing in them (or only empty folders)")
+                        .changed()
+                    {
+                        self.apply_filter();
+                    }
+                    if self.show_empty_only
+                        && !self.is_read_only()
+                        && ui
+                            .add_enabled(!self.filtered_files.is_empty(), egui::Button::new("🗑 Delete all empty..."))
+                            .on_hover_text("Delete the listed empty files and folders, after a confirmation")
+                            .clicked()
+                    {
+                        self.prepare_empty_delete();
+                    }
+
+                    ui.add_space(10.0);
+
+                    // Extension that disagrees with the file's magic bytes (.jpg that is a PNG, .exe renamed .txt)
+                    let mut wrong_extension = self.extension_check.is_some();
+                    if ui
+                        .checkbox(&mut wrong_extension, counted("Wrong extension", self.filter_counts.wrong_extension))
+                        .on_hover_text("Show only files whose content is another format than their extension says (reads the first bytes of each file; online-only files are skipped)")
+                        .changed()
+                    {
+                        if wrong_extension {
+                            self.start_extension_check();
+                        } else {
+                            self.extension_check = None;
+                            self.apply_filter();
+                        }
+                    }
+                    if let Some(check) = &self.extension_check {
+                        if check.receiver.is_some() {
+                            ui.spinner();
+                            ui.label(format!("{}/{}", check.progress.0, check.progress.1));
+                        } else if !self.is_read_only()
+                            && ui
+                                .add_enabled(!self.filtered_files.is_empty(), egui::Button::new("Fix extensions..."))
+                                .on_hover_text("Rename to the extension of the detected format (selected files, or all listed), with a preview")
+                                .clicked()
                         {
-                            self.export_csv(&path);
+                            self.open_bulk_rename(BulkRenameKind::Extensions(ExtensionRules::mismatched_only()));
                         }
                     }
 
-                    ui.label(format!("  |  Showing {} of {} files", self.filtered_files.len(), self.files.len()));
-                }
+                    // NTFS alternate data streams (Zone.Identifier, hidden data)
+                    if cfg!(windows) {
+                        ui.add_space(10.0);
+                        let mut with_streams = self.stream_check.is_some();
+                        if ui
+                            .checkbox(&mut with_streams, counted("Streams", self.filter_counts.streams))
+                            .on_hover_text("Show only files with alternate data streams (Zone.Identifier download marks, data hidden next to the content), with their names and sizes")
+                            .changed()
+                        {
+                            if with_streams {
+                                self.start_stream_check();
+                            } else {
+                                self.stream_check = None;
+                                self.apply_filter();
+                            }
+                        }
+                        if let Some(check) = self.stream_check.as_ref().filter(|c| c.receiver.is_some()) {
+                            ui.spinner();
+                            ui.label(format!("{}/{}", check.progress.0, check.progress.1));
+                        }
+                    }
+
+                    // Orphaned owner checkbox (needs owner lookup)
+                    if self.settings.resolve_owners {
+                        ui.add_space(10.0);
+                        if ui
+                            .checkbox(&mut self.show_orphaned_only, counted("Orphaned owner", Some(self.filter_counts.orphaned)))
+                            .on_hover_text("Show only files whose owner account no longer exists")
+                            .changed()
+                        {
+                            self.apply_filter();
+                        }
+                    }
+
+                    ui.add_space(10.0);
+
+                    // File age heatmap column
+                    if ui
+                        .checkbox(&mut self.settings.show_age_heatmap, "Age heatmap")
+                        .on_hover_text("Color each row from modified today (red) to untouched for years (blue)")
+                        .changed()
+                    {
+                        let _ = self.settings.save();
+                    }
+
+                    // Last access time column
+                    if ui
+                        .checkbox(&mut self.settings.show_last_accessed, "Last accessed")
+                        .on_hover_text("Show when each file was last read (if the volume records access times)")
+                        .changed()
+                    {
+                        let _ = self.settings.save();
+                    }
+
+                    // Name and path length columns
+                    if ui
+                        .checkbox(&mut self.settings.show_lengths, "Lengths")
+                        .on_hover_text("Show name length and full path length columns; values over the limits are red")
+                        .changed()
+                    {
+                        let _ = self.settings.save();
+                    }
 
-                // Spacer to push download buttons to the right
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    // Pdfium download button
-                    if Self::is_pdfium_ready() {
-                        ui.colored_label(egui::Color32::GREEN, "✓ PDF");
-                    } else if Self::is_pdfium_downloading() {
-                        ui.spinner();
-                        ui.label("Downloading Pdfium...");
-                        ctx.request_repaint(); // Keep updating while downloading
-                    } else {
-                        if ui.button("📥 Download Pdfium").clicked() {
-                            // Set downloading flag BEFORE spawning thread to avoid race condition
-                            PDFIUM_DOWNLOADING.store(true, Ordering::SeqCst);
-                            thread::spawn(|| {
-                                Self::download_pdfium();
+                    // Allocated size column and hard link markers
+                    if ui
+                        .checkbox(&mut self.settings.show_disk_usage, "On disk")
+                        .on_hover_text("Show the space each file takes on disk and mark hard-linked files (exported too)")
+                        .changed()
+                    {
+                        let _ = self.settings.save();
+                    }
+
+                    // Checksum column, filled in the background
+                    let old_hashing = (self.settings.show_hashes, self.settings.hash_algorithm);
+                    ui.checkbox(&mut self.settings.show_hashes, "Hash")
+                        .on_hover_text("Compute a checksum of every file in the background after each scan; shown in a Hash column and exported");
+                    if self.settings.show_hashes {
+                        egui::ComboBox::from_id_salt("hash_algorithm")
+                            .selected_text(self.settings.hash_algorithm.label())
+                            .width(70.0)
+                            .show_ui(ui, |ui| {
+                                for algorithm in HashAlgorithm::ALL {
+                                    ui.selectable_value(&mut self.settings.hash_algorithm, algorithm, algorithm.label());
+                                }
                             });
+                    }
+                    if let Some(running) = &self.content_hashing {
+                        ui.spinner();
+                        ui.weak(format!("{} / {}", running.done, running.total));
+                    }
+                    if old_hashing != (self.settings.show_hashes, self.settings.hash_algorithm) {
+                        if old_hashing.1 != self.settings.hash_algorithm {
+                            // Checksums of the other algorithm no longer apply
+                            for file in self.files.iter_mut().chain(self.filtered_files.iter_mut()) {
+                                file.hash = None;
+                            }
                         }
+                        self.start_content_hashing();
+                        let _ = self.settings.save();
                     }
 
-                    ui.separator();
-
-                    // FFmpeg status/install button
-                    if Self::is_ffmpeg_ready() {
-                        ui.colored_label(egui::Color32::GREEN, "✓ Video");
-                    } else {
-                        if ui.button("📥 Install FFmpeg").clicked() {
-                            // Open FFmpeg download page
-                            let _ = open::that("https://www.gyan.dev/ffmpeg/builds/");
+                    // Line / word counts of text files, filled in the background
+                    if ui
+                        .checkbox(&mut self.settings.show_text_counts, "Lines/words")
+                        .on_hover_text("Count the lines of text and code files (and the words of .txt/.md prose) in the background after each scan; shown in Lines and Words columns and exported")
+                        .changed()
+                    {
+                        if self.settings.show_text_counts {
+                            self.start_text_counting();
+                        } else {
+                            self.stop_text_counting();
                         }
-                        ui.label("⚠").on_hover_text("FFmpeg not found.\nClick to download, or run:\nwinget install ffmpeg");
+                        let _ = self.settings.save();
+                    }
+                    if let Some(running) = &self.text_counting {
+                        ui.spinner();
+                        ui.weak(format!("{} / {}", running.done, running.total));
                     }
 
-                    ui.separator();
-                    ui.label("Preview Tools:");
-                });
-            });
-            ui.add_space(10.0);
-        });
-
-        // Central panel for filter and table
-        egui::CentralPanel::default().show(ctx, |ui| {
-            if !self.files.is_empty() {
-                // Filter input
-                ui.horizontal(|ui| {
-                    ui.label("Filter:");
-                    let response = ui.add(
-                        egui::TextEdit::singleline(&mut self.filter_text)
-                            .hint_text("Type to filter by name, extension, or path...")
-                            .desired_width(300.0)
-                    );
-                    if response.changed() {
+                    // Companion files as one row
+                    if ui
+                        .checkbox(&mut self.settings.group_sidecars, "Group sidecars")
+                        .on_hover_text("Show RAW+JPEG pairs, video+subtitles and .xmp sidecars as one expandable row")
+                        .changed()
+                    {
+                        let _ = self.settings.save();
                         self.apply_filter();
                     }
-                    if ui.button("Clear").clicked() {
-                        self.filter_text.clear();
-                        self.apply_filter();
+                    if self.settings.group_sidecars
+                        && ui
+                            .checkbox(&mut self.settings.sidecars_follow, "Move/delete together")
+                            .on_hover_text("Moving or deleting a grouped file also moves or deletes its companions")
+                            .changed()
+                    {
+                        let _ = self.settings.save();
                     }
 
-                    ui.add_space(20.0);
-
-                    // Show duplicates only checkbox
-                    let old_show_duplicates = self.show_duplicates_only;
-                    ui.checkbox(&mut self.show_duplicates_only, "Show duplicates only");
-                    if old_show_duplicates != self.show_duplicates_only {
+                    // Folder rows with totals
+                    if ui
+                        .checkbox(&mut self.settings.show_folders, "Show folders")
+                        .on_hover_text("List the subfolders above the files with their total size and item count; sort by size to find what takes the space")
+                        .changed()
+                    {
+                        let _ = self.settings.save();
+                        self.refresh_folder_rows();
                         self.apply_filter();
                     }
 
-                    ui.add_space(10.0);
-
-                    // Show today only checkbox
-                    let old_show_today = self.show_today_only;
-                    ui.checkbox(&mut self.show_today_only, "Show today only");
-                    if old_show_today != self.show_today_only {
-                        self.apply_filter();
+                    // Inline row actions column
+                    if ui
+                        .checkbox(&mut self.settings.show_row_actions, "Row actions")
+                        .on_hover_text("Open / open location / delete buttons at the end of each row")
+                        .changed()
+                    {
+                        let _ = self.settings.save();
                     }
 
                     ui.add_space(20.0);
 
-                    // Move Selected and Delete Selected buttons
+                    // Move Selected and Delete Selected buttons (hidden in read-only mode)
                     let selected_count = self.selected_files.len();
-                    ui.add_enabled_ui(selected_count > 0, |ui| {
-                        if ui.button(format!("Move Selected ({})", selected_count)).clicked() {
-                            self.move_selected_files();
+                    if !self.is_read_only() {
+                        ui.add_enabled_ui(selected_count > 0, |ui| {
+                            if ui.button(format!("Move / Copy Selected ({})", selected_count)).clicked() {
+                                self.move_selected_files();
+                            }
+                            if ui.button(format!("Delete Selected ({})", selected_count)).clicked() {
+                                self.prepare_bulk_delete();
+                            }
+                        });
+                        if ui
+                            .add_enabled(!self.filtered_files.is_empty(), egui::Button::new("Normalize Extensions..."))
+                            .on_hover_text("Lowercase extensions, fix variants like JPEG -> jpg and add missing ones (selected files, or all listed)")
+                            .clicked()
+                        {
+                            self.open_bulk_rename(BulkRenameKind::Extensions(ExtensionRules::default()));
+                        }
+                        if !self.extension_suggestions.is_empty()
+                            && ui
+                                .button(format!("Append detected extensions ({})", self.extension_suggestions.len()))
+                                .on_hover_text("Add the extension detected from the content to files that have none, shown as \"(png?)\" (selected files, or all listed), with a preview")
+                                .clicked()
+                        {
+                            self.open_bulk_rename(BulkRenameKind::Extensions(ExtensionRules::missing_only()));
+                        }
+                        if ui
+                            .add_enabled(!self.filtered_files.is_empty(), egui::Button::new("Sanitize Names..."))
+                            .on_hover_text("Preview and fix names that won't survive a copy to Windows, SharePoint or a ZIP (selected files, or all listed)")
+                            .clicked()
+                        {
+                            self.open_bulk_rename(BulkRenameKind::SafeNames);
+                        }
+                    }
+                    if ui
+                        .add_enabled(selected_count > 0, egui::Button::new(format!("📂 Open Locations ({})", selected_count)))
+                        .on_hover_text(format!(
+                            "Open the folder of each selected file once; more than {} folders are confirmed first (at most {})",
+                            OPEN_LOCATIONS_UNASKED, OPEN_LOCATIONS_MAX
+                        ))
+                        .clicked()
+                    {
+                        self.open_selected_locations();
+                    }
+                    ui.add_enabled_ui(!self.filtered_files.is_empty(), |ui| {
+                        if ui
+                            .button("✔ Mark reviewed")
+                            .on_hover_text("Mark the selected files (or all listed) as reviewed, for \"Hide reviewed\" in later sessions")
+                            .clicked()
+                        {
+                            self.mark_reviewed(true);
                         }
-                        if ui.button(format!("Delete Selected ({})", selected_count)).clicked() {
-                            self.prepare_bulk_delete();
+                        if ui.button("Unmark").on_hover_text("Remove the reviewed mark (selected files, or all listed)").clicked() {
+                            self.mark_reviewed(false);
                         }
                     });
+                    if !self.stale_paths.is_empty()
+                        && ui
+                            .button(format!("⟳ Refresh stale rows ({})", self.stale_paths.len()))
+                            .on_hover_text("Re-read only the rows that were moved or deleted since the scan")
+                            .clicked()
+                    {
+                        self.refresh_stale_rows();
+                    }
+                });
+
+                // Selection total and "fit to target size" helper
+                ui.horizontal(|ui| {
+                    self.show_selection_summary(ui);
                 });
 
                 ui.add_space(5.0);
                 ui.separator();
                 ui.add_space(5.0);
 
-                let available_height = ui.available_height();
+                self.show_scan_report(ui);
+                self.show_folder_notes(ui);
 
-                // Store paths and duplicate info for table (to avoid borrow issues)
-                let file_paths: Vec<String> = self.filtered_files
-                    .iter()
-                    .map(|f| f.absolute_path.clone())
-                    .collect();
+                if let Some(explanation) = self.empty_list_explanation() {
+                    ui.colored_label(egui::Color32::from_rgb(255, 140, 0), explanation);
+                }
 
-                let duplicate_info: Vec<Option<usize>> = self.filtered_files
-                    .iter()
-                    .map(|f| self.is_duplicate(&f.full_name))
-                    .collect();
+                let available_height = ui.available_height();
 
-                // Track header checkbox state
-                let all_selected = !self.filtered_files.is_empty()
-                    && self.selected_files.len() == self.filtered_files.len();
+                // Group column of the duplicates view (similar images or equal content)
+                let show_group_column = self.show_duplicates_only && self.duplicate_groups().is_some();
+                let mut toggled_sidecars: Option<usize> = None;
 
-                TableBuilder::new(ui)
+                // Track header checkbox state
+                let file_rows = self.filtered_files.iter().filter(|f| !f.is_dir).count();
+                let all_selected = file_rows > 0 && self.selected_files.len() == file_rows;
+
+                let show_age_heatmap = self.settings.show_age_heatmap;
+                let show_last_accessed = self.settings.show_last_accessed;
+                let show_lengths = self.settings.show_lengths;
+                let show_hashes = self.settings.show_hashes;
+                let show_text_counts = self.settings.show_text_counts;
+                let show_disk_usage = self.settings.show_disk_usage;
+                let show_folders = self.settings.show_folders;
+                let (name_limit, path_limit) = (self.settings.name_length_limit, self.settings.path_length_limit);
+                let show_row_actions = self.settings.show_row_actions;
+                // Shift-click on a header sets the secondary sort
+                let secondary_click = ui.input(|i| i.modifiers.shift);
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs() as i64)
+                    .unwrap_or(0);
+
+                let mut table = TableBuilder::new(ui)
                     .striped(true)
                     .resizable(true)
-                    .sense(egui::Sense::hover())  // Enable hover detection
+                    .sense(egui::Sense::click())  // Enable hover detection and row context menu
                     .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
                     .min_scrolled_height(100.0)
                     .max_scroll_height(available_height)
@@ -1773,10 +9122,48 @@ impl eframe::App for FileListerApp {
                     .column(Column::initial(50.0).resizable(false).clip(true))  // Icons (type + dup)
                     .column(Column::initial(150.0).resizable(true).clip(true))  // Name
                     .column(Column::initial(70.0).resizable(true).clip(true))   // Extension
-                    .column(Column::initial(80.0).resizable(true).clip(true))   // Size
-                    .column(Column::initial(130.0).resizable(true).clip(true))  // Date Modified
-                    .column(Column::initial(200.0).resizable(true).clip(true))  // Path
-                    .column(Column::remainder().resizable(true).clip(true))     // Full Path
+                    .column(Column::initial(80.0).resizable(true).clip(true));  // Size
+                if show_disk_usage {
+                    table = table.column(Column::initial(80.0).resizable(true).clip(true)); // On disk
+                }
+                if show_folders {
+                    table = table.column(Column::initial(100.0).resizable(true).clip(true)); // Items + content mix
+                }
+                table = table.column(Column::initial(130.0).resizable(true).clip(true)); // Date Modified
+                if show_last_accessed {
+                    table = table.column(Column::initial(130.0).resizable(true).clip(true)); // Last accessed
+                }
+                if show_age_heatmap {
+                    table = table.column(Column::exact(36.0).resizable(false)); // Age heatmap
+                }
+                table = table.column(Column::initial(200.0).resizable(true).clip(true)); // Path
+                if show_lengths {
+                    table = table
+                        .column(Column::initial(50.0).resizable(true).clip(true))  // Name length
+                        .column(Column::initial(50.0).resizable(true).clip(true)); // Path length
+                }
+                if show_hashes {
+                    table = table.column(Column::initial(120.0).resizable(true).clip(true)); // Hash
+                }
+                if show_text_counts {
+                    table = table
+                        .column(Column::initial(60.0).resizable(true).clip(true))  // Lines
+                        .column(Column::initial(60.0).resizable(true).clip(true)); // Words
+                }
+                if show_group_column {
+                    table = table.column(Column::initial(50.0).resizable(true).clip(true)); // Duplicate group
+                }
+                table = table.column(Column::remainder().resizable(true).clip(true)); // Full Path
+                if show_row_actions {
+                    table = table.column(Column::exact(84.0).resizable(false)); // Row actions
+                }
+                // Back to where the list was before a refresh
+                if !self.is_scanning {
+                    if let Some(offset) = self.pending_scroll.take() {
+                        table = table.vertical_scroll_offset(offset);
+                    }
+                }
+                let scroll = table
                     .header(24.0, |mut header| {
                         header.col(|ui| {
                             // Header checkbox for select all/none
@@ -1793,53 +9180,182 @@ impl eframe::App for FileListerApp {
                             ui.strong("");  // Icon column - no header text
                         });
                         header.col(|ui| {
-                            if ui.button(format!("Name{}", self.get_sort_indicator(SortColumn::Name))).clicked() {
-                                self.toggle_sort(SortColumn::Name);
+                            if ui.button(format!("Name{}", self.get_sort_indicator(columns::NAME))).clicked() {
+                                self.toggle_sort(columns::NAME, secondary_click);
                             }
                         });
                         header.col(|ui| {
-                            if ui.button(format!("Ext{}", self.get_sort_indicator(SortColumn::Extension))).clicked() {
-                                self.toggle_sort(SortColumn::Extension);
+                            if ui.button(format!("Ext{}", self.get_sort_indicator(columns::EXTENSION))).clicked() {
+                                self.toggle_sort(columns::EXTENSION, secondary_click);
                             }
                         });
                         header.col(|ui| {
-                            if ui.button(format!("Size{}", self.get_sort_indicator(SortColumn::Size))).clicked() {
-                                self.toggle_sort(SortColumn::Size);
+                            if ui.button(format!("Size{}", self.get_sort_indicator(columns::SIZE))).clicked() {
+                                self.toggle_sort(columns::SIZE, secondary_click);
                             }
                         });
+                        if show_disk_usage {
+                            header.col(|ui| {
+                                let label = format!("On disk{}", self.get_sort_indicator(columns::ON_DISK));
+                                if ui
+                                    .button(label)
+                                    .on_hover_text("Space allocated on disk (whole clusters; less for sparse, compressed or online-only files)")
+                                    .clicked()
+                                {
+                                    self.toggle_sort(columns::ON_DISK, secondary_click);
+                                }
+                            });
+                        }
+                        if show_folders {
+                            header.col(|ui| {
+                                let label = format!("Items{}", self.get_sort_indicator(columns::ITEMS));
+                                if ui.button(label).on_hover_text("Files and subfolders in a folder, at any depth").clicked() {
+                                    self.toggle_sort(columns::ITEMS, secondary_click);
+                                }
+                            });
+                        }
                         header.col(|ui| {
-                            if ui.button(format!("Date{}", self.get_sort_indicator(SortColumn::Date))).clicked() {
-                                self.toggle_sort(SortColumn::Date);
+                            if ui.button(format!("Date{}", self.get_sort_indicator(columns::MODIFIED))).clicked() {
+                                self.toggle_sort(columns::MODIFIED, secondary_click);
                             }
                         });
+                        if show_last_accessed {
+                            header.col(|ui| {
+                                let reliable = self.access_time_mode.is_reliable();
+                                let label = format!(
+                                    "Accessed{}{}",
+                                    if reliable { "" } else { " ⚠" },
+                                    self.get_sort_indicator(columns::ACCESSED)
+                                );
+                                if ui.button(label).on_hover_text(self.access_time_mode.describe()).clicked() {
+                                    self.toggle_sort(columns::ACCESSED, secondary_click);
+                                }
+                            });
+                        }
+                        if show_age_heatmap {
+                            header.col(|ui| {
+                                ui.strong("Age");
+                            });
+                        }
                         header.col(|ui| {
-                            if ui.button(format!("Path{}", self.get_sort_indicator(SortColumn::Path))).clicked() {
-                                self.toggle_sort(SortColumn::Path);
+                            if ui.button(format!("Path{}", self.get_sort_indicator(columns::RELATIVE_PATH))).clicked() {
+                                self.toggle_sort(columns::RELATIVE_PATH, secondary_click);
                             }
                         });
+                        if show_lengths {
+                            header.col(|ui| {
+                                let label = format!("Name#{}", self.get_sort_indicator(columns::NAME_LENGTH));
+                                if ui.button(label).on_hover_text("Name length (UTF-16 units)").clicked() {
+                                    self.toggle_sort(columns::NAME_LENGTH, secondary_click);
+                                }
+                            });
+                            header.col(|ui| {
+                                let label = format!("Path#{}", self.get_sort_indicator(columns::PATH_LENGTH));
+                                if ui.button(label).on_hover_text("Full path length (UTF-16 units)").clicked() {
+                                    self.toggle_sort(columns::PATH_LENGTH, secondary_click);
+                                }
+                            });
+                        }
+                        if show_hashes {
+                            header.col(|ui| {
+                                let label = format!("{}{}", self.settings.hash_algorithm.label(), self.get_sort_indicator(columns::HASH));
+                                if ui.button(label).on_hover_text("Checksum of the file's content").clicked() {
+                                    self.toggle_sort(columns::HASH, secondary_click);
+                                }
+                            });
+                        }
+                        if show_text_counts {
+                            for (column, tooltip) in [
+                                (columns::LINES, "Lines of a text or code file"),
+                                (columns::WORDS, "Words of a prose file (.txt, .md, ...)"),
+                            ] {
+                                header.col(|ui| {
+                                    let label = format!("{}{}", column.header, self.get_sort_indicator(column));
+                                    if ui.button(label).on_hover_text(tooltip).clicked() {
+                                        self.toggle_sort(column, secondary_click);
+                                    }
+                                });
+                            }
+                        }
+                        if show_group_column {
+                            header.col(|ui| {
+                                ui.strong("Group").on_hover_text("Duplicate group (files listed together)");
+                            });
+                        }
                         header.col(|ui| {
                             ui.strong("Full Path");
                         });
+                        if show_row_actions {
+                            header.col(|ui| {
+                                ui.strong("Actions");
+                            });
+                        }
                     })
                     .body(|body| {
                         body.rows(24.0, self.filtered_files.len(), |mut row| {
                             let idx = row.index();
-                            // Clone all file data upfront to avoid borrow conflicts
-                            let file_name = self.filtered_files[idx].name.clone();
-                            let file_extension = self.filtered_files[idx].extension.clone();
-                            let file_size = self.filtered_files[idx].file_size;
-                            let file_modified = self.filtered_files[idx].modified_timestamp;
-                            let file_relative_path = self.filtered_files[idx].relative_path.clone();
-                            let file_absolute_path = self.filtered_files[idx].absolute_path.clone();
-                            let file_path = file_paths[idx].clone();
+                            // Shared display text of the row (built once, not per frame), so the
+                            // cells can call &mut self methods without cloning the file's strings
+                            let text = self.row_text(idx);
+                            let file_name: &str = &text.name;
+                            let file_extension: &str = &text.extension;
+                            let file_absolute_path: &str = &text.absolute_path;
+                            let file = &self.filtered_files[idx];
+                            let file_modified = file.modified_timestamp;
+                            let is_cloud = file.is_cloud_placeholder;
+                            let is_dir = file.is_dir;
+                            let is_stale = self.stale_paths.contains(file_absolute_path);
+                            let is_reviewed = self.reviewed.contains(file);
+                            let name_problems = name_audit::name_problems(file.full_name());
+                            let detected_format = self
+                                .extension_check
+                                .as_ref()
+                                .and_then(|check| check.mismatches.get(file_absolute_path).copied());
+                            let suggested_extension = self.extension_suggestions.get(file_absolute_path).copied();
+                            let data_streams: Option<String> = self
+                                .stream_check
+                                .as_ref()
+                                .and_then(|check| check.streams.get(file_absolute_path))
+                                .map(|streams| streams.iter().map(DataStream::describe).collect::<Vec<_>>().join("\n"));
+                            let orphaned_owner = file.owner.as_ref().filter(|_| file.owner_orphaned).cloned();
+                            let dup_count = if is_dir { None } else { self.is_duplicate(file) };
+                            let similar_group = self
+                                .similar_images
+                                .as_ref()
+                                .and_then(|similar| similar.groups.get(file_absolute_path).copied());
+                            let duplicate_group = if show_group_column {
+                                self.duplicate_groups().and_then(|groups| groups.get(file_absolute_path).copied())
+                            } else {
+                                None
+                            };
+                            let version = self.versions.get(file_absolute_path).copied();
+                            // Sidecar groups: companion names of primary rows, and rows that are companions
+                            let sidecar_names: Option<(usize, String)> = self.sidecars.get(file_absolute_path).map(|members| {
+                                let names: Vec<&str> = members.iter().map(|m| m.full_name()).collect();
+                                (names.len(), names.join("\n"))
+                            });
+                            let is_companion = self.sidecar_companions.contains(file_absolute_path);
+                            // Hard-linked file: its link count and the other scanned names
+                            let hard_link: Option<(u32, Vec<String>)> = file
+                                .file_id
+                                .filter(|_| show_disk_usage)
+                                .map(|id| {
+                                    let others = self.hard_links.get(&id).into_iter().flatten();
+                                    (file.link_count, others.filter(|p| p.as_str() != file_absolute_path).cloned().collect())
+                                });
+                            // Reading online-only files would download them
+                            let can_preview = !is_dir
+                                && Self::is_previewable(file_extension)
+                                && (!is_cloud || self.settings.preview_cloud_files)
+                                && !is_stale;
                             let is_editing = self.editing_index == Some(idx);
-                            let dup_count = duplicate_info[idx];
+                            let sidecars_expanded = self.expanded_sidecars.contains(file_absolute_path);
                             let is_selected = self.selected_files.contains(&idx);
 
-                            // Checkbox column for selection
+                            // Checkbox column for selection (folder rows can't be selected)
                             row.col(|ui| {
                                 let mut checked = is_selected;
-                                if ui.checkbox(&mut checked, "").changed() {
+                                if !is_dir && ui.checkbox(&mut checked, "").changed() {
                                     self.toggle_selection(idx);
                                 }
                             });
@@ -1847,11 +9363,79 @@ impl eframe::App for FileListerApp {
                             // Icon column: file type + duplicate indicator + preview on hover
                             row.col(|ui| {
                                 let icon_response = ui.horizontal(|ui| {
+                                    // Companion listed below its primary file
+                                    if is_companion {
+                                        ui.weak("↳");
+                                    }
+
                                     // File type icon
-                                    let icon_label = ui.add(
-                                        egui::Label::new(Self::get_file_type_icon(&file_extension))
-                                            .sense(egui::Sense::hover())
-                                    );
+                                    let icon = if is_dir { "📁" } else { Self::get_file_type_icon(file_extension) };
+                                    let icon_label = ui.add(egui::Label::new(icon).sense(egui::Sense::hover()));
+
+                                    // Grouped companions (RAW+JPEG, video+subtitles): expand / collapse
+                                    if let Some((count, names)) = &sidecar_names {
+                                        let arrow = if sidecars_expanded { "⏷" } else { "⏵" };
+                                        if ui
+                                            .small_button(format!("{}{}", arrow, count))
+                                            .on_hover_text(format!("Companion files:\n{}", names))
+                                            .clicked()
+                                        {
+                                            toggled_sidecars = Some(idx);
+                                        }
+                                    }
+
+                                    // File moved or deleted since the scan
+                                    if is_stale {
+                                        ui.colored_label(egui::Color32::GRAY, "⊘")
+                                            .on_hover_text("No longer exists (moved or deleted since the scan); use \"Refresh stale rows\"");
+                                    }
+
+                                    // Same file under several names (counted once on disk)
+                                    if let Some((links, others)) = &hard_link {
+                                        let listed = if others.is_empty() {
+                                            "No other name is in the scan".to_string()
+                                        } else {
+                                            format!("Also scanned as:\n{}", others.join("\n"))
+                                        };
+                                        ui.colored_label(egui::Color32::from_rgb(90, 150, 230), "🔗")
+                                            .on_hover_text(format!("Hard link: {} names for the same data\n{}", links, listed));
+                                    }
+
+                                    // Online-only cloud file indicator
+                                    if is_cloud {
+                                        ui.colored_label(egui::Color32::from_rgb(90, 150, 230), "☁")
+                                            .on_hover_text("Online-only cloud file: previews are skipped to avoid downloading it");
+                                    }
+
+                                    // Orphaned owner indicator
+                                    if let Some(sid) = &orphaned_owner {
+                                        ui.colored_label(egui::Color32::RED, "👤")
+                                            .on_hover_text(format!("Owner account no longer exists: {}", sid));
+                                    }
+
+                                    // Name that won't survive a copy to Windows, SharePoint or a ZIP
+                                    if !name_problems.is_empty() {
+                                        ui.colored_label(egui::Color32::from_rgb(220, 50, 50), "✖")
+                                            .on_hover_text(format!("Unsafe name: {}", name_problems.join(", ")));
+                                    }
+
+                                    // Content is another format than the extension says
+                                    if let Some(detected) = detected_format {
+                                        ui.colored_label(egui::Color32::from_rgb(220, 50, 50), "≠")
+                                            .on_hover_text(format!("Content is .{} (extension doesn't match)", detected));
+                                    }
+
+                                    // Alternate data streams next to the content
+                                    if let Some(streams) = &data_streams {
+                                        ui.colored_label(egui::Color32::from_rgb(255, 140, 0), "⛓")
+                                            .on_hover_text(format!("Alternate data streams:\n{}", streams));
+                                    }
+
+                                    // Reviewed in an earlier pass (and unchanged since)
+                                    if is_reviewed {
+                                        ui.colored_label(egui::Color32::from_rgb(80, 170, 90), "✔")
+                                            .on_hover_text("Reviewed");
+                                    }
 
                                     // Duplicate indicator
                                     if let Some(count) = dup_count {
@@ -1862,23 +9446,46 @@ impl eframe::App for FileListerApp {
                                         dup_label.on_hover_text(format!("Duplicate: {} files with this name", count));
                                     }
 
+                                    // Visually similar image group
+                                    if let Some((group, size)) = similar_group {
+                                        ui.colored_label(egui::Color32::from_rgb(170, 110, 220), "≈")
+                                            .on_hover_text(format!("Similar images group {}: {} images", group + 1, size));
+                                    }
+
+                                    // Version group: the suggested one to keep, or an older version / copy
+                                    if let Some(version) = version {
+                                        if version.latest {
+                                            ui.colored_label(egui::Color32::from_rgb(230, 180, 40), "★").on_hover_text(format!(
+                                                "Latest of {} versions (group {}): suggested to keep",
+                                                version.size,
+                                                version.group + 1
+                                            ));
+                                        } else {
+                                            ui.colored_label(egui::Color32::GRAY, "☆").on_hover_text(format!(
+                                                "Older version or copy (group {} of {} versions)",
+                                                version.group + 1,
+                                                version.size
+                                            ));
+                                        }
+                                    }
+
                                     icon_label
                                 }).inner;
 
                                 // Show preview on hover for previewable files (on icon)
-                                if icon_response.hovered() && Self::is_previewable(&file_extension) {
-                                    let is_video = Self::is_video_file(&file_extension);
-                                    let is_pdf = Self::is_pdf_file(&file_extension);
-                                    let is_document = Self::is_document_file(&file_extension);
-                                    let is_audio = Self::is_audio_file(&file_extension);
-                                    let is_code = Self::is_code_file(&file_extension);
+                                if icon_response.hovered() && can_preview {
+                                    let is_video = Self::is_video_file(file_extension);
+                                    let is_pdf = Self::is_pdf_file(file_extension);
+                                    let is_document = Self::is_document_file(file_extension);
+                                    let is_audio = Self::is_audio_file(file_extension);
+                                    let is_code = Self::is_code_file(file_extension);
 
                                     if is_document || is_audio || is_code {
                                         // Start audio playback immediately when hovering on audio file
                                         if is_audio {
                                             self.audio_hover_active = true;
                                             // Try to get duration from cache, otherwise play without seeking
-                                            let duration_secs = self.document_cache.get(&file_absolute_path)
+                                            let duration_secs = self.document_cache.get(file_absolute_path)
                                                 .and_then(|content| {
                                                     if let DocumentPreviewContent::Audio { duration, .. } = content {
                                                         duration.as_ref().and_then(|d| {
@@ -1903,20 +9510,20 @@ impl eframe::App for FileListerApp {
                                                     }
                                                 });
                                             // Start background audio loading (non-blocking)
-                                            self.load_audio_in_background(&file_absolute_path, duration_secs);
+                                            self.load_audio_in_background(file_absolute_path, duration_secs);
                                         }
                                         // Check if this audio file is currently playing, loading, or has error
-                                        let is_audio_playing = is_audio && self.audio_playing_path.as_ref() == Some(&file_absolute_path);
-                                        let is_audio_loading = is_audio && self.audio_loading_path.as_ref() == Some(&file_absolute_path);
-                                        let has_audio_error = is_audio && self.audio_error_path.as_ref() == Some(&file_absolute_path);
+                                        let is_audio_playing = is_audio && self.audio_playing_path.as_deref() == Some(file_absolute_path);
+                                        let is_audio_loading = is_audio && self.audio_loading_path.as_deref() == Some(file_absolute_path);
+                                        let has_audio_error = is_audio && self.audio_error_path.as_deref() == Some(file_absolute_path);
 
                                         // Document/Audio/Code preview (text/table/audio metadata)
-                                        if let Some(content) = self.document_cache.get(&file_absolute_path) {
+                                        if let Some(content) = self.document_cache.get(file_absolute_path) {
                                             icon_response.on_hover_ui_at_pointer(|ui| {
                                                 ui.set_max_width(if is_code { 600.0 } else { 500.0 });
                                                 ui.set_max_height(if is_code { 500.0 } else { 400.0 });
                                                 ui.horizontal(|ui| {
-                                                    ui.label(egui::RichText::new(&file_name).strong());
+                                                    ui.label(egui::RichText::new(file_name).strong());
                                                     let icon = if is_audio { " 🎵" } else if is_code { " 💻" } else { " 📄" };
                                                     ui.label(egui::RichText::new(icon).color(egui::Color32::GRAY));
                                                     // Show playing, loading, or error indicator for audio
@@ -2017,14 +9624,14 @@ impl eframe::App for FileListerApp {
                                             });
                                         } else {
                                             // Start loading document/audio/code in background
-                                            if self.document_loading_path.as_ref() != Some(&file_absolute_path) {
+                                            if self.document_loading_path.as_deref() != Some(file_absolute_path) {
                                                 self.load_document_preview(idx, ctx);
                                             }
                                             // Show appropriate loading text with audio status
                                             let loading_text = if is_audio {
-                                                if self.audio_playing_path.as_ref() == Some(&file_absolute_path) {
+                                                if self.audio_playing_path.as_deref() == Some(file_absolute_path) {
                                                     "🎵 ▶ Playing... (loading metadata)"
-                                                } else if self.audio_error_path.as_ref() == Some(&file_absolute_path) {
+                                                } else if self.audio_error_path.as_deref() == Some(file_absolute_path) {
                                                     "🎵 ⚠ Unsupported format"
                                                 } else {
                                                     "🎵 Loading & playing..."
@@ -2037,12 +9644,12 @@ impl eframe::App for FileListerApp {
                                             icon_response.on_hover_text(loading_text);
                                             ctx.request_repaint();
                                         }
-                                    } else if let Some(tex) = self.image_cache.get(&file_absolute_path) {
+                                    } else if let Some(tex) = self.image_cache.get(file_absolute_path) {
                                         // Show image/video/PDF from cache
                                         icon_response.on_hover_ui_at_pointer(|ui| {
-                                            ui.set_max_width(420.0);
+                                            ui.set_max_width(self.settings.preview_max_size + 20.0);
                                             ui.horizontal(|ui| {
-                                                ui.label(egui::RichText::new(&file_name).strong());
+                                                ui.label(egui::RichText::new(file_name).strong());
                                                 if is_video {
                                                     ui.label(egui::RichText::new(" 🎬").color(egui::Color32::GRAY));
                                                 } else if is_pdf {
@@ -2050,17 +9657,19 @@ impl eframe::App for FileListerApp {
                                                 }
                                             });
                                             ui.add_space(4.0);
-                                            let size = tex.size();
-                                            ui.image((tex.id(), egui::vec2(size[0] as f32, size[1] as f32)));
+                                            let display_size = Self::preview_display_size(ui.ctx(), tex.size(), self.settings.preview_max_size);
+                                            ui.image((tex.id(), display_size));
                                         });
                                     } else {
                                         // Show status for videos
                                         if is_video {
-                                            if !Self::is_ffmpeg_ready() {
+                                            if !Self::is_video_ready() {
                                                 icon_response.on_hover_text("📹 Video preview requires FFmpeg\nInstall: winget install ffmpeg");
                                             } else {
+                                                // Keeps the extraction alive (see cancel_video_preview)
+                                                self.video_hover_seen = true;
                                                 // Start loading in background if not already loading this file
-                                                if self.image_loading_path.as_ref() != Some(&file_absolute_path) {
+                                                if self.image_loading_path.as_deref() != Some(file_absolute_path) {
                                                     self.load_hover_preview(idx, ctx);
                                                 }
                                                 let elapsed = self.get_loading_elapsed_secs().unwrap_or(0);
@@ -2083,7 +9692,7 @@ impl eframe::App for FileListerApp {
                                                 }
                                             } else {
                                                 // Start loading in background if not already loading this file
-                                                if self.image_loading_path.as_ref() != Some(&file_absolute_path) {
+                                                if self.image_loading_path.as_deref() != Some(file_absolute_path) {
                                                     self.load_hover_preview(idx, ctx);
                                                 }
                                                 let elapsed = self.get_loading_elapsed_secs().unwrap_or(0);
@@ -2097,7 +9706,7 @@ impl eframe::App for FileListerApp {
                                             }
                                         } else {
                                             // Start loading in background if not already loading this file
-                                            if self.image_loading_path.as_ref() != Some(&file_absolute_path) {
+                                            if self.image_loading_path.as_deref() != Some(file_absolute_path) {
                                                 self.load_hover_preview(idx, ctx);
                                             }
                                         }
@@ -2133,28 +9742,33 @@ impl eframe::App for FileListerApp {
                                         self.cancel_rename();
                                     }
                                 } else {
-                                    // Normal label with double-click to rename
+                                    // Normal label with double-click to rename (struck through if stale)
+                                    let name_text = if is_stale {
+                                        egui::RichText::new(file_name).strikethrough().weak()
+                                    } else {
+                                        egui::RichText::new(file_name)
+                                    };
                                     let label = ui.add(
-                                        egui::Label::new(&file_name).sense(egui::Sense::click())
+                                        egui::Label::new(name_text).sense(egui::Sense::click())
                                     );
                                     if label.double_clicked() {
                                         self.start_rename(idx);
                                     }
 
                                     // Show preview on hover for previewable files
-                                    if label.hovered() && Self::is_previewable(&file_extension) {
-                                        let is_video = Self::is_video_file(&file_extension);
-                                        let is_pdf = Self::is_pdf_file(&file_extension);
-                                        let is_document = Self::is_document_file(&file_extension);
-                                        let is_audio = Self::is_audio_file(&file_extension);
-                                        let is_code = Self::is_code_file(&file_extension);
+                                    if label.hovered() && can_preview {
+                                        let is_video = Self::is_video_file(file_extension);
+                                        let is_pdf = Self::is_pdf_file(file_extension);
+                                        let is_document = Self::is_document_file(file_extension);
+                                        let is_audio = Self::is_audio_file(file_extension);
+                                        let is_code = Self::is_code_file(file_extension);
 
                                         if is_document || is_audio || is_code {
                                             // Start audio playback immediately when hovering on audio file (name column)
                                             if is_audio {
                                                 self.audio_hover_active = true;
                                                 // Try to get duration from cache, otherwise play without seeking
-                                                let duration_secs = self.document_cache.get(&file_absolute_path)
+                                                let duration_secs = self.document_cache.get(file_absolute_path)
                                                     .and_then(|content| {
                                                         if let DocumentPreviewContent::Audio { duration, .. } = content {
                                                             duration.as_ref().and_then(|d| {
@@ -2179,20 +9793,20 @@ impl eframe::App for FileListerApp {
                                                         }
                                                     });
                                                 // Start background audio loading (non-blocking)
-                                                self.load_audio_in_background(&file_absolute_path, duration_secs);
+                                                self.load_audio_in_background(file_absolute_path, duration_secs);
                                             }
                                             // Check if this audio file is currently playing, loading, or has error
-                                            let is_audio_playing = is_audio && self.audio_playing_path.as_ref() == Some(&file_absolute_path);
-                                            let is_audio_loading = is_audio && self.audio_loading_path.as_ref() == Some(&file_absolute_path);
-                                            let has_audio_error = is_audio && self.audio_error_path.as_ref() == Some(&file_absolute_path);
+                                            let is_audio_playing = is_audio && self.audio_playing_path.as_deref() == Some(file_absolute_path);
+                                            let is_audio_loading = is_audio && self.audio_loading_path.as_deref() == Some(file_absolute_path);
+                                            let has_audio_error = is_audio && self.audio_error_path.as_deref() == Some(file_absolute_path);
 
                                             // Document/Audio/Code preview (text/table/audio metadata)
-                                            if let Some(content) = self.document_cache.get(&file_absolute_path) {
+                                            if let Some(content) = self.document_cache.get(file_absolute_path) {
                                                 label.clone().on_hover_ui_at_pointer(|ui| {
                                                     ui.set_max_width(if is_code { 600.0 } else { 500.0 });
                                                     ui.set_max_height(if is_code { 500.0 } else { 400.0 });
                                                     ui.horizontal(|ui| {
-                                                        ui.label(egui::RichText::new(&file_name).strong());
+                                                        ui.label(egui::RichText::new(file_name).strong());
                                                         let icon = if is_audio { " 🎵" } else if is_code { " 💻" } else { " 📄" };
                                                         ui.label(egui::RichText::new(icon).color(egui::Color32::GRAY));
                                                         // Show playing, loading, or error indicator for audio
@@ -2293,14 +9907,14 @@ impl eframe::App for FileListerApp {
                                                 });
                                             } else {
                                                 // Start loading document/audio/code in background
-                                                if self.document_loading_path.as_ref() != Some(&file_absolute_path) {
+                                                if self.document_loading_path.as_deref() != Some(file_absolute_path) {
                                                     self.load_document_preview(idx, ctx);
                                                 }
                                                 // Show appropriate loading text with audio status
                                                 let loading_text = if is_audio {
-                                                    if self.audio_playing_path.as_ref() == Some(&file_absolute_path) {
+                                                    if self.audio_playing_path.as_deref() == Some(file_absolute_path) {
                                                         "🎵 ▶ Playing... (loading metadata)"
-                                                    } else if self.audio_error_path.as_ref() == Some(&file_absolute_path) {
+                                                    } else if self.audio_error_path.as_deref() == Some(file_absolute_path) {
                                                         "🎵 ⚠ Unsupported format"
                                                     } else {
                                                         "🎵 Loading & playing..."
@@ -2313,12 +9927,12 @@ impl eframe::App for FileListerApp {
                                                 label.clone().on_hover_text(loading_text);
                                                 ctx.request_repaint();
                                             }
-                                        } else if let Some(tex) = self.image_cache.get(&file_absolute_path) {
+                                        } else if let Some(tex) = self.image_cache.get(file_absolute_path) {
                                             // Show image/video/PDF from cache
                                             label.clone().on_hover_ui_at_pointer(|ui| {
-                                                ui.set_max_width(420.0);
+                                                ui.set_max_width(self.settings.preview_max_size + 20.0);
                                                 ui.horizontal(|ui| {
-                                                    ui.label(egui::RichText::new(&file_name).strong());
+                                                    ui.label(egui::RichText::new(file_name).strong());
                                                     if is_video {
                                                         ui.label(egui::RichText::new(" 🎬").color(egui::Color32::GRAY));
                                                     } else if is_pdf {
@@ -2326,17 +9940,19 @@ impl eframe::App for FileListerApp {
                                                     }
                                                 });
                                                 ui.add_space(4.0);
-                                                let size = tex.size();
-                                                ui.image((tex.id(), egui::vec2(size[0] as f32, size[1] as f32)));
+                                                let display_size = Self::preview_display_size(ui.ctx(), tex.size(), self.settings.preview_max_size);
+                                                ui.image((tex.id(), display_size));
                                             });
                                         } else {
                                             // Show status for videos
                                             if is_video {
-                                                if !Self::is_ffmpeg_ready() {
+                                                if !Self::is_video_ready() {
                                                     label.clone().on_hover_text("📹 Video preview requires FFmpeg\nInstall: winget install ffmpeg");
                                                 } else {
+                                                    // Keeps the extraction alive (see cancel_video_preview)
+                                                    self.video_hover_seen = true;
                                                     // Start loading in background if not already loading this file
-                                                    if self.image_loading_path.as_ref() != Some(&file_absolute_path) {
+                                                    if self.image_loading_path.as_deref() != Some(file_absolute_path) {
                                                         self.load_hover_preview(idx, ctx);
                                                     }
                                                     let elapsed = self.get_loading_elapsed_secs().unwrap_or(0);
@@ -2359,7 +9975,7 @@ impl eframe::App for FileListerApp {
                                                     }
                                                 } else {
                                                     // Start loading in background if not already loading this file
-                                                    if self.image_loading_path.as_ref() != Some(&file_absolute_path) {
+                                                    if self.image_loading_path.as_deref() != Some(file_absolute_path) {
                                                         self.load_hover_preview(idx, ctx);
                                                     }
                                                     let elapsed = self.get_loading_elapsed_secs().unwrap_or(0);
@@ -2373,150 +9989,150 @@ impl eframe::App for FileListerApp {
                                                 }
                                             } else {
                                                 // Start loading in background if not already loading this file
-                                                if self.image_loading_path.as_ref() != Some(&file_absolute_path) {
+                                                if self.image_loading_path.as_deref() != Some(file_absolute_path) {
                                                     self.load_hover_preview(idx, ctx);
                                                 }
                                             }
                                         }
                                     }
 
-                                    label.context_menu(|ui| {
-                                        if ui.button("📂 Open file location").clicked() {
-                                            Self::open_in_explorer(&file_path);
-                                            ui.close();
-                                        }
-                                        if ui.button("✏️ Rename").clicked() {
-                                            self.start_rename(idx);
-                                            ui.close();
-                                        }
-                                        if ui.button("📁 Move to folder...").clicked() {
-                                            self.move_file(&file_path);
-                                            ui.close();
-                                        }
-                                        ui.separator();
-                                        if ui.button("🗑️ Delete").clicked() {
-                                            self.delete_file(&file_path);
-                                            ui.close();
-                                        }
-                                    });
+                                    // Name label consumes clicks, so it needs its own copy of the row menu
+                                    label.context_menu(|ui| self.row_context_menu(ui, idx));
                                 }
                             });
 
                             row.col(|ui| {
-                                let label = ui.label(&file_extension);
-                                label.context_menu(|ui| {
-                                    if ui.button("📂 Open file location").clicked() {
-                                        Self::open_in_explorer(&file_path);
-                                        ui.close();
-                                    }
-                                    if ui.button("✏️ Rename").clicked() {
-                                        self.start_rename(idx);
-                                        ui.close();
-                                    }
-                                    if ui.button("📁 Move to folder...").clicked() {
-                                        self.move_file(&file_path);
-                                        ui.close();
-                                    }
-                                    ui.separator();
-                                    if ui.button("🗑️ Delete").clicked() {
-                                        self.delete_file(&file_path);
-                                        ui.close();
-                                    }
-                                });
+                                if is_stale {
+                                    ui.disable();
+                                }
+                                if let Some(suggested) = suggested_extension.filter(|_| file_extension.is_empty()) {
+                                    ui.weak(format!("({}?)", suggested))
+                                        .on_hover_text(format!("No extension; the content looks like .{}", suggested));
+                                } else {
+                                    ui.label(file_extension);
+                                }
                             });
                             row.col(|ui| {
-                                let label = ui.label(format_size(file_size));
-                                label.context_menu(|ui| {
-                                    if ui.button("📂 Open file location").clicked() {
-                                        Self::open_in_explorer(&file_path);
-                                        ui.close();
-                                    }
-                                    if ui.button("✏️ Rename").clicked() {
-                                        self.start_rename(idx);
-                                        ui.close();
-                                    }
-                                    if ui.button("📁 Move to folder...").clicked() {
-                                        self.move_file(&file_path);
-                                        ui.close();
+                                if is_stale {
+                                    ui.disable();
+                                }
+                                ui.label(&text.size).on_hover_text(&text.size_bytes);
+                            });
+                            if show_disk_usage {
+                                row.col(|ui| {
+                                    if is_stale {
+                                        ui.disable();
                                     }
-                                    ui.separator();
-                                    if ui.button("🗑️ Delete").clicked() {
-                                        self.delete_file(&file_path);
-                                        ui.close();
+                                    ui.label(&text.on_disk);
+                                });
+                            }
+                            if show_folders {
+                                row.col(|ui| {
+                                    ui.label(&text.items);
+                                    if let Some(mix) = is_dir.then(|| self.folder_mixes.get(file_absolute_path)).flatten() {
+                                        Self::content_mix_bar(ui, mix);
                                     }
                                 });
-                            });
+                            }
                             row.col(|ui| {
-                                let label = ui.label(format_date(file_modified));
-                                label.context_menu(|ui| {
-                                    if ui.button("📂 Open file location").clicked() {
-                                        Self::open_in_explorer(&file_path);
-                                        ui.close();
-                                    }
-                                    if ui.button("✏️ Rename").clicked() {
-                                        self.start_rename(idx);
-                                        ui.close();
-                                    }
-                                    if ui.button("📁 Move to folder...").clicked() {
-                                        self.move_file(&file_path);
-                                        ui.close();
+                                if is_stale {
+                                    ui.disable();
+                                }
+                                ui.label(&text.modified);
+                            });
+                            if show_last_accessed {
+                                row.col(|ui| {
+                                    if is_stale {
+                                        ui.disable();
                                     }
-                                    ui.separator();
-                                    if ui.button("🗑️ Delete").clicked() {
-                                        self.delete_file(&file_path);
-                                        ui.close();
+                                    ui.label(&text.accessed);
+                                });
+                            }
+                            if show_age_heatmap {
+                                row.col(|ui| {
+                                    let (rect, response) = ui.allocate_exact_size(
+                                        egui::vec2(ui.available_width(), 12.0),
+                                        egui::Sense::hover(),
+                                    );
+                                    ui.painter().rect_filled(rect, 3.0, Self::age_heat_color(file_modified, now));
+                                    if file_modified != 0 {
+                                        response.on_hover_text(Self::format_age(file_modified));
                                     }
                                 });
-                            });
+                            }
                             row.col(|ui| {
-                                let label = ui.label(&file_relative_path);
-                                label.context_menu(|ui| {
-                                    if ui.button("📂 Open file location").clicked() {
-                                        Self::open_in_explorer(&file_path);
-                                        ui.close();
-                                    }
-                                    if ui.button("✏️ Rename").clicked() {
-                                        self.start_rename(idx);
-                                        ui.close();
-                                    }
-                                    if ui.button("📁 Move to folder...").clicked() {
-                                        self.move_file(&file_path);
-                                        ui.close();
+                                if is_stale {
+                                    ui.disable();
+                                }
+                                ui.label(&*text.relative_path);
+                            });
+                            if show_lengths {
+                                for (length, limit) in [(text.name_length, name_limit), (text.path_length, path_limit)] {
+                                    row.col(|ui| {
+                                        if length > limit {
+                                            ui.colored_label(egui::Color32::from_rgb(220, 50, 50), length.to_string())
+                                                .on_hover_text(format!("{} over the limit of {}", length - limit, limit));
+                                        } else {
+                                            ui.label(length.to_string());
+                                        }
+                                    });
+                                }
+                            }
+                            if show_hashes {
+                                row.col(|ui| {
+                                    if !text.hash.is_empty() {
+                                        ui.monospace(&text.hash).on_hover_text(&text.hash);
                                     }
-                                    ui.separator();
-                                    if ui.button("🗑️ Delete").clicked() {
-                                        self.delete_file(&file_path);
-                                        ui.close();
+                                });
+                            }
+                            if show_text_counts {
+                                for count in [&text.lines, &text.words] {
+                                    row.col(|ui| {
+                                        ui.label(count);
+                                    });
+                                }
+                            }
+                            if show_group_column {
+                                row.col(|ui| {
+                                    if let Some((group, size)) = duplicate_group {
+                                        ui.label((group + 1).to_string()).on_hover_text(format!("{} files", size));
                                     }
                                 });
-                            });
+                            }
                             row.col(|ui| {
-                                let label = ui.label(&file_absolute_path);
-                                label.context_menu(|ui| {
-                                    if ui.button("📂 Open file location").clicked() {
-                                        Self::open_in_explorer(&file_path);
-                                        ui.close();
+                                if is_stale {
+                                    ui.disable();
+                                }
+                                ui.label(file_absolute_path);
+                            });
+                            if show_row_actions {
+                                row.col(|ui| {
+                                    let mut action: Option<RowAction> = None;
+                                    if ui.small_button("📄").on_hover_text("Open").clicked() {
+                                        action = Some(RowAction::Open);
                                     }
-                                    if ui.button("✏️ Rename").clicked() {
-                                        self.start_rename(idx);
-                                        ui.close();
+                                    if ui.small_button("📂").on_hover_text("Open file location").clicked() {
+                                        action = Some(RowAction::OpenLocation);
                                     }
-                                    if ui.button("📁 Move to folder...").clicked() {
-                                        self.move_file(&file_path);
-                                        ui.close();
+                                    if !self.is_read_only() && ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                                        action = Some(RowAction::Delete);
                                     }
-                                    ui.separator();
-                                    if ui.button("🗑️ Delete").clicked() {
-                                        self.delete_file(&file_path);
-                                        ui.close();
+                                    if let Some(action) = action {
+                                        self.execute_row_action(ui.ctx(), action, idx);
                                     }
                                 });
-                            });
+                            }
 
-                            // Set hover highlighting after all columns are rendered
-                            row.set_hovered(row.response().hovered());
+                            // Set hover highlighting and the row context menu after all columns are rendered
+                            let row_response = row.response();
+                            row.set_hovered(row_response.hovered());
+                            row_response.context_menu(|ui| self.row_context_menu(ui, idx));
                         });
                     });
+                self.table_scroll = scroll.state.offset.y;
+                if let Some(idx) = toggled_sidecars {
+                    self.toggle_sidecars(idx);
+                }
             } else {
                 ui.centered_and_justified(|ui| {
                     ui.label("Select a folder to view files");
@@ -2658,9 +10274,74 @@ impl eframe::App for FileListerApp {
                 });
         }
 
+        // Properties window (row action)
+        self.show_properties_window(ctx);
+
+        // Settings window
+        self.show_settings_window(ctx);
+
+        // Move / Copy destination window
+        self.show_transfer_window(ctx);
+
+        // Root comparison results
+        self.show_comparison_window(ctx);
+
+        // Extension normalization preview
+        self.show_bulk_rename_window(ctx);
+
+        // Retry as administrator after access denied
+        self.show_elevation_window(ctx);
+
+        // Search across saved snapshots
+        self.show_snapshot_window(ctx);
+
+        // Modification activity by weekday and hour
+        self.show_statistics_window(ctx);
+        self.show_permissions_window(ctx);
+        self.show_manifest_check_window(ctx);
+
+        // Confirm opening many folders
+        self.show_open_locations_window(ctx);
+
+        // Row actions by name for the selected file
+        self.show_command_palette(ctx);
+
+        // Split a file into parts or join them
+        self.show_split_window(ctx);
+
+        // Retention rules: dry run, run, undo
+        self.show_retention_window(ctx);
+
+        // Duplicate groups side by side with keep / delete selection
+        self.show_duplicate_manager_window(ctx);
+
+        // Export progress with Cancel
+        self.show_export_window(ctx);
+        self.show_cloud_export_window(ctx);
+        self.show_report_mail_window(ctx);
+
+        // Shared workstation: pick the user profile first
+        self.show_user_profile_chooser(ctx);
+
+        // Crash recovery: offer the previous session, then keep autosaving
+        self.show_session_restore_window(ctx);
+        self.autosave_session(ctx);
+
         // Stop audio playback if not hovering over any audio file this frame
         if !self.audio_hover_active && self.audio_playing_path.is_some() {
             self.stop_audio_preview();
         }
+
+        // Cancel video thumbnail extraction if the pointer left the video rows
+        if !self.video_hover_seen && (self.video_hover_intent.is_some() || self.image_loading_path.is_some()) {
+            self.cancel_video_preview();
+        }
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Persist window placement and settings
+        let _ = self.settings.save();
+        // Clean exit: nothing to recover next time
+        Session::clear();
     }
 }
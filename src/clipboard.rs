@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+/// Read file/folder paths from the clipboard.
+/// Accepts files copied in a file manager, or text with one path per line
+/// (quoted "Copy as path" output and file:// URIs included).
+pub fn read_clipboard_paths() -> Result<Vec<PathBuf>, String> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| format!("Clipboard unavailable: {}", e))?;
+
+    // Files copied in Explorer/Finder/file managers
+    if let Ok(files) = clipboard.get().file_list() {
+        if !files.is_empty() {
+            return Ok(files);
+        }
+    }
+
+    let text = clipboard
+        .get_text()
+        .map_err(|_| "Clipboard does not contain text or files".to_string())?;
+    Ok(parse_paths(&text))
+}
+
+/// Parse one path per line, dropping blank lines and duplicates
+pub fn parse_paths(text: &str) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for line in text.lines() {
+        let line = line.trim().trim_matches(|c| c == '"' || c == '\'');
+        if line.is_empty() {
+            continue;
+        }
+        let path = match line.strip_prefix("file://") {
+            Some(uri) => PathBuf::from(percent_decode(uri)),
+            None => PathBuf::from(line),
+        };
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+/// Decode %XX escapes in a file:// URI path
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
@@ -0,0 +1,503 @@
+use crate::csv_export::{ExportOptions, CANCELLED};
+use crate::file_scanner::{format_date, Calendar, FileInfo};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Rows sent per API request (keeps each request well below the Sheets and Graph size limits)
+const CHUNK_ROWS: usize = 1000;
+
+/// Grant type of the OAuth device authorization flow (RFC 8628)
+const DEVICE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Cloud spreadsheet service the listing is pushed to
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloudTarget {
+    #[default]
+    GoogleSheets,
+    /// Excel workbook in OneDrive or SharePoint (Microsoft Graph)
+    Microsoft365,
+}
+
+impl CloudTarget {
+    pub const ALL: [CloudTarget; 2] = [CloudTarget::GoogleSheets, CloudTarget::Microsoft365];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CloudTarget::GoogleSheets => "Google Sheets",
+            CloudTarget::Microsoft365 => "Excel (OneDrive / SharePoint)",
+        }
+    }
+}
+
+/// Where cloud exports go and which OAuth clients sign in ("cloud_export" in settings.json).
+/// Access tokens are only kept in memory; every app start signs in again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CloudExportSettings {
+    pub target: CloudTarget,
+    /// OAuth client of type "TVs and Limited Input devices" in a Google Cloud project
+    pub google_client_id: String,
+    pub google_client_secret: String,
+    /// Spreadsheet to overwrite (created by an earlier export); empty: create a new one
+    pub google_spreadsheet_id: String,
+    /// Entra ID app registration with public client flows allowed
+    pub microsoft_client_id: String,
+    /// Tenant ID, or "organizations" for any work account
+    pub microsoft_tenant: String,
+    /// Graph drive of the workbook: "me/drive" (OneDrive) or "sites/{site-id}/drive" (SharePoint)
+    pub microsoft_drive: String,
+    /// Path of an existing .xlsx workbook in the drive, e.g. "Inventory/files.xlsx"
+    pub microsoft_workbook: String,
+    /// Sheet (tab) the listing replaces; created if missing
+    pub sheet_name: String,
+}
+
+impl Default for CloudExportSettings {
+    fn default() -> Self {
+        Self {
+            target: CloudTarget::GoogleSheets,
+            google_client_id: String::new(),
+            google_client_secret: String::new(),
+            google_spreadsheet_id: String::new(),
+            microsoft_client_id: String::new(),
+            microsoft_tenant: "organizations".to_string(),
+            microsoft_drive: "me/drive".to_string(),
+            microsoft_workbook: String::new(),
+            sheet_name: "Files".to_string(),
+        }
+    }
+}
+
+impl CloudExportSettings {
+    /// Missing setting that prevents an export to the selected target
+    pub fn missing(&self) -> Option<&'static str> {
+        match self.target {
+            CloudTarget::GoogleSheets if self.google_client_id.trim().is_empty() => Some("Google client ID"),
+            CloudTarget::GoogleSheets if self.google_client_secret.trim().is_empty() => Some("Google client secret"),
+            CloudTarget::Microsoft365 if self.microsoft_client_id.trim().is_empty() => Some("Microsoft client ID"),
+            CloudTarget::Microsoft365 if self.microsoft_workbook.trim().is_empty() => Some("workbook path"),
+            _ if self.sheet_name.trim().is_empty() => Some("sheet name"),
+            _ => None,
+        }
+    }
+}
+
+/// Result of a finished cloud export
+pub struct CloudOutcome {
+    /// Link to the spreadsheet or workbook
+    pub url: String,
+    pub rows: usize,
+    /// Google spreadsheet created by this export (remembered for the next one)
+    pub created_spreadsheet: Option<String>,
+}
+
+enum CloudMessage {
+    /// The user has to enter `code` at `url` to let the export in
+    SignIn { url: String, code: String },
+    Progress(usize),
+    Finished(Result<CloudOutcome, String>),
+}
+
+/// Access tokens of this run, per target
+static TOKENS: Mutex<Vec<(CloudTarget, String, Instant)>> = Mutex::new(Vec::new());
+
+/// Handle to a cloud export running on a background thread
+pub struct CloudExportJob {
+    receiver: Receiver<CloudMessage>,
+    cancelled: Arc<AtomicBool>,
+    pub target: CloudTarget,
+    /// Verification page and code while waiting for the user to sign in
+    pub sign_in: Option<(String, String)>,
+    pub rows_done: usize,
+    pub rows_total: usize,
+}
+
+impl CloudExportJob {
+    /// Sign in if needed (OAuth device flow), then replace the configured sheet with `files`
+    pub fn spawn(settings: CloudExportSettings, files: Vec<FileInfo>, options: ExportOptions) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let worker_cancelled = Arc::clone(&cancelled);
+        let target = settings.target;
+        let rows_total = files.len();
+
+        thread::spawn(move || {
+            let mut rows = vec![options.columns.iter().map(|c| c.header_in(options.calendar)).collect::<Vec<_>>()];
+            rows.extend(files.iter().map(|file| {
+                options.columns.iter().map(|c| c.value(file, options.calendar)).collect()
+            }));
+            let export = Export {
+                agent: ureq::AgentBuilder::new().timeout(Duration::from_secs(60)).build(),
+                settings,
+                tx: tx.clone(),
+                cancelled: worker_cancelled,
+            };
+            let result = match export.settings.target {
+                CloudTarget::GoogleSheets => export.to_google(&rows),
+                CloudTarget::Microsoft365 => export.to_microsoft(&rows),
+            };
+            let _ = tx.send(CloudMessage::Finished(result));
+        });
+
+        Self {
+            receiver: rx,
+            cancelled,
+            target,
+            sign_in: None,
+            rows_done: 0,
+            rows_total,
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.rows_total == 0 {
+            0.0
+        } else {
+            self.rows_done as f32 / self.rows_total as f32
+        }
+    }
+
+    /// Apply pending messages; returns the result once the export has finished
+    pub fn poll(&mut self) -> Option<Result<CloudOutcome, String>> {
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                CloudMessage::SignIn { url, code } => self.sign_in = Some((url, code)),
+                CloudMessage::Progress(rows) => {
+                    self.sign_in = None;
+                    self.rows_done = rows;
+                }
+                CloudMessage::Finished(result) => return Some(result),
+            }
+        }
+        None
+    }
+}
+
+/// OAuth endpoints and client of a provider
+struct OAuthClient<'a> {
+    device_url: String,
+    token_url: String,
+    client_id: &'a str,
+    /// Google requires the secret of the (non-confidential) device client; Microsoft has none
+    client_secret: Option<&'a str>,
+    scope: &'static str,
+}
+
+/// State of the export thread
+struct Export {
+    agent: ureq::Agent,
+    settings: CloudExportSettings,
+    tx: Sender<CloudMessage>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Export {
+    fn check_cancelled(&self) -> Result<(), String> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            Err(CANCELLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Token of this run for the target, or a new one from the device flow
+    fn access_token(&self, client: &OAuthClient) -> Result<String, String> {
+        let target = self.settings.target;
+        if let Ok(tokens) = TOKENS.lock() {
+            let valid = tokens.iter().find(|(t, _, expires)| *t == target && *expires > Instant::now());
+            if let Some((_, token, _)) = valid {
+                return Ok(token.clone());
+            }
+        }
+
+        let device = reply(self.agent.post(&client.device_url).send_form(&[
+            ("client_id", client.client_id.trim()),
+            ("scope", client.scope),
+        ]))?;
+        let field = |name: &str| device[name].as_str().map(str::to_string);
+        let (Some(device_code), Some(code)) = (field("device_code"), field("user_code")) else {
+            return Err(error_message(&device));
+        };
+        // Google names it verification_url, RFC 8628 verification_uri
+        let url = field("verification_uri").or_else(|| field("verification_url")).unwrap_or_default();
+        let expires = Instant::now() + Duration::from_secs(device["expires_in"].as_u64().unwrap_or(900));
+        let mut interval = Duration::from_secs(device["interval"].as_u64().unwrap_or(5));
+        let _ = self.tx.send(CloudMessage::SignIn { url, code });
+
+        loop {
+            let wake = Instant::now() + interval;
+            while Instant::now() < wake {
+                self.check_cancelled()?;
+                thread::sleep(Duration::from_millis(200));
+            }
+            if Instant::now() > expires {
+                return Err("The sign-in code expired; start the export again".to_string());
+            }
+
+            let mut form = vec![
+                ("client_id", client.client_id.trim()),
+                ("device_code", device_code.as_str()),
+                ("grant_type", DEVICE_GRANT),
+            ];
+            if let Some(secret) = client.client_secret {
+                form.push(("client_secret", secret.trim()));
+            }
+            let token = reply(self.agent.post(&client.token_url).send_form(&form))?;
+            if let Some(access) = token["access_token"].as_str() {
+                let lifetime = token["expires_in"].as_u64().unwrap_or(3600).saturating_sub(60);
+                if let Ok(mut tokens) = TOKENS.lock() {
+                    tokens.retain(|(t, _, _)| *t != target);
+                    tokens.push((target, access.to_string(), Instant::now() + Duration::from_secs(lifetime)));
+                }
+                return Ok(access.to_string());
+            }
+            match token["error"].as_str() {
+                Some("authorization_pending") => {}
+                Some("slow_down") => interval += Duration::from_secs(5),
+                Some("access_denied" | "authorization_declined") => return Err("Sign-in was declined".to_string()),
+                Some("expired_token") => return Err("The sign-in code expired; start the export again".to_string()),
+                _ => return Err(error_message(&token)),
+            }
+        }
+    }
+
+    fn progress(&self, rows: usize) {
+        let _ = self.tx.send(CloudMessage::Progress(rows));
+    }
+
+    /// Replace the sheet of the configured spreadsheet (or a new one) with the rows
+    fn to_google(&self, rows: &[Vec<String>]) -> Result<CloudOutcome, String> {
+        let token = self.access_token(&OAuthClient {
+            device_url: "https://oauth2.googleapis.com/device/code".to_string(),
+            token_url: "https://oauth2.googleapis.com/token".to_string(),
+            client_id: &self.settings.google_client_id,
+            client_secret: Some(&self.settings.google_client_secret),
+            // Only files this app created or was given; the device flow allows no broader Sheets scope
+            scope: "https://www.googleapis.com/auth/drive.file",
+        })?;
+        self.progress(0);
+        let auth = format!("Bearer {}", token);
+        let sheet = self.settings.sheet_name.trim().to_string();
+        let base = "https://sheets.googleapis.com/v4/spreadsheets";
+
+        let spreadsheet_id = self.settings.google_spreadsheet_id.trim().to_string();
+        let (spreadsheet_id, url, created) = if spreadsheet_id.is_empty() {
+            let now = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0);
+            let body = json!({
+                "properties": { "title": format!("File list {}", format_date(now, Calendar::Gregorian)) },
+                "sheets": [{ "properties": { "title": sheet } }],
+            });
+            let created = self.agent.post(base).set("Authorization", &auth).send_json(&body)?;
+            let id = created["spreadsheetId"].as_str().unwrap_or_default().to_string();
+            let url = created["spreadsheetUrl"].as_str().unwrap_or_default().to_string();
+            (id.clone(), url, Some(id))
+        } else {
+            let existing = api(self
+                .agent
+                .get(&format!("{}/{}", base, encode(&spreadsheet_id)))
+                .query("fields", "spreadsheetUrl,sheets.properties.title")
+                .set("Authorization", &auth)
+                .call())?;
+            let has_sheet = existing["sheets"]
+                .as_array()
+                .is_some_and(|sheets| sheets.iter().any(|s| s["properties"]["title"].as_str() == Some(&sheet)));
+            if !has_sheet {
+                let body = json!({ "requests": [{ "addSheet": { "properties": { "title": sheet } } }] });
+                self.agent
+                    .post(&format!("{}/{}:batchUpdate", base, encode(&spreadsheet_id)))
+                    .set("Authorization", &auth)
+                    .send_json(&body)?;
+            }
+            let url = existing["spreadsheetUrl"].as_str().unwrap_or_default().to_string();
+            (spreadsheet_id, url, None)
+        };
+
+        let range = encode(&format!("'{}'", sheet.replace('\'', "''")));
+        self.agent
+            .post(&format!("{}/{}/values/{}:clear", base, encode(&spreadsheet_id), range))
+            .set("Authorization", &auth)
+            .send_json(&json!({}))?;
+        // Appending after the cleared rows grows the sheet as needed (an update would stop at the grid size)
+        for (index, chunk) in rows.chunks(CHUNK_ROWS).enumerate() {
+            self.check_cancelled()?;
+            self.agent
+                .post(&format!("{}/{}/values/{}:append", base, encode(&spreadsheet_id), range))
+                .query("valueInputOption", "RAW")
+                .query("insertDataOption", "OVERWRITE")
+                .set("Authorization", &auth)
+                .send_json(&json!({ "values": chunk }))?;
+            self.progress((index * CHUNK_ROWS + chunk.len()).saturating_sub(1));
+        }
+
+        Ok(CloudOutcome {
+            url,
+            rows: rows.len() - 1,
+            created_spreadsheet: created,
+        })
+    }
+
+    /// Replace the sheet of an existing workbook in OneDrive or SharePoint with the rows
+    fn to_microsoft(&self, rows: &[Vec<String>]) -> Result<CloudOutcome, String> {
+        let tenant = self.settings.microsoft_tenant.trim();
+        let tenant = if tenant.is_empty() { "organizations" } else { tenant };
+        let token = self.access_token(&OAuthClient {
+            device_url: format!("https://login.microsoftonline.com/{}/oauth2/v2.0/devicecode", tenant),
+            token_url: format!("https://login.microsoftonline.com/{}/oauth2/v2.0/token", tenant),
+            client_id: &self.settings.microsoft_client_id,
+            client_secret: None,
+            scope: "Files.ReadWrite.All",
+        })?;
+        self.progress(0);
+        let auth = format!("Bearer {}", token);
+        let drive = self.settings.microsoft_drive.trim().trim_matches('/');
+        let drive = if drive.is_empty() { "me/drive" } else { drive };
+        let path = self.settings.microsoft_workbook.trim().trim_matches('/').replace('\\', "/");
+        let item = format!(
+            "https://graph.microsoft.com/v1.0/{}/root:/{}:",
+            drive,
+            path.split('/').map(encode).collect::<Vec<_>>().join("/")
+        );
+
+        let workbook = match reply_status(self.agent.get(&item).set("Authorization", &auth).call())? {
+            (404, _) => return Err(format!("Workbook {} not found: create it in OneDrive or SharePoint first", path)),
+            (status, value) if status >= 400 => return Err(error_message(&value)),
+            (_, value) => value,
+        };
+        let url = workbook["webUrl"].as_str().unwrap_or_default().to_string();
+
+        let sheet = self.settings.sheet_name.trim().to_string();
+        let worksheet = format!("{}/workbook/worksheets/{}", item, encode(&sheet));
+        match reply_status(self.agent.get(&worksheet).set("Authorization", &auth).call())? {
+            (404, _) => {
+                self.agent
+                    .post(&format!("{}/workbook/worksheets/add", item))
+                    .set("Authorization", &auth)
+                    .send_json(&json!({ "name": sheet }))?;
+            }
+            (status, value) if status >= 400 => return Err(error_message(&value)),
+            _ => {}
+        }
+
+        // Clear what an earlier export left (it may have had more rows or columns)
+        let used = api(self
+            .agent
+            .get(&format!("{}/usedRange(valuesOnly=true)", worksheet))
+            .query("$select", "address")
+            .set("Authorization", &auth)
+            .call())?;
+        if let Some(address) = used["address"].as_str().and_then(|a| a.rsplit('!').next()) {
+            self.agent
+                .post(&format!("{}/range(address='{}')/clear", worksheet, address))
+                .set("Authorization", &auth)
+                .send_json(&json!({ "applyTo": "Contents" }))?;
+        }
+
+        let last_column = column_letters(rows.first().map_or(1, |header| header.len()));
+        for (index, chunk) in rows.chunks(CHUNK_ROWS).enumerate() {
+            self.check_cancelled()?;
+            let first_row = index * CHUNK_ROWS + 1;
+            let address = format!("A{}:{}{}", first_row, last_column, first_row + chunk.len() - 1);
+            self.agent
+                .request("PATCH", &format!("{}/range(address='{}')", worksheet, address))
+                .set("Authorization", &auth)
+                .send_json(&json!({ "values": chunk }))?;
+            self.progress((index * CHUNK_ROWS + chunk.len()).saturating_sub(1));
+        }
+
+        Ok(CloudOutcome {
+            url,
+            rows: rows.len() - 1,
+            created_spreadsheet: None,
+        })
+    }
+}
+
+/// API calls with a JSON body (without ureq's "json" feature)
+trait SendJson {
+    fn send_json(self, body: &Value) -> Result<Value, String>;
+}
+
+impl SendJson for ureq::Request {
+    fn send_json(self, body: &Value) -> Result<Value, String> {
+        api(self.set("Content-Type", "application/json").send_string(&body.to_string()))
+    }
+}
+
+/// Status and JSON body of a reply; error statuses are replies too (OAuth and the APIs explain them in the body)
+fn reply_status(result: Result<ureq::Response, ureq::Error>) -> Result<(u16, Value), String> {
+    let response = match result {
+        Ok(response) => response,
+        Err(ureq::Error::Status(_, response)) => response,
+        Err(e) => return Err(format!("Network error: {}", e)),
+    };
+    let status = response.status();
+    let text = response.into_string().map_err(|e| e.to_string())?;
+    let value = if text.trim().is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_str(&text).unwrap_or(Value::String(text))
+    };
+    Ok((status, value))
+}
+
+/// JSON body of a reply, whatever its status
+fn reply(result: Result<ureq::Response, ureq::Error>) -> Result<Value, String> {
+    reply_status(result).map(|(_, value)| value)
+}
+
+/// JSON body of a successful API call; error statuses become their message
+fn api(result: Result<ureq::Response, ureq::Error>) -> Result<Value, String> {
+    match reply_status(result)? {
+        (status, value) if status >= 400 => Err(error_message(&value)),
+        (_, value) => Ok(value),
+    }
+}
+
+/// Message of an OAuth, Sheets or Graph error body
+fn error_message(value: &Value) -> String {
+    value["error_description"]
+        .as_str()
+        .or_else(|| value["error"]["message"].as_str())
+        .or_else(|| value["error"].as_str())
+        .or_else(|| value.as_str())
+        .unwrap_or("Unexpected reply from the server")
+        .to_string()
+}
+
+/// Percent-encode a URL path segment
+fn encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Spreadsheet column name of the n-th column (1 = A, 27 = AA)
+fn column_letters(mut n: usize) -> String {
+    let mut letters = Vec::new();
+    while n > 0 {
+        letters.push((b'A' + ((n - 1) % 26) as u8) as char);
+        n = (n - 1) / 26;
+    }
+    letters.iter().rev().collect()
+}
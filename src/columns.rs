@@ -0,0 +1,225 @@
+use crate::file_scanner::{format_date, format_size, Calendar, FileInfo, SizeFormat};
+use crate::name_audit;
+use crate::text_match;
+use crate::transliterate;
+use std::cmp::Ordering;
+
+/// Value of a column for one file
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnValue {
+    Text(String),
+    Count(u64),
+    /// Bytes, shown with the size format and exported as a plain number
+    Size(u64),
+    /// Seconds since the UNIX epoch (None: not available)
+    Time(Option<i64>),
+}
+
+impl ColumnValue {
+    /// Text shown in the table
+    pub fn display(&self, format: &ColumnFormat) -> String {
+        match self {
+            ColumnValue::Text(text) => text.clone(),
+            ColumnValue::Count(count) => count.to_string(),
+            ColumnValue::Size(bytes) => format_size(*bytes, &format.size),
+            ColumnValue::Time(Some(timestamp)) => format_date(*timestamp, format.calendar),
+            ColumnValue::Time(None) => "-".to_string(),
+        }
+    }
+
+    /// Text written to exports: sizes in bytes, dates in the chosen calendar
+    pub fn export(&self, calendar: Calendar) -> String {
+        match self {
+            ColumnValue::Text(text) => text.clone(),
+            ColumnValue::Count(count) | ColumnValue::Size(count) => count.to_string(),
+            ColumnValue::Time(Some(timestamp)) => format_date(*timestamp, calendar),
+            ColumnValue::Time(None) => String::new(),
+        }
+    }
+
+    /// Default ordering: text case-insensitively (or by the configured locale collation), numbers and times by value (unknown times first)
+    pub fn compare(&self, other: &ColumnValue) -> Ordering {
+        match (self, other) {
+            (ColumnValue::Text(a), ColumnValue::Text(b)) => text_match::compare(a, b),
+            (ColumnValue::Count(a), ColumnValue::Count(b)) | (ColumnValue::Size(a), ColumnValue::Size(b)) => a.cmp(b),
+            (ColumnValue::Time(a), ColumnValue::Time(b)) => a.cmp(b),
+            _ => Ordering::Equal,
+        }
+    }
+}
+
+/// Display settings passed to column formatters
+pub struct ColumnFormat {
+    pub size: SizeFormat,
+    pub calendar: Calendar,
+}
+
+/// A column of the file list: how its value is read from a file, shown and compared.
+/// Sorting, the text filter and exports go through these, so a new column (built in or
+/// computed) is one more entry in ALL.
+#[derive(Clone, Copy)]
+pub struct FileColumn {
+    /// Stable key, e.g. for the sort column
+    pub id: &'static str,
+    /// Header in exports
+    pub header: &'static str,
+    pub extract: fn(&FileInfo) -> ColumnValue,
+    pub format: fn(&ColumnValue, &ColumnFormat) -> String,
+    pub compare: fn(&ColumnValue, &ColumnValue) -> Ordering,
+    /// The filter box matches this column's text
+    pub searchable: bool,
+}
+
+impl FileColumn {
+    /// Column with the default formatter and comparator of its values
+    pub const fn new(id: &'static str, header: &'static str, extract: fn(&FileInfo) -> ColumnValue) -> Self {
+        Self {
+            id,
+            header,
+            extract,
+            format: ColumnValue::display,
+            compare: ColumnValue::compare,
+            searchable: false,
+        }
+    }
+
+    pub fn value(&self, file: &FileInfo) -> ColumnValue {
+        (self.extract)(file)
+    }
+
+    /// Text shown in the table
+    pub fn text(&self, file: &FileInfo, format: &ColumnFormat) -> String {
+        (self.format)(&self.value(file), format)
+    }
+
+    pub fn is(&self, other: &FileColumn) -> bool {
+        self.id == other.id
+    }
+}
+
+/// Names and paths: numbers in them by value when natural sorting is on
+fn compare_natural(a: &ColumnValue, b: &ColumnValue) -> Ordering {
+    match (a, b) {
+        (ColumnValue::Text(a), ColumnValue::Text(b)) => text_match::compare_natural(a, b),
+        _ => a.compare(b),
+    }
+}
+
+pub const NAME: FileColumn = FileColumn {
+    searchable: true,
+    compare: compare_natural,
+    ..FileColumn::new("name", "File Name", |f| ColumnValue::Text(f.name().to_string()))
+};
+pub const EXTENSION: FileColumn = FileColumn {
+    searchable: true,
+    ..FileColumn::new("extension", "Extension", |f| ColumnValue::Text(f.extension().to_string()))
+};
+pub const SIZE: FileColumn = FileColumn::new("size", "Size (bytes)", |f| ColumnValue::Size(f.file_size));
+/// Space allocated on disk (the size where the platform doesn't report it)
+pub const ON_DISK: FileColumn = FileColumn::new("on_disk", "Size on Disk (bytes)", |f| {
+    ColumnValue::Size(f.allocated_size.unwrap_or(f.file_size))
+});
+/// Counts shown blank where they are zero (don't apply)
+fn count_or_blank(value: &ColumnValue, format: &ColumnFormat) -> String {
+    match value {
+        ColumnValue::Count(0) => String::new(),
+        value => value.display(format),
+    }
+}
+
+/// Files and subfolders below a folder row (blank for files)
+pub const ITEMS: FileColumn = FileColumn {
+    format: count_or_blank,
+    ..FileColumn::new("items", "Items", |f| ColumnValue::Count(f.item_count.into()))
+};
+pub const MODIFIED: FileColumn =
+    FileColumn::new("modified", "Modified", |f| ColumnValue::Time(Some(f.modified_timestamp)));
+pub const ACCESSED: FileColumn = FileColumn::new("accessed", "Accessed", |f| ColumnValue::Time(f.accessed_timestamp));
+pub const RELATIVE_PATH: FileColumn = FileColumn {
+    searchable: true,
+    compare: compare_natural,
+    ..FileColumn::new("relative_path", "Relative Path", |f| ColumnValue::Text(f.relative_path().to_string()))
+};
+pub const FULL_PATH: FileColumn = FileColumn {
+    compare: compare_natural,
+    ..FileColumn::new("full_path", "Full Path", |f| ColumnValue::Text(f.absolute_path().to_string()))
+};
+pub const NAME_LENGTH: FileColumn =
+    FileColumn::new("name_length", "Name Length", |f| ColumnValue::Count(name_audit::name_length(f) as u64));
+pub const PATH_LENGTH: FileColumn =
+    FileColumn::new("path_length", "Path Length", |f| ColumnValue::Count(name_audit::path_length(f) as u64));
+/// Content checksum (empty until hashed)
+pub const HASH: FileColumn = FileColumn {
+    searchable: true,
+    ..FileColumn::new("hash", "Hash", |f| ColumnValue::Text(f.hash.as_deref().unwrap_or_default().to_string()))
+};
+/// Lines of a text or code file (blank until counted and for other files)
+pub const LINES: FileColumn = FileColumn {
+    format: count_or_blank,
+    ..FileColumn::new("lines", "Lines", |f| ColumnValue::Count(f.line_count.unwrap_or(0)))
+};
+/// Words of a prose file
+pub const WORDS: FileColumn = FileColumn {
+    format: count_or_blank,
+    ..FileColumn::new("words", "Words", |f| ColumnValue::Count(f.word_count.unwrap_or(0)))
+};
+
+/// Every column, in table order
+pub const ALL: [FileColumn; 14] = [
+    NAME,
+    EXTENSION,
+    SIZE,
+    ON_DISK,
+    ITEMS,
+    MODIFIED,
+    ACCESSED,
+    RELATIVE_PATH,
+    NAME_LENGTH,
+    PATH_LENGTH,
+    FULL_PATH,
+    HASH,
+    LINES,
+    WORDS,
+];
+
+/// Sort files by (column, descending) keys in turn; each value is extracted once. Ties left
+/// after the keys go by name and then relative path, ascending, so files of equal size or
+/// extension always come out in the same order.
+pub fn sort(files: &mut Vec<FileInfo>, keys: &[(FileColumn, bool)]) {
+    let mut keys = keys.to_vec();
+    for tie_break in [NAME, RELATIVE_PATH] {
+        if !keys.iter().any(|(column, _)| column.is(&tie_break)) {
+            keys.push((tie_break, false));
+        }
+    }
+    let mut keyed: Vec<(Vec<ColumnValue>, FileInfo)> = files
+        .drain(..)
+        .map(|f| (keys.iter().map(|(column, _)| column.value(&f)).collect(), f))
+        .collect();
+    keyed.sort_by(|(a, _), (b, _)| {
+        keys.iter()
+            .zip(a.iter().zip(b))
+            .map(|((column, descending), (a, b))| {
+                let cmp = (column.compare)(a, b);
+                if *descending { cmp.reverse() } else { cmp }
+            })
+            .find(|cmp| cmp.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+    files.extend(keyed.into_iter().map(|(_, f)| f));
+}
+
+/// True if any searchable column contains the (folded) filter text; with `transliterate`,
+/// a Latin filter also matches the Latin transcription of Thai, Cyrillic or CJK names
+pub fn matches_filter(file: &FileInfo, filter: &str, fold_accents: bool, transliterate: bool) -> bool {
+    let latin_filter = transliterate && filter.is_ascii();
+    ALL.iter()
+        .filter(|column| column.searchable)
+        .any(|column| match column.value(file) {
+            ColumnValue::Text(text) => {
+                text_match::contains(&text, filter, fold_accents)
+                    || (latin_filter && !text.is_ascii() && transliterate::to_latin(&text).contains(filter))
+            }
+            _ => false,
+        })
+}
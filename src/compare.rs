@@ -0,0 +1,118 @@
+use crate::file_scanner::FileInfo;
+use crate::hashing::HashCache;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Comparison result for one root folder
+pub struct RootSummary {
+    pub root: PathBuf,
+    /// Files under this root
+    pub total: usize,
+    /// Absolute paths of files whose content exists in no other root
+    pub unique: Vec<String>,
+    pub unique_size: u64,
+    /// Files whose content also exists in another root
+    pub shared: usize,
+}
+
+/// Content comparison of two or more root folders
+pub struct RootComparison {
+    pub roots: Vec<RootSummary>,
+    /// Number of files that had to be hashed (same size as a file in another root)
+    pub hashed: usize,
+    /// Hashes taken from the checksum cache (file unchanged since it was last hashed)
+    pub cached: usize,
+    /// Files that could not be read (counted as unique)
+    pub errors: usize,
+    /// Online-only cloud files left out (hashing would download them)
+    pub skipped_cloud: usize,
+}
+
+/// Compare file content (size + SHA-256) across roots.
+/// Files are only hashed when a file of the same size exists under another root.
+pub fn compare_roots(files: &[FileInfo], roots: &[PathBuf], cache: &mut HashCache) -> RootComparison {
+    let hits_before = cache.hits;
+    let canonical_roots: Vec<PathBuf> = roots
+        .iter()
+        .map(|r| r.canonicalize().unwrap_or_else(|_| r.clone()))
+        .collect();
+
+    // (file index, root index) grouped by size
+    let mut by_size: HashMap<u64, Vec<(usize, usize)>> = HashMap::new();
+    let mut skipped_cloud = 0;
+    for (file_idx, file) in files.iter().enumerate() {
+        if file.is_cloud_placeholder {
+            skipped_cloud += 1;
+            continue;
+        }
+        let path = Path::new(file.absolute_path());
+        if let Some(root_idx) = canonical_roots.iter().position(|root| path.starts_with(root)) {
+            by_size.entry(file.file_size).or_default().push((file_idx, root_idx));
+        }
+    }
+
+    let mut summaries: Vec<RootSummary> = roots
+        .iter()
+        .map(|root| RootSummary {
+            root: root.clone(),
+            total: 0,
+            unique: Vec::new(),
+            unique_size: 0,
+            shared: 0,
+        })
+        .collect();
+    let mut hashed = 0;
+    let mut errors = 0;
+
+    for group in by_size.values() {
+        for &(_, root_idx) in group {
+            summaries[root_idx].total += 1;
+        }
+
+        // Content groups: files that are identical to each other
+        let mut content_groups: Vec<Vec<(usize, usize)>> = Vec::new();
+        let roots_in_group: HashSet<usize> = group.iter().map(|&(_, r)| r).collect();
+        if roots_in_group.len() == 1 {
+            // Size only occurs in one root - unique without hashing
+            content_groups.push(group.clone());
+        } else {
+            let mut by_hash: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+            for &(file_idx, root_idx) in group {
+                hashed += 1;
+                match cache.sha256(&files[file_idx]) {
+                    Ok(hash) => by_hash.entry(hash).or_default().push((file_idx, root_idx)),
+                    Err(_) => {
+                        errors += 1;
+                        content_groups.push(vec![(file_idx, root_idx)]);
+                    }
+                }
+            }
+            content_groups.extend(by_hash.into_values());
+        }
+
+        for content in content_groups {
+            let content_roots: HashSet<usize> = content.iter().map(|&(_, r)| r).collect();
+            for (file_idx, root_idx) in content {
+                let summary = &mut summaries[root_idx];
+                if content_roots.len() == 1 {
+                    summary.unique.push(files[file_idx].absolute_path().to_string());
+                    summary.unique_size += files[file_idx].file_size;
+                } else {
+                    summary.shared += 1;
+                }
+            }
+        }
+    }
+
+    for summary in &mut summaries {
+        summary.unique.sort();
+    }
+
+    RootComparison {
+        roots: summaries,
+        hashed,
+        cached: cache.hits - hits_before,
+        errors,
+        skipped_cloud,
+    }
+}
@@ -0,0 +1,111 @@
+use crate::document_parser;
+use crate::file_scanner::FileInfo;
+use crate::thumbnail::ThumbnailKind;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Broad content type of a file, for the folder composition bars
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContentKind {
+    Media,
+    Document,
+    Archive,
+    Other,
+}
+
+impl ContentKind {
+    pub const ALL: [ContentKind; 4] = [ContentKind::Media, ContentKind::Document, ContentKind::Archive, ContentKind::Other];
+
+    /// Kind of a file by its extension (images, video and audio are media)
+    pub fn of(extension: &str) -> Self {
+        let extension = extension.to_lowercase();
+        if ThumbnailKind::from_extension(&extension).is_some_and(|kind| kind != ThumbnailKind::Pdf)
+            || document_parser::AUDIO_EXTENSIONS.contains(&extension.as_str())
+            || matches!(extension.as_str(), "svg" | "tif" | "tiff" | "heic" | "raw" | "cr2" | "nef" | "dng")
+        {
+            return ContentKind::Media;
+        }
+        match extension.as_str() {
+            "pdf" | "doc" | "docx" | "xls" | "xlsx" | "ppt" | "pptx" | "odt" | "ods" | "odp" | "rtf" | "txt"
+            | "md" | "csv" | "epub" => ContentKind::Document,
+            "zip" | "rar" | "7z" | "tar" | "gz" | "tgz" | "bz2" | "xz" | "zst" | "iso" | "cab" => {
+                ContentKind::Archive
+            }
+            _ => ContentKind::Other,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ContentKind::Media => "Media",
+            ContentKind::Document => "Documents",
+            ContentKind::Archive => "Archives",
+            ContentKind::Other => "Other",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Bytes and files of each content kind below a folder
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContentMix {
+    pub bytes: [u64; 4],
+    pub files: [usize; 4],
+}
+
+impl ContentMix {
+    fn add(&mut self, file: &FileInfo) {
+        let kind = ContentKind::of(file.extension()).index();
+        self.bytes[kind] += file.file_size;
+        self.files[kind] += 1;
+    }
+
+    /// Share (0.0-1.0) of the kind by size; by file count when all files are empty
+    pub fn share(&self, kind: ContentKind) -> f32 {
+        let total_bytes: u64 = self.bytes.iter().sum();
+        if total_bytes > 0 {
+            return self.bytes[kind.index()] as f32 / total_bytes as f32;
+        }
+        let total_files: usize = self.files.iter().sum();
+        if total_files == 0 {
+            return 0.0;
+        }
+        self.files[kind.index()] as f32 / total_files as f32
+    }
+
+    /// "Media 90% (12 files), Documents 10% (3 files)", largest share first
+    pub fn describe(&self) -> String {
+        let mut kinds: Vec<ContentKind> =
+            ContentKind::ALL.into_iter().filter(|kind| self.files[kind.index()] > 0).collect();
+        kinds.sort_by(|a, b| self.share(*b).total_cmp(&self.share(*a)));
+        kinds
+            .iter()
+            .map(|kind| {
+                format!(
+                    "{} {:.0}% ({} files)",
+                    kind.label(),
+                    self.share(*kind) * 100.0,
+                    self.files[kind.index()]
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Content mix of each folder row (by absolute path), over all files below it
+pub fn folder_mixes(folders: &[FileInfo], files: &[FileInfo]) -> HashMap<String, ContentMix> {
+    let mut mixes: HashMap<String, ContentMix> =
+        folders.iter().map(|folder| (folder.absolute_path().to_string(), ContentMix::default())).collect();
+    for file in files.iter().filter(|f| !f.is_dir) {
+        for folder in Path::new(file.absolute_path()).ancestors().skip(1) {
+            if let Some(mix) = mixes.get_mut(folder.to_string_lossy().as_ref()) {
+                mix.add(file);
+            }
+        }
+    }
+    mixes
+}
@@ -1,28 +1,506 @@
-use crate::file_scanner::FileInfo;
+use crate::columns::{self, ColumnValue, FileColumn};
+use crate::file_scanner::{Calendar, FileInfo, SizeFormat};
+use crate::html_report;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::Write;
-use std::path::Path;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Error message of an export stopped by the user
+pub const CANCELLED: &str = "Export cancelled";
+
+/// Rows between progress messages of a background export
+const PROGRESS_ROWS: usize = 1000;
+
+/// A column of the exported file
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportColumn {
+    FileName,
+    Extension,
+    Size,
+    /// Allocated size of the "On disk" column
+    SizeOnDisk,
+    Modified,
+    Accessed,
+    RelativePath,
+    FullPath,
+    /// Checksum of the Hash column
+    Hash,
+    /// Counts of the Lines / Words columns
+    Lines,
+    Words,
+}
+
+impl ExportColumn {
+    /// Columns of the standard export
+    pub const DEFAULT: [ExportColumn; 5] = [
+        ExportColumn::FileName,
+        ExportColumn::Extension,
+        ExportColumn::Size,
+        ExportColumn::RelativePath,
+        ExportColumn::FullPath,
+    ];
+
+    /// Every column, in the order exports write them
+    pub const ALL: [ExportColumn; 11] = [
+        ExportColumn::FileName,
+        ExportColumn::Extension,
+        ExportColumn::Size,
+        ExportColumn::SizeOnDisk,
+        ExportColumn::Modified,
+        ExportColumn::Accessed,
+        ExportColumn::RelativePath,
+        ExportColumn::FullPath,
+        ExportColumn::Hash,
+        ExportColumn::Lines,
+        ExportColumn::Words,
+    ];
+
+    /// File list column the values come from
+    pub fn column(self) -> FileColumn {
+        match self {
+            ExportColumn::FileName => columns::NAME,
+            ExportColumn::Extension => columns::EXTENSION,
+            ExportColumn::Size => columns::SIZE,
+            ExportColumn::SizeOnDisk => columns::ON_DISK,
+            ExportColumn::Modified => columns::MODIFIED,
+            ExportColumn::Accessed => columns::ACCESSED,
+            ExportColumn::RelativePath => columns::RELATIVE_PATH,
+            ExportColumn::FullPath => columns::FULL_PATH,
+            ExportColumn::Hash => columns::HASH,
+            ExportColumn::Lines => columns::LINES,
+            ExportColumn::Words => columns::WORDS,
+        }
+    }
+
+    pub fn header(self) -> &'static str {
+        self.column().header
+    }
+
+    /// Header naming the era of exported dates, so B.E. years are not mistaken for Gregorian ones
+    pub fn header_in(self, calendar: Calendar) -> String {
+        match (self, calendar) {
+            (ExportColumn::Modified, Calendar::ThaiBuddhist) => "Modified (B.E.)".to_string(),
+            _ => self.header().to_string(),
+        }
+    }
+
+    pub fn value(self, file: &FileInfo, calendar: Calendar) -> String {
+        self.column().value(file).export(calendar)
+    }
+
+    /// Value in a JSON export: sizes and counts as numbers, missing dates as null
+    fn json_value(self, file: &FileInfo, calendar: Calendar) -> serde_json::Value {
+        match self.column().value(file) {
+            ColumnValue::Count(count) | ColumnValue::Size(count) => count.into(),
+            ColumnValue::Time(None) => serde_json::Value::Null,
+            value => value.export(calendar).into(),
+        }
+    }
+}
+
+/// File format of the export
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    /// Tab-separated
+    Tsv,
+    /// Array of objects keyed by column id ("name", "size", "modified", ...)
+    Json,
+    /// Self-contained page with a summary and a sortable table
+    Html,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 4] = [ExportFormat::Csv, ExportFormat::Tsv, ExportFormat::Json, ExportFormat::Html];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Tsv => "TSV",
+            ExportFormat::Json => "JSON",
+            ExportFormat::Html => "HTML",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Tsv => "tsv",
+            ExportFormat::Json => "json",
+            ExportFormat::Html => "html",
+        }
+    }
+
+    /// Content type of an exported file, e.g. for an e-mail attachment
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Tsv => "text/tab-separated-values",
+            ExportFormat::Json => "application/json",
+            ExportFormat::Html => "text/html",
+        }
+    }
+
+    /// Format for an output file by its extension (CSV if it has none of the others)
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+        ExportFormat::ALL
+            .into_iter()
+            .find(|format| format.extension() == extension)
+            .unwrap_or_default()
+    }
+
+    fn delimiter(self) -> u8 {
+        match self {
+            ExportFormat::Tsv => b'\t',
+            ExportFormat::Csv | ExportFormat::Json | ExportFormat::Html => b',',
+        }
+    }
+}
+
+/// How an export is split into several files
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportSplit {
+    /// One file
+    #[default]
+    None,
+    /// At most this many rows per file: name_part001.csv, name_part002.csv, ...
+    MaxRows(usize),
+    /// One file per extension: name_jpg.csv, name_pdf.csv, ...
+    ByExtension,
+    /// One file per top-level folder of the relative path
+    ByTopFolder,
+}
+
+impl ExportSplit {
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportSplit::None => "Single file",
+            ExportSplit::MaxRows(_) => "Max rows per file",
+            ExportSplit::ByExtension => "One file per extension",
+            ExportSplit::ByTopFolder => "One file per top-level folder",
+        }
+    }
+}
+
+/// Columns, format and splitting of an export
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportOptions {
+    pub columns: Vec<ExportColumn>,
+    pub format: ExportFormat,
+    pub split: ExportSplit,
+    /// Era of exported years
+    pub calendar: Calendar,
+    /// Sizes shown in HTML reports (other formats write bytes)
+    pub size_format: SizeFormat,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            columns: ExportColumn::DEFAULT.to_vec(),
+            format: ExportFormat::Csv,
+            split: ExportSplit::None,
+            calendar: Calendar::Gregorian,
+            size_format: SizeFormat::default(),
+        }
+    }
+}
 
 pub fn export_to_csv(files: &[FileInfo], output_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    write_file(files, &ExportOptions::default(), None, output_path, &mut || true)
+}
+
+/// Export with the given columns, format and splitting. Returns the files written.
+pub fn export_with_options(
+    files: &[FileInfo],
+    options: &ExportOptions,
+    output_path: &Path,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    export_split(files, options, None, output_path, &mut || true)
+}
+
+/// Result of a finished background export
+pub struct ExportOutcome {
+    pub written: Vec<PathBuf>,
+    /// Total size of the files written
+    pub bytes: u64,
+    pub elapsed: Duration,
+}
+
+enum ExportMessage {
+    Progress(usize),
+    Finished(Result<ExportOutcome, String>),
+}
+
+/// Handle to an export running on a background thread
+pub struct ExportJob {
+    receiver: Receiver<ExportMessage>,
+    cancelled: Arc<AtomicBool>,
+    pub rows_done: usize,
+    pub rows_total: usize,
+}
+
+impl ExportJob {
+    /// Export `files` on a background thread. With `thumbnails`, an extra "Thumbnail" column
+    /// holds the relative path of each file's thumbnail (HTML reports embed the images).
+    pub fn spawn(
+        files: Vec<FileInfo>,
+        thumbnails: Option<Vec<Option<String>>>,
+        options: ExportOptions,
+        output_path: PathBuf,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let worker_cancelled = Arc::clone(&cancelled);
+        let rows_total = files.len();
+
+        thread::spawn(move || {
+            let start = Instant::now();
+            let mut rows = 0;
+            let mut on_row = || {
+                rows += 1;
+                if rows % PROGRESS_ROWS == 0 {
+                    let _ = tx.send(ExportMessage::Progress(rows));
+                }
+                !worker_cancelled.load(Ordering::Relaxed)
+            };
+            let result = export_split(&files, &options, thumbnails.as_deref(), &output_path, &mut on_row)
+                .map(|written| ExportOutcome {
+                    bytes: written.iter().filter_map(|p| std::fs::metadata(p).ok()).map(|m| m.len()).sum(),
+                    written,
+                    elapsed: start.elapsed(),
+                })
+                .map_err(|e| e.to_string());
+            let _ = tx.send(ExportMessage::Finished(result));
+        });
+
+        Self {
+            receiver: rx,
+            cancelled,
+            rows_done: 0,
+            rows_total,
+        }
+    }
+
+    /// Stop the export; files it has written so far are removed
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.rows_total == 0 {
+            0.0
+        } else {
+            self.rows_done as f32 / self.rows_total as f32
+        }
+    }
+
+    /// Apply pending progress messages; returns the result once the export has finished
+    pub fn poll(&mut self) -> Option<Result<ExportOutcome, String>> {
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                ExportMessage::Progress(rows) => self.rows_done = rows,
+                ExportMessage::Finished(result) => return Some(result),
+            }
+        }
+        None
+    }
+}
+
+/// Write one file per split group, named "<stem>_<group>.<ext>" next to output_path
+/// `on_row` is called after each row and stops the export (Err(CANCELLED)) when it returns false.
+fn export_split(
+    files: &[FileInfo],
+    options: &ExportOptions,
+    thumbnails: Option<&[Option<String>]>,
+    output_path: &Path,
+    on_row: &mut dyn FnMut() -> bool,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let groups = split_groups(files, options.split);
+    if groups.len() <= 1 && !matches!(options.split, ExportSplit::MaxRows(_)) {
+        write_file(files, options, thumbnails, output_path, on_row)?;
+        return Ok(vec![output_path.to_path_buf()]);
+    }
+
+    let stem = output_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "files".to_string());
+    let extension = output_path
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_else(|| options.format.extension().to_string());
+
+    let mut written = Vec::new();
+    for (suffix, indices) in groups {
+        let group_files: Vec<FileInfo> = indices.iter().map(|&i| files[i].clone()).collect();
+        let group_thumbnails: Option<Vec<Option<String>>> =
+            thumbnails.map(|t| indices.iter().map(|&i| t.get(i).cloned().flatten()).collect());
+        let path = output_path.with_file_name(format!("{}_{}.{}", stem, suffix, extension));
+        if let Err(e) = write_file(&group_files, options, group_thumbnails.as_deref(), &path, on_row) {
+            if e.to_string() == CANCELLED {
+                // Don't leave a partial set of split files behind
+                for path in &written {
+                    let _ = std::fs::remove_file(path);
+                }
+            }
+            return Err(e);
+        }
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// File indices per output file, keyed by the file name suffix (in output order)
+fn split_groups(files: &[FileInfo], split: ExportSplit) -> Vec<(String, Vec<usize>)> {
+    let group_by = |key: &dyn Fn(&FileInfo) -> String| {
+        let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (idx, file) in files.iter().enumerate() {
+            groups.entry(sanitize_suffix(&key(file))).or_default().push(idx);
+        }
+        groups.into_iter().collect()
+    };
+
+    match split {
+        ExportSplit::None => vec![(String::new(), (0..files.len()).collect())],
+        ExportSplit::MaxRows(max_rows) => {
+            let indices: Vec<usize> = (0..files.len()).collect();
+            let chunks: Vec<&[usize]> = indices.chunks(max_rows.max(1)).collect();
+            // Zero-padded so the parts sort in order
+            let width = chunks.len().to_string().len().max(3);
+            chunks
+                .iter()
+                .enumerate()
+                .map(|(n, chunk)| (format!("part{:0width$}", n + 1, width = width), chunk.to_vec()))
+                .collect()
+        }
+        ExportSplit::ByExtension => group_by(&|f| {
+            if f.extension().is_empty() {
+                "no_extension".to_string()
+            } else {
+                f.extension().to_lowercase()
+            }
+        }),
+        ExportSplit::ByTopFolder => group_by(&|f| {
+            let relative = f.relative_path().replace('\\', "/");
+            match relative.split_once('/') {
+                // "[folder]/..." prefix of multi-folder scans
+                Some((top, _)) => top.trim_start_matches('[').trim_end_matches(']').to_string(),
+                None => "root".to_string(),
+            }
+        }),
+    }
+}
+
+/// Make a group name safe to use in a file name
+fn sanitize_suffix(text: &str) -> String {
+    let cleaned: String = text
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect();
+    let cleaned = cleaned.trim_matches(|c| c == '_' || c == '.');
+    if cleaned.is_empty() {
+        "other".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+/// Write one output file in the format of the options
+fn write_file(
+    files: &[FileInfo],
+    options: &ExportOptions,
+    thumbnails: Option<&[Option<String>]>,
+    output_path: &Path,
+    on_row: &mut dyn FnMut() -> bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match options.format {
+        ExportFormat::Json => write_json(files, options, thumbnails, output_path, on_row),
+        ExportFormat::Html => html_report::write_report(files, options, thumbnails, output_path, on_row),
+        ExportFormat::Csv | ExportFormat::Tsv => write_csv(files, options, thumbnails, output_path, on_row),
+    }
+}
+
+/// Objects are built first, so a cancelled export leaves no file behind
+fn write_json(
+    files: &[FileInfo],
+    options: &ExportOptions,
+    thumbnails: Option<&[Option<String>]>,
+    output_path: &Path,
+    on_row: &mut dyn FnMut() -> bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut rows = Vec::with_capacity(files.len());
+    for (idx, file_info) in files.iter().enumerate() {
+        let mut row: serde_json::Map<String, serde_json::Value> = options
+            .columns
+            .iter()
+            .map(|c| (c.column().id.to_string(), c.json_value(file_info, options.calendar)))
+            .collect();
+        if let Some(thumbnails) = thumbnails {
+            row.insert("thumbnail".to_string(), thumbnails.get(idx).cloned().flatten().into());
+        }
+        rows.push(serde_json::Value::Object(row));
+        if !on_row() {
+            return Err(CANCELLED.into());
+        }
+    }
+    let mut writer = BufWriter::new(File::create(output_path)?);
+    serde_json::to_writer_pretty(&mut writer, &rows)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_csv(
+    files: &[FileInfo],
+    options: &ExportOptions,
+    thumbnails: Option<&[Option<String>]>,
+    output_path: &Path,
+    on_row: &mut dyn FnMut() -> bool,
+) -> Result<(), Box<dyn std::error::Error>> {
     let mut file = File::create(output_path)?;
 
     // Write UTF-8 BOM for Excel compatibility with non-English characters
     file.write_all(&[0xEF, 0xBB, 0xBF])?;
 
-    let mut writer = csv::Writer::from_writer(file);
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(options.format.delimiter())
+        .from_writer(file);
 
     // Write header manually for better column names
-    writer.write_record(["File Name", "Extension", "Size (bytes)", "Relative Path", "Full Path"])?;
+    let mut header: Vec<String> = options.columns.iter().map(|c| c.header_in(options.calendar)).collect();
+    if thumbnails.is_some() {
+        header.push("Thumbnail".to_string());
+    }
+    writer.write_record(&header)?;
 
     // Write data rows
-    for file_info in files {
-        writer.write_record([
-            &file_info.name,
-            &file_info.extension,
-            &file_info.file_size.to_string(),
-            &file_info.relative_path,
-            &file_info.absolute_path,
-        ])?;
+    for (idx, file_info) in files.iter().enumerate() {
+        let mut record: Vec<String> = options.columns.iter().map(|c| c.value(file_info, options.calendar)).collect();
+        if let Some(thumbnails) = thumbnails {
+            record.push(thumbnails.get(idx).and_then(|t| t.clone()).unwrap_or_default());
+        }
+        writer.write_record(&record)?;
+        if !on_row() {
+            drop(writer);
+            let _ = std::fs::remove_file(output_path);
+            return Err(CANCELLED.into());
+        }
     }
 
     writer.flush()?;
@@ -0,0 +1,122 @@
+use crate::settings::AppSettings;
+use serde_json::Value;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Lines from the end of the debug log put in a bundle
+const LOG_LINES: usize = 2000;
+
+/// Debug log of previews and downloads (in the temp folder)
+pub fn debug_log_path() -> PathBuf {
+    std::env::temp_dir().join("file_lister_debug.log")
+}
+
+/// Replaces what identifies the user in bundle text: scanned folders, home folder, user name
+pub struct Anonymizer {
+    /// (text, placeholder), longest text first so a folder wins over the home folder inside it
+    replacements: Vec<(String, String)>,
+}
+
+impl Anonymizer {
+    pub fn new(folders: &[PathBuf]) -> Self {
+        let mut replacements: Vec<(String, String)> = folders
+            .iter()
+            .enumerate()
+            .map(|(idx, folder)| (folder.display().to_string(), format!("<folder {}>", idx + 1)))
+            .collect();
+        if let Some(home) = dirs::home_dir() {
+            replacements.push((home.display().to_string(), "<home>".to_string()));
+        }
+        // Short names would replace parts of ordinary words
+        if let Some(user) = std::env::var("USERNAME").or_else(|_| std::env::var("USER")).ok().filter(|u| u.len() >= 3) {
+            replacements.push((user, "<user>".to_string()));
+        }
+        replacements.retain(|(text, _)| !text.is_empty());
+        replacements.sort_by_key(|(text, _)| std::cmp::Reverse(text.len()));
+        Self { replacements }
+    }
+
+    /// Leaves everything as it is
+    pub fn off() -> Self {
+        Self { replacements: Vec::new() }
+    }
+
+    /// Replace in plain text and in JSON, where Windows paths have their backslashes doubled
+    pub fn apply(&self, text: &str) -> String {
+        self.replacements.iter().fold(text.to_string(), |text, (from, to)| {
+            text.replace(&from.replace('\\', "\\\\"), to).replace(from, to)
+        })
+    }
+}
+
+/// Settings as they go into a bundle: the SMTP password always removed; with anonymizing,
+/// also mail addresses and hook targets (webhook URLs can carry tokens)
+fn redacted_settings(settings: &AppSettings, anonymize: bool) -> AppSettings {
+    let mut settings = settings.clone();
+    let redact = |text: &mut String| {
+        if !text.is_empty() {
+            *text = "<redacted>".to_string();
+        }
+    };
+    redact(&mut settings.report_mail.smtp_password);
+    if anonymize {
+        redact(&mut settings.report_mail.to);
+        redact(&mut settings.report_mail.from);
+        redact(&mut settings.report_mail.smtp_user);
+        for hook in &mut settings.hooks {
+            redact(&mut hook.target);
+        }
+    }
+    settings
+}
+
+/// The end of the debug log ("" if there is none)
+fn debug_log_tail() -> String {
+    let log = std::fs::read_to_string(debug_log_path()).unwrap_or_default();
+    let lines: Vec<&str> = log.lines().collect();
+    lines[lines.len().saturating_sub(LOG_LINES)..].join("\n")
+}
+
+/// What the app puts in a diagnostic bundle besides settings and the debug log
+pub struct BundleContents {
+    /// "key: value" lines of environment.txt
+    pub environment: Vec<(String, String)>,
+    /// Counts of the current scan, scan errors, failed previews
+    pub scan_summary: Value,
+    /// Folders, options, filters and view toggles
+    pub app_state: Value,
+}
+
+/// Write a zip for bug reports: environment.txt, scan_summary.json, app_state.json,
+/// settings.json and debug.log, all passed through the anonymizer
+pub fn write_bundle(
+    path: &Path,
+    contents: &BundleContents,
+    settings: &AppSettings,
+    anonymizer: &Anonymizer,
+) -> Result<(), String> {
+    let anonymize = !anonymizer.replacements.is_empty();
+    let mut environment: String =
+        contents.environment.iter().map(|(key, value)| format!("{}: {}\n", key, value)).collect();
+    environment.push_str(&format!("Anonymized: {}\n", if anonymize { "yes" } else { "no" }));
+    let pretty = |value: &Value| serde_json::to_string_pretty(value).unwrap_or_default();
+    let settings = serde_json::to_string_pretty(&redacted_settings(settings, anonymize))
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    let entries = [
+        ("environment.txt", environment),
+        ("scan_summary.json", pretty(&contents.scan_summary)),
+        ("app_state.json", pretty(&contents.app_state)),
+        ("settings.json", settings),
+        ("debug.log", debug_log_tail()),
+    ];
+
+    let file = std::fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, text) in entries {
+        zip.start_file(name, options).map_err(|e| format!("Failed to write bundle: {}", e))?;
+        zip.write_all(anonymizer.apply(&text).as_bytes()).map_err(|e| format!("Failed to write bundle: {}", e))?;
+    }
+    zip.finish().map_err(|e| format!("Failed to write bundle: {}", e))?;
+    Ok(())
+}
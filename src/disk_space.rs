@@ -0,0 +1,257 @@
+use std::path::Path;
+
+/// Free and total space of a volume
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolumeSpace {
+    /// Bytes available to the current user (excludes space reserved for root)
+    pub free: u64,
+    pub total: u64,
+}
+
+/// Space of the volume holding `path` (the nearest existing ancestor, so a destination
+/// folder that is about to be created works too)
+pub fn volume_space(path: &Path) -> Option<VolumeSpace> {
+    let existing = path.ancestors().find(|p| p.exists())?;
+    sys::volume_space(existing)
+}
+
+/// Largest file a FAT volume can hold (4 GiB - 1 byte)
+pub const FAT_MAX_FILE_SIZE: u64 = u32::MAX as u64;
+
+/// File system of a destination, as far as copying to it is concerned
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileSystem {
+    /// FAT32 (or FAT16/12): files up to 4 GiB
+    Fat,
+    ExFat,
+    Other,
+}
+
+/// File system and cluster (allocation unit) size of a volume
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolumeFormat {
+    pub file_system: FileSystem,
+    pub cluster_size: u64,
+}
+
+/// Format of the volume holding `path` (the nearest existing ancestor)
+pub fn volume_format(path: &Path) -> Option<VolumeFormat> {
+    let existing = path.ancestors().find(|p| p.exists())?;
+    sys::volume_format(existing)
+}
+
+/// Bytes a file of `size` takes in whole clusters (empty files take none)
+pub fn size_in_clusters(size: u64, cluster_size: u64) -> u64 {
+    if cluster_size == 0 {
+        return size;
+    }
+    size.div_ceil(cluster_size) * cluster_size
+}
+
+/// What a copy will take on the destination, and what can't go there at all
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransferEstimate {
+    /// Sum of the file sizes
+    pub bytes: u64,
+    /// Space on the destination: each file rounded up to whole clusters
+    pub on_destination: u64,
+    /// Cluster size used for the estimate (0: unknown, sizes as they are)
+    pub cluster_size: u64,
+    /// (name, size) of files over the destination's file size limit (FAT: 4 GiB)
+    pub too_large: Vec<(String, u64)>,
+}
+
+/// Estimate a copy of the (name, size) files to `dest`
+pub fn estimate_transfer(files: &[(String, u64)], dest: &Path) -> TransferEstimate {
+    let format = volume_format(dest);
+    let cluster_size = format.map_or(0, |f| f.cluster_size);
+    let limit = format.filter(|f| f.file_system == FileSystem::Fat).map(|_| FAT_MAX_FILE_SIZE);
+    TransferEstimate {
+        bytes: files.iter().map(|(_, size)| size).sum(),
+        on_destination: files.iter().map(|(_, size)| size_in_clusters(*size, cluster_size)).sum(),
+        cluster_size,
+        too_large: files
+            .iter()
+            .filter(|(_, size)| limit.is_some_and(|limit| *size > limit))
+            .cloned()
+            .collect(),
+    }
+}
+
+/// True if both paths are on the same volume, so a move is a rename and needs no free space.
+/// Unknown counts as different (the caller then checks space).
+pub fn same_volume(a: &Path, b: &Path) -> bool {
+    let existing = |p: &Path| p.ancestors().find(|p| p.exists()).map(|p| p.to_path_buf());
+    match (existing(a), existing(b)) {
+        (Some(a), Some(b)) => sys::volume_id(&a).is_some_and(|id| sys::volume_id(&b) == Some(id)),
+        _ => false,
+    }
+}
+
+/// Check that `required` bytes fit on the volume of `dest`; the error names both sizes
+pub fn check_fits(dest: &Path, required: u64, format: impl Fn(u64) -> String) -> Result<(), String> {
+    match volume_space(dest) {
+        Some(space) if space.free < required => Err(format!(
+            "Not enough space on the destination: needs {}, only {} free",
+            format(required),
+            format(space.free)
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use super::{FileSystem, VolumeFormat, VolumeSpace};
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::MetadataExt;
+    use std::path::Path;
+
+    pub fn volume_space(path: &Path) -> Option<VolumeSpace> {
+        let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        // c_path is NUL-terminated and stat is a valid out pointer
+        if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+            return None;
+        }
+        let block = stat.f_frsize as u64;
+        Some(VolumeSpace {
+            free: stat.f_bavail as u64 * block,
+            total: stat.f_blocks as u64 * block,
+        })
+    }
+
+    pub fn volume_id(path: &Path) -> Option<u64> {
+        std::fs::metadata(path).ok().map(|m| m.dev())
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn volume_format(path: &Path) -> Option<VolumeFormat> {
+        let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+        let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+        // c_path is NUL-terminated and stat is a valid out pointer
+        if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+            return None;
+        }
+        // MSDOS_SUPER_MAGIC (vfat) and EXFAT_SUPER_MAGIC
+        let file_system = match stat.f_type {
+            0x4d44 => FileSystem::Fat,
+            0x2011_bab0 => FileSystem::ExFat,
+            _ => FileSystem::Other,
+        };
+        Some(VolumeFormat {
+            file_system,
+            cluster_size: u64::try_from(stat.f_bsize).ok()?,
+        })
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn volume_format(path: &Path) -> Option<VolumeFormat> {
+        let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+        let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+        // c_path is NUL-terminated and stat is a valid out pointer
+        if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+            return None;
+        }
+        // f_fstypename is NUL-terminated
+        let name = unsafe { std::ffi::CStr::from_ptr(stat.f_fstypename.as_ptr()) };
+        let file_system = match name.to_bytes() {
+            b"msdos" => FileSystem::Fat,
+            b"exfat" => FileSystem::ExFat,
+            _ => FileSystem::Other,
+        };
+        Some(VolumeFormat {
+            file_system,
+            cluster_size: u64::from(stat.f_bsize),
+        })
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    pub fn volume_format(_path: &Path) -> Option<VolumeFormat> {
+        None
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use super::{FileSystem, VolumeFormat, VolumeSpace};
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::{Component, Path};
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetDiskFreeSpaceExW, GetDiskFreeSpaceW, GetVolumeInformationW, GetVolumePathNameW,
+    };
+
+    pub fn volume_space(path: &Path) -> Option<VolumeSpace> {
+        let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+        let (mut free, mut total) = (0u64, 0u64);
+        // wide is NUL-terminated; the out pointers are valid
+        let ok = unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut free, &mut total, std::ptr::null_mut()) };
+        (ok != 0).then_some(VolumeSpace { free, total })
+    }
+
+    /// Drive letter or UNC share (mounted folders inside a drive are treated as that drive)
+    pub fn volume_id(path: &Path) -> Option<u64> {
+        use std::hash::{Hash, Hasher};
+        let canonical = path.canonicalize().ok()?;
+        let Some(Component::Prefix(prefix)) = canonical.components().next() else {
+            return None;
+        };
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        prefix.as_os_str().to_string_lossy().to_lowercase().hash(&mut hasher);
+        Some(hasher.finish())
+    }
+
+    pub fn volume_format(path: &Path) -> Option<VolumeFormat> {
+        let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+        let mut root = [0u16; 261];
+        let mut name = [0u16; 32];
+        // All buffers are NUL-terminated or sized by their length; the out pointers are valid
+        unsafe {
+            if GetVolumePathNameW(wide.as_ptr(), root.as_mut_ptr(), root.len() as u32) == 0
+                || GetVolumeInformationW(
+                    root.as_ptr(),
+                    std::ptr::null_mut(),
+                    0,
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    name.as_mut_ptr(),
+                    name.len() as u32,
+                ) == 0
+            {
+                return None;
+            }
+        }
+        let length = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+        let file_system = match String::from_utf16_lossy(&name[..length]).to_uppercase().as_str() {
+            "FAT32" | "FAT" | "FAT16" | "FAT12" => FileSystem::Fat,
+            "EXFAT" => FileSystem::ExFat,
+            _ => FileSystem::Other,
+        };
+        let (mut sectors, mut bytes, mut free, mut total) = (0u32, 0u32, 0u32, 0u32);
+        let read = unsafe { GetDiskFreeSpaceW(root.as_ptr(), &mut sectors, &mut bytes, &mut free, &mut total) };
+        Some(VolumeFormat {
+            file_system,
+            cluster_size: if read != 0 { u64::from(sectors) * u64::from(bytes) } else { 0 },
+        })
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    use super::{VolumeFormat, VolumeSpace};
+    use std::path::Path;
+
+    pub fn volume_space(_path: &Path) -> Option<VolumeSpace> {
+        None
+    }
+
+    pub fn volume_id(_path: &Path) -> Option<u64> {
+        None
+    }
+
+    pub fn volume_format(_path: &Path) -> Option<VolumeFormat> {
+        None
+    }
+}
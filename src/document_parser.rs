@@ -1,3 +1,4 @@
+use crate::retry;
 use std::path::Path;
 
 /// Maximum lines to show for text preview
@@ -9,9 +10,33 @@ const MAX_TABLE_ROWS: usize = 100;
 /// Maximum columns to show for table preview
 const MAX_TABLE_COLS: usize = 20;
 
+/// Extensions previewed as audio (duration, sample rate, codec)
+pub const AUDIO_EXTENSIONS: [&str; 8] = ["mp3", "wav", "ogg", "flac", "aac", "m4a", "wma", "opus"];
+
+/// Extensions previewed as source code
+pub const CODE_EXTENSIONS: [&str; 34] = [
+    "html", "htm", "js", "jsx", "ts", "tsx", "css", "scss", "less",
+    "xml", "yaml", "yml", "json", "toml", "ini", "conf", "cfg",
+    "rs", "py", "rb", "go", "java", "c", "cpp", "h", "hpp",
+    "sh", "bash", "zsh", "bat", "ps1", "sql", "md", "markdown",
+];
+
+/// Larger files are not read into memory whole (text previews, images); a multi-GB log or disk
+/// image fails with a message instead of exhausting memory, which a 32-bit build runs out of first
+pub const MAX_WHOLE_READ_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Read a whole file unless it is over MAX_WHOLE_READ_BYTES
+pub fn read_whole(path: &Path) -> std::io::Result<Vec<u8>> {
+    let size = std::fs::metadata(path)?.len();
+    if size > MAX_WHOLE_READ_BYTES {
+        return Err(std::io::Error::other(format!("too large to load ({} bytes)", size)));
+    }
+    std::fs::read(path)
+}
+
 /// Read file bytes and decode with encoding detection
-fn read_text_with_encoding(path: &Path) -> Result<String, String> {
-    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+pub fn read_text_with_encoding(path: &Path) -> Result<String, String> {
+    let bytes = retry::with_retry(|| read_whole(path)).map_err(|e| format!("Failed to read file: {}", e))?;
 
     // Try UTF-8 first (most common)
     if let Ok(content) = std::str::from_utf8(&bytes) {
@@ -92,7 +117,7 @@ pub fn extract_audio_metadata(path: &Path) -> Result<AudioMetadata, String> {
     use symphonia::core::meta::MetadataOptions;
     use symphonia::core::probe::Hint;
 
-    let file = std::fs::File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let file = retry::with_retry(|| std::fs::File::open(path)).map_err(|e| format!("Failed to open file: {}", e))?;
     let mss = MediaSourceStream::new(Box::new(file), Default::default());
 
     let mut hint = Hint::new();
@@ -163,7 +188,7 @@ pub fn extract_docx_text(path: &Path) -> Result<String, String> {
     use std::io::{BufReader, Read};
 
     // Open the docx file as a zip archive
-    let file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let file = retry::with_retry(|| File::open(path)).map_err(|e| format!("Failed to open file: {}", e))?;
     let reader = BufReader::new(file);
     let mut archive =
         zip::ZipArchive::new(reader).map_err(|e| format!("Failed to read DOCX archive: {}", e))?;
@@ -0,0 +1,62 @@
+use crate::file_scanner::FileInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Which file of a duplicate group the duplicate manager keeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeepRule {
+    #[default]
+    Newest,
+    Oldest,
+    ShortestPath,
+}
+
+impl KeepRule {
+    pub const ALL: [KeepRule; 3] = [KeepRule::Newest, KeepRule::Oldest, KeepRule::ShortestPath];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            KeepRule::Newest => "Keep newest",
+            KeepRule::Oldest => "Keep oldest",
+            KeepRule::ShortestPath => "Keep shortest path",
+        }
+    }
+
+    /// Index of the file to keep in a group (ties: the first listed)
+    pub fn keeper(self, group: &[FileInfo]) -> Option<usize> {
+        let indexed = group.iter().enumerate();
+        match self {
+            // max_by_key returns the last of equal maxima; reversed so ties go to the first
+            KeepRule::Newest => indexed.rev().max_by_key(|(_, f)| f.modified_timestamp),
+            KeepRule::Oldest => indexed.min_by_key(|(_, f)| f.modified_timestamp),
+            KeepRule::ShortestPath => indexed.min_by_key(|(_, f)| f.absolute_path().chars().count()),
+        }
+        .map(|(idx, _)| idx)
+    }
+}
+
+/// Files with the same name (full_name, in the configured Unicode form), in groups of two or
+/// more, by name
+pub fn by_name(files: &[FileInfo]) -> Vec<Vec<FileInfo>> {
+    let mut groups: HashMap<String, Vec<FileInfo>> = HashMap::new();
+    for file in files.iter().filter(|f| !f.is_dir) {
+        groups.entry(file.normalized_name().into_owned()).or_default().push(file.clone());
+    }
+    let mut groups: Vec<(String, Vec<FileInfo>)> = groups.into_iter().filter(|(_, members)| members.len() > 1).collect();
+    groups.sort_by(|a, b| a.0.cmp(&b.0));
+    groups.into_iter().map(|(_, members)| members).collect()
+}
+
+/// Files of numbered groups (path -> (group id, group size)), in group order
+pub fn by_group(files: &[FileInfo], groups: &HashMap<String, (usize, usize)>) -> Vec<Vec<FileInfo>> {
+    let count = groups.values().map(|&(group, _)| group + 1).max().unwrap_or(0);
+    let mut result: Vec<Vec<FileInfo>> = vec![Vec::new(); count];
+    for file in files {
+        if let Some(&(group, _)) = groups.get(file.absolute_path()) {
+            result[group].push(file.clone());
+        }
+    }
+    result.retain(|members| members.len() > 1);
+    result
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// One result per operation, or an error if the helper could not run (e.g. UAC declined)
+pub type ElevatedResults = Result<Vec<Result<(), String>>, String>;
+
+/// File operation to retry in an elevated (administrator) helper process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ElevatedOp {
+    Delete { path: PathBuf },
+    /// Rename or move (copy + delete across volumes)
+    Move { from: PathBuf, to: PathBuf },
+    Copy { from: PathBuf, to: PathBuf },
+}
+
+impl ElevatedOp {
+    /// File the operation acts on
+    pub fn path(&self) -> &Path {
+        match self {
+            ElevatedOp::Delete { path } => path,
+            ElevatedOp::Move { from, .. } | ElevatedOp::Copy { from, .. } => from,
+        }
+    }
+
+    /// Every path the operation reads or writes
+    pub fn paths(&self) -> Vec<&Path> {
+        match self {
+            ElevatedOp::Delete { path } => vec![path],
+            ElevatedOp::Move { from, to } | ElevatedOp::Copy { from, to } => vec![from, to],
+        }
+    }
+
+    fn run(&self) -> Result<(), String> {
+        let result = match self {
+            ElevatedOp::Delete { path } => std::fs::remove_file(path),
+            ElevatedOp::Move { from, to } => std::fs::rename(from, to).or_else(|_| {
+                std::fs::copy(from, to)?;
+                std::fs::remove_file(from)
+            }),
+            ElevatedOp::Copy { from, to } => std::fs::copy(from, to).map(|_| ()),
+        };
+        result.map_err(|e| e.to_string())
+    }
+}
+
+/// Whether "Retry as administrator" can be offered on this platform
+pub fn is_supported() -> bool {
+    cfg!(windows)
+}
+
+/// Whether an I/O error is worth retrying elevated
+pub fn is_access_denied(error: &std::io::Error) -> bool {
+    error.kind() == std::io::ErrorKind::PermissionDenied
+}
+
+/// Helper entry point (`--run-elevated-ops <file>`): run the operations listed in the file
+/// and write one result per operation to `<file>.result`. Nothing runs if `check` refuses any
+/// of their paths.
+pub fn run_ops_file(ops_path: &Path, check: &dyn Fn(&Path) -> Result<(), String>) -> Result<(), Box<dyn std::error::Error>> {
+    let ops: Vec<ElevatedOp> = serde_json::from_str(&std::fs::read_to_string(ops_path)?)?;
+    for path in ops.iter().flat_map(ElevatedOp::paths) {
+        check(path)?;
+    }
+    let results: Vec<Result<(), String>> = ops.iter().map(ElevatedOp::run).collect();
+    std::fs::write(result_path(ops_path), serde_json::to_string(&results)?)?;
+    Ok(())
+}
+
+fn result_path(ops_path: &Path) -> PathBuf {
+    let mut name = ops_path.as_os_str().to_owned();
+    name.push(".result");
+    PathBuf::from(name)
+}
+
+/// Run operations in an elevated copy of this executable (shows the UAC prompt) and wait for it
+#[cfg(windows)]
+pub fn run_elevated(ops: &[ElevatedOp]) -> ElevatedResults {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let ops_path = std::env::temp_dir().join(format!("file-lister-elevated-{}.json", std::process::id()));
+    let results_path = result_path(&ops_path);
+    let _ = std::fs::remove_file(&results_path);
+    std::fs::write(&ops_path, serde_json::to_string(ops).map_err(|e| e.to_string())?)
+        .map_err(|e| format!("Failed to write operations file: {}", e))?;
+
+    // PowerShell single-quoted strings escape ' as ''
+    let quote = |text: &str| format!("'{}'", text.replace('\'', "''"));
+    let script = format!(
+        "Start-Process -FilePath {} -ArgumentList {} -Verb RunAs -Wait -WindowStyle Hidden",
+        quote(&exe.to_string_lossy()),
+        quote(&format!("--run-elevated-ops \"{}\"", ops_path.display())),
+    );
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to run PowerShell: {}", e));
+    let _ = std::fs::remove_file(&ops_path);
+    let output = output?;
+
+    let results = std::fs::read_to_string(&results_path);
+    let _ = std::fs::remove_file(&results_path);
+    match results {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| e.to_string()),
+        // No results: the UAC prompt was declined (or the helper failed)
+        Err(_) if !output.status.success() => Err("Elevation was cancelled".to_string()),
+        Err(e) => Err(format!("Elevated helper produced no results: {}", e)),
+    }
+}
+
+/// Elevation is only implemented for Windows (UAC)
+#[cfg(not(windows))]
+pub fn run_elevated(_ops: &[ElevatedOp]) -> ElevatedResults {
+    Err("Running as administrator is only supported on Windows".to_string())
+}
@@ -0,0 +1,244 @@
+use crate::file_scanner::FileInfo;
+use crate::priority;
+use crate::renames::RenameOp;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// Formats stored in a ZIP container (their files start with the ZIP signature)
+const ZIP_BASED: [&str; 32] = [
+    "docx", "docm", "dotx", "xlsx", "xlsm", "xltx", "pptx", "pptm", "potx", "ppsx", "odt", "ods", "odp", "odg",
+    "epub", "jar", "war", "apk", "aab", "ipa", "xpi", "cbz", "kmz", "vsix", "nupkg", "whl", "appx", "msix", "3mf",
+    "xps", "oxps", "sketch",
+];
+
+/// Which extension fixes to apply
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExtensionRules {
+    /// "JPG" -> "jpg"
+    pub lowercase: bool,
+    /// Canonical spelling: "jpeg" -> "jpg", "tiff" -> "tif", ...
+    pub canonical: bool,
+    /// Add an extension detected from the file's magic bytes when it has none
+    pub add_missing: bool,
+    /// Replace an extension that disagrees with the magic bytes (".jpg" that is a PNG)
+    pub fix_mismatched: bool,
+}
+
+impl Default for ExtensionRules {
+    fn default() -> Self {
+        Self {
+            lowercase: true,
+            canonical: true,
+            add_missing: true,
+            fix_mismatched: false,
+        }
+    }
+}
+
+impl ExtensionRules {
+    /// Only append detected extensions to files that have none
+    pub fn missing_only() -> Self {
+        Self {
+            lowercase: false,
+            canonical: false,
+            add_missing: true,
+            fix_mismatched: false,
+        }
+    }
+
+    /// Only correct extensions that disagree with the content
+    pub fn mismatched_only() -> Self {
+        Self {
+            lowercase: false,
+            canonical: false,
+            add_missing: false,
+            fix_mismatched: true,
+        }
+    }
+}
+
+/// Message from the background extension check
+pub enum CheckMessage {
+    Progress { done: usize, total: usize },
+    /// (absolute path, extension of the detected format) of files whose extension disagrees
+    Finished(Vec<(String, &'static str)>),
+}
+
+/// Canonical spelling for extensions with common variants
+fn canonical_extension(extension: &str) -> Option<&'static str> {
+    match extension.to_lowercase().as_str() {
+        "jpeg" | "jpe" | "jfif" => Some("jpg"),
+        "tiff" => Some("tif"),
+        "htm" => Some("html"),
+        "mpeg" => Some("mpg"),
+        "yml" => Some("yaml"),
+        "markdown" => Some("md"),
+        _ => None,
+    }
+}
+
+/// Detect a file type from its first bytes
+pub fn detect_extension(path: &Path) -> Option<&'static str> {
+    let mut header = [0u8; 16];
+    let mut file = File::open(path).ok()?;
+    let read = file.read(&mut header).ok()?;
+    let header = &header[..read];
+
+    let extension = match header {
+        [0xFF, 0xD8, 0xFF, ..] => "jpg",
+        [0x89, b'P', b'N', b'G', ..] => "png",
+        [b'G', b'I', b'F', b'8', ..] => "gif",
+        // The reserved header fields are zero, so text starting with "BM" isn't taken for a bitmap
+        [b'B', b'M', _, _, _, _, 0, 0, 0, 0, ..] => "bmp",
+        [b'%', b'P', b'D', b'F', ..] => "pdf",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "webp",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => "wav",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'A', b'V', b'I', b' ', ..] => "avi",
+        [_, _, _, _, b'f', b't', b'y', b'p', b'q', b't', ..] => "mov",
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => "mp4",
+        [0x1A, 0x45, 0xDF, 0xA3, ..] => "mkv",
+        [b'I', b'D', b'3', ..] | [0xFF, 0xFB, ..] | [0xFF, 0xF3, ..] => "mp3",
+        [b'O', b'g', b'g', b'S', ..] => "ogg",
+        [b'f', b'L', b'a', b'C', ..] => "flac",
+        [b'P', b'K', 0x03, 0x04, ..] => "zip",
+        [b'R', b'a', b'r', b'!', ..] => "rar",
+        [b'7', b'z', 0xBC, 0xAF, ..] => "7z",
+        [0x1F, 0x8B, ..] => "gz",
+        [0x00, 0x00, 0x01, 0x00, ..] => "ico",
+        [b'M', b'Z', ..] => "exe",
+        _ => return None,
+    };
+    Some(extension)
+}
+
+/// Whether files with `extension` may start with the signature of `detected`
+/// (an .xlsx is a ZIP, an .m4a an MP4, a .webm a Matroska file)
+fn compatible(extension: &str, detected: &str) -> bool {
+    let extension = extension.to_lowercase();
+    let extension = canonical_extension(&extension).unwrap_or(&extension);
+    let related: &[&str] = match detected {
+        "zip" => &ZIP_BASED,
+        "mp4" => &["m4a", "m4v", "m4b", "m4p", "3gp", "3g2", "mov", "heic", "heif", "avif", "f4v"],
+        "mov" => &["mp4", "m4v", "qt"],
+        "mkv" => &["webm", "mka", "mks", "mk3d"],
+        "ogg" => &["oga", "ogv", "opus", "spx"],
+        "gz" => &["tgz", "svgz"],
+        "rar" => &["cbr"],
+        "7z" => &["cb7"],
+        "mp3" => &["mp2", "mpga"],
+        "ico" => &["cur"],
+        "bmp" => &["dib"],
+        "exe" => &["dll", "sys", "scr", "com", "ocx", "cpl", "drv", "efi", "mui", "ax"],
+        _ => &[],
+    };
+    extension == detected || related.contains(&extension)
+}
+
+/// Extension of the detected format if the file's extension disagrees with its content
+/// (files without extension, folders and online-only cloud files are not read)
+pub fn mismatch(file: &FileInfo) -> Option<&'static str> {
+    if file.is_dir || file.is_cloud_placeholder || file.extension().is_empty() {
+        return None;
+    }
+    detect_extension(Path::new(file.absolute_path())).filter(|detected| !compatible(file.extension(), detected))
+}
+
+/// Extension of the detected format for a file without extension (folders and online-only
+/// cloud files are not read)
+pub fn suggestion(file: &FileInfo) -> Option<&'static str> {
+    if file.is_dir || file.is_cloud_placeholder || !file.extension().is_empty() {
+        return None;
+    }
+    detect_extension(Path::new(file.absolute_path()))
+}
+
+/// Detect the format of the files without extension on a background thread: (absolute path,
+/// suggested extension) of those recognized
+pub fn spawn_suggestions(files: Vec<FileInfo>) -> Receiver<Vec<(String, &'static str)>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        priority::enter_background();
+        let suggestions = files
+            .iter()
+            .filter(|file| file.extension().is_empty())
+            .filter_map(|file| {
+                priority::throttle();
+                suggestion(file).map(|extension| (file.absolute_path().to_string(), extension))
+            })
+            .collect();
+        let _ = tx.send(suggestions);
+    });
+    rx
+}
+
+/// Check the extensions of `files` against their content on a background thread
+pub fn spawn_check(files: Vec<FileInfo>) -> Receiver<CheckMessage> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        priority::enter_background();
+        let mut mismatches = Vec::new();
+        for (done, file) in files.iter().enumerate() {
+            if done % 256 == 0 {
+                let _ = tx.send(CheckMessage::Progress { done, total: files.len() });
+            }
+            priority::throttle();
+            if let Some(detected) = mismatch(file) {
+                mismatches.push((file.absolute_path().to_string(), detected));
+            }
+        }
+        let _ = tx.send(CheckMessage::Finished(mismatches));
+    });
+    rx
+}
+
+/// New file name for a file under the rules (None if unchanged)
+pub fn normalized_name(file: &FileInfo, rules: &ExtensionRules) -> Option<String> {
+    let path = Path::new(file.absolute_path());
+
+    let new_extension = if file.extension().is_empty() {
+        // Reading the header would download online-only cloud files
+        if !rules.add_missing || file.is_cloud_placeholder {
+            return None;
+        }
+        detect_extension(path)?.to_string()
+    } else if let Some(detected) = rules.fix_mismatched.then(|| mismatch(file)).flatten() {
+        detected.to_string()
+    } else {
+        let mut extension = file.extension().to_string();
+        if rules.canonical {
+            if let Some(canonical) = canonical_extension(&extension) {
+                // Keep the case style of the original unless lowercasing
+                extension = if extension.chars().all(|c| c.is_uppercase()) && !rules.lowercase {
+                    canonical.to_uppercase()
+                } else {
+                    canonical.to_string()
+                };
+            }
+        }
+        if rules.lowercase {
+            extension = extension.to_lowercase();
+        }
+        if extension == file.extension() {
+            return None;
+        }
+        extension
+    };
+
+    Some(format!("{}.{}", file.name(), new_extension))
+}
+
+/// Plan renames for a set of files (check them with renames::simulate)
+pub fn plan_renames(files: &[FileInfo], rules: &ExtensionRules) -> Vec<RenameOp> {
+    files
+        .iter()
+        .filter_map(|file| {
+            let new_name = normalized_name(file, rules)?;
+            let from = PathBuf::from(file.absolute_path());
+            let to = from.with_file_name(new_name);
+            Some(RenameOp { from, to })
+        })
+        .collect()
+}
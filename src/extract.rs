@@ -0,0 +1,168 @@
+use crate::document_parser::{self, AUDIO_EXTENSIONS, CODE_EXTENSIONS};
+use crate::file_scanner::{self, FileInfo};
+use crate::thumbnail::{ThumbnailGenerator, ThumbnailKind, ThumbnailOptions};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Serialize;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+/// Thumbnail as a base64 PNG
+#[derive(Serialize)]
+pub struct ExtractedThumbnail {
+    pub width: usize,
+    pub height: usize,
+    pub png_base64: String,
+}
+
+/// First rows of a spreadsheet or CSV file
+#[derive(Serialize)]
+pub struct ExtractedTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub sheet: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ExtractedAudio {
+    pub duration_secs: Option<f64>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u8>,
+    pub codec: Option<String>,
+    pub bitrate: Option<u32>,
+}
+
+/// What the preview pipeline knows about one file; a step that fails is listed in `errors`
+/// and the others are still reported
+#[derive(Serialize)]
+pub struct Extraction {
+    pub path: String,
+    /// Same fields as the JSON export; None if the path doesn't exist
+    pub metadata: Option<FileInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<ExtractedThumbnail>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<ExtractedTable>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio: Option<ExtractedAudio>,
+    pub errors: Vec<String>,
+}
+
+impl Extraction {
+    fn new(path: &Path) -> Self {
+        Self {
+            path: path.display().to_string(),
+            metadata: None,
+            thumbnail: None,
+            text: None,
+            table: None,
+            audio: None,
+            errors: Vec::new(),
+        }
+    }
+
+    fn failed(path: &Path, error: String) -> Self {
+        let mut extraction = Self::new(path);
+        extraction.errors.push(error);
+        extraction
+    }
+}
+
+/// Metadata, thumbnail and text (or table, or audio properties) of a file, as shown in the preview.
+/// Online-only cloud files are only read with `read_cloud_files` (reading downloads them).
+pub fn extract(path: &Path, generator: &mut ThumbnailGenerator, options: &ThumbnailOptions, read_cloud_files: bool) -> Extraction {
+    if !path.exists() {
+        return Extraction::failed(path, format!("{} not found", path.display()));
+    }
+    let file = file_scanner::file_info(path, path.parent().unwrap_or(path));
+    let mut extraction = Extraction::new(path);
+    if file.is_cloud_placeholder && !read_cloud_files {
+        extraction.errors.push("Online-only file: content not downloaded".to_string());
+    } else if !file.is_dir {
+        let extension = file.extension().to_lowercase();
+        if let Some(kind) = ThumbnailKind::from_extension(&extension) {
+            match generator
+                .generate(file.absolute_path(), kind, options)
+                .and_then(|thumbnail| Ok((thumbnail.to_png()?, thumbnail)))
+            {
+                Ok((png, thumbnail)) => {
+                    extraction.thumbnail = Some(ExtractedThumbnail {
+                        width: thumbnail.width,
+                        height: thumbnail.height,
+                        png_base64: BASE64.encode(png),
+                    })
+                }
+                Err(e) => extraction.errors.push(format!("Thumbnail: {}", e)),
+            }
+        }
+        extract_content(path, &extension, &mut extraction);
+    }
+    extraction.metadata = Some(file);
+    extraction
+}
+
+/// Text, table or audio properties by extension (the same readers as the document preview)
+fn extract_content(path: &Path, extension: &str, extraction: &mut Extraction) {
+    let result = if AUDIO_EXTENSIONS.contains(&extension) {
+        document_parser::extract_audio_metadata(path).map(|meta| {
+            extraction.audio = Some(ExtractedAudio {
+                duration_secs: meta.duration_secs,
+                sample_rate: meta.sample_rate,
+                channels: meta.channels,
+                codec: meta.codec,
+                bitrate: meta.bitrate,
+            })
+        })
+    } else if CODE_EXTENSIONS.contains(&extension) {
+        document_parser::extract_code_text(path).map(|text| extraction.text = Some(text))
+    } else {
+        match extension {
+            "docx" => document_parser::extract_docx_text(path).map(|text| extraction.text = Some(text)),
+            "txt" => document_parser::extract_txt_text(path).map(|text| extraction.text = Some(text)),
+            "xlsx" | "xls" => document_parser::extract_xlsx_table(path).map(|(headers, rows, sheet)| {
+                extraction.table = Some(ExtractedTable { headers, rows, sheet })
+            }),
+            "csv" => document_parser::extract_csv_table(path).map(|(headers, rows)| {
+                extraction.table = Some(ExtractedTable {
+                    headers,
+                    rows,
+                    sheet: None,
+                })
+            }),
+            _ => Ok(()),
+        }
+    };
+    if let Err(e) = result {
+        extraction.errors.push(e);
+    }
+}
+
+/// Answer requests until the input ends: one path per line in, one JSON object per line out.
+/// Pdfium and FFmpeg are looked up once for the whole session.
+pub fn serve(
+    input: impl BufRead,
+    mut output: impl Write,
+    options: &ThumbnailOptions,
+    read_cloud_files: bool,
+    check: &dyn Fn(&Path) -> Result<(), String>,
+) -> io::Result<()> {
+    let mut generator = ThumbnailGenerator::default();
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim_end_matches('\r');
+        if line.is_empty() {
+            continue;
+        }
+        let path = Path::new(line);
+        let extraction = match check(path) {
+            Ok(()) => extract(path, &mut generator, options, read_cloud_files),
+            Err(e) => Extraction::failed(path, e),
+        };
+        serde_json::to_writer(&mut output, &extraction)?;
+        writeln!(output)?;
+        output.flush()?;
+    }
+    Ok(())
+}
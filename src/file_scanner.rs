@@ -1,64 +1,416 @@
-use serde::Serialize;
+use crate::gitignore::IgnoreRules;
+use crate::priority;
+use crate::retry;
+use crate::text_match;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
 use std::time::SystemTime;
 
-#[derive(Debug, Clone, Serialize)]
+/// A listed file. To keep multi-million-file scans small, only two strings are stored per file
+/// (the absolute and the listed path); name, stem and extension are slices of the listed path
+/// and the source folder name is shared by all files of a folder.
+#[derive(Debug, Clone)]
 pub struct FileInfo {
-    pub name: String,
-    pub extension: String,
-    pub full_name: String,
-    pub relative_path: String,
-    pub absolute_path: String,
+    absolute_path: Box<str>,
+    /// Path below the scanned folder, prefixed with "[folder]/" for multi-folder scans
+    relative_path: Box<str>,
+    /// Source folder name (for multi-folder scanning)
+    source_folder: Arc<str>,
     pub file_size: u64,
     /// Modification timestamp (seconds since UNIX epoch)
     pub modified_timestamp: i64,
-    /// Source folder name (for multi-folder scanning)
-    #[serde(skip_serializing_if = "String::is_empty")]
-    pub source_folder: String,
+    /// Last access timestamp (only meaningful where the volume updates access times, see access_times)
+    pub accessed_timestamp: Option<i64>,
+    /// Creation timestamp (not available on every file system)
+    pub created_timestamp: Option<i64>,
+    /// Online-only cloud placeholder (reading it downloads the content)
+    pub is_cloud_placeholder: bool,
+    /// Owner account ("DOMAIN\user", or the SID if it can't be resolved); filled by owner::resolve_owners
+    pub owner: Option<String>,
+    /// Owner SID no longer maps to an account (deleted user)
+    pub owner_orphaned: bool,
+    /// Folder row (see folder_rows): file_size is the total below it, modified the newest file's time
+    pub is_dir: bool,
+    /// Folder rows: files and subfolders below it, at any depth
+    pub item_count: u32,
+    /// Content checksum for the Hash column (see hashing::spawn_content_hashing)
+    pub hash: Option<Arc<str>>,
+    /// Bytes allocated on disk (whole clusters; less than the size for sparse, compressed or
+    /// online-only files); None where the platform doesn't report it
+    pub allocated_size: Option<u64>,
+    /// Names (hard links) the file has on its volume
+    pub link_count: u32,
+    /// Volume and file id of hard-linked files (link_count > 1), so they can be counted once
+    pub file_id: Option<FileId>,
+    /// Lines of a text or code file (see text_stats::spawn_text_counting)
+    pub line_count: Option<u64>,
+    /// Words of a prose file (.txt, .md, ...)
+    pub word_count: Option<u64>,
+}
+
+/// Identity of a file on its volume (device and inode, or volume serial and NTFS file index)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileId {
+    pub volume: u64,
+    pub index: u64,
 }
 
-/// Check if a timestamp (seconds since UNIX epoch) is from today
-pub fn is_today(timestamp: i64) -> bool {
-    use std::time::{Duration, UNIX_EPOCH};
+impl FileInfo {
+    /// Canonical full path
+    pub fn absolute_path(&self) -> &str {
+        &self.absolute_path
+    }
 
-    let file_time = UNIX_EPOCH + Duration::from_secs(timestamp as u64);
-    let now = SystemTime::now();
+    /// Path as listed: below the scanned folder, with the "[folder]/" prefix of multi-folder scans
+    pub fn relative_path(&self) -> &str {
+        &self.relative_path
+    }
 
-    // Get start of today (midnight)
-    if let Ok(now_duration) = now.duration_since(UNIX_EPOCH) {
-        let now_secs = now_duration.as_secs();
-        // Calculate seconds since midnight (86400 seconds per day)
-        let secs_since_midnight = now_secs % 86400;
-        let today_start = now_secs - secs_since_midnight;
+    /// Name of the scanned folder the file was found in (empty for single-folder scans)
+    pub fn source_folder(&self) -> &str {
+        &self.source_folder
+    }
 
-        if let Ok(file_duration) = file_time.duration_since(UNIX_EPOCH) {
-            let file_secs = file_duration.as_secs();
-            return file_secs >= today_start && file_secs < today_start + 86400;
+    /// File name with extension (the last component of the listed path)
+    pub fn full_name(&self) -> &str {
+        Path::new(&*self.relative_path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+    }
+
+    /// File name with extension in the configured Unicode form, for comparing names
+    pub fn normalized_name(&self) -> Cow<'_, str> {
+        text_match::normalize_name(self.full_name())
+    }
+
+    /// File name without extension (the whole name of a folder)
+    pub fn name(&self) -> &str {
+        if self.is_dir {
+            return self.full_name();
+        }
+        Path::new(&*self.relative_path)
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+    }
+
+    /// Extension without the dot (empty for folders)
+    pub fn extension(&self) -> &str {
+        if self.is_dir {
+            return "";
+        }
+        Path::new(&*self.relative_path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+    }
+
+    /// Set the listed path and the (shared) source folder name
+    pub fn set_location(&mut self, relative_path: &str, source_folder: &Arc<str>) {
+        self.relative_path = relative_path.into();
+        self.source_folder = source_folder.clone();
+    }
+}
+
+/// Same fields as before the storage was compacted, so serialized listings keep their shape
+impl Serialize for FileInfo {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("FileInfo", 20)?;
+        state.serialize_field("name", self.name())?;
+        state.serialize_field("extension", self.extension())?;
+        state.serialize_field("full_name", self.full_name())?;
+        state.serialize_field("relative_path", self.relative_path())?;
+        state.serialize_field("absolute_path", self.absolute_path())?;
+        state.serialize_field("file_size", &self.file_size)?;
+        state.serialize_field("modified_timestamp", &self.modified_timestamp)?;
+        state.serialize_field("accessed_timestamp", &self.accessed_timestamp)?;
+        state.serialize_field("created_timestamp", &self.created_timestamp)?;
+        if self.source_folder.is_empty() {
+            state.skip_field("source_folder")?;
+        } else {
+            state.serialize_field("source_folder", self.source_folder())?;
+        }
+        state.serialize_field("is_cloud_placeholder", &self.is_cloud_placeholder)?;
+        state.serialize_field("owner", &self.owner)?;
+        state.serialize_field("owner_orphaned", &self.owner_orphaned)?;
+        if self.is_dir {
+            state.serialize_field("is_dir", &self.is_dir)?;
+            state.serialize_field("item_count", &self.item_count)?;
+        } else {
+            state.skip_field("is_dir")?;
+            state.skip_field("item_count")?;
+        }
+        match &self.hash {
+            Some(hash) => state.serialize_field("hash", &**hash)?,
+            None => state.skip_field("hash")?,
+        }
+        state.serialize_field("allocated_size", &self.allocated_size)?;
+        if self.link_count > 1 {
+            state.serialize_field("link_count", &self.link_count)?;
+        } else {
+            state.skip_field("link_count")?;
+        }
+        for (key, count) in [("line_count", self.line_count), ("word_count", self.word_count)] {
+            match count {
+                Some(count) => state.serialize_field(key, &count)?,
+                None => state.skip_field(key)?,
+            }
+        }
+        state.end()
+    }
+}
+
+/// Start (seconds since UNIX epoch) of the local calendar day `days_ago` days before today
+/// (-1: tomorrow). Where a clock change skips midnight, the day starts at the first valid time.
+fn local_day_start(days_ago: i64) -> i64 {
+    use chrono::{Duration, Local, TimeZone};
+
+    let midnight = (Local::now().date_naive() - Duration::days(days_ago)).and_time(chrono::NaiveTime::MIN);
+    (0..=3)
+        .find_map(|hours| Local.from_local_datetime(&(midnight + Duration::hours(hours))).earliest())
+        .map_or_else(|| midnight.and_utc().timestamp(), |start| start.timestamp())
+}
+
+/// Local calendar days between `timestamp` and today (0: today or later, 1: yesterday)
+pub fn local_days_ago(timestamp: i64) -> i64 {
+    let today = local_day_start(0);
+    if timestamp >= today {
+        0
+    } else if timestamp >= local_day_start(1) {
+        1
+    } else {
+        (today - timestamp - 1) / 86400 + 1
+    }
+}
+
+/// Quick filters on the modification date, by calendar days of the local time zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecentDays {
+    Today,
+    Yesterday,
+    /// Today and the six days before
+    LastWeek,
+}
+
+impl RecentDays {
+    pub const ALL: [RecentDays; 3] = [RecentDays::Today, RecentDays::Yesterday, RecentDays::LastWeek];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RecentDays::Today => "Today",
+            RecentDays::Yesterday => "Yesterday",
+            RecentDays::LastWeek => "Last 7 days",
+        }
+    }
+
+    /// Start and end (exclusive) of the range as of now; computed once per filter pass
+    pub fn bounds(self) -> (i64, i64) {
+        match self {
+            RecentDays::Today => (local_day_start(0), local_day_start(-1)),
+            RecentDays::Yesterday => (local_day_start(1), local_day_start(0)),
+            RecentDays::LastWeek => (local_day_start(6), local_day_start(-1)),
+        }
+    }
+}
+
+/// Unit system for displayed file sizes
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum UnitSystem {
+    /// Powers of 1024 (KiB, MiB, GiB)
+    Binary,
+    /// Powers of 1000 (kB, MB, GB)
+    Si,
+}
+
+/// Unit used for every displayed size
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SizeUnit {
+    /// Largest unit that keeps the value >= 1
+    Auto,
+    Bytes,
+    Kilo,
+    Mega,
+    Giga,
+    Tera,
+}
+
+impl SizeUnit {
+    pub const ALL: [SizeUnit; 6] =
+        [SizeUnit::Auto, SizeUnit::Bytes, SizeUnit::Kilo, SizeUnit::Mega, SizeUnit::Giga, SizeUnit::Tera];
+
+    pub fn label(self, system: UnitSystem) -> &'static str {
+        match (self, system) {
+            (SizeUnit::Auto, _) => "Auto",
+            (SizeUnit::Bytes, _) => "B",
+            (SizeUnit::Kilo, UnitSystem::Binary) => "KiB",
+            (SizeUnit::Kilo, UnitSystem::Si) => "kB",
+            (SizeUnit::Mega, UnitSystem::Binary) => "MiB",
+            (SizeUnit::Mega, UnitSystem::Si) => "MB",
+            (SizeUnit::Giga, UnitSystem::Binary) => "GiB",
+            (SizeUnit::Giga, UnitSystem::Si) => "GB",
+            (SizeUnit::Tera, UnitSystem::Binary) => "TiB",
+            (SizeUnit::Tera, UnitSystem::Si) => "TB",
+        }
+    }
+
+    fn exponent(self) -> i32 {
+        match self {
+            SizeUnit::Auto | SizeUnit::Bytes => 0,
+            SizeUnit::Kilo => 1,
+            SizeUnit::Mega => 2,
+            SizeUnit::Giga => 3,
+            SizeUnit::Tera => 4,
+        }
+    }
+}
+
+/// How file sizes are displayed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SizeFormat {
+    pub system: UnitSystem,
+    pub unit: SizeUnit,
+    /// Group digits with the locale's thousands separator
+    pub thousands_separators: bool,
+}
+
+impl Default for SizeFormat {
+    fn default() -> Self {
+        Self {
+            system: UnitSystem::Binary,
+            unit: SizeUnit::Auto,
+            thousands_separators: false,
         }
     }
-    false
 }
 
 /// Format file size to human readable string
-pub fn format_size(size: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-
-    if size >= GB {
-        format!("{:.2} GB", size as f64 / GB as f64)
-    } else if size >= MB {
-        format!("{:.2} MB", size as f64 / MB as f64)
-    } else if size >= KB {
-        format!("{:.2} KB", size as f64 / KB as f64)
+pub fn format_size(size: u64, format: &SizeFormat) -> String {
+    let base: f64 = match format.system {
+        UnitSystem::Binary => 1024.0,
+        UnitSystem::Si => 1000.0,
+    };
+
+    let unit = match format.unit {
+        SizeUnit::Auto => [SizeUnit::Tera, SizeUnit::Giga, SizeUnit::Mega, SizeUnit::Kilo]
+            .into_iter()
+            .find(|u| size as f64 >= base.powi(u.exponent()))
+            .unwrap_or(SizeUnit::Bytes),
+        unit => unit,
+    };
+
+    let separator = format.thousands_separators.then(locale_thousands_separator);
+    let label = unit.label(format.system);
+    if unit == SizeUnit::Bytes {
+        format!("{} {}", group_digits(&size.to_string(), separator), label)
     } else {
-        format!("{} B", size)
+        let value = format!("{:.2}", size as f64 / base.powi(unit.exponent()));
+        let (integer, fraction) = value.split_once('.').unwrap_or((&value, "00"));
+        // Locales that group with '.' use ',' as the decimal mark
+        let decimal = if separator == Some('.') { ',' } else { '.' };
+        format!("{}{}{} {}", group_digits(integer, separator), decimal, fraction, label)
+    }
+}
+
+/// Exact byte count ("1,234,567 bytes") for tooltips
+pub fn format_bytes(size: u64, format: &SizeFormat) -> String {
+    let separator = format.thousands_separators.then(locale_thousands_separator);
+    format!("{} bytes", group_digits(&size.to_string(), separator))
+}
+
+/// Insert a separator every three digits
+fn group_digits(digits: &str, separator: Option<char>) -> String {
+    let Some(separator) = separator else {
+        return digits.to_string();
+    };
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(c);
     }
+    grouped
 }
 
-/// Format timestamp to human readable date string (YYYY-MM-DD HH:MM)
-pub fn format_date(timestamp: i64) -> String {
+/// Thousands separator of the user's locale (detected once)
+pub fn locale_thousands_separator() -> char {
+    static SEPARATOR: OnceLock<char> = OnceLock::new();
+    *SEPARATOR.get_or_init(detect_thousands_separator)
+}
+
+/// Read the digit grouping symbol from the regional settings
+#[cfg(windows)]
+fn detect_thousands_separator() -> char {
+    std::process::Command::new("reg")
+        .args(["query", r"HKCU\Control Panel\International", "/v", "sThousand"])
+        .output()
+        .ok()
+        .and_then(|output| {
+            // Line format: "    sThousand    REG_SZ    ,"
+            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+            let line = stdout.lines().find(|l| l.contains("sThousand"))?.to_string();
+            let value = line.split("REG_SZ").nth(1)?;
+            // Columns are separated by 4 spaces; the value itself may be a (no-break) space
+            value.strip_prefix("    ").unwrap_or(value).chars().next()
+        })
+        .unwrap_or(',')
+}
+
+/// Guess the digit grouping symbol from the locale environment variables
+#[cfg(not(windows))]
+fn detect_thousands_separator() -> char {
+    let locale = ["LC_ALL", "LC_NUMERIC", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default();
+    let language = locale.split(['_', '.', '-']).next().unwrap_or("");
+    match language {
+        "de" if locale.contains("CH") => '\'',
+        "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" => '.',
+        "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "nb" | "no" | "fi" | "uk" | "hu" | "bg" => '\u{a0}',
+        _ => ',',
+    }
+}
+
+/// Era of the displayed and exported years
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Calendar {
+    #[default]
+    Gregorian,
+    /// Thai Buddhist Era (พ.ศ.): Gregorian year + 543, same months and days
+    ThaiBuddhist,
+}
+
+impl Calendar {
+    pub const ALL: [Calendar; 2] = [Calendar::Gregorian, Calendar::ThaiBuddhist];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Calendar::Gregorian => "Gregorian (CE)",
+            Calendar::ThaiBuddhist => "Thai Buddhist Era (พ.ศ., CE + 543)",
+        }
+    }
+
+    fn year_offset(self) -> i64 {
+        match self {
+            Calendar::Gregorian => 0,
+            Calendar::ThaiBuddhist => 543,
+        }
+    }
+}
+
+/// Format timestamp to human readable date string (YYYY-MM-DD HH:MM, year in the given era)
+pub fn format_date(timestamp: i64, calendar: Calendar) -> String {
     if timestamp == 0 {
         return String::from("-");
     }
@@ -102,16 +454,106 @@ pub fn format_date(timestamp: i64) -> String {
 
     let day = remaining_days + 1;
 
-    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, hours, minutes)
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year + calendar.year_offset(), month, day, hours, minutes)
 }
 
 fn is_leap_year(year: i64) -> bool {
     (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0)
 }
 
-pub fn scan_folder(path: &Path, recursive: bool) -> Result<Vec<FileInfo>, std::io::Error> {
-    let mut files = Vec::new();
+/// Which files and folders a scan leaves out
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScanFilter {
+    /// List dotfiles (Unix) and files with the Hidden or System attribute (Windows)
+    pub include_hidden: bool,
+    /// Leave out files and folders matched by .gitignore/.ignore files
+    pub respect_gitignore: bool,
+}
+
+impl ScanFilter {
+    /// .gitignore rules for a scanned folder, if they are respected
+    pub fn ignore_rules(&self, folder: &Path) -> Option<IgnoreRules> {
+        self.respect_gitignore.then(|| IgnoreRules::for_root(folder))
+    }
+
+    /// True if a walk leaves an entry out (a folder is not entered either).
+    /// `metadata` is only read where hidden is an attribute (Windows, macOS).
+    pub fn skips(
+        &self,
+        rules: Option<&IgnoreRules>,
+        path: &Path,
+        is_dir: bool,
+        metadata: impl FnOnce() -> Option<fs::Metadata>,
+    ) -> bool {
+        (!self.include_hidden && is_hidden(path, metadata)) || rules.is_some_and(|rules| rules.is_ignored(path, is_dir))
+    }
+}
+
+/// A folder or file a scan could not read
+#[derive(Debug, Clone)]
+pub struct ScanError {
+    pub path: PathBuf,
+    pub error: String,
+    /// A folder that could not be listed (its contents are missing), else a single entry
+    pub is_folder: bool,
+}
+
+/// What a scan had to leave out; the rest of the scan goes on without it
+#[derive(Debug, Clone, Default)]
+pub struct ScanReport {
+    pub errors: Vec<ScanError>,
+    /// Folder rows (item count 0) of the folders a recursive scan found empty: no entries at
+    /// all, or only empty folders. Hidden or ignored entries count as content. Deepest first
+    pub empty_folders: Vec<FileInfo>,
+}
+
+impl ScanReport {
+    /// No errors (empty folders are not a problem)
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    fn push(&mut self, path: &Path, error: &std::io::Error, is_folder: bool) {
+        self.errors.push(ScanError {
+            path: path.to_path_buf(),
+            error: describe_error(error),
+            is_folder,
+        });
+    }
+
+    /// "3 folders could not be read", "2 folders and 1 file could not be read"
+    pub fn summary(&self) -> String {
+        let folders = self.errors.iter().filter(|e| e.is_folder).count();
+        let files = self.errors.len() - folders;
+        let count = |n: usize, what: &str| format!("{} {}{}", n, what, if n == 1 { "" } else { "s" });
+        let what = match (folders, files) {
+            (0, _) => count(files, "file"),
+            (_, 0) => count(folders, "folder"),
+            _ => format!("{} and {}", count(folders, "folder"), count(files, "file")),
+        };
+        format!("{} could not be read", what)
+    }
+}
+
+/// The OS message, with the usual causes spelled out
+fn describe_error(error: &std::io::Error) -> String {
+    // ERROR_FILENAME_EXCED_RANGE / ENAMETOOLONG
+    let too_long = if cfg!(windows) { 206 } else { 36 };
+    match error.kind() {
+        std::io::ErrorKind::PermissionDenied => format!("Access denied ({})", error),
+        _ if error.raw_os_error() == Some(too_long) => format!("Path too long ({})", error),
+        _ => error.to_string(),
+    }
+}
+
+/// Scan one folder, leaving out what `filter` excludes.
+/// Fails if the folder itself can't be read; unreadable subfolders are left out.
+pub fn scan_folder(path: &Path, recursive: bool, filter: ScanFilter) -> Result<Vec<FileInfo>, std::io::Error> {
+    scan_folder_with_report(path, recursive, filter).map(|(files, _)| files)
+}
 
+/// Scan one folder; subfolders and entries that can't be read are listed in the report
+pub fn scan_folder_with_report(path: &Path, recursive: bool, filter: ScanFilter) -> Result<(Vec<FileInfo>, ScanReport), std::io::Error> {
     if !path.is_dir() {
         return Err(std::io::Error::new(
             std::io::ErrorKind::NotADirectory,
@@ -119,101 +561,446 @@ pub fn scan_folder(path: &Path, recursive: bool) -> Result<Vec<FileInfo>, std::i
         ));
     }
 
-    scan_folder_internal(path, path, recursive, &mut files)?;
+    let ignore = filter.ignore_rules(path);
+    let scanned = AtomicUsize::new(0);
+    let mut walk = Walk::new(path, recursive, filter, &scanned);
+    walk.folder(path, ignore.as_ref())?;
+    let Walk { mut files, report, .. } = walk;
 
-    // Sort alphabetically by relative path
-    files.sort_by(|a, b| a.relative_path.to_lowercase().cmp(&b.relative_path.to_lowercase()));
+    // Sort by relative path ("img2" before "img10" with natural sorting)
+    files.sort_by(|a, b| text_match::compare_natural(&a.relative_path, &b.relative_path));
+
+    Ok((files, report))
+}
 
-    Ok(files)
+/// Build FileInfo for a file, with relative_path taken from base_path
+pub fn file_info(path: &Path, base_path: &Path) -> FileInfo {
+    // Calculate relative path from base folder (just the name if the file is not below it);
+    // name and extension are read from its last component
+    let relative_path = path
+        .strip_prefix(base_path)
+        .ok()
+        .filter(|p| !p.as_os_str().is_empty())
+        .or_else(|| path.file_name().map(Path::new))
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    // Get absolute path
+    let absolute_path = path
+        .canonicalize()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_string_lossy().to_string());
+
+    // Get file metadata
+    let metadata = retry::with_retry(|| fs::metadata(path)).ok();
+    let file_size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+    let is_cloud_placeholder = metadata.as_ref().map(has_placeholder_attributes).unwrap_or(false)
+        || is_icloud_stub(path);
+
+    // Get modification, access and creation times as timestamps
+    let timestamp = |time: std::io::Result<SystemTime>| {
+        time.ok()
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+    };
+    let modified_timestamp = metadata.as_ref().and_then(|m| timestamp(m.modified())).unwrap_or(0);
+    let accessed_timestamp = metadata.as_ref().and_then(|m| timestamp(m.accessed()));
+    let created_timestamp = metadata.as_ref().and_then(|m| timestamp(m.created()));
+    let (allocated_size, link_count, file_id) = match &metadata {
+        Some(metadata) => disk_usage_of(path, metadata),
+        None => (None, 1, None),
+    };
+
+    FileInfo {
+        absolute_path: absolute_path.into(),
+        relative_path: relative_path.into(),
+        source_folder: Arc::from(""),
+        file_size,
+        modified_timestamp,
+        accessed_timestamp,
+        created_timestamp,
+        is_cloud_placeholder,
+        owner: None,
+        owner_orphaned: false,
+        is_dir: false,
+        item_count: 0,
+        hash: None,
+        allocated_size,
+        link_count,
+        file_id,
+        line_count: None,
+        word_count: None,
+    }
+}
+
+/// Allocated size, number of hard links and (for hard-linked files) the file id
+#[cfg(unix)]
+fn disk_usage_of(_path: &Path, metadata: &fs::Metadata) -> (Option<u64>, u32, Option<FileId>) {
+    use std::os::unix::fs::MetadataExt;
+    let links = u32::try_from(metadata.nlink()).unwrap_or(u32::MAX);
+    let id = (links > 1).then(|| FileId {
+        volume: metadata.dev(),
+        index: metadata.ino(),
+    });
+    // st_blocks is in 512-byte units on every Unix
+    (Some(metadata.blocks() * 512), links, id)
+}
+
+/// Allocated size, number of hard links and (for hard-linked files) the file id.
+/// The file is opened without data access, so online-only files are not recalled.
+#[cfg(windows)]
+fn disk_usage_of(path: &Path, _metadata: &fs::Metadata) -> (Option<u64>, u32, Option<FileId>) {
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::{
+        FileStandardInfo, GetFileInformationByHandle, GetFileInformationByHandleEx, BY_HANDLE_FILE_INFORMATION,
+        FILE_STANDARD_INFO,
+    };
+
+    let Ok(file) = fs::OpenOptions::new().access_mode(0).open(path) else {
+        return (None, 1, None);
+    };
+    let handle = file.as_raw_handle();
+    let mut standard: FILE_STANDARD_INFO = unsafe { std::mem::zeroed() };
+    let read = unsafe {
+        GetFileInformationByHandleEx(
+            handle,
+            FileStandardInfo,
+            &mut standard as *mut FILE_STANDARD_INFO as *mut std::ffi::c_void,
+            std::mem::size_of::<FILE_STANDARD_INFO>() as u32,
+        )
+    };
+    if read == 0 {
+        return (None, 1, None);
+    }
+    let links = standard.NumberOfLinks.max(1);
+    let id = if links > 1 {
+        let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+        (unsafe { GetFileInformationByHandle(handle, &mut info) } != 0).then(|| FileId {
+            volume: info.dwVolumeSerialNumber.into(),
+            index: (u64::from(info.nFileIndexHigh) << 32) | u64::from(info.nFileIndexLow),
+        })
+    } else {
+        None
+    };
+    (u64::try_from(standard.AllocationSize).ok(), links, id)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn disk_usage_of(_path: &Path, _metadata: &fs::Metadata) -> (Option<u64>, u32, Option<FileId>) {
+    (None, 1, None)
+}
+
+/// Identity of the file or folder at `path` (a link itself, not its target); None if it can't be read
+#[cfg(unix)]
+pub fn file_id(path: &Path) -> Option<FileId> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = fs::symlink_metadata(path).ok()?;
+    Some(FileId {
+        volume: metadata.dev(),
+        index: metadata.ino(),
+    })
+}
+
+/// Identity of the file or folder at `path` (a link itself, not its target); None if it can't be read
+#[cfg(windows)]
+pub fn file_id(path: &Path) -> Option<FileId> {
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION, FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OPEN_REPARSE_POINT,
+    };
+
+    let file = fs::OpenOptions::new()
+        .access_mode(0)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OPEN_REPARSE_POINT)
+        .open(path)
+        .ok()?;
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    (unsafe { GetFileInformationByHandle(file.as_raw_handle(), &mut info) } != 0).then(|| FileId {
+        volume: info.dwVolumeSerialNumber.into(),
+        index: (u64::from(info.nFileIndexHigh) << 32) | u64::from(info.nFileIndexLow),
+    })
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn file_id(_path: &Path) -> Option<FileId> {
+    None
 }
 
-fn scan_folder_internal(
-    base_path: &Path,
-    current_path: &Path,
+/// Logical size of a set of files and the space it takes on disk
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DiskUsage {
+    /// Sum of the file sizes (every name of a hard-linked file counted)
+    pub logical: u64,
+    /// Allocated bytes, each hard-linked file counted once (the size where allocation is unknown)
+    pub allocated: u64,
+    /// Names that are further hard links of a file already counted
+    pub extra_links: usize,
+}
+
+/// Disk usage of the files (folder rows are left out)
+pub fn disk_usage(files: &[FileInfo]) -> DiskUsage {
+    let mut usage = DiskUsage::default();
+    let mut seen: HashSet<FileId> = HashSet::new();
+    for file in files.iter().filter(|f| !f.is_dir) {
+        usage.logical += file.file_size;
+        if file.file_id.is_some_and(|id| !seen.insert(id)) {
+            usage.extra_links += 1;
+        } else {
+            usage.allocated += file.allocated_size.unwrap_or(file.file_size);
+        }
+    }
+    usage
+}
+
+/// Listed paths of each hard-linked file, in list order
+pub fn hard_link_groups(files: &[FileInfo]) -> HashMap<FileId, Vec<String>> {
+    let mut groups: HashMap<FileId, Vec<String>> = HashMap::new();
+    for file in files.iter().filter(|f| !f.is_dir) {
+        if let Some(id) = file.file_id {
+            groups.entry(id).or_default().push(file.absolute_path().to_string());
+        }
+    }
+    groups
+}
+
+/// Check for OneDrive/Dropbox (Cloud Files API) online-only attributes
+#[cfg(windows)]
+fn has_placeholder_attributes(metadata: &fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_OFFLINE: u32 = 0x1000;
+    const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x40000;
+    const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x400000;
+    metadata.file_attributes()
+        & (FILE_ATTRIBUTE_OFFLINE | FILE_ATTRIBUTE_RECALL_ON_OPEN | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS)
+        != 0
+}
+
+/// Check for the dataless flag set on evicted iCloud/File Provider files
+#[cfg(target_os = "macos")]
+fn has_placeholder_attributes(metadata: &fs::Metadata) -> bool {
+    use std::os::macos::fs::MetadataExt;
+    const SF_DATALESS: u32 = 0x40000000;
+    metadata.st_flags() & SF_DATALESS != 0
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn has_placeholder_attributes(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// Hidden by the platform's convention: the Hidden or System attribute on Windows (Thumbs.db, desktop.ini)
+#[cfg(windows)]
+fn is_hidden(_path: &Path, metadata: impl FnOnce() -> Option<fs::Metadata>) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+    metadata().is_some_and(|m| m.file_attributes() & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM) != 0)
+}
+
+/// A leading dot (.DS_Store, .git), or the hidden flag set by `chflags hidden`.
+/// Evicted iCloud files are dot-named stubs but stay listed as cloud placeholders.
+#[cfg(target_os = "macos")]
+fn is_hidden(path: &Path, metadata: impl FnOnce() -> Option<fs::Metadata>) -> bool {
+    use std::os::macos::fs::MetadataExt;
+    const UF_HIDDEN: u32 = 0x8000;
+    (has_dot_name(path) && !is_icloud_stub(path)) || metadata().is_some_and(|m| m.st_flags() & UF_HIDDEN != 0)
+}
+
+/// A leading dot (.cache, .git)
+#[cfg(not(any(windows, target_os = "macos")))]
+fn is_hidden(path: &Path, _metadata: impl FnOnce() -> Option<fs::Metadata>) -> bool {
+    has_dot_name(path)
+}
+
+#[cfg(not(windows))]
+fn has_dot_name(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+/// Evicted iCloud Drive files are stored as ".name.icloud" stubs
+fn is_icloud_stub(path: &Path) -> bool {
+    path.file_name()
+        .map(|n| {
+            let n = n.to_string_lossy();
+            n.starts_with('.') && n.ends_with(".icloud")
+        })
+        .unwrap_or(false)
+}
+
+/// One folder walk: the listed files and what couldn't be read
+struct Walk<'a> {
+    base_path: &'a Path,
     recursive: bool,
-    files: &mut Vec<FileInfo>,
-) -> Result<(), std::io::Error> {
-    for entry in fs::read_dir(current_path)? {
-        let entry = entry?;
-        let path = entry.path();
-
-        if path.is_file() {
-            let full_name = entry.file_name().to_string_lossy().to_string();
-            let extension = path
-                .extension()
-                .map(|e| e.to_string_lossy().to_string())
-                .unwrap_or_default();
-            let name = path
-                .file_stem()
-                .map(|n| n.to_string_lossy().to_string())
-                .unwrap_or_default();
-
-            // Calculate relative path from base folder
-            let relative_path = path
-                .strip_prefix(base_path)
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_else(|_| full_name.clone());
-
-            // Get absolute path
-            let absolute_path = path
-                .canonicalize()
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_else(|_| path.to_string_lossy().to_string());
-
-            // Get file metadata
-            let metadata = entry.metadata().ok();
-            let file_size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
-
-            // Get modification time as timestamp
-            let modified_timestamp = metadata
-                .and_then(|m| m.modified().ok())
-                .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0);
-
-            files.push(FileInfo {
-                name,
-                extension,
-                full_name,
-                relative_path,
-                absolute_path,
-                file_size,
-                modified_timestamp,
-                source_folder: String::new(),
-            });
-        } else if path.is_dir() && recursive {
-            // Recursively scan subdirectories
-            scan_folder_internal(base_path, &path, recursive, files)?;
+    filter: ScanFilter,
+    scanned: &'a AtomicUsize,
+    files: Vec<FileInfo>,
+    report: ScanReport,
+}
+
+impl<'a> Walk<'a> {
+    fn new(base_path: &'a Path, recursive: bool, filter: ScanFilter, scanned: &'a AtomicUsize) -> Self {
+        Self {
+            base_path,
+            recursive,
+            filter,
+            scanned,
+            files: Vec::new(),
+            report: ScanReport::default(),
         }
     }
 
-    Ok(())
+    /// List `current_path`. Only a failure to list the folder itself is returned; entries and
+    /// subfolders that can't be read are recorded in the report and the walk goes on.
+    /// Returns true if the folder holds nothing but empty folders (recursive walks only).
+    fn folder(&mut self, current_path: &Path, ignore: Option<&IgnoreRules>) -> Result<bool, std::io::Error> {
+        let mut empty = self.recursive;
+        for entry in retry::with_retry(|| fs::read_dir(current_path))? {
+            priority::throttle();
+            // Anything but an empty subfolder, even unreadable or left out, makes the folder non-empty
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    self.report.push(current_path, &e, true);
+                    empty = false;
+                    continue;
+                }
+            };
+            let path = entry.path();
+            // Follows links like Path::is_file; entries that still fail after retries are reported
+            let metadata = match retry::with_retry(|| fs::metadata(&path)) {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    self.report.push(&path, &e, entry.file_type().is_ok_and(|t| t.is_dir()));
+                    empty = false;
+                    continue;
+                }
+            };
+            if self.filter.skips(ignore, &path, metadata.is_dir(), || Some(metadata.clone())) {
+                empty = false;
+                continue;
+            }
+
+            if metadata.is_file() {
+                self.files.push(file_info(&path, self.base_path));
+                self.scanned.fetch_add(1, Ordering::Relaxed);
+                empty = false;
+            } else if metadata.is_dir() && self.recursive {
+                // Recursively scan subdirectories
+                let rules = ignore.map(|rules| rules.enter(&path));
+                match self.folder(&path, rules.as_ref()) {
+                    Ok(subfolder_empty) => empty &= subfolder_empty,
+                    Err(e) => {
+                        self.report.push(&path, &e, true);
+                        empty = false;
+                    }
+                }
+            } else {
+                empty = false;
+            }
+        }
+
+        if empty && current_path != self.base_path {
+            self.report.empty_folders.push(empty_folder_row(current_path, self.base_path));
+        }
+        Ok(empty)
+    }
+}
+
+/// Progress of a running scan, shared with the UI thread
+#[derive(Debug, Default)]
+pub struct ScanProgress {
+    /// Files listed so far by the full scan
+    pub scanned: AtomicUsize,
+    /// Files found so far by the quick count pass
+    pub estimated: AtomicUsize,
+    /// The quick count pass has finished, so `estimated` is the expected total
+    pub counted: AtomicBool,
+    /// The full scan has finished (stops a count pass that is still running)
+    pub finished: AtomicBool,
+}
+
+impl ScanProgress {
+    /// Share of the expected total scanned so far, once the count pass has finished
+    pub fn fraction(&self) -> Option<f32> {
+        if !self.counted.load(Ordering::Relaxed) {
+            return None;
+        }
+        let total = self.estimated.load(Ordering::Relaxed).max(1);
+        Some((self.scanned.load(Ordering::Relaxed) as f32 / total as f32).min(1.0))
+    }
+}
+
+/// Quick count pass for the scan progress: directory listings only, without reading each file's
+/// metadata (the entry type comes with the listing on NTFS, ext4 and APFS), so it finishes well
+/// before the full scan. Unreadable folders are skipped.
+pub fn count_files(paths: &[std::path::PathBuf], recursive: bool, filter: ScanFilter, progress: &ScanProgress) {
+    let mut pending: Vec<(std::path::PathBuf, Option<IgnoreRules>)> = paths
+        .iter()
+        .map(|path| (path.clone(), filter.ignore_rules(path)))
+        .collect();
+    while let Some((folder, ignore)) = pending.pop() {
+        if progress.finished.load(Ordering::Relaxed) {
+            return;
+        }
+        let Ok(entries) = fs::read_dir(&folder) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            priority::throttle();
+            match entry.file_type() {
+                // Hidden or ignored by .gitignore/.ignore: not counted, not entered
+                Ok(kind) if filter.skips(ignore.as_ref(), &entry.path(), kind.is_dir(), || entry.metadata().ok()) => {}
+                Ok(kind) if kind.is_dir() => {
+                    if recursive {
+                        let path = entry.path();
+                        let rules = ignore.as_ref().map(|rules| rules.enter(&path));
+                        pending.push((path, rules));
+                    }
+                }
+                Ok(_) => {
+                    progress.estimated.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(_) => {}
+            }
+        }
+    }
+    progress.counted.store(true, Ordering::Relaxed);
 }
 
 /// Scan multiple folders and return combined results
 /// Each file's relative_path will be prefixed with the folder name to distinguish source
-pub fn scan_folders(paths: &[std::path::PathBuf], recursive: bool) -> Result<Vec<FileInfo>, std::io::Error> {
+pub fn scan_folders(paths: &[std::path::PathBuf], recursive: bool, filter: ScanFilter) -> (Vec<FileInfo>, ScanReport) {
+    scan_folders_with_progress(paths, recursive, filter, &ScanProgress::default())
+}
+
+/// Scan multiple folders, counting listed files in `progress.scanned`.
+/// Folders that can't be read (scanned ones included) are listed in the report instead of failing the scan.
+pub fn scan_folders_with_progress(
+    paths: &[std::path::PathBuf],
+    recursive: bool,
+    filter: ScanFilter,
+    progress: &ScanProgress,
+) -> (Vec<FileInfo>, ScanReport) {
     let mut all_files = Vec::new();
+    let mut report = ScanReport::default();
 
     for path in paths {
         if !path.is_dir() {
             continue; // Skip non-directories
         }
 
-        let folder_name = path
-            .file_name()
-            .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_else(|| path.to_string_lossy().to_string());
-
-        let mut folder_files = Vec::new();
-        scan_folder_internal(path, path, recursive, &mut folder_files)?;
-
-        // Prefix relative_path with folder name and set source_folder
-        for file in &mut folder_files {
-            file.relative_path = format!("[{}]/{}", folder_name, file.relative_path);
-            file.source_folder = folder_name.clone();
+        let ignore = filter.ignore_rules(path);
+        let mut walk = Walk::new(path, recursive, filter, &progress.scanned);
+        if let Err(e) = walk.folder(path, ignore.as_ref()) {
+            walk.report.push(path, &e, true);
         }
-
+        let Walk { files: mut folder_files, report: mut folder_report, .. } = walk;
+        report.errors.extend(folder_report.errors);
+        tag_source_folder(&mut folder_files, path);
+        tag_source_folder(&mut folder_report.empty_folders, path);
+        report.empty_folders.extend(folder_report.empty_folders);
         all_files.extend(folder_files);
     }
 
@@ -224,5 +1011,181 @@ pub fn scan_folders(paths: &[std::path::PathBuf], recursive: bool) -> Result<Vec
             .cmp(&b.relative_path.to_lowercase())
     });
 
-    Ok(all_files)
+    (all_files, report)
+}
+
+/// Prefix relative_path with the scanned folder's name ("[Photos]/2024/a.jpg") and set source_folder
+pub fn tag_source_folder(files: &mut [FileInfo], folder: &Path) {
+    let folder_name: Arc<str> = folder
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_else(|| folder.to_string_lossy())
+        .into();
+    for file in files {
+        let relative_path = format!("[{}]/{}", folder_name, file.relative_path);
+        file.set_location(&relative_path, &folder_name);
+    }
+}
+
+/// Folder row of an empty folder (no size, no items; its own modification time)
+fn empty_folder_row(path: &Path, base_path: &Path) -> FileInfo {
+    FileInfo {
+        is_dir: true,
+        file_size: 0,
+        allocated_size: Some(0),
+        link_count: 1,
+        file_id: None,
+        ..file_info(path, base_path)
+    }
+}
+
+/// Rows for the folders below the scanned folders that hold listed files. Size and item count
+/// (files and subfolders) are totals over everything below a folder; its date is the newest
+/// file's, so no folder is read from disk.
+pub fn folder_rows(files: &[FileInfo]) -> Vec<FileInfo> {
+    struct Totals {
+        relative_path: String,
+        source_folder: Arc<str>,
+        size: u64,
+        /// Hard-linked files count in the folder of their first listed name only
+        allocated: Option<u64>,
+        items: u32,
+        modified: i64,
+    }
+
+    let mut folders: HashMap<String, Totals> = HashMap::new();
+    let mut seen_links: HashSet<FileId> = HashSet::new();
+    for file in files.iter().filter(|f| !f.is_dir) {
+        let repeated_link = file.file_id.is_some_and(|id| !seen_links.insert(id));
+        let allocated = if repeated_link { Some(0) } else { file.allocated_size };
+        let mut relative = Path::new(file.relative_path()).parent();
+        let mut absolute = Path::new(file.absolute_path()).parent();
+        let mut depth = relative.map_or(0, |path| path.components().count());
+        // The "[folder]" prefix of multi-folder scans is the scanned folder itself
+        let top = usize::from(!file.source_folder.is_empty());
+        while let (Some(rel), Some(abs), true) = (relative, absolute, depth > top) {
+            let totals = folders.entry(abs.to_string_lossy().into_owned()).or_insert_with(|| Totals {
+                relative_path: rel.to_string_lossy().into_owned(),
+                source_folder: file.source_folder.clone(),
+                size: 0,
+                allocated: Some(0),
+                items: 0,
+                modified: 0,
+            });
+            totals.size += file.file_size;
+            totals.allocated = totals.allocated.zip(allocated).map(|(total, bytes)| total + bytes);
+            totals.items += 1;
+            totals.modified = totals.modified.max(file.modified_timestamp);
+            relative = rel.parent();
+            absolute = abs.parent();
+            depth -= 1;
+        }
+    }
+
+    // Every subfolder is an item of each folder above it
+    let paths: Vec<String> = folders.keys().cloned().collect();
+    for path in &paths {
+        let mut parent = Path::new(path).parent();
+        while let Some(totals) = parent.and_then(|dir| folders.get_mut(dir.to_string_lossy().as_ref())) {
+            totals.items += 1;
+            parent = parent.and_then(Path::parent);
+        }
+    }
+
+    folders
+        .into_iter()
+        .map(|(absolute_path, totals)| FileInfo {
+            absolute_path: absolute_path.into(),
+            relative_path: totals.relative_path.into(),
+            source_folder: totals.source_folder,
+            file_size: totals.size,
+            modified_timestamp: totals.modified,
+            accessed_timestamp: None,
+            created_timestamp: None,
+            is_cloud_placeholder: false,
+            owner: None,
+            owner_orphaned: false,
+            is_dir: true,
+            item_count: totals.items,
+            hash: None,
+            allocated_size: totals.allocated,
+            link_count: 1,
+            file_id: None,
+            line_count: None,
+            word_count: None,
+        })
+        .collect()
+}
+
+/// Re-read a listed file from disk, keeping its list position fields (relative path, source folder, owner).
+/// None if it no longer exists.
+pub fn refresh_file(file: &FileInfo) -> Option<FileInfo> {
+    let path = Path::new(file.absolute_path());
+    if !path.is_file() {
+        return None;
+    }
+    let mut fresh = file_info(path, path);
+    fresh.set_location(&file.relative_path, &file.source_folder);
+    fresh.owner = file.owner.clone();
+    fresh.owner_orphaned = file.owner_orphaned;
+    if fresh.file_size == file.file_size && fresh.modified_timestamp == file.modified_timestamp {
+        fresh.hash = file.hash.clone();
+    }
+    Some(fresh)
+}
+
+/// Source folder name used for individually added files
+pub const ADDED_FILES_SOURCE: &str = "Added files";
+
+/// Build FileInfo for individually added files (missing files are skipped)
+pub fn scan_files(paths: &[std::path::PathBuf]) -> Vec<FileInfo> {
+    let source: Arc<str> = ADDED_FILES_SOURCE.into();
+    paths
+        .iter()
+        .filter(|path| path.is_file())
+        .map(|path| {
+            let mut file = file_info(path, path);
+            let relative_path = format!("[{}]/{}", ADDED_FILES_SOURCE, file.full_name());
+            file.set_location(&relative_path, &source);
+            file
+        })
+        .collect()
+}
+
+/// Case-insensitive wildcard match (`*` any run of characters, `?` one character)
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    // Greedy match with backtracking to the last '*'
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// True if a file matches any exclude pattern.
+/// Patterns with a path separator match the relative path, others the file name.
+pub fn is_excluded(file: &FileInfo, excludes: &[String]) -> bool {
+    excludes.iter().any(|pattern| {
+        if pattern.contains('/') || pattern.contains('\\') {
+            wildcard_match(&pattern.replace('\\', "/"), &file.relative_path.replace('\\', "/"))
+        } else {
+            wildcard_match(pattern, file.full_name())
+        }
+    })
 }
@@ -0,0 +1,108 @@
+use crate::users;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Names (case-insensitive, without extension) of files that describe their folder
+const README_STEMS: [&str; 5] = ["readme", "read me", "read_me", "about", "info"];
+
+/// Extensions a README is read with (plain text only)
+const README_EXTENSIONS: [&str; 6] = ["", "txt", "md", "markdown", "nfo", "rst"];
+
+/// Lines of a README shown in the banner
+pub const EXCERPT_LINES: usize = 4;
+
+/// Bytes read from a README (enough for the first lines)
+const EXCERPT_BYTES: u64 = 16 * 1024;
+
+/// First lines of the README / ABOUT file of a scanned folder
+#[derive(Debug, Clone)]
+pub struct ReadmeExcerpt {
+    pub path: PathBuf,
+    pub file_name: String,
+    /// First non-empty lines
+    pub lines: Vec<String>,
+    /// The file has more lines than shown
+    pub truncated: bool,
+}
+
+/// The folder's README or ABOUT file (README first), if it has one
+pub fn readme_excerpt(folder: &Path) -> Option<ReadmeExcerpt> {
+    let mut candidates: Vec<(usize, PathBuf)> = std::fs::read_dir(folder)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            let path = entry.path();
+            let stem = path.file_stem()?.to_string_lossy().to_lowercase();
+            let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+            let rank = README_STEMS.iter().position(|name| *name == stem)?;
+            README_EXTENSIONS.contains(&extension.as_str()).then_some((rank, path))
+        })
+        .collect();
+    candidates.sort();
+    let path = candidates.into_iter().next()?.1;
+
+    let mut bytes = Vec::new();
+    std::fs::File::open(&path).ok()?.take(EXCERPT_BYTES).read_to_end(&mut bytes).ok()?;
+    let text = String::from_utf8_lossy(&bytes);
+    let mut lines = text
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+    let excerpt: Vec<String> = lines.by_ref().take(EXCERPT_LINES).map(str::to_string).collect();
+    let truncated = lines.next().is_some();
+    Some(ReadmeExcerpt {
+        file_name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+        path,
+        lines: excerpt,
+        truncated,
+    })
+}
+
+/// The user's own notes on folders ("what is this dump"), kept across sessions
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FolderNotes {
+    /// Folder path -> note
+    entries: HashMap<String, String>,
+}
+
+impl FolderNotes {
+    /// Path of the notes file (next to settings.json)
+    pub fn path() -> PathBuf {
+        users::config_dir().join("folder_notes.json")
+    }
+
+    /// Load the notes (empty if missing or unreadable)
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize folder notes: {}", e))?;
+        std::fs::write(&path, content).map_err(|e| format!("Failed to write folder notes: {}", e))
+    }
+
+    pub fn get(&self, folder: &Path) -> Option<&str> {
+        self.entries.get(&folder.display().to_string()).map(String::as_str)
+    }
+
+    /// Set the folder's note; a blank note removes it
+    pub fn set(&mut self, folder: &Path, note: &str) {
+        let key = folder.display().to_string();
+        if note.trim().is_empty() {
+            self.entries.remove(&key);
+        } else {
+            self.entries.insert(key, note.trim_end().to_string());
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Ignore files read in every scanned folder; patterns of the later ones win
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// One pattern line of an ignore file
+struct Pattern {
+    /// Folder of the ignore file (anchored patterns match the path below it)
+    base: PathBuf,
+    /// Glob without the "!" prefix, the leading "/" and the trailing "/"
+    glob: Vec<char>,
+    /// "!pattern": re-includes what an earlier pattern ignored
+    negated: bool,
+    /// "pattern/": only matches folders
+    dir_only: bool,
+    /// Contains a "/" (other than a trailing one): matches the path below base, otherwise the name at any depth
+    anchored: bool,
+}
+
+impl Pattern {
+    fn parse(line: &str, base: &Path) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        // "\#" and "\!" start a literal pattern
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').filter(|rest| rest.starts_with(['#', '!'])).unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let glob = line.strip_prefix('/').unwrap_or(line);
+        if glob.is_empty() {
+            return None;
+        }
+        Some(Self {
+            base: base.to_path_buf(),
+            glob: fold_case(glob).chars().collect(),
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let Ok(relative) = path.strip_prefix(&self.base) else {
+            return false;
+        };
+        let text = if self.anchored {
+            relative.to_string_lossy().replace('\\', "/")
+        } else {
+            match relative.file_name() {
+                Some(name) => name.to_string_lossy().into_owned(),
+                None => return false,
+            }
+        };
+        let text: Vec<char> = fold_case(&text).chars().collect();
+        glob_match(&self.glob, &text)
+    }
+}
+
+/// Git matches case-insensitively on Windows (core.ignoreCase)
+fn fold_case(text: &str) -> String {
+    if cfg!(windows) {
+        text.to_lowercase()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Gitignore glob: `*` and `?` stay within one folder, `**` spans folders, `[a-z]`/`[!a-z]` are character classes
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', rest @ ..] => {
+            // "**/x" matches x in any subfolder, "x/**" everything below x
+            let rest = rest.strip_prefix(&['/']).unwrap_or(rest);
+            rest.is_empty() || (0..=text.len()).any(|i| (i == 0 || text[i - 1] == '/') && glob_match(rest, &text[i..]))
+        }
+        ['*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != '/')
+            .any(|i| glob_match(rest, &text[i..])),
+        ['?', rest @ ..] => matches!(text, [c, ..] if *c != '/') && glob_match(rest, &text[1..]),
+        ['[', rest @ ..] => match class_end(pattern) {
+            Some(end) => {
+                matches!(text, [c, ..] if *c != '/' && class_matches(&pattern[1..end], *c))
+                    && glob_match(&pattern[end + 1..], &text[1..])
+            }
+            None => text.first() == Some(&'[') && glob_match(rest, &text[1..]),
+        },
+        ['\\', escaped, rest @ ..] => text.first() == Some(escaped) && glob_match(rest, &text[1..]),
+        [c, rest @ ..] => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
+}
+
+/// Index of the "]" closing the class at the start of the pattern (a "]" right after "[" or "[!" is literal)
+fn class_end(pattern: &[char]) -> Option<usize> {
+    let first = if matches!(pattern.get(1), Some('!' | '^')) { 3 } else { 2 };
+    pattern.iter().skip(first).position(|&c| c == ']').map(|i| i + first)
+}
+
+fn class_matches(class: &[char], c: char) -> bool {
+    let (negated, class) = match class {
+        ['!' | '^', rest @ ..] => (true, rest),
+        _ => (false, class),
+    };
+    let mut found = false;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            found |= (class[i]..=class[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            found |= class[i] == c;
+            i += 1;
+        }
+    }
+    found != negated
+}
+
+/// Ignore rules in effect for a folder: its own .gitignore/.ignore and those of the folders above it.
+/// Cloning is cheap (the pattern lists are shared), so every folder of a walk gets its own copy.
+#[derive(Clone, Default)]
+pub struct IgnoreRules {
+    layers: Vec<Arc<Vec<Pattern>>>,
+}
+
+impl IgnoreRules {
+    /// Rules for a scanned folder: ignore files from the enclosing repository root (the nearest folder
+    /// above with a .git entry) down to the folder itself; outside a repository only the folder's own
+    pub fn for_root(folder: &Path) -> Self {
+        let repository = folder.ancestors().position(|dir| dir.join(".git").exists()).unwrap_or(0);
+        let chain: Vec<&Path> = folder.ancestors().take(repository + 1).collect();
+        chain
+            .into_iter()
+            .rev()
+            .fold(Self::default(), |rules, dir| rules.enter(dir))
+    }
+
+    /// Rules for a subfolder: these plus the subfolder's own ignore files
+    pub fn enter(&self, folder: &Path) -> Self {
+        let patterns: Vec<Pattern> = IGNORE_FILES
+            .iter()
+            .filter_map(|name| fs::read_to_string(folder.join(name)).ok())
+            .flat_map(|content| content.lines().filter_map(|line| Pattern::parse(line, folder)).collect::<Vec<_>>())
+            .collect();
+        let mut rules = self.clone();
+        if !patterns.is_empty() {
+            rules.layers.push(Arc::new(patterns));
+        }
+        rules
+    }
+
+    /// True if a file or folder should be left out: the last matching pattern decides.
+    /// The .git folder itself is always left out.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        if is_dir && path.file_name().is_some_and(|name| name == ".git") {
+            return true;
+        }
+        let mut ignored = false;
+        for pattern in self.layers.iter().flat_map(|layer| layer.iter()) {
+            if pattern.matches(path, is_dir) {
+                ignored = !pattern.negated;
+            }
+        }
+        ignored
+    }
+}
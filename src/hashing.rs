@@ -0,0 +1,335 @@
+use crate::file_scanner::FileInfo;
+use crate::jobs;
+use crate::priority;
+use crate::similarity::PerceptualHash;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Most worker threads for the Hash column (hashing is mostly disk-bound)
+const MAX_HASH_WORKERS: usize = 4;
+
+/// Minimum interval between batches of hashes sent to the GUI
+const HASH_BATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Checksum shown in the Hash column and exported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    #[default]
+    Sha256,
+}
+
+impl HashAlgorithm {
+    pub const ALL: [HashAlgorithm; 3] = [HashAlgorithm::Md5, HashAlgorithm::Sha1, HashAlgorithm::Sha256];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HashAlgorithm::Md5 => "MD5",
+            HashAlgorithm::Sha1 => "SHA-1",
+            HashAlgorithm::Sha256 => "SHA-256",
+        }
+    }
+}
+
+/// Checksum of a file's content as lowercase hex
+pub fn hash_file(path: &Path, algorithm: HashAlgorithm) -> std::io::Result<String> {
+    match algorithm {
+        HashAlgorithm::Md5 => digest_file::<md5::Md5>(path),
+        HashAlgorithm::Sha1 => digest_file::<sha1::Sha1>(path),
+        HashAlgorithm::Sha256 => digest_file::<Sha256>(path),
+    }
+}
+
+fn digest_file<D: Digest>(path: &Path) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = D::new();
+    let mut buffer = vec![0u8; jobs::CHUNK_BYTES];
+    loop {
+        priority::throttle();
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(to_hex(&hasher.finalize()))
+}
+
+/// Lowercase hex of a digest
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hashes remembered for one file, valid while size and modification time are unchanged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedHash {
+    size: u64,
+    modified: i64,
+    #[serde(default)]
+    sha256: Option<String>,
+    #[serde(default)]
+    sha1: Option<String>,
+    #[serde(default)]
+    md5: Option<String>,
+    /// Perceptual image hashes (see similarity::PerceptualHash)
+    #[serde(default)]
+    dhash: Option<u64>,
+    #[serde(default)]
+    phash: Option<u64>,
+}
+
+impl CachedHash {
+    fn slot(&mut self, algorithm: HashAlgorithm) -> &mut Option<String> {
+        match algorithm {
+            HashAlgorithm::Md5 => &mut self.md5,
+            HashAlgorithm::Sha1 => &mut self.sha1,
+            HashAlgorithm::Sha256 => &mut self.sha256,
+        }
+    }
+
+    fn perceptual_slot(&mut self, method: PerceptualHash) -> &mut Option<u64> {
+        match method {
+            PerceptualHash::Difference => &mut self.dhash,
+            PerceptualHash::Dct => &mut self.phash,
+        }
+    }
+}
+
+/// Persistent checksum cache keyed by absolute path (+ size + mtime),
+/// so rescans of mostly static archives don't re-hash unchanged files
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HashCache {
+    entries: HashMap<String, CachedHash>,
+    #[serde(skip)]
+    dirty: bool,
+    /// Lookups answered from the cache since loading
+    #[serde(skip)]
+    pub hits: usize,
+}
+
+impl HashCache {
+    /// Path of the cache file (next to settings.json)
+    pub fn path() -> PathBuf {
+        let base = dirs::config_dir().unwrap_or_else(std::env::temp_dir);
+        base.join("file-lister").join("hash_cache.json")
+    }
+
+    /// Load the cache (empty if missing or unreadable)
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the cache if anything was added
+    pub fn save(&self) -> Result<(), String> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        }
+        let content = serde_json::to_string(self).map_err(|e| format!("Failed to serialize hash cache: {}", e))?;
+        std::fs::write(&path, content).map_err(|e| format!("Failed to write hash cache: {}", e))
+    }
+
+    /// Delete the cache file
+    pub fn clear() -> Result<(), String> {
+        match std::fs::remove_file(Self::path()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("Failed to clear hash cache: {}", e)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Cache entry for a file, reset if its size or mtime changed
+    fn entry(&mut self, file: &FileInfo) -> &mut CachedHash {
+        let fresh = CachedHash {
+            size: file.file_size,
+            modified: file.modified_timestamp,
+            sha256: None,
+            sha1: None,
+            md5: None,
+            dhash: None,
+            phash: None,
+        };
+        let entry = self.entries.entry(file.absolute_path().to_string()).or_insert_with(|| fresh.clone());
+        if entry.size != fresh.size || entry.modified != fresh.modified {
+            *entry = fresh;
+        }
+        entry
+    }
+
+    /// SHA-256 of a file, from the cache if its size and mtime are unchanged
+    pub fn sha256(&mut self, file: &FileInfo) -> std::io::Result<String> {
+        self.hash(file, HashAlgorithm::Sha256)
+    }
+
+    /// Checksum of a file, from the cache if its size and mtime are unchanged
+    pub fn hash(&mut self, file: &FileInfo, algorithm: HashAlgorithm) -> std::io::Result<String> {
+        if let Some(hash) = self.cached(file, algorithm) {
+            return Ok(hash);
+        }
+        let hash = hash_file(Path::new(file.absolute_path()), algorithm)?;
+        self.insert(file, algorithm, hash.clone());
+        Ok(hash)
+    }
+
+    /// Cached checksum, if the file is unchanged (counts as a hit)
+    fn cached(&mut self, file: &FileInfo, algorithm: HashAlgorithm) -> Option<String> {
+        let hash = self.entry(file).slot(algorithm).clone()?;
+        self.hits += 1;
+        Some(hash)
+    }
+
+    fn insert(&mut self, file: &FileInfo, algorithm: HashAlgorithm, hash: String) {
+        *self.entry(file).slot(algorithm) = Some(hash);
+        self.dirty = true;
+    }
+
+    /// Perceptual hash of an image, from the cache if its size and mtime are unchanged
+    pub fn perceptual(&mut self, file: &FileInfo, method: PerceptualHash) -> Option<u64> {
+        if let Some(hash) = *self.entry(file).perceptual_slot(method) {
+            self.hits += 1;
+            return Some(hash);
+        }
+
+        priority::throttle();
+        let hash = method.compute(Path::new(file.absolute_path()))?;
+        *self.entry(file).perceptual_slot(method) = Some(hash);
+        self.dirty = true;
+        Some(hash)
+    }
+}
+
+/// Message from the background hashing (Hash column, duplicates by content)
+pub enum ContentHashMessage {
+    /// (absolute path, checksum) of files hashed since the last batch
+    Hashed(Vec<(String, String)>),
+    /// All files done (or cancelled); files that couldn't be read
+    Finished { failed: usize },
+}
+
+/// Hash `files` on worker threads, sending the checksums in batches so the Hash column fills in
+/// while it runs. Folder rows and online-only cloud files are skipped; `cancel` stops it.
+pub fn spawn_content_hashing(
+    files: Vec<FileInfo>,
+    algorithm: HashAlgorithm,
+    use_cache: bool,
+    cancel: Arc<AtomicBool>,
+) -> Receiver<ContentHashMessage> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let cache = Mutex::new(if use_cache { HashCache::load() } else { HashCache::default() });
+        let files: Vec<FileInfo> = files.into_iter().filter(|f| !f.is_dir && !f.is_cloud_placeholder).collect();
+        let next = std::sync::atomic::AtomicUsize::new(0);
+        let failed = std::sync::atomic::AtomicUsize::new(0);
+        let workers = thread::available_parallelism().map_or(1, |n| n.get()).min(MAX_HASH_WORKERS);
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                let tx = tx.clone();
+                let (cache, files, next, failed, cancel) = (&cache, &files, &next, &failed, &cancel);
+                scope.spawn(move || {
+                    priority::enter_background();
+                    let mut batch = Vec::new();
+                    let mut last_sent = Instant::now();
+                    while !cancel.load(Ordering::Relaxed) {
+                        let Some(file) = files.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                            break;
+                        };
+                        // Hash outside the lock; only lookups and inserts share the cache
+                        let cached = cache.lock().unwrap_or_else(|e| e.into_inner()).cached(file, algorithm);
+                        let hash = match cached {
+                            Some(hash) => hash,
+                            None => match hash_file(Path::new(file.absolute_path()), algorithm) {
+                                Ok(hash) => {
+                                    cache.lock().unwrap_or_else(|e| e.into_inner()).insert(file, algorithm, hash.clone());
+                                    hash
+                                }
+                                Err(_) => {
+                                    failed.fetch_add(1, Ordering::Relaxed);
+                                    continue;
+                                }
+                            },
+                        };
+                        batch.push((file.absolute_path().to_string(), hash));
+                        if last_sent.elapsed() >= HASH_BATCH_INTERVAL {
+                            let _ = tx.send(ContentHashMessage::Hashed(std::mem::take(&mut batch)));
+                            last_sent = Instant::now();
+                        }
+                    }
+                    if !batch.is_empty() {
+                        let _ = tx.send(ContentHashMessage::Hashed(batch));
+                    }
+                });
+            }
+        });
+
+        if use_cache {
+            let _ = cache.into_inner().unwrap_or_else(|e| e.into_inner()).save();
+        }
+        let _ = tx.send(ContentHashMessage::Finished {
+            failed: failed.into_inner(),
+        });
+    });
+    rx
+}
+
+/// Fill in `hash` for every file that lacks one (command line: waits for all files)
+pub fn fill_hashes(files: &mut [FileInfo], algorithm: HashAlgorithm, use_cache: bool) -> usize {
+    let mut cache = if use_cache { HashCache::load() } else { HashCache::default() };
+    let mut failed = 0;
+    for file in files.iter_mut().filter(|f| f.hash.is_none() && !f.is_dir) {
+        match cache.hash(file, algorithm) {
+            Ok(hash) => file.hash = Some(hash.into()),
+            Err(_) => failed += 1,
+        }
+    }
+    if use_cache {
+        let _ = cache.save();
+    }
+    failed
+}
+
+/// Files sharing their size with another file: the only ones that can have equal content.
+/// Empty files, folder rows and online-only cloud files are left out.
+pub fn same_size_files(files: &[FileInfo]) -> Vec<FileInfo> {
+    let candidates = || files.iter().filter(|f| !f.is_dir && !f.is_cloud_placeholder && f.file_size > 0);
+    let mut sizes: HashMap<u64, usize> = HashMap::new();
+    for file in candidates() {
+        *sizes.entry(file.file_size).or_insert(0) += 1;
+    }
+    candidates().filter(|f| sizes[&f.file_size] > 1).cloned().collect()
+}
+
+/// Group files with equal size and checksum, from (absolute path, size, checksum).
+/// Returns path -> (group id, group size); the largest files get the first groups.
+pub fn group_by_content(hashed: &[(String, u64, String)]) -> HashMap<String, (usize, usize)> {
+    let mut by_content: HashMap<(u64, &str), Vec<&str>> = HashMap::new();
+    for (path, size, hash) in hashed {
+        by_content.entry((*size, hash.as_str())).or_default().push(path);
+    }
+    let mut groups: Vec<((u64, &str), Vec<&str>)> = by_content.into_iter().filter(|(_, paths)| paths.len() > 1).collect();
+    groups.sort_by(|((size_a, hash_a), _), ((size_b, hash_b), _)| size_b.cmp(size_a).then(hash_a.cmp(hash_b)));
+
+    let mut result = HashMap::new();
+    for (group, (_, paths)) in groups.iter().enumerate() {
+        for path in paths {
+            result.insert(path.to_string(), (group, paths.len()));
+        }
+    }
+    result
+}
@@ -0,0 +1,201 @@
+use crate::file_scanner::FileInfo;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Time allowed for a webhook request
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// When a hook fires
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    /// A scan finished (also every live update batch)
+    #[default]
+    Scan,
+    /// A listing export (file or cloud spreadsheet) finished
+    Export,
+}
+
+impl HookEvent {
+    pub const ALL: [HookEvent; 2] = [HookEvent::Scan, HookEvent::Export];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HookEvent::Scan => "After scan",
+            HookEvent::Export => "After export",
+        }
+    }
+
+    /// Name in the JSON summary and FILE_LISTER_EVENT
+    fn name(self) -> &'static str {
+        match self {
+            HookEvent::Scan => "scan",
+            HookEvent::Export => "export",
+        }
+    }
+}
+
+/// What a hook does with the summary
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookKind {
+    /// POST the JSON summary to a URL
+    #[default]
+    Webhook,
+    /// Run a shell command with the JSON summary on stdin
+    Command,
+}
+
+impl HookKind {
+    pub const ALL: [HookKind; 2] = [HookKind::Webhook, HookKind::Command];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HookKind::Webhook => "POST to URL",
+            HookKind::Command => "Run command",
+        }
+    }
+}
+
+/// One configured hook ("hooks" in settings.json)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Hook {
+    pub on: HookEvent,
+    pub kind: HookKind,
+    /// URL for a webhook, command line for a command
+    pub target: String,
+    pub enabled: bool,
+}
+
+impl Default for Hook {
+    fn default() -> Self {
+        Self {
+            on: HookEvent::Scan,
+            kind: HookKind::Webhook,
+            target: String::new(),
+            enabled: true,
+        }
+    }
+}
+
+impl Hook {
+    fn fires_on(&self, event: HookEvent) -> bool {
+        self.enabled && self.on == event && !self.target.trim().is_empty()
+    }
+
+    fn run(&self, summary: &Value) -> Result<(), String> {
+        match self.kind {
+            HookKind::Webhook => post(self.target.trim(), summary),
+            HookKind::Command => run_command(self.target.trim(), summary),
+        }
+    }
+}
+
+/// JSON summary passed to hooks. `mode` is "gui", "cli" or "watch" (live update);
+/// `outputs` are the written files or the spreadsheet URL of an export.
+pub fn summary(event: HookEvent, mode: &str, folders: &[PathBuf], files: &[FileInfo], outputs: &[String]) -> Value {
+    let finished_at = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    json!({
+        "event": event.name(),
+        "mode": mode,
+        "finished_at": finished_at,
+        "folders": folders.iter().map(|f| f.display().to_string()).collect::<Vec<_>>(),
+        "file_count": files.len(),
+        "total_size": files.iter().map(|f| f.file_size).sum::<u64>(),
+        "outputs": outputs,
+    })
+}
+
+/// Run the hooks of an event one after another; returns a message for every hook that failed
+pub fn run(hooks: &[Hook], event: HookEvent, summary: &Value) -> Vec<String> {
+    hooks
+        .iter()
+        .filter(|hook| hook.fires_on(event))
+        .filter_map(|hook| hook.run(summary).err().map(|e| format!("Hook \"{}\" failed: {}", hook.target.trim(), e)))
+        .collect()
+}
+
+/// Run the hooks of an event on a background thread; None if no hook fires on it
+pub fn spawn(hooks: &[Hook], event: HookEvent, summary: Value) -> Option<Receiver<Vec<String>>> {
+    let hooks: Vec<Hook> = hooks.iter().filter(|hook| hook.fires_on(event)).cloned().collect();
+    if hooks.is_empty() {
+        return None;
+    }
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(run(&hooks, event, &summary));
+    });
+    Some(receiver)
+}
+
+fn post(url: &str, summary: &Value) -> Result<(), String> {
+    let result = ureq::post(url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .set("Content-Type", "application/json")
+        .send_string(&summary.to_string());
+    match result {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::Status(code, response)) => {
+            Err(format!("HTTP {} {}", code, response.status_text()))
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Run through the shell, so pipes and quoting work as typed; the summary goes to stdin
+/// and the basics to FILE_LISTER_EVENT, FILE_LISTER_FILE_COUNT and FILE_LISTER_OUTPUT
+fn run_command(command: &str, summary: &Value) -> Result<(), String> {
+    let text = |key: &str| match &summary[key] {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let mut child = shell(command)
+        .env("FILE_LISTER_EVENT", text("event"))
+        .env("FILE_LISTER_FILE_COUNT", text("file_count"))
+        .env("FILE_LISTER_OUTPUT", summary["outputs"][0].as_str().unwrap_or_default())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("can't start: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A command that doesn't read stdin may exit before the write
+        let _ = stdin.write_all(summary.to_string().as_bytes());
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match stderr.lines().rev().find(|line| !line.trim().is_empty()) {
+        Some(line) => Err(format!("{}: {}", output.status, line.trim())),
+        None => Err(output.status.to_string()),
+    }
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+    let mut cmd = Command::new("cmd");
+    // cmd.exe parses the rest of its command line itself
+    cmd.arg("/C").raw_arg(command).creation_flags(CREATE_NO_WINDOW);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command]);
+    cmd
+}
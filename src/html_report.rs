@@ -0,0 +1,225 @@
+use crate::columns::{ColumnFormat, ColumnValue};
+use crate::csv_export::{ExportOptions, CANCELLED};
+use crate::file_scanner::{format_date, format_size, FileInfo};
+use base64::Engine;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Extensions listed in the size-by-extension table; the rest are added up as "Other"
+const TOP_EXTENSIONS: usize = 20;
+
+const STYLE: &str = "body{font-family:system-ui,-apple-system,'Segoe UI',sans-serif;margin:24px;color:#222}\
+h1{font-size:1.4em;margin-bottom:4px}h2{font-size:1.1em;margin-top:28px}.generated{color:#777}\
+table{border-collapse:collapse;font-size:0.9em}th,td{padding:4px 8px;border-bottom:1px solid #e4e4e4;text-align:left}\
+th{background:#f4f4f4;position:sticky;top:0}table.sortable th{cursor:pointer;user-select:none}\
+th[aria-sort=ascending]::after{content:' \\25B2'}th[aria-sort=descending]::after{content:' \\25BC'}\
+td.number{text-align:right;white-space:nowrap}tbody tr:hover{background:#f7f9fc}\
+.bar{background:#4a90d9;height:10px;min-width:1px}td.preview img{max-width:96px;max-height:96px;display:block}\
+dl.summary{display:grid;grid-template-columns:max-content auto;gap:4px 16px}dl.summary dt{color:#555}dl.summary dd{margin:0}";
+
+/// Sorts a table by the clicked header: numbers by their data-v value, text naturally
+const SCRIPT: &str = "document.querySelectorAll('table.sortable th').forEach(function (th) {\n\
+  if (th.classList.contains('nosort')) return;\n\
+  th.addEventListener('click', function () {\n\
+    var table = th.closest('table'), body = table.tBodies[0];\n\
+    var ascending = th.getAttribute('aria-sort') !== 'ascending';\n\
+    table.querySelectorAll('th').forEach(function (h) { h.removeAttribute('aria-sort'); });\n\
+    th.setAttribute('aria-sort', ascending ? 'ascending' : 'descending');\n\
+    var collator = new Intl.Collator(undefined, { numeric: true, sensitivity: 'base' });\n\
+    var key = function (row) {\n\
+      var cell = row.cells[th.cellIndex];\n\
+      return cell.hasAttribute('data-v') ? Number(cell.getAttribute('data-v')) : cell.textContent;\n\
+    };\n\
+    var rows = Array.prototype.slice.call(body.rows);\n\
+    rows.sort(function (a, b) {\n\
+      var x = key(a), y = key(b);\n\
+      var order = typeof x === 'number' ? x - y : collator.compare(x, y);\n\
+      return ascending ? order : -order;\n\
+    });\n\
+    rows.forEach(function (row) { body.appendChild(row); });\n\
+  });\n\
+});\n";
+
+/// Text safe inside HTML elements and quoted attributes
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Table cell of a value: numbers right-aligned and sorted by value, sizes with the exact
+/// byte count on hover
+fn cell(value: &ColumnValue, format: &ColumnFormat) -> String {
+    let text = escape(&value.display(format));
+    match value {
+        ColumnValue::Text(_) => format!("<td>{}</td>", text),
+        ColumnValue::Count(count) => format!("<td class=\"number\" data-v=\"{}\">{}</td>", count, text),
+        ColumnValue::Size(bytes) => format!("<td class=\"number\" data-v=\"{0}\" title=\"{0} bytes\">{1}</td>", bytes, text),
+        // Unknown dates sort first, as in the file list
+        ColumnValue::Time(timestamp) => format!("<td data-v=\"{}\">{}</td>", timestamp.unwrap_or(-1), text),
+    }
+}
+
+/// PNG at `thumbnail` (relative to the report's folder, or absolute) as a data URI
+fn embedded_image(report_dir: &Path, thumbnail: &str) -> Option<String> {
+    let png = std::fs::read(report_dir.join(thumbnail)).ok()?;
+    Some(format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(png)))
+}
+
+/// Files and total size per lowercased extension, largest total first
+fn size_by_extension(files: &[&FileInfo]) -> Vec<(String, usize, u64)> {
+    let mut totals: HashMap<String, (usize, u64)> = HashMap::new();
+    for file in files {
+        let extension = if file.extension().is_empty() { "(none)".to_string() } else { file.extension().to_lowercase() };
+        let total = totals.entry(extension).or_default();
+        total.0 += 1;
+        total.1 += file.file_size;
+    }
+    let mut totals: Vec<(String, usize, u64)> = totals.into_iter().map(|(extension, (count, bytes))| (extension, count, bytes)).collect();
+    totals.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+    if totals.len() > TOP_EXTENSIONS {
+        let rest = totals.split_off(TOP_EXTENSIONS);
+        totals.push((
+            format!("Other ({} types)", rest.len()),
+            rest.iter().map(|r| r.1).sum(),
+            rest.iter().map(|r| r.2).sum(),
+        ));
+    }
+    totals
+}
+
+/// Write a self-contained HTML report: a summary (files, total size, size by extension) and
+/// the files as a table sorted by clicking its headers. With `thumbnails`, a Preview column
+/// embeds each file's thumbnail, so the page needs nothing next to it. The page is built in
+/// memory, so a cancelled export leaves no file behind.
+pub fn write_report(
+    files: &[FileInfo],
+    options: &ExportOptions,
+    thumbnails: Option<&[Option<String>]>,
+    output_path: &Path,
+    on_row: &mut dyn FnMut() -> bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let format = ColumnFormat {
+        size: options.size_format,
+        calendar: options.calendar,
+    };
+    let report_dir = output_path.parent().unwrap_or(Path::new(""));
+    let title = output_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "File report".to_string());
+    let listed: Vec<&FileInfo> = files.iter().filter(|f| !f.is_dir).collect();
+    let total_size: u64 = listed.iter().map(|f| f.file_size).sum();
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n", escape(&title), STYLE));
+    html.push_str(&format!(
+        "<h1>{}</h1>\n<div class=\"generated\">Generated {}</div>\n",
+        escape(&title),
+        escape(&format_date(now, options.calendar))
+    ));
+
+    html.push_str("<h2>Summary</h2>\n<dl class=\"summary\">\n");
+    html.push_str(&format!("<dt>Files</dt><dd>{}</dd>\n", listed.len()));
+    if listed.len() < files.len() {
+        html.push_str(&format!("<dt>Folders</dt><dd>{}</dd>\n", files.len() - listed.len()));
+    }
+    html.push_str(&format!(
+        "<dt>Total size</dt><dd title=\"{} bytes\">{}</dd>\n</dl>\n",
+        total_size,
+        escape(&format_size(total_size, &options.size_format))
+    ));
+
+    html.push_str("<h2>Size by extension</h2>\n<table class=\"sortable\">\n<thead><tr>");
+    html.push_str("<th>Extension</th><th>Files</th><th>Size</th><th>Share</th><th class=\"nosort\"></th></tr></thead>\n<tbody>\n");
+    for (extension, count, bytes) in size_by_extension(&listed) {
+        let share = if total_size == 0 { 0.0 } else { bytes as f64 * 100.0 / total_size as f64 };
+        html.push_str(&format!(
+            "<tr><td>{}</td>{}{}<td class=\"number\" data-v=\"{:.4}\">{:.1}%</td><td><div class=\"bar\" style=\"width:{:.0}px\"></div></td></tr>\n",
+            escape(&extension),
+            cell(&ColumnValue::Count(count as u64), &format),
+            cell(&ColumnValue::Size(bytes), &format),
+            share,
+            share,
+            share * 2.0
+        ));
+    }
+    html.push_str("</tbody>\n</table>\n");
+
+    html.push_str("<h2>Files</h2>\n<table class=\"sortable\">\n<thead><tr>");
+    if thumbnails.is_some() {
+        html.push_str("<th class=\"nosort\">Preview</th>");
+    }
+    for column in &options.columns {
+        // Sizes are shown in the size format here, with the bytes on hover
+        let header = column.header_in(options.calendar);
+        html.push_str(&format!("<th>{}</th>", escape(header.trim_end_matches(" (bytes)"))));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for (idx, file) in files.iter().enumerate() {
+        html.push_str("<tr>");
+        if let Some(thumbnails) = thumbnails {
+            match thumbnails.get(idx).and_then(|t| t.as_deref()).and_then(|t| embedded_image(report_dir, t)) {
+                Some(uri) => html.push_str(&format!("<td class=\"preview\"><img src=\"{}\" alt=\"\" loading=\"lazy\"></td>", uri)),
+                None => html.push_str("<td class=\"preview\"></td>"),
+            }
+        }
+        for column in &options.columns {
+            html.push_str(&cell(&column.column().value(file), &format));
+        }
+        html.push_str("</tr>\n");
+        if !on_row() {
+            return Err(CANCELLED.into());
+        }
+    }
+    html.push_str("</tbody>\n</table>\n");
+    html.push_str(&format!("<script>\n{}</script>\n</body>\n</html>\n", SCRIPT));
+
+    std::fs::write(output_path, html)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_scanner::{scan_folder, ScanFilter};
+
+    #[test]
+    fn size_by_extension_adds_up_the_rest_as_other() {
+        let folder = std::env::temp_dir().join(format!("file-lister-html-report-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&folder);
+        std::fs::create_dir_all(&folder).unwrap();
+        // Extensions e1..e22 with 1..22 bytes each, plus two spellings of .JPG and a file without one
+        for n in 1..=22 {
+            std::fs::write(folder.join(format!("file.e{}", n)), vec![b'x'; n]).unwrap();
+        }
+        std::fs::write(folder.join("a.JPG"), vec![b'x'; 100]).unwrap();
+        std::fs::write(folder.join("b.jpg"), vec![b'x'; 50]).unwrap();
+        std::fs::write(folder.join("README"), vec![b'x'; 30]).unwrap();
+        let files = scan_folder(&folder, false, ScanFilter::default()).unwrap();
+        let files: Vec<&FileInfo> = files.iter().collect();
+
+        let totals = size_by_extension(&files);
+        assert_eq!(totals.len(), TOP_EXTENSIONS + 1);
+        assert_eq!(totals[0], ("jpg".to_string(), 2, 150));
+        assert_eq!(totals[1], ("(none)".to_string(), 1, 30));
+        assert_eq!(totals[2], ("e22".to_string(), 1, 22));
+        // jpg, (none) and e22..e5 make the top 20
+        assert_eq!(totals[TOP_EXTENSIONS], ("Other (4 types)".to_string(), 4, 10));
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+}
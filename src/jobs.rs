@@ -0,0 +1,248 @@
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Minimum interval between progress messages
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Buffer of copies, splits, joins and hashing: files of any size stream through it
+pub const CHUNK_BYTES: usize = 1024 * 1024;
+
+/// Ignore throughput until this much time has passed (first samples are noise)
+const MIN_RATE_ELAPSED: Duration = Duration::from_millis(500);
+
+/// One file of a bulk job
+pub struct JobItem {
+    pub path: PathBuf,
+    pub name: String,
+}
+
+/// Progress snapshot of a running bulk job
+#[derive(Clone, Default)]
+pub struct JobProgress {
+    pub files_done: usize,
+    pub files_total: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    /// File currently being processed
+    pub current: String,
+    pub elapsed: Duration,
+}
+
+impl JobProgress {
+    /// Fraction of bytes processed (file count when sizes are all zero). Divided in f64: an f32
+    /// holds byte counts only to 24 bits, so progress within a multi-GB file would move in steps.
+    pub fn fraction(&self) -> f32 {
+        let fraction = if self.bytes_total > 0 {
+            self.bytes_done as f64 / self.bytes_total as f64
+        } else if self.files_total > 0 {
+            self.files_done as f64 / self.files_total as f64
+        } else {
+            0.0
+        };
+        fraction as f32
+    }
+
+    /// Average bytes per second since the job started
+    pub fn bytes_per_sec(&self) -> Option<f64> {
+        if self.elapsed < MIN_RATE_ELAPSED {
+            return None;
+        }
+        Some(self.bytes_done as f64 / self.elapsed.as_secs_f64())
+    }
+
+    /// Estimated time remaining at the average throughput
+    pub fn eta(&self) -> Option<Duration> {
+        let rate = self.bytes_per_sec()?;
+        if rate <= 0.0 {
+            return None;
+        }
+        let remaining = self.bytes_total.saturating_sub(self.bytes_done) as f64;
+        Some(Duration::from_secs_f64(remaining / rate))
+    }
+}
+
+/// Failure of one job item
+pub struct JobError {
+    pub message: String,
+    /// Failed for lack of permissions (may succeed elevated)
+    pub access_denied: bool,
+}
+
+impl From<std::io::Error> for JobError {
+    fn from(error: std::io::Error) -> Self {
+        Self {
+            access_denied: error.kind() == std::io::ErrorKind::PermissionDenied,
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<String> for JobError {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            access_denied: false,
+        }
+    }
+}
+
+/// Result of a finished bulk job
+pub struct JobOutcome {
+    pub done: usize,
+    /// "name: error" per failed file
+    pub errors: Vec<String>,
+    /// Files that no longer existed when their turn came (skipped)
+    pub missing: Vec<PathBuf>,
+    /// Files that failed with access denied
+    pub access_denied: Vec<PathBuf>,
+    pub bytes_done: u64,
+    pub elapsed: Duration,
+}
+
+enum JobMessage {
+    Progress(JobProgress),
+    Finished(JobOutcome),
+}
+
+/// Handle to a bulk job running on a background thread
+pub struct Job {
+    receiver: Receiver<JobMessage>,
+    /// Latest progress received
+    pub progress: JobProgress,
+}
+
+impl Job {
+    /// Run `operation` for each item on a background thread.
+    /// The operation reports bytes processed for the current file through its callback.
+    pub fn spawn<F>(items: Vec<JobItem>, mut operation: F) -> Self
+    where
+        F: FnMut(&JobItem, &mut dyn FnMut(u64)) -> Result<(), JobError> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        let files_total = items.len();
+
+        thread::spawn(move || {
+            let start = Instant::now();
+            let sizes: Vec<u64> = items
+                .iter()
+                .map(|item| std::fs::metadata(&item.path).map(|m| m.len()).unwrap_or(0))
+                .collect();
+            let mut progress = JobProgress {
+                files_total,
+                bytes_total: sizes.iter().sum(),
+                ..Default::default()
+            };
+            let mut errors = Vec::new();
+            let mut missing = Vec::new();
+            let mut access_denied = Vec::new();
+            let mut done = 0;
+            let mut last_sent = start;
+
+            for (item, size) in items.iter().zip(&sizes) {
+                // Stat right before operating: the file may have been moved or deleted since the scan
+                if std::fs::symlink_metadata(&item.path).is_err() {
+                    missing.push(item.path.clone());
+                    progress.files_done += 1;
+                    continue;
+                }
+
+                progress.current = item.name.clone();
+                let file_start = progress.bytes_done;
+                let result = {
+                    let mut on_bytes = |bytes: u64| {
+                        progress.bytes_done = file_start + bytes.min(*size);
+                        if last_sent.elapsed() >= PROGRESS_INTERVAL {
+                            progress.elapsed = start.elapsed();
+                            let _ = tx.send(JobMessage::Progress(progress.clone()));
+                            last_sent = Instant::now();
+                        }
+                    };
+                    on_bytes(0);
+                    operation(item, &mut on_bytes)
+                };
+
+                match result {
+                    Ok(_) => done += 1,
+                    Err(e) => {
+                        if e.access_denied {
+                            access_denied.push(item.path.clone());
+                        }
+                        errors.push(format!("{}: {}", item.name, e.message));
+                    }
+                }
+                progress.files_done += 1;
+                progress.bytes_done = file_start + size;
+            }
+
+            let _ = tx.send(JobMessage::Finished(JobOutcome {
+                done,
+                errors,
+                missing,
+                access_denied,
+                bytes_done: progress.bytes_done,
+                elapsed: start.elapsed(),
+            }));
+        });
+
+        Self {
+            receiver: rx,
+            progress: JobProgress {
+                files_total,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Apply pending progress messages; returns the outcome once the job has finished
+    pub fn poll(&mut self) -> Option<JobOutcome> {
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                JobMessage::Progress(progress) => self.progress = progress,
+                JobMessage::Finished(outcome) => return Some(outcome),
+            }
+        }
+        None
+    }
+}
+
+/// Copy a file in chunks, reporting bytes copied so far
+pub fn copy_with_progress(source: &Path, dest: &Path, on_bytes: &mut dyn FnMut(u64)) -> std::io::Result<()> {
+    let mut reader = File::open(source)?;
+    let mut writer = File::create(dest)?;
+    let mut buffer = vec![0u8; CHUNK_BYTES];
+    let mut copied = 0u64;
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..read])?;
+        copied += read as u64;
+        on_bytes(copied);
+    }
+    writer.flush()?;
+
+    // Keep permissions (like fs::copy) and the modification time
+    let metadata = reader.metadata()?;
+    if let Ok(modified) = metadata.modified() {
+        let _ = writer.set_modified(modified);
+    }
+    drop(writer);
+    std::fs::set_permissions(dest, metadata.permissions())
+}
+
+/// Format a duration as "1h 02m", "3m 05s" or "12s"
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
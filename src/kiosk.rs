@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+/// Restrictions set by an administrator for a shared workstation. Read from a machine-wide
+/// file users can't edit: %ProgramData%\file-lister\kiosk.json or /etc/file-lister/kiosk.json.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KioskPolicy {
+    /// Delete, move and rename are disabled (as with --read-only)
+    pub read_only: bool,
+    /// Only these folders and their subfolders can be scanned, browsed or copied to (empty: any)
+    pub allowed_roots: Vec<PathBuf>,
+    /// User profiles to choose from at startup; no others can be created (empty: any)
+    pub user_profiles: Vec<String>,
+    /// Settings and retention rules can't be opened
+    pub lock_settings: bool,
+}
+
+impl KioskPolicy {
+    pub fn path() -> PathBuf {
+        let base = if cfg!(windows) {
+            std::env::var_os("ProgramData").map_or_else(|| PathBuf::from(r"C:\ProgramData"), PathBuf::from)
+        } else {
+            PathBuf::from("/etc")
+        };
+        base.join("file-lister").join("kiosk.json")
+    }
+
+    /// The machine's policy (None without a policy file). A policy file that exists but can't
+    /// be read locks the tool down instead of lifting the restrictions.
+    pub fn load() -> Option<Self> {
+        let content = match std::fs::read_to_string(Self::path()) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(_) => return Some(Self::locked()),
+        };
+        Some(serde_json::from_str(&content).unwrap_or_else(|_| Self::locked()))
+    }
+
+    fn locked() -> Self {
+        Self {
+            read_only: true,
+            lock_settings: true,
+            ..Default::default()
+        }
+    }
+
+    /// Whether a path lies in one of the allowed roots
+    pub fn allows(&self, path: &Path) -> bool {
+        within(&self.allowed_roots, path)
+    }
+
+    /// Error for a path outside the allowed roots
+    pub fn check(&self, path: &Path) -> Result<(), String> {
+        if self.allows(path) {
+            Ok(())
+        } else {
+            Err(format!("{} is outside the folders allowed on this workstation", path.display()))
+        }
+    }
+
+    /// Whether a user profile can be chosen
+    pub fn allows_profile(&self, name: Option<&str>) -> bool {
+        match name {
+            _ if self.user_profiles.is_empty() => true,
+            Some(name) => self.user_profiles.iter().any(|allowed| allowed.eq_ignore_ascii_case(name)),
+            None => false,
+        }
+    }
+}
+
+/// Whether `path` is one of `roots` or below one (any path if there are no roots).
+/// Links and ".." are resolved first, so they can't lead out of a root.
+pub fn within(roots: &[PathBuf], path: &Path) -> bool {
+    if roots.is_empty() {
+        return true;
+    }
+    let resolve = |path: &Path| std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let path = resolve(path);
+    // Not resolved (doesn't exist yet): ".." can't be checked
+    if path.components().any(|c| c == Component::ParentDir) {
+        return false;
+    }
+    roots.iter().any(|root| {
+        let root = resolve(root);
+        if cfg!(windows) {
+            // Case-insensitive file system
+            let lower = |p: &Path| PathBuf::from(p.to_string_lossy().to_lowercase());
+            lower(&path).starts_with(lower(&root))
+        } else {
+            path.starts_with(&root)
+        }
+    })
+}
@@ -1,4 +1,56 @@
+pub mod access_times;
+pub mod activity;
 pub mod app;
+pub mod clipboard;
+pub mod cloud_export;
+pub mod columns;
+pub mod compare;
+pub mod content_mix;
 pub mod csv_export;
+pub mod diagnostics;
+pub mod disk_space;
 pub mod document_parser;
+pub mod duplicates;
+pub mod elevation;
+pub mod extensions;
+pub mod extract;
 pub mod file_scanner;
+pub mod folder_notes;
+pub mod gitignore;
+pub mod hashing;
+pub mod hooks;
+pub mod html_report;
+pub mod jobs;
+pub mod kiosk;
+pub mod links;
+pub mod mail_store;
+pub mod manifest;
+pub mod manifest_check;
+pub mod mft;
+pub mod name_audit;
+pub mod notification;
+pub mod owner;
+pub mod pane;
+pub mod permissions;
+pub mod priority;
+pub mod renames;
+pub mod report_mail;
+pub mod retention;
+pub mod retry;
+pub mod reviewed;
+pub mod sample_data;
+pub mod session;
+pub mod settings;
+pub mod sidecars;
+pub mod similarity;
+pub mod snapshots;
+pub mod split;
+pub mod streams;
+pub mod text_match;
+pub mod text_stats;
+pub mod thumb_sync;
+pub mod thumbnail;
+pub mod transliterate;
+pub mod users;
+pub mod versions;
+pub mod watcher;
@@ -0,0 +1,59 @@
+use std::path::Path;
+
+/// Create a symbolic link at link_path pointing to target
+pub fn create_symlink(target: &Path, link_path: &Path) -> Result<(), String> {
+    #[cfg(unix)]
+    let result = std::os::unix::fs::symlink(target, link_path);
+
+    // Needs Developer Mode or administrator rights on Windows
+    #[cfg(windows)]
+    let result = std::os::windows::fs::symlink_file(target, link_path);
+
+    #[cfg(not(any(unix, windows)))]
+    let result: std::io::Result<()> = Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Symlinks are not supported on this platform",
+    ));
+
+    result.map_err(|e| {
+        if cfg!(windows) && e.kind() == std::io::ErrorKind::PermissionDenied {
+            format!("{} (enable Developer Mode or run as administrator)", e)
+        } else {
+            e.to_string()
+        }
+    })
+}
+
+/// Create a Windows shortcut (.lnk) at link_path pointing to target
+#[cfg(windows)]
+pub fn create_shortcut(target: &Path, link_path: &Path) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    // PowerShell single-quoted strings escape ' as ''
+    let quote = |p: &Path| format!("'{}'", p.to_string_lossy().replace('\'', "''"));
+    let script = format!(
+        "$s = (New-Object -ComObject WScript.Shell).CreateShortcut({}); $s.TargetPath = {}; $s.WorkingDirectory = {}; $s.Save()",
+        quote(link_path),
+        quote(target),
+        quote(target.parent().unwrap_or(target)),
+    );
+
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to run PowerShell: {}", e))?;
+
+    if output.status.success() && link_path.exists() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).lines().next().unwrap_or("Failed to create shortcut").to_string())
+    }
+}
+
+/// Create a Windows shortcut (.lnk) at link_path pointing to target
+#[cfg(not(windows))]
+pub fn create_shortcut(_target: &Path, _link_path: &Path) -> Result<(), String> {
+    Err("Shortcuts (.lnk) can only be created on Windows".to_string())
+}
@@ -1,11 +1,66 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod access_times;
+mod activity;
 mod app;
+mod clipboard;
+mod cloud_export;
+mod columns;
+mod compare;
+mod content_mix;
 mod csv_export;
+mod diagnostics;
+mod disk_space;
 mod document_parser;
+mod duplicates;
+mod elevation;
+mod extensions;
+mod extract;
 mod file_scanner;
+mod folder_notes;
+mod gitignore;
+mod hashing;
+mod hooks;
+mod html_report;
+mod jobs;
+mod kiosk;
+mod links;
+mod mail_store;
+mod manifest;
+mod manifest_check;
+mod mft;
+mod name_audit;
+mod notification;
+mod owner;
+mod pane;
+mod permissions;
+mod priority;
+mod renames;
+mod report_mail;
+mod retention;
+mod retry;
+mod reviewed;
+mod sample_data;
+mod session;
+mod settings;
+mod sidecars;
+mod similarity;
+mod snapshots;
+mod split;
+mod streams;
+mod text_match;
+mod text_stats;
+mod thumb_sync;
+mod thumbnail;
+mod transliterate;
+mod users;
+mod versions;
+mod watcher;
 
 use clap::Parser;
+use csv_export::{ExportColumn, ExportFormat, ExportOptions};
+use hooks::{Hook, HookEvent};
+use settings::ScanProfile;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
@@ -16,56 +71,447 @@ struct Args {
     #[arg(short, long)]
     folder: Option<PathBuf>,
 
-    /// Output CSV file path
-    #[arg(short, long, default_value = "files.csv")]
-    output: PathBuf,
+    /// Output CSV file path (default: files.csv)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
 
     /// Scan subfolders recursively
     #[arg(short, long, default_value = "false")]
     recursive: bool,
+
+    /// Leave out files and folders matched by .gitignore/.ignore files
+    #[arg(long)]
+    gitignore: bool,
+
+    /// List hidden and system files (dotfiles on Unix, Hidden/System attribute on Windows)
+    #[arg(long)]
+    include_hidden: bool,
+
+    /// Run a named scan profile from the settings file (flags given on the command line override it)
+    #[arg(short, long)]
+    profile: Option<String>,
+
+    /// List only zero-byte files and, with --recursive, empty folders (cleanup after failed syncs)
+    #[arg(long)]
+    empty: bool,
+
+    /// Scan at low CPU and I/O priority with regular pauses, whatever the settings say
+    #[arg(long)]
+    low_priority: bool,
+
+    /// Only count the files in --folder (quick pass without reading file metadata) and print the number
+    #[arg(long)]
+    count: bool,
+
+    /// Print the dry-run report of the retention rules in the settings file (add --apply to run them)
+    #[arg(long)]
+    retention: bool,
+
+    /// With --retention: move/delete the matched files and log the run
+    #[arg(long, requires = "retention")]
+    apply: bool,
+
+    /// Move the files of the last logged retention run back
+    #[arg(long, conflicts_with = "retention")]
+    undo_retention: bool,
+
+    /// Start the GUI in read-only (audit) mode: delete, move and rename are disabled and can't be re-enabled
+    #[arg(long)]
+    read_only: bool,
+
+    /// Print metadata, a base64 PNG thumbnail and the text of a file as JSON;
+    /// "-" reads one path per line from stdin and answers each with one JSON line
+    #[arg(long, value_name = "PATH")]
+    extract: Option<PathBuf>,
+
+    /// With --extract or --thumbs: maximum thumbnail width/height in pixels
+    #[arg(long, default_value = "256")]
+    thumbnail_size: u32,
+
+    /// Write a PNG thumbnail of every image, video and PDF in --folder to this folder
+    /// (same relative path plus ".png"), skipping thumbnails that are up to date
+    #[arg(long, value_name = "DIR", requires = "folder")]
+    thumbs: Option<PathBuf>,
+
+    /// With --thumbs: keep running and update the thumbnails as files arrive, change or are deleted
+    #[arg(long, requires = "thumbs")]
+    watch: bool,
+
+    /// Check --folder (with subfolders) against a manifest of expected files (CSV, TSV, JSON or
+    /// checksum file) and list missing, unexpected and mismatched files; -o saves them as CSV.
+    /// Exits with an error if any file is not as expected
+    #[arg(long, value_name = "FILE", requires = "folder")]
+    check_manifest: Option<PathBuf>,
+
+    /// Use a named user profile (own settings, bookmarks, reviewed marks, session and snapshots)
+    #[arg(long)]
+    user: Option<String>,
+
+    /// Internal: run file operations from this file (elevated helper for "Retry as administrator")
+    #[arg(long, hide = true)]
+    run_elevated_ops: Option<PathBuf>,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
 
-    if let Some(folder) = args.folder {
+    // Restrictions of a shared workstation apply to the command line too
+    let kiosk = kiosk::KioskPolicy::load();
+    let read_only = args.read_only || kiosk.as_ref().is_some_and(|k| k.read_only);
+
+    if let Some(ops_file) = &args.run_elevated_ops {
+        if read_only {
+            return Err("Read-only mode: file operations are disabled".into());
+        }
+        return elevation::run_ops_file(ops_file, &|path| check_allowed(kiosk.as_ref(), &[path.to_path_buf()]));
+    }
+
+    if let Some(name) = &args.user {
+        if kiosk.as_ref().is_some_and(|k| !k.allows_profile(Some(name))) {
+            return Err(format!("User profile '{}' is not allowed on this workstation", name).into());
+        }
+        users::switch(Some(name))?;
+    }
+
+    // Retry transient network errors while scanning and reading files (CLI and GUI)
+    let settings = settings::AppSettings::load();
+    retry::configure(settings.network_retries, settings.retry_delay_ms);
+    priority::configure(settings.background_priority || args.low_priority, settings.throttle_pause_ms);
+    text_match::configure_collation(settings.sort_collation);
+    text_match::configure_name_form(settings.name_form);
+    text_match::configure_natural_sort(settings.natural_sort);
+
+    if args.retention || args.undo_retention {
+        if read_only && (args.apply || args.undo_retention) {
+            return Err("Read-only mode: retention rules can only be reported".into());
+        }
+        return run_retention_mode(&settings, kiosk.as_ref(), args.apply, args.undo_retention);
+    }
+
+    let thumbnail_options = thumbnail::ThumbnailOptions {
+        max_size: args.thumbnail_size,
+        video_frame_secs: settings.video_frame_secs,
+        pdf_render_dpi: settings.pdf_render_dpi,
+        quality: settings.preview_quality,
+    };
+    if let Some(path) = &args.extract {
+        // Preview pipeline for other programs: JSON on stdout
+        if path.as_os_str() == "-" {
+            let check = |path: &std::path::Path| check_allowed(kiosk.as_ref(), &[path.to_path_buf()]);
+            extract::serve(std::io::stdin().lock(), std::io::stdout().lock(), &thumbnail_options, settings.preview_cloud_files, &check)?;
+        } else {
+            check_allowed(kiosk.as_ref(), std::slice::from_ref(path))?;
+            let mut generator = thumbnail::ThumbnailGenerator::default();
+            let extraction = extract::extract(path, &mut generator, &thumbnail_options, settings.preview_cloud_files);
+            println!("{}", serde_json::to_string_pretty(&extraction)?);
+        }
+        return Ok(());
+    }
+
+    if let (Some(out), Some(folder)) = (&args.thumbs, &args.folder) {
+        check_allowed(kiosk.as_ref(), std::slice::from_ref(folder))?;
+        let filter = file_scanner::ScanFilter {
+            include_hidden: args.include_hidden,
+            respect_gitignore: args.gitignore,
+        };
+        let sync = thumb_sync::ThumbnailSync::new(folder, out, thumbnail_options, settings.preview_cloud_files)?;
+        return run_thumbs_mode(&sync, folder, args.recursive, filter, args.watch);
+    }
+
+    if let (Some(manifest), Some(folder)) = (&args.check_manifest, &args.folder) {
+        check_allowed(kiosk.as_ref(), std::slice::from_ref(folder))?;
+        let filter = file_scanner::ScanFilter {
+            include_hidden: args.include_hidden,
+            respect_gitignore: args.gitignore,
+        };
+        return run_manifest_check(manifest, folder, filter, args.output.as_deref(), &settings);
+    }
+
+    if let Some(name) = &args.profile {
+        // CLI mode with a saved profile
+        let mut profile = settings.profile(name).cloned().ok_or_else(|| {
+            format!(
+                "Profile '{}' not found in {}",
+                name,
+                settings::AppSettings::settings_path().display()
+            )
+        })?;
+        if let Some(folder) = args.folder {
+            profile.folders = vec![folder];
+        }
+        if args.recursive {
+            profile.recursive = true;
+        }
+        if args.gitignore {
+            profile.respect_gitignore = true;
+        }
+        if args.include_hidden {
+            profile.include_hidden = true;
+        }
+        if args.empty {
+            profile.empty_only = true;
+        }
+        if args.output.is_some() {
+            profile.output = args.output;
+        }
+        check_allowed(kiosk.as_ref(), &profile.folders)?;
+        run_cli_mode(&profile, &settings)?;
+    } else if let (true, Some(folder)) = (args.count, &args.folder) {
+        // Quick count mode: no export
+        check_allowed(kiosk.as_ref(), std::slice::from_ref(folder))?;
+        let progress = file_scanner::ScanProgress::default();
+        let filter = file_scanner::ScanFilter {
+            include_hidden: args.include_hidden,
+            respect_gitignore: args.gitignore,
+        };
+        file_scanner::count_files(std::slice::from_ref(folder), args.recursive, filter, &progress);
+        println!("{}", progress.estimated.load(std::sync::atomic::Ordering::Relaxed));
+    } else if let Some(folder) = args.folder {
         // CLI mode: scan folder and export directly
-        run_cli_mode(folder, args.output, args.recursive)?;
+        let profile = ScanProfile {
+            folders: vec![folder],
+            recursive: args.recursive,
+            include_hidden: args.include_hidden,
+            respect_gitignore: args.gitignore,
+            empty_only: args.empty,
+            // Format by the output's extension: -o files.json writes JSON, -o report.html an HTML report
+            format: args.output.as_deref().map_or(ExportFormat::Csv, ExportFormat::from_path),
+            output: args.output,
+            ..Default::default()
+        };
+        check_allowed(kiosk.as_ref(), &profile.folders)?;
+        run_cli_mode(&profile, &settings)?;
     } else {
         // GUI mode: launch the application
-        run_gui_mode()?;
+        run_gui_mode(read_only, kiosk)?;
+    }
+
+    Ok(())
+}
+
+/// Refuse folders outside the roots allowed by the workstation policy
+fn check_allowed(kiosk: Option<&kiosk::KioskPolicy>, folders: &[PathBuf]) -> Result<(), String> {
+    match kiosk {
+        Some(kiosk) => folders.iter().try_for_each(|folder| kiosk.check(folder)),
+        None => Ok(()),
+    }
+}
+
+/// Print what a thumbnail pass did, failures on stderr
+fn print_thumb_summary(summary: &thumb_sync::SyncSummary) {
+    println!("Thumbnails: {}", summary.describe());
+    for (path, error) in &summary.failed {
+        eprintln!("  {}: {}", path.display(), error);
     }
+}
 
+fn run_thumbs_mode(
+    sync: &thumb_sync::ThumbnailSync,
+    folder: &std::path::Path,
+    recursive: bool,
+    filter: file_scanner::ScanFilter,
+    watch: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if watch {
+        println!("Watching {} for new and changed files (Ctrl+C to stop)", folder.display());
+        sync.watch(recursive, filter, &mut print_thumb_summary)?;
+    } else {
+        print_thumb_summary(&sync.sync_all(recursive, filter)?);
+    }
     Ok(())
 }
 
-fn run_cli_mode(folder: PathBuf, output: PathBuf, recursive: bool) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Scanning folder: {}", folder.display());
-    if recursive {
+/// Deliverable acceptance: check a folder against a manifest, print the differences
+fn run_manifest_check(
+    manifest: &std::path::Path,
+    folder: &std::path::Path,
+    filter: file_scanner::ScanFilter,
+    report: Option<&std::path::Path>,
+    settings: &settings::AppSettings,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Checking {} against {}", folder.display(), manifest.display());
+    priority::enter_background();
+    let mut cache = if settings.cache_hashes { hashing::HashCache::load() } else { hashing::HashCache::default() };
+    let check = manifest_check::verify(manifest, folder, filter, &mut cache, &mut |_, _| {});
+    if settings.cache_hashes {
+        let _ = cache.save();
+    }
+    let check = check?;
+    for entry in &check.entries {
+        if entry.detail.is_empty() {
+            println!("{:<10} {}", entry.status.label(), entry.path);
+        } else {
+            println!("{:<10} {}: {}", entry.status.label(), entry.path, entry.detail);
+        }
+    }
+    if let Some(report) = report {
+        manifest_check::write_report(&check, report)?;
+        println!("Report saved to: {}", report.display());
+    }
+    if check.is_clean() {
+        println!("OK: {}", check.describe());
+        Ok(())
+    } else {
+        Err(check.describe().into())
+    }
+}
+
+fn run_cli_mode(profile: &ScanProfile, settings: &settings::AppSettings) -> Result<(), Box<dyn std::error::Error>> {
+    let hooks = &settings.hooks;
+    if profile.folders.is_empty() {
+        return Err(format!("Profile '{}' has no folders", profile.name).into());
+    }
+    for folder in &profile.folders {
+        println!("Scanning folder: {}", folder.display());
+    }
+    if profile.recursive {
         println!("(including subfolders)");
     }
+    if profile.include_hidden {
+        println!("(including hidden files)");
+    }
+    if profile.respect_gitignore {
+        println!("(respecting .gitignore)");
+    }
+    priority::enter_background();
 
-    let files = file_scanner::scan_folder(&folder, recursive)?;
+    let (mut files, report) = if let [folder] = profile.folders.as_slice() {
+        file_scanner::scan_folder_with_report(folder, profile.recursive, profile.scan_filter())?
+    } else {
+        file_scanner::scan_folders(&profile.folders, profile.recursive, profile.scan_filter())
+    };
     println!("Found {} files", files.len());
+    if !report.is_empty() {
+        eprintln!("{}:", report.summary());
+        for error in &report.errors {
+            eprintln!("  {}: {}", error.path.display(), error.error);
+        }
+    }
+    let retries = retry::take_thread_retries();
+    if retries > 0 {
+        println!("Retried {} network reads", retries);
+    }
+
+    if !profile.excludes.is_empty() {
+        let before = files.len();
+        files.retain(|f| !file_scanner::is_excluded(f, &profile.excludes));
+        println!("Excluded {} files", before - files.len());
+    }
+    if profile.empty_only {
+        files.retain(|f| f.file_size == 0);
+        println!("{} empty files, {} empty folders", files.len(), report.empty_folders.len());
+        files.splice(0..0, report.empty_folders.iter().cloned());
+    }
+    run_hooks(hooks, HookEvent::Scan, &profile.folders, &files, &[]);
+
+    let options = ExportOptions {
+        size_format: settings.size_format,
+        ..profile.export_options(settings.calendar, &settings.format_columns)
+    };
+    if options.columns.contains(&ExportColumn::Hash) {
+        println!("Hashing files ({})...", settings.hash_algorithm.label());
+        let failed = hashing::fill_hashes(&mut files, settings.hash_algorithm, settings.cache_hashes);
+        if failed > 0 {
+            println!("{} files could not be read", failed);
+        }
+    }
+    if options.columns.iter().any(|c| matches!(c, ExportColumn::Lines | ExportColumn::Words)) {
+        println!("Counting lines and words...");
+        let failed = text_stats::fill_text_counts(&mut files);
+        if failed > 0 {
+            println!("{} text files could not be read", failed);
+        }
+    }
 
-    csv_export::export_to_csv(&files, &output)?;
-    println!("Exported to: {}", output.display());
+    let output = profile.output_path();
+    let written = csv_export::export_with_options(&files, &options, &output)?;
+    for path in &written {
+        println!("Exported to: {}", path.display());
+    }
+    let outputs: Vec<String> = written.iter().map(|p| p.display().to_string()).collect();
+    run_hooks(hooks, HookEvent::Export, &profile.folders, &files, &outputs);
 
     Ok(())
 }
 
-fn run_gui_mode() -> Result<(), Box<dyn std::error::Error>> {
+/// Run the configured hooks and wait for them; a failed hook is reported but doesn't fail the run
+fn run_hooks(hooks: &[Hook], event: HookEvent, folders: &[PathBuf], files: &[file_scanner::FileInfo], outputs: &[String]) {
+    let summary = hooks::summary(event, "cli", folders, files, outputs);
+    for error in hooks::run(hooks, event, &summary) {
+        eprintln!("{}", error);
+    }
+}
+
+/// Retention rules from the command line (for Task Scheduler or cron): report, apply or undo
+fn run_retention_mode(
+    settings: &settings::AppSettings,
+    kiosk: Option<&kiosk::KioskPolicy>,
+    apply: bool,
+    undo: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Rules and the files of the run to undo must stay within the allowed folders
+    let folders: Vec<PathBuf> = if undo {
+        let log = retention::RetentionLog::load();
+        let moves = log.last_undoable().map(|run| run.moves()).unwrap_or_default();
+        moves.into_iter().flat_map(|(destination, source)| [destination, source]).collect()
+    } else {
+        let rules = settings.retention_rules.iter().filter(|rule| rule.enabled);
+        rules.flat_map(|rule| rule.folders()).map(PathBuf::from).collect()
+    };
+    check_allowed(kiosk, &folders)?;
+
+    if undo {
+        let (restored, errors) = retention::undo_last()?;
+        for error in &errors {
+            eprintln!("{}", error);
+        }
+        println!("Moved back {} files ({} failed)", restored, errors.len());
+    } else if apply {
+        let run = retention::run_now(&settings.retention_rules)?;
+        for entry in &run.entries {
+            if let Some(error) = &entry.error {
+                eprintln!("{}: {}", entry.source.display(), error);
+            }
+        }
+        println!("Retention run: {}", run.summary());
+    } else {
+        let items = retention::plan(&settings.retention_rules)?;
+        print!(
+            "{}",
+            retention::report(&settings.retention_rules, &items, &settings.size_format, settings.calendar)
+        );
+    }
+    Ok(())
+}
+
+fn run_gui_mode(read_only: bool, kiosk: Option<kiosk::KioskPolicy>) -> Result<(), Box<dyn std::error::Error>> {
+    let settings = settings::AppSettings::load();
+
+    let mut viewport = eframe::egui::ViewportBuilder::default()
+        .with_inner_size([1000.0, 600.0])
+        .with_min_inner_size([600.0, 400.0])
+        .with_window_level(if settings.always_on_top {
+            eframe::egui::WindowLevel::AlwaysOnTop
+        } else {
+            eframe::egui::WindowLevel::Normal
+        });
+
+    // Restore window placement from the monitor used last
+    if let Some(placement) = settings.last_placement() {
+        viewport = viewport
+            .with_position([placement.x, placement.y])
+            .with_inner_size([placement.width, placement.height]);
+    }
+
     let options = eframe::NativeOptions {
-        viewport: eframe::egui::ViewportBuilder::default()
-            .with_inner_size([1000.0, 600.0])
-            .with_min_inner_size([600.0, 400.0]),
+        viewport,
         ..Default::default()
     };
 
     eframe::run_native(
         "File Lister",
         options,
-        Box::new(|cc| Ok(Box::new(app::FileListerApp::new(cc)))),
+        Box::new(|cc| Ok(Box::new(app::FileListerApp::new(cc, settings, read_only, kiosk)))),
     )?;
 
     Ok(())
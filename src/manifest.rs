@@ -0,0 +1,266 @@
+use crate::file_scanner::FileInfo;
+use crate::hashing::HashCache;
+use crate::priority;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// Message from the background manifest thread
+pub enum ManifestMessage {
+    Progress { done: usize, total: usize },
+    /// Files listed, or an error
+    Finished(Result<usize, String>),
+}
+
+/// Path of `target` relative to `base` ("../Music/a.mp3"); None if they share no root (other drive)
+pub fn relative_path(base: &Path, target: &Path) -> Option<PathBuf> {
+    let base: Vec<Component> = base.components().collect();
+    let target: Vec<Component> = target.components().collect();
+    if base.first() != target.first() {
+        return None;
+    }
+    let common = base.iter().zip(&target).take_while(|(a, b)| a == b).count();
+    let mut relative = PathBuf::new();
+    for _ in common..base.len() {
+        relative.push("..");
+    }
+    for component in &target[common..] {
+        relative.push(component);
+    }
+    Some(relative)
+}
+
+/// Path as written into a playlist or manifest next to `output`: relative (with forward slashes,
+/// which players and sha256sum accept on every platform) or absolute
+fn listed_path(output: &Path, file: &FileInfo, relative: bool) -> String {
+    let absolute = Path::new(file.absolute_path());
+    let folder = output.parent().and_then(|p| p.canonicalize().ok());
+    match folder.filter(|_| relative).and_then(|folder| relative_path(&folder, absolute)) {
+        Some(path) => path.to_string_lossy().replace('\\', "/"),
+        None => file.absolute_path().to_string(),
+    }
+}
+
+/// Write an extended M3U playlist (UTF-8, as .m3u8 expects). Files on another drive keep absolute paths.
+pub fn write_playlist(files: &[FileInfo], output: &Path, relative: bool) -> Result<(), String> {
+    let file = File::create(output).map_err(|e| format!("Failed to create playlist: {}", e))?;
+    let mut writer = BufWriter::new(file);
+    let mut write = || -> std::io::Result<()> {
+        writeln!(writer, "#EXTM3U")?;
+        for file in files {
+            // Duration -1: unknown (players read it from the file)
+            writeln!(writer, "#EXTINF:-1,{}", file.name())?;
+            writeln!(writer, "{}", listed_path(output, file, relative))?;
+        }
+        writer.flush()
+    };
+    write().map_err(|e| format!("Failed to write playlist: {}", e))
+}
+
+/// Write a manifest of paths, sizes and SHA-256 hashes on a background thread.
+/// A `.sha256` output uses the `sha256sum` format (checkable with `sha256sum -c`), anything else CSV.
+pub fn spawn_manifest(files: Vec<FileInfo>, output: PathBuf, use_cache: bool) -> Receiver<ManifestMessage> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        priority::enter_background();
+        let mut cache = if use_cache { HashCache::load() } else { HashCache::default() };
+        let result = write_manifest(&files, &output, &mut cache, &mut |done| {
+            let _ = tx.send(ManifestMessage::Progress { done, total: files.len() });
+        });
+        if use_cache {
+            let _ = cache.save();
+        }
+        if result.is_err() {
+            let _ = std::fs::remove_file(&output);
+        }
+        let _ = tx.send(ManifestMessage::Finished(result.map(|_| files.len())));
+    });
+    rx
+}
+
+fn write_manifest(
+    files: &[FileInfo],
+    output: &Path,
+    cache: &mut HashCache,
+    on_progress: &mut dyn FnMut(usize),
+) -> Result<(), String> {
+    let checksum_format = output
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("sha256"));
+    let file = File::create(output).map_err(|e| format!("Failed to create manifest: {}", e))?;
+    let mut writer = BufWriter::new(file);
+
+    let mut hashed = files.iter().enumerate().map(|(done, file)| {
+        if done % 16 == 0 {
+            on_progress(done);
+        }
+        let hash = cache
+            .sha256(file)
+            .map_err(|e| format!("Failed to hash {}: {}", file.absolute_path(), e))?;
+        Ok::<_, String>((listed_path(output, file, true), file.file_size, hash))
+    });
+
+    if checksum_format {
+        for entry in &mut hashed {
+            let (path, _, hash) = entry?;
+            writeln!(writer, "{}  {}", hash, path).map_err(|e| e.to_string())?;
+        }
+        return writer.flush().map_err(|e| format!("Failed to write manifest: {}", e));
+    }
+
+    // UTF-8 BOM for Excel, like the CSV export
+    writer.write_all(&[0xEF, 0xBB, 0xBF]).map_err(|e| e.to_string())?;
+    let mut csv = csv::Writer::from_writer(writer);
+    csv.write_record(["Path", "Size (bytes)", "SHA-256"]).map_err(|e| e.to_string())?;
+    for entry in hashed {
+        let (path, size, hash) = entry?;
+        csv.write_record([path, size.to_string(), hash]).map_err(|e| e.to_string())?;
+    }
+    csv.flush().map_err(|e| format!("Failed to write manifest: {}", e))
+}
+
+/// Fit to target: of `files` (index, size), the largest ones that still fit into `capacity` after `used` bytes
+pub fn fit_largest_first(files: &[(usize, u64)], used: u64, capacity: u64) -> Vec<usize> {
+    let mut candidates = files.to_vec();
+    candidates.sort_by_key(|&(_, size)| std::cmp::Reverse(size));
+    let mut total = used;
+    let mut picked = Vec::new();
+    for (idx, size) in candidates {
+        if total + size <= capacity {
+            total += size;
+            picked.push(idx);
+        }
+    }
+    picked
+}
+
+/// Medium number (from 1) for each size, filling one medium after another with fit_largest_first.
+/// Err with the indices of files larger than a whole medium.
+pub fn group_into_media(sizes: &[u64], capacity: u64) -> Result<Vec<usize>, Vec<usize>> {
+    let too_large: Vec<usize> = (0..sizes.len()).filter(|&idx| sizes[idx] > capacity).collect();
+    if !too_large.is_empty() {
+        return Err(too_large);
+    }
+    let mut media = vec![0; sizes.len()];
+    let mut left: Vec<(usize, u64)> = sizes.iter().copied().enumerate().collect();
+    let mut medium = 0;
+    while !left.is_empty() {
+        medium += 1;
+        for idx in fit_largest_first(&left, 0, capacity) {
+            media[idx] = medium;
+        }
+        left.retain(|&(idx, _)| media[idx] == 0);
+    }
+    Ok(media)
+}
+
+/// Write a burn list for archiving tools on a background thread: the files grouped into media of
+/// `capacity` bytes, with paths, sizes and SHA-256. A `.xml` output is XML, anything else CSV.
+pub fn spawn_burn_list(files: Vec<FileInfo>, output: PathBuf, capacity: u64, use_cache: bool) -> Receiver<ManifestMessage> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        priority::enter_background();
+        let mut cache = if use_cache { HashCache::load() } else { HashCache::default() };
+        let result = write_burn_list(&files, &output, capacity, &mut cache, &mut |done| {
+            let _ = tx.send(ManifestMessage::Progress { done, total: files.len() });
+        });
+        if use_cache {
+            let _ = cache.save();
+        }
+        if result.is_err() {
+            let _ = std::fs::remove_file(&output);
+        }
+        let _ = tx.send(ManifestMessage::Finished(result.map(|_| files.len())));
+    });
+    rx
+}
+
+fn write_burn_list(
+    files: &[FileInfo],
+    output: &Path,
+    capacity: u64,
+    cache: &mut HashCache,
+    on_progress: &mut dyn FnMut(usize),
+) -> Result<(), String> {
+    let sizes: Vec<u64> = files.iter().map(|f| f.file_size).collect();
+    let media = group_into_media(&sizes, capacity).map_err(|too_large| {
+        let names: Vec<&str> = too_large.iter().take(3).map(|&idx| files[idx].full_name()).collect();
+        format!(
+            "{} files are larger than one medium ({}...); split them first",
+            too_large.len(),
+            names.join(", ")
+        )
+    })?;
+    // Hash everything before writing, so a failure leaves no half-written list
+    let mut hashes = Vec::with_capacity(files.len());
+    for (done, file) in files.iter().enumerate() {
+        if done % 16 == 0 {
+            on_progress(done);
+        }
+        let hash = cache
+            .sha256(file)
+            .map_err(|e| format!("Failed to hash {}: {}", file.absolute_path(), e))?;
+        hashes.push(hash);
+    }
+    let mut order: Vec<usize> = (0..files.len()).collect();
+    order.sort_by_key(|&idx| (media[idx], files[idx].relative_path().to_lowercase()));
+
+    let file = File::create(output).map_err(|e| format!("Failed to create burn list: {}", e))?;
+    let mut writer = BufWriter::new(file);
+    let xml = output.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("xml"));
+    if !xml {
+        // UTF-8 BOM for Excel, like the CSV export
+        writer.write_all(&[0xEF, 0xBB, 0xBF]).map_err(|e| e.to_string())?;
+        let mut csv = csv::Writer::from_writer(writer);
+        csv.write_record(["Medium", "Path", "Relative path", "Size (bytes)", "SHA-256"])
+            .map_err(|e| e.to_string())?;
+        for idx in order {
+            let file = &files[idx];
+            csv.write_record([
+                media[idx].to_string(),
+                file.absolute_path().to_string(),
+                file.relative_path().to_string(),
+                file.file_size.to_string(),
+                hashes[idx].clone(),
+            ])
+            .map_err(|e| e.to_string())?;
+        }
+        return csv.flush().map_err(|e| format!("Failed to write burn list: {}", e));
+    }
+
+    let media_count = media.iter().copied().max().unwrap_or(0);
+    let mut write = || -> std::io::Result<()> {
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(writer, r#"<burnlist capacity="{}" media="{}">"#, capacity, media_count)?;
+        for medium in 1..=media_count {
+            let on_medium: Vec<usize> = order.iter().copied().filter(|&idx| media[idx] == medium).collect();
+            let used: u64 = on_medium.iter().map(|&idx| files[idx].file_size).sum();
+            writeln!(writer, r#"  <medium number="{}" files="{}" size="{}">"#, medium, on_medium.len(), used)?;
+            for idx in on_medium {
+                let file = &files[idx];
+                writeln!(
+                    writer,
+                    r#"    <file path="{}" relative="{}" size="{}" sha256="{}"/>"#,
+                    xml_escape(file.absolute_path()),
+                    xml_escape(file.relative_path()),
+                    file.file_size,
+                    hashes[idx]
+                )?;
+            }
+            writeln!(writer, "  </medium>")?;
+        }
+        writeln!(writer, "</burnlist>")?;
+        writer.flush()
+    };
+    write().map_err(|e| format!("Failed to write burn list: {}", e))
+}
+
+/// Escape text for an XML attribute
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,466 @@
+use crate::file_scanner::{self, FileInfo, ScanFilter};
+use crate::hashing::{HashAlgorithm, HashCache};
+use crate::priority;
+use crate::text_match;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// Headers naming the path column, most specific first
+const PATH_HEADERS: [&str; 6] = ["relative path", "relative_path", "path", "file", "file name", "name"];
+const SIZE_HEADERS: [&str; 4] = ["size (bytes)", "size", "bytes", "file_size"];
+const HASH_HEADERS: [&str; 8] = ["sha-256", "sha256", "sha-1", "sha1", "md5", "hash", "checksum", "sha256sum"];
+
+/// A file the manifest requires
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpectedFile {
+    /// Relative to the checked folder, with "/" separators
+    pub path: String,
+    pub size: Option<u64>,
+    /// Lowercase hex; MD5, SHA-1 or SHA-256 by its length
+    pub hash: Option<String>,
+}
+
+/// Algorithm of a hex checksum, by its length
+fn hash_algorithm(hash: &str) -> Option<HashAlgorithm> {
+    match hash.len() {
+        32 => Some(HashAlgorithm::Md5),
+        40 => Some(HashAlgorithm::Sha1),
+        64 => Some(HashAlgorithm::Sha256),
+        _ => None,
+    }
+}
+
+/// "./Docs\\a.pdf" -> "Docs/a.pdf"; an absolute path inside `folder` becomes relative to it
+fn manifest_path(path: &str, folder: &Path) -> String {
+    let path = path.trim();
+    let relative = Path::new(path).strip_prefix(folder).map_or(path.to_string(), |p| p.to_string_lossy().into_owned());
+    let relative = relative.replace('\\', "/");
+    relative.trim_start_matches("./").trim_start_matches('/').to_string()
+}
+
+/// How two relative paths are matched: Unicode form as configured, case-insensitively where the
+/// file system is (Windows, macOS)
+fn path_key(path: &str) -> String {
+    let path = text_match::normalize_name(&path.replace('\\', "/")).into_owned();
+    if cfg!(any(windows, target_os = "macos")) {
+        path.to_lowercase()
+    } else {
+        path
+    }
+}
+
+fn expected_hash(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_lowercase())
+}
+
+/// Read the expected files: CSV or TSV with a header row (path column required, size and hash
+/// optional), JSON (paths, or objects with path, size and hash), or a checksum list
+/// ("hash  path" lines, as sha256sum writes them)
+pub fn load_expected(manifest: &Path, folder: &Path) -> Result<Vec<ExpectedFile>, String> {
+    let text = std::fs::read_to_string(manifest).map_err(|e| format!("Failed to read {}: {}", manifest.display(), e))?;
+    let text = text.trim_start_matches('\u{feff}');
+    let extension = manifest.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let files = match extension.as_str() {
+        "json" => parse_json(text, folder)?,
+        "sha256" | "sha1" | "md5" | "txt" => parse_checksums(text, folder)?,
+        "tsv" => parse_csv(text, b'\t', folder)?,
+        _ => parse_csv(text, b',', folder)?,
+    };
+    if files.is_empty() {
+        return Err(format!("{} lists no files", manifest.display()));
+    }
+    Ok(files)
+}
+
+fn parse_csv(text: &str, delimiter: u8, folder: &Path) -> Result<Vec<ExpectedFile>, String> {
+    let mut reader = csv::ReaderBuilder::new().delimiter(delimiter).flexible(true).from_reader(text.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let column = |names: &[&str]| names.iter().find_map(|name| headers.iter().position(|h| h == name));
+    let path_column = column(&PATH_HEADERS).ok_or("No path column (Path, Relative path or File) in the header row")?;
+    let (size_column, hash_column) = (column(&SIZE_HEADERS), column(&HASH_HEADERS));
+
+    let mut files = Vec::new();
+    for (line, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| e.to_string())?;
+        let path = record.get(path_column).unwrap_or_default();
+        if path.trim().is_empty() {
+            continue;
+        }
+        let size = match size_column.and_then(|c| record.get(c)).map(str::trim).filter(|s| !s.is_empty()) {
+            Some(size) => Some(size.parse().map_err(|_| format!("Row {}: size \"{}\" is not a number", line + 2, size))?),
+            None => None,
+        };
+        files.push(ExpectedFile {
+            path: manifest_path(path, folder),
+            size,
+            hash: hash_column.and_then(|c| record.get(c)).and_then(expected_hash),
+        });
+    }
+    Ok(files)
+}
+
+fn parse_json(text: &str, folder: &Path) -> Result<Vec<ExpectedFile>, String> {
+    let value: Value = serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let entries = match &value {
+        Value::Array(entries) => entries,
+        Value::Object(object) => object
+            .get("files")
+            .and_then(Value::as_array)
+            .ok_or("Expected a list of files or an object with \"files\"")?,
+        _ => return Err("Expected a list of files or an object with \"files\"".to_string()),
+    };
+    entries
+        .iter()
+        .map(|entry| {
+            if let Some(path) = entry.as_str() {
+                return Ok(ExpectedFile { path: manifest_path(path, folder), size: None, hash: None });
+            }
+            let field = |names: &[&str]| names.iter().find_map(|name| entry.get(*name));
+            let path = field(&["relative_path", "path", "file", "name"])
+                .and_then(Value::as_str)
+                .ok_or_else(|| format!("Entry without a path: {}", entry))?;
+            Ok(ExpectedFile {
+                path: manifest_path(path, folder),
+                size: field(&["size", "bytes", "file_size"]).and_then(Value::as_u64),
+                hash: field(&["sha256", "sha1", "md5", "hash", "checksum"]).and_then(Value::as_str).and_then(expected_hash),
+            })
+        })
+        .collect()
+}
+
+fn parse_checksums(text: &str, folder: &Path) -> Result<Vec<ExpectedFile>, String> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (hash, path) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| format!("Not a \"hash  path\" line: {}", line))?;
+            // sha256sum marks binary mode with "*" before the name
+            let path = path.trim_start().trim_start_matches('*');
+            Ok(ExpectedFile {
+                path: manifest_path(path, folder),
+                size: None,
+                hash: expected_hash(hash),
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckStatus {
+    Missing,
+    Mismatched,
+    Unexpected,
+}
+
+impl CheckStatus {
+    pub const ALL: [CheckStatus; 3] = [CheckStatus::Missing, CheckStatus::Mismatched, CheckStatus::Unexpected];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CheckStatus::Missing => "Missing",
+            CheckStatus::Mismatched => "Mismatched",
+            CheckStatus::Unexpected => "Unexpected",
+        }
+    }
+}
+
+/// A file that is not as the manifest says
+#[derive(Debug, Clone)]
+pub struct CheckEntry {
+    pub status: CheckStatus,
+    /// As in the manifest, or relative to the folder for unexpected files
+    pub path: String,
+    /// Found file (mismatched and unexpected files)
+    pub absolute_path: Option<String>,
+    /// What differs: "size 120 bytes, expected 125", "SHA-256 differs"
+    pub detail: String,
+}
+
+/// Result of checking a folder against a manifest
+#[derive(Debug, Clone, Default)]
+pub struct ManifestCheck {
+    pub folder: PathBuf,
+    pub expected: usize,
+    /// Expected files present with the expected size and hash
+    pub matched: usize,
+    /// Sorted by status, then path
+    pub entries: Vec<CheckEntry>,
+}
+
+impl ManifestCheck {
+    pub fn count(&self, status: CheckStatus) -> usize {
+        self.entries.iter().filter(|e| e.status == status).count()
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// "120 expected: 117 OK, 1 missing, 2 mismatched, 4 unexpected"
+    pub fn describe(&self) -> String {
+        format!(
+            "{} expected: {} OK, {} missing, {} mismatched, {} unexpected",
+            self.expected,
+            self.matched,
+            self.count(CheckStatus::Missing),
+            self.count(CheckStatus::Mismatched),
+            self.count(CheckStatus::Unexpected)
+        )
+    }
+}
+
+/// Compare the files of `folder` (scanned recursively) with the expected ones. Sizes are compared
+/// where the manifest has them, hashes (through the cache) where it has them and the size matched.
+pub fn check(
+    folder: &Path,
+    expected: &[ExpectedFile],
+    files: &[FileInfo],
+    cache: &mut HashCache,
+    on_progress: &mut dyn FnMut(usize, usize),
+) -> ManifestCheck {
+    let folder_files: Vec<(&FileInfo, String)> = files
+        .iter()
+        .filter(|f| !f.is_dir)
+        .map(|f| (f, f.relative_path().replace('\\', "/")))
+        .collect();
+    let mut found: HashMap<String, usize> =
+        folder_files.iter().enumerate().map(|(idx, (_, relative))| (path_key(relative), idx)).collect();
+
+    let mut result = ManifestCheck {
+        folder: folder.to_path_buf(),
+        ..Default::default()
+    };
+    let mut seen = std::collections::HashSet::new();
+    for (done, wanted) in expected.iter().enumerate() {
+        if done % 16 == 0 {
+            on_progress(done, expected.len());
+        }
+        let key = path_key(&wanted.path);
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+        result.expected += 1;
+        let Some(idx) = found.remove(&key) else {
+            result.entries.push(CheckEntry {
+                status: CheckStatus::Missing,
+                path: wanted.path.clone(),
+                absolute_path: None,
+                detail: String::new(),
+            });
+            continue;
+        };
+        let file = folder_files[idx].0;
+        match difference(wanted, file, cache) {
+            None => result.matched += 1,
+            Some(detail) => result.entries.push(CheckEntry {
+                status: CheckStatus::Mismatched,
+                path: wanted.path.clone(),
+                absolute_path: Some(file.absolute_path().to_string()),
+                detail,
+            }),
+        }
+    }
+    for idx in found.into_values() {
+        let (file, relative) = &folder_files[idx];
+        result.entries.push(CheckEntry {
+            status: CheckStatus::Unexpected,
+            path: relative.clone(),
+            absolute_path: Some(file.absolute_path().to_string()),
+            detail: String::new(),
+        });
+    }
+    result
+        .entries
+        .sort_by(|a, b| a.status.cmp(&b.status).then_with(|| text_match::compare_natural(&a.path, &b.path)));
+    result
+}
+
+/// How a found file differs from the manifest entry; None if it doesn't
+fn difference(wanted: &ExpectedFile, file: &FileInfo, cache: &mut HashCache) -> Option<String> {
+    if let Some(size) = wanted.size.filter(|&size| size != file.file_size) {
+        return Some(format!("size {} bytes, expected {}", file.file_size, size));
+    }
+    let hash = wanted.hash.as_ref()?;
+    let Some(algorithm) = hash_algorithm(hash) else {
+        return Some(format!("unknown checksum \"{}\" (not MD5, SHA-1 or SHA-256)", hash));
+    };
+    priority::throttle();
+    match cache.hash(file, algorithm) {
+        Ok(actual) if actual == *hash => None,
+        Ok(_) => Some(format!("{} differs", algorithm.label())),
+        Err(e) => Some(format!("could not be read: {}", e)),
+    }
+}
+
+/// Message from the background check
+pub enum VerifyMessage {
+    Progress { done: usize, total: usize },
+    Finished(Result<ManifestCheck, String>),
+}
+
+/// Read the manifest, scan `folder` recursively and check it. The manifest itself is not
+/// reported as unexpected when it lies in the folder.
+pub fn verify(
+    manifest: &Path,
+    folder: &Path,
+    filter: ScanFilter,
+    cache: &mut HashCache,
+    on_progress: &mut dyn FnMut(usize, usize),
+) -> Result<ManifestCheck, String> {
+    let expected = load_expected(manifest, folder)?;
+    let (mut files, _) = file_scanner::scan_folder_with_report(folder, true, filter)
+        .map_err(|e| format!("Failed to scan {}: {}", folder.display(), e))?;
+    if let (Ok(manifest), Ok(root)) = (manifest.canonicalize(), folder.canonicalize()) {
+        if let Ok(relative) = manifest.strip_prefix(&root) {
+            files.retain(|f| Path::new(f.relative_path()) != relative);
+        }
+    }
+    Ok(check(folder, &expected, &files, cache, on_progress))
+}
+
+/// Run `verify` on a background thread
+pub fn spawn_verify(manifest: PathBuf, folder: PathBuf, filter: ScanFilter, use_cache: bool) -> Receiver<VerifyMessage> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        priority::enter_background();
+        let mut cache = if use_cache { HashCache::load() } else { HashCache::default() };
+        let result = verify(&manifest, &folder, filter, &mut cache, &mut |done, total| {
+            let _ = tx.send(VerifyMessage::Progress { done, total });
+        });
+        if use_cache {
+            let _ = cache.save();
+        }
+        let _ = tx.send(VerifyMessage::Finished(result));
+    });
+    rx
+}
+
+/// Write the files that are not as expected as CSV: Status, Path, Detail
+pub fn write_report(check: &ManifestCheck, path: &Path) -> Result<(), String> {
+    let mut file = std::fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    // UTF-8 BOM for Excel, like the CSV export
+    file.write_all(&[0xEF, 0xBB, 0xBF]).map_err(|e| e.to_string())?;
+    let mut csv = csv::Writer::from_writer(file);
+    csv.write_record(["Status", "Path", "Detail"]).map_err(|e| e.to_string())?;
+    for entry in &check.entries {
+        csv.write_record([entry.status.label(), &entry.path, &entry.detail]).map_err(|e| e.to_string())?;
+    }
+    csv.flush().map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    /// Empty folder under the temp directory for one test
+    fn temp_folder(name: &str) -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("file-lister-manifest-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&folder);
+        std::fs::create_dir_all(&folder).unwrap();
+        folder
+    }
+
+    fn expected(path: &str, size: Option<u64>, hash: Option<&str>) -> ExpectedFile {
+        ExpectedFile {
+            path: path.to_string(),
+            size,
+            hash: hash.map(str::to_string),
+        }
+    }
+
+    /// Write `text` as a manifest file and load it for `folder`
+    fn load(root: &Path, name: &str, text: &str, folder: &Path) -> Result<Vec<ExpectedFile>, String> {
+        let manifest = root.join(name);
+        std::fs::write(&manifest, text).unwrap();
+        load_expected(&manifest, folder)
+    }
+
+    #[test]
+    fn loads_csv_tsv_json_and_checksum_lists() {
+        let root = temp_folder("load");
+        let folder = Path::new("/data/delivery");
+
+        let csv = "\u{feff}File,Size (bytes),SHA-256\n./Docs\\a.pdf,125,ABC\n,,\n/data/delivery/b.txt,,\n";
+        assert_eq!(
+            load(&root, "list.csv", csv, folder).unwrap(),
+            [expected("Docs/a.pdf", Some(125), Some("abc")), expected("b.txt", None, None)]
+        );
+
+        let tsv = "Name\tBytes\na.pdf\t7\n";
+        assert_eq!(load(&root, "list.tsv", tsv, folder).unwrap(), [expected("a.pdf", Some(7), None)]);
+
+        let json = r#"{"files": ["a.pdf", {"relative_path": "Docs/b.txt", "size": 3, "sha256": "ABC"}]}"#;
+        assert_eq!(
+            load(&root, "list.json", json, folder).unwrap(),
+            [expected("a.pdf", None, None), expected("Docs/b.txt", Some(3), Some("abc"))]
+        );
+
+        let checksums = format!("# made by sha256sum\n{}  Docs/a.pdf\n{} *b.bin\n", ABC_SHA256, ABC_SHA256);
+        assert_eq!(
+            load(&root, "list.sha256", &checksums, folder).unwrap(),
+            [expected("Docs/a.pdf", None, Some(ABC_SHA256)), expected("b.bin", None, Some(ABC_SHA256))]
+        );
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn rejects_unusable_manifests() {
+        let root = temp_folder("errors");
+        let folder = Path::new("");
+        assert!(load(&root, "list.csv", "Owner,Date\nme,today\n", folder).unwrap_err().starts_with("No path column"));
+        assert_eq!(load(&root, "list.csv", "Path,Size\na.pdf,big\n", folder).unwrap_err(), "Row 2: size \"big\" is not a number");
+        assert!(load(&root, "list.csv", "Path\n", folder).unwrap_err().ends_with("lists no files"));
+        assert!(load(&root, "list.json", "42", folder).is_err());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn reports_missing_mismatched_and_unexpected_files() {
+        let folder = temp_folder("check");
+        std::fs::create_dir_all(folder.join("Docs")).unwrap();
+        std::fs::write(folder.join("Docs/a.txt"), "abc").unwrap();
+        std::fs::write(folder.join("b.txt"), "abc").unwrap();
+        std::fs::write(folder.join("c.txt"), "abcd").unwrap();
+        std::fs::write(folder.join("d.txt"), "xyz").unwrap();
+        std::fs::write(folder.join("extra.txt"), "").unwrap();
+        let files = file_scanner::scan_folder(&folder, true, ScanFilter::default()).unwrap();
+
+        let wanted = [
+            expected("Docs/a.txt", Some(3), Some(ABC_SHA256)),
+            expected("b.txt", None, None),
+            // Listed twice: counted once
+            expected("b.txt", None, None),
+            expected("c.txt", Some(3), None),
+            expected("d.txt", None, Some(ABC_SHA256)),
+            expected("gone.txt", None, None),
+        ];
+        let result = check(&folder, &wanted, &files, &mut HashCache::default(), &mut |_, _| {});
+        assert_eq!(result.expected, 5);
+        assert_eq!(result.matched, 2);
+        let entries: Vec<(CheckStatus, &str, &str)> =
+            result.entries.iter().map(|e| (e.status, e.path.as_str(), e.detail.as_str())).collect();
+        assert_eq!(
+            entries,
+            [
+                (CheckStatus::Missing, "gone.txt", ""),
+                (CheckStatus::Mismatched, "c.txt", "size 4 bytes, expected 3"),
+                (CheckStatus::Mismatched, "d.txt", "SHA-256 differs"),
+                (CheckStatus::Unexpected, "extra.txt", ""),
+            ]
+        );
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+}
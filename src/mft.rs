@@ -0,0 +1,492 @@
+use crate::file_scanner::FileInfo;
+use std::path::PathBuf;
+
+/// List the files under each folder from the NTFS master file table instead of walking the folders.
+/// Returns None when a folder isn't on a local NTFS volume or the volume can't be opened (needs
+/// administrator rights); the caller then falls back to the folder walker.
+/// The index of each volume is kept, and later scans only apply the changes recorded in the USN
+/// journal since then; the number of changed files is returned (None if a volume was read in full).
+/// Metadata is read per file, but only again for files the journal reports as changed.
+/// Without `include_hidden`, files and folders with the Hidden or System attribute are left out.
+#[cfg(windows)]
+pub fn scan(folders: &[PathBuf], recursive: bool, include_hidden: bool) -> Option<(Vec<FileInfo>, Option<usize>)> {
+    let mut volumes = VOLUMES.lock().unwrap_or_else(|e| e.into_inner());
+    let mut refreshed: Vec<String> = Vec::new();
+    let mut journal_changes = Some(0);
+    let mut all_files = Vec::new();
+    for folder in folders {
+        let folder = folder.canonicalize().ok()?;
+        let device = sys::volume_device(&folder)?;
+
+        // Folders on the same drive share one refresh of its index
+        if !refreshed.contains(&device) {
+            let changes = volumes
+                .iter_mut()
+                .find(|volume| volume.device == device)
+                .and_then(sys::apply_journal);
+            match changes {
+                Some(count) => journal_changes = journal_changes.map(|total| total + count),
+                None => {
+                    // First scan of the drive, or the journal was reset or wrapped: read the whole MFT
+                    let volume = sys::read_volume(&device)?;
+                    volumes.retain(|v| v.device != device);
+                    volumes.push(volume);
+                    journal_changes = None;
+                }
+            }
+            refreshed.push(device.clone());
+        }
+        let volume = volumes.iter_mut().find(|volume| volume.device == device)?;
+        let root = sys::file_reference(&folder)?;
+
+        let mut folder_files: Vec<FileInfo> = volume
+            .index
+            .files_under(root, &folder, recursive, include_hidden)
+            .into_iter()
+            .map(|(reference, path)| {
+                crate::priority::throttle();
+                volume.file_info(reference, &path, &folder)
+            })
+            .collect();
+        crate::file_scanner::tag_source_folder(&mut folder_files, &folder);
+        all_files.extend(folder_files);
+    }
+
+    // Same order as the folder walker
+    all_files.sort_by(|a, b| crate::text_match::compare_natural(a.relative_path(), b.relative_path()));
+    Some((all_files, journal_changes))
+}
+
+/// Indexes of the volumes scanned so far, refreshed from their USN journals
+#[cfg(windows)]
+static VOLUMES: std::sync::Mutex<Vec<index::Volume>> = std::sync::Mutex::new(Vec::new());
+
+/// MFT enumeration is only available on Windows
+#[cfg(not(windows))]
+pub fn scan(_folders: &[PathBuf], _recursive: bool, _include_hidden: bool) -> Option<(Vec<FileInfo>, Option<usize>)> {
+    None
+}
+
+#[cfg(windows)]
+mod index {
+    use crate::file_scanner::{self, FileInfo};
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+
+    /// Deepest folder nesting followed when building paths (guards against parent loops)
+    const MAX_DEPTH: usize = 512;
+
+    /// One MFT record: a file or folder name and the folder it is in
+    pub struct Entry {
+        pub parent: u64,
+        pub name: String,
+        pub is_dir: bool,
+        /// Hidden or System attribute
+        pub hidden: bool,
+    }
+
+    /// Every file and folder of a volume, keyed by file reference number
+    #[derive(Default)]
+    pub struct Index {
+        pub entries: HashMap<u64, Entry>,
+    }
+
+    impl Index {
+        /// References and full paths of the files in the folder with reference `root` (at `root_path`)
+        pub fn files_under(&self, root: u64, root_path: &Path, recursive: bool, include_hidden: bool) -> Vec<(u64, PathBuf)> {
+            // Folder reference -> its path, or None if it isn't below root
+            let mut folders: HashMap<u64, Option<PathBuf>> = HashMap::new();
+            folders.insert(root, Some(root_path.to_path_buf()));
+
+            let mut files = Vec::new();
+            for (&reference, entry) in self.entries.iter().filter(|(_, e)| !e.is_dir && (include_hidden || !e.hidden)) {
+                if !recursive {
+                    if entry.parent == root {
+                        files.push((reference, root_path.join(&entry.name)));
+                    }
+                    continue;
+                }
+                if let Some(folder) = self.folder_path(entry.parent, include_hidden, &mut folders, 0) {
+                    files.push((reference, folder.join(&entry.name)));
+                }
+            }
+            files
+        }
+
+        /// Path of a folder below root (memoized, so each folder is resolved once);
+        /// None below a hidden folder unless hidden ones are included
+        fn folder_path(
+            &self,
+            reference: u64,
+            include_hidden: bool,
+            folders: &mut HashMap<u64, Option<PathBuf>>,
+            depth: usize,
+        ) -> Option<PathBuf> {
+            if let Some(path) = folders.get(&reference) {
+                return path.clone();
+            }
+            let path = match self.entries.get(&reference) {
+                Some(entry) if depth < MAX_DEPTH && entry.parent != reference && (include_hidden || !entry.hidden) => self
+                    .folder_path(entry.parent, include_hidden, folders, depth + 1)
+                    .map(|parent| parent.join(&entry.name)),
+                // Volume root or a folder outside the index
+                _ => None,
+            };
+            folders.insert(reference, path.clone());
+            path
+        }
+    }
+
+    /// Cached index of one volume and where its USN journal was last read
+    pub struct Volume {
+        /// Device path ("\\?\Volume{guid}")
+        pub device: String,
+        pub index: Index,
+        /// Journal id and the next USN to read (None if the volume has no active journal)
+        pub journal: Option<(u64, i64)>,
+        /// Metadata read in earlier scans, with the path it was read at
+        infos: HashMap<u64, (PathBuf, FileInfo)>,
+    }
+
+    impl Volume {
+        pub fn new(device: String, index: Index, journal: Option<(u64, i64)>) -> Self {
+            Self {
+                device,
+                index,
+                journal,
+                infos: HashMap::new(),
+            }
+        }
+
+        /// Record a created, renamed or modified file or folder
+        pub fn update(&mut self, reference: u64, entry: Entry) {
+            self.index.entries.insert(reference, entry);
+            self.infos.remove(&reference);
+        }
+
+        /// Record a deleted file or folder
+        pub fn remove(&mut self, reference: u64) {
+            self.index.entries.remove(&reference);
+            self.infos.remove(&reference);
+        }
+
+        /// FileInfo of a listed file; metadata is only read again if the file changed or moved
+        pub fn file_info(&mut self, reference: u64, path: &Path, folder: &Path) -> FileInfo {
+            let mut info = match self.infos.get(&reference) {
+                Some((cached_path, info)) if cached_path == path => info.clone(),
+                _ => {
+                    let info = file_scanner::file_info(path, folder);
+                    self.infos.insert(reference, (path.to_path_buf(), info.clone()));
+                    info
+                }
+            };
+            // The same file can be listed under a different scanned folder
+            if let Ok(relative) = path.strip_prefix(folder) {
+                info.set_location(&relative.to_string_lossy(), &Arc::from(""));
+            }
+            info
+        }
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use super::index::{Entry, Index, Volume};
+    use std::collections::HashSet;
+    use std::ffi::c_void;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use windows_sys::Win32::Foundation::{CloseHandle, GetLastError, ERROR_HANDLE_EOF, GENERIC_READ, HANDLE, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Storage::FileSystem::{
+        CreateFileW, GetFileInformationByHandle, GetVolumeInformationW, GetVolumeNameForVolumeMountPointW,
+        GetVolumePathNameW, BY_HANDLE_FILE_INFORMATION, FILE_ATTRIBUTE_DIRECTORY, FILE_ATTRIBUTE_HIDDEN,
+        FILE_ATTRIBUTE_SYSTEM, FILE_FLAG_BACKUP_SEMANTICS, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+    };
+    use windows_sys::Win32::System::Ioctl::{
+        FSCTL_ENUM_USN_DATA, FSCTL_QUERY_USN_JOURNAL, FSCTL_READ_USN_JOURNAL, MFT_ENUM_DATA_V0, READ_USN_JOURNAL_DATA_V0,
+        USN_JOURNAL_DATA_V0, USN_REASON_FILE_DELETE, USN_REASON_RENAME_OLD_NAME,
+    };
+    use windows_sys::Win32::System::IO::DeviceIoControl;
+
+    /// Records below this number are NTFS metadata files ($MFT, $LogFile, $Extend...)
+    const FIRST_USER_RECORD: u64 = 16;
+
+    /// Closes the handle when dropped
+    struct Handle(HANDLE);
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+
+    fn wide(text: &str) -> Vec<u16> {
+        std::ffi::OsStr::new(text).encode_wide().chain(Some(0)).collect()
+    }
+
+    fn from_wide(buffer: &[u16]) -> String {
+        let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+        String::from_utf16_lossy(&buffer[..len])
+    }
+
+    fn open(path: &str, flags: u32) -> Option<Handle> {
+        let path = wide(path);
+        let handle = unsafe {
+            CreateFileW(
+                path.as_ptr(),
+                GENERIC_READ,
+                FILE_SHARE_READ | FILE_SHARE_WRITE,
+                std::ptr::null(),
+                OPEN_EXISTING,
+                flags,
+                std::ptr::null_mut(),
+            )
+        };
+        (handle != INVALID_HANDLE_VALUE).then_some(Handle(handle))
+    }
+
+    /// Device path ("\\?\Volume{guid}") of the local NTFS volume holding the folder
+    pub fn volume_device(folder: &Path) -> Option<String> {
+        let folder = folder.to_string_lossy();
+        // canonicalize adds \\?\; network shares (\\?\UNC\) have no MFT we can read
+        let folder = folder.strip_prefix(r"\\?\").unwrap_or(&folder);
+        if folder.starts_with(r"UNC\") || folder.starts_with(r"\\") {
+            return None;
+        }
+
+        let mut mount_point = [0u16; 261];
+        let mut volume_name = [0u16; 64];
+        let mut file_system = [0u16; 16];
+        unsafe {
+            if GetVolumePathNameW(wide(folder).as_ptr(), mount_point.as_mut_ptr(), mount_point.len() as u32) == 0 {
+                return None;
+            }
+            if GetVolumeInformationW(
+                mount_point.as_ptr(),
+                std::ptr::null_mut(),
+                0,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                file_system.as_mut_ptr(),
+                file_system.len() as u32,
+            ) == 0
+                || from_wide(&file_system) != "NTFS"
+            {
+                return None;
+            }
+            if GetVolumeNameForVolumeMountPointW(mount_point.as_ptr(), volume_name.as_mut_ptr(), volume_name.len() as u32) == 0 {
+                return None;
+            }
+        }
+        // Opening the volume itself needs the name without the trailing backslash
+        Some(from_wide(&volume_name).trim_end_matches('\\').to_string())
+    }
+
+    /// File reference number of a file or folder (matches the numbers in the MFT records)
+    pub fn file_reference(path: &Path) -> Option<u64> {
+        let handle = open(&path.to_string_lossy(), FILE_FLAG_BACKUP_SEMANTICS)?;
+        let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+        if unsafe { GetFileInformationByHandle(handle.0, &mut info) } == 0 {
+            return None;
+        }
+        Some(((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64)
+    }
+
+    /// Size of the buffer the records are read into
+    const BUFFER_BYTES: usize = 64 * 1024;
+
+    /// One USN_RECORD_V2: the MFT record of a file or folder, or a change to it
+    struct Record {
+        reference: u64,
+        parent: u64,
+        reason: u32,
+        is_dir: bool,
+        hidden: bool,
+        name: String,
+    }
+
+    impl Record {
+        fn entry(self) -> Entry {
+            Entry {
+                parent: self.parent,
+                name: self.name,
+                is_dir: self.is_dir,
+                hidden: self.hidden,
+            }
+        }
+    }
+
+    /// Run a volume control code; the output starts with a reference number or USN to continue from,
+    /// followed by records. Returns the output bytes, or the error code.
+    fn control<T>(volume: &Handle, code: u32, input: &T, buffer: &mut [u64]) -> Result<Vec<u8>, u32> {
+        let mut returned = 0u32;
+        let ok = unsafe {
+            DeviceIoControl(
+                volume.0,
+                code,
+                input as *const T as *const c_void,
+                std::mem::size_of::<T>() as u32,
+                buffer.as_mut_ptr() as *mut c_void,
+                (buffer.len() * 8) as u32,
+                &mut returned,
+                std::ptr::null_mut(),
+            )
+        };
+        if ok == 0 {
+            return Err(unsafe { GetLastError() });
+        }
+        let bytes = unsafe { std::slice::from_raw_parts(buffer.as_ptr() as *const u8, returned as usize) };
+        Ok(bytes.to_vec())
+    }
+
+    /// Id and position of the volume's change journal (None if the journal isn't active)
+    fn query_journal(volume: &Handle) -> Option<USN_JOURNAL_DATA_V0> {
+        let mut journal: USN_JOURNAL_DATA_V0 = unsafe { std::mem::zeroed() };
+        let mut returned = 0u32;
+        let ok = unsafe {
+            DeviceIoControl(
+                volume.0,
+                FSCTL_QUERY_USN_JOURNAL,
+                std::ptr::null(),
+                0,
+                &mut journal as *mut USN_JOURNAL_DATA_V0 as *mut c_void,
+                std::mem::size_of::<USN_JOURNAL_DATA_V0>() as u32,
+                &mut returned,
+                std::ptr::null_mut(),
+            )
+        };
+        (ok != 0).then_some(journal)
+    }
+
+    /// Read every file and folder record of the volume with FSCTL_ENUM_USN_DATA.
+    /// Fails without administrator rights (the volume can't be opened).
+    pub fn read_volume(device: &str) -> Option<Volume> {
+        let handle = open(device, 0)?;
+        // Journal position before enumerating, so changes made meanwhile are applied on the next scan
+        let journal = query_journal(&handle).map(|j| (j.UsnJournalID, j.NextUsn));
+
+        let mut index = Index::default();
+        let mut request = MFT_ENUM_DATA_V0 {
+            StartFileReferenceNumber: 0,
+            LowUsn: 0,
+            HighUsn: i64::MAX,
+        };
+        // u64 elements keep the records 8-byte aligned
+        let mut buffer = vec![0u64; BUFFER_BYTES / 8];
+        loop {
+            let bytes = match control(&handle, FSCTL_ENUM_USN_DATA, &request, &mut buffer) {
+                Ok(bytes) if bytes.len() >= 8 => bytes,
+                Ok(_) => break,
+                // Every record has been read
+                Err(ERROR_HANDLE_EOF) => break,
+                Err(_) => return None,
+            };
+            request.StartFileReferenceNumber = read_u64(&bytes, 0);
+            for record in parse_records(&bytes[8..]) {
+                index.entries.insert(record.reference, record.entry());
+            }
+        }
+        Some(Volume::new(device.to_string(), index, journal))
+    }
+
+    /// Apply the changes recorded in the USN journal since the volume was last read.
+    /// Returns the number of changed files and folders, or None if the index can't be brought up
+    /// to date that way (no journal, or it was recreated or has wrapped past the last position).
+    pub fn apply_journal(volume: &mut Volume) -> Option<usize> {
+        let (journal_id, start) = volume.journal?;
+        let handle = open(&volume.device, 0)?;
+        let journal = query_journal(&handle)?;
+        if journal.UsnJournalID != journal_id || start < journal.FirstUsn {
+            return None;
+        }
+
+        let mut request = READ_USN_JOURNAL_DATA_V0 {
+            StartUsn: start,
+            ReasonMask: u32::MAX,
+            ReturnOnlyOnClose: 0,
+            Timeout: 0,
+            BytesToWaitFor: 0,
+            UsnJournalID: journal_id,
+        };
+        let mut changed = HashSet::new();
+        let mut buffer = vec![0u64; BUFFER_BYTES / 8];
+        // Stop at the end of the journal as it was when the refresh started
+        while request.StartUsn < journal.NextUsn {
+            let bytes = control(&handle, FSCTL_READ_USN_JOURNAL, &request, &mut buffer).ok()?;
+            if bytes.len() < 8 {
+                break;
+            }
+            let next = read_u64(&bytes, 0) as i64;
+            for record in parse_records(&bytes[8..]) {
+                changed.insert(record.reference);
+                if record.reason & USN_REASON_FILE_DELETE != 0 {
+                    volume.remove(record.reference);
+                } else if record.reason & USN_REASON_RENAME_OLD_NAME == 0 {
+                    // The old-name record of a rename is followed by one with the new name and folder
+                    volume.update(record.reference, record.entry());
+                }
+            }
+            if next <= request.StartUsn {
+                break;
+            }
+            request.StartUsn = next;
+        }
+        volume.journal = Some((journal_id, request.StartUsn));
+        Some(changed.len())
+    }
+
+    fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+        u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+    }
+
+    fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap_or_default())
+    }
+
+    fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+        u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap_or_default())
+    }
+
+    /// USN_RECORD_V2 entries of one output buffer, without NTFS metadata files
+    fn parse_records(mut bytes: &[u8]) -> Vec<Record> {
+        // Fixed part of USN_RECORD_V2, up to the file name
+        const HEADER: usize = 60;
+        let mut records = Vec::new();
+        while bytes.len() >= HEADER {
+            let length = read_u32(bytes, 0) as usize;
+            if length < HEADER || length > bytes.len() {
+                break;
+            }
+            let record = &bytes[..length];
+            bytes = &bytes[length..];
+            // Version 2 is what MFT_ENUM_DATA_V0 and READ_USN_JOURNAL_DATA_V0 return
+            if read_u16(record, 4) != 2 {
+                continue;
+            }
+            let reference = read_u64(record, 8);
+            if reference & 0xFFFF_FFFF_FFFF < FIRST_USER_RECORD {
+                continue;
+            }
+            let name_length = read_u16(record, 56) as usize;
+            let name_offset = read_u16(record, 58) as usize;
+            if name_offset + name_length > length {
+                continue;
+            }
+            let name: Vec<u16> = record[name_offset..name_offset + name_length]
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            records.push(Record {
+                reference,
+                parent: read_u64(record, 16),
+                reason: read_u32(record, 40),
+                is_dir: read_u32(record, 52) & FILE_ATTRIBUTE_DIRECTORY != 0,
+                hidden: read_u32(record, 52) & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM) != 0,
+                name: String::from_utf16_lossy(&name),
+            });
+        }
+        records
+    }
+}
@@ -0,0 +1,133 @@
+use crate::file_scanner::FileInfo;
+use crate::renames::RenameOp;
+use std::path::PathBuf;
+
+/// Longest file name most file systems accept (NTFS, ext4, APFS)
+pub const DEFAULT_NAME_LIMIT: usize = 255;
+
+/// Windows MAX_PATH, still enforced by many backup and sync tools
+pub const DEFAULT_PATH_LIMIT: usize = 260;
+
+/// Length as Windows and most backup tools count it: UTF-16 code units
+/// (an emoji counts 2, an accented letter 1)
+pub fn length(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// Length of the file name including its extension
+pub fn name_length(file: &FileInfo) -> usize {
+    length(file.full_name())
+}
+
+/// Length of the full path as other programs see it (without the `\\?\` prefix canonicalize adds on Windows)
+pub fn path_length(file: &FileInfo) -> usize {
+    let path = file.absolute_path();
+    match path.strip_prefix(r"\\?\") {
+        // \\?\UNC\server\share -> \\server\share
+        Some(rest) if rest.starts_with(r"UNC\") => length(rest) - 2,
+        Some(rest) => length(rest),
+        None => length(path),
+    }
+}
+
+/// Name or full path longer than the limits
+pub fn is_too_long(file: &FileInfo, name_limit: usize, path_limit: usize) -> bool {
+    name_length(file) > name_limit || path_length(file) > path_limit
+}
+
+/// Device names Windows reserves in every folder, with or without an extension (CON, con.txt)
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+    "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Characters Windows, SharePoint/OneDrive and ZIP tools on Windows reject
+const FORBIDDEN_CHARS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Pictographs, dingbats, flags and the joiners that combine them; many ZIP tools, older SMB
+/// servers and backup software mangle them
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0xFE0F | 0x200D | 0xE0020..=0xE007F)
+}
+
+/// Reasons a name won't survive a copy to Windows, SharePoint/OneDrive or a ZIP archive (empty if safe)
+pub fn name_problems(name: &str) -> Vec<&'static str> {
+    let mut problems = Vec::new();
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    if RESERVED_NAMES.iter().any(|reserved| stem.eq_ignore_ascii_case(reserved)) {
+        problems.push("reserved device name");
+    }
+    if name.chars().any(|c| FORBIDDEN_CHARS.contains(&c)) {
+        problems.push("forbidden character");
+    }
+    if name.chars().any(|c| c.is_control()) {
+        problems.push("control character");
+    }
+    if name.ends_with([' ', '.']) {
+        problems.push("trailing space or dot");
+    }
+    if name.starts_with(' ') {
+        problems.push("leading space");
+    }
+    if name.starts_with("~$") || name.to_lowercase().starts_with("_vti_") {
+        problems.push("blocked by SharePoint");
+    }
+    if name.chars().any(is_emoji) {
+        problems.push("emoji");
+    }
+    problems
+}
+
+/// True if the name has any cross-platform problem
+pub fn is_unsafe_name(name: &str) -> bool {
+    !name_problems(name).is_empty()
+}
+
+/// Safe replacement name (None if the name is already safe): forbidden and control characters
+/// become "_", emoji are dropped, leading/trailing spaces and trailing dots are trimmed and
+/// reserved names get a "_" suffix ("CON.txt" -> "CON_.txt")
+pub fn sanitized_name(name: &str) -> Option<String> {
+    if !is_unsafe_name(name) {
+        return None;
+    }
+    let mut safe: String = name
+        .chars()
+        .filter(|&c| !is_emoji(c))
+        .map(|c| if FORBIDDEN_CHARS.contains(&c) || c.is_control() { '_' } else { c })
+        .collect();
+    safe = safe.trim_start().trim_end_matches([' ', '.']).to_string();
+    if let Some(rest) = safe.strip_prefix("~$") {
+        safe = format!("_{}", rest);
+    } else if safe.to_lowercase().starts_with("_vti_") {
+        safe.insert(0, '_');
+    }
+
+    let (stem, extension) = match safe.split_once('.') {
+        Some((stem, extension)) => (stem.trim_end().to_string(), Some(extension.to_string())),
+        None => (safe.clone(), None),
+    };
+    if RESERVED_NAMES.iter().any(|reserved| stem.eq_ignore_ascii_case(reserved)) {
+        safe = match extension {
+            Some(extension) => format!("{}_.{}", stem, extension),
+            None => format!("{}_", stem),
+        };
+    }
+    if safe.is_empty() {
+        safe = "_".to_string();
+    }
+    (safe != name).then_some(safe)
+}
+
+/// Plan renames of unsafe names to their sanitized form (check them with renames::simulate)
+pub fn plan_sanitize(files: &[FileInfo]) -> Vec<RenameOp> {
+    files
+        .iter()
+        .filter_map(|file| {
+            let new_name = sanitized_name(file.full_name())?;
+            let from = PathBuf::from(file.absolute_path());
+            let to = from.with_file_name(new_name);
+            Some(RenameOp { from, to })
+        })
+        .collect()
+}
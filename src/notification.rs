@@ -0,0 +1,75 @@
+use std::process::Command;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+const APP_NAME: &str = "File Lister";
+
+/// Show an OS notification in the background.
+/// `clicked` receives a message if the user clicks the notification (where the platform reports it).
+pub fn notify(title: &str, body: &str, clicked: Sender<()>) {
+    let title = title.to_string();
+    let body = body.to_string();
+    thread::spawn(move || {
+        if show(&title, &body) {
+            let _ = clicked.send(());
+        }
+    });
+}
+
+/// Windows toast through PowerShell (WinRT ToastNotificationManager)
+#[cfg(windows)]
+fn show(title: &str, body: &str) -> bool {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    // Toast XML text, inside a PowerShell single-quoted string
+    let escape = |text: &str| {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('\'', "''")
+    };
+    let script = format!(
+        "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null; \
+         [Windows.Data.Xml.Dom.XmlDocument, Windows.Data.Xml.Dom.XmlDocument, ContentType = WindowsRuntime] > $null; \
+         $xml = New-Object Windows.Data.Xml.Dom.XmlDocument; \
+         $xml.LoadXml('<toast><visual><binding template=\"ToastGeneric\"><text>{}</text><text>{}</text></binding></visual></toast>'); \
+         $toast = New-Object Windows.UI.Notifications.ToastNotification $xml; \
+         [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('{}').Show($toast)",
+        escape(title),
+        escape(body),
+        APP_NAME,
+    );
+    let _ = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output();
+    // Toast activation can't be reported back from PowerShell; the taskbar flash handles click-to-focus
+    false
+}
+
+/// macOS notification through AppleScript
+#[cfg(target_os = "macos")]
+fn show(title: &str, body: &str) -> bool {
+    let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+    let script = format!("display notification {} with title {}", quote(body), quote(title));
+    let _ = Command::new("osascript").args(["-e", &script]).output();
+    false
+}
+
+/// Desktop notification through notify-send; waits for a click on the "Show" action
+#[cfg(not(any(windows, target_os = "macos")))]
+fn show(title: &str, body: &str) -> bool {
+    let output = Command::new("notify-send")
+        .args(["--app-name", APP_NAME, "--action=default=Show", "--wait", title, body])
+        .output();
+    match output {
+        // notify-send prints the invoked action name
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).trim() == "default",
+        // Older notify-send without --action/--wait
+        _ => {
+            let _ = Command::new("notify-send").args(["--app-name", APP_NAME, title, body]).output();
+            false
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::file_scanner::FileInfo;
+
+/// Fill in owner and owner_orphaned for each file.
+/// SID lookups are cached, since resolving a deleted domain account can be slow.
+#[cfg(windows)]
+pub fn resolve_owners(files: &mut [FileInfo]) {
+    use std::collections::HashMap;
+
+    let mut cache: HashMap<String, SidLookup> = HashMap::new();
+    for file in files.iter_mut() {
+        let Some(sid) = sys::owner_sid(file.absolute_path()) else {
+            continue;
+        };
+        let lookup = cache
+            .entry(sid.string.clone())
+            .or_insert_with(|| sys::lookup_sid(&sid));
+        match lookup {
+            SidLookup::Account(name) => file.owner = Some(name.clone()),
+            SidLookup::Deleted => {
+                file.owner = Some(sid.string.clone());
+                file.owner_orphaned = true;
+            }
+            // Domain unreachable etc. - don't report as orphaned
+            SidLookup::Unknown => file.owner = Some(sid.string.clone()),
+        }
+    }
+}
+
+/// Owner lookup is only implemented for Windows (NTFS owner SIDs)
+#[cfg(not(windows))]
+pub fn resolve_owners(_files: &mut [FileInfo]) {}
+
+/// Result of resolving an owner SID to an account name
+#[cfg(windows)]
+enum SidLookup {
+    /// "DOMAIN\user"
+    Account(String),
+    /// No account is mapped to the SID (deleted user)
+    Deleted,
+    /// Lookup failed for another reason
+    Unknown,
+}
+
+#[cfg(windows)]
+mod sys {
+    use super::SidLookup;
+    use std::ffi::c_void;
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::{GetLastError, LocalFree, ERROR_NONE_MAPPED, ERROR_SUCCESS};
+    use windows_sys::Win32::Security::Authorization::{ConvertSidToStringSidW, GetNamedSecurityInfoW, SE_FILE_OBJECT};
+    use windows_sys::Win32::Security::{LookupAccountSidW, OWNER_SECURITY_INFORMATION, SID_NAME_USE};
+
+    /// Owner SID of a file; owns the security descriptor the SID points into
+    pub struct OwnerSid {
+        sid: *mut c_void,
+        descriptor: *mut c_void,
+        pub string: String,
+    }
+
+    impl Drop for OwnerSid {
+        fn drop(&mut self) {
+            unsafe {
+                LocalFree(self.descriptor);
+            }
+        }
+    }
+
+    fn to_wide(text: &str) -> Vec<u16> {
+        std::ffi::OsStr::new(text).encode_wide().chain(std::iter::once(0)).collect()
+    }
+
+    fn from_wide(buffer: &[u16]) -> String {
+        let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+        String::from_utf16_lossy(&buffer[..len])
+    }
+
+    /// Read the owner SID of a file
+    pub fn owner_sid(path: &str) -> Option<OwnerSid> {
+        let wide_path = to_wide(path);
+        let mut sid: *mut c_void = std::ptr::null_mut();
+        let mut descriptor: *mut c_void = std::ptr::null_mut();
+        let result = unsafe {
+            GetNamedSecurityInfoW(
+                wide_path.as_ptr(),
+                SE_FILE_OBJECT,
+                OWNER_SECURITY_INFORMATION,
+                &mut sid,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                &mut descriptor,
+            )
+        };
+        if result != ERROR_SUCCESS || sid.is_null() {
+            return None;
+        }
+
+        let mut owner = OwnerSid {
+            sid,
+            descriptor,
+            string: String::new(),
+        };
+        let mut string_sid: *mut u16 = std::ptr::null_mut();
+        if unsafe { ConvertSidToStringSidW(sid, &mut string_sid) } != 0 {
+            let len = (0..).take_while(|&i| unsafe { *string_sid.add(i) } != 0).count();
+            owner.string = String::from_utf16_lossy(unsafe { std::slice::from_raw_parts(string_sid, len) });
+            unsafe {
+                LocalFree(string_sid as *mut c_void);
+            }
+        }
+        Some(owner)
+    }
+
+    /// Resolve a SID to "DOMAIN\user"
+    pub fn lookup_sid(owner: &OwnerSid) -> SidLookup {
+        let mut name = [0u16; 256];
+        let mut domain = [0u16; 256];
+        let mut name_len = name.len() as u32;
+        let mut domain_len = domain.len() as u32;
+        let mut sid_use: SID_NAME_USE = 0;
+        let ok = unsafe {
+            LookupAccountSidW(
+                std::ptr::null(),
+                owner.sid,
+                name.as_mut_ptr(),
+                &mut name_len,
+                domain.as_mut_ptr(),
+                &mut domain_len,
+                &mut sid_use,
+            )
+        };
+
+        if ok != 0 {
+            let name = from_wide(&name);
+            let domain = from_wide(&domain);
+            if domain.is_empty() {
+                SidLookup::Account(name)
+            } else {
+                SidLookup::Account(format!("{}\\{}", domain, name))
+            }
+        } else if unsafe { GetLastError() } == ERROR_NONE_MAPPED {
+            SidLookup::Deleted
+        } else {
+            SidLookup::Unknown
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use crate::file_scanner::{self, format_bytes, format_date, format_size, Calendar, FileInfo, ScanFilter, SizeFormat};
+use crate::kiosk;
+use crate::text_match;
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// Which pane of the two-panel layout has focus
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ActivePane {
+    Left,
+    Right,
+}
+
+impl ActivePane {
+    pub fn label(self) -> &'static str {
+        match self {
+            ActivePane::Left => "Left",
+            ActivePane::Right => "Right",
+        }
+    }
+}
+
+/// Second file list for two-panel mode: one folder, scanned independently of the main list
+#[derive(Default)]
+pub struct FilePane {
+    pub folder: Option<PathBuf>,
+    pub files: Vec<FileInfo>,
+    /// Selected indices into files
+    pub selected: HashSet<usize>,
+    scan_receiver: Option<Receiver<Result<Vec<FileInfo>, String>>>,
+    pub error_message: Option<String>,
+    /// Folders the pane may show (workstation policy; empty: any)
+    allowed_roots: Vec<PathBuf>,
+}
+
+impl FilePane {
+    pub fn new(folder: Option<PathBuf>, allowed_roots: Vec<PathBuf>) -> Self {
+        let mut pane = Self {
+            folder,
+            allowed_roots,
+            ..Default::default()
+        };
+        pane.rescan();
+        pane
+    }
+
+    pub fn is_scanning(&self) -> bool {
+        self.scan_receiver.is_some()
+    }
+
+    /// Scan the pane folder (top level only) in the background
+    pub fn rescan(&mut self) {
+        self.selected.clear();
+        self.error_message = None;
+        let Some(folder) = self.folder.clone() else {
+            self.files.clear();
+            return;
+        };
+        if !kiosk::within(&self.allowed_roots, &folder) {
+            self.files.clear();
+            self.scan_receiver = None;
+            self.error_message = Some(format!("{} is outside the folders allowed on this workstation", folder.display()));
+            return;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        self.scan_receiver = Some(rx);
+        thread::spawn(move || {
+            // Like a file manager pane, hidden files are listed too
+            let filter = ScanFilter {
+                include_hidden: true,
+                respect_gitignore: false,
+            };
+            let result = file_scanner::scan_folder(&folder, false, filter).map_err(|e| e.to_string());
+            let _ = tx.send(result);
+        });
+    }
+
+    /// Check for scan results from the background thread
+    pub fn check_scan_results(&mut self) {
+        let Some(receiver) = &self.scan_receiver else {
+            return;
+        };
+        if let Ok(result) = receiver.try_recv() {
+            match result {
+                Ok(mut files) => {
+                    files.sort_by(|a, b| text_match::compare_natural(a.full_name(), b.full_name()));
+                    self.files = files;
+                }
+                Err(e) => {
+                    self.error_message = Some(format!("Error scanning folder: {}", e));
+                    self.files.clear();
+                }
+            }
+            self.scan_receiver = None;
+        }
+    }
+
+    /// Selected files as (absolute path, file name)
+    pub fn selected_files(&self) -> Vec<(String, String)> {
+        let mut indices: Vec<usize> = self.selected.iter().copied().collect();
+        indices.sort_unstable();
+        indices
+            .into_iter()
+            .filter_map(|idx| self.files.get(idx))
+            .map(|f| (f.absolute_path().to_string(), f.full_name().to_string()))
+            .collect()
+    }
+
+    /// Draw the pane. Returns true if the folder changed (caller persists it).
+    pub fn show(&mut self, ui: &mut egui::Ui, active: bool, size_format: &SizeFormat, calendar: Calendar) -> bool {
+        let mut folder_changed = false;
+
+        ui.horizontal(|ui| {
+            let title = if active { "▶ Right pane" } else { "Right pane" };
+            ui.strong(title);
+            if ui.button("📂 Folder...").clicked() {
+                if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                    self.folder = Some(folder);
+                    self.rescan();
+                    folder_changed = true;
+                }
+            }
+            if ui.add_enabled(self.folder.is_some(), egui::Button::new("⟳")).on_hover_text("Rescan").clicked() {
+                self.rescan();
+            }
+            if self.is_scanning() {
+                ui.spinner();
+            }
+        });
+
+        match &self.folder {
+            Some(folder) => ui.label(folder.display().to_string()),
+            None => ui.label(egui::RichText::new("Choose a folder for this pane").weak()),
+        };
+        if let Some(error) = &self.error_message {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        ui.label(format!("{} files, {} selected", self.files.len(), self.selected.len()));
+        ui.separator();
+
+        let mut clicked: Option<usize> = None;
+        TableBuilder::new(ui)
+            .id_salt("right_pane_table")
+            .striped(true)
+            .sense(egui::Sense::click())
+            .column(Column::remainder().at_least(120.0).clip(true))
+            .column(Column::auto().at_least(70.0))
+            .column(Column::auto().at_least(110.0))
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.strong("Name");
+                });
+                header.col(|ui| {
+                    ui.strong("Size");
+                });
+                header.col(|ui| {
+                    ui.strong("Modified");
+                });
+            })
+            .body(|body| {
+                body.rows(20.0, self.files.len(), |mut row| {
+                    let idx = row.index();
+                    let file = &self.files[idx];
+                    row.set_selected(self.selected.contains(&idx));
+                    row.col(|ui| {
+                        ui.label(file.full_name());
+                    });
+                    row.col(|ui| {
+                        ui.label(format_size(file.file_size, size_format))
+                            .on_hover_text(format_bytes(file.file_size, size_format));
+                    });
+                    row.col(|ui| {
+                        ui.label(format_date(file.modified_timestamp, calendar));
+                    });
+                    let response = row.response();
+                    if response.double_clicked() {
+                        let _ = open::that(file.absolute_path());
+                    } else if response.clicked() {
+                        clicked = Some(idx);
+                    }
+                });
+            });
+
+        // Click toggles selection (commander-style marking)
+        if let Some(idx) = clicked {
+            if !self.selected.remove(&idx) {
+                self.selected.insert(idx);
+            }
+        }
+
+        folder_changed
+    }
+}
@@ -0,0 +1,399 @@
+use crate::file_scanner::FileInfo;
+use crate::priority;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// Something an audit flags about a folder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionFlag {
+    /// Everyone, anonymous or all (authenticated / domain) users, or Unix "others" can write
+    WorldWritable,
+    /// The same broad groups can list and read it
+    EveryoneReadable,
+}
+
+impl PermissionFlag {
+    pub fn label(self) -> &'static str {
+        match self {
+            PermissionFlag::WorldWritable => "World-writable",
+            PermissionFlag::EveryoneReadable => "Everyone can read",
+        }
+    }
+}
+
+/// Effective permissions of one folder as the audit reports them
+#[derive(Debug, Clone)]
+pub struct FolderPermissions {
+    pub path: PathBuf,
+    pub owner: Option<String>,
+    /// "rwxr-xr-x" on Unix; "N entries (M inherited)" of the ACL on Windows
+    pub summary: String,
+    /// Accounts and groups that can create, change or delete files in it
+    pub writers: Vec<String>,
+    /// Accounts and groups that can list it
+    pub readers: Vec<String>,
+    pub flags: Vec<PermissionFlag>,
+    /// The permissions could not be read
+    pub error: Option<String>,
+}
+
+impl FolderPermissions {
+    pub fn is_flagged(&self) -> bool {
+        !self.flags.is_empty()
+    }
+
+    /// "World-writable, Everyone can read"
+    pub fn flag_labels(&self) -> String {
+        self.flags.iter().map(|flag| flag.label()).collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// The scanned folders and every folder between them and a listed file, sorted
+pub fn audited_folders(roots: &[PathBuf], files: &[FileInfo]) -> Vec<PathBuf> {
+    let mut folders: BTreeSet<PathBuf> = roots.iter().cloned().collect();
+    for file in files {
+        let path = Path::new(file.absolute_path());
+        let start = if file.is_dir { Some(path) } else { path.parent() };
+        for folder in start.into_iter().flat_map(Path::ancestors) {
+            if !roots.iter().any(|root| folder.starts_with(root)) || !folders.insert(folder.to_path_buf()) {
+                break;
+            }
+        }
+    }
+    folders.into_iter().collect()
+}
+
+/// Read the owner and effective permissions of a folder
+pub fn read_permissions(path: &Path) -> FolderPermissions {
+    let mut permissions = FolderPermissions {
+        path: path.to_path_buf(),
+        owner: None,
+        summary: String::new(),
+        writers: Vec::new(),
+        readers: Vec::new(),
+        flags: Vec::new(),
+        error: None,
+    };
+    if let Err(e) = sys::read(path, &mut permissions) {
+        permissions.error = Some(e);
+    }
+    permissions
+}
+
+/// Message from the background audit
+pub enum AuditMessage {
+    Progress { done: usize, total: usize },
+    Finished(Vec<FolderPermissions>),
+}
+
+/// Read the permissions of the folders on a background thread
+pub fn spawn_audit(folders: Vec<PathBuf>) -> Receiver<AuditMessage> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        priority::enter_background();
+        let mut results = Vec::with_capacity(folders.len());
+        for (done, folder) in folders.iter().enumerate() {
+            if done % 64 == 0 {
+                let _ = tx.send(AuditMessage::Progress { done, total: folders.len() });
+            }
+            priority::throttle();
+            results.push(read_permissions(folder));
+        }
+        let _ = tx.send(AuditMessage::Finished(results));
+    });
+    rx
+}
+
+/// Write the audit as CSV: folder, owner, permissions, who can write and read, flags, error
+pub fn write_report(folders: &[&FolderPermissions], path: &Path) -> Result<(), String> {
+    let mut file = std::fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    // UTF-8 BOM for Excel, like the CSV export
+    file.write_all(&[0xEF, 0xBB, 0xBF]).map_err(|e| e.to_string())?;
+    let mut csv = csv::Writer::from_writer(file);
+    csv.write_record(["Folder", "Owner", "Permissions", "Can write", "Can read", "Flags", "Error"])
+        .map_err(|e| e.to_string())?;
+    for folder in folders {
+        csv.write_record([
+            folder.path.display().to_string(),
+            folder.owner.clone().unwrap_or_default(),
+            folder.summary.clone(),
+            folder.writers.join("; "),
+            folder.readers.join("; "),
+            folder.flag_labels(),
+            folder.error.clone().unwrap_or_default(),
+        ])
+        .map_err(|e| e.to_string())?;
+    }
+    csv.flush().map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Mode bits: owner, group and others, each needing write and search (x) to add files
+#[cfg(unix)]
+mod sys {
+    use super::{FolderPermissions, PermissionFlag};
+    use std::ffi::CStr;
+    use std::os::unix::fs::MetadataExt;
+    use std::path::Path;
+
+    /// Name of a user or group through getpwuid_r / getgrgid_r; the number if there is none
+    fn account_name(id: u32, group: bool) -> String {
+        let mut buffer = vec![0 as libc::c_char; 16 * 1024];
+        let name = if group {
+            let mut entry: libc::group = unsafe { std::mem::zeroed() };
+            let mut result: *mut libc::group = std::ptr::null_mut();
+            let status = unsafe { libc::getgrgid_r(id, &mut entry, buffer.as_mut_ptr(), buffer.len(), &mut result) };
+            (status == 0 && !result.is_null()).then_some(entry.gr_name)
+        } else {
+            let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+            let mut result: *mut libc::passwd = std::ptr::null_mut();
+            let status = unsafe { libc::getpwuid_r(id, &mut entry, buffer.as_mut_ptr(), buffer.len(), &mut result) };
+            (status == 0 && !result.is_null()).then_some(entry.pw_name)
+        };
+        match name {
+            Some(name) => unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned(),
+            None => id.to_string(),
+        }
+    }
+
+    /// "rwxr-xr-x", with "t" for the sticky bit
+    fn mode_string(mode: u32) -> String {
+        let mut text: String = (0..9)
+            .map(|bit| if mode & (0o400 >> bit) != 0 { ['r', 'w', 'x'][bit % 3] } else { '-' })
+            .collect();
+        if mode & 0o1000 != 0 {
+            text.replace_range(8.., if mode & 0o001 != 0 { "t" } else { "T" });
+        }
+        text
+    }
+
+    pub fn read(path: &Path, permissions: &mut FolderPermissions) -> Result<(), String> {
+        let metadata = std::fs::metadata(path).map_err(|e| e.to_string())?;
+        let mode = metadata.mode();
+        let owner = account_name(metadata.uid(), false);
+        let classes = [
+            (format!("{} (owner)", owner), 6),
+            (format!("{} (group)", account_name(metadata.gid(), true)), 3),
+            ("others".to_string(), 0),
+        ];
+        for (name, shift) in classes {
+            let bits = (mode >> shift) & 0o7;
+            if bits & 0o3 == 0o3 {
+                permissions.writers.push(name.clone());
+            }
+            if bits & 0o5 == 0o5 {
+                permissions.readers.push(name);
+            }
+        }
+        permissions.owner = Some(owner);
+        permissions.summary = mode_string(mode);
+        // A sticky world-writable folder (/tmp) only lets owners delete their own files, but anyone can still add files
+        if mode & 0o003 == 0o003 {
+            permissions.flags.push(PermissionFlag::WorldWritable);
+        }
+        if mode & 0o005 == 0o005 {
+            permissions.flags.push(PermissionFlag::EveryoneReadable);
+        }
+        Ok(())
+    }
+}
+
+/// The DACL: allow entries that apply to the folder itself, less the rights denied to the
+/// same account. Group membership is not expanded.
+#[cfg(windows)]
+mod sys {
+    use super::{FolderPermissions, PermissionFlag};
+    use std::collections::HashSet;
+    use std::ffi::c_void;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use windows_sys::Win32::Foundation::{LocalFree, ERROR_SUCCESS};
+    use windows_sys::Win32::Security::Authorization::{ConvertSidToStringSidW, GetNamedSecurityInfoW, SE_FILE_OBJECT};
+    use windows_sys::Win32::Security::{
+        GetAce, LookupAccountSidW, ACCESS_ALLOWED_ACE, ACL, DACL_SECURITY_INFORMATION, OWNER_SECURITY_INFORMATION,
+        SID_NAME_USE,
+    };
+
+    const ACCESS_ALLOWED: u8 = 0;
+    const ACCESS_DENIED: u8 = 1;
+    /// Entry only passed on to children, not applied to the folder
+    const INHERIT_ONLY: u8 = 0x08;
+    const INHERITED: u8 = 0x10;
+
+    /// FILE_ADD_FILE, FILE_ADD_SUBDIRECTORY, GENERIC_WRITE, GENERIC_ALL
+    const WRITE_RIGHTS: u32 = 0x2 | 0x4 | 0x4000_0000 | 0x1000_0000;
+    /// FILE_LIST_DIRECTORY, GENERIC_READ, GENERIC_ALL
+    const READ_RIGHTS: u32 = 0x1 | 0x8000_0000 | 0x1000_0000;
+    /// WRITE_DAC, WRITE_OWNER, GENERIC_ALL: can change the permissions themselves
+    const CONTROL_RIGHTS: u32 = 0x4_0000 | 0x8_0000 | 0x1000_0000;
+
+    /// Everyone, Anonymous, Authenticated Users, BUILTIN\Users
+    const BROAD_SIDS: [&str; 4] = ["S-1-1-0", "S-1-5-7", "S-1-5-11", "S-1-5-32-545"];
+
+    /// Everyone, all users, or the Domain Users group (RID 513) of any domain
+    fn is_broad(sid: &str) -> bool {
+        BROAD_SIDS.contains(&sid) || (sid.starts_with("S-1-5-21-") && sid.ends_with("-513"))
+    }
+
+    fn sid_string(sid: *mut c_void) -> String {
+        let mut string_sid: *mut u16 = std::ptr::null_mut();
+        if unsafe { ConvertSidToStringSidW(sid, &mut string_sid) } == 0 {
+            return String::new();
+        }
+        let len = (0..).take_while(|&i| unsafe { *string_sid.add(i) } != 0).count();
+        let text = String::from_utf16_lossy(unsafe { std::slice::from_raw_parts(string_sid, len) });
+        unsafe {
+            LocalFree(string_sid as *mut c_void);
+        }
+        text
+    }
+
+    /// "DOMAIN\name", or the SID string for a deleted or unresolvable account
+    fn account_name(sid: *mut c_void, fallback: &str) -> String {
+        let mut name = [0u16; 256];
+        let mut domain = [0u16; 256];
+        let mut name_len = name.len() as u32;
+        let mut domain_len = domain.len() as u32;
+        let mut sid_use: SID_NAME_USE = 0;
+        let ok = unsafe {
+            LookupAccountSidW(
+                std::ptr::null(),
+                sid,
+                name.as_mut_ptr(),
+                &mut name_len,
+                domain.as_mut_ptr(),
+                &mut domain_len,
+                &mut sid_use,
+            )
+        };
+        if ok == 0 {
+            return fallback.to_string();
+        }
+        let text = |buffer: &[u16]| {
+            let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+            String::from_utf16_lossy(&buffer[..len])
+        };
+        let (name, domain) = (text(&name), text(&domain));
+        if domain.is_empty() {
+            name
+        } else {
+            format!("{}\\{}", domain, name)
+        }
+    }
+
+    /// Security descriptor of a folder; freed on drop
+    struct Descriptor(*mut c_void);
+
+    impl Drop for Descriptor {
+        fn drop(&mut self) {
+            unsafe {
+                LocalFree(self.0);
+            }
+        }
+    }
+
+    pub fn read(path: &Path, permissions: &mut FolderPermissions) -> Result<(), String> {
+        let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        let mut owner: *mut c_void = std::ptr::null_mut();
+        let mut dacl: *mut ACL = std::ptr::null_mut();
+        let mut descriptor: *mut c_void = std::ptr::null_mut();
+        let result = unsafe {
+            GetNamedSecurityInfoW(
+                wide.as_ptr(),
+                SE_FILE_OBJECT,
+                OWNER_SECURITY_INFORMATION | DACL_SECURITY_INFORMATION,
+                &mut owner,
+                std::ptr::null_mut(),
+                &mut dacl,
+                std::ptr::null_mut(),
+                &mut descriptor,
+            )
+        };
+        if result != ERROR_SUCCESS {
+            return Err(std::io::Error::from_raw_os_error(result as i32).to_string());
+        }
+        let _descriptor = Descriptor(descriptor);
+        if !owner.is_null() {
+            permissions.owner = Some(account_name(owner, &sid_string(owner)));
+        }
+
+        // No DACL at all grants everyone full control
+        if dacl.is_null() {
+            permissions.summary = "No ACL (full control for everyone)".to_string();
+            permissions.writers.push("Everyone (full control)".to_string());
+            permissions.readers.push("Everyone".to_string());
+            permissions.flags = vec![PermissionFlag::WorldWritable, PermissionFlag::EveryoneReadable];
+            return Ok(());
+        }
+
+        let count = unsafe { (*dacl).AceCount };
+        let mut inherited = 0;
+        let mut denied_write: HashSet<String> = HashSet::new();
+        let mut denied_read: HashSet<String> = HashSet::new();
+        let (mut writable, mut readable) = (false, false);
+        for index in 0..u32::from(count) {
+            let mut ace: *mut c_void = std::ptr::null_mut();
+            if unsafe { GetAce(dacl, index, &mut ace) } == 0 || ace.is_null() {
+                continue;
+            }
+            // Allowed and denied entries share this layout
+            let entry = unsafe { &*(ace as *const ACCESS_ALLOWED_ACE) };
+            if entry.Header.AceFlags & INHERITED != 0 {
+                inherited += 1;
+            }
+            if entry.Header.AceFlags & INHERIT_ONLY != 0 {
+                continue;
+            }
+            let sid = &entry.SidStart as *const u32 as *mut c_void;
+            let sid_text = sid_string(sid);
+            match entry.Header.AceType {
+                ACCESS_DENIED => {
+                    if entry.Mask & WRITE_RIGHTS != 0 {
+                        denied_write.insert(sid_text.clone());
+                    }
+                    if entry.Mask & READ_RIGHTS != 0 {
+                        denied_read.insert(sid_text);
+                    }
+                }
+                ACCESS_ALLOWED => {
+                    let name = account_name(sid, &sid_text);
+                    if entry.Mask & WRITE_RIGHTS != 0 && !denied_write.contains(&sid_text) {
+                        let control = entry.Mask & CONTROL_RIGHTS != 0;
+                        let label = if control { format!("{} (full control)", name) } else { name.clone() };
+                        if !permissions.writers.contains(&label) {
+                            permissions.writers.push(label);
+                        }
+                        writable |= is_broad(&sid_text);
+                    }
+                    if entry.Mask & READ_RIGHTS != 0 && !denied_read.contains(&sid_text) {
+                        if !permissions.readers.contains(&name) {
+                            permissions.readers.push(name);
+                        }
+                        readable |= is_broad(&sid_text);
+                    }
+                }
+                _ => {}
+            }
+        }
+        permissions.summary = format!("{} entries ({} inherited)", count, inherited);
+        if writable {
+            permissions.flags.push(PermissionFlag::WorldWritable);
+        }
+        if readable {
+            permissions.flags.push(PermissionFlag::EveryoneReadable);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    use super::FolderPermissions;
+    use std::path::Path;
+
+    pub fn read(_path: &Path, _permissions: &mut FolderPermissions) -> Result<(), String> {
+        Err("Not supported on this platform".to_string())
+    }
+}
@@ -0,0 +1,94 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Background priority: scans and hashing yield to other programs (configured from the settings)
+static BACKGROUND: AtomicBool = AtomicBool::new(false);
+
+/// Time a throttled thread works before pausing
+const WORK_SLICE: Duration = Duration::from_millis(50);
+
+/// Default pause after each work slice (so throttled I/O runs about half the time)
+pub const DEFAULT_PAUSE_MS: u64 = 50;
+
+/// Pause after each work slice in milliseconds (from the settings)
+static PAUSE_MS: AtomicU64 = AtomicU64::new(DEFAULT_PAUSE_MS);
+
+thread_local! {
+    /// When the current work slice of this thread began
+    static SLICE_START: Cell<Option<Instant>> = const { Cell::new(None) };
+    /// Set by enter_background: the thread may run at background priority
+    static BACKGROUND_THREAD: Cell<bool> = const { Cell::new(false) };
+    /// The thread's priority has been lowered
+    static LOWERED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Turn background priority on or off and set the pause between work slices. Takes effect
+/// for running scans and hashing too, at their next throttle point.
+pub fn configure(background: bool, pause_ms: u64) {
+    BACKGROUND.store(background, Ordering::Relaxed);
+    PAUSE_MS.store(pause_ms, Ordering::Relaxed);
+}
+
+/// Mark the current thread as background work and lower its CPU and I/O priority if
+/// background priority is on. Called at the start of scan and hashing threads.
+pub fn enter_background() {
+    BACKGROUND_THREAD.with(|background| background.set(true));
+    if BACKGROUND.load(Ordering::Relaxed) {
+        lower_once();
+    }
+}
+
+/// Lower the thread's priority unless already done (it is not raised again when background
+/// priority is turned off: going back up needs privileges on Linux)
+fn lower_once() {
+    if !LOWERED.with(|lowered| lowered.replace(true)) {
+        lower_thread_priority();
+    }
+}
+
+/// Pause briefly every few milliseconds of work if background priority is on.
+/// Called per file (and per read chunk while hashing).
+pub fn throttle() {
+    if !BACKGROUND.load(Ordering::Relaxed) {
+        return;
+    }
+    // Turned on while the thread was already running
+    if BACKGROUND_THREAD.with(Cell::get) {
+        lower_once();
+    }
+    SLICE_START.with(|start| match start.get() {
+        Some(begun) if begun.elapsed() >= WORK_SLICE => {
+            thread::sleep(Duration::from_millis(PAUSE_MS.load(Ordering::Relaxed)));
+            start.set(Some(Instant::now()));
+        }
+        Some(_) => {}
+        None => start.set(Some(Instant::now())),
+    });
+}
+
+/// Background mode lowers both the CPU and the I/O priority of the thread
+#[cfg(windows)]
+fn lower_thread_priority() {
+    use windows_sys::Win32::System::Threading::{GetCurrentThread, SetThreadPriority, THREAD_MODE_BACKGROUND_BEGIN};
+    unsafe {
+        SetThreadPriority(GetCurrentThread(), THREAD_MODE_BACKGROUND_BEGIN);
+    }
+}
+
+/// Nice value 19 and the idle I/O class for the calling thread
+#[cfg(target_os = "linux")]
+fn lower_thread_priority() {
+    // ioprio_set(IOPRIO_WHO_PROCESS, 0 = calling thread, IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT)
+    const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+    const IOPRIO_IDLE: libc::c_long = 3 << 13;
+    unsafe {
+        libc::setpriority(libc::PRIO_PROCESS, libc::gettid() as libc::id_t, 19);
+        libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, IOPRIO_IDLE);
+    }
+}
+
+/// Elsewhere only the throttling applies
+#[cfg(not(any(windows, target_os = "linux")))]
+fn lower_thread_priority() {}
@@ -0,0 +1,250 @@
+use crate::file_scanner::{self, FileId};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// One rename of a bulk rename plan
+#[derive(Debug, Clone)]
+pub struct RenameOp {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// Dry run of a rename plan
+#[derive(Debug, Default)]
+pub struct RenameSimulation {
+    /// Problem per operation (None if it can be applied)
+    pub conflicts: Vec<Option<String>>,
+    /// Renames whose new name is the old name of another file in the batch (chains and cycles like a -> b, b -> a)
+    pub chained: usize,
+}
+
+impl RenameSimulation {
+    pub fn conflict_count(&self) -> usize {
+        self.conflicts.iter().filter(|c| c.is_some()).count()
+    }
+}
+
+/// Comparison key: names differing only in case are the same file where the file system is
+/// case-insensitive (Windows, macOS), different files elsewhere
+fn key(path: &Path) -> String {
+    if cfg!(any(windows, target_os = "macos")) {
+        path.to_string_lossy().to_lowercase()
+    } else {
+        path.to_string_lossy().into_owned()
+    }
+}
+
+/// The new name is held by a file the rename would replace: another file than `op.from` that
+/// no rename of the batch moves away. Files are told apart by their id, so a case-only rename
+/// is only allowed where both names lead to the same file, whatever the file system.
+fn target_taken(op: &RenameOp, source_ids: &HashSet<FileId>, source_paths: &HashSet<&Path>) -> bool {
+    if std::fs::symlink_metadata(&op.to).is_err() {
+        return false;
+    }
+    match file_scanner::file_id(&op.to) {
+        Some(id) if file_scanner::file_id(&op.from) == Some(id) => {
+            // Another hard link of the same file would be left in place by the rename
+            op.to.to_string_lossy().to_lowercase() != op.from.to_string_lossy().to_lowercase()
+        }
+        Some(id) => !source_ids.contains(&id),
+        None => !source_paths.contains(op.to.as_path()),
+    }
+}
+
+/// Check a rename plan without touching any file
+pub fn simulate(ops: &[RenameOp]) -> RenameSimulation {
+    let sources: HashSet<String> = ops.iter().map(|op| key(&op.from)).collect();
+    let source_ids: HashSet<FileId> = ops.iter().filter_map(|op| file_scanner::file_id(&op.from)).collect();
+    let source_paths: HashSet<&Path> = ops.iter().map(|op| op.from.as_path()).collect();
+    let mut target_counts: HashMap<String, usize> = HashMap::new();
+    for op in ops {
+        *target_counts.entry(key(&op.to)).or_insert(0) += 1;
+    }
+
+    let mut chained = 0;
+    let conflicts = ops
+        .iter()
+        .map(|op| {
+            let name = op.to.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let target = key(&op.to);
+            if target != key(&op.from) && sources.contains(&target) {
+                chained += 1;
+            }
+
+            if name.trim().is_empty() || name.contains(['/', '\\']) {
+                Some("Invalid file name".to_string())
+            } else if std::fs::symlink_metadata(&op.from).is_err() {
+                Some("File no longer exists".to_string())
+            } else if target_counts[&target] > 1 {
+                Some("Another file in this batch gets the same name".to_string())
+            } else if op.to.parent().is_some_and(|parent| !parent.is_dir()) {
+                Some("Destination folder does not exist".to_string())
+            } else if target_taken(op, &source_ids, &source_paths) {
+                // E.g. lowercasing photo.JPG next to a separate photo.jpg on a case-sensitive file system
+                if op.to.to_string_lossy().to_lowercase() == op.from.to_string_lossy().to_lowercase() {
+                    Some("Another file has this name in a different case".to_string())
+                } else {
+                    Some("A file with this name already exists".to_string())
+                }
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    RenameSimulation { conflicts, chained }
+}
+
+/// Temporary name next to the file, used while a batch is in flight
+fn temp_path(from: &Path, index: usize) -> PathBuf {
+    from.with_file_name(format!(".file-lister-rename-{}-{}.tmp", std::process::id(), index))
+}
+
+/// Undo completed steps (newest first); returns the paths that could not be restored
+fn roll_back(steps: &[(PathBuf, PathBuf)]) -> Vec<String> {
+    steps
+        .iter()
+        .rev()
+        .filter_map(|(from, to)| std::fs::rename(to, from).err().map(|e| format!("{}: {}", to.display(), e)))
+        .collect()
+}
+
+/// Apply a rename plan all-or-nothing.
+/// Every file is first moved to a temporary name and then to its new name, so chains and
+/// cycles (a -> b, b -> a) work; if any step fails, the completed steps are undone.
+pub fn apply(ops: &[RenameOp]) -> Result<(), String> {
+    let simulation = simulate(ops);
+    if simulation.conflict_count() > 0 {
+        return Err(format!("{} renames have conflicts; nothing was renamed", simulation.conflict_count()));
+    }
+
+    let temps: Vec<PathBuf> = ops.iter().enumerate().map(|(i, op)| temp_path(&op.from, i)).collect();
+
+    let mut to_temp: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for (op, temp) in ops.iter().zip(&temps) {
+        if let Err(e) = std::fs::rename(&op.from, temp) {
+            return Err(failure(&op.from, &e, roll_back(&to_temp)));
+        }
+        to_temp.push((op.from.clone(), temp.clone()));
+    }
+
+    let mut to_final: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for (op, temp) in ops.iter().zip(&temps) {
+        if let Err(e) = std::fs::rename(temp, &op.to) {
+            let mut unrestored = roll_back(&to_final);
+            unrestored.extend(roll_back(&to_temp));
+            return Err(failure(&op.from, &e, unrestored));
+        }
+        to_final.push((temp.clone(), op.to.clone()));
+    }
+    Ok(())
+}
+
+fn failure(path: &Path, error: &std::io::Error, unrestored: Vec<String>) -> String {
+    if unrestored.is_empty() {
+        format!("Renaming {} failed ({}); all renames were undone", path.display(), error)
+    } else {
+        format!(
+            "Renaming {} failed ({}); could not restore {} files: {}",
+            path.display(),
+            error,
+            unrestored.len(),
+            unrestored.join("; ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Empty folder under the temp directory for one test
+    fn temp_folder(name: &str) -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("file-lister-renames-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        fs::create_dir_all(&folder).unwrap();
+        folder
+    }
+
+    fn op(folder: &Path, from: &str, to: &str) -> RenameOp {
+        RenameOp {
+            from: folder.join(from),
+            to: folder.join(to),
+        }
+    }
+
+    #[test]
+    fn chains_and_swaps_are_applied() {
+        let folder = temp_folder("swap");
+        fs::write(folder.join("a.txt"), "a").unwrap();
+        fs::write(folder.join("b.txt"), "b").unwrap();
+        fs::write(folder.join("c.txt"), "c").unwrap();
+
+        let ops = [op(&folder, "a.txt", "b.txt"), op(&folder, "b.txt", "a.txt"), op(&folder, "c.txt", "d.txt")];
+        let simulation = simulate(&ops);
+        assert_eq!(simulation.conflicts, [None, None, None]);
+        assert_eq!(simulation.chained, 2);
+
+        apply(&ops).unwrap();
+        assert_eq!(fs::read_to_string(folder.join("a.txt")).unwrap(), "b");
+        assert_eq!(fs::read_to_string(folder.join("b.txt")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(folder.join("d.txt")).unwrap(), "c");
+        assert!(!folder.join("c.txt").exists());
+        let _ = fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn reports_conflicts_and_renames_nothing() {
+        let folder = temp_folder("conflicts");
+        for name in ["a.txt", "b.txt", "c.txt", "d.txt", "e.txt", "taken.txt"] {
+            fs::write(folder.join(name), name).unwrap();
+        }
+
+        let ops = [
+            op(&folder, "a.txt", "taken.txt"),
+            op(&folder, "b.txt", "same.txt"),
+            op(&folder, "c.txt", "same.txt"),
+            op(&folder, "gone.txt", "back.txt"),
+            op(&folder, "d.txt", " "),
+            op(&folder, "e.txt", "no-such-folder/e.txt"),
+        ];
+        let simulation = simulate(&ops);
+        let conflicts: Vec<Option<&str>> = simulation.conflicts.iter().map(Option::as_deref).collect();
+        assert_eq!(
+            conflicts,
+            [
+                Some("A file with this name already exists"),
+                Some("Another file in this batch gets the same name"),
+                Some("Another file in this batch gets the same name"),
+                Some("File no longer exists"),
+                Some("Invalid file name"),
+                Some("Destination folder does not exist"),
+            ]
+        );
+
+        assert_eq!(apply(&ops).unwrap_err(), "6 renames have conflicts; nothing was renamed");
+        assert_eq!(fs::read_to_string(folder.join("a.txt")).unwrap(), "a.txt");
+        assert_eq!(fs::read_to_string(folder.join("taken.txt")).unwrap(), "taken.txt");
+        let _ = fs::remove_dir_all(&folder);
+    }
+
+    /// Linux file systems are case-sensitive: photo.JPG and photo.jpg are two files
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn case_only_rename_next_to_another_file() {
+        let folder = temp_folder("case");
+        fs::write(folder.join("photo.JPG"), "upper").unwrap();
+        fs::write(folder.join("scan.PDF"), "scan").unwrap();
+        fs::write(folder.join("photo.jpg"), "lower").unwrap();
+
+        let ops = [op(&folder, "photo.JPG", "photo.jpg"), op(&folder, "scan.PDF", "scan.pdf")];
+        let simulation = simulate(&ops);
+        let conflicts: Vec<Option<&str>> = simulation.conflicts.iter().map(Option::as_deref).collect();
+        assert_eq!(conflicts, [Some("Another file has this name in a different case"), None]);
+
+        apply(&ops[1..]).unwrap();
+        assert_eq!(fs::read_to_string(folder.join("scan.pdf")).unwrap(), "scan");
+        let _ = fs::remove_dir_all(&folder);
+    }
+}
@@ -0,0 +1,507 @@
+use crate::csv_export::{self, ExportFormat, ExportOptions};
+use crate::file_scanner::{format_date, format_size, Calendar, FileInfo, SizeFormat};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Extensions listed in the summary, largest total size first
+const SUMMARY_EXTENSIONS: usize = 8;
+
+/// How the report is sent
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MailDelivery {
+    /// Compose a message in the default mail app for the user to send
+    #[default]
+    MailApp,
+    /// Send directly through the configured SMTP server
+    Smtp,
+}
+
+/// Encryption of the SMTP connection
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with STARTTLS (usually port 587)
+    #[default]
+    StartTls,
+    /// TLS from the start (usually port 465)
+    Tls,
+    /// Unencrypted (local relays only)
+    None,
+}
+
+impl SmtpSecurity {
+    pub const ALL: [SmtpSecurity; 3] = [SmtpSecurity::StartTls, SmtpSecurity::Tls, SmtpSecurity::None];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SmtpSecurity::StartTls => "STARTTLS",
+            SmtpSecurity::Tls => "TLS",
+            SmtpSecurity::None => "None",
+        }
+    }
+}
+
+/// Report e-mail settings ("report_mail" in settings.json)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportMailSettings {
+    /// Recipients, separated by commas or semicolons
+    pub to: String,
+    pub delivery: MailDelivery,
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub smtp_security: SmtpSecurity,
+    /// Sender address (also the login if no user name is set)
+    pub from: String,
+    pub smtp_user: String,
+    /// Stored in plain text in settings.json; the FILE_LISTER_SMTP_PASSWORD environment variable takes precedence
+    pub smtp_password: String,
+}
+
+impl Default for ReportMailSettings {
+    fn default() -> Self {
+        Self {
+            to: String::new(),
+            delivery: MailDelivery::MailApp,
+            smtp_host: String::new(),
+            smtp_port: 587,
+            smtp_security: SmtpSecurity::StartTls,
+            from: String::new(),
+            smtp_user: String::new(),
+            smtp_password: String::new(),
+        }
+    }
+}
+
+impl ReportMailSettings {
+    fn recipients(&self) -> Vec<&str> {
+        self.to.split([',', ';']).map(str::trim).filter(|r| !r.is_empty()).collect()
+    }
+
+    /// Missing setting that prevents sending with the selected delivery
+    pub fn missing(&self) -> Option<&'static str> {
+        match self.delivery {
+            MailDelivery::Smtp if self.recipients().is_empty() => Some("recipient"),
+            MailDelivery::Smtp if self.smtp_host.trim().is_empty() => Some("SMTP server"),
+            MailDelivery::Smtp if self.from.trim().is_empty() => Some("sender address"),
+            _ => None,
+        }
+    }
+
+    fn password(&self) -> String {
+        std::env::var("FILE_LISTER_SMTP_PASSWORD").unwrap_or_else(|_| self.smtp_password.clone())
+    }
+}
+
+/// Subject and summary text of a storage report
+pub fn summary(files: &[FileInfo], folders: &[PathBuf], size_format: &SizeFormat, calendar: Calendar) -> (String, String) {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let total: u64 = files.iter().map(|f| f.file_size).sum();
+    let subject = format!(
+        "File report: {} files, {} ({})",
+        files.len(),
+        format_size(total, size_format),
+        format_date(now, calendar)
+    );
+
+    let mut body = String::from("File report for:\n");
+    for folder in folders {
+        body.push_str(&format!("  {}\n", folder.display()));
+    }
+    body.push_str(&format!("Generated: {}\n\n", format_date(now, calendar)));
+    body.push_str(&format!("Files: {}\n", files.len()));
+    body.push_str(&format!("Total size: {}\n", format_size(total, size_format)));
+    if let Some(newest) = files.iter().max_by_key(|f| f.modified_timestamp) {
+        body.push_str(&format!(
+            "Newest file: {} ({})\n",
+            newest.relative_path(),
+            format_date(newest.modified_timestamp, calendar)
+        ));
+    }
+
+    // Largest types by total size
+    let mut by_extension: HashMap<String, (usize, u64)> = HashMap::new();
+    for file in files {
+        let entry = by_extension.entry(file.extension().to_lowercase()).or_default();
+        entry.0 += 1;
+        entry.1 += file.file_size;
+    }
+    let mut extensions: Vec<(String, (usize, u64))> = by_extension.into_iter().collect();
+    extensions.sort_by(|a, b| b.1 .1.cmp(&a.1 .1).then_with(|| a.0.cmp(&b.0)));
+    if !extensions.is_empty() {
+        body.push_str("\nLargest file types:\n");
+        for (extension, (count, bytes)) in extensions.iter().take(SUMMARY_EXTENSIONS) {
+            let name = if extension.is_empty() { "(none)".to_string() } else { format!(".{}", extension) };
+            body.push_str(&format!("  {:<10} {:>8} files  {}\n", name, count, format_size(*bytes, size_format)));
+        }
+    }
+    body.push_str("\nThe full listing is attached.\n");
+    (subject, body)
+}
+
+/// Write the report to a temporary file and send or compose the e-mail on a background thread.
+/// The result is a status line.
+pub fn spawn_send(
+    files: Vec<FileInfo>,
+    options: ExportOptions,
+    subject: String,
+    body: String,
+    settings: ReportMailSettings,
+) -> Receiver<Result<String, String>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let result = write_attachment(&files, &options).and_then(|attachment| match settings.delivery {
+            MailDelivery::Smtp => send_smtp(&settings, &subject, &body, &attachment, options.format)
+                .map(|()| format!("Report sent to {}", settings.recipients().join(", "))),
+            MailDelivery::MailApp => compose(&settings.recipients(), &subject, &body, &attachment),
+        });
+        let _ = tx.send(result);
+    });
+    rx
+}
+
+/// Export the listing into a fresh folder under the temp directory
+fn write_attachment(files: &[FileInfo], options: &ExportOptions) -> Result<PathBuf, String> {
+    let stamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let dir = std::env::temp_dir().join("file-lister-report").join(stamp.to_string());
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("files.{}", options.format.extension()));
+    csv_export::export_with_options(files, options, &path).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// Open a new message in the mail app. Attaching needs MAPI (Windows), xdg-email (Linux) or Mail (macOS);
+/// otherwise a mailto: link opens the message without it and the report's folder is shown instead.
+fn compose(to: &[&str], subject: &str, body: &str, attachment: &Path) -> Result<String, String> {
+    if compose_with_attachment(to, subject, body, attachment) {
+        return Ok("Report opened in the mail app".to_string());
+    }
+    let url = format!(
+        "mailto:{}?subject={}&body={}",
+        to.iter().map(|r| percent_encode(r)).collect::<Vec<_>>().join(","),
+        percent_encode(subject),
+        percent_encode(body)
+    );
+    open::that(&url).map_err(|e| format!("No mail app found: {}", e))?;
+    if let Some(folder) = attachment.parent() {
+        let _ = open::that(folder);
+    }
+    Ok(format!("Report opened in the mail app; attach {} (its folder was opened)", attachment.display()))
+}
+
+#[cfg(windows)]
+fn compose_with_attachment(to: &[&str], subject: &str, body: &str, attachment: &Path) -> bool {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::FreeLibrary;
+    use windows_sys::Win32::System::LibraryLoader::{GetProcAddress, LoadLibraryW};
+    use windows_sys::Win32::System::Mapi::{
+        MapiFileDescW, MapiMessageW, MapiRecipDescW, LPMAPISENDMAILW, MAPI_DIALOG, MAPI_LOGON_UI, MAPI_TO,
+        MAPI_USER_ABORT, SUCCESS_SUCCESS,
+    };
+
+    let wide = |text: &str| -> Vec<u16> { text.encode_utf16().chain(Some(0)).collect() };
+    let mut subject = wide(subject);
+    let mut body = wide(body);
+    let mut path: Vec<u16> = attachment.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut addresses: Vec<Vec<u16>> = to.iter().map(|r| wide(&format!("SMTP:{}", r))).collect();
+    let mut recipients: Vec<MapiRecipDescW> = addresses
+        .iter_mut()
+        .map(|address| MapiRecipDescW {
+            ulReserved: 0,
+            ulRecipClass: MAPI_TO,
+            lpszName: address.as_mut_ptr().wrapping_add(5),
+            lpszAddress: address.as_mut_ptr(),
+            ulEIDSize: 0,
+            lpEntryID: std::ptr::null_mut(),
+        })
+        .collect();
+    let mut file = MapiFileDescW {
+        ulReserved: 0,
+        flFlags: 0,
+        nPosition: u32::MAX,
+        lpszPathName: path.as_mut_ptr(),
+        lpszFileName: std::ptr::null_mut(),
+        lpFileType: std::ptr::null_mut(),
+    };
+    let message = MapiMessageW {
+        ulReserved: 0,
+        lpszSubject: subject.as_mut_ptr(),
+        lpszNoteText: body.as_mut_ptr(),
+        lpszMessageType: std::ptr::null_mut(),
+        lpszDateReceived: std::ptr::null_mut(),
+        lpszConversationID: std::ptr::null_mut(),
+        flFlags: 0,
+        lpOriginator: std::ptr::null_mut(),
+        nRecipCount: recipients.len() as u32,
+        lpRecips: if recipients.is_empty() { std::ptr::null_mut() } else { recipients.as_mut_ptr() },
+        nFileCount: 1,
+        lpFiles: &mut file,
+    };
+
+    unsafe {
+        let library = LoadLibraryW(wide("mapi32.dll").as_ptr());
+        if library.is_null() {
+            return false;
+        }
+        let result = match GetProcAddress(library, c"MAPISendMailW".as_ptr().cast()) {
+            Some(proc) => {
+                let send: LPMAPISENDMAILW = std::mem::transmute(proc);
+                send.map(|send| send(0, 0, &message, MAPI_DIALOG | MAPI_LOGON_UI, 0))
+            }
+            None => None,
+        };
+        FreeLibrary(library);
+        // The user closing the draft counts as handled
+        matches!(result, Some(SUCCESS_SUCCESS | MAPI_USER_ABORT))
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn compose_with_attachment(to: &[&str], subject: &str, body: &str, attachment: &Path) -> bool {
+    std::process::Command::new("xdg-email")
+        .arg("--subject")
+        .arg(subject)
+        .arg("--body")
+        .arg(body)
+        .arg("--attach")
+        .arg(attachment)
+        .args(to)
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(target_os = "macos")]
+fn compose_with_attachment(to: &[&str], subject: &str, body: &str, attachment: &Path) -> bool {
+    let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+    let recipients: String = to
+        .iter()
+        .map(|r| format!("make new to recipient at end of to recipients with properties {{address:{}}}\n", quote(r)))
+        .collect();
+    let script = format!(
+        "tell application \"Mail\"\n\
+         set message to make new outgoing message with properties {{subject:{}, content:{}, visible:true}}\n\
+         tell message\n{}make new attachment with properties {{file name:POSIX file {}}} at after the last paragraph\nend tell\n\
+         activate\nend tell",
+        quote(subject),
+        quote(body),
+        recipients,
+        quote(&attachment.to_string_lossy())
+    );
+    std::process::Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn compose_with_attachment(_to: &[&str], _subject: &str, _body: &str, _attachment: &Path) -> bool {
+    false
+}
+
+/// Percent-encode a mailto: component
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'@' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// SMTP connection, plain or TLS
+enum Connection {
+    Plain(TcpStream),
+    Tls(Box<rustls::StreamOwned<rustls::ClientConnection, TcpStream>>),
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Connection::Plain(stream) => stream.read(buf),
+            Connection::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Connection::Plain(stream) => stream.write(buf),
+            Connection::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Connection::Plain(stream) => stream.flush(),
+            Connection::Tls(stream) => stream.flush(),
+        }
+    }
+}
+
+impl Connection {
+    /// Wrap a TCP stream in TLS, verifying the server against the bundled root certificates
+    fn tls(stream: TcpStream, host: &str) -> Result<Self, String> {
+        let roots = rustls::RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        let config = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| e.to_string())?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let name = rustls::pki_types::ServerName::try_from(host.to_string()).map_err(|e| e.to_string())?;
+        let connection = rustls::ClientConnection::new(Arc::new(config), name).map_err(|e| e.to_string())?;
+        Ok(Connection::Tls(Box::new(rustls::StreamOwned::new(connection, stream))))
+    }
+
+    /// Read a (possibly multi-line) reply; Err unless its code starts with `expected`
+    fn reply(&mut self, expected: char) -> Result<String, String> {
+        let mut text = String::new();
+        loop {
+            let mut line = Vec::new();
+            let mut byte = [0u8; 1];
+            while line.last() != Some(&b'\n') {
+                match self.read(&mut byte) {
+                    Ok(0) => return Err("The SMTP server closed the connection".to_string()),
+                    Ok(_) => line.push(byte[0]),
+                    Err(e) => return Err(format!("SMTP: {}", e)),
+                }
+            }
+            let line = String::from_utf8_lossy(&line).trim_end().to_string();
+            text.push_str(&line);
+            text.push('\n');
+            // "250-..." continues, "250 ..." ends the reply
+            if line.as_bytes().get(3) != Some(&b'-') {
+                break;
+            }
+        }
+        if text.starts_with(expected) {
+            Ok(text)
+        } else {
+            Err(format!("SMTP server: {}", text.trim()))
+        }
+    }
+
+    fn command(&mut self, line: &str, expected: char) -> Result<String, String> {
+        self.write_all(format!("{}\r\n", line).as_bytes())
+            .and_then(|()| self.flush())
+            .map_err(|e| format!("SMTP: {}", e))?;
+        self.reply(expected)
+    }
+}
+
+fn send_smtp(
+    settings: &ReportMailSettings,
+    subject: &str,
+    body: &str,
+    attachment: &Path,
+    format: ExportFormat,
+) -> Result<(), String> {
+    let host = settings.smtp_host.trim();
+    let stream = TcpStream::connect((host, settings.smtp_port)).map_err(|e| format!("Can't reach {}: {}", host, e))?;
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(60)));
+    let _ = stream.set_write_timeout(Some(Duration::from_secs(60)));
+
+    let mut connection = match settings.smtp_security {
+        SmtpSecurity::Tls => Connection::tls(stream, host)?,
+        _ => Connection::Plain(stream),
+    };
+    connection.reply('2')?;
+    connection.command("EHLO file-lister", '2')?;
+    if settings.smtp_security == SmtpSecurity::StartTls {
+        connection.command("STARTTLS", '2')?;
+        let Connection::Plain(stream) = connection else {
+            return Err("STARTTLS on an encrypted connection".to_string());
+        };
+        connection = Connection::tls(stream, host)?;
+        connection.command("EHLO file-lister", '2')?;
+    }
+
+    let from = settings.from.trim();
+    let user = match settings.smtp_user.trim() {
+        "" => from,
+        user => user,
+    };
+    let password = settings.password();
+    if !password.is_empty() {
+        let credentials = BASE64.encode(format!("\0{}\0{}", user, password));
+        connection.command(&format!("AUTH PLAIN {}", credentials), '2')?;
+    }
+
+    let recipients = settings.recipients();
+    connection.command(&format!("MAIL FROM:<{}>", from), '2')?;
+    for recipient in &recipients {
+        connection.command(&format!("RCPT TO:<{}>", recipient), '2')?;
+    }
+    connection.command("DATA", '3')?;
+    let message = mime_message(from, &recipients, subject, body, attachment, format)?;
+    connection
+        .write_all(message.as_bytes())
+        .map_err(|e| format!("SMTP: {}", e))?;
+    connection.command(".", '2')?;
+    let _ = connection.command("QUIT", '2');
+    Ok(())
+}
+
+/// multipart/mixed message: the summary as text and the report as a base64 attachment
+fn mime_message(
+    from: &str,
+    to: &[&str],
+    subject: &str,
+    body: &str,
+    attachment: &Path,
+    format: ExportFormat,
+) -> Result<String, String> {
+    let content = std::fs::read(attachment).map_err(|e| e.to_string())?;
+    let file_name = attachment.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let boundary = format!("file-lister-{:x}", content.len() as u64 ^ 0x5eed_f11e);
+
+    let mut message = String::new();
+    message.push_str(&format!("From: <{}>\r\n", from));
+    message.push_str(&format!("To: {}\r\n", to.iter().map(|r| format!("<{}>", r)).collect::<Vec<_>>().join(", ")));
+    message.push_str(&format!("Subject: =?UTF-8?B?{}?=\r\n", BASE64.encode(subject)));
+    message.push_str(&format!("Date: {}\r\n", chrono::Local::now().to_rfc2822()));
+    message.push_str("MIME-Version: 1.0\r\n");
+    message.push_str(&format!("Content-Type: multipart/mixed; boundary=\"{}\"\r\n\r\n", boundary));
+
+    message.push_str(&format!("--{}\r\n", boundary));
+    message.push_str("Content-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: base64\r\n\r\n");
+    message.push_str(&wrap_base64(body.replace('\n', "\r\n").as_bytes()));
+
+    message.push_str(&format!("--{}\r\n", boundary));
+    message.push_str(&format!("Content-Type: {}; charset=utf-8; name=\"{}\"\r\n", format.mime_type(), file_name));
+    message.push_str("Content-Transfer-Encoding: base64\r\n");
+    message.push_str(&format!("Content-Disposition: attachment; filename=\"{}\"\r\n\r\n", file_name));
+    message.push_str(&wrap_base64(&content));
+    message.push_str(&format!("--{}--\r\n", boundary));
+    Ok(message)
+}
+
+/// Base64 in 76-character lines (base64 never starts a line with ".", so no dot-stuffing is needed)
+fn wrap_base64(data: &[u8]) -> String {
+    let encoded = BASE64.encode(data);
+    let mut wrapped = String::with_capacity(encoded.len() + encoded.len() / 38 + 2);
+    for line in encoded.as_bytes().chunks(76) {
+        wrapped.push_str(&String::from_utf8_lossy(line));
+        wrapped.push_str("\r\n");
+    }
+    wrapped
+}
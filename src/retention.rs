@@ -0,0 +1,532 @@
+use crate::file_scanner::{self, format_date, format_size, Calendar, ScanFilter, SizeFormat};
+use crate::jobs::{self, JobError};
+use crate::users;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Runs kept in the execution log (oldest are dropped)
+const MAX_LOGGED_RUNS: usize = 50;
+
+/// What a rule does with the files it matches
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionAction {
+    /// Move into the destination folder, keeping the folder structure below the rule's folder
+    #[default]
+    Move,
+    /// Delete permanently (can't be undone)
+    Delete,
+}
+
+impl RetentionAction {
+    pub const ALL: [RetentionAction; 2] = [RetentionAction::Move, RetentionAction::Delete];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RetentionAction::Move => "Move to",
+            RetentionAction::Delete => "Delete",
+        }
+    }
+}
+
+/// A retention rule ("retention_rules" in settings.json): files below `folder` that match
+/// every condition get the action. Zero limits and an empty pattern list match everything.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionRule {
+    pub name: String,
+    pub folder: PathBuf,
+    /// Modified at least this many days ago
+    pub older_than_days: u32,
+    /// Larger than this many MB (10^6 bytes)
+    pub larger_than_mb: u64,
+    /// Wildcards as in profile excludes, separated by spaces ("*.iso logs/*"); the file must match one
+    pub patterns: String,
+    pub action: RetentionAction,
+    /// Archive folder for Move
+    pub destination: PathBuf,
+    pub enabled: bool,
+}
+
+impl Default for RetentionRule {
+    fn default() -> Self {
+        Self {
+            name: "New rule".to_string(),
+            folder: PathBuf::new(),
+            older_than_days: 180,
+            larger_than_mb: 0,
+            patterns: String::new(),
+            action: RetentionAction::Move,
+            destination: PathBuf::new(),
+            enabled: true,
+        }
+    }
+}
+
+impl RetentionRule {
+    /// What still has to be filled in before the rule can run
+    pub fn missing(&self) -> Option<&'static str> {
+        if self.folder.as_os_str().is_empty() {
+            Some("folder")
+        } else if self.action == RetentionAction::Move && self.destination.as_os_str().is_empty() {
+            Some("destination folder")
+        } else {
+            None
+        }
+    }
+
+    /// Folders the rule works in: its folder and, for Move, the archive folder
+    pub fn folders(&self) -> Vec<&Path> {
+        match self.action {
+            RetentionAction::Move => vec![&self.folder, &self.destination],
+            RetentionAction::Delete => vec![&self.folder],
+        }
+    }
+
+    /// "older than 180 days, larger than 100 MB, *.iso → Move to D:\Archive"
+    pub fn describe(&self) -> String {
+        let mut conditions = Vec::new();
+        if self.older_than_days > 0 {
+            conditions.push(format!("older than {} days", self.older_than_days));
+        }
+        if self.larger_than_mb > 0 {
+            conditions.push(format!("larger than {} MB", self.larger_than_mb));
+        }
+        if !self.patterns.trim().is_empty() {
+            conditions.push(self.patterns.trim().to_string());
+        }
+        if conditions.is_empty() {
+            conditions.push("all files".to_string());
+        }
+        let action = match self.action {
+            RetentionAction::Move => format!("move to {}", self.destination.display()),
+            RetentionAction::Delete => "delete".to_string(),
+        };
+        format!("{} in {} → {}", conditions.join(", "), self.folder.display(), action)
+    }
+}
+
+/// A file a rule will act on
+#[derive(Debug, Clone)]
+pub struct PlannedItem {
+    pub rule: String,
+    pub action: RetentionAction,
+    pub source: PathBuf,
+    /// Where a moved file goes
+    pub destination: Option<PathBuf>,
+    pub size: u64,
+    pub modified: i64,
+}
+
+/// Dry run: the files the enabled rules match right now. A file matched by several rules
+/// gets the first rule's action; files already in a rule's archive folder are skipped.
+pub fn plan(rules: &[RetentionRule]) -> Result<Vec<PlannedItem>, String> {
+    let now = unix_now();
+    let mut items: Vec<PlannedItem> = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    for rule in rules.iter().filter(|rule| rule.enabled) {
+        if let Some(missing) = rule.missing() {
+            return Err(format!("Rule \"{}\": set the {}", rule.name, missing));
+        }
+        let files = file_scanner::scan_folder(&rule.folder, true, ScanFilter::default())
+            .map_err(|e| format!("Rule \"{}\": {}: {}", rule.name, rule.folder.display(), e))?;
+        let patterns: Vec<String> = rule.patterns.split_whitespace().map(str::to_string).collect();
+        let archive = rule.destination.canonicalize().ok();
+        for file in files {
+            let source = PathBuf::from(file.absolute_path());
+            let old_enough = now - file.modified_timestamp >= i64::from(rule.older_than_days) * 86_400;
+            // 0 MB means no size condition, so empty files match too
+            let large_enough = rule.larger_than_mb == 0 || file.file_size > rule.larger_than_mb.saturating_mul(1_000_000);
+            // is_excluded matches the same wildcards as profile excludes
+            let matches = patterns.is_empty() || file_scanner::is_excluded(&file, &patterns);
+            let archived = archive.as_ref().is_some_and(|archive| source.starts_with(archive));
+            if !(old_enough && large_enough && matches) || archived || !seen.insert(source.clone()) {
+                continue;
+            }
+            items.push(PlannedItem {
+                rule: rule.name.clone(),
+                action: rule.action,
+                destination: (rule.action == RetentionAction::Move)
+                    .then(|| rule.destination.join(file.relative_path())),
+                source,
+                size: file.file_size,
+                modified: file.modified_timestamp,
+            });
+        }
+    }
+    Ok(items)
+}
+
+/// Dry-run report: totals per rule, then one line per file
+pub fn report(rules: &[RetentionRule], items: &[PlannedItem], size_format: &SizeFormat, calendar: Calendar) -> String {
+    let mut text = format!("Retention dry run, {}\n\n", format_date(unix_now(), calendar));
+    for rule in rules.iter().filter(|rule| rule.enabled) {
+        let matched: Vec<&PlannedItem> = items.iter().filter(|item| item.rule == rule.name).collect();
+        text.push_str(&format!(
+            "{}: {} files, {} ({})\n",
+            rule.name,
+            matched.len(),
+            format_size(matched.iter().map(|item| item.size).sum(), size_format),
+            rule.describe()
+        ));
+    }
+    text.push('\n');
+    for item in items {
+        let target = match &item.destination {
+            Some(destination) => format!("→ {}", destination.display()),
+            None => "delete".to_string(),
+        };
+        text.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\n",
+            item.rule,
+            item.source.display(),
+            format_size(item.size, size_format),
+            format_date(item.modified, calendar),
+            target
+        ));
+    }
+    text
+}
+
+/// Carry out one planned item; a moved file never overwrites an existing one
+pub fn apply(item: &PlannedItem, on_bytes: &mut dyn FnMut(u64)) -> Result<(), JobError> {
+    match &item.destination {
+        Some(destination) => move_file(&item.source, destination, on_bytes),
+        None => fs::remove_file(&item.source).map_err(JobError::from),
+    }
+}
+
+/// Move a file, creating the destination's folders; copy + delete across volumes
+pub fn move_file(from: &Path, to: &Path, on_bytes: &mut dyn FnMut(u64)) -> Result<(), JobError> {
+    if to.exists() {
+        return Err(format!("{} already exists", to.display()).into());
+    }
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(from, to)
+        .or_else(|_| {
+            jobs::copy_with_progress(from, to, on_bytes)?;
+            fs::remove_file(from)
+        })
+        .map_err(JobError::from)
+}
+
+/// One file of an executed run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub rule: String,
+    pub action: RetentionAction,
+    pub source: PathBuf,
+    pub destination: Option<PathBuf>,
+    /// None if it succeeded
+    pub error: Option<String>,
+    /// Moved back by an undo
+    #[serde(default)]
+    pub moved_back: bool,
+}
+
+impl LogEntry {
+    pub fn new(item: &PlannedItem, result: &Result<(), JobError>) -> Self {
+        Self {
+            rule: item.rule.clone(),
+            action: item.action,
+            source: item.source.clone(),
+            destination: item.destination.clone(),
+            error: result.as_ref().err().map(|e| e.message.clone()),
+            moved_back: false,
+        }
+    }
+
+    /// Moved successfully and not moved back yet
+    pub fn is_undoable(&self) -> bool {
+        self.error.is_none() && self.destination.is_some() && !self.moved_back
+    }
+}
+
+/// One executed run of the rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionRun {
+    /// Unix seconds; also identifies the run
+    pub started_at: i64,
+    pub entries: Vec<LogEntry>,
+    pub undone_at: Option<i64>,
+}
+
+impl RetentionRun {
+    /// A run starting now; entries are added as files are done
+    pub fn start() -> Self {
+        Self {
+            started_at: unix_now(),
+            entries: Vec::new(),
+            undone_at: None,
+        }
+    }
+
+    /// (destination, source) of the files it moved that are still to be moved back
+    pub fn moves(&self) -> Vec<(PathBuf, PathBuf)> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.is_undoable())
+            .filter_map(|entry| Some((entry.destination.clone()?, entry.source.clone())))
+            .collect()
+    }
+
+    pub fn summary(&self) -> String {
+        let moved = self.entries.iter().filter(|e| e.error.is_none() && e.destination.is_some()).count();
+        let deleted = self.entries.iter().filter(|e| e.error.is_none() && e.destination.is_none()).count();
+        let failed = self.entries.iter().filter(|e| e.error.is_some()).count();
+        format!("{} moved, {} deleted, {} failed", moved, deleted, failed)
+    }
+}
+
+/// Execution log of retention runs (retention_log.json next to settings.json)
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RetentionLog {
+    pub runs: Vec<RetentionRun>,
+}
+
+impl RetentionLog {
+    pub fn path() -> PathBuf {
+        users::config_dir().join("retention_log.json")
+    }
+
+    /// Load the log (empty if missing or unreadable)
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        }
+        let content =
+            serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize retention log: {}", e))?;
+        fs::write(&path, content).map_err(|e| format!("Failed to write retention log: {}", e))
+    }
+
+    /// Add a run and save
+    pub fn record(run: RetentionRun) -> Result<(), String> {
+        let mut log = Self::load();
+        log.runs.push(run);
+        let excess = log.runs.len().saturating_sub(MAX_LOGGED_RUNS);
+        log.runs.drain(..excess);
+        log.save()
+    }
+
+    /// Newest run with moves that haven't been undone
+    pub fn last_undoable(&self) -> Option<&RetentionRun> {
+        self.runs
+            .iter()
+            .rev()
+            .find(|run| run.undone_at.is_none() && run.entries.iter().any(LogEntry::is_undoable))
+    }
+
+    /// Record the files of a run that were moved back and save; the run is undone once none
+    /// are left, otherwise the next undo retries the rest
+    pub fn mark_moved_back(started_at: i64, sources: &[PathBuf]) -> Result<(), String> {
+        let mut log = Self::load();
+        if let Some(run) = log.runs.iter_mut().find(|run| run.started_at == started_at) {
+            for entry in run.entries.iter_mut().filter(|entry| sources.contains(&entry.source)) {
+                entry.moved_back = true;
+            }
+            if !run.entries.iter().any(LogEntry::is_undoable) {
+                run.undone_at = Some(unix_now());
+            }
+        }
+        log.save()
+    }
+}
+
+/// Run the rules now and log the run (command line: no job window, one file after another)
+pub fn run_now(rules: &[RetentionRule]) -> Result<RetentionRun, String> {
+    let items = plan(rules)?;
+    let mut run = RetentionRun::start();
+    run.entries = items
+        .iter()
+        .map(|item| LogEntry::new(item, &apply(item, &mut |_| {})))
+        .collect();
+    RetentionLog::record(run.clone())?;
+    Ok(run)
+}
+
+/// Move the files of the newest undoable run back; returns (restored, errors). Files that
+/// could not be moved back stay in the log for the next undo.
+pub fn undo_last() -> Result<(usize, Vec<String>), String> {
+    let log = RetentionLog::load();
+    let run = log.last_undoable().ok_or("No retention run to undo")?;
+    let mut restored = Vec::new();
+    let mut errors = Vec::new();
+    for (destination, source) in run.moves() {
+        match move_file(&destination, &source, &mut |_| {}) {
+            Ok(()) => restored.push(source),
+            Err(e) => errors.push(format!("{}: {}", destination.display(), e.message)),
+        }
+    }
+    RetentionLog::mark_moved_back(run.started_at, &restored)?;
+    Ok((restored.len(), errors))
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Empty folder under the temp directory for one test
+    fn temp_folder(name: &str) -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("file-lister-retention-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        fs::create_dir_all(&folder).unwrap();
+        folder
+    }
+
+    /// File of `size` bytes last modified `days_ago` days ago
+    fn write_file(path: &Path, size: usize, days_ago: u64) {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(path, vec![b'x'; size]).unwrap();
+        let modified = SystemTime::now() - Duration::from_secs(days_ago * 86_400);
+        fs::File::options().write(true).open(path).unwrap().set_modified(modified).unwrap();
+    }
+
+    fn rule(folder: &Path, destination: &Path) -> RetentionRule {
+        RetentionRule {
+            name: "Old files".to_string(),
+            folder: folder.to_path_buf(),
+            older_than_days: 30,
+            destination: destination.to_path_buf(),
+            ..Default::default()
+        }
+    }
+
+    fn sources(items: &[PlannedItem]) -> Vec<PathBuf> {
+        let mut sources: Vec<PathBuf> = items.iter().map(|item| item.source.clone()).collect();
+        sources.sort();
+        sources
+    }
+
+    #[test]
+    fn plans_old_files_and_keeps_their_subfolders() {
+        let root = temp_folder("age");
+        let folder = root.join("projects");
+        let archive = root.join("archive");
+        write_file(&folder.join("old.txt"), 10, 60);
+        write_file(&folder.join("2019/report.pdf"), 10, 400);
+        write_file(&folder.join("new.txt"), 10, 1);
+
+        let items = plan(&[rule(&folder, &archive)]).unwrap();
+        assert_eq!(sources(&items), [folder.join("2019").join("report.pdf"), folder.join("old.txt")]);
+        let report = items.iter().find(|item| item.source.ends_with("report.pdf")).unwrap();
+        assert_eq!(report.action, RetentionAction::Move);
+        assert_eq!(report.destination.as_deref(), Some(archive.join("2019").join("report.pdf").as_path()));
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn zero_size_limit_matches_empty_files() {
+        let root = temp_folder("size");
+        write_file(&root.join("empty.log"), 0, 60);
+        write_file(&root.join("big.iso"), 2_000_001, 60);
+
+        let all = RetentionRule {
+            action: RetentionAction::Delete,
+            ..rule(&root, Path::new(""))
+        };
+        assert_eq!(plan(&[all]).unwrap().len(), 2);
+
+        let large = RetentionRule {
+            action: RetentionAction::Delete,
+            larger_than_mb: 2,
+            ..rule(&root, Path::new(""))
+        };
+        let items = plan(&[large]).unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(items[0].source.ends_with("big.iso"));
+        assert_eq!(items[0].destination, None);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn patterns_limit_the_files() {
+        let root = temp_folder("patterns");
+        write_file(&root.join("disk.iso"), 10, 60);
+        write_file(&root.join("notes.txt"), 10, 60);
+        write_file(&root.join("logs/app.log"), 10, 60);
+
+        let items = plan(&[RetentionRule {
+            action: RetentionAction::Delete,
+            patterns: "*.iso logs/*".to_string(),
+            ..rule(&root, Path::new(""))
+        }])
+        .unwrap();
+        assert_eq!(sources(&items), [root.join("disk.iso"), root.join("logs").join("app.log")]);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn first_rule_wins_and_skips_its_archive() {
+        let root = temp_folder("rules");
+        let archive = root.join("archive");
+        write_file(&root.join("old.txt"), 10, 60);
+        write_file(&archive.join("moved-before.txt"), 10, 60);
+
+        let delete = RetentionRule {
+            name: "Delete old".to_string(),
+            action: RetentionAction::Delete,
+            ..rule(&root, Path::new(""))
+        };
+        let items = plan(&[rule(&root, &archive), delete]).unwrap();
+        assert_eq!(sources(&items), [archive.join("moved-before.txt"), root.join("old.txt")]);
+        let rule_of = |name: &str| &items.iter().find(|item| item.source.ends_with(name)).unwrap().rule;
+        assert_eq!(rule_of("old.txt"), "Old files");
+        // Only the Move rule skips its own archive folder
+        assert_eq!(rule_of("moved-before.txt"), "Delete old");
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn moved_back_files_leave_the_undo() {
+        let entry = |source: &str, moved_back: bool| LogEntry {
+            rule: "Old files".to_string(),
+            action: RetentionAction::Move,
+            source: PathBuf::from(source),
+            destination: Some(Path::new("archive").join(source)),
+            error: None,
+            moved_back,
+        };
+        let mut run = RetentionRun::start();
+        run.entries = vec![entry("a.txt", true), entry("b.txt", false)];
+        assert_eq!(run.moves(), [(Path::new("archive").join("b.txt"), PathBuf::from("b.txt"))]);
+        assert!(!run.entries[0].is_undoable());
+    }
+
+    #[test]
+    fn disabled_and_incomplete_rules() {
+        let root = temp_folder("incomplete");
+        write_file(&root.join("old.txt"), 10, 60);
+
+        let disabled = RetentionRule {
+            enabled: false,
+            ..rule(&root, &root.join("archive"))
+        };
+        assert!(plan(&[disabled]).unwrap().is_empty());
+
+        let error = plan(&[rule(&root, Path::new(""))]).unwrap_err();
+        assert_eq!(error, "Rule \"Old files\": set the destination folder");
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,86 @@
+use std::cell::Cell;
+use std::io;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// Retries after the first failed attempt (configured from the settings)
+static MAX_RETRIES: AtomicU32 = AtomicU32::new(3);
+/// Delay before the first retry; doubled for every further retry
+static BASE_DELAY_MS: AtomicU64 = AtomicU64::new(200);
+/// Retries made since startup, on any thread
+static TOTAL_RETRIES: AtomicUsize = AtomicUsize::new(0);
+
+/// Upper bound for a single backoff delay
+const MAX_DELAY_MS: u64 = 5000;
+
+thread_local! {
+    /// Retries made on the current thread (a scan reads its own count)
+    static THREAD_RETRIES: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Set the retry count and first backoff delay used by scans and previews
+pub fn configure(retries: u32, base_delay_ms: u64) {
+    MAX_RETRIES.store(retries, Ordering::Relaxed);
+    BASE_DELAY_MS.store(base_delay_ms, Ordering::Relaxed);
+}
+
+/// Retries made since startup
+pub fn total_retries() -> usize {
+    TOTAL_RETRIES.load(Ordering::Relaxed)
+}
+
+/// Retries made on this thread since the last call
+pub fn take_thread_retries() -> usize {
+    THREAD_RETRIES.with(|count| count.replace(0))
+}
+
+/// Errors a network share or flaky drive can recover from (timeouts, dropped connections, busy files).
+/// Not found and permission errors fail at once.
+pub fn is_transient(error: &io::Error) -> bool {
+    use io::ErrorKind::*;
+    if matches!(
+        error.kind(),
+        TimedOut | Interrupted | WouldBlock | ConnectionReset | ConnectionAborted | NotConnected | BrokenPipe | ResourceBusy
+    ) {
+        return true;
+    }
+    error.raw_os_error().is_some_and(is_transient_os_error)
+}
+
+#[cfg(windows)]
+fn is_transient_os_error(code: i32) -> bool {
+    // ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION, ERROR_BAD_NETPATH, ERROR_NETWORK_BUSY,
+    // ERROR_UNEXP_NET_ERR, ERROR_NETNAME_DELETED, ERROR_SEM_TIMEOUT
+    matches!(code, 32 | 33 | 53 | 54 | 59 | 64 | 121)
+}
+
+#[cfg(unix)]
+fn is_transient_os_error(code: i32) -> bool {
+    // EIO from a dropped NFS/SMB mount, ESTALE file handles, EHOSTDOWN while a share reconnects
+    matches!(code, libc::EIO | libc::ESTALE | libc::EHOSTDOWN | libc::EHOSTUNREACH)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_transient_os_error(_code: i32) -> bool {
+    false
+}
+
+/// Run a file system operation, retrying transient errors with exponential backoff
+pub fn with_retry<T>(mut operation: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    let max_retries = MAX_RETRIES.load(Ordering::Relaxed);
+    let mut delay = BASE_DELAY_MS.load(Ordering::Relaxed);
+    let mut attempt = 0;
+    loop {
+        match operation() {
+            Err(e) if attempt < max_retries && is_transient(&e) => {
+                attempt += 1;
+                TOTAL_RETRIES.fetch_add(1, Ordering::Relaxed);
+                THREAD_RETRIES.with(|count| count.set(count.get() + 1));
+                thread::sleep(Duration::from_millis(delay));
+                delay = (delay * 2).min(MAX_DELAY_MS);
+            }
+            result => return result,
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::file_scanner::FileInfo;
+use crate::users;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Files marked as reviewed, kept across sessions.
+/// A mark only holds while the file's modification time is unchanged.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReviewedFiles {
+    /// Absolute path -> modification timestamp when it was reviewed
+    entries: HashMap<String, i64>,
+}
+
+impl ReviewedFiles {
+    /// Path of the reviewed-files list (next to settings.json)
+    pub fn path() -> PathBuf {
+        users::config_dir().join("reviewed.json")
+    }
+
+    /// Load the list (empty if missing or unreadable)
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        }
+        let content = serde_json::to_string(self).map_err(|e| format!("Failed to serialize reviewed files: {}", e))?;
+        std::fs::write(&path, content).map_err(|e| format!("Failed to write reviewed files: {}", e))
+    }
+
+    /// Reviewed and not modified since
+    pub fn contains(&self, file: &FileInfo) -> bool {
+        self.entries.get(file.absolute_path()) == Some(&file.modified_timestamp)
+    }
+
+    pub fn mark(&mut self, file: &FileInfo) {
+        self.entries.insert(file.absolute_path().to_string(), file.modified_timestamp);
+    }
+
+    pub fn unmark(&mut self, file: &FileInfo) {
+        self.entries.remove(file.absolute_path());
+    }
+
+    /// Forget all marks
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
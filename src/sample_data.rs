@@ -0,0 +1,105 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Folder (in the temp folder) the sample data is written to, recreated each time
+const SAMPLE_FOLDER: &str = "file-lister-sample";
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Text of the sample notes and documents
+const NOTES: &str = "Meeting notes\n\nAgenda:\n- Budget review\n- Office move\n- Holiday schedule\n\n\
+                     Action items are listed in the shared tracker.\n";
+
+/// A gradient picture, different for each seed so the images are not duplicates of each other
+fn sample_image(width: u32, height: u32, seed: u8) -> image::RgbImage {
+    image::RgbImage::from_fn(width, height, |x, y| {
+        let r = (x * 255 / width) as u8;
+        let g = (y * 255 / height) as u8;
+        image::Rgb([r, g, seed.wrapping_mul(40)])
+    })
+}
+
+fn write(path: &Path, contents: &[u8]) -> Result<(), String> {
+    fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn save_image(path: &Path, image: &image::RgbImage, format: image::ImageFormat) -> Result<(), String> {
+    image
+        .save_with_format(path, format)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Backdate a file so the date filters and columns have something to show
+fn set_age(path: &Path, days: u32) -> Result<(), String> {
+    let file = File::options().write(true).open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    file.set_modified(SystemTime::now() - DAY * days)
+        .map_err(|e| format!("Failed to set the date of {}: {}", path.display(), e))
+}
+
+/// Write a folder of varied fake files for trying out filters, previews and bulk operations
+/// without touching real shares: images, documents, code, duplicates, empty files and folders,
+/// files with a wrong or missing extension, awkward names and old dates. Returns the folder.
+pub fn create_sample_folder() -> Result<PathBuf, String> {
+    let root = std::env::temp_dir().join(SAMPLE_FOLDER);
+    if root.exists() {
+        fs::remove_dir_all(&root).map_err(|e| format!("Failed to clear {}: {}", root.display(), e))?;
+    }
+    let folders = ["Photos/2023 Holiday", "Photos/Scans", "Documents/Reports", "Projects/website/src", "Archive/Old", "Empty folder"];
+    for folder in folders {
+        let path = root.join(folder);
+        fs::create_dir_all(&path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    }
+
+    // Images, with a copy under another name and two with a misleading extension
+    let png = image::ImageFormat::Png;
+    let jpeg = image::ImageFormat::Jpeg;
+    for idx in 0..6u8 {
+        let path = root.join(format!("Photos/2023 Holiday/IMG_{:04}.jpg", 1000 + u32::from(idx)));
+        save_image(&path, &sample_image(640, 480, idx), jpeg)?;
+        set_age(&path, 300 + u32::from(idx))?;
+    }
+    save_image(&root.join("Photos/Scans/scan_001.png"), &sample_image(800, 1100, 7), png)?;
+    save_image(&root.join("Photos/Scans/scan_002.png"), &sample_image(800, 1100, 8), png)?;
+    fs::copy(root.join("Photos/2023 Holiday/IMG_1000.jpg"), root.join("Photos/IMG_1000 (copy).jpg"))
+        .map_err(|e| format!("Failed to copy sample image: {}", e))?;
+    save_image(&root.join("Photos/Scans/scan_003"), &sample_image(400, 300, 9), png)?;
+    save_image(&root.join("Photos/Scans/receipt.jpg"), &sample_image(300, 500, 10), png)?;
+
+    // Documents and data
+    write(&root.join("Documents/meeting notes.txt"), NOTES.as_bytes())?;
+    write(&root.join("Documents/Reports/README.md"), b"# Reports\n\nQuarterly reports for the team.\n")?;
+    let mut csv = String::from("date,region,amount\n");
+    for day in 1..=90 {
+        csv.push_str(&format!("2024-01-{:02},{},{}\n", (day - 1) % 28 + 1, ["North", "South", "East"][day % 3], day * 37));
+    }
+    write(&root.join("Documents/Reports/sales 2024.csv"), csv.as_bytes())?;
+    write(&root.join("Documents/Reports/sales 2024 - Copy.csv"), csv.as_bytes())?;
+    write(&root.join("Documents/Reports/Q1 report (final) (2).txt"), NOTES.repeat(3).as_bytes())?;
+    write(&root.join("Documents/draft.txt"), b"")?;
+    set_age(&root.join("Documents/meeting notes.txt"), 45)?;
+    set_age(&root.join("Documents/Reports/Q1 report (final) (2).txt"), 900)?;
+
+    // Code
+    write(
+        &root.join("Projects/website/src/main.rs"),
+        b"fn main() {\n    println!(\"Hello, world!\");\n}\n",
+    )?;
+    write(
+        &root.join("Projects/website/src/app.js"),
+        b"export function greet(name) {\n  return `Hello, ${name}`;\n}\n",
+    )?;
+    write(&root.join("Projects/website/index.html"), b"<!doctype html>\n<title>Sample</title>\n<p>Sample page</p>\n")?;
+    write(&root.join("Projects/website/.gitignore"), b"target/\nnode_modules/\n")?;
+
+    // Old, oddly named and empty files
+    let long_name = format!("{}.txt", "a very long file name that keeps going ".repeat(4).trim_end());
+    write(&root.join("Archive/Old").join(long_name), NOTES.as_bytes())?;
+    write(&root.join("Archive/Old/notes   with  spaces .txt"), NOTES.as_bytes())?;
+    write(&root.join("Archive/Old/backup.bak"), &[0u8; 4096])?;
+    write(&root.join("Archive/Old/empty.log"), b"")?;
+    for name in ["notes   with  spaces .txt", "backup.bak", "empty.log"] {
+        set_age(&root.join("Archive/Old").join(name), 2000)?;
+    }
+    Ok(root)
+}
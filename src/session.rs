@@ -0,0 +1,73 @@
+use crate::file_scanner::RecentDays;
+use crate::users;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Working state autosaved while the app runs; removed on a clean exit,
+/// so a session file found at startup means the last run crashed or was killed
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub folders: Vec<PathBuf>,
+    pub added_files: Vec<PathBuf>,
+    pub recursive: bool,
+    /// "Hidden files" was on
+    pub include_hidden: bool,
+    /// "Respect .gitignore" was on
+    pub respect_gitignore: bool,
+    /// Live update (folder watcher) was on
+    pub live_update: bool,
+    pub filter_text: String,
+    pub show_duplicates_only: bool,
+    /// "Versions" view was on
+    pub show_versions_only: bool,
+    /// Today / yesterday / last 7 days filter
+    pub modified_within: Option<RecentDays>,
+    pub show_cloud_only: bool,
+    pub show_orphaned_only: bool,
+    pub hide_reviewed: bool,
+    pub show_never_accessed: bool,
+    pub show_too_long: bool,
+    pub show_unsafe_names: bool,
+    /// "Empty" filter (zero-byte files, empty folders) was on
+    pub show_empty_only: bool,
+    /// Absolute paths of the selected files
+    pub selected_paths: Vec<String>,
+    /// When the session was saved (Unix seconds)
+    pub saved_at: i64,
+}
+
+impl Session {
+    /// Path of the session file (next to settings.json)
+    pub fn path() -> PathBuf {
+        users::config_dir().join("session.json")
+    }
+
+    /// Session left behind by a run that did not exit cleanly
+    pub fn load() -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path()).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// True if there is nothing worth restoring
+    pub fn is_empty(&self) -> bool {
+        self.folders.is_empty() && self.added_files.is_empty()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize session: {}", e))?;
+        // Write then rename, so a crash mid-write doesn't leave a truncated session
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, content).map_err(|e| format!("Failed to write session: {}", e))?;
+        std::fs::rename(&temp, &path).map_err(|e| format!("Failed to write session: {}", e))
+    }
+
+    /// Remove the session file (clean exit or discarded restore)
+    pub fn clear() {
+        let _ = std::fs::remove_file(Self::path());
+    }
+}
@@ -0,0 +1,440 @@
+use crate::cloud_export::CloudExportSettings;
+use crate::csv_export::{ExportColumn, ExportFormat, ExportOptions, ExportSplit};
+use crate::duplicates::KeepRule;
+use crate::file_scanner::{Calendar, ScanFilter, SizeFormat};
+use crate::hashing::HashAlgorithm;
+use crate::hooks::Hook;
+use crate::name_audit;
+use crate::priority;
+use crate::report_mail::ReportMailSettings;
+use crate::retention::RetentionRule;
+use crate::similarity::PerceptualHash;
+use crate::split;
+use crate::text_match::{Collation, NameForm};
+use crate::users;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Saved window geometry for one monitor (logical points)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowPlacement {
+    /// Monitor key: physical resolution as "WIDTHxHEIGHT"
+    pub monitor: String,
+    /// Outer window position
+    pub x: f32,
+    pub y: f32,
+    /// Inner window size
+    pub width: f32,
+    pub height: f32,
+}
+
+/// User-pinned folder in the bookmarks sidebar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FolderBookmark {
+    pub label: String,
+    pub path: PathBuf,
+}
+
+/// Columns each export format gets; an empty list means the standard columns
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatColumns {
+    pub csv: Vec<ExportColumn>,
+    pub tsv: Vec<ExportColumn>,
+    pub json: Vec<ExportColumn>,
+    pub html: Vec<ExportColumn>,
+}
+
+impl FormatColumns {
+    /// Columns chosen for the format (None: the standard ones)
+    pub fn get(&self, format: ExportFormat) -> Option<&[ExportColumn]> {
+        let columns = match format {
+            ExportFormat::Csv => &self.csv,
+            ExportFormat::Tsv => &self.tsv,
+            ExportFormat::Json => &self.json,
+            ExportFormat::Html => &self.html,
+        };
+        (!columns.is_empty()).then_some(columns.as_slice())
+    }
+
+    pub fn get_mut(&mut self, format: ExportFormat) -> &mut Vec<ExportColumn> {
+        match format {
+            ExportFormat::Csv => &mut self.csv,
+            ExportFormat::Tsv => &mut self.tsv,
+            ExportFormat::Json => &mut self.json,
+            ExportFormat::Html => &mut self.html,
+        }
+    }
+}
+
+/// Named scan + export configuration for the command line (`--profile <name>`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanProfile {
+    pub name: String,
+    pub folders: Vec<PathBuf>,
+    pub recursive: bool,
+    /// List hidden and system files (dotfiles, Hidden/System attribute)
+    pub include_hidden: bool,
+    /// Leave out files matched by .gitignore/.ignore files
+    pub respect_gitignore: bool,
+    /// Wildcard patterns (`*`, `?`) for files to leave out
+    pub excludes: Vec<String>,
+    /// Export only zero-byte files and (recursive) empty folders
+    pub empty_only: bool,
+    /// Export columns (empty: the format's columns from the settings)
+    pub columns: Vec<ExportColumn>,
+    pub format: ExportFormat,
+    /// Split the output into several files
+    pub split: ExportSplit,
+    /// Output file (default "files.<format>")
+    pub output: Option<PathBuf>,
+}
+
+impl Default for ScanProfile {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            folders: Vec::new(),
+            recursive: false,
+            include_hidden: false,
+            respect_gitignore: false,
+            excludes: Vec::new(),
+            empty_only: false,
+            columns: Vec::new(),
+            format: ExportFormat::Csv,
+            split: ExportSplit::None,
+            output: None,
+        }
+    }
+}
+
+impl ScanProfile {
+    /// Export options of the profile, with dates in the app's calendar; without columns of its
+    /// own, the profile exports the columns set for its format
+    pub fn export_options(&self, calendar: Calendar, format_columns: &FormatColumns) -> ExportOptions {
+        let columns = if self.columns.is_empty() {
+            format_columns.get(self.format).unwrap_or(&ExportColumn::DEFAULT).to_vec()
+        } else {
+            self.columns.clone()
+        };
+        ExportOptions {
+            columns,
+            format: self.format,
+            split: self.split,
+            calendar,
+            ..Default::default()
+        }
+    }
+
+    /// Files the profile's scan leaves out
+    pub fn scan_filter(&self) -> ScanFilter {
+        ScanFilter {
+            include_hidden: self.include_hidden,
+            respect_gitignore: self.respect_gitignore,
+        }
+    }
+
+    /// Output path, defaulting to files.csv / files.tsv / files.json
+    pub fn output_path(&self) -> PathBuf {
+        self.output
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(format!("files.{}", self.format.extension())))
+    }
+}
+
+/// Number of recent move/copy destinations to remember
+pub const MAX_RECENT_DESTINATIONS: usize = 10;
+
+/// Default maximum hover preview size (points)
+pub const DEFAULT_PREVIEW_MAX_SIZE: f32 = 400.0;
+
+/// Quality/speed trade-off for generated previews
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PreviewQuality {
+    /// JPEG video frames, bilinear resize
+    Fast,
+    /// High-quality JPEG video frames, Catmull-Rom resize
+    Balanced,
+    /// Lossless PNG video frames, Lanczos resize
+    High,
+}
+
+impl PreviewQuality {
+    pub const ALL: [PreviewQuality; 3] = [PreviewQuality::Fast, PreviewQuality::Balanced, PreviewQuality::High];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PreviewQuality::Fast => "Fast",
+            PreviewQuality::Balanced => "Balanced",
+            PreviewQuality::High => "High",
+        }
+    }
+}
+
+/// User settings persisted between sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// Last known window placement for each monitor
+    pub window_placements: Vec<WindowPlacement>,
+    /// Monitor the window was on when the app was last closed
+    pub last_monitor: Option<String>,
+    /// Keep the window above other windows
+    pub always_on_top: bool,
+    /// Maximum hover preview width/height (points)
+    pub preview_max_size: f32,
+    /// Video timestamp to grab the thumbnail frame from (seconds)
+    pub video_frame_secs: f32,
+    /// Resolution used to render PDF pages before downscaling
+    pub pdf_render_dpi: f32,
+    /// Preview encoding and resampling quality
+    pub preview_quality: PreviewQuality,
+    /// Capacity of the media target for "fit to target size" (bytes)
+    pub target_size_bytes: Option<u64>,
+    /// Part size for "Split / join" (bytes)
+    pub split_part_size: u64,
+    /// Write a thumbnails/ folder next to exported reports
+    pub export_thumbnails: bool,
+    /// Split exported reports into several files
+    pub export_split: ExportSplit,
+    /// Write playlist entries relative to the playlist's folder
+    pub playlist_relative_paths: bool,
+    /// Most recently used move/copy destination folders (newest first)
+    pub recent_destinations: Vec<String>,
+    /// Pinned move/copy destination folders
+    pub favorite_destinations: Vec<String>,
+    /// Show the second file pane (two-panel mode)
+    pub dual_pane: bool,
+    /// Folder shown in the second pane
+    pub right_pane_folder: Option<PathBuf>,
+    /// Pinned folders for one-click scanning
+    pub bookmarks: Vec<FolderBookmark>,
+    /// Show the bookmarks sidebar
+    pub show_bookmarks: bool,
+    /// Show the file age heatmap column
+    pub show_age_heatmap: bool,
+    /// Show the "Accessed" (last access time) column
+    pub show_last_accessed: bool,
+    /// Show the trailing column with inline row action buttons
+    pub show_row_actions: bool,
+    /// Show name length and full path length columns
+    pub show_lengths: bool,
+    /// Show the "On disk" (allocated size) column and mark hard-linked files
+    pub show_disk_usage: bool,
+    /// Hash column: checksums computed in the background after each scan
+    pub show_hashes: bool,
+    /// Checksum of the Hash column (also used by the command line for a "hash" column)
+    pub hash_algorithm: HashAlgorithm,
+    /// Lines / Words columns: text and code files counted in the background after each scan
+    pub show_text_counts: bool,
+    /// "Too long" flags names longer than this (UTF-16 units)
+    pub name_length_limit: usize,
+    /// "Too long" flags full paths longer than this (UTF-16 units)
+    pub path_length_limit: usize,
+    /// Read online-only cloud files for previews/thumbnails (downloads them)
+    pub preview_cloud_files: bool,
+    /// Units and digit grouping for displayed sizes
+    pub size_format: SizeFormat,
+    /// Era of displayed and exported dates (Gregorian or Thai Buddhist Era)
+    pub calendar: Calendar,
+    /// Look up file owners during scanning (Windows)
+    pub resolve_owners: bool,
+    /// Command-line scan profiles
+    pub profiles: Vec<ScanProfile>,
+    /// Raise an OS notification when a long operation finishes while the window is unfocused
+    pub notify_when_unfocused: bool,
+    /// Reuse checksums of files whose size and modification time are unchanged
+    pub cache_hashes: bool,
+    /// Tolerance for "similar images" in the duplicates view (differing bits of the perceptual hash)
+    pub similar_image_distance: u32,
+    /// Perceptual hash of "similar images" (dHash or pHash)
+    pub similar_image_hash: PerceptualHash,
+    /// Which file of each group the duplicate manager keeps
+    pub duplicate_keep_rule: KeepRule,
+    /// Filter ignores accents ("cafe" matches "café")
+    pub fold_accents: bool,
+    /// Filter typed in Latin letters also matches romanized Thai, Cyrillic, CJK names
+    pub transliterate_search: bool,
+    /// Locale order of names when sorting
+    pub sort_collation: Collation,
+    /// Names and paths sort numbers by value ("file2" before "file10")
+    pub natural_sort: bool,
+    /// Unicode form names are compared in for sorting and equal-name duplicates
+    pub name_form: NameForm,
+    /// UTC offset of the activity charts, in minutes
+    pub activity_utc_offset: i32,
+    /// Show companion files (RAW+JPEG, video+subtitles) as one expandable row
+    pub group_sidecars: bool,
+    /// Move and delete take a grouped file's companions along
+    pub sidecars_follow: bool,
+    /// List the folders above the files with their total size and item count
+    pub show_folders: bool,
+    /// Read-only (audit) mode: delete, move and rename are disabled
+    pub read_only: bool,
+    /// Retries of a scan or preview read that failed with a transient (network) error
+    pub network_retries: u32,
+    /// Delay before the first retry in milliseconds; doubled for every further retry
+    pub retry_delay_ms: u64,
+    /// List local NTFS volumes from the master file table when running as administrator
+    pub ntfs_fast_scan: bool,
+    /// Scans and hashing yield to other programs (low thread/IO priority, throttled reads)
+    pub background_priority: bool,
+    /// Columns of exports per format (GUI exports and profiles without columns)
+    pub format_columns: FormatColumns,
+    /// Pause of background priority after every 50 ms of work, in milliseconds
+    pub throttle_pause_ms: u64,
+    /// Google Sheets / Microsoft 365 export target and OAuth clients
+    pub cloud_export: CloudExportSettings,
+    /// Recipients and delivery of "Send report"
+    pub report_mail: ReportMailSettings,
+    /// Webhooks and commands run when a scan or export finishes (GUI and CLI)
+    pub hooks: Vec<Hook>,
+    /// Rules run from "Retention rules" or with --retention
+    pub retention_rules: Vec<RetentionRule>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            window_placements: Vec::new(),
+            last_monitor: None,
+            always_on_top: false,
+            preview_max_size: DEFAULT_PREVIEW_MAX_SIZE,
+            video_frame_secs: 1.0,
+            pdf_render_dpi: 150.0,
+            preview_quality: PreviewQuality::Balanced,
+            target_size_bytes: None,
+            split_part_size: split::PART_SIZE_PRESETS[0].1,
+            export_thumbnails: false,
+            export_split: ExportSplit::None,
+            playlist_relative_paths: true,
+            recent_destinations: Vec::new(),
+            favorite_destinations: Vec::new(),
+            dual_pane: false,
+            right_pane_folder: None,
+            bookmarks: Vec::new(),
+            show_bookmarks: false,
+            show_age_heatmap: false,
+            show_last_accessed: false,
+            show_row_actions: false,
+            show_lengths: false,
+            show_disk_usage: false,
+            show_hashes: false,
+            hash_algorithm: HashAlgorithm::Sha256,
+            show_text_counts: false,
+            name_length_limit: name_audit::DEFAULT_NAME_LIMIT,
+            path_length_limit: name_audit::DEFAULT_PATH_LIMIT,
+            preview_cloud_files: false,
+            size_format: SizeFormat::default(),
+            calendar: Calendar::Gregorian,
+            resolve_owners: false,
+            profiles: Vec::new(),
+            notify_when_unfocused: true,
+            cache_hashes: true,
+            similar_image_distance: 6,
+            similar_image_hash: PerceptualHash::Difference,
+            duplicate_keep_rule: KeepRule::Newest,
+            fold_accents: true,
+            transliterate_search: false,
+            sort_collation: Collation::CodePoint,
+            natural_sort: true,
+            name_form: NameForm::Nfc,
+            activity_utc_offset: 0,
+            group_sidecars: false,
+            sidecars_follow: true,
+            show_folders: false,
+            read_only: false,
+            network_retries: 3,
+            retry_delay_ms: 200,
+            ntfs_fast_scan: true,
+            background_priority: false,
+            format_columns: FormatColumns::default(),
+            throttle_pause_ms: priority::DEFAULT_PAUSE_MS,
+            cloud_export: CloudExportSettings::default(),
+            report_mail: ReportMailSettings::default(),
+            hooks: Vec::new(),
+            retention_rules: Vec::new(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// Get the path of the settings file
+    pub fn settings_path() -> PathBuf {
+        users::config_dir().join("settings.json")
+    }
+
+    /// Load settings from disk (defaults if missing or unreadable)
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::settings_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save settings to disk
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::settings_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        std::fs::write(&path, content).map_err(|e| format!("Failed to write settings: {}", e))
+    }
+
+    /// Placement to restore at startup (from the monitor used last)
+    pub fn last_placement(&self) -> Option<&WindowPlacement> {
+        let monitor = self.last_monitor.as_ref()?;
+        self.window_placements.iter().find(|p| &p.monitor == monitor)
+    }
+
+    /// Find a scan profile by name (case-insensitive)
+    pub fn profile(&self, name: &str) -> Option<&ScanProfile> {
+        self.profiles.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Record a move/copy destination as the most recent one
+    pub fn remember_destination(&mut self, folder: &str) {
+        self.recent_destinations.retain(|f| f != folder);
+        self.recent_destinations.insert(0, folder.to_string());
+        self.recent_destinations.truncate(MAX_RECENT_DESTINATIONS);
+    }
+
+    /// Pin or unpin a destination folder as a favorite
+    pub fn toggle_favorite_destination(&mut self, folder: &str) {
+        if let Some(pos) = self.favorite_destinations.iter().position(|f| f == folder) {
+            self.favorite_destinations.remove(pos);
+        } else {
+            self.favorite_destinations.push(folder.to_string());
+        }
+    }
+
+    /// Pin a folder as a bookmark (labelled with its folder name); no-op if already pinned
+    pub fn add_bookmark(&mut self, path: PathBuf) {
+        if self.bookmarks.iter().any(|b| b.path == path) {
+            return;
+        }
+        let label = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        self.bookmarks.push(FolderBookmark { label, path });
+    }
+
+    /// Remember the placement for its monitor, replacing any older entry
+    pub fn remember_placement(&mut self, placement: WindowPlacement) {
+        self.last_monitor = Some(placement.monitor.clone());
+        if let Some(existing) = self
+            .window_placements
+            .iter_mut()
+            .find(|p| p.monitor == placement.monitor)
+        {
+            *existing = placement;
+        } else {
+            self.window_placements.push(placement);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use crate::file_scanner::FileInfo;
+use std::collections::HashMap;
+
+/// Camera RAW formats (usually shot together with a JPEG/HEIC of the same name)
+const RAW_EXTENSIONS: [&str; 14] = [
+    "cr2", "cr3", "crw", "nef", "nrw", "arw", "srf", "sr2", "dng", "raf", "orf", "rw2", "pef", "srw",
+];
+
+/// Processed photo formats
+const PHOTO_EXTENSIONS: [&str; 5] = ["jpg", "jpeg", "heic", "heif", "png"];
+
+const VIDEO_EXTENSIONS: [&str; 9] = ["mp4", "mkv", "mov", "avi", "wmv", "webm", "m4v", "mpg", "mts"];
+
+/// Subtitles may carry a language code: movie.en.srt, movie.pt-BR.srt
+const SUBTITLE_EXTENSIONS: [&str; 6] = ["srt", "ass", "ssa", "vtt", "sub", "idx"];
+
+/// Metadata sidecars written by photo tools and cameras (Lightroom .xmp, iOS .aae, camera .thm)
+const METADATA_EXTENSIONS: [&str; 3] = ["xmp", "aae", "thm"];
+
+/// Role of a file within a sidecar group; the lowest rank becomes the group's visible row
+fn rank(extension: &str) -> Option<u8> {
+    let extension = extension.to_lowercase();
+    let ext = extension.as_str();
+    if VIDEO_EXTENSIONS.contains(&ext) {
+        Some(0)
+    } else if PHOTO_EXTENSIONS.contains(&ext) {
+        Some(1)
+    } else if RAW_EXTENSIONS.contains(&ext) {
+        Some(2)
+    } else if SUBTITLE_EXTENSIONS.contains(&ext) || METADATA_EXTENSIONS.contains(&ext) {
+        Some(3)
+    } else {
+        None
+    }
+}
+
+/// Folder + base name shared by companion files (case-insensitive)
+fn group_key(file: &FileInfo) -> String {
+    let folder = file
+        .absolute_path()
+        .rsplit_once(['/', '\\'])
+        .map(|(folder, _)| folder)
+        .unwrap_or("");
+    let mut stem = file.name().to_lowercase();
+    // movie.en.srt belongs to movie.mkv
+    if SUBTITLE_EXTENSIONS.contains(&file.extension().to_lowercase().as_str()) {
+        if let Some((base, language)) = stem.rsplit_once('.') {
+            if (2..=5).contains(&language.len()) && language.chars().all(|c| c.is_ascii_alphabetic() || c == '-') {
+                stem = base.to_string();
+            }
+        }
+    }
+    format!("{}/{}", folder.to_lowercase(), stem)
+}
+
+/// Group companion files (IMG_001.CR2 + IMG_001.JPG, movie.mkv + movie.srt).
+/// Returns the index of each group's primary file (video, else photo, else RAW) -> indices of its companions.
+/// Files sharing a name but no media file (notes.txt + notes.srt) are not grouped.
+pub fn group(files: &[FileInfo]) -> HashMap<usize, Vec<usize>> {
+    let mut by_key: HashMap<String, Vec<usize>> = HashMap::new();
+    for (idx, file) in files.iter().enumerate() {
+        if rank(file.extension()).is_some() {
+            by_key.entry(group_key(file)).or_default().push(idx);
+        }
+    }
+
+    let mut groups = HashMap::new();
+    for mut members in by_key.into_values().filter(|members| members.len() > 1) {
+        // Stable: equal ranks keep the list order
+        members.sort_by_key(|&idx| rank(files[idx].extension()));
+        let primary = members.remove(0);
+        if rank(files[primary].extension()).is_some_and(|r| r < 3) {
+            groups.insert(primary, members);
+        }
+    }
+    groups
+}
@@ -0,0 +1,171 @@
+use crate::file_scanner::FileInfo;
+use crate::hashing::HashCache;
+use crate::priority;
+use crate::thumbnail::ThumbnailKind;
+use image::imageops::FilterType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// Largest tolerance offered in the duplicates view (bits of the 64-bit hash that may differ)
+pub const MAX_DISTANCE: u32 = 16;
+
+/// Message from the background image hashing thread
+pub enum HashingMessage {
+    Progress { done: usize, total: usize },
+    /// Perceptual hash per image path (images that could not be decoded are left out)
+    Finished(Vec<(String, u64)>),
+}
+
+/// Perceptual hash used to find similar images
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PerceptualHash {
+    /// dHash: fast, finds resized and recompressed copies
+    #[default]
+    Difference,
+    /// pHash: slower, also tolerates brightness, contrast and color edits of a re-export
+    Dct,
+}
+
+impl PerceptualHash {
+    pub const ALL: [PerceptualHash; 2] = [PerceptualHash::Difference, PerceptualHash::Dct];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PerceptualHash::Difference => "dHash (fast)",
+            PerceptualHash::Dct => "pHash (edited re-exports)",
+        }
+    }
+
+    /// Hash of an image file; None if it can't be decoded
+    pub fn compute(self, path: &Path) -> Option<u64> {
+        match self {
+            PerceptualHash::Difference => dhash(path),
+            PerceptualHash::Dct => phash(path),
+        }
+    }
+}
+
+/// Difference hash (dHash): compares neighbouring pixels of a 9x8 grayscale thumbnail,
+/// so recompressed or resized copies of a photo get the same or a close hash
+pub fn dhash(path: &Path) -> Option<u64> {
+    let image = image::open(path).ok()?;
+    let small = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if small.get_pixel(x, y)[0] < small.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+    Some(hash)
+}
+
+/// DCT hash (pHash): the lowest 8x8 frequencies of a 32x32 grayscale thumbnail, each bit set
+/// if the coefficient is above their median, so overall tone changes don't alter the hash
+pub fn phash(path: &Path) -> Option<u64> {
+    let image = image::open(path).ok()?;
+    let small = image.resize_exact(32, 32, FilterType::Triangle).to_luma8();
+    // cos((2x + 1) * u * PI / 64) for the 8 lowest frequencies u
+    let cosines: Vec<[f32; 32]> = (0..8)
+        .map(|u| std::array::from_fn(|x| ((2 * x + 1) as f32 * u as f32 * PI / 64.0).cos()))
+        .collect();
+    let mut coefficients = [0f32; 64];
+    for v in 0..8 {
+        for u in 0..8 {
+            let mut sum = 0.0;
+            for y in 0..32 {
+                for x in 0..32 {
+                    sum += small.get_pixel(x as u32, y as u32)[0] as f32 * cosines[u][x] * cosines[v][y];
+                }
+            }
+            coefficients[v * 8 + u] = sum;
+        }
+    }
+    // The DC term (average brightness) is left out of the median
+    let mut sorted = coefficients[1..].to_vec();
+    sorted.sort_by(f32::total_cmp);
+    let median = sorted[sorted.len() / 2];
+    Some(coefficients.iter().fold(0u64, |hash, &c| (hash << 1) | u64::from(c > median)))
+}
+
+/// Perceptually hash the image files among `files` on a background thread.
+/// Online-only cloud files are skipped (decoding them would download them).
+pub fn spawn_hashing(files: Vec<FileInfo>, method: PerceptualHash, use_cache: bool) -> Receiver<HashingMessage> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        priority::enter_background();
+        let images: Vec<&FileInfo> = files
+            .iter()
+            .filter(|f| !f.is_cloud_placeholder)
+            .filter(|f| ThumbnailKind::from_extension(f.extension()) == Some(ThumbnailKind::Image))
+            .collect();
+        let mut cache = if use_cache { HashCache::load() } else { HashCache::default() };
+
+        let mut hashes = Vec::new();
+        for (done, file) in images.iter().enumerate() {
+            if done % 16 == 0 {
+                let _ = tx.send(HashingMessage::Progress { done, total: images.len() });
+            }
+            if let Some(hash) = cache.perceptual(file, method) {
+                hashes.push((file.absolute_path().to_string(), hash));
+            }
+        }
+
+        if use_cache {
+            // A cache that fails to save only costs re-hashing next time
+            let _ = cache.save();
+        }
+        let _ = tx.send(HashingMessage::Finished(hashes));
+    });
+    rx
+}
+
+/// Group images whose hashes differ in at most `max_distance` bits.
+/// Returns path -> (group id, group size) for images in groups of two or more.
+pub fn group_similar(hashes: &[(String, u64)], max_distance: u32) -> HashMap<String, (usize, usize)> {
+    // Union-find over all pairs
+    let mut parent: Vec<usize> = (0..hashes.len()).collect();
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for i in 0..hashes.len() {
+        for j in (i + 1)..hashes.len() {
+            if (hashes[i].1 ^ hashes[j].1).count_ones() <= max_distance {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                if a != b {
+                    parent[b] = a;
+                }
+            }
+        }
+    }
+
+    let roots: Vec<usize> = (0..hashes.len()).map(|i| find(&mut parent, i)).collect();
+    let mut sizes: HashMap<usize, usize> = HashMap::new();
+    for &root in &roots {
+        *sizes.entry(root).or_insert(0) += 1;
+    }
+
+    // Number groups in order of their first image
+    let mut group_ids: HashMap<usize, usize> = HashMap::new();
+    let mut groups = HashMap::new();
+    for (i, &root) in roots.iter().enumerate() {
+        let size = sizes[&root];
+        if size > 1 {
+            let next_id = group_ids.len();
+            let id = *group_ids.entry(root).or_insert(next_id);
+            groups.insert(hashes[i].0.clone(), (id, size));
+        }
+    }
+    groups
+}
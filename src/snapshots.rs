@@ -0,0 +1,162 @@
+use crate::file_scanner::{wildcard_match, FileInfo};
+use crate::users;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Maximum hits returned by a search (the total is still counted)
+pub const MAX_HITS: usize = 5000;
+
+/// One file recorded in a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    pub path: String,
+    pub size: u64,
+    /// Modification timestamp (seconds since UNIX epoch)
+    pub modified: i64,
+}
+
+/// Saved listing of scanned folders, searchable after the disk is unplugged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// User label, e.g. "Backup drive 2TB"
+    pub label: String,
+    pub roots: Vec<PathBuf>,
+    /// When the snapshot was taken (Unix seconds)
+    pub created: i64,
+    pub files: Vec<SnapshotFile>,
+}
+
+/// Saved snapshot without its file list
+#[derive(Debug, Clone)]
+pub struct SnapshotSummary {
+    pub path: PathBuf,
+    pub label: String,
+    pub created: i64,
+    pub file_count: usize,
+}
+
+/// A file found in a snapshot
+#[derive(Debug, Clone)]
+pub struct SnapshotHit {
+    pub label: String,
+    pub created: i64,
+    pub file: SnapshotFile,
+}
+
+/// Result of searching all snapshots
+#[derive(Debug, Default)]
+pub struct SnapshotSearch {
+    pub hits: Vec<SnapshotHit>,
+    /// Matches found (may exceed the hits returned)
+    pub total: usize,
+    pub snapshots: Vec<SnapshotSummary>,
+    /// "file: error" per unreadable snapshot
+    pub errors: Vec<String>,
+}
+
+/// Directory holding the snapshot files (next to settings.json)
+pub fn snapshots_dir() -> PathBuf {
+    users::config_dir().join("snapshots")
+}
+
+/// Save the listing of `files` under `label`
+pub fn save(label: &str, roots: &[PathBuf], files: &[FileInfo]) -> Result<PathBuf, String> {
+    let dir = snapshots_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create snapshots directory: {}", e))?;
+
+    let created = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let snapshot = Snapshot {
+        label: label.to_string(),
+        roots: roots.to_vec(),
+        created,
+        files: files
+            .iter()
+            .map(|f| SnapshotFile {
+                path: f.absolute_path().to_string(),
+                size: f.file_size,
+                modified: f.modified_timestamp,
+            })
+            .collect(),
+    };
+
+    let safe_label: String = label
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let path = dir.join(format!("{}_{}.json", safe_label, created));
+    let content = serde_json::to_string(&snapshot).map_err(|e| format!("Failed to serialize snapshot: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write snapshot: {}", e))?;
+    Ok(path)
+}
+
+fn load(path: &Path) -> Result<Snapshot, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+fn snapshot_files() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(snapshots_dir())
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                .collect()
+        })
+        .unwrap_or_default();
+    paths.sort();
+    paths
+}
+
+/// Search every saved snapshot by file name pattern.
+/// `*` and `?` are wildcards; a pattern without them matches anywhere in the name.
+/// An empty pattern only lists the snapshots.
+pub fn search(pattern: &str) -> SnapshotSearch {
+    let pattern = pattern.trim();
+    let list_only = pattern.is_empty();
+    let pattern = if pattern.contains('*') || pattern.contains('?') {
+        pattern.to_string()
+    } else {
+        format!("*{}*", pattern)
+    };
+
+    let mut result = SnapshotSearch::default();
+    for path in snapshot_files() {
+        let snapshot = match load(&path) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                result.errors.push(format!("{}: {}", path.display(), e));
+                continue;
+            }
+        };
+
+        for file in snapshot.files.iter().filter(|_| !list_only) {
+            let name = file.path.rsplit(['/', '\\']).next().unwrap_or(&file.path);
+            if wildcard_match(&pattern, name) {
+                result.total += 1;
+                if result.hits.len() < MAX_HITS {
+                    result.hits.push(SnapshotHit {
+                        label: snapshot.label.clone(),
+                        created: snapshot.created,
+                        file: file.clone(),
+                    });
+                }
+            }
+        }
+        result.snapshots.push(SnapshotSummary {
+            path,
+            label: snapshot.label,
+            created: snapshot.created,
+            file_count: snapshot.files.len(),
+        });
+    }
+    result
+}
+
+/// Delete a saved snapshot
+pub fn delete(path: &Path) -> Result<(), String> {
+    std::fs::remove_file(path).map_err(|e| format!("Failed to delete snapshot: {}", e))
+}
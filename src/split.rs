@@ -0,0 +1,267 @@
+use crate::hashing::to_hex;
+use crate::jobs::{self, JobError};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Manifest file name suffix ("video.mkv.split.json")
+pub const MANIFEST_SUFFIX: &str = ".split.json";
+
+/// Part size presets (label, bytes)
+pub const PART_SIZE_PRESETS: [(&str, u64); 4] = [
+    ("FAT32 max (4 GB)", 4_294_967_295),
+    ("1 GB", 1_000_000_000),
+    ("100 MB", 100_000_000),
+    ("25 MB (e-mail)", 25_000_000),
+];
+
+/// One part of a split file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitPart {
+    /// File name, in the manifest's folder
+    pub name: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// Written next to the parts; everything needed to join and verify them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitManifest {
+    pub file_name: String,
+    pub size: u64,
+    pub sha256: String,
+    pub part_size: u64,
+    pub parts: Vec<SplitPart>,
+}
+
+impl SplitManifest {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("Can't read {}: {}", path.display(), e))?;
+        serde_json::from_str(&content).map_err(|e| format!("{} is not a split manifest: {}", path.display(), e))
+    }
+}
+
+/// Whether a file is a split manifest (offered for joining)
+pub fn is_manifest(path: &Path) -> bool {
+    path.to_string_lossy().to_lowercase().ends_with(MANIFEST_SUFFIX)
+}
+
+/// Split `source` into parts of `part_size` bytes in `dest_folder` ("name.001", "name.002", ...)
+/// plus "name.split.json"; nothing is overwritten, and the parts are removed if it fails.
+/// Returns the manifest path.
+pub fn split(source: &Path, dest_folder: &Path, part_size: u64, on_bytes: &mut dyn FnMut(u64)) -> Result<PathBuf, JobError> {
+    let file_name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("{} has no file name", source.display()))?;
+    let size = fs::metadata(source)?.len();
+    if part_size == 0 {
+        return Err("The part size must be more than 0".to_string().into());
+    }
+    let count = size.div_ceil(part_size).max(1);
+    // At least three digits, so the parts sort in order
+    let digits = count.to_string().len().max(3);
+    let names: Vec<String> = (1..=count).map(|n| format!("{}.{:0width$}", file_name, n, width = digits)).collect();
+    let manifest_path = dest_folder.join(format!("{}{}", file_name, MANIFEST_SUFFIX));
+    if let Some(existing) = names
+        .iter()
+        .map(|name| dest_folder.join(name))
+        .chain([manifest_path.clone()])
+        .find(|path| path.exists())
+    {
+        return Err(format!("{} already exists", existing.display()).into());
+    }
+
+    let result = write_parts(source, dest_folder, &names, part_size, on_bytes).and_then(|(sha256, parts)| {
+        let manifest = SplitManifest {
+            file_name,
+            size,
+            sha256,
+            part_size,
+            parts,
+        };
+        let content = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+        fs::write(&manifest_path, content)?;
+        Ok(manifest_path)
+    });
+    if result.is_err() {
+        for name in &names {
+            let _ = fs::remove_file(dest_folder.join(name));
+        }
+    }
+    result
+}
+
+/// Copy `source` into the named parts; returns the whole file's hash and the parts
+fn write_parts(
+    source: &Path,
+    dest_folder: &Path,
+    names: &[String],
+    part_size: u64,
+    on_bytes: &mut dyn FnMut(u64),
+) -> Result<(String, Vec<SplitPart>), JobError> {
+    let mut reader = File::open(source)?;
+    let mut whole = Sha256::new();
+    let mut buffer = vec![0u8; jobs::CHUNK_BYTES];
+    let mut written = 0u64;
+    let mut parts = Vec::with_capacity(names.len());
+    for name in names {
+        let mut writer = File::create_new(dest_folder.join(name))?;
+        let mut hasher = Sha256::new();
+        let mut part_written = 0u64;
+        while part_written < part_size {
+            // Compared as u64: the rest of a part over 4 GB doesn't fit a 32-bit usize
+            let want = usize::try_from(part_size - part_written).map_or(buffer.len(), |rest| rest.min(buffer.len()));
+            let read = reader.read(&mut buffer[..want])?;
+            if read == 0 {
+                break;
+            }
+            writer.write_all(&buffer[..read])?;
+            hasher.update(&buffer[..read]);
+            whole.update(&buffer[..read]);
+            part_written += read as u64;
+            written += read as u64;
+            on_bytes(written);
+        }
+        writer.flush()?;
+        parts.push(SplitPart {
+            name: name.clone(),
+            size: part_written,
+            sha256: to_hex(&hasher.finalize()),
+        });
+    }
+    Ok((to_hex(&whole.finalize()), parts))
+}
+
+/// Rebuilds a split file part by part (one job item per part), checking each part's hash and
+/// size and at the end the whole file's hash. Writes to "name.joining" and renames it when verified.
+pub struct Joiner {
+    manifest: SplitManifest,
+    folder: PathBuf,
+    output: PathBuf,
+    writer: Option<File>,
+    whole: Sha256,
+    next: usize,
+    failed: bool,
+}
+
+impl Joiner {
+    /// Read the manifest; fails if the joined file already exists
+    pub fn open(manifest_path: &Path) -> Result<Self, String> {
+        let manifest = SplitManifest::load(manifest_path)?;
+        let folder = manifest_path.parent().map(Path::to_path_buf).unwrap_or_default();
+        // Only the file name, so a manifest can't write outside its folder
+        let file_name = Path::new(&manifest.file_name)
+            .file_name()
+            .ok_or_else(|| format!("{}: no file name", manifest_path.display()))?;
+        let output = folder.join(file_name);
+        if output.exists() {
+            return Err(format!("{} already exists", output.display()));
+        }
+        Ok(Self {
+            manifest,
+            folder,
+            output,
+            writer: None,
+            whole: Sha256::new(),
+            next: 0,
+            failed: false,
+        })
+    }
+
+    /// Paths of the parts, in order
+    pub fn parts(&self) -> Vec<PathBuf> {
+        self.manifest
+            .parts
+            .iter()
+            .filter_map(|part| Path::new(&part.name).file_name().map(|name| self.folder.join(name)))
+            .collect()
+    }
+
+    /// The joined file (exists once the last part is verified)
+    pub fn output(&self) -> &Path {
+        &self.output
+    }
+
+    fn temp_path(&self) -> PathBuf {
+        let mut name = self.output.as_os_str().to_owned();
+        name.push(".joining");
+        PathBuf::from(name)
+    }
+
+    /// Append the next part. Parts after a failure are passed over, so only the first error is reported.
+    pub fn append(&mut self, part_path: &Path, on_bytes: &mut dyn FnMut(u64)) -> Result<(), JobError> {
+        if self.failed {
+            return Ok(());
+        }
+        let result = self.append_part(part_path, on_bytes);
+        if result.is_err() {
+            self.failed = true;
+            self.writer = None;
+            let _ = fs::remove_file(self.temp_path());
+        }
+        result
+    }
+
+    fn append_part(&mut self, part_path: &Path, on_bytes: &mut dyn FnMut(u64)) -> Result<(), JobError> {
+        let Some(part) = self.manifest.parts.get(self.next) else {
+            return Err("More parts than the manifest lists".to_string().into());
+        };
+        let expected = self.folder.join(Path::new(&part.name).file_name().unwrap_or_default());
+        if part_path != expected {
+            // The job skips parts that don't exist
+            return Err(format!("{} is missing", part.name).into());
+        }
+        if self.writer.is_none() {
+            self.writer = Some(File::create_new(self.temp_path())?);
+        }
+        let Some(writer) = self.writer.as_mut() else {
+            return Ok(());
+        };
+
+        let mut reader = File::open(part_path)?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; jobs::CHUNK_BYTES];
+        let mut read_total = 0u64;
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            writer.write_all(&buffer[..read])?;
+            hasher.update(&buffer[..read]);
+            self.whole.update(&buffer[..read]);
+            read_total += read as u64;
+            on_bytes(read_total);
+        }
+        if read_total != part.size || to_hex(&hasher.finalize()) != part.sha256 {
+            return Err(format!("{} is damaged (size or SHA-256 doesn't match the manifest)", part.name).into());
+        }
+        self.next += 1;
+
+        if self.next == self.manifest.parts.len() {
+            writer.flush()?;
+            self.writer = None;
+            let whole = std::mem::take(&mut self.whole);
+            if to_hex(&whole.finalize()) != self.manifest.sha256 {
+                return Err("The joined file's SHA-256 doesn't match the manifest".to_string().into());
+            }
+            fs::rename(self.temp_path(), &self.output)?;
+        }
+        Ok(())
+    }
+
+    /// Whether every part was appended and the result verified
+    pub fn is_complete(&self) -> bool {
+        !self.failed && self.next == self.manifest.parts.len()
+    }
+
+    /// Remove a partial output (after a missing last part, for example)
+    pub fn discard(&self) {
+        if !self.is_complete() {
+            let _ = fs::remove_file(self.temp_path());
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::file_scanner::FileInfo;
+use crate::priority;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// An alternate data stream of an NTFS file (Zone.Identifier, or data hidden next to the content)
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataStream {
+    /// Stream name without the ":" and ":$DATA" around it
+    pub name: String,
+    pub size: u64,
+    /// What a Zone.Identifier stream says: zone and download URL
+    pub detail: Option<String>,
+}
+
+impl DataStream {
+    /// "Zone.Identifier (26 bytes): Internet, from https://..."
+    pub fn describe(&self) -> String {
+        match &self.detail {
+            Some(detail) => format!("{} ({} bytes): {}", self.name, self.size, detail),
+            None => format!("{} ({} bytes)", self.name, self.size),
+        }
+    }
+}
+
+/// Message from the background stream listing
+pub enum StreamMessage {
+    Progress { done: usize, total: usize },
+    /// (absolute path, streams) of the files that have alternate streams, and the number of
+    /// files whose streams could not be listed
+    Finished(Vec<(String, Vec<DataStream>)>, usize),
+}
+
+/// Name of an alternate stream from FindFirstStreamW's ":name:$DATA"; None for the file's
+/// content ("::$DATA")
+#[cfg(windows)]
+fn stream_name(raw: &str) -> Option<String> {
+    let name = raw.strip_prefix(':').unwrap_or(raw);
+    let name = name.strip_suffix(":$DATA").unwrap_or(name);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// "Internet, from https://example.com/setup.exe" from a Zone.Identifier stream
+fn describe_zone_identifier(text: &str) -> Option<String> {
+    let value = |key: &str| {
+        text.lines()
+            .filter_map(|line| line.trim().split_once('='))
+            .find(|(k, _)| k.trim().eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let zone = value("ZoneId")?;
+    let zone = match zone.as_str() {
+        "0" => "Local computer".to_string(),
+        "1" => "Local intranet".to_string(),
+        "2" => "Trusted sites".to_string(),
+        "3" => "Internet".to_string(),
+        "4" => "Restricted sites".to_string(),
+        other => format!("Zone {}", other),
+    };
+    match value("HostUrl").or_else(|| value("ReferrerUrl")) {
+        Some(url) => Some(format!("{}, from {}", zone, url)),
+        None => Some(zone),
+    }
+}
+
+/// Alternate data streams of a file (empty on volumes without streams, e.g. FAT32 or exFAT)
+#[cfg(windows)]
+pub fn alternate_streams(path: &Path) -> Result<Vec<DataStream>, String> {
+    use std::ffi::c_void;
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::{GetLastError, ERROR_HANDLE_EOF, ERROR_INVALID_PARAMETER, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Storage::FileSystem::{
+        FindClose, FindFirstStreamW, FindNextStreamW, FindStreamInfoStandard, WIN32_FIND_STREAM_DATA,
+    };
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut data: WIN32_FIND_STREAM_DATA = unsafe { std::mem::zeroed() };
+    let data_ptr = &mut data as *mut WIN32_FIND_STREAM_DATA as *mut c_void;
+    let handle = unsafe { FindFirstStreamW(wide.as_ptr(), FindStreamInfoStandard, data_ptr, 0) };
+    if handle == INVALID_HANDLE_VALUE {
+        let error = unsafe { GetLastError() };
+        // No stream at all, or a file system without streams
+        if error == ERROR_HANDLE_EOF || error == ERROR_INVALID_PARAMETER {
+            return Ok(Vec::new());
+        }
+        return Err(std::io::Error::from_raw_os_error(error as i32).to_string());
+    }
+
+    let mut streams = Vec::new();
+    loop {
+        let length = data.cStreamName.iter().position(|&c| c == 0).unwrap_or(data.cStreamName.len());
+        if let Some(name) = stream_name(&String::from_utf16_lossy(&data.cStreamName[..length])) {
+            streams.push(DataStream {
+                name,
+                size: u64::try_from(data.StreamSize).unwrap_or(0),
+                detail: None,
+            });
+        }
+        if unsafe { FindNextStreamW(handle, data_ptr) } == 0 {
+            break;
+        }
+    }
+    unsafe { FindClose(handle) };
+    Ok(streams)
+}
+
+#[cfg(not(windows))]
+pub fn alternate_streams(_path: &Path) -> Result<Vec<DataStream>, String> {
+    Ok(Vec::new())
+}
+
+/// Read what a (small) Zone.Identifier stream says about the file's origin
+fn add_zone_detail(path: &Path, stream: &mut DataStream) {
+    const MAX_ZONE_BYTES: u64 = 64 * 1024;
+    if !stream.name.eq_ignore_ascii_case("Zone.Identifier") || stream.size > MAX_ZONE_BYTES {
+        return;
+    }
+    let stream_path = format!("{}:{}", path.display(), stream.name);
+    if let Ok(text) = std::fs::read_to_string(stream_path) {
+        stream.detail = describe_zone_identifier(&text);
+    }
+}
+
+/// List the alternate streams of the files on a background thread (online-only files are
+/// listed, but their Zone.Identifier is not read)
+pub fn spawn_stream_scan(files: Vec<FileInfo>) -> Receiver<StreamMessage> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        priority::enter_background();
+        let mut found = Vec::new();
+        let mut failed = 0;
+        let files: Vec<&FileInfo> = files.iter().filter(|f| !f.is_dir).collect();
+        for (done, file) in files.iter().enumerate() {
+            if done % 256 == 0 {
+                let _ = tx.send(StreamMessage::Progress { done, total: files.len() });
+            }
+            priority::throttle();
+            let path = Path::new(file.absolute_path());
+            match alternate_streams(path) {
+                Ok(mut streams) if !streams.is_empty() => {
+                    if !file.is_cloud_placeholder {
+                        for stream in &mut streams {
+                            add_zone_detail(path, stream);
+                        }
+                    }
+                    found.push((file.absolute_path().to_string(), streams));
+                }
+                Ok(_) => {}
+                Err(_) => failed += 1,
+            }
+        }
+        let _ = tx.send(StreamMessage::Finished(found, failed));
+    });
+    rx
+}
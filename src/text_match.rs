@@ -0,0 +1,226 @@
+use icu_collator::options::CollatorOptions;
+use icu_collator::{Collator, CollatorBorrowed};
+use icu_normalizer::{ComposingNormalizerBorrowed, DecomposingNormalizerBorrowed};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::sync::atomic::{self, AtomicBool, AtomicUsize};
+
+/// Diacritics removed by accent folding. Only the generic combining marks used by Latin,
+/// Greek and Cyrillic: Thai and other scripts' vowel and tone marks change meaning and are kept.
+fn is_accent(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}' | '\u{1AB0}'..='\u{1AFF}' | '\u{1DC0}'..='\u{1DFF}' | '\u{20D0}'..='\u{20FF}' | '\u{FE20}'..='\u{FE2F}'
+    )
+}
+
+/// Fold text for matching: Unicode compatibility normalization (so "e" + combining acute equals "é",
+/// and Thai sara am equals nikhahit + sara aa) and lowercase; with `fold_accents`, diacritics are dropped too
+pub fn fold(text: &str, fold_accents: bool) -> String {
+    if text.is_ascii() {
+        return text.to_ascii_lowercase();
+    }
+    if fold_accents {
+        let decomposed = DecomposingNormalizerBorrowed::new_nfkd().normalize(text);
+        decomposed.chars().filter(|&c| !is_accent(c)).collect::<String>().to_lowercase()
+    } else {
+        ComposingNormalizerBorrowed::new_nfkc().normalize(text).to_lowercase()
+    }
+}
+
+/// True if `text` contains `folded_query` (already folded with the same setting)
+pub fn contains(text: &str, folded_query: &str, fold_accents: bool) -> bool {
+    fold(text, fold_accents).contains(folded_query)
+}
+
+/// Unicode form names are brought to before they are compared (sorting, equal-name duplicates),
+/// so a name written on macOS (decomposed) matches the same name written on Windows (composed)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameForm {
+    /// Compare names as stored on disk
+    Off,
+    /// Composed: "é" as one character, as Windows writes it
+    #[default]
+    Nfc,
+    /// Decomposed: "e" + combining accent, as macOS writes it
+    Nfd,
+}
+
+impl NameForm {
+    pub const ALL: [NameForm; 3] = [NameForm::Nfc, NameForm::Nfd, NameForm::Off];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            NameForm::Off => "Off (as stored)",
+            NameForm::Nfc => "NFC (composed, Windows)",
+            NameForm::Nfd => "NFD (decomposed, macOS)",
+        }
+    }
+}
+
+/// Form names are compared in (index into NameForm::ALL, configured from the settings)
+static NAME_FORM: AtomicUsize = AtomicUsize::new(0);
+
+/// Set the Unicode form names are compared in
+pub fn configure_name_form(form: NameForm) {
+    let index = NameForm::ALL.iter().position(|&f| f == form).unwrap_or(0);
+    NAME_FORM.store(index, atomic::Ordering::Relaxed);
+}
+
+/// A name in the configured form, for comparing (not for display or file operations)
+pub fn normalize_name(name: &str) -> Cow<'_, str> {
+    if name.is_ascii() {
+        return Cow::Borrowed(name);
+    }
+    match NameForm::ALL[NAME_FORM.load(atomic::Ordering::Relaxed)] {
+        NameForm::Off => Cow::Borrowed(name),
+        NameForm::Nfc => ComposingNormalizerBorrowed::new_nfc().normalize(name),
+        NameForm::Nfd => DecomposingNormalizerBorrowed::new_nfd().normalize(name),
+    }
+}
+
+/// Order of names when sorting by a text column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Collation {
+    /// Case-insensitive by Unicode code point (fast, the same everywhere)
+    #[default]
+    CodePoint,
+    English,
+    German,
+    Swedish,
+    Russian,
+    Thai,
+    Chinese,
+    Japanese,
+}
+
+impl Collation {
+    pub const ALL: [Collation; 8] = [
+        Collation::CodePoint,
+        Collation::English,
+        Collation::German,
+        Collation::Swedish,
+        Collation::Russian,
+        Collation::Thai,
+        Collation::Chinese,
+        Collation::Japanese,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Collation::CodePoint => "Code point (no locale)",
+            Collation::English => "English",
+            Collation::German => "German",
+            Collation::Swedish => "Swedish (å, ä, ö after z)",
+            Collation::Russian => "Russian",
+            Collation::Thai => "Thai (ก ข ค ... with leading vowels)",
+            Collation::Chinese => "Chinese (pinyin)",
+            Collation::Japanese => "Japanese",
+        }
+    }
+
+    fn locale(self) -> Option<&'static str> {
+        match self {
+            Collation::CodePoint => None,
+            Collation::English => Some("en"),
+            Collation::German => Some("de"),
+            Collation::Swedish => Some("sv"),
+            Collation::Russian => Some("ru"),
+            Collation::Thai => Some("th"),
+            Collation::Chinese => Some("zh"),
+            Collation::Japanese => Some("ja"),
+        }
+    }
+
+    fn collator(self) -> Option<CollatorBorrowed<'static>> {
+        let locale: icu_locale::Locale = self.locale()?.parse().ok()?;
+        Collator::try_new((&locale).into(), CollatorOptions::default()).ok()
+    }
+}
+
+/// Collation used by `compare` (index into Collation::ALL, configured from the settings)
+static COLLATION: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// Collators aren't shared between threads; each thread builds one for the configured locale
+    static COLLATOR: RefCell<Option<(Collation, Option<CollatorBorrowed<'static>>)>> = const { RefCell::new(None) };
+}
+
+/// Set the collation text columns are sorted by
+pub fn configure_collation(collation: Collation) {
+    let index = Collation::ALL.iter().position(|&c| c == collation).unwrap_or(0);
+    COLLATION.store(index, atomic::Ordering::Relaxed);
+}
+
+/// Compare two names for sorting, in the configured form and collation
+pub fn compare(a: &str, b: &str) -> Ordering {
+    let (a, b) = (&*normalize_name(a), &*normalize_name(b));
+    let collation = Collation::ALL[COLLATION.load(atomic::Ordering::Relaxed)];
+    if collation == Collation::CodePoint {
+        return a.to_lowercase().cmp(&b.to_lowercase());
+    }
+    COLLATOR.with(|cached| {
+        let mut cached = cached.borrow_mut();
+        if cached.as_ref().is_none_or(|(built, _)| *built != collation) {
+            *cached = Some((collation, collation.collator()));
+        }
+        match cached.as_ref().and_then(|(_, collator)| collator.as_ref()) {
+            Some(collator) => collator.compare(a, b),
+            None => a.to_lowercase().cmp(&b.to_lowercase()),
+        }
+    })
+}
+
+/// Natural sorting of names and paths is on (configured from the settings)
+static NATURAL: AtomicBool = AtomicBool::new(true);
+
+/// Turn natural sorting of names and paths on or off
+pub fn configure_natural_sort(natural: bool) {
+    NATURAL.store(natural, atomic::Ordering::Relaxed);
+}
+
+/// Runs of ASCII digits and runs of everything else: "IMG_0010.jpg" -> "IMG_", "0010", ".jpg"
+fn chunks(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let digits = rest.chars().next()?.is_ascii_digit();
+        let end = rest.find(|c: char| c.is_ascii_digit() != digits).unwrap_or(rest.len());
+        let (chunk, tail) = rest.split_at(end);
+        rest = tail;
+        Some(chunk)
+    })
+}
+
+/// Compare digit runs by value, of any length ("0010" = "10" < "9000000000000000000000")
+fn compare_numbers(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.trim_start_matches('0'), b.trim_start_matches('0'));
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// Compare names or paths for sorting: with natural sorting on, numbers in them by value
+/// ("file2" before "file10"), the text between by `compare`; names equal that way (e.g.
+/// "file01" and "file1") fall back to `compare` so the order stays stable
+pub fn compare_natural(a: &str, b: &str) -> Ordering {
+    if !NATURAL.load(atomic::Ordering::Relaxed) {
+        return compare(a, b);
+    }
+    let (mut left, mut right) = (chunks(a), chunks(b));
+    loop {
+        let order = match (left.next(), right.next()) {
+            (None, None) => return compare(a, b),
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(x), Some(y)) if x.starts_with(|c: char| c.is_ascii_digit()) && y.starts_with(|c: char| c.is_ascii_digit()) => {
+                compare_numbers(x, y)
+            }
+            (Some(x), Some(y)) => compare(x, y),
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+}
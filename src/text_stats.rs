@@ -0,0 +1,107 @@
+use crate::document_parser;
+use crate::file_scanner::FileInfo;
+use crate::priority;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Prose files: counted in lines and words
+const PROSE_EXTENSIONS: [&str; 8] = ["txt", "md", "markdown", "rst", "tex", "adoc", "org", "log"];
+
+/// Larger text files are left uncounted (logs and dumps, not something anyone wrote)
+const MAX_TEXT_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Minimum interval between batches of counts sent to the GUI
+const BATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Lines (and words, for prose) of a text file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextCounts {
+    pub lines: u64,
+    /// None for code and data files
+    pub words: Option<u64>,
+}
+
+/// The Lines / Words columns count files with this extension (source code and plain text)
+pub fn is_counted(extension: &str) -> bool {
+    let extension = extension.to_lowercase();
+    PROSE_EXTENSIONS.contains(&extension.as_str())
+        || document_parser::CODE_EXTENSIONS.contains(&extension.as_str())
+        || extension == "csv"
+}
+
+/// Files the background counting reads: text files up to 64 MB, not online-only
+fn is_countable(file: &FileInfo) -> bool {
+    !file.is_dir && !file.is_cloud_placeholder && file.file_size <= MAX_TEXT_BYTES && is_counted(file.extension())
+}
+
+/// Count a text file's lines, and its words if it is prose; binary content is an error
+pub fn count_text(path: &Path, extension: &str) -> Result<TextCounts, String> {
+    let content = document_parser::read_text_with_encoding(path)?;
+    if content.contains('\0') {
+        return Err("Not a text file".to_string());
+    }
+    let prose = PROSE_EXTENSIONS.contains(&extension.to_lowercase().as_str());
+    Ok(TextCounts {
+        lines: content.lines().count() as u64,
+        words: prose.then(|| content.split_whitespace().count() as u64),
+    })
+}
+
+/// Message from the background counting
+pub enum TextCountMessage {
+    /// (absolute path, counts) of files counted since the last batch
+    Counted(Vec<(String, TextCounts)>),
+    /// All files done (or cancelled); files that couldn't be read as text
+    Finished { failed: usize },
+}
+
+/// Count the text files among `files` on a background thread, sending the counts in batches so
+/// the columns fill in while it runs; `cancel` stops it.
+pub fn spawn_text_counting(files: Vec<FileInfo>, cancel: Arc<AtomicBool>) -> Receiver<TextCountMessage> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        priority::enter_background();
+        let mut batch = Vec::new();
+        let mut last_sent = Instant::now();
+        let mut failed = 0;
+        for file in files.iter().filter(|f| is_countable(f)) {
+            if cancel.load(Ordering::Relaxed) {
+                break;
+            }
+            priority::throttle();
+            match count_text(Path::new(file.absolute_path()), file.extension()) {
+                Ok(counts) => batch.push((file.absolute_path().to_string(), counts)),
+                Err(_) => failed += 1,
+            }
+            if last_sent.elapsed() >= BATCH_INTERVAL {
+                let _ = tx.send(TextCountMessage::Counted(std::mem::take(&mut batch)));
+                last_sent = Instant::now();
+            }
+        }
+        if !batch.is_empty() {
+            let _ = tx.send(TextCountMessage::Counted(batch));
+        }
+        let _ = tx.send(TextCountMessage::Finished { failed });
+    });
+    rx
+}
+
+/// Fill in the counts of text files not counted yet (command line); returns the files that
+/// could not be read as text
+pub fn fill_text_counts(files: &mut [FileInfo]) -> usize {
+    let mut failed = 0;
+    for file in files.iter_mut().filter(|f| f.line_count.is_none() && is_countable(f)) {
+        match count_text(Path::new(file.absolute_path()), file.extension()) {
+            Ok(counts) => {
+                file.line_count = Some(counts.lines);
+                file.word_count = counts.words;
+            }
+            Err(_) => failed += 1,
+        }
+    }
+    failed
+}
@@ -0,0 +1,229 @@
+use crate::file_scanner::{self, ScanFilter};
+use crate::priority;
+use crate::thumbnail::{ThumbnailGenerator, ThumbnailKind, ThumbnailOptions};
+use crate::watcher::FolderWatcher;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Most threads generating thumbnails at once
+const MAX_WORKERS: usize = 8;
+
+/// What one pass over the folder (or one batch of changes) did
+#[derive(Debug, Default)]
+pub struct SyncSummary {
+    pub written: usize,
+    /// Thumbnails newer than their file, left as they are
+    pub current: usize,
+    pub removed: usize,
+    /// (file, error) of files that could not be previewed
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+impl SyncSummary {
+    /// "12 written, 340 up to date, 1 removed, 2 failed"
+    pub fn describe(&self) -> String {
+        format!(
+            "{} written, {} up to date, {} removed, {} failed",
+            self.written,
+            self.current,
+            self.removed,
+            self.failed.len()
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.written == 0 && self.removed == 0 && self.failed.is_empty()
+    }
+}
+
+/// Thumbnail of the file at `relative` (below the synced folder): the same relative path in
+/// `out` with ".png" appended, so names stay stable as files come and go
+pub fn thumbnail_path(out: &Path, relative: &Path) -> PathBuf {
+    let mut name = out.join(relative).into_os_string();
+    name.push(".png");
+    PathBuf::from(name)
+}
+
+/// The thumbnail exists and is not older than its file
+fn is_current(source: &Path, thumbnail: &Path) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    match (modified(source), modified(thumbnail)) {
+        (Some(source), Some(thumbnail)) => thumbnail >= source,
+        _ => false,
+    }
+}
+
+/// Keeps a folder of PNG thumbnails in step with the images, videos and PDFs of a source folder
+pub struct ThumbnailSync {
+    /// Canonical source folder (the watcher reports paths below it)
+    root: PathBuf,
+    out: PathBuf,
+    options: ThumbnailOptions,
+    /// Online-only cloud files are only read (downloaded) with this
+    read_cloud_files: bool,
+}
+
+impl ThumbnailSync {
+    pub fn new(folder: &Path, out: &Path, options: ThumbnailOptions, read_cloud_files: bool) -> Result<Self, String> {
+        let root = folder.canonicalize().map_err(|e| format!("Cannot open {}: {}", folder.display(), e))?;
+        fs::create_dir_all(out).map_err(|e| format!("Failed to create {}: {}", out.display(), e))?;
+        let out = out.canonicalize().map_err(|e| format!("Cannot open {}: {}", out.display(), e))?;
+        // The thumbnails would be previewed in turn
+        if out.starts_with(&root) {
+            return Err("The thumbnail folder must not be inside the scanned folder".to_string());
+        }
+        Ok(Self { root, out, options, read_cloud_files })
+    }
+
+    /// Bring the thumbnails of all files up to date and remove those of files that are gone
+    pub fn sync_all(&self, recursive: bool, filter: ScanFilter) -> Result<SyncSummary, String> {
+        let (files, _) = file_scanner::scan_folder_with_report(&self.root, recursive, filter)
+            .map_err(|e| format!("Failed to scan {}: {}", self.root.display(), e))?;
+        let sources = files
+            .iter()
+            .filter(|f| !f.is_dir && (self.read_cloud_files || !f.is_cloud_placeholder))
+            .map(|f| PathBuf::from(f.absolute_path()))
+            .collect();
+        let mut summary = self.generate(sources);
+        summary.removed = self.remove_orphans();
+        Ok(summary)
+    }
+
+    /// Follow the folder until the process ends, updating thumbnails as files arrive, change
+    /// or go; `report` is called after each batch of changes. A file still being copied when
+    /// it is seen fails or gives a partial preview, and is redone when its size or date changes.
+    pub fn watch(&self, recursive: bool, filter: ScanFilter, report: &mut dyn FnMut(&SyncSummary)) -> Result<(), String> {
+        // Started first, so files arriving during the initial pass are not missed
+        let watcher = FolderWatcher::start(std::slice::from_ref(&self.root), recursive, filter);
+        report(&self.sync_all(recursive, filter)?);
+        for changes in watcher.receiver.iter() {
+            let sources = changes
+                .added
+                .into_iter()
+                .chain(changes.modified)
+                .filter(|path| self.read_cloud_files || !file_scanner::file_info(path, &self.root).is_cloud_placeholder)
+                .collect();
+            let mut summary = self.generate(sources);
+            summary.removed = changes.removed.iter().filter(|path| self.remove(path)).count();
+            if !summary.is_empty() {
+                report(&summary);
+            }
+        }
+        Err("The folder watcher stopped".to_string())
+    }
+
+    /// Generate the missing or outdated thumbnails on worker threads. Pdfium can't be used from
+    /// several threads, so PDFs all go to the first worker.
+    fn generate(&self, sources: Vec<PathBuf>) -> SyncSummary {
+        let (pdfs, others): (Vec<_>, Vec<_>) = sources
+            .into_iter()
+            .filter_map(|path| {
+                let kind = ThumbnailKind::from_extension(&path.extension()?.to_string_lossy())?;
+                Some((path, kind))
+            })
+            .partition(|(_, kind)| *kind == ThumbnailKind::Pdf);
+        let next = AtomicUsize::new(0);
+        let workers = thread::available_parallelism().map_or(1, |n| n.get()).min(MAX_WORKERS);
+
+        let results: Vec<Vec<(PathBuf, Result<bool, String>)>> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|worker| {
+                    let (pdfs, others, next) = (&pdfs, &others, &next);
+                    scope.spawn(move || {
+                        priority::enter_background();
+                        let mut generator = ThumbnailGenerator::default();
+                        let mut results = Vec::new();
+                        let own_pdfs = if worker == 0 { pdfs.as_slice() } else { &[] };
+                        for (path, kind) in own_pdfs {
+                            results.push((path.clone(), self.update(&mut generator, path, *kind)));
+                        }
+                        while let Some((path, kind)) = others.get(next.fetch_add(1, Ordering::Relaxed)) {
+                            results.push((path.clone(), self.update(&mut generator, path, *kind)));
+                        }
+                        results
+                    })
+                })
+                .collect();
+            handles.into_iter().filter_map(|handle| handle.join().ok()).collect()
+        });
+
+        let mut summary = SyncSummary::default();
+        for (path, result) in results.into_iter().flatten() {
+            match result {
+                Ok(true) => summary.written += 1,
+                Ok(false) => summary.current += 1,
+                Err(e) => summary.failed.push((path, e)),
+            }
+        }
+        summary
+    }
+
+    /// Write the thumbnail of one file unless it is current; Ok(false) if it was
+    fn update(&self, generator: &mut ThumbnailGenerator, source: &Path, kind: ThumbnailKind) -> Result<bool, String> {
+        let relative = source.strip_prefix(&self.root).map_err(|_| "Outside the scanned folder".to_string())?;
+        let target = thumbnail_path(&self.out, relative);
+        if is_current(source, &target) {
+            return Ok(false);
+        }
+        priority::throttle();
+        let png = generator.generate(&source.to_string_lossy(), kind, &self.options)?.to_png()?;
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        // Written next to it and renamed, so a player never shows a half-written thumbnail
+        let partial = target.with_extension("png.partial");
+        fs::write(&partial, png).map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+        fs::rename(&partial, &target).map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+        Ok(true)
+    }
+
+    /// Delete the thumbnail of a removed file and the folders it leaves empty; true if there was one
+    fn remove(&self, source: &Path) -> bool {
+        let Ok(relative) = source.strip_prefix(&self.root) else {
+            return false;
+        };
+        let target = thumbnail_path(&self.out, relative);
+        if fs::remove_file(&target).is_err() {
+            return false;
+        }
+        for folder in target.ancestors().skip(1).take_while(|folder| *folder != self.out) {
+            if fs::remove_dir(folder).is_err() {
+                break;
+            }
+        }
+        true
+    }
+
+    /// Delete thumbnails whose file no longer exists. Only "<previewable file>.png" names are
+    /// considered, so other files in the folder are left alone.
+    fn remove_orphans(&self) -> usize {
+        let mut orphans = Vec::new();
+        let mut pending = vec![self.out.clone()];
+        while let Some(dir) = pending.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for path in entries.flatten().map(|entry| entry.path()) {
+                if path.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+                let Ok(relative) = path.strip_prefix(&self.out) else {
+                    continue;
+                };
+                let Some(source) = relative.to_str().and_then(|name| name.strip_suffix(".png")).map(|name| self.root.join(name)) else {
+                    continue;
+                };
+                let previewable = source
+                    .extension()
+                    .is_some_and(|extension| ThumbnailKind::from_extension(&extension.to_string_lossy()).is_some());
+                if previewable && !source.exists() {
+                    orphans.push(source);
+                }
+            }
+        }
+        orphans.iter().filter(|source| self.remove(source)).count()
+    }
+}
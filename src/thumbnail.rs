@@ -0,0 +1,478 @@
+use crate::document_parser;
+use crate::retry;
+use crate::settings::PreviewQuality;
+use pdfium_render::prelude::*;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Error returned when a thumbnail was cancelled before it finished
+pub const CANCELLED: &str = "Cancelled";
+
+/// Decoded RGBA thumbnail
+pub struct Thumbnail {
+    pub pixels: Vec<u8>,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Thumbnail {
+    /// Encode as PNG bytes
+    pub fn to_png(&self) -> Result<Vec<u8>, String> {
+        let buffer = image::RgbaImage::from_raw(self.width as u32, self.height as u32, self.pixels.clone())
+            .ok_or("Invalid thumbnail buffer")?;
+        let mut png_bytes = Vec::new();
+        image::DynamicImage::ImageRgba8(buffer)
+            .write_to(&mut std::io::Cursor::new(&mut png_bytes), image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+        Ok(png_bytes)
+    }
+}
+
+/// Source type a thumbnail is generated from
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ThumbnailKind {
+    Image,
+    Video,
+    Pdf,
+}
+
+impl ThumbnailKind {
+    /// Get the thumbnail kind for a file extension (None if not supported)
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "jpg" | "jpeg" | "png" | "gif" | "bmp" | "ico" | "webp" => Some(ThumbnailKind::Image),
+            "mp4" | "avi" | "mkv" | "mov" | "wmv" | "flv" | "webm" | "m4v" | "mpeg" | "mpg" | "3gp" => {
+                Some(ThumbnailKind::Video)
+            }
+            "pdf" => Some(ThumbnailKind::Pdf),
+            _ => None,
+        }
+    }
+}
+
+/// Thumbnail generation options
+#[derive(Clone, Copy, Debug)]
+pub struct ThumbnailOptions {
+    /// Maximum width/height in pixels
+    pub max_size: u32,
+    /// Video timestamp to grab (seconds)
+    pub video_frame_secs: f32,
+    /// PDF render resolution
+    pub pdf_render_dpi: f32,
+    pub quality: PreviewQuality,
+}
+
+/// Program that extracts video frames
+#[derive(Debug, Clone, PartialEq)]
+pub enum VideoDecoder {
+    Ffmpeg(PathBuf),
+    /// Windows' built-in decoders (through WinRT MediaComposition in PowerShell)
+    #[cfg(windows)]
+    MediaFoundation,
+    /// gst-launch-1.0 of a GStreamer install (first frame only)
+    #[cfg(not(windows))]
+    GStreamer(PathBuf),
+}
+
+impl VideoDecoder {
+    pub fn label(&self) -> &'static str {
+        match self {
+            VideoDecoder::Ffmpeg(_) => "FFmpeg",
+            #[cfg(windows)]
+            VideoDecoder::MediaFoundation => "Media Foundation",
+            #[cfg(not(windows))]
+            VideoDecoder::GStreamer(_) => "GStreamer",
+        }
+    }
+
+    /// Extract one frame (JPEG or PNG bytes); the decoder process is killed as soon as `cancelled` returns true
+    pub fn extract_frame(
+        &self,
+        video_path: &str,
+        frame_secs: f32,
+        quality: PreviewQuality,
+        cancelled: &dyn Fn() -> bool,
+    ) -> Result<Vec<u8>, String> {
+        match self {
+            VideoDecoder::Ffmpeg(ffmpeg) => extract_video_frame(ffmpeg, video_path, frame_secs, quality, cancelled),
+            #[cfg(windows)]
+            VideoDecoder::MediaFoundation => media_foundation_frame(video_path, frame_secs, cancelled),
+            #[cfg(not(windows))]
+            VideoDecoder::GStreamer(gst_launch) => gstreamer_frame(gst_launch, video_path, cancelled),
+        }
+    }
+}
+
+/// Generates thumbnails for images, videos and PDFs.
+/// Binds Pdfium and resolves the video decoder once per generator, so keep one alive
+/// for a whole session or batch.
+#[derive(Default)]
+pub struct ThumbnailGenerator {
+    pdfium: Option<Pdfium>,
+    video: Option<VideoDecoder>,
+}
+
+impl ThumbnailGenerator {
+    /// Generate a thumbnail for the file at path
+    pub fn generate(&mut self, path: &str, kind: ThumbnailKind, options: &ThumbnailOptions) -> Result<Thumbnail, String> {
+        self.generate_cancellable(path, kind, options, &|| false)
+    }
+
+    /// Generate a thumbnail, stopping the video decoder early (Err(CANCELLED)) once `cancelled` returns true
+    pub fn generate_cancellable(
+        &mut self,
+        path: &str,
+        kind: ThumbnailKind,
+        options: &ThumbnailOptions,
+        cancelled: &dyn Fn() -> bool,
+    ) -> Result<Thumbnail, String> {
+        let data = match kind {
+            ThumbnailKind::Video => {
+                if self.video.is_none() {
+                    self.video = find_video_decoder();
+                }
+                let decoder = self.video.as_ref().ok_or("No video decoder found (install FFmpeg)")?;
+                decoder.extract_frame(path, options.video_frame_secs, options.quality, cancelled)?
+            }
+            ThumbnailKind::Pdf => {
+                // Bind lazily: Pdfium may finish downloading after the generator was created
+                if self.pdfium.is_none() {
+                    self.pdfium = bind_pdfium();
+                }
+                let pdfium = self.pdfium.as_ref().ok_or("Pdfium not available")?;
+                render_pdf_page(pdfium, path, options.pdf_render_dpi, options.max_size)?
+            }
+            ThumbnailKind::Image => retry::with_retry(|| document_parser::read_whole(Path::new(path))).map_err(|e| format!("Failed to read image: {}", e))?,
+        };
+
+        decode_thumbnail(&data, options.max_size, options.quality)
+    }
+}
+
+/// Directory where the downloaded Pdfium library is stored
+pub fn pdfium_dir() -> PathBuf {
+    // Store in user's app data directory
+    let base = dirs::data_local_dir().unwrap_or_else(std::env::temp_dir);
+    base.join("file-lister").join("pdfium")
+}
+
+/// Bind to the Pdfium library (system library first, then downloaded library)
+pub fn bind_pdfium() -> Option<Pdfium> {
+    let bindings = Pdfium::bind_to_system_library()
+        .or_else(|_| Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path(&pdfium_dir())))
+        .ok()?;
+    Some(Pdfium::new(bindings))
+}
+
+/// Find FFmpeg executable in system PATH
+pub fn find_ffmpeg() -> Option<PathBuf> {
+    if let Ok(output) = Command::new("where").arg("ffmpeg").output() {
+        if output.status.success() {
+            let path_str = String::from_utf8_lossy(&output.stdout);
+            if let Some(first_line) = path_str.lines().next() {
+                let path = PathBuf::from(first_line.trim());
+                if path.exists() {
+                    return Some(path);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// FFmpeg when installed, otherwise the platform's own decoder (Media Foundation on Windows,
+/// GStreamer on Linux), so common codecs preview without installing anything
+pub fn find_video_decoder() -> Option<VideoDecoder> {
+    find_ffmpeg().map(VideoDecoder::Ffmpeg).or_else(platform_video_decoder)
+}
+
+/// Media Foundation is missing on Windows N editions without the Media Feature Pack
+#[cfg(windows)]
+fn platform_video_decoder() -> Option<VideoDecoder> {
+    let system_root = std::env::var_os("SystemRoot")?;
+    Path::new(&system_root)
+        .join("System32")
+        .join("mfplat.dll")
+        .exists()
+        .then_some(VideoDecoder::MediaFoundation)
+}
+
+#[cfg(not(windows))]
+fn platform_video_decoder() -> Option<VideoDecoder> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join("gst-launch-1.0"))
+        .find(|path| path.is_file())
+        .map(VideoDecoder::GStreamer)
+}
+
+/// Decode image bytes and resize to fit within max_size pixels
+pub fn decode_thumbnail(data: &[u8], max_size: u32, quality: PreviewQuality) -> Result<Thumbnail, String> {
+    let image = image::load_from_memory(data).map_err(|e| format!("Failed to decode image: {}", e))?;
+
+    // Resize large images for preview
+    let (width, height) = if image.width() > max_size || image.height() > max_size {
+        let aspect = image.width() as f32 / image.height() as f32;
+        if aspect > 1.0 {
+            (max_size, (max_size as f32 / aspect) as u32)
+        } else {
+            ((max_size as f32 * aspect) as u32, max_size)
+        }
+    } else {
+        (image.width(), image.height())
+    };
+
+    let filter = match quality {
+        PreviewQuality::Fast => image::imageops::FilterType::Triangle,
+        PreviewQuality::Balanced => image::imageops::FilterType::CatmullRom,
+        PreviewQuality::High => image::imageops::FilterType::Lanczos3,
+    };
+    let resized = image.resize(width, height, filter);
+    let pixels = resized.to_rgba8().into_raw();
+
+    Ok(Thumbnail {
+        pixels,
+        width: resized.width() as usize,
+        height: resized.height() as usize,
+    })
+}
+
+/// Render the first page of a PDF as PNG bytes
+pub fn render_pdf_page(pdfium: &Pdfium, pdf_path: &str, render_dpi: f32, max_size: u32) -> Result<Vec<u8>, String> {
+    let document = pdfium
+        .load_pdf_from_file(pdf_path, None)
+        .map_err(|e| format!("Failed to open PDF: {}", e))?;
+
+    if document.pages().is_empty() {
+        return Err("PDF has no pages".to_string());
+    }
+
+    let page = document.pages().get(0).map_err(|e| format!("Failed to load page: {}", e))?;
+
+    // Render at the configured DPI (page size is in points, 72 per inch), capped to
+    // 2x the preview size - the result is downscaled to the preview size afterwards
+    let page_width: f32 = page.width().value;
+    let page_height: f32 = page.height().value;
+    let scale: f32 = (render_dpi / 72.0).min(2.0 * max_size as f32 / page_width.max(page_height));
+    let width = (page_width * scale) as i32;
+    let height = (page_height * scale) as i32;
+
+    let bitmap = page
+        .render_with_config(
+            &PdfRenderConfig::new()
+                .set_target_width(width)
+                .set_target_height(height),
+        )
+        .map_err(|e| format!("Failed to render page: {}", e))?;
+
+    // Convert to PNG bytes
+    let image = bitmap.as_image();
+    let mut png_bytes = Vec::new();
+    let mut cursor = std::io::Cursor::new(&mut png_bytes);
+    image
+        .write_to(&mut cursor, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode page: {}", e))?;
+
+    Ok(png_bytes)
+}
+
+/// Output file of one decoder run. Several generators decode at once (thumbnail sync workers,
+/// the preview service), so each run gets its own name and removes only that file.
+fn frame_temp_file(prefix: &str, extension: &str) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let run = NEXT.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!("{}_{}_{}.{}", prefix, std::process::id(), run, extension))
+}
+
+/// Extract a frame from a video file using FFmpeg.
+/// The FFmpeg process is killed as soon as `cancelled` returns true.
+pub fn extract_video_frame(
+    ffmpeg: &Path,
+    video_path: &str,
+    frame_secs: f32,
+    quality: PreviewQuality,
+    cancelled: &dyn Fn() -> bool,
+) -> Result<Vec<u8>, String> {
+    // Lossless PNG for high quality, otherwise JPEG (smaller and faster to decode)
+    let (extension, codec_args): (&str, &[&str]) = match quality {
+        PreviewQuality::High => ("png", &["-vcodec", "png"]),
+        PreviewQuality::Balanced => ("jpg", &["-vcodec", "mjpeg", "-q:v", "2"]),
+        PreviewQuality::Fast => ("jpg", &["-vcodec", "mjpeg", "-q:v", "8"]),
+    };
+
+    // Use a temp file instead of pipe (more reliable on Windows)
+    let temp_file = frame_temp_file("thumb", extension);
+    let temp_path = temp_file.to_string_lossy().to_string();
+
+    // Try the configured timestamp first, then the first frame (for short videos)
+    let mut timestamps = vec![frame_secs.max(0.0)];
+    if frame_secs > 0.0 {
+        timestamps.push(0.0);
+    }
+
+    let mut last_error = String::from("Failed to extract thumbnail");
+    for secs in timestamps {
+        let timestamp = format!("{:.3}", secs);
+        // Errors only, so the stderr pipe can't fill up while we poll
+        let mut command = Command::new(ffmpeg);
+        command
+            .args(["-nostdin", "-loglevel", "error", "-i", video_path, "-ss", &timestamp, "-vframes", "1"])
+            .args(codec_args)
+            .args(["-y", &temp_path]);
+        let (success, stderr) = run_cancellable(command, "FFmpeg", cancelled).inspect_err(|_| {
+            let _ = std::fs::remove_file(&temp_file);
+        })?;
+
+        if success {
+            // Read the temp file
+            if let Ok(data) = std::fs::read(&temp_file) {
+                let _ = std::fs::remove_file(&temp_file);
+                if !data.is_empty() {
+                    return Ok(data);
+                }
+            }
+        } else {
+            last_error = format!(
+                "FFmpeg failed at {}s: {}",
+                timestamp,
+                stderr.lines().last().unwrap_or_default()
+            );
+        }
+    }
+
+    let _ = std::fs::remove_file(&temp_file);
+    Err(last_error)
+}
+
+/// Run a decoder process, polling `cancelled` (the process is killed and Err(CANCELLED) returned).
+/// Returns whether it succeeded and its stderr.
+fn run_cancellable(mut command: Command, name: &str, cancelled: &dyn Fn() -> bool) -> Result<(bool, String), String> {
+    // Decoders run quietly, so the stderr pipe can't fill up while we poll
+    let mut child = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", name, e))?;
+
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| format!("Failed to run {}: {}", name, e))? {
+            break status;
+        }
+        if cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(CANCELLED.to_string());
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    let mut stderr = String::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        let _ = pipe.read_to_string(&mut stderr);
+    }
+    Ok((status.success(), stderr))
+}
+
+/// Read and remove a decoder's output file
+fn take_frame(temp_file: &Path, success: bool, stderr: &str, name: &str) -> Result<Vec<u8>, String> {
+    let data = std::fs::read(temp_file);
+    let _ = std::fs::remove_file(temp_file);
+    match data {
+        Ok(data) if success && !data.is_empty() => Ok(data),
+        _ => Err(format!(
+            "{} failed: {}",
+            name,
+            stderr.lines().rfind(|line| !line.trim().is_empty()).unwrap_or("no frame decoded").trim()
+        )),
+    }
+}
+
+/// Extract a frame through Windows' MediaComposition (Media Foundation decoders, including
+/// hardware decoding); the frame time is capped to the middle of short videos
+#[cfg(windows)]
+fn media_foundation_frame(video_path: &str, frame_secs: f32, cancelled: &dyn Fn() -> bool) -> Result<Vec<u8>, String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+    const SCRIPT: &str = "$ErrorActionPreference = 'Stop'; \
+        Add-Type -AssemblyName System.Runtime.WindowsRuntime; \
+        $asTask = [System.WindowsRuntimeSystemExtensions].GetMethods() | Where-Object { $_.Name -eq 'AsTask' -and $_.GetParameters().Count -eq 1 -and $_.GetParameters()[0].ParameterType.Name -eq 'IAsyncOperation`1' } | Select-Object -First 1; \
+        function Await($operation, [Type]$type) { $task = $asTask.MakeGenericMethod($type).Invoke($null, @($operation)); $task.Wait() | Out-Null; $task.Result }; \
+        [Windows.Storage.StorageFile, Windows.Storage, ContentType = WindowsRuntime] > $null; \
+        [Windows.Media.Editing.MediaComposition, Windows.Media.Editing, ContentType = WindowsRuntime] > $null; \
+        [Windows.Graphics.Imaging.ImageStream, Windows.Graphics, ContentType = WindowsRuntime] > $null; \
+        $file = Await ([Windows.Storage.StorageFile]::GetFileFromPathAsync($env:THUMB_VIDEO)) ([Windows.Storage.StorageFile]); \
+        $clip = Await ([Windows.Media.Editing.MediaClip]::CreateFromFileAsync($file)) ([Windows.Media.Editing.MediaClip]); \
+        $composition = New-Object Windows.Media.Editing.MediaComposition; \
+        $composition.Clips.Add($clip); \
+        $size = $clip.GetVideoEncodingProperties(); \
+        $at = [TimeSpan]::FromSeconds([Math]::Min([double]$env:THUMB_SECONDS, $clip.OriginalDuration.TotalSeconds / 2)); \
+        $image = Await ($composition.GetThumbnailAsync($at, $size.Width, $size.Height, [Windows.Media.Editing.VideoFramePrecision]::NearestFrame)) ([Windows.Graphics.Imaging.ImageStream]); \
+        $source = [System.IO.WindowsRuntimeStreamExtensions]::AsStreamForRead($image); \
+        $target = [System.IO.File]::Create($env:THUMB_OUTPUT); \
+        $source.CopyTo($target); \
+        $target.Close()";
+
+    let temp_file = frame_temp_file("thumb_mf", "jpg");
+    let mut command = Command::new("powershell");
+    // Paths are passed in the environment, so they need no quoting
+    command
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .env("THUMB_VIDEO", video_path)
+        .env("THUMB_SECONDS", format!("{:.3}", frame_secs.max(0.0)))
+        .env("THUMB_OUTPUT", &temp_file)
+        .stdin(Stdio::null())
+        .creation_flags(CREATE_NO_WINDOW);
+    let (success, stderr) = run_cancellable(command, "Media Foundation", cancelled).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temp_file);
+    })?;
+    take_frame(&temp_file, success, &stderr, "Media Foundation")
+}
+
+/// Extract the first frame with a GStreamer pipeline (decodebin picks the installed
+/// decoders, VA-API included); gst-launch can't seek, so the frame time isn't used
+#[cfg(not(windows))]
+fn gstreamer_frame(gst_launch: &Path, video_path: &str, cancelled: &dyn Fn() -> bool) -> Result<Vec<u8>, String> {
+    let temp_file = frame_temp_file("thumb_gst", "png");
+    // gst-launch re-parses its arguments as one pipeline description: quote the paths
+    let quote = |path: &str| format!("\"{}\"", path.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut command = Command::new(gst_launch);
+    command
+        .arg("-q")
+        .arg("filesrc")
+        .arg(format!("location={}", quote(video_path)))
+        .args(["!", "decodebin", "!", "videoconvert", "!", "pngenc", "snapshot=true", "!", "filesink"])
+        .arg(format!("location={}", quote(&temp_file.to_string_lossy())))
+        .stdin(Stdio::null());
+    let (success, stderr) = run_cancellable(command, "GStreamer", cancelled).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temp_file);
+    })?;
+    take_frame(&temp_file, success, &stderr, "GStreamer")
+}
+
+/// Generate thumbnail PNGs for a batch of files into dir.
+/// Returns the written file name for each input (None if not previewable or failed).
+pub fn export_thumbnails(
+    generator: &mut ThumbnailGenerator,
+    files: &[(String, Option<ThumbnailKind>)],
+    dir: &Path,
+    options: &ThumbnailOptions,
+) -> Result<Vec<Option<String>>, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create thumbnail folder: {}", e))?;
+
+    let mut names = Vec::with_capacity(files.len());
+    for (index, (path, kind)) in files.iter().enumerate() {
+        let name = kind.and_then(|kind| {
+            let thumbnail = generator.generate(path, kind, options).ok()?;
+            let png = thumbnail.to_png().ok()?;
+            let file_name = Path::new(path).file_name()?.to_string_lossy().to_string();
+            // Index prefix keeps names unique when files share a name
+            let name = format!("{:05}_{}.png", index, file_name);
+            std::fs::write(dir.join(&name), png).ok()?;
+            Some(name)
+        });
+        names.push(name);
+    }
+    Ok(names)
+}
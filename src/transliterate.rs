@@ -0,0 +1,238 @@
+/// Thai vowels written before their consonant (เ แ โ ใ ไ)
+fn is_leading_vowel(c: char) -> bool {
+    matches!(c, 'เ' | 'แ' | 'โ' | 'ใ' | 'ไ')
+}
+
+fn is_consonant(c: char) -> bool {
+    ('ก'..='ฮ').contains(&c) && c != 'ฤ' && c != 'ฦ'
+}
+
+/// Consonants a silent ห makes high class (หนู, หมา, ใหม่)
+fn is_sonorant(c: char) -> bool {
+    matches!(c, 'ง' | 'ญ' | 'น' | 'ม' | 'ย' | 'ร' | 'ล' | 'ว')
+}
+
+/// True consonant clusters (กร, กล, กว, ปร, พล, ...)
+fn is_cluster(first: char, second: char) -> bool {
+    matches!(first, 'ก' | 'ข' | 'ค' | 'ป' | 'พ' | 'ต' | 'ผ') && matches!(second, 'ร' | 'ล' | 'ว')
+}
+
+/// Vowel spellings after the leading vowels were moved behind their consonant, longest first
+const VOWELS: [(&str, &str); 30] = [
+    ("เีย", "ia"),
+    ("เือ", "uea"),
+    ("เาะ", "o"),
+    ("เา", "ao"),
+    ("เะ", "e"),
+    ("เอ", "oe"),
+    ("เิ", "oe"),
+    ("แะ", "ae"),
+    ("โะ", "o"),
+    ("ัว", "ua"),
+    ("ไย", "ai"),
+    ("ือ", "ue"),
+    ("ํา", "am"),
+    ("ำ", "am"),
+    ("ะ", "a"),
+    ("ั", "a"),
+    ("า", "a"),
+    ("ิ", "i"),
+    ("ี", "i"),
+    ("ึ", "ue"),
+    ("ื", "ue"),
+    ("ุ", "u"),
+    ("ู", "u"),
+    ("เ", "e"),
+    ("แ", "ae"),
+    ("โ", "o"),
+    ("ใ", "ai"),
+    ("ไ", "ai"),
+    ("ฤ", "rue"),
+    ("ฦ", "lue"),
+];
+
+/// The vowel spelled at `i`: (chars, romanization)
+fn vowel_at(chars: &[char], i: usize) -> Option<(usize, &'static str)> {
+    VOWELS.iter().find_map(|(spelling, latin)| {
+        let len = spelling.chars().count();
+        (chars.len() >= i + len && spelling.chars().eq(chars[i..i + len].iter().copied())).then_some((len, *latin))
+    })
+}
+
+fn initial(c: char) -> &'static str {
+    match c {
+        'ก' => "k",
+        'ข' | 'ฃ' | 'ค' | 'ฅ' | 'ฆ' => "kh",
+        'ง' => "ng",
+        'จ' | 'ฉ' | 'ช' | 'ฌ' => "ch",
+        'ซ' | 'ศ' | 'ษ' | 'ส' => "s",
+        'ญ' | 'ย' => "y",
+        'ฎ' | 'ด' => "d",
+        'ฏ' | 'ต' => "t",
+        'ฐ' | 'ฑ' | 'ฒ' | 'ถ' | 'ท' | 'ธ' => "th",
+        'ณ' | 'น' => "n",
+        'บ' => "b",
+        'ป' => "p",
+        'ผ' | 'พ' | 'ภ' => "ph",
+        'ฝ' | 'ฟ' => "f",
+        'ม' => "m",
+        'ร' => "r",
+        'ล' | 'ฬ' => "l",
+        'ว' => "w",
+        'ห' | 'ฮ' => "h",
+        _ => "",
+    }
+}
+
+fn final_sound(c: char) -> &'static str {
+    match c {
+        'ก' | 'ข' | 'ค' | 'ฆ' => "k",
+        'บ' | 'ป' | 'พ' | 'ฟ' | 'ภ' => "p",
+        'ง' => "ng",
+        'ญ' | 'ณ' | 'น' | 'ร' | 'ล' | 'ฬ' => "n",
+        'ม' => "m",
+        'ย' => "i",
+        'ว' => "o",
+        'อ' => "",
+        _ => "t",
+    }
+}
+
+/// Drop what isn't pronounced (tone marks, the repetition mark, a letter silenced by
+/// thanthakhat) and move leading vowels behind their consonant or cluster
+fn prepare_thai(word: &str) -> Vec<char> {
+    let mut chars: Vec<char> = Vec::new();
+    for c in word.chars() {
+        match c {
+            '\u{0E47}'..='\u{0E4B}' | 'ๆ' => {}
+            '\u{0E4C}' => {
+                // "ศักดิ์": the silenced letter takes its vowel along
+                if chars.pop().is_some_and(|last| !is_consonant(last)) {
+                    chars.pop();
+                }
+            }
+            _ => chars.push(c),
+        }
+    }
+
+    let mut i = 0;
+    while i + 1 < chars.len() {
+        if is_leading_vowel(chars[i]) && is_consonant(chars[i + 1]) {
+            let mut end = i + 1;
+            if let Some(&second) = chars.get(end + 1) {
+                if (chars[end] == 'ห' && is_sonorant(second)) || is_cluster(chars[end], second) {
+                    end += 1;
+                }
+            }
+            let vowel = chars.remove(i);
+            chars.insert(end, vowel);
+            i = end;
+        }
+        i += 1;
+    }
+    chars
+}
+
+/// Simplified Royal Thai General System romanization of a run of Thai text:
+/// "กรุงเทพ" -> "krungthep", "เชียงใหม่" -> "chiangmai", "สวัสดี" -> "sawatdi"
+fn thai_to_latin(word: &str) -> String {
+    let chars = prepare_thai(word);
+    let mut out = String::new();
+    // A vowel was read in the current syllable, so the next lone consonant closes it
+    let mut has_vowel = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if let Some((len, latin)) = vowel_at(&chars, i) {
+            out.push_str(latin);
+            has_vowel = true;
+            i += len;
+            continue;
+        }
+        if ('๐'..='๙').contains(&c) {
+            out.push(char::from(b'0' + (c as u32 - '๐' as u32) as u8));
+            has_vowel = false;
+            i += 1;
+            continue;
+        }
+        if !is_consonant(c) {
+            i += 1;
+            continue;
+        }
+
+        let next = chars.get(i + 1).copied();
+        let vowel_next = vowel_at(&chars, i + 1).is_some();
+        if has_vowel && !vowel_next {
+            out.push_str(final_sound(c));
+            has_vowel = false;
+            i += 1;
+            continue;
+        }
+
+        // Start of a syllable
+        has_vowel = false;
+        i += 1;
+        if c == 'ห' && next.is_some_and(is_sonorant) {
+            // Silent ห: only the tone changes
+            continue;
+        }
+        out.push_str(initial(c));
+        if vowel_next {
+            continue;
+        }
+        let Some(next) = next.filter(|&n| is_consonant(n)) else {
+            // A consonant on its own ("ณ")
+            out.push('a');
+            continue;
+        };
+        let after_next_vowel = vowel_at(&chars, i + 1).is_some();
+        if is_cluster(c, next) && after_next_vowel {
+            continue;
+        }
+        if next == 'อ' && !after_next_vowel {
+            // อ as the vowel "o" (ขอบ, พอ)
+            out.push('o');
+            has_vowel = true;
+            i += 1;
+            continue;
+        }
+        // Inherent vowel: "a" before a consonant that starts the next syllable, else "o" (คน)
+        if after_next_vowel {
+            out.push('a');
+        } else {
+            out.push('o');
+            has_vowel = true;
+        }
+    }
+    out
+}
+
+/// Latin transcription of a name for searching, lowercase: Thai by a simplified RTGS,
+/// other scripts (Cyrillic, Greek, CJK, Korean, ...) letter by letter.
+/// ASCII text is returned lowercased.
+pub fn to_latin(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_ascii_lowercase();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut thai = String::new();
+    for c in text.chars() {
+        if ('\u{0E00}'..='\u{0E7F}').contains(&c) {
+            thai.push(c);
+            continue;
+        }
+        if !thai.is_empty() {
+            out.push_str(&thai_to_latin(&thai));
+            thai.clear();
+        }
+        if c.is_ascii() {
+            out.push(c);
+        } else {
+            out.push_str(any_ascii::any_ascii_char(c));
+        }
+    }
+    if !thai.is_empty() {
+        out.push_str(&thai_to_latin(&thai));
+    }
+    out.to_lowercase()
+}
@@ -0,0 +1,66 @@
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+/// Active user profile (None: the default profile)
+static ACTIVE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Longest user profile name
+const MAX_NAME_CHARS: usize = 40;
+
+fn base_dir() -> PathBuf {
+    dirs::config_dir().unwrap_or_else(std::env::temp_dir).join("file-lister")
+}
+
+/// Folder of the active user's files: settings (bookmarks, rules), reviewed marks, session,
+/// snapshots and retention log. The default profile uses the config folder itself.
+pub fn config_dir() -> PathBuf {
+    match active() {
+        Some(name) => base_dir().join("profiles").join(name),
+        None => base_dir(),
+    }
+}
+
+/// Name of the active user profile (None: default)
+pub fn active() -> Option<String> {
+    ACTIVE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Switch to a user profile (None: default); a new name starts with default settings
+pub fn switch(name: Option<&str>) -> Result<(), String> {
+    let name = name.map(validate).transpose()?;
+    *ACTIVE.write().unwrap_or_else(|e| e.into_inner()) = name;
+    Ok(())
+}
+
+/// Trimmed profile name, if it can be a folder name on every system
+pub fn validate(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Enter a profile name".to_string());
+    }
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(format!("Profile names have at most {} characters", MAX_NAME_CHARS));
+    }
+    if name.starts_with('.') || !name.chars().all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.')) {
+        return Err(format!("'{}': use letters, digits, spaces, '-', '_' and '.'", name));
+    }
+    Ok(name.to_string())
+}
+
+/// User profiles that have been saved, by name
+pub fn list() -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(base_dir().join("profiles"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    names
+}
+
+/// Display name of a profile
+pub fn label(name: Option<&str>) -> &str {
+    name.unwrap_or("Default")
+}
@@ -0,0 +1,154 @@
+use crate::file_scanner::FileInfo;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// Words marking a later version ("report_final_FINAL", "budget new")
+const LATER_WORDS: [&str; 4] = ["final", "latest", "new", "newest"];
+
+/// Words marking an earlier version or a copy that isn't the one to keep
+const EARLIER_WORDS: [&str; 7] = ["old", "draft", "backup", "bak", "orig", "original", "wip"];
+
+/// Words that only mark a copy ("photo - Copy", "Copy of photo")
+const COPY_WORDS: [&str; 2] = ["copy", "kopie"];
+
+/// Membership of a file in a group of versions of the same document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionInfo {
+    pub group: usize,
+    /// Files in the group
+    pub size: usize,
+    /// Suggested version to keep (highest version, else newest)
+    pub latest: bool,
+}
+
+/// Version order of one name: later/earlier words, then the version number
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+struct Rank {
+    status: i32,
+    version: u32,
+}
+
+/// "v2", "ver2", "version2", "rev2" -> 2
+fn version_number(token: &str) -> Option<u32> {
+    let digits = ["version", "ver", "rev", "v"]
+        .iter()
+        .find_map(|prefix| token.strip_prefix(prefix))?;
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// "20240131" date stamp
+fn is_date(token: &str) -> bool {
+    token.len() == 8 && token.chars().all(|c| c.is_ascii_digit()) && (token.starts_with("19") || token.starts_with("20"))
+}
+
+/// "(1)", "[2]" copy counters
+fn is_counter(token: &str) -> bool {
+    let inner = token
+        .strip_prefix('(')
+        .and_then(|t| t.strip_suffix(')'))
+        .or_else(|| token.strip_prefix('[').and_then(|t| t.strip_suffix(']')));
+    inner.is_some_and(|inner| !inner.is_empty() && inner.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Base name of a versioned file name without extension, with its rank:
+/// "Report_v2" -> "report", "report_final_FINAL" -> "report", "file (1)" -> "file".
+/// Plain trailing numbers (IMG_0001) are part of the name, not a version.
+fn base_name(stem: &str) -> (String, Rank) {
+    let mut lower = stem.to_lowercase();
+    // Windows XP style "Copy of report", "Copy (2) of report"
+    if let Some(rest) = lower.strip_prefix("copy of ") {
+        lower = rest.to_string();
+    } else if let Some((_, rest)) = lower.split_once(") of ").filter(|(head, _)| head.starts_with("copy (")) {
+        lower = rest.to_string();
+    }
+    // "file(1)" without a space
+    if let Some(open) = lower.rfind('(').filter(|&open| open > 0 && is_counter(&lower[open..])) {
+        lower.insert(open, ' ');
+    }
+
+    let mut tokens: Vec<&str> = lower.split([' ', '_', '-', '.']).filter(|t| !t.is_empty()).collect();
+    let mut rank = Rank::default();
+    while tokens.len() > 1 {
+        let last = tokens[tokens.len() - 1];
+        let before = tokens.len().checked_sub(2).map(|i| tokens[i]).unwrap_or_default();
+        // Tokens to drop from the end (0 stops)
+        let strip = if let Some(version) = version_number(last) {
+            rank.version = rank.version.max(version);
+            1
+        } else if last.chars().all(|c| c.is_ascii_digit()) && ["version", "ver", "rev", "v"].contains(&before) {
+            // "version 2"
+            rank.version = rank.version.max(last.parse().unwrap_or(0));
+            2
+        } else if LATER_WORDS.contains(&last) {
+            rank.status += 1;
+            1
+        } else if EARLIER_WORDS.contains(&last) {
+            rank.status -= 1;
+            1
+        } else if COPY_WORDS.contains(&last) || is_counter(last) || is_date(last) {
+            1
+        } else if tokens.len() > 3 && is_date(&tokens[tokens.len() - 3..].concat()) {
+            // "2024-01-31" was split into three tokens
+            3
+        } else {
+            0
+        };
+        if strip == 0 || strip >= tokens.len() {
+            break;
+        }
+        tokens.truncate(tokens.len() - strip);
+    }
+    (tokens.join(" "), rank)
+}
+
+/// Group key: folder + base name + extension (case-insensitive)
+fn group_key(file: &FileInfo) -> (String, Rank) {
+    let folder = file
+        .absolute_path()
+        .rsplit_once(['/', '\\'])
+        .map(|(folder, _)| folder)
+        .unwrap_or("");
+    let (base, rank) = base_name(file.name());
+    (format!("{}/{}.{}", folder.to_lowercase(), base, file.extension().to_lowercase()), rank)
+}
+
+/// Group files that are versions or copies of the same document in the same folder
+/// (report_v1.docx, report_v2.docx, report_final.docx; photo.jpg, photo (1).jpg).
+/// Returns absolute path -> group; the latest version of each group is marked to keep.
+pub fn group(files: &[FileInfo]) -> HashMap<String, VersionInfo> {
+    let mut by_key: HashMap<String, Vec<(usize, Rank)>> = HashMap::new();
+    for (idx, file) in files.iter().enumerate().filter(|(_, f)| !f.is_dir) {
+        let (key, rank) = group_key(file);
+        by_key.entry(key).or_default().push((idx, rank));
+    }
+
+    let mut groups: Vec<(String, Vec<(usize, Rank)>)> =
+        by_key.into_iter().filter(|(_, members)| members.len() > 1).collect();
+    groups.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut result = HashMap::new();
+    for (group, (_, members)) in groups.into_iter().enumerate() {
+        // Highest version, then newest; on a tie the shortest name (the original, not the copy)
+        let latest = members
+            .iter()
+            .max_by_key(|(idx, rank)| {
+                let file = &files[*idx];
+                (*rank, file.modified_timestamp, Reverse(file.full_name().len()))
+            })
+            .map(|(idx, _)| *idx);
+        for (idx, _) in &members {
+            result.insert(
+                files[*idx].absolute_path().to_string(),
+                VersionInfo {
+                    group,
+                    size: members.len(),
+                    latest: latest == Some(*idx),
+                },
+            );
+        }
+    }
+    result
+}
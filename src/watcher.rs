@@ -0,0 +1,144 @@
+use crate::file_scanner::ScanFilter;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Shortest time between two polls of the watched folders
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Files added, removed or modified in one watched folder since the last poll.
+/// A rename shows up as the old path removed and the new one added.
+pub struct FolderChanges {
+    /// Folder as selected (names the `[folder]/` prefix)
+    pub folder: PathBuf,
+    /// Canonical folder path the reported paths start with
+    pub root: PathBuf,
+    pub added: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+    pub modified: Vec<PathBuf>,
+}
+
+impl FolderChanges {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Size and modification time of every file below a folder
+type Snapshot = HashMap<PathBuf, (u64, Option<SystemTime>)>;
+
+/// Polls folders on a background thread and reports file changes; stops when dropped.
+/// Polling needs no OS watch handles, so it also works on network shares.
+pub struct FolderWatcher {
+    pub receiver: Receiver<FolderChanges>,
+    stop: Arc<AtomicBool>,
+}
+
+impl FolderWatcher {
+    /// Changes to files the filter leaves out are not reported (like the scan)
+    pub fn start(folders: &[PathBuf], recursive: bool, filter: ScanFilter) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let folders = folders.to_vec();
+        let thread_stop = stop.clone();
+        thread::spawn(move || watch(folders, recursive, filter, &thread_stop, tx));
+        Self { receiver: rx, stop }
+    }
+}
+
+impl Drop for FolderWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn watch(folders: Vec<PathBuf>, recursive: bool, filter: ScanFilter, stop: &AtomicBool, tx: Sender<FolderChanges>) {
+    let roots: Vec<(PathBuf, PathBuf)> = folders
+        .into_iter()
+        .filter_map(|folder| {
+            let root = folder.canonicalize().ok()?;
+            Some((folder, root))
+        })
+        .collect();
+    // The first snapshot is the baseline (the scan that just finished)
+    let mut snapshots: Vec<Option<Snapshot>> = roots
+        .iter()
+        .map(|(_, root)| snapshot(root, recursive, filter))
+        .collect();
+    let mut poll_time = Duration::ZERO;
+    loop {
+        // Big trees are polled less often: wait at least four times as long as a poll takes
+        let until = Instant::now() + POLL_INTERVAL.max(poll_time * 4);
+        while Instant::now() < until {
+            if stop.load(Ordering::Relaxed) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(200));
+        }
+
+        let started = Instant::now();
+        for ((folder, root), previous) in roots.iter().zip(snapshots.iter_mut()) {
+            // Unreadable right now (share offline): keep the last snapshot instead of reporting everything removed
+            let Some(current) = snapshot(root, recursive, filter) else {
+                continue;
+            };
+            if let Some(previous) = previous {
+                let changes = diff(folder, root, previous, &current);
+                if !changes.is_empty() && tx.send(changes).is_err() {
+                    return;
+                }
+            }
+            *previous = Some(current);
+        }
+        poll_time = started.elapsed();
+    }
+}
+
+/// Files below root with size and modification time (None if a folder can't be read)
+fn snapshot(root: &Path, recursive: bool, filter: ScanFilter) -> Option<Snapshot> {
+    let mut files = Snapshot::new();
+    let mut pending = vec![(root.to_path_buf(), filter.ignore_rules(root))];
+    while let Some((dir, ignore)) = pending.pop() {
+        for entry in fs::read_dir(&dir).ok()? {
+            let path = entry.ok()?.path();
+            // Follows links, like the scanner
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            if filter.skips(ignore.as_ref(), &path, metadata.is_dir(), || Some(metadata.clone())) {
+                continue;
+            }
+            if metadata.is_file() {
+                files.insert(path, (metadata.len(), metadata.modified().ok()));
+            } else if metadata.is_dir() && recursive {
+                let rules = ignore.as_ref().map(|rules| rules.enter(&path));
+                pending.push((path, rules));
+            }
+        }
+    }
+    Some(files)
+}
+
+fn diff(folder: &Path, root: &Path, previous: &Snapshot, current: &Snapshot) -> FolderChanges {
+    let mut changes = FolderChanges {
+        folder: folder.to_path_buf(),
+        root: root.to_path_buf(),
+        added: Vec::new(),
+        removed: Vec::new(),
+        modified: Vec::new(),
+    };
+    for (path, state) in current {
+        match previous.get(path) {
+            None => changes.added.push(path.clone()),
+            Some(old) if old != state => changes.modified.push(path.clone()),
+            Some(_) => {}
+        }
+    }
+    changes.removed = previous.keys().filter(|path| !current.contains_key(*path)).cloned().collect();
+    changes
+}